@@ -0,0 +1,282 @@
+//! CLAP/VST3 instrument hosting a doux [`Engine`].
+//!
+//! Each MIDI note plays the persisted patch (an event path such as
+//! `/sound/saw/lpf/800`) at the note's pitch and velocity, held until note
+//! off. Host parameters map onto a fixed set of engine
+//! [`ParamId`](doux::voice::ParamId)s: moving one updates the held notes,
+//! and notes started afterwards pick it up.
+
+mod params;
+
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+
+use doux::event::Event;
+use doux::sampling::scan_samples_dir;
+use doux::types::midi2freq;
+use doux::Engine;
+use nih_plug::prelude::*;
+
+pub use params::{DouxParams, DEFAULT_PATCH, MAPPED};
+
+/// Voice group every plugin note is tagged with, so parameter moves and
+/// pitch bend reach all held notes at once.
+const GROUP: &str = "doux-plugin";
+const MAX_VOICES: usize = 64;
+/// Pitch bend range either way, in semitones.
+const BEND_RANGE: f32 = 2.0;
+
+pub struct DouxPlugin {
+    params: Arc<DouxParams>,
+    engine: Option<Engine>,
+    /// The patch, parsed once per activation.
+    patch: Event,
+    /// Stable voice ID of the note held on each channel and key.
+    notes: [[Option<u32>; 128]; 16],
+    /// Mapped parameter values the held notes last received.
+    sent: [f32; MAPPED],
+    /// Interleaved engine output for one host buffer.
+    scratch: Vec<f32>,
+}
+
+impl Default for DouxPlugin {
+    fn default() -> Self {
+        Self {
+            params: Arc::new(DouxParams::default()),
+            engine: None,
+            patch: Event::default(),
+            notes: [[None; 128]; 16],
+            sent: [0.0; MAPPED],
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl DouxPlugin {
+    fn note_on(&mut self, channel: u8, note: u8, velocity: f32) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+        let slot = &mut self.notes[channel as usize % 16][note as usize % 128];
+        // A retriggered key lets its previous note ring out
+        if let Some(i) = slot.take().and_then(|id| engine.voice_index_by_id(id)) {
+            engine.voices[i].force_release();
+        }
+        let mut event = self.patch.clone();
+        self.params.apply_to_note(&mut event);
+        event.note = Some(note as f32);
+        event.freq = Some(midi2freq(note as f32));
+        event.velocity = Some(velocity);
+        event.gate = Some(0.0);
+        event.id = Some(GROUP.to_string());
+        *slot = engine.dispatch_event(event).map(|i| engine.voices[i].id);
+    }
+
+    fn note_off(&mut self, channel: u8, note: u8) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+        let slot = &mut self.notes[channel as usize % 16][note as usize % 128];
+        if let Some(i) = slot.take().and_then(|id| engine.voice_index_by_id(id)) {
+            engine.voices[i].force_release();
+        }
+    }
+
+    fn handle(&mut self, event: NoteEvent<()>) {
+        match event {
+            NoteEvent::NoteOn {
+                channel,
+                note,
+                velocity,
+                ..
+            } => self.note_on(channel, note, velocity),
+            NoteEvent::NoteOff { channel, note, .. } | NoteEvent::Choke { channel, note, .. } => {
+                self.note_off(channel, note)
+            }
+            NoteEvent::PolyPressure {
+                channel,
+                note,
+                pressure,
+                ..
+            } => {
+                let vid = self.notes[channel as usize % 16][note as usize % 128];
+                if let (Some(engine), Some(vid)) = (self.engine.as_mut(), vid) {
+                    engine.dispatch_event(Event {
+                        vid: Some(vid),
+                        pressure: Some(pressure),
+                        ..Default::default()
+                    });
+                }
+            }
+            NoteEvent::MidiPitchBend { value, .. } => {
+                if let Some(engine) = self.engine.as_mut() {
+                    engine.dispatch_event(Event {
+                        cmd: Some("update".to_string()),
+                        id: Some(GROUP.to_string()),
+                        bend: Some((value * 2.0 - 1.0) * BEND_RANGE),
+                        ..Default::default()
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends parameters moved since the last buffer to the held notes.
+    fn send_param_changes(&mut self) {
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
+        let mut update = Event::default();
+        let mut changed = false;
+        for (i, (id, param)) in self.params.mapped().into_iter().enumerate() {
+            let value = param.value();
+            if value != self.sent[i] {
+                params::set_param(&mut update, id, value);
+                self.sent[i] = value;
+                changed = true;
+            }
+        }
+        if changed {
+            update.cmd = Some("update".to_string());
+            update.id = Some(GROUP.to_string());
+            engine.dispatch_event(update);
+        }
+    }
+
+    fn load_samples(engine: &mut Engine, dirs: &[String]) {
+        for dir in dirs {
+            let index = scan_samples_dir(Path::new(dir));
+            engine.sample_index.extend(index);
+        }
+        #[cfg(feature = "soundfont")]
+        for dir in dirs {
+            engine.load_soundfont_from_dir(Path::new(dir));
+        }
+    }
+}
+
+impl Plugin for DouxPlugin {
+    const NAME: &'static str = "Doux";
+    const VENDOR: &'static str = "Sova";
+    const URL: &'static str = "https://doux.livecoding.fr";
+    const EMAIL: &'static str = "";
+    const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
+        main_input_channels: None,
+        main_output_channels: NonZeroU32::new(2),
+        ..AudioIOLayout::const_default()
+    }];
+
+    const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+
+    type SysExMessage = ();
+    type BackgroundTask = ();
+
+    fn params(&self) -> Arc<dyn Params> {
+        self.params.clone()
+    }
+
+    fn initialize(
+        &mut self,
+        _audio_io_layout: &AudioIOLayout,
+        buffer_config: &BufferConfig,
+        _context: &mut impl InitContext<Self>,
+    ) -> bool {
+        let sr = buffer_config.sample_rate;
+        let block = buffer_config.max_buffer_size as usize;
+        let mut engine = Engine::new_with_channels(sr, 2, MAX_VOICES, block);
+        if let Ok(dirs) = self.params.sample_dirs.read() {
+            Self::load_samples(&mut engine, &dirs);
+        }
+        self.patch = match self.params.patch.read() {
+            Ok(patch) => Event::parse(&patch, sr),
+            Err(_) => Event::parse(DEFAULT_PATCH, sr),
+        };
+        for (sent, (_, param)) in self.sent.iter_mut().zip(self.params.mapped()) {
+            *sent = param.value();
+        }
+        self.scratch = vec![0.0; block * 2];
+        self.notes = [[None; 128]; 16];
+        self.engine = Some(engine);
+        true
+    }
+
+    fn reset(&mut self) {
+        if let Some(engine) = self.engine.as_mut() {
+            engine.panic_now();
+        }
+        self.notes = [[None; 128]; 16];
+    }
+
+    fn process(
+        &mut self,
+        buffer: &mut Buffer,
+        _aux: &mut AuxiliaryBuffers,
+        context: &mut impl ProcessContext<Self>,
+    ) -> ProcessStatus {
+        let frames = buffer.samples();
+        if self.engine.is_none() || self.scratch.len() < frames * 2 {
+            return ProcessStatus::Error("engine not initialized");
+        }
+        self.send_param_changes();
+
+        // Render up to each note event so notes land on their sample
+        let mut next = context.next_event();
+        let mut pos = 0;
+        while pos < frames {
+            while next.as_ref().is_some_and(|e| e.timing() as usize <= pos) {
+                if let Some(event) = next.take() {
+                    self.handle(event);
+                }
+                next = context.next_event();
+            }
+            let end = next
+                .as_ref()
+                .map_or(frames, |e| (e.timing() as usize).min(frames));
+            if let Some(engine) = self.engine.as_mut() {
+                engine.process_block(&mut self.scratch[pos * 2..end * 2], &[], &[]);
+            }
+            pos = end;
+        }
+        while let Some(event) = next.take() {
+            self.handle(event);
+            next = context.next_event();
+        }
+
+        for (i, mut frame) in buffer.iter_samples().enumerate() {
+            for (c, sample) in frame.iter_mut().enumerate() {
+                *sample = self.scratch[i * 2 + c.min(1)];
+            }
+        }
+        ProcessStatus::KeepAlive
+    }
+}
+
+impl ClapPlugin for DouxPlugin {
+    const CLAP_ID: &'static str = "fr.livecoding.doux";
+    const CLAP_DESCRIPTION: Option<&'static str> =
+        Some("Doux synthesizer and sampler engine as an instrument");
+    const CLAP_MANUAL_URL: Option<&'static str> = Some(Self::URL);
+    const CLAP_SUPPORT_URL: Option<&'static str> = None;
+    const CLAP_FEATURES: &'static [ClapFeature] = &[
+        ClapFeature::Instrument,
+        ClapFeature::Synthesizer,
+        ClapFeature::Sampler,
+        ClapFeature::Stereo,
+    ];
+}
+
+impl Vst3Plugin for DouxPlugin {
+    const VST3_CLASS_ID: [u8; 16] = *b"DouxSovaInstrmnt";
+    const VST3_SUBCATEGORIES: &'static [Vst3SubCategory] = &[
+        Vst3SubCategory::Instrument,
+        Vst3SubCategory::Synth,
+        Vst3SubCategory::Sampler,
+    ];
+}
+
+nih_export_clap!(DouxPlugin);
+nih_export_vst3!(DouxPlugin);
@@ -0,0 +1,200 @@
+use std::sync::RwLock;
+
+use doux::event::Event;
+use doux::voice::ParamId;
+use nih_plug::prelude::*;
+
+/// Number of host parameters that drive an engine [`ParamId`].
+pub const MAPPED: usize = 13;
+
+/// Patch played when the plugin state has none.
+pub const DEFAULT_PATCH: &str = "/sound/saw";
+
+#[derive(Params)]
+pub struct DouxParams {
+    /// Event path every note starts from, like `/sound/pluck/pluckdecay/4`.
+    /// The note, velocity and host parameters are added on top.
+    #[persist = "patch"]
+    pub patch: RwLock<String>,
+    /// Sample folders scanned when the plugin is activated.
+    #[persist = "sample-dirs"]
+    pub sample_dirs: RwLock<Vec<String>>,
+
+    #[id = "gain"]
+    pub gain: FloatParam,
+    #[id = "pan"]
+    pub pan: FloatParam,
+    #[id = "detune"]
+    pub detune: FloatParam,
+    #[id = "wave"]
+    pub wave: FloatParam,
+    #[id = "pw"]
+    pub pw: FloatParam,
+    #[id = "harmonics"]
+    pub harmonics: FloatParam,
+    #[id = "timbre"]
+    pub timbre: FloatParam,
+    #[id = "morph"]
+    pub morph: FloatParam,
+    #[id = "lpf"]
+    pub lpf: FloatParam,
+    #[id = "lpq"]
+    pub lpq: FloatParam,
+    #[id = "hpf"]
+    pub hpf: FloatParam,
+    #[id = "fm"]
+    pub fm: FloatParam,
+    #[id = "fmh"]
+    pub fmh: FloatParam,
+
+    #[id = "attack"]
+    pub attack: FloatParam,
+    #[id = "decay"]
+    pub decay: FloatParam,
+    #[id = "sustain"]
+    pub sustain: FloatParam,
+    #[id = "release"]
+    pub release: FloatParam,
+}
+
+fn unit(name: &str, default: f32) -> FloatParam {
+    FloatParam::new(name, default, FloatRange::Linear { min: 0.0, max: 1.0 })
+        .with_value_to_string(formatters::v2s_f32_rounded(2))
+}
+
+fn hz(name: &str, default: f32) -> FloatParam {
+    FloatParam::new(
+        name,
+        default,
+        FloatRange::Skewed {
+            min: 20.0,
+            max: 20000.0,
+            factor: FloatRange::skew_factor(-2.0),
+        },
+    )
+    .with_value_to_string(formatters::v2s_f32_hz_then_khz(0))
+    .with_string_to_value(formatters::s2v_f32_hz_then_khz())
+}
+
+fn seconds(name: &str, default: f32, max: f32) -> FloatParam {
+    FloatParam::new(
+        name,
+        default,
+        FloatRange::Skewed {
+            min: 0.0,
+            max,
+            factor: FloatRange::skew_factor(-2.0),
+        },
+    )
+    .with_unit(" s")
+    .with_value_to_string(formatters::v2s_f32_rounded(3))
+}
+
+impl Default for DouxParams {
+    fn default() -> Self {
+        Self {
+            patch: RwLock::new(DEFAULT_PATCH.to_string()),
+            sample_dirs: RwLock::new(Vec::new()),
+            gain: FloatParam::new("Gain", 1.0, FloatRange::Linear { min: 0.0, max: 2.0 })
+                .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            pan: unit("Pan", 0.5),
+            detune: FloatParam::new(
+                "Detune",
+                0.0,
+                FloatRange::Linear {
+                    min: -100.0,
+                    max: 100.0,
+                },
+            )
+            .with_unit(" ct")
+            .with_value_to_string(formatters::v2s_f32_rounded(1)),
+            wave: unit("Wave", 0.0),
+            pw: unit("Pulse Width", 0.5),
+            harmonics: unit("Harmonics", 0.5),
+            timbre: unit("Timbre", 0.5),
+            morph: unit("Morph", 0.5),
+            lpf: hz("Low-pass", 20000.0),
+            lpq: unit("Low-pass Q", 0.2),
+            hpf: hz("High-pass", 20.0),
+            fm: FloatParam::new(
+                "FM",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 10.0,
+                },
+            )
+            .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            fmh: FloatParam::new("FM Ratio", 1.0, FloatRange::Linear { min: 0.0, max: 8.0 })
+                .with_value_to_string(formatters::v2s_f32_rounded(2)),
+            attack: seconds("Attack", 0.003, 10.0),
+            decay: seconds("Decay", 0.0, 10.0),
+            sustain: unit("Sustain", 1.0),
+            release: seconds("Release", 0.005, 10.0),
+        }
+    }
+}
+
+impl DouxParams {
+    /// Host parameters with the engine parameter each one drives.
+    pub fn mapped(&self) -> [(ParamId, &FloatParam); MAPPED] {
+        [
+            (ParamId::Gain, &self.gain),
+            (ParamId::Pan, &self.pan),
+            (ParamId::Detune, &self.detune),
+            (ParamId::Wave, &self.wave),
+            (ParamId::Pw, &self.pw),
+            (ParamId::Harmonics, &self.harmonics),
+            (ParamId::Timbre, &self.timbre),
+            (ParamId::Morph, &self.morph),
+            (ParamId::Lpf, &self.lpf),
+            (ParamId::Lpq, &self.lpq),
+            (ParamId::Hpf, &self.hpf),
+            (ParamId::Fm, &self.fm),
+            (ParamId::Fmh, &self.fmh),
+        ]
+    }
+
+    /// Adds the host parameters to a note event. Parameters left at their
+    /// default keep whatever the patch sets.
+    pub fn apply_to_note(&self, event: &mut Event) {
+        for (id, param) in self.mapped() {
+            let value = param.value();
+            if value != param.default_plain_value() {
+                set_param(event, id, value);
+            }
+        }
+        for (field, param) in [
+            (&mut event.attack, &self.attack),
+            (&mut event.decay, &self.decay),
+            (&mut event.sustain, &self.sustain),
+            (&mut event.release, &self.release),
+        ] {
+            let value = param.value();
+            if value != param.default_plain_value() {
+                *field = Some(value);
+            }
+        }
+    }
+}
+
+/// Sets the event field that `id` is read from.
+pub fn set_param(event: &mut Event, id: ParamId, value: f32) {
+    let field = match id {
+        ParamId::Gain => &mut event.gain,
+        ParamId::Pan => &mut event.pan,
+        ParamId::Detune => &mut event.detune,
+        ParamId::Wave => &mut event.wave,
+        ParamId::Pw => &mut event.pw,
+        ParamId::Harmonics => &mut event.harmonics,
+        ParamId::Timbre => &mut event.timbre,
+        ParamId::Morph => &mut event.morph,
+        ParamId::Lpf => &mut event.lpf,
+        ParamId::Lpq => &mut event.lpq,
+        ParamId::Hpf => &mut event.hpf,
+        ParamId::Fm => &mut event.fm,
+        ParamId::Fmh => &mut event.fmh,
+        _ => return,
+    };
+    *field = Some(value);
+}
@@ -14,8 +14,9 @@ use doux::audio::{
     find_device, get_host, host_controls_buffer_size, max_output_channels, HostSelection,
 };
 use doux::config::DouxConfig;
+use doux::crash::{self, RecentEvents};
 use doux::error::DouxError;
-use doux::telemetry::EngineMetrics;
+use doux::telemetry::{EngineMetrics, SpectrumBuffer, VoiceEnvelope};
 use doux::Engine;
 use sova_core::clock::SyncTime;
 use sova_core::protocol::audio_engine_proxy::AudioEngineProxy;
@@ -57,6 +58,9 @@ pub struct AudioEngineState {
     pub sample_pool_mb: f32,
     #[serde(default = "default_volume")]
     pub volume: f32,
+    /// Envelope stage and value of each active voice.
+    #[serde(default)]
+    pub voices: Vec<VoiceEnvelope>,
 }
 
 fn default_volume() -> f32 {
@@ -80,6 +84,7 @@ impl Default for AudioEngineState {
             schedule_depth: 0,
             sample_pool_mb: 0.0,
             volume: 1.0,
+            voices: Vec::new(),
         }
     }
 }
@@ -228,7 +233,8 @@ fn spawn_preload(
         .spawn(move || {
             let mut batch = Vec::with_capacity(entries.len());
             for (name, path) in &entries {
-                match doux::sampling::decode_sample_head(path.as_ref(), target_sr) {
+                let options = registry.decode_options();
+                match doux::sampling::decode_sample_head_with(path.as_ref(), target_sr, options) {
                     Ok(data) => batch.push((name.to_string(), Arc::new(data))),
                     Err(e) => eprintln!("[doux] preload {name}: {e}"),
                 }
@@ -260,6 +266,8 @@ impl DouxManager {
             Arc::clone(&metrics),
             block_size,
         );
+        engine.set_orbit_count(config.orbits);
+        engine.out_mode = config.out_mode;
 
         for path in &config.sample_paths {
             let index = doux::sampling::scan_samples_dir(path);
@@ -489,18 +497,20 @@ impl DouxManager {
                 // never needs to call the allocator. 8192 covers all common configs.
                 let mut conv_buf: Vec<f32> = vec![0.0f32; max_buffer_samples * output_channels];
                 let mut panicked = false;
+                let restart = Arc::clone(&flag);
                 output_device.build_output_stream(
                     &stream_config,
                     move |data: &mut [$T], _| {
                         // A panic inside a cpal callback (called from C/ALSA) is UB.
-                        // Wrap everything in catch_unwind; on panic output silence.
+                        // Catch it, dump the engine state and output silence
+                        // until the stream is rebuilt.
                         if panicked {
                             for s in data.iter_mut() {
                                 *s = <$T as FromSample<f32>>::from_sample_(0.0);
                             }
                             return;
                         }
-                        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let result = crash::catch_in_callback(|| {
                             // Clamp to pre-allocated size: never allocate on the RT thread.
                             let usable = (data.len()).min(conv_buf.len());
                             let conv = &mut conv_buf[..usable];
@@ -582,10 +592,24 @@ impl DouxManager {
                             for (out, &src) in data.iter_mut().zip(conv.iter()) {
                                 *out = <$T as FromSample<f32>>::from_sample_(src);
                             }
-                        })); // end catch_unwind
-                        if result.is_err() {
+                        }); // end catch_in_callback
+                        if let Err(payload) = result {
                             panicked = true;
-                            eprintln!("[doux] PANIC in audio callback — outputting silence");
+                            // Events arrive structured here, so none are listed
+                            let report = crash::take_callback_panic(payload.as_ref())
+                                + "\n"
+                                + &crash::engine_report(&engine, &RecentEvents::default());
+                            match crash::write_dump(&report) {
+                                Ok(path) => eprintln!(
+                                    "[doux] PANIC in audio callback, dump written to {}",
+                                    path.display()
+                                ),
+                                Err(e) => {
+                                    eprintln!("[doux] PANIC in audio callback (no dump: {e})")
+                                }
+                            }
+                            eprintln!("[doux] restarting audio");
+                            restart.store(true, Ordering::Release);
                             for s in data.iter_mut() {
                                 *s = <$T as FromSample<f32>>::from_sample_(0.0);
                             }
@@ -667,7 +691,9 @@ impl DouxManager {
         self.actual_channels = actual_channels;
 
         // Create fresh engine for the new audio callback
+        let spectrum_on = self.metrics.spectrum.is_enabled();
         self.metrics = Arc::new(EngineMetrics::default());
+        self.metrics.spectrum.set_enabled(spectrum_on);
         let block_size = self
             .config
             .buffer_size
@@ -680,6 +706,8 @@ impl DouxManager {
             Arc::clone(&self.metrics),
             block_size,
         );
+        engine.set_orbit_count(self.config.orbits);
+        engine.out_mode = self.config.out_mode;
         for path in &self.config.sample_paths {
             let index = doux::sampling::scan_samples_dir(path);
             engine.sample_index.extend(index);
@@ -744,6 +772,8 @@ impl DouxManager {
             Arc::clone(&metrics),
             block_size,
         );
+        engine.set_orbit_count(config.orbits);
+        engine.out_mode = config.out_mode;
 
         for path in &config.sample_paths {
             let index = doux::sampling::scan_samples_dir(path);
@@ -802,6 +832,7 @@ impl DouxManager {
             schedule_depth: self.metrics.schedule_depth.load(Ordering::Relaxed) as usize,
             sample_pool_mb: self.metrics.sample_pool_mb(),
             volume: self.master_volume(),
+            voices: self.metrics.voice_envelopes(),
         }
     }
 
@@ -854,6 +885,12 @@ impl DouxManager {
     pub fn peak_capture(&self) -> Option<Arc<PeakCapture>> {
         self.peaks.clone()
     }
+
+    /// Output spectrum for spectrogram displays; enable it with
+    /// [`SpectrumBuffer::set_enabled`]. Stays on across restarts.
+    pub fn spectrum(&self) -> &SpectrumBuffer {
+        &self.metrics.spectrum
+    }
 }
 
 impl Drop for DouxManager {
@@ -0,0 +1,215 @@
+//! Typed event builder for Rust embedders.
+//!
+//! [`EventBuilder`] fills an [`Event`] field by field, with each parameter
+//! taking its own type, instead of going through the `/key/value` grammar:
+//!
+//! ```
+//! use doux::{Engine, EventBuilder};
+//! use doux::types::Source;
+//!
+//! # #[cfg(feature = "native")]
+//! let mut engine = Engine::new_with_channels(48000.0, 2, 32, 256);
+//! # #[cfg(not(feature = "native"))]
+//! # let mut engine = Engine::new_with_channels(48000.0, 2, 32);
+//! EventBuilder::new()
+//!     .sound(Source::Saw)
+//!     .freq(220.0)
+//!     .lpf(800.0)
+//!     .send(&mut engine);
+//! ```
+//!
+//! Parameters are named after their event keys; `note`, `time` and the
+//! sound setters take the place of keys that need converting.
+
+use crate::event::Event;
+use crate::types::{
+    midi2freq, DelayType, LfoShape, PanLaw, PluckExcite, ReverbType, Source, SubWave, SyncMode,
+};
+use crate::voice::{ModChain, ParamId};
+use crate::Engine;
+
+/// Builds an [`Event`] with typed setters. See the [module docs](self).
+#[derive(Clone, Default, Debug)]
+pub struct EventBuilder {
+    event: Event,
+    /// Start time in seconds, turned into a tick once the sample rate is known.
+    time: Option<f64>,
+}
+
+macro_rules! setters {
+    ($ty:ty: $($field:ident),+ $(,)?) => {
+        $(
+            #[doc = concat!("Sets `", stringify!($field), "`.")]
+            pub fn $field(mut self, value: $ty) -> Self {
+                self.event.$field = Some(value);
+                self
+            }
+        )+
+    };
+}
+
+// `sub` is the sub-oscillator key, not subtraction
+#[allow(clippy::should_implement_trait)]
+impl EventBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plays a built-in source.
+    pub fn sound(mut self, source: Source) -> Self {
+        self.event.sound = Some(source.info().module.name.to_string());
+        self
+    }
+
+    /// Plays sample `n` of the folder `name`.
+    pub fn sample(mut self, name: &str, n: usize) -> Self {
+        self.event.sound = Some(name.to_string());
+        self.event.n = Some(n.to_string());
+        self
+    }
+
+    /// Plays a source registered with
+    /// [`register_source`](Engine::register_source).
+    pub fn custom(mut self, name: &str) -> Self {
+        self.event.sound = Some(name.to_string());
+        self
+    }
+
+    /// Sample bank suffix (`sound_bank`).
+    pub fn bank(mut self, bank: &str) -> Self {
+        self.event.bank = Some(bank.to_string());
+        self
+    }
+
+    /// Tags the voice with a named group.
+    pub fn id(mut self, id: &str) -> Self {
+        self.event.id = Some(id.to_string());
+        self
+    }
+
+    /// Engine command instead of a note (`rec`, `keep`, `reset`, ...).
+    pub fn cmd(mut self, cmd: &str) -> Self {
+        self.event.cmd = Some(cmd.to_string());
+        self
+    }
+
+    /// Pitch as a MIDI note number, or a degree of the event's `tuning`.
+    pub fn note(mut self, note: f32) -> Self {
+        self.event.note = Some(note);
+        self.freq(midi2freq(note))
+    }
+
+    /// Registered tuning that `note` maps through.
+    pub fn tuning(mut self, name: &str) -> Self {
+        self.event.tuning = Some(name.to_string());
+        self
+    }
+
+    /// Start time in seconds on the engine clock.
+    pub fn time(mut self, seconds: f64) -> Self {
+        self.time = Some(seconds);
+        self
+    }
+
+    /// Live input channel (`inputch`).
+    pub fn inputch(mut self, channel: usize) -> Self {
+        self.event.inchan = Some(channel);
+        self
+    }
+
+    /// Modulates `param` with `chain`, like `lpf/200~2000:0.3t`.
+    pub fn modulate(mut self, param: ParamId, chain: ModChain) -> Self {
+        self.event.mods.push((param, chain));
+        self
+    }
+
+    setters!(f32:
+        freq, detune, speed, stretch, fit, pw, spread, warp, mirror, harmonics, timbre, morph,
+        partials, begin, end, slice, pick, loopbegin, loopend, loopxfade, wave, sub, sync_ratio,
+        sync_phase, scan, pluckdamp, pluckdecay, gain, postgain, velocity, pan, pan2d, pan2dspread,
+        gate, rep, envdelay, attack, hold, decay, sustain, release, attackcurve, decaycurve,
+        releasecurve, lpf, lpq, hpf, hpq, bpf, bpq, llpf, llpq, lhpf, lhpq, lbpf, lbpq, slpf, slpq,
+        shpf, shpq, sbpf, sbpq, vib, vibmod, fm, fmh, fm2, fm2h, fmfb, fmpivot, am, amdepth, rm,
+        rmdepth, bend, pressure, pressure_depth, expr_slew, phaser, phaserdepth, phasersweep,
+        phasercenter, phasermix, flanger, flangerdepth, flangerfeedback, flangermix, smear,
+        smearfreq, smearfb, smearmix, fshift, fshiftmix, chorus, chorusdepth, chorusdelay,
+        chorusmix, reso, resostruct, resobright, resodamp, coarse, crush, fold, wrap, distort,
+        distortvol, eqlo, eqmid, eqhi, eqlofreq, eqmidfreq, eqhifreq, tilt, width, haas, decor,
+        comp, compthresh, compratio, compmakeup, compattack, comprelease, transattack, transsustain,
+        inputgain, delay, delaytime, delayfeedback, delayreturn, delaysendlpf, delaysendhpf,
+        delayfreeze, verb, verbdecay, verbdamp, verbpredelay, verbdiff, verbsize, verbprelow,
+        verbprehigh, verblowcut, verbhighcut, verblowgain, verbchorus, verbchorusfreq, verbfreeze,
+        verbreturn, verbsendlpf, verbsendhpf, orbithpf, orbitlpf, orbiteqlo, orbiteqmid, orbiteqhi,
+        comb, combfreq, combfeedback, combdamp, combreturn, feedback, fbtime, fbdamp, fblfo,
+        fblfodepth, fbcross, fbreturn, ratchetdecay, ratchetstep, repjitter, div, mult, swing, flam,
+        cutfade,
+    );
+    setters!(bool: reset, modsync, revert, cue, late_ok, sampleloop, insum);
+    setters!(usize: voice, orbit, cut, ratchet, swinggrid, comporbit);
+    setters!(u64: tick, endtick, ack);
+    setters!(u32: vid, wtlen, seed);
+    setters!(i64: delta);
+    setters!(u8: sub_oct);
+    setters!(LfoShape: vibshape, fmshape, amshape, rmshape, fblfoshape);
+    setters!(PanLaw: panlaw);
+    setters!(SubWave: sub_wave);
+    setters!(SyncMode: sync_mode);
+    setters!(PluckExcite: pluck_excite);
+    setters!(DelayType: delaytype);
+    setters!(ReverbType: verbtype);
+
+    /// Finishes the event for an engine running at `sr`.
+    pub fn build(mut self, sr: f32) -> Event {
+        if let Some(t) = self.time {
+            self.event.tick = Some((t * sr as f64).floor() as u64);
+        }
+        self.event.resolve_name();
+        self.event
+    }
+
+    /// Dispatches the event to `engine`; returns the voice it played on.
+    pub fn send(self, engine: &mut Engine) -> Option<usize> {
+        let event = self.build(engine.sr);
+        engine.dispatch_event(event)
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_same_voice_as_the_key_value_grammar() {
+        let mut parsed = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let mut built = Engine::new_with_channels(48000.0, 2, 8, 64);
+        parsed.evaluate("/sound/saw/note/57/lpf/800/lpq/0.3/panlaw/linear/gate/0.5/orbit/2");
+        EventBuilder::new()
+            .sound(Source::Saw)
+            .note(57.0)
+            .lpf(800.0)
+            .lpq(0.3)
+            .panlaw(PanLaw::Linear)
+            .gate(0.5)
+            .orbit(2)
+            .send(&mut built);
+        let (a, b) = (&parsed.voices[0].params, &built.voices[0].params);
+        assert_eq!(parsed.active_voices, built.active_voices);
+        assert_eq!(
+            (a.sound, a.freq, a.lpf, a.lpq),
+            (b.sound, b.freq, b.lpf, b.lpq)
+        );
+        assert_eq!((a.gate, a.orbit), (b.gate, b.orbit));
+        assert!(a.panlaw == b.panlaw);
+    }
+
+    #[test]
+    fn time_becomes_a_tick_at_the_engine_rate() {
+        let event = EventBuilder::new()
+            .sound(Source::Sine)
+            .bank("b")
+            .time(0.5)
+            .build(48000.0);
+        assert_eq!(event.tick, Some(24000));
+        assert_eq!(event.effective_name.as_deref(), Some("sine_b"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Round-trip latency measurement for the live input.
+//!
+//! `/doux/calibrate` plays a few clicks straight to the outputs and listens
+//! for them on the live input, so it needs a loopback: a cable from an
+//! output to an input, or a microphone near a speaker. The median delay
+//! between sending a click and hearing it is the round-trip latency, kept
+//! on the engine for lining recorded input up with the output.
+//!
+//! | Command                               | Measures                            |
+//! |---------------------------------------|-------------------------------------|
+//! | `/doux/calibrate`                     | Clicks on every output, any input   |
+//! | `/doux/calibrate/out/<i>/inchan/<j>`  | Clicks on output `i`, input `j`     |
+//!
+//! The input is listened to quietly first, so the detection threshold
+//! sits above its noise floor.
+
+/// Clicks per run; the result is the median of the ones heard.
+const PULSES: usize = 5;
+/// Fewest clicks that must be heard for a result.
+const MIN_HITS: usize = 3;
+/// Quiet listening before the first click, for the noise floor.
+const PREROLL_SECS: f32 = 0.25;
+/// Time between clicks, which is also the longest latency measured.
+const PERIOD_SECS: f32 = 0.5;
+const CLICK_FRAMES: usize = 4;
+const CLICK_LEVEL: f32 = 0.5;
+/// Detection threshold: this far above the noise floor, and at least
+/// `MIN_THRESHOLD`.
+const FLOOR_MARGIN: f32 = 4.0;
+const MIN_THRESHOLD: f32 = 0.01;
+
+pub struct LatencyProbe {
+    running: bool,
+    pos: usize,
+    out: Option<usize>,
+    inchan: Option<usize>,
+    floor: f32,
+    hits: [Option<u32>; PULSES],
+    /// Result of a finished run, until taken.
+    result: Option<Option<u32>>,
+    sr: f32,
+}
+
+impl LatencyProbe {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            running: false,
+            pos: 0,
+            out: None,
+            inchan: None,
+            floor: 0.0,
+            hits: [None; PULSES],
+            result: None,
+            sr,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Starts a run clicking on `out` (or every output) and listening on
+    /// input channel `inchan` (or the loudest one).
+    pub fn start(&mut self, out: Option<usize>, inchan: Option<usize>) {
+        *self = Self {
+            running: true,
+            out,
+            inchan,
+            ..Self::new(self.sr)
+        };
+    }
+
+    /// Output channel to click on, or `None` for all of them.
+    pub fn target(&self) -> Option<usize> {
+        self.out
+    }
+
+    /// Advances one frame. `input` is that frame of the live input; returns
+    /// the click sample for the outputs.
+    pub fn next(&mut self, input: &[f32]) -> f32 {
+        if !self.running {
+            return 0.0;
+        }
+        let level = match self.inchan {
+            Some(ch) => input.get(ch).map_or(0.0, |s| s.abs()),
+            None => input.iter().fold(0.0f32, |m, s| m.max(s.abs())),
+        };
+        let preroll = (PREROLL_SECS * self.sr) as usize;
+        let period = ((PERIOD_SECS * self.sr) as usize).max(CLICK_FRAMES + 1);
+        let pos = self.pos;
+        self.pos += 1;
+        if pos < preroll {
+            self.floor = self.floor.max(level);
+            return 0.0;
+        }
+
+        let (pulse, offset) = ((pos - preroll) / period, (pos - preroll) % period);
+        if pulse >= PULSES {
+            self.finish();
+            return 0.0;
+        }
+        let threshold = (self.floor * FLOOR_MARGIN).max(MIN_THRESHOLD);
+        if self.hits[pulse].is_none() && level > threshold {
+            self.hits[pulse] = Some(offset as u32);
+        }
+        if offset < CLICK_FRAMES {
+            CLICK_LEVEL
+        } else {
+            0.0
+        }
+    }
+
+    fn finish(&mut self) {
+        // Missed clicks sort first, leaving the heard ones at the end
+        self.hits.sort_unstable();
+        let missed = self.hits.iter().filter(|h| h.is_none()).count();
+        let heard = PULSES - missed;
+        self.running = false;
+        self.result = Some(if heard >= MIN_HITS {
+            self.hits[missed + heard / 2]
+        } else {
+            None
+        });
+    }
+
+    /// Takes the outcome of a finished run: the round-trip latency in
+    /// frames, or `None` when too few clicks came back.
+    pub fn take_result(&mut self) -> Option<Option<u32>> {
+        self.result.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    /// Runs the probe with its clicks looped back `delay` frames later,
+    /// over a constant input `noise`.
+    fn loopback(delay: usize, noise: f32) -> Option<u32> {
+        let mut probe = LatencyProbe::new(8000.0);
+        probe.start(None, Some(1));
+        let mut line = std::collections::VecDeque::from(vec![0.0; delay]);
+        for _ in 0..8000 * 4 {
+            line.push_back(probe.next(&[0.9, line[0] + noise]));
+            line.pop_front();
+            if let Some(result) = probe.take_result() {
+                return result;
+            }
+        }
+        panic!("probe never finished");
+    }
+
+    #[test]
+    fn measures_the_loopback_delay() {
+        assert_eq!(loopback(300, 0.0), Some(300));
+        assert_eq!(loopback(1, 0.05), Some(1));
+        // Input 0 is loud but not listened to, and 1 never hears a click
+        assert_eq!(loopback(30000, 0.0), None);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn calibration_measures_a_block_of_loopback() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/calibrate/out/1/inchan/0");
+        // Output 1 comes back on input 0 one block later
+        let (mut out, mut input) = (vec![0.0f32; 128], vec![0.0f32; 128]);
+        for _ in 0..48000 * 3 / 64 {
+            engine.process_block(&mut out, &[], &input);
+            assert!(out.chunks(2).all(|f| f[0] == 0.0));
+            for (i, o) in input.chunks_mut(2).zip(out.chunks(2)) {
+                i[0] = o[1];
+            }
+        }
+        assert_eq!(engine.input_latency, Some(64));
+        assert_eq!(engine.metrics.input_latency(), Some(64));
+        assert!(engine.is_idle());
+    }
+}
@@ -0,0 +1,146 @@
+//! Sample chain export for doux.
+//!
+//! Decodes samples from a samples directory and writes them to one WAV in
+//! evenly spaced slots, with a cue sheet, for hardware samplers that slice
+//! a file into equal parts.
+
+use clap::Parser;
+use doux::sampling::{
+    decode_sample_file_with, expand_chain_names, scan_samples_dir, ChainOptions, DecodeOptions,
+    SampleChain,
+};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "doux-chain")]
+#[command(about = "Export samples as an evenly spaced sample chain WAV", long_about = None)]
+struct Args {
+    /// Samples to chain, in slot order: `kick/2` for one sample, `kick` for
+    /// the whole folder.
+    #[arg(required = true)]
+    names: Vec<String>,
+
+    /// Directory containing audio samples.
+    #[arg(short, long)]
+    samples: PathBuf,
+
+    /// Output WAV file path; the cue sheet goes next to it as `.cue`.
+    #[arg(short, long, visible_alias = "out")]
+    output: PathBuf,
+
+    /// Sample rate (default: 48000).
+    #[arg(long, default_value = "48000")]
+    sample_rate: u32,
+
+    /// Number of output channels, 1 or 2 (default: 2).
+    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u16).range(1..=2))]
+    channels: u16,
+
+    /// Bit depth (default: 16).
+    #[arg(long, default_value = "16", value_parser = ["16", "24", "32"])]
+    bits: String,
+
+    /// Slot count, e.g. 16 or 64 to match the sampler's slice grid
+    /// (default: one per sample).
+    #[arg(long)]
+    slots: Option<usize>,
+
+    /// Slot length in seconds (default: the longest sample). Longer samples
+    /// are cut with a short fade.
+    #[arg(long)]
+    slot_length: Option<f32>,
+
+    /// Normalise each slot to the `--peak` level.
+    #[arg(long)]
+    normalize: bool,
+
+    /// Peak level for `--normalize`, in dBFS (default: -0.3).
+    #[arg(
+        long,
+        value_name = "DB",
+        default_value = "-0.3",
+        allow_negative_numbers = true
+    )]
+    peak: f32,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let index = scan_samples_dir(&args.samples);
+    let entries = expand_chain_names(&index, &args.names).unwrap_or_else(|err| panic!("{err}"));
+    let decoded: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let data = decode_sample_file_with(
+                &entry.path,
+                args.sample_rate as f32,
+                DecodeOptions::default(),
+            )
+            .unwrap_or_else(|err| panic!("failed to load {}: {err}", entry.name));
+            (entry.name.to_string(), data)
+        })
+        .collect();
+    let samples: Vec<_> = decoded.iter().map(|(name, d)| (name.clone(), d)).collect();
+
+    let options = ChainOptions {
+        channels: args.channels as usize,
+        slots: args.slots,
+        slot_frames: args
+            .slot_length
+            .map(|s| (s * args.sample_rate as f32).round() as usize),
+        normalize: args.normalize.then(|| 10f32.powf(args.peak / 20.0)),
+    };
+    let chain = SampleChain::build(&samples, &options).unwrap_or_else(|err| panic!("{err}"));
+
+    let bits: u16 = args.bits.parse().unwrap_or(16);
+    let spec = WavSpec {
+        channels: args.channels,
+        sample_rate: args.sample_rate,
+        bits_per_sample: bits,
+        sample_format: if bits == 32 {
+            SampleFormat::Float
+        } else {
+            SampleFormat::Int
+        },
+    };
+    let mut writer = WavWriter::create(&args.output, spec).expect("failed to create WAV file");
+    let full_scale = ((1i64 << (bits - 1)) - 1) as f32;
+    for &sample in &chain.frames {
+        if bits == 32 {
+            writer.write_sample(sample)
+        } else {
+            writer.write_sample((sample.clamp(-1.0, 1.0) * full_scale).round() as i32)
+        }
+        .expect("failed to write sample");
+    }
+    writer.finalize().expect("failed to finalize WAV");
+
+    let cue_path = args.output.with_extension("cue");
+    let wav_name = args
+        .output
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("chain.wav");
+    std::fs::write(&cue_path, chain.cue_sheet(wav_name, args.sample_rate))
+        .expect("failed to write cue sheet");
+
+    for slot in &chain.slots {
+        let note = if slot.truncated { " (cut)" } else { "" };
+        println!(
+            "  {:>3}  {:<24} {:+.1} dB{note}",
+            slot.start / chain.slot_frames + 1,
+            slot.name,
+            20.0 * slot.gain.log10()
+        );
+    }
+    println!(
+        "Chained {} samples into {} slots of {:.3}s: {} (+ {})",
+        chain.slots.len(),
+        chain.slot_count,
+        chain.slot_frames as f32 / args.sample_rate as f32,
+        args.output.display(),
+        cue_path.display()
+    );
+}
@@ -3,7 +3,11 @@
 //! Renders audio synthesis to a WAV file instead of real-time playback.
 
 use clap::Parser;
-use doux::offline::{apply_setup_commands, create_engine, render_to_buffer, OfflineEngineConfig};
+use doux::offline::{
+    apply_setup_commands, create_engine, read_script, render_session, render_to_buffer,
+    render_with_tail, OfflineEngineConfig,
+};
+use doux::session::read_session;
 use hound::{SampleFormat, WavSpec, WavWriter};
 use std::path::PathBuf;
 
@@ -19,8 +23,18 @@ struct Args {
     #[arg(short, long)]
     eval: Vec<String>,
 
-    /// Output WAV file path.
+    /// Script of commands to evaluate after `--eval`, one per line
+    /// (`//` starts a comment).
     #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Session log written by `doux --session` to play back, each event at
+    /// the tick it arrived at.
+    #[arg(long, value_name = "FILE")]
+    session: Option<PathBuf>,
+
+    /// Output WAV file path.
+    #[arg(short, long, visible_alias = "out")]
     output: PathBuf,
 
     /// Directory containing audio samples.
@@ -38,6 +52,38 @@ struct Args {
     /// Maximum polyphony (default: 64).
     #[arg(long, default_value = "64")]
     max_voices: usize,
+
+    /// Number of orbits (effect buses, default: 8, max 16).
+    #[arg(long, default_value = "8")]
+    orbits: usize,
+
+    /// Give each orbit its own output channels instead of sharing pairs:
+    /// stereo (2 per orbit) or mono (1 per orbit).
+    #[arg(long, value_name = "MODE", value_parser = ["pairs", "stereo", "mono"], default_value = "pairs")]
+    multi_out: String,
+
+    /// Keep rendering past the duration until voices and FX tails are
+    /// silent, for at most this many seconds.
+    #[arg(long)]
+    tail: Option<f32>,
+
+    /// Longest orbit delay time in seconds (sizes the delay memory).
+    #[arg(long)]
+    max_delay: Option<f32>,
+
+    /// Plate reverb tank rate: auto (48 kHz or less), high (render rate) or
+    /// low (half of auto).
+    #[arg(long, value_name = "QUALITY", value_parser = ["auto", "high", "low"], default_value = "auto")]
+    verb_quality: String,
+
+    /// Detect each sample's pitch on load, so `freq`/`note` play it in tune.
+    #[arg(long)]
+    detect_pitch: bool,
+
+    /// Resampling for files at another rate than the render: fast (linear),
+    /// medium or high (windowed sinc).
+    #[arg(long, value_name = "QUALITY", value_parser = ["fast", "medium", "high"], default_value = "medium")]
+    resample: String,
 }
 
 fn main() {
@@ -48,14 +94,33 @@ fn main() {
         channels: args.channels as usize,
         max_voices: args.max_voices,
         block_size: 512,
+        orbits: args.orbits,
+        out_mode: args.multi_out.parse().unwrap_or_default(),
+        detect_pitch: args.detect_pitch,
+        resample: args.resample.parse().unwrap_or_default(),
     };
 
     let mut engine =
         create_engine(config, args.samples.as_deref()).unwrap_or_else(|err| panic!("{err}"));
+    if let Some(seconds) = args.max_delay {
+        engine.set_max_delay_time(seconds);
+    }
+    engine.set_reverb_quality(args.verb_quality.parse().unwrap_or_default());
     apply_setup_commands(&mut engine, &args.eval);
-    let output = render_to_buffer(&mut engine, args.duration)
-        .output
-        .expect("offline render should capture output");
+    if let Some(path) = &args.input {
+        let script = read_script(path).unwrap_or_else(|err| panic!("{err}"));
+        apply_setup_commands(&mut engine, &script);
+    }
+    let pass = match (&args.session, args.tail) {
+        (Some(path), tail) => {
+            let session = read_session(path).unwrap_or_else(|err| panic!("{err}"));
+            render_session(&mut engine, &session, args.duration, tail)
+        }
+        (None, Some(max_tail)) => render_with_tail(&mut engine, args.duration, max_tail),
+        (None, None) => render_to_buffer(&mut engine, args.duration),
+    };
+    let rendered_seconds = pass.samples as f32 / config.sample_rate;
+    let output = pass.output.expect("offline render should capture output");
 
     let spec = WavSpec {
         channels: args.channels,
@@ -72,7 +137,7 @@ fn main() {
 
     println!(
         "Rendered {:.2}s to {} ({} Hz, {} ch)",
-        args.duration,
+        rendered_seconds,
         args.output.display(),
         args.sample_rate,
         args.channels
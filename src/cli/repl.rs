@@ -27,26 +27,43 @@
 //! | `.hush`   |       | Fade out all voices                  |
 //! | `.panic`  |       | Immediately silence all voices       |
 //! | `.voices` |       | Show active voice count              |
+//! | `.voices -v` |    | List active voices with their params |
+//! | `.maxvoices N` |  | Change the polyphony limit           |
+//! | `.save FILE` |    | Save the session (voices, orbits, schedule) |
+//! | `.load FILE` |    | Restore a saved session              |
+//! | `.run FILE` |     | Run a script of patterns (see below) |
 //! | `.time`   |       | Show engine time in seconds          |
+//! | `.calibrate` |    | Measure the live input's round-trip latency |
+//! | `.stats`  | `.s`  | Show engine telemetry                |
+//! | `.stats detail` | | Per-stage timing (`profiling` builds) |
+//! | `.stats reset`  | | Clear the per-stage timing           |
 //! | `.help`   | `.h`  | Show available commands              |
 //!
-//! Any other input is evaluated as a doux pattern.
+//! Any other input is evaluated as a doux pattern; a line ending in `\`
+//! continues on the next one. After `/doux/strict/1`,
+//! patterns with unknown keys or unparsable values are not sent; the REPL
+//! prints the offending tokens instead (`/doux/strict/0` turns this off).
 
 use clap::Parser;
 use doux::cli_common::{
-    build_audio_streams, init_audio_host, recreate_engine, setup_engine_samples, CommonAudioArgs,
-    HostInit, StreamParams,
+    apply_master_eq, apply_max_delay, build_audio_streams, init_audio_host, open_session_log,
+    read_master_eq, recreate_engine, setup_engine_samples, CommonAudioArgs, HostInit, StreamParams,
 };
+use doux::event::Event;
+use doux::offline::parse_script;
+use doux::sampling::DecodeOptions;
+use doux::telemetry::EngineMetrics;
+use doux::types::MAX_VOICES;
 use doux::AudioCmd;
-use doux::Engine;
+use doux::{Engine, EngineState};
 use rustyline::completion::Completer;
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
-use rustyline::validate::Validator;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::Helper;
 use std::borrow::Cow;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -134,7 +151,16 @@ impl Hinter for DouxHighlighter {
     type Hint = String;
 }
 
-impl Validator for DouxHighlighter {}
+impl Validator for DouxHighlighter {
+    /// A line ending in `\` continues on the next one.
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        Ok(if ctx.input().trim_end().ends_with('\\') {
+            ValidationResult::Incomplete
+        } else {
+            ValidationResult::Valid(None)
+        })
+    }
+}
 
 impl Helper for DouxHighlighter {}
 
@@ -144,6 +170,44 @@ impl Helper for DouxHighlighter {}
 struct Args {
     #[command(flatten)]
     common: CommonAudioArgs,
+
+    /// Script of events to run at startup, as with `.run`.
+    #[arg(long, value_name = "FILE")]
+    exec: Option<PathBuf>,
+}
+
+/// Head start `@time` events get over the audio thread, so `@0` isn't late.
+const SCRIPT_LEAD: f64 = 0.05;
+
+#[cfg(feature = "profiling")]
+#[global_allocator]
+static ALLOC: doux::telemetry::CountingAllocator = doux::telemetry::CountingAllocator;
+
+fn print_profile(metrics: &EngineMetrics) {
+    if !cfg!(feature = "profiling") {
+        println!("Per-stage timing needs a build with `--features profiling`");
+        return;
+    }
+    let snapshot = metrics.profiling_snapshot();
+    println!(
+        "{:<18} {:>6} {:>9} {:>8} {:>8}",
+        "Stage", "%", "ns/smp", "p50", "p99"
+    );
+    let us = |ns: u64| format!("{:.1}us", ns as f64 / 1000.0);
+    for s in snapshot.sorted_summaries() {
+        println!(
+            "{:<18} {:>5.1}% {:>9.1} {:>8} {:>8}",
+            s.label,
+            s.percent_total,
+            s.ns_per_sample,
+            us(s.p50_ns),
+            us(s.p99_ns)
+        );
+    }
+    println!(
+        "{} blocks, {} audio-thread allocations",
+        snapshot.total_blocks, snapshot.audio_allocations
+    );
 }
 
 fn print_help() {
@@ -153,15 +217,156 @@ fn print_help() {
     println!("  .hush        Fade out all voices");
     println!("  .panic       Immediately silence all voices");
     println!("  .voices      Show active voice count");
+    println!("  .voices -v   List active voices (sound, freq, gain, orbit, envelope, sends, age)");
+    println!("  .maxvoices N Change the polyphony limit");
+    println!("  .revert N    Flip voice N back to its previous params");
+    println!("  .save FILE   Save the session (voices, orbits, schedule)");
+    println!("  .load FILE   Restore a session saved with .save");
+    println!(
+        "  .run FILE    Run a script: one pattern per line, // comments, @SECS prefix schedules"
+    );
     println!("  .time        Show engine time");
+    println!("  .calibrate   Measure input latency (needs an output looped to an input)");
     println!("  .stats, .s   Show engine telemetry (load, voices, memory)");
+    println!("  .stats detail  Per-stage timing and audio-thread allocations");
+    println!("  .stats reset   Clear the per-stage timing");
     println!("  .help, .h    Show this help");
     println!();
-    println!("Any other input is evaluated as a doux pattern.");
+    println!("Any other input is evaluated as a doux pattern; end a line with \\ to continue it.");
+    println!("set NAME VALUE stores a variable; $NAME in later patterns reads it (/freq/$NAME*2).");
+    println!("/doux/strict/1 rejects patterns with unknown keys or bad values.");
+}
+
+/// Asks the audio thread for a snapshot and writes it to `path` as JSON.
+fn save_session(cmd_tx: &crossbeam_channel::Sender<AudioCmd>, path: &str) -> Result<(), String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    cmd_tx
+        .send(AudioCmd::Snapshot {
+            state: Box::new(EngineState::reserved()),
+            reply: tx,
+        })
+        .map_err(|_| "audio thread is gone".to_string())?;
+    let state = rx
+        .recv_timeout(std::time::Duration::from_secs(2))
+        .map_err(|_| "audio thread did not answer".to_string())?;
+    let json = state.to_json()?;
+    std::fs::write(path, json).map_err(|e| format!("failed to write {path}: {e}"))
+}
+
+/// Sends a script's events, scheduling `@time` ones from now. Returns how
+/// many were sent.
+fn run_script(
+    cmd_tx: &crossbeam_channel::Sender<AudioCmd>,
+    path: &Path,
+    metrics: &EngineMetrics,
+    sample_rate: f32,
+    strict: bool,
+) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let start = f64::from_bits(metrics.time_bits.load(Ordering::Relaxed)) + SCRIPT_LEAD;
+    let mut sent = 0;
+    for line in parse_script(&text) {
+        if strict {
+            let warnings = Event::check(&line.event);
+            for warning in &warnings {
+                println!("{RED}[warning]{RESET} {warning}");
+            }
+            if !warnings.is_empty() {
+                continue;
+            }
+        }
+        let tick = line
+            .at
+            .map(|t| ((start + t.max(0.0)) * sample_rate as f64) as u64);
+        let mut gone = false;
+        AudioCmd::route(line.event, tick, |cmd| gone |= cmd_tx.send(cmd).is_err());
+        if gone {
+            return Err("audio thread is gone".to_string());
+        }
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Asks the audio thread for its active voices and prints one per line.
+fn print_voice_details(cmd_tx: &crossbeam_channel::Sender<AudioCmd>) -> Result<(), String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    cmd_tx
+        .send(AudioCmd::VoiceDetails {
+            buffer: Vec::with_capacity(MAX_VOICES),
+            reply: tx,
+        })
+        .map_err(|_| "audio thread is gone".to_string())?;
+    let voices = rx
+        .recv_timeout(std::time::Duration::from_secs(2))
+        .map_err(|_| "audio thread did not answer".to_string())?;
+    if voices.is_empty() {
+        println!("No active voices");
+    }
+    for v in voices {
+        let stage = format!("{:?}", v.stage).to_lowercase();
+        print!(
+            "{:>3} #{:<5} {:<12} {:>8.2} Hz  gain {:.2}  vel {:.2}  orbit {}  {stage} {:.2}  {:.2}s",
+            v.index,
+            v.id,
+            v.sound(),
+            v.freq,
+            v.gain,
+            v.velocity,
+            v.orbit,
+            v.env,
+            v.age
+        );
+        for (orbit, level) in v.active_sends() {
+            print!("  send {orbit}:{level:.2}");
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn load_session(cmd_tx: &crossbeam_channel::Sender<AudioCmd>, path: &str) -> Result<(), String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("failed to read {path}: {e}"))?;
+    let state = EngineState::from_json(&json).map_err(|e| format!("bad session file: {e}"))?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    cmd_tx
+        .send(AudioCmd::Restore {
+            state: Box::new(state),
+            reply: tx,
+        })
+        .map_err(|_| "audio thread is gone".to_string())?;
+    // The spent state comes back to be dropped here
+    rx.recv_timeout(std::time::Duration::from_secs(2))
+        .map(drop)
+        .map_err(|_| "audio thread did not answer".to_string())
+}
+
+/// Runs `/doux/calibrate` and waits for the engine to publish the result.
+fn calibrate(
+    cmd_tx: &crossbeam_channel::Sender<AudioCmd>,
+    metrics: &EngineMetrics,
+) -> Result<Option<u32>, String> {
+    let runs = metrics.calibrations.load(Ordering::Relaxed);
+    cmd_tx
+        .send(AudioCmd::Evaluate {
+            path: "/doux/calibrate".into(),
+            tick: None,
+        })
+        .map_err(|_| "audio thread is gone".to_string())?;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        if metrics.calibrations.load(Ordering::Relaxed) != runs {
+            return Ok(metrics.input_latency());
+        }
+    }
+    Err("calibration did not finish".to_string())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    doux::crash::install_panic_hook(args.common.crash_dir.clone());
 
     let (host, oc, block_size) = match init_audio_host(&args.common)? {
         HostInit::Ready {
@@ -184,8 +389,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         args.common.max_voices,
         block_size,
     );
+    let master_eq = read_master_eq(args.common.master_eq.as_deref());
+    let orbits = args.common.orbits;
+    let out_mode = args.common.multi_out.parse().unwrap_or_default();
+    engine.set_orbit_count(orbits);
+    engine.out_mode = out_mode;
+    apply_master_eq(&mut engine, master_eq.as_deref());
+    let max_delay = args.common.max_delay;
+    let verb_quality = args.common.verb_quality.parse().unwrap_or_default();
+    apply_max_delay(&mut engine, max_delay);
+    engine.set_reverb_quality(verb_quality);
 
     if let Some(ref dir) = args.common.samples {
+        engine.sample_registry.set_decode_options(DecodeOptions {
+            detect_pitch: args.common.detect_pitch,
+            resample: args.common.resample.parse().unwrap_or_default(),
+        });
         setup_engine_samples(&mut engine, dir, false, false);
         println!(
             "Samples: {} from {}",
@@ -198,19 +417,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let sample_registry = Arc::clone(&engine.sample_registry);
     #[cfg(feature = "soundfont")]
     let gm_bank = engine.gm_bank.clone();
-    let max_voices = args.common.max_voices;
+    let mut max_voices = args.common.max_voices;
+    let mut strict = false;
     let mut metrics = Arc::clone(&engine.metrics);
 
     let device_lost = Arc::new(AtomicBool::new(false));
 
     let (mut cmd_tx, cmd_rx) = crossbeam_channel::unbounded::<AudioCmd>();
 
+    let session = open_session_log(args.common.session.as_deref());
     let stream_params = StreamParams {
         host: &host,
         input_spec: args.common.input.as_deref(),
         output_spec: args.common.output.as_deref(),
         config: &oc,
         device_lost: &device_lost,
+        session: session.as_ref(),
     };
 
     let mut streams = build_audio_streams(&stream_params, engine, cmd_rx)?;
@@ -222,6 +444,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|_| PathBuf::from(".doux_history"));
     let _ = rl.load_history(&history_path);
 
+    if let Some(path) = &args.exec {
+        match run_script(&cmd_tx, path, &metrics, oc.sample_rate, strict) {
+            Ok(n) => println!("Ran {n} events from {}", path.display()),
+            Err(e) => eprintln!("{RED}[error]{RESET} {e}"),
+        }
+    }
+
     println!("Type .help for commands");
 
     loop {
@@ -231,7 +460,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             drop(streams);
             std::thread::sleep(std::time::Duration::from_secs(1));
 
-            let engine = recreate_engine(
+            let mut engine = recreate_engine(
                 oc.sample_rate,
                 oc.output_channels,
                 max_voices,
@@ -241,6 +470,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 #[cfg(feature = "soundfont")]
                 &gm_bank,
             );
+            engine.set_orbit_count(orbits);
+            engine.out_mode = out_mode;
+            apply_master_eq(&mut engine, master_eq.as_deref());
+            apply_max_delay(&mut engine, max_delay);
+            engine.set_reverb_quality(verb_quality);
             metrics = Arc::clone(&engine.metrics);
             let (new_tx, new_rx) = crossbeam_channel::unbounded::<AudioCmd>();
             cmd_tx = new_tx;
@@ -259,6 +493,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         match rl.readline("doux> ") {
             Ok(line) => {
                 let _ = rl.add_history_entry(&line);
+                let line: String = line.split("\\\n").map(str::trim).collect();
                 let trimmed = line.trim();
 
                 match trimmed {
@@ -272,21 +507,108 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     ".voices" | ".v" => {
                         println!("{}", metrics.active_voices.load(Ordering::Relaxed));
                     }
+                    ".voices -v" | ".v -v" => {
+                        if let Err(e) = print_voice_details(&cmd_tx) {
+                            eprintln!("{RED}[error]{RESET} {e}");
+                        }
+                    }
                     ".time" | ".t" => {
                         let t = f64::from_bits(metrics.time_bits.load(Ordering::Relaxed));
                         println!("{t:.3}s");
                     }
+                    ".stats detail" | ".s detail" => print_profile(&metrics),
+                    ".stats reset" | ".s reset" => metrics.reset_profiling(),
                     ".stats" | ".s" => {
                         let load_pct = metrics.load.get_load() * 100.0;
                         let voices = metrics.active_voices.load(Ordering::Relaxed);
                         let peak = metrics.peak_voices.load(Ordering::Relaxed);
                         let sched = metrics.schedule_depth.load(Ordering::Relaxed);
                         let mem = metrics.sample_pool_mb();
+                        let sleeps = metrics.orbit_sleeps.load(Ordering::Relaxed);
+                        let wakes = metrics.orbit_wakes.load(Ordering::Relaxed);
+                        let hot = metrics.hot_frames.load(Ordering::Relaxed);
+                        let latency = metrics.input_latency().map_or("-".to_string(), |f| {
+                            format!("{f} frames ({:.1} ms)", f as f32 / oc.sample_rate * 1000.0)
+                        });
                         println!("Load:     {load_pct:5.1}%");
                         println!("Voices:   {voices:3}/{max_voices}");
                         println!("Peak:     {peak:3}");
                         println!("Schedule: {sched:3}");
                         println!("Samples:  {mem:.1} MB");
+                        println!("Orbits:   {sleeps} sleeps, {wakes} wakes");
+                        println!("Hot:      {hot} frames over peakwarn");
+                        println!(
+                            "Stereo:   {:+.2} correlation, {} mono warnings",
+                            metrics.correlation(None),
+                            metrics.mono_warnings.load(Ordering::Relaxed)
+                        );
+                        println!("Latency:  {latency}");
+                    }
+                    s if s.starts_with(".maxvoices") => {
+                        match s[".maxvoices".len()..].trim().parse::<usize>() {
+                            Ok(n) if n > 0 => {
+                                max_voices = n;
+                                let _ = cmd_tx.send(AudioCmd::Evaluate {
+                                    path: format!("/doux/max_voices/n/{n}"),
+                                    tick: None,
+                                });
+                            }
+                            _ => println!("{max_voices}"),
+                        }
+                    }
+                    s if s.starts_with(".revert") => {
+                        match s[".revert".len()..].trim().parse::<usize>() {
+                            Ok(n) => {
+                                let _ = cmd_tx.send(AudioCmd::Evaluate {
+                                    path: format!("/voice/{n}/revert/1"),
+                                    tick: None,
+                                });
+                            }
+                            _ => println!("usage: .revert N"),
+                        }
+                    }
+                    s if s.starts_with(".save") || s.starts_with(".load") => {
+                        let (cmd, path) = s.split_at(".save".len());
+                        let path = path.trim();
+                        if path.is_empty() {
+                            println!("usage: {cmd} FILE");
+                            continue;
+                        }
+                        let result = if cmd == ".save" {
+                            save_session(&cmd_tx, path)
+                        } else {
+                            load_session(&cmd_tx, path)
+                        };
+                        match result {
+                            Ok(()) if cmd == ".save" => println!("Saved session to {path}"),
+                            Ok(()) => println!("Loaded session from {path}"),
+                            Err(e) => eprintln!("{RED}[error]{RESET} {e}"),
+                        }
+                    }
+                    s if s.starts_with(".run") => {
+                        let path = s[".run".len()..].trim();
+                        if path.is_empty() {
+                            println!("usage: .run FILE");
+                            continue;
+                        }
+                        let path = Path::new(path);
+                        match run_script(&cmd_tx, path, &metrics, oc.sample_rate, strict) {
+                            Ok(n) => println!("Ran {n} events from {}", path.display()),
+                            Err(e) => eprintln!("{RED}[error]{RESET} {e}"),
+                        }
+                    }
+                    ".calibrate" => {
+                        println!("Calibrating...");
+                        match calibrate(&cmd_tx, &metrics) {
+                            Ok(Some(f)) => println!(
+                                "Input latency: {f} frames ({:.1} ms)",
+                                f as f32 / oc.sample_rate * 1000.0
+                            ),
+                            Ok(None) => eprintln!(
+                                "{RED}[error]{RESET} No clicks came back; loop an output to an input"
+                            ),
+                            Err(e) => eprintln!("{RED}[error]{RESET} {e}"),
+                        }
                     }
                     ".hush" => {
                         let _ = cmd_tx.send(AudioCmd::Hush);
@@ -298,9 +620,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         print_help();
                     }
                     s if !s.is_empty() => {
-                        let _ = cmd_tx.send(AudioCmd::Evaluate {
-                            path: s.into(),
-                            tick: None,
+                        let event = Event::parse(s, oc.sample_rate);
+                        if event.cmd.as_deref() == Some("strict") {
+                            strict = !matches!(event.n.as_deref(), Some("0" | "off" | "false"));
+                            println!("Strict mode {}", if strict { "on" } else { "off" });
+                            continue;
+                        }
+                        if strict {
+                            let warnings = Event::check(s);
+                            for warning in &warnings {
+                                println!("{RED}[warning]{RESET} {warning}");
+                            }
+                            if !warnings.is_empty() {
+                                continue;
+                            }
+                        }
+                        AudioCmd::route(s.into(), None, |cmd| {
+                            let _ = cmd_tx.send(cmd);
                         });
                     }
                     _ => {}
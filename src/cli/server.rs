@@ -5,11 +5,16 @@
 
 use clap::Parser;
 use doux::cli_common::{
-    build_audio_streams, init_audio_host, recreate_engine, setup_engine_samples, CommonAudioArgs,
-    HostInit, StreamParams,
+    apply_master_eq, apply_max_delay, build_audio_streams, init_audio_host, open_session_log,
+    read_master_eq, recreate_engine, resolve_output_config, setup_engine_samples, AudioStreams,
+    CommonAudioArgs, HostInit, OutputConfig, StreamParams,
 };
-use doux::AudioCmd;
+use doux::osc::{OscOptions, OscRoute};
+use doux::sampling::DecodeOptions;
 use doux::Engine;
+use doux::{AudioCmd, EventAck, KeepRequest, VoiceEvent};
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -28,10 +33,154 @@ struct Args {
     /// Preload all samples at startup (blocks until complete).
     #[arg(long)]
     preload: bool,
+
+    /// Send `/doux/trigger` OSC messages for every voice to this address.
+    #[arg(long, value_name = "HOST:PORT")]
+    osc_out: Option<SocketAddr>,
+
+    /// Send each orbit's output envelope as `/doux/env/<orbit>` to this address.
+    #[arg(long, value_name = "HOST:PORT")]
+    env_out: Option<SocketAddr>,
+
+    /// Messages per second for `--env-out`.
+    #[arg(long, default_value = "60")]
+    env_rate: f32,
+
+    /// Send the engine clock (time, tick, voices, CPU load) to this address.
+    #[arg(long, value_name = "HOST:PORT")]
+    clock_out: Option<SocketAddr>,
+
+    /// OSC address of `--clock-out` messages.
+    #[arg(long, value_name = "PATH", default_value = doux::osc::CLOCK_ADDRESS)]
+    clock_address: String,
+
+    /// Seconds between `--clock-out` messages.
+    #[arg(long, value_name = "SECS", default_value = "0.05")]
+    clock_interval: f32,
+
+    /// Seconds of master output kept for `/doux/keep/<secs>` (0 disables).
+    #[arg(long, default_value = "60")]
+    retro: f32,
+
+    /// Directory where `/doux/keep` and `/doux/record` write their WAV files.
+    #[arg(long, default_value = ".")]
+    keep_dir: PathBuf,
+
+    /// Reject messages with unknown keys or bad values, replying with
+    /// `/doux/warning` (toggle at runtime with `/doux/strict [0|1]`).
+    #[arg(long)]
+    strict: bool,
+
+    /// Extra engine on another output device, addressed as `/doux/<NAME>`
+    /// (repeatable).
+    #[arg(long = "engine", value_name = "NAME=DEVICE")]
+    engines: Vec<String>,
+}
+
+/// An extra engine from `--engine`, with its device's output config.
+struct ExtraEngine {
+    name: String,
+    device: String,
+    config: OutputConfig,
+}
+
+/// Parses `NAME=DEVICE` and resolves the device's output config.
+fn parse_extra_engine(host: &cpal::Host, args: &Args, spec: &str) -> Result<ExtraEngine, String> {
+    let (name, device) = spec
+        .split_once('=')
+        .filter(|(name, device)| !name.is_empty() && !device.is_empty())
+        .ok_or_else(|| format!("--engine {spec}: expected NAME=DEVICE"))?;
+    if name.contains('/') || name == "overview" {
+        return Err(format!("--engine {spec}: invalid engine name"));
+    }
+    let config = resolve_output_config(
+        host,
+        Some(device),
+        args.common.channels,
+        args.common.buffer_size,
+    )
+    .map_err(|e| format!("--engine {spec}: {e}"))?;
+    Ok(ExtraEngine {
+        name: name.to_string(),
+        device: device.to_string(),
+        config,
+    })
+}
+
+/// Capacity of the engine → OSC ack channel. Acks beyond this are dropped.
+const ACK_CHANNEL_SIZE: usize = 256;
+
+/// Capacity of the engine → OSC trigger-out channel.
+const TRIGGER_CHANNEL_SIZE: usize = 1024;
+
+/// Wires the engine's triggers to a sender thread that lives as long as it.
+fn spawn_trigger_out(engine: &mut Engine, target: SocketAddr) {
+    let (tx, rx) = crossbeam_channel::bounded::<VoiceEvent>(TRIGGER_CHANNEL_SIZE);
+    engine.set_voice_event_sender(tx);
+    std::thread::spawn(move || {
+        if let Err(e) = doux::osc::run_trigger_out(rx, target) {
+            eprintln!("OSC out error: {e}");
+        }
+    });
+}
+
+/// Streams the engine's orbit envelopes until the engine is dropped.
+fn spawn_env_out(engine: &Engine, target: SocketAddr, rate: f32) {
+    let metrics = Arc::downgrade(&engine.metrics);
+    std::thread::spawn(move || {
+        if let Err(e) = doux::osc::run_env_out(metrics, target, rate) {
+            eprintln!("OSC env out error: {e}");
+        }
+    });
+}
+
+/// Broadcasts the engine clock until the engine is dropped.
+fn spawn_clock_out(engine: &Engine, args: &Args, target: SocketAddr) {
+    let metrics = Arc::downgrade(&engine.metrics);
+    let (address, interval) = (args.clock_address.clone(), args.clock_interval);
+    std::thread::spawn(move || {
+        if let Err(e) = doux::osc::run_clock_out(metrics, target, &address, interval) {
+            eprintln!("OSC clock out error: {e}");
+        }
+    });
+}
+
+/// Capacity of the engine → keep writer channel.
+const KEEP_CHANNEL_SIZE: usize = 4;
+
+/// Enables the rolling capture and writes each `/doux/keep` to `dir`.
+fn spawn_keep_writer(engine: &mut Engine, seconds: f32, dir: PathBuf) {
+    if seconds <= 0.0 {
+        return;
+    }
+    engine.set_retro_seconds(seconds);
+    let (tx, rx) = crossbeam_channel::bounded::<KeepRequest>(KEEP_CHANNEL_SIZE);
+    engine.set_keep_sender(tx);
+    std::thread::spawn(move || {
+        for req in rx {
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            let path = dir.join(format!("keep-{stamp}.wav"));
+            match req.write_wav(&path) {
+                Ok(()) => println!("Kept {:.1}s to {}", req.duration(), path.display()),
+                Err(e) => eprintln!("Keep error ({}): {e}", path.display()),
+            }
+        }
+    });
+}
+
+/// Enables `/doux/record`, writing to `dir`.
+fn spawn_tap_writer(engine: &mut Engine, dir: PathBuf) {
+    if let Err(e) = engine.set_tap_dir(dir) {
+        eprintln!("Record error: {e}");
+    }
 }
 
 fn main() {
     let args = Args::parse();
+    doux::crash::install_panic_hook(args.common.crash_dir.clone());
 
     let (host, oc, block_size) = match init_audio_host(&args.common) {
         Ok(HostInit::Ready {
@@ -58,8 +207,20 @@ fn main() {
         args.common.max_voices,
         block_size,
     );
+    let master_eq = read_master_eq(args.common.master_eq.as_deref());
+    engine.set_orbit_count(args.common.orbits);
+    engine.out_mode = args.common.multi_out.parse().unwrap_or_default();
+    apply_master_eq(&mut engine, master_eq.as_deref());
+    let max_delay = args.common.max_delay;
+    let verb_quality = args.common.verb_quality.parse().unwrap_or_default();
+    apply_max_delay(&mut engine, max_delay);
+    engine.set_reverb_quality(verb_quality);
 
     if let Some(ref dir) = args.common.samples {
+        engine.sample_registry.set_decode_options(DecodeOptions {
+            detect_pitch: args.common.detect_pitch,
+            resample: args.common.resample.parse().unwrap_or_default(),
+        });
         setup_engine_samples(&mut engine, dir, args.preload, true);
     }
 
@@ -68,23 +229,52 @@ fn main() {
     #[cfg(feature = "soundfont")]
     let gm_bank = engine.gm_bank.take();
 
+    let extras: Vec<ExtraEngine> = args
+        .engines
+        .iter()
+        .map(|spec| parse_extra_engine(&host, &args, spec))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        });
+
     let device_lost = Arc::new(AtomicBool::new(false));
 
     println!("Listening for OSC on port {}", args.port);
     println!("Press Ctrl+C to stop");
 
     let (mut cmd_tx, mut cmd_rx) = crossbeam_channel::unbounded::<AudioCmd>();
+    let (ack_tx, mut ack_rx) = crossbeam_channel::bounded::<EventAck>(ACK_CHANNEL_SIZE);
+    engine.set_ack_sender(ack_tx);
+    if let Some(target) = args.osc_out {
+        spawn_trigger_out(&mut engine, target);
+        println!("Sending triggers to {target}");
+    }
+    if let Some(target) = args.env_out {
+        spawn_env_out(&engine, target, args.env_rate);
+        println!("Sending orbit envelopes to {target}");
+    }
+    if let Some(target) = args.clock_out {
+        spawn_clock_out(&engine, &args, target);
+        println!("Sending the clock to {target} as {}", args.clock_address);
+    }
+    spawn_keep_writer(&mut engine, args.retro, args.keep_dir.clone());
+    spawn_tap_writer(&mut engine, args.keep_dir.clone());
 
+    let session = open_session_log(args.common.session.as_deref());
     let stream_params = StreamParams {
         host: &host,
         input_spec: args.common.input.as_deref(),
         output_spec: args.common.output.as_deref(),
         config: &oc,
         device_lost: &device_lost,
+        session: session.as_ref(),
     };
 
     loop {
         let anchor = engine.time_anchor();
+        let metrics = Arc::clone(&engine.metrics);
         let streams = match build_audio_streams(&stream_params, engine, cmd_rx) {
             Ok(s) => s,
             Err(e) => {
@@ -93,8 +283,64 @@ fn main() {
             }
         };
 
-        let lost = match doux::osc::run_recoverable(cmd_tx.clone(), args.port, anchor, &device_lost)
-        {
+        // Extra engines are rebuilt from scratch on every (re)connect
+        let mut extra_streams: Vec<AudioStreams> = Vec::with_capacity(extras.len());
+        let mut routes = Vec::with_capacity(extras.len());
+        for extra in &extras {
+            let mut engine = recreate_engine(
+                extra.config.sample_rate,
+                extra.config.output_channels,
+                args.common.max_voices,
+                block_size,
+                &sample_index,
+                &sample_registry,
+                #[cfg(feature = "soundfont")]
+                &gm_bank,
+            );
+            engine.set_orbit_count(args.common.orbits);
+            engine.out_mode = args.common.multi_out.parse().unwrap_or_default();
+            apply_master_eq(&mut engine, master_eq.as_deref());
+            apply_max_delay(&mut engine, max_delay);
+            engine.set_reverb_quality(verb_quality);
+            let (tx, rx) = crossbeam_channel::unbounded::<AudioCmd>();
+            let (ack_tx, ack_rx) = crossbeam_channel::bounded::<EventAck>(ACK_CHANNEL_SIZE);
+            engine.set_ack_sender(ack_tx);
+            let anchor = engine.time_anchor();
+            let params = StreamParams {
+                output_spec: Some(&extra.device),
+                config: &extra.config,
+                session: None,
+                ..stream_params
+            };
+            match build_audio_streams(&params, engine, rx) {
+                Ok(s) => extra_streams.push(s),
+                Err(e) => {
+                    eprintln!("Error (engine {}): {e}", extra.name);
+                    std::process::exit(1);
+                }
+            }
+            println!("Engine {:?} at /doux/{}", extra.name, extra.name);
+            routes.push(OscRoute {
+                name: extra.name.clone(),
+                tx,
+                anchor,
+                acks: Some(ack_rx),
+            });
+        }
+
+        let lost = match doux::osc::run_recoverable(
+            cmd_tx.clone(),
+            args.port,
+            anchor,
+            &device_lost,
+            OscOptions {
+                acks: Some(ack_rx),
+                registry: Some(Arc::clone(&sample_registry)),
+                metrics: Some(metrics),
+                routes,
+                strict: args.strict,
+            },
+        ) {
             Ok(lost) => lost,
             Err(e) => {
                 eprintln!("Error binding OSC port {}: {e}", args.port);
@@ -103,6 +349,7 @@ fn main() {
         };
 
         drop(streams);
+        drop(extra_streams);
 
         if !lost {
             break;
@@ -122,8 +369,27 @@ fn main() {
             #[cfg(feature = "soundfont")]
             &gm_bank,
         );
+        engine.set_orbit_count(args.common.orbits);
+        engine.out_mode = args.common.multi_out.parse().unwrap_or_default();
+        apply_master_eq(&mut engine, master_eq.as_deref());
+        apply_max_delay(&mut engine, max_delay);
+        engine.set_reverb_quality(verb_quality);
         let (new_tx, new_rx) = crossbeam_channel::unbounded::<AudioCmd>();
         cmd_tx = new_tx;
         cmd_rx = new_rx;
+        let (new_ack_tx, new_ack_rx) = crossbeam_channel::bounded::<EventAck>(ACK_CHANNEL_SIZE);
+        engine.set_ack_sender(new_ack_tx);
+        ack_rx = new_ack_rx;
+        if let Some(target) = args.osc_out {
+            spawn_trigger_out(&mut engine, target);
+        }
+        if let Some(target) = args.env_out {
+            spawn_env_out(&engine, target, args.env_rate);
+        }
+        if let Some(target) = args.clock_out {
+            spawn_clock_out(&engine, &args, target);
+        }
+        spawn_keep_writer(&mut engine, args.retro, args.keep_dir.clone());
+        spawn_tap_writer(&mut engine, args.keep_dir.clone());
     }
 }
@@ -11,7 +11,9 @@ use crate::audio::{
     find_device, get_host, host_controls_buffer_size, list_hosts, max_output_channels,
     print_diagnostics, HostSelection,
 };
+use crate::crash::{self, RecentEvents};
 use crate::error::DouxError;
+use crate::session::SessionLog;
 use crate::types::DEFAULT_NATIVE_BLOCK_SIZE;
 use crate::{AudioCmd, Engine};
 
@@ -48,6 +50,15 @@ pub struct CommonAudioArgs {
     #[arg(long, default_value = "32")]
     pub max_voices: usize,
 
+    /// Number of orbits (effect buses, default: 8, max 16).
+    #[arg(long, default_value = "8")]
+    pub orbits: usize,
+
+    /// Give each orbit its own output channels instead of sharing pairs:
+    /// stereo (2 per orbit) or mono (1 per orbit).
+    #[arg(long, value_name = "MODE", value_parser = ["pairs", "stereo", "mono"], default_value = "pairs")]
+    pub multi_out: String,
+
     /// Audio host backend: jack, alsa, asio, or auto (default: auto).
     #[arg(long, default_value = "auto")]
     pub host: String,
@@ -55,6 +66,37 @@ pub struct CommonAudioArgs {
     /// Run audio diagnostics and exit.
     #[arg(long)]
     pub diagnose: bool,
+
+    /// Master correction EQ (Equalizer APO / REW filter file, up to 8 bands).
+    #[arg(long, value_name = "FILE")]
+    pub master_eq: Option<PathBuf>,
+
+    /// Longest orbit delay time in seconds (sizes the delay memory).
+    #[arg(long, value_name = "SECS")]
+    pub max_delay: Option<f32>,
+
+    /// Plate reverb tank rate: auto (48 kHz or less), high (engine rate) or
+    /// low (half of auto).
+    #[arg(long, value_name = "QUALITY", value_parser = ["auto", "high", "low"], default_value = "auto")]
+    pub verb_quality: String,
+
+    /// Detect each sample's pitch on load, so `freq`/`note` play it in tune.
+    #[arg(long)]
+    pub detect_pitch: bool,
+
+    /// Resampling for files at another rate than the device: fast (linear),
+    /// medium or high (windowed sinc).
+    #[arg(long, value_name = "QUALITY", value_parser = ["fast", "medium", "high"], default_value = "medium")]
+    pub resample: String,
+
+    /// Where crash dumps go when the audio thread panics (default: temp dir).
+    #[arg(long, value_name = "DIR")]
+    pub crash_dir: Option<PathBuf>,
+
+    /// Log every event the engine receives, with the tick it arrived at, so
+    /// `doux-render --session` can render the session again.
+    #[arg(long, value_name = "FILE")]
+    pub session: Option<PathBuf>,
 }
 
 /// Outcome of host initialisation.
@@ -106,6 +148,53 @@ pub fn init_audio_host(common: &CommonAudioArgs) -> Result<HostInit, DouxError>
     })
 }
 
+/// Reads the `--master-eq` file once, so it can be applied to every engine
+/// the binary creates (including after a device reconnect).
+pub fn read_master_eq(path: Option<&Path>) -> Option<String> {
+    let path = path?;
+    match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            eprintln!("Master EQ ({}): {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Resizes the orbit delay lines when `--max-delay` was given.
+pub fn apply_max_delay(engine: &mut Engine, seconds: Option<f32>) {
+    if let Some(seconds) = seconds {
+        engine.set_max_delay_time(seconds);
+    }
+}
+
+/// Opens the `--session` log, if any. A log that can't be created is
+/// reported and skipped.
+pub fn open_session_log(path: Option<&Path>) -> Option<SessionLog> {
+    let path = path?;
+    match SessionLog::create(path) {
+        Ok(log) => {
+            println!("Logging session to {}", path.display());
+            Some(log)
+        }
+        Err(e) => {
+            eprintln!("Session log error ({}): {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Loads a correction file read by [`read_master_eq`] into the master bus.
+pub fn apply_master_eq(engine: &mut Engine, text: Option<&str>) {
+    let Some(text) = text else {
+        return;
+    };
+    match engine.master.load_correction(text) {
+        Ok(n) => println!("Master EQ: {n} bands"),
+        Err(e) => eprintln!("Master EQ: {e}"),
+    }
+}
+
 /// Loads samples from `dir` into the engine.
 ///
 /// `preload = true` decodes everything up front (blocking); otherwise sample data
@@ -123,7 +212,8 @@ pub fn setup_engine_samples(engine: &mut Engine, dir: &Path, preload: bool, verb
         }
         let sr = engine.sr;
         for entry in &index {
-            match crate::sampling::decode_sample_file(&entry.path, sr) {
+            let options = engine.sample_registry.decode_options();
+            match crate::sampling::decode_sample_file_with(&entry.path, sr, options) {
                 Ok(data) => {
                     engine
                         .sample_registry
@@ -273,6 +363,8 @@ pub struct StreamParams<'a> {
     pub output_spec: Option<&'a str>,
     pub config: &'a OutputConfig,
     pub device_lost: &'a Arc<AtomicBool>,
+    /// Log every event the engine takes, for replay with `doux-render`.
+    pub session: Option<&'a SessionLog>,
 }
 
 pub fn build_audio_streams(
@@ -365,36 +457,54 @@ pub fn build_audio_streams(
     let ch = params.config.output_channels;
     let mut scratch = vec![0.0f32; 1024];
     let output_format = params.config.sample_format;
+    let session = params.session.cloned();
+    if let Some(log) = &session {
+        log.start(sr);
+    }
 
     macro_rules! build_output {
         ($T:ty) => {{
             let mut conv_buf: Vec<f32> = Vec::new();
             let mut panicked = false;
+            let mut recent = RecentEvents::default();
+            let restart = Arc::clone(&flag);
             device.build_output_stream(
                 &params.config.stream_config,
                 move |data: &mut [$T], _| {
                     // A panic inside a cpal callback (called from C/ALSA) is UB.
-                    // Wrap in catch_unwind; on panic output silence.
+                    // Catch it, dump the engine state and output silence
+                    // until the stream is rebuilt.
                     if panicked {
                         for s in data.iter_mut() {
                             *s = <$T as FromSample<f32>>::from_sample_(0.0);
                         }
                         return;
                     }
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    let result = crash::catch_in_callback(|| {
                         conv_buf.resize(data.len(), 0.0f32);
 
                         while let Ok(cmd) = cmd_rx.try_recv() {
                             match cmd {
                                 AudioCmd::Evaluate { path, tick } => {
-                                    let mut event = crate::event::Event::parse(&path, engine.sr);
-                                    if event.tick.is_none() {
-                                        event.tick = tick;
+                                    if let Some(log) = &session {
+                                        log.record(engine.tick, tick, &path);
+                                    }
+                                    engine.evaluate_at(&path, tick);
+                                    recent.push(path);
+                                }
+                                AudioCmd::Hush => {
+                                    if let Some(log) = &session {
+                                        log.record(engine.tick, None, "/doux/hush");
+                                    }
+                                    engine.hush();
+                                }
+                                AudioCmd::Panic => {
+                                    if let Some(log) = &session {
+                                        log.record(engine.tick, None, "/doux/panic");
                                     }
-                                    engine.dispatch_event(event);
+                                    engine.panic();
                                 }
-                                AudioCmd::Hush => engine.hush(),
-                                AudioCmd::Panic => engine.panic(),
+                                cmd => engine.apply(cmd),
                             }
                         }
 
@@ -413,10 +523,21 @@ pub fn build_audio_streams(
                         for (out, &src) in data.iter_mut().zip(conv_buf.iter()) {
                             *out = <$T as FromSample<f32>>::from_sample_(src);
                         }
-                    })); // end catch_unwind
-                    if result.is_err() {
+                    }); // end catch_in_callback
+                    if let Err(payload) = result {
                         panicked = true;
-                        eprintln!("[doux] PANIC in audio callback — outputting silence");
+                        let report = crash::take_callback_panic(payload.as_ref())
+                            + "\n"
+                            + &crash::engine_report(&engine, &recent);
+                        match crash::write_dump(&report) {
+                            Ok(path) => eprintln!(
+                                "[doux] PANIC in audio callback, dump written to {}",
+                                path.display()
+                            ),
+                            Err(e) => eprintln!("[doux] PANIC in audio callback (no dump: {e})"),
+                        }
+                        eprintln!("[doux] restarting audio");
+                        restart.store(true, Ordering::Release);
                         for s in data.iter_mut() {
                             *s = <$T as FromSample<f32>>::from_sample_(0.0);
                         }
@@ -0,0 +1,149 @@
+//! Metronome click locked to the engine clock.
+//!
+//! Driven by `/doux/click/...` commands. Beats fall on the engine tempo's
+//! beats (`/doux/bpm`), so they line up with events scheduled in beats and
+//! follow tempo changes and `/doux/reset_time`. `clickbpm` overrides the
+//! tempo with beats counted from tick 0 instead; `clickbpm/0` follows the
+//! engine again:
+//!
+//! | Command                          | Effect                                 |
+//! |----------------------------------|----------------------------------------|
+//! | `/doux/click/1`                  | Start clicking                         |
+//! | `/doux/click/0`                  | Stop                                   |
+//! | `/doux/click/1/clickgain/0.5`    | Level (default 0.5)                    |
+//! | `/doux/click/1/clickbpm/96`      | Own tempo (default: the engine's)      |
+//! | `/doux/click/1/clickbeats/3`     | Accent every 3rd beat (0 = no accent)  |
+//! | `/doux/click/1/orbit/2`          | Play through orbit 2 (default 0)       |
+//! | `/doux/click/1/cue/1`            | Play on the cue orbit instead          |
+
+use crate::time::Tempo;
+
+const CLICK_FREQ: f32 = 1000.0;
+const ACCENT_FREQ: f32 = 1500.0;
+/// Time for a click to fall by 1/e.
+const CLICK_DECAY_SECS: f32 = 0.008;
+/// Clicks are cut after this, well under any usable beat length.
+const CLICK_SECS: f32 = 0.05;
+
+pub struct Click {
+    active: bool,
+    pub gain: f32,
+    /// Tempo of its own instead of the engine's.
+    pub bpm: Option<f32>,
+    /// Beats per bar; the first one is accented (0 = no accent).
+    pub beats: u32,
+    /// Orbit the click plays through, unless `cue` is set.
+    pub orbit: usize,
+    pub cue: bool,
+    sr: f32,
+}
+
+impl Click {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            active: false,
+            gain: 0.5,
+            bpm: None,
+            beats: 4,
+            orbit: 0,
+            cue: false,
+            sr,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    /// Click sample at engine tick `tick` (0 when off or between clicks).
+    pub fn next(&self, tick: u64, tempo: &Tempo) -> f32 {
+        if !self.active {
+            return 0.0;
+        }
+        let (beat, bpm) = match self.bpm {
+            Some(bpm) => (
+                tick as f64 * f64::from(bpm) / (60.0 * f64::from(self.sr)),
+                bpm,
+            ),
+            None => (tempo.beat_at(tick), tempo.bpm()),
+        };
+        let index = beat.floor();
+        let t = ((beat - index) * 60.0 / f64::from(bpm)) as f32;
+        if t >= CLICK_SECS {
+            return 0.0;
+        }
+        let accent = self.beats > 0 && (index as i64).rem_euclid(i64::from(self.beats)) == 0;
+        let freq = if accent { ACCENT_FREQ } else { CLICK_FREQ };
+        (t * freq * std::f32::consts::TAU).sin() * (-t / CLICK_DECAY_SECS).exp() * self.gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    #[test]
+    fn clicks_land_on_beats_of_the_engine_clock() {
+        let sr = 8000.0;
+        let mut tempo = Tempo::new(sr);
+        tempo.set_bpm(60.0, 0);
+        let mut click = Click::new(sr);
+        assert_eq!(click.next(10, &tempo), 0.0);
+        click.set_active(true);
+
+        // One beat per second: sound right after each multiple of 8000 ticks
+        let loud = |click: &Click, tempo: &Tempo, start: u64| {
+            (start..start + 200).any(|t| click.next(t, tempo) > 0.1)
+        };
+        assert!(loud(&click, &tempo, 0) && loud(&click, &tempo, 8000));
+        assert!(!loud(&click, &tempo, 1000) && !loud(&click, &tempo, 12000));
+        assert!((1..8000).all(|t| click.next(t, &tempo).abs() <= 0.5));
+
+        // The beat phase moves the clicks, a tempo change spaces them
+        tempo.set_beat(0.5, 0);
+        assert!(loud(&click, &tempo, 4000) && !loud(&click, &tempo, 8000));
+        tempo.set_bpm(120.0, 12000);
+        assert!(loud(&click, &tempo, 16000) && !loud(&click, &tempo, 18000));
+
+        // Its own tempo ignores the engine's
+        click.bpm = Some(240.0);
+        assert!(loud(&click, &tempo, 0) && loud(&click, &tempo, 2000));
+        assert!(!loud(&click, &tempo, 3000));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn click_plays_through_the_chosen_orbit_or_cue() {
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/click/1/clickgain/0.5/clickbpm/240/orbit/1");
+        assert!(!engine.is_idle());
+        let peaks = |engine: &mut Engine| {
+            let mut peak = [0.0f32; 4];
+            for _ in 0..200 {
+                engine.dsp();
+                for frame in engine.output.chunks(4) {
+                    for (p, x) in peak.iter_mut().zip(frame) {
+                        *p = p.max(x.abs());
+                    }
+                }
+            }
+            peak
+        };
+        let peak = peaks(&mut engine);
+        assert!(peak[2] > 0.1 && peak[0] == 0.0, "{peak:?}");
+
+        engine.evaluate("/doux/master/cueout/0");
+        engine.evaluate("/doux/click/1/cue/1");
+        let peak = peaks(&mut engine);
+        assert!(peak[0] > 0.1 && peak[2] == 0.0, "{peak:?}");
+
+        engine.evaluate("/doux/click/0");
+        assert_eq!(engine.click.next(0, &engine.tempo), 0.0);
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::path::PathBuf;
 
-use crate::types::DEFAULT_MAX_VOICES;
+use crate::types::{OutMode, DEFAULT_MAX_VOICES, DEFAULT_ORBITS};
 
 /// Configuration for the Doux audio engine.
 #[derive(Debug, Clone)]
@@ -21,6 +21,10 @@ pub struct DouxConfig {
     pub buffer_size: Option<u32>,
     /// Maximum polyphony (number of simultaneous voices).
     pub max_voices: usize,
+    /// Number of orbits (effect buses), clamped to `1..=MAX_ORBITS`.
+    pub orbits: usize,
+    /// Stereo pairs (default) or one channel group per orbit.
+    pub out_mode: OutMode,
 }
 
 impl Default for DouxConfig {
@@ -33,6 +37,8 @@ impl Default for DouxConfig {
             sample_paths: Vec::new(),
             buffer_size: None,
             max_voices: DEFAULT_MAX_VOICES,
+            orbits: DEFAULT_ORBITS,
+            out_mode: OutMode::Pairs,
         }
     }
 }
@@ -81,4 +87,14 @@ impl DouxConfig {
         self.max_voices = max_voices;
         self
     }
+
+    pub fn with_orbits(mut self, orbits: usize) -> Self {
+        self.orbits = orbits;
+        self
+    }
+
+    pub fn with_out_mode(mut self, out_mode: OutMode) -> Self {
+        self.out_mode = out_mode;
+        self
+    }
 }
@@ -0,0 +1,201 @@
+//! Crash dumps for panics on the audio and receiver threads. Native only.
+//!
+//! [`install_panic_hook`] chains onto the default hook. A panic inside
+//! [`catch_in_callback`] is only recorded: the audio callback turns it into a
+//! dump once it has caught the unwind and can still read the engine. Panics
+//! anywhere else are dumped straight from the hook.
+
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Engine;
+
+/// Events kept for the dump, newest last.
+pub const RECENT_EVENTS: usize = 16;
+
+static DUMP_DIR: OnceLock<PathBuf> = OnceLock::new();
+static CALLBACK_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+thread_local! {
+    static IN_CALLBACK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs the crash-dump panic hook. Dumps go to `dir`, or the system
+/// temp directory. Only the first call picks the directory.
+pub fn install_panic_hook(dir: Option<PathBuf>) {
+    let _ = DUMP_DIR.set(dir.unwrap_or_else(std::env::temp_dir));
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default(info);
+        let payload = payload_message(info.payload());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "<unknown>".into());
+        let thread = std::thread::current();
+        let report = format!(
+            "thread '{}' panicked at {location}:\n{payload}\n\nbacktrace:\n{}\n",
+            thread.name().unwrap_or("<unnamed>"),
+            std::backtrace::Backtrace::force_capture(),
+        );
+        if IN_CALLBACK.get() {
+            if let Ok(mut slot) = CALLBACK_PANIC.lock() {
+                *slot = Some(report);
+            }
+        } else {
+            match write_dump(&report) {
+                Ok(path) => eprintln!("[doux] crash dump written to {}", path.display()),
+                Err(e) => eprintln!("[doux] could not write crash dump: {e}"),
+            }
+        }
+    }));
+}
+
+/// Runs `f` like `catch_unwind`, leaving the dump of a panic to the caller.
+pub fn catch_in_callback<R>(f: impl FnOnce() -> R) -> std::thread::Result<R> {
+    IN_CALLBACK.set(true);
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    IN_CALLBACK.set(false);
+    result
+}
+
+/// Takes the report the hook recorded for a callback panic, or falls back
+/// to the bare payload when no hook is installed.
+pub fn take_callback_panic(payload: &(dyn Any + Send)) -> String {
+    CALLBACK_PANIC
+        .lock()
+        .ok()
+        .and_then(|mut slot| slot.take())
+        .unwrap_or_else(|| format!("panicked: {}\n", payload_message(payload)))
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string payload>".into())
+}
+
+/// Writes a time-stamped dump file and returns its path.
+pub fn write_dump(report: &str) -> std::io::Result<PathBuf> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let dir = DUMP_DIR.get().cloned().unwrap_or_else(std::env::temp_dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "doux-crash-{}-{:03}.txt",
+        now.as_secs(),
+        now.subsec_millis()
+    ));
+    let header = format!(
+        "doux {} crash dump\nunix time: {}.{:03}\n\n",
+        env!("CARGO_PKG_VERSION"),
+        now.as_secs(),
+        now.subsec_millis()
+    );
+    std::fs::write(&path, header + report)?;
+    Ok(path)
+}
+
+/// The last [`RECENT_EVENTS`] events the audio callback received.
+pub struct RecentEvents(VecDeque<String>);
+
+impl Default for RecentEvents {
+    fn default() -> Self {
+        Self(VecDeque::with_capacity(RECENT_EVENTS))
+    }
+}
+
+impl RecentEvents {
+    pub fn push(&mut self, event: String) {
+        if self.0.len() == RECENT_EVENTS {
+            self.0.pop_front();
+        }
+        self.0.push_back(event);
+    }
+}
+
+/// Engine state for a dump: clock, active voices, recent events, metrics.
+pub fn engine_report(engine: &Engine, recent: &RecentEvents) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "engine: {} Hz, {} ch, time {:.3}s, tick {}",
+        engine.sr, engine.output_channels, engine.time, engine.tick
+    );
+    let active = engine.active_voices.min(engine.voices.len());
+    let _ = writeln!(out, "\nvoices: {active}/{}", engine.max_voices);
+    for (i, v) in engine.voices[..active].iter().enumerate() {
+        let p = &v.params;
+        let _ = writeln!(
+            out,
+            "  [{i}] id {} {:?} freq {:.2} gain {:.3} orbit {} env {:?}",
+            v.id,
+            p.sound,
+            p.freq,
+            p.gain,
+            p.orbit,
+            v.dahdsr.state()
+        );
+    }
+    let _ = writeln!(out, "\nrecent events (oldest first):");
+    for event in &recent.0 {
+        let _ = writeln!(out, "  {event}");
+    }
+    let m = &engine.metrics;
+    let _ = writeln!(out, "\nmetrics:");
+    let _ = writeln!(out, "  load      {:.1}%", m.load.get_load() * 100.0);
+    let _ = writeln!(
+        out,
+        "  peak      {} voices",
+        m.peak_voices.load(Ordering::Relaxed)
+    );
+    let _ = writeln!(
+        out,
+        "  schedule  {} events",
+        m.schedule_depth.load(Ordering::Relaxed)
+    );
+    let _ = writeln!(
+        out,
+        "  dropped   {} events",
+        m.dropped_events.load(Ordering::Relaxed)
+    );
+    let _ = writeln!(out, "  samples   {:.1} MB", m.sample_pool_mb());
+    let _ = writeln!(
+        out,
+        "  hot       {} frames",
+        m.hot_frames.load(Ordering::Relaxed)
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_voices_and_the_last_events() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let mut recent = RecentEvents::default();
+        for i in 0..RECENT_EVENTS + 2 {
+            let event = format!("/sound/saw/freq/{}", 100 + i);
+            engine.evaluate(&event);
+            recent.push(event);
+        }
+        let report = engine_report(&engine, &recent);
+        assert!(report.contains("voices: 8/8"));
+        assert!(report.contains("Saw freq 100.00"));
+        // Only the newest events are kept
+        assert!(!report.contains("/freq/101\n"));
+        assert!(report.contains("/freq/102\n") && report.contains("/freq/117\n"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Stereo correlation metering.
+//!
+//! The correlation of a stereo pair runs from 1 (mono: both sides equal)
+//! through 0 (unrelated) to −1 (one side inverted). Material that stays
+//! negative cancels when the pair is summed to mono, as on many PAs, so
+//! wideners (Haas delays, `width` above 1) are worth keeping an eye on.
+
+/// Averaging time of the meter.
+const WINDOW_SECS: f32 = 0.3;
+/// Anti-correlation must last this long before it counts.
+const HOLD_SECS: f32 = 0.5;
+/// Mean power below which the pair counts as silent (about −80 dBFS).
+const SILENCE: f32 = 1e-8;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CorrelationMeter {
+    lr: f32,
+    ll: f32,
+    rr: f32,
+    coeff: f32,
+    /// Frames the pair has stayed below the warning threshold.
+    anti_frames: u32,
+    hold_frames: u32,
+    /// Sustained anti-correlation right now.
+    pub warning: bool,
+    /// Times the warning went up.
+    pub warnings: u64,
+}
+
+impl CorrelationMeter {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            lr: 0.0,
+            ll: 0.0,
+            rr: 0.0,
+            coeff: 1.0 - (-1.0 / (WINDOW_SECS * sr)).exp(),
+            anti_frames: 0,
+            hold_frames: (HOLD_SECS * sr) as u32,
+            warning: false,
+            warnings: 0,
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) {
+        self.lr += (l * r - self.lr) * self.coeff;
+        self.ll += (l * l - self.ll) * self.coeff;
+        self.rr += (r * r - self.rr) * self.coeff;
+    }
+
+    /// Correlation over the last few hundred milliseconds, 0 when silent.
+    pub fn value(&self) -> f32 {
+        let power = self.ll * self.rr;
+        if power < SILENCE * SILENCE {
+            return 0.0;
+        }
+        (self.lr / power.sqrt()).clamp(-1.0, 1.0)
+    }
+
+    /// Updates the warning after `frames` more frames: it goes up once the
+    /// correlation has stayed below `-threshold` for a while (0 disables).
+    pub fn check(&mut self, threshold: f32, frames: usize) -> bool {
+        if threshold > 0.0 && self.value() < -threshold {
+            self.anti_frames = self.anti_frames.saturating_add(frames as u32);
+        } else {
+            self.anti_frames = 0;
+        }
+        let warning = self.anti_frames >= self.hold_frames;
+        if warning && !self.warning {
+            self.warnings += 1;
+        }
+        self.warning = warning;
+        warning
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    fn run(meter: &mut CorrelationMeter, frames: usize, side: f32) {
+        for i in 0..frames {
+            let s = (i as f32 * 0.05).sin() * 0.5;
+            meter.process(s, s * side);
+        }
+    }
+
+    #[test]
+    fn inverted_sides_raise_a_warning_after_a_while() {
+        let mut meter = CorrelationMeter::new(1000.0);
+        run(&mut meter, 2000, 1.0);
+        assert!(meter.value() > 0.99);
+        assert!(!meter.check(0.3, 2000));
+
+        run(&mut meter, 2000, -1.0);
+        assert!(meter.value() < -0.99);
+        assert!(!meter.check(0.3, 100));
+        assert!(meter.check(0.3, 400));
+        assert!(meter.check(0.3, 100));
+        assert_eq!(meter.warnings, 1);
+        // Disabled, and silence reads as neutral
+        assert!(!meter.check(0.0, 1000));
+        let mut quiet = CorrelationMeter::new(1000.0);
+        run(&mut quiet, 100, 0.0);
+        assert_eq!(quiet.value(), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn inverted_haas_raises_a_mono_warning() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 480);
+        engine.evaluate("/doux/master/monowarn/0.5");
+        // Half a period late on one side: the sides cancel in mono
+        engine.evaluate("/sound/sine/freq/500/haas/1/gate/2/orbit/1");
+        for _ in 0..100 {
+            engine.process_block(&mut [0.0; 960], &[], &[]);
+        }
+        let metrics = &engine.metrics;
+        assert!(metrics.correlation(Some(1)) < -0.9);
+        assert!(metrics.anti_phase(Some(1)) && metrics.anti_phase(None));
+        assert!(!metrics.anti_phase(Some(0)));
+        assert_eq!(
+            metrics
+                .mono_warnings
+                .load(std::sync::atomic::Ordering::Relaxed),
+            2
+        );
+    }
+}
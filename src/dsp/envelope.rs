@@ -20,9 +20,13 @@
 //!
 //! # Curve Shaping
 //!
-//! Attack and decay/release phases use exponential curves controlled by internal
-//! parameters. Positive exponents create convex curves (slow start, fast finish),
-//! while negative exponents create concave curves (fast start, slow finish).
+//! Attack, decay and release each follow a power curve set with
+//! [`Dahdsr::set_curves`] (`attackcurve`, `decaycurve`, `releasecurve` in
+//! `-1..=1`). 0 is linear, positive values are exponential (attack starts
+//! slow, decay and release start fast), negative values logarithmic. The
+//! default 0.5 gives an exponent of 2.
+
+use serde::{Deserialize, Serialize};
 
 use super::fastmath::powf;
 
@@ -48,11 +52,28 @@ fn lerp(x: f32, y0: f32, y1: f32, exp: f32) -> f32 {
     } else {
         1.0 - powf(1.0 - x, -exp)
     };
-    y0 + (y1 - y0) * curved
+    // The fast `powf` can overshoot by ~1e-3 near the ends
+    y0 + (y1 - y0) * curved.clamp(0.0, 1.0)
+}
+
+/// Curve shape each stage starts with (see [`Dahdsr::set_curves`]).
+pub const DEFAULT_CURVE: f32 = 0.5;
+
+/// Maps a `-1..=1` stage curve to a [`lerp`] exponent of magnitude `4^|curve|`.
+/// Rising stages bend the other way, so positive is exponential in both.
+fn curve_exponent(curve: f32, rising: bool) -> f32 {
+    let curve = curve.clamp(-1.0, 1.0);
+    let exp = 4f32.powf(curve.abs());
+    if (curve >= 0.0) == rising {
+        exp
+    } else {
+        -exp
+    }
 }
 
 /// Current phase of the DAHDSR envelope state machine.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DahdsrState {
     /// Envelope is inactive, outputting zero.
     Off,
@@ -78,7 +99,7 @@ pub enum DahdsrState {
 /// # Curve Parameters
 ///
 /// Default curves use an exponent of `2.0` for attack (convex) and decay/release
-/// (concave when negated internally), producing natural-sounding amplitude shapes.
+/// (concave), producing natural-sounding amplitude shapes.
 #[derive(Clone, Copy)]
 pub struct Dahdsr {
     state: DahdsrState,
@@ -87,8 +108,14 @@ pub struct Dahdsr {
     start_val: f32,
     pub(crate) current_val: f32,
     gate_time: f32,
+    /// [`lerp`] exponents per stage.
     attack_curve: f32,
     decay_curve: f32,
+    release_curve: f32,
+    /// Stage and shape params seen last sample, so monotonicity is only
+    /// checked while they stay put.
+    #[cfg(feature = "debug-dsp")]
+    check_key: (DahdsrState, [f32; 2]),
 }
 
 impl Default for Dahdsr {
@@ -101,7 +128,10 @@ impl Default for Dahdsr {
             current_val: 0.0,
             gate_time: 0.0,
             attack_curve: 2.0,
-            decay_curve: 2.0,
+            decay_curve: -2.0,
+            release_curve: -2.0,
+            #[cfg(feature = "debug-dsp")]
+            check_key: (DahdsrState::Off, [0.0; 2]),
         }
     }
 }
@@ -116,6 +146,24 @@ impl Dahdsr {
         matches!(self.state, DahdsrState::Release)
     }
 
+    /// Current stage of the state machine.
+    pub fn state(&self) -> DahdsrState {
+        self.state
+    }
+
+    /// Last value returned by [`Dahdsr::update`].
+    pub fn value(&self) -> f32 {
+        self.current_val
+    }
+
+    /// Sets the attack, decay and release curves, each in `-1..=1` (see the
+    /// module docs).
+    pub fn set_curves(&mut self, attack: f32, decay: f32, release: f32) {
+        self.attack_curve = curve_exponent(attack, true);
+        self.decay_curve = curve_exponent(decay, false);
+        self.release_curve = curve_exponent(release, false);
+    }
+
     /// Start the envelope. `gate` is the total time before release (0.0 = infinite).
     pub fn trigger(&mut self, gate: f32) {
         self.gate_time = gate;
@@ -135,6 +183,29 @@ impl Dahdsr {
         self.phase_time = 0.0;
     }
 
+    /// Asserts the envelope stays in `[0, 1]` and moves in the stage's
+    /// direction (rising in attack, falling in decay/release).
+    #[cfg(feature = "debug-dsp")]
+    fn check_monotonic(&mut self, val: f32, shape: [f32; 2]) {
+        const EPS: f32 = 1e-5;
+        assert!(
+            (0.0..=1.0 + EPS).contains(&val),
+            "{:?} at {val}",
+            self.state
+        );
+        let key = (self.state, shape);
+        if key == self.check_key {
+            let prev = self.current_val;
+            let ok = match self.state {
+                DahdsrState::Attack => val >= prev - EPS,
+                DahdsrState::Decay | DahdsrState::Release => val <= prev + EPS,
+                _ => true,
+            };
+            assert!(ok, "{:?} not monotonic: {prev} -> {val}", self.state);
+        }
+        self.check_key = key;
+    }
+
     /// Check if gate time has elapsed and auto-release if so.
     #[inline]
     fn check_gate(&mut self) -> bool {
@@ -217,6 +288,8 @@ impl Dahdsr {
                     self.current_val = 1.0;
                     return 1.0;
                 }
+                #[cfg(feature = "debug-dsp")]
+                self.check_monotonic(val, [attack, 0.0]);
                 self.current_val = val;
                 val
             }
@@ -239,13 +312,15 @@ impl Dahdsr {
                 if self.check_gate() {
                     return self.current_val;
                 }
-                let val = lerp(self.phase_time / decay, 1.0, sustain, -self.decay_curve);
+                let val = lerp(self.phase_time / decay, 1.0, sustain, self.decay_curve);
                 if (val - sustain).abs() < 0.0001 {
                     self.state = DahdsrState::Sustain;
                     self.phase_time = 0.0;
                     self.current_val = sustain;
                     return sustain;
                 }
+                #[cfg(feature = "debug-dsp")]
+                self.check_monotonic(val, [decay, sustain]);
                 self.current_val = val;
                 val
             }
@@ -264,13 +339,15 @@ impl Dahdsr {
                     self.phase_time / release,
                     self.start_val,
                     0.0,
-                    -self.decay_curve,
+                    self.release_curve,
                 );
                 if val < 0.0001 {
                     self.state = DahdsrState::Off;
                     self.current_val = 0.0;
                     return 0.0;
                 }
+                #[cfg(feature = "debug-dsp")]
+                self.check_monotonic(val, [release, 0.0]);
                 self.current_val = val;
                 val
             }
@@ -366,6 +443,49 @@ mod tests {
             .fold(0.0, f32::max)
     }
 
+    #[test]
+    fn stage_curves_bend_each_way_and_default_to_squared() {
+        let isr = 1.0 / 1000.0;
+        // Level halfway through a 0.1 s decay to 0
+        let mid_decay = |curve: Option<f32>| {
+            let mut env = Dahdsr::default();
+            if let Some(c) = curve {
+                env.set_curves(DEFAULT_CURVE, c, DEFAULT_CURVE);
+            }
+            env.trigger(0.0);
+            let mut val = 0.0;
+            for _ in 0..53 {
+                val = env.update(isr, 0.0, 0.001, 0.0, 0.1, 0.0, 0.01);
+            }
+            val
+        };
+        assert_eq!(mid_decay(None), mid_decay(Some(DEFAULT_CURVE)));
+        assert!((mid_decay(Some(0.0)) - 0.5).abs() < 0.02);
+        assert!(mid_decay(Some(1.0)) < 0.1);
+        assert!(mid_decay(Some(-1.0)) > 0.9);
+
+        assert_eq!(curve_exponent(DEFAULT_CURVE, true), 2.0);
+        assert_eq!(curve_exponent(-1.0, true), -4.0);
+        assert_eq!(curve_exponent(3.0, false), -4.0);
+    }
+
+    /// Under `debug-dsp` this also runs `check_monotonic` on every stage.
+    #[test]
+    fn plain_adsr_stays_in_range() {
+        let mut env = Dahdsr::default();
+        let isr = 1.0 / 48000.0;
+        env.trigger(0.5);
+        let mut prev = 0.0;
+        while !env.is_off() {
+            let val = env.update(isr, 0.0, 0.01, 0.0, 0.1, 0.3, 0.2);
+            assert!((0.0..=1.0).contains(&val), "{:?} at {val}", env.state());
+            if env.state() == DahdsrState::Decay {
+                assert!(val <= prev, "{prev} -> {val}");
+            }
+            prev = val;
+        }
+    }
+
     #[test]
     fn all_transitions_smooth() {
         let mut env = Dahdsr::default();
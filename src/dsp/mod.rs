@@ -1,5 +1,6 @@
 //! Low-level DSP primitives.
 
+pub mod correlation;
 pub mod delay_line;
 pub mod envelope;
 pub mod fastmath;
@@ -7,7 +8,10 @@ pub mod fft;
 pub mod filter;
 pub mod noise;
 pub mod oscillator;
+pub mod resample;
+pub mod spectrum;
 
+pub use correlation::CorrelationMeter;
 pub use delay_line::DelayLine;
 pub use envelope::{init_envelope, Dahdsr, DahdsrState, EnvelopeParams};
 pub use fastmath::{
@@ -17,3 +21,5 @@ pub use fastmath::{
 pub use filter::{Biquad, SvfCascade, SvfMode, SvfState};
 pub use noise::{BrownNoise, PinkNoise};
 pub use oscillator::{polyblep_square, PhaseShape, Phasor};
+pub use resample::{resample, ResampleQuality};
+pub use spectrum::{SpectrumAnalyzer, SPECTRUM_BINS, SPECTRUM_SIZE};
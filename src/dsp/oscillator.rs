@@ -26,6 +26,7 @@
 
 use super::fastmath::{exp2f, powf, sinf};
 use crate::types::LfoShape;
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
 
 /// Wraps `phase + offset` into `[0, 1)`. Zero-alloc, handles any finite offset.
@@ -90,6 +91,29 @@ pub(crate) fn blamp_post_kink(wrap_frac: f32) -> f32 {
     d * d * d / 6.0
 }
 
+/// Correction for the sample after a hard-sync reset moves a saw's phase
+/// from `before` to `after`, `wrap_frac` into the sample. Applies the lobe
+/// for the real step and takes back the natural-wrap lobe the saw put on
+/// `after` if it landed within one step of 0. Full scale, like the saw.
+#[inline]
+pub(crate) fn sync_reset_post(before: f32, after: f32, wrap_frac: f32, dt: f32) -> f32 {
+    let h = 2.0 * (after - before);
+    let natural = if after < dt {
+        let d = 1.0 - after / dt;
+        d * d
+    } else {
+        0.0
+    };
+    h * blep_post_step(wrap_frac) - natural
+}
+
+/// Correction for the sample before a hard-sync reset from `before` to
+/// `after`, `wrap_frac` into the next sample.
+#[inline]
+pub(crate) fn sync_reset_pre(before: f32, after: f32, wrap_frac: f32) -> f32 {
+    2.0 * (after - before) * blep_pre_step(wrap_frac)
+}
+
 /// Band-limited square wave via PolyBLEP.
 ///
 /// Returns `+1` while `phase < 0.5`, `-1` otherwise, with smoothed transitions
@@ -113,7 +137,7 @@ pub fn polyblep_square(phase: f32, dt: f32) -> f32 {
 /// warp → mirror → size (in that order).
 ///
 /// All parameters have neutral defaults that result in no transformation.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct PhaseShape {
     /// Phase quantization steps. Values >= 2 create stair-step waveforms.
     pub size: u16,
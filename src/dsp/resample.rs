@@ -0,0 +1,209 @@
+//! Sample-rate conversion for decoded and uploaded audio.
+//!
+//! Runs once per sample at load time, never on the audio thread. The sinc
+//! qualities use a Blackman-windowed kernel from a table, widened below
+//! unity ratio so downsampling is band-limited to the new Nyquist.
+
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+/// Table entries per zero crossing of the kernel.
+const OVERSAMPLE: usize = 256;
+
+/// Interpolation used when a sample's rate differs from the engine's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ResampleQuality {
+    /// Linear interpolation: cheapest, aliases on downsampling.
+    Fast,
+    /// Windowed sinc, 8 zero crossings per side.
+    #[default]
+    Medium,
+    /// Windowed sinc, 32 zero crossings per side.
+    High,
+}
+
+impl ResampleQuality {
+    pub fn from_index(i: u8) -> Self {
+        match i {
+            0 => Self::Fast,
+            2 => Self::High,
+            _ => Self::Medium,
+        }
+    }
+
+    pub fn index(self) -> u8 {
+        match self {
+            Self::Fast => 0,
+            Self::Medium => 1,
+            Self::High => 2,
+        }
+    }
+
+    fn zero_crossings(self) -> usize {
+        match self {
+            Self::Fast => 0,
+            Self::Medium => 8,
+            Self::High => 32,
+        }
+    }
+}
+
+impl FromStr for ResampleQuality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fast" | "linear" => Ok(Self::Fast),
+            "medium" => Ok(Self::Medium),
+            "high" | "best" => Ok(Self::High),
+            _ => Err(format!("unknown resample quality: {s}")),
+        }
+    }
+}
+
+/// Converts interleaved `samples` from `from_sr` to `to_sr`.
+///
+/// The output holds `floor(frames * to_sr / from_sr)` frames. Equal rates
+/// return a copy.
+pub fn resample(
+    samples: &[f32],
+    channels: usize,
+    from_sr: f32,
+    to_sr: f32,
+    quality: ResampleQuality,
+) -> Vec<f32> {
+    let channels = channels.max(1);
+    if from_sr == to_sr || from_sr <= 0.0 || to_sr <= 0.0 {
+        return samples.to_vec();
+    }
+    let ratio = to_sr as f64 / from_sr as f64;
+    let in_frames = samples.len() / channels;
+    let out_frames = (in_frames as f64 * ratio) as usize;
+    let mut output = vec![0.0; out_frames * channels];
+    if in_frames == 0 {
+        return output;
+    }
+
+    if quality == ResampleQuality::Fast {
+        let last = in_frames - 1;
+        for (o, out) in output.chunks_exact_mut(channels).enumerate() {
+            let pos = o as f64 / ratio;
+            let i = (pos as usize).min(last);
+            let next = (i + 1).min(last);
+            let frac = (pos - i as f64) as f32;
+            for (ch, s) in out.iter_mut().enumerate() {
+                let s0 = samples[i * channels + ch];
+                let s1 = samples[next * channels + ch];
+                *s = s0 + frac * (s1 - s0);
+            }
+        }
+        return output;
+    }
+
+    let zc = quality.zero_crossings();
+    let kernel = sinc_table(zc);
+    // Below unity the kernel stretches so its cutoff follows the new Nyquist
+    let cutoff = ratio.min(1.0);
+    let reach = zc as f64 / cutoff;
+    let mut weights = Vec::with_capacity(2 * reach.ceil() as usize + 2);
+
+    for (o, out) in output.chunks_exact_mut(channels).enumerate() {
+        let pos = o as f64 / ratio;
+        let lo = (pos - reach).ceil().max(0.0) as usize;
+        let hi = ((pos + reach).floor() as usize).min(in_frames - 1);
+        weights.clear();
+        for i in lo..=hi {
+            let x = ((i as f64 - pos) * cutoff).abs() * OVERSAMPLE as f64;
+            let idx = x as usize;
+            let w = match (kernel.get(idx), kernel.get(idx + 1)) {
+                (Some(&a), Some(&b)) => a + (x - idx as f64) * (b - a),
+                (Some(&a), None) => a,
+                _ => 0.0,
+            };
+            weights.push(w * cutoff);
+        }
+        for (ch, s) in out.iter_mut().enumerate() {
+            let mut acc = 0.0;
+            for (k, w) in weights.iter().enumerate() {
+                acc += w * samples[(lo + k) * channels + ch] as f64;
+            }
+            *s = acc as f32;
+        }
+    }
+    output
+}
+
+/// One side of the windowed sinc, sampled [`OVERSAMPLE`] times per zero crossing.
+fn sinc_table(zero_crossings: usize) -> Vec<f64> {
+    let len = zero_crossings * OVERSAMPLE;
+    (0..=len)
+        .map(|i| {
+            let x = i as f64 / OVERSAMPLE as f64;
+            let sinc = if i == 0 {
+                1.0
+            } else {
+                (PI * x).sin() / (PI * x)
+            };
+            let t = x / zero_crossings as f64;
+            let window = 0.42 + 0.5 * (PI * t).cos() + 0.08 * (2.0 * PI * t).cos();
+            sinc * window
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    fn sine(freq: f32, sr: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (TAU * freq * i as f32 / sr).sin())
+            .collect()
+    }
+
+    /// Amplitude of `freq` in `signal`, by correlation over its middle half.
+    fn level(signal: &[f32], freq: f32, sr: f32) -> f32 {
+        let n = signal.len();
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (i, &s) in signal.iter().enumerate().take(3 * n / 4).skip(n / 4) {
+            let phase = (TAU * freq * i as f32 / sr) as f64;
+            re += s as f64 * phase.cos();
+            im += s as f64 * phase.sin();
+        }
+        (2.0 * re.hypot(im) / (n / 2) as f64) as f32
+    }
+
+    #[test]
+    fn sinc_keeps_pitch_and_level() {
+        for quality in [ResampleQuality::Medium, ResampleQuality::High] {
+            let out = resample(&sine(1000.0, 48000.0, 4800), 1, 48000.0, 44100.0, quality);
+            assert_eq!(out.len(), 4410);
+            assert!((level(&out, 1000.0, 44100.0) - 1.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn downsampling_filters_above_new_nyquist() {
+        // 30 kHz folds to 14.1 kHz at 44.1 kHz unless filtered out
+        let input = sine(30000.0, 96000.0, 9600);
+        let folded = |quality| {
+            let out = resample(&input, 1, 96000.0, 44100.0, quality);
+            level(&out, 14100.0, 44100.0)
+        };
+        assert!(folded(ResampleQuality::Fast) > 0.1);
+        assert!(folded(ResampleQuality::Medium) < 0.01);
+        assert!(folded(ResampleQuality::High) < 0.001);
+    }
+
+    #[test]
+    fn channels_stay_separate() {
+        let stereo: Vec<f32> = [1.0, -0.5].repeat(1000);
+        let out = resample(&stereo, 2, 22050.0, 44100.0, ResampleQuality::High);
+        assert_eq!(out.len(), 4000);
+        let mid = &out[1800..2200];
+        assert!(mid
+            .chunks(2)
+            .all(|f| (f[0] - 1.0).abs() < 1e-3 && (f[1] + 0.5).abs() < 1e-3));
+    }
+}
@@ -0,0 +1,127 @@
+//! Magnitude spectrum for analyzer and spectrogram displays.
+//!
+//! Samples are pushed one at a time into a ring; [`SpectrumAnalyzer::analyze`]
+//! runs a Hann-windowed FFT over the latest [`SPECTRUM_SIZE`] of them.
+//! Magnitudes are linear and scaled so a full-scale sine centred on a bin
+//! reads 1.0 there.
+
+use std::f32::consts::TAU;
+
+use super::fft::fft;
+
+/// FFT size, within the precomputed twiddle table.
+pub const SPECTRUM_SIZE: usize = 1024;
+/// Magnitude bins, from DC up to just below Nyquist.
+pub const SPECTRUM_BINS: usize = SPECTRUM_SIZE / 2;
+
+pub struct SpectrumAnalyzer {
+    ring: Vec<f32>,
+    pos: usize,
+    /// Samples pushed since the last analysis.
+    fresh: usize,
+    /// Hann window, pre-scaled to the magnitude normalisation.
+    window: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    mags: Vec<f32>,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new() -> Self {
+        let hann: Vec<f32> = (0..SPECTRUM_SIZE)
+            .map(|i| 0.5 - 0.5 * (TAU * i as f32 / SPECTRUM_SIZE as f32).cos())
+            .collect();
+        // A sine of amplitude A sums to A/2 * sum(window) in its bin
+        let scale = 2.0 / hann.iter().sum::<f32>();
+        Self {
+            ring: vec![0.0; SPECTRUM_SIZE],
+            pos: 0,
+            fresh: 0,
+            window: hann.into_iter().map(|w| w * scale).collect(),
+            re: vec![0.0; SPECTRUM_SIZE],
+            im: vec![0.0; SPECTRUM_SIZE],
+            mags: vec![0.0; SPECTRUM_BINS],
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, sample: f32) {
+        self.ring[self.pos] = sample;
+        self.pos = (self.pos + 1) % SPECTRUM_SIZE;
+        self.fresh += 1;
+    }
+
+    /// Magnitudes of the latest window, or `None` if nothing was pushed
+    /// since the last call.
+    pub fn analyze(&mut self) -> Option<&[f32]> {
+        if self.fresh == 0 {
+            return None;
+        }
+        self.fresh = 0;
+        // Oldest sample first, starting at the write position
+        for i in 0..SPECTRUM_SIZE {
+            let s = self.ring[(self.pos + i) % SPECTRUM_SIZE];
+            self.re[i] = s * self.window[i];
+            self.im[i] = 0.0;
+        }
+        fft(&mut self.re, &mut self.im, false);
+        for (k, mag) in self.mags.iter_mut().enumerate() {
+            *mag = self.re[k].hypot(self.im[k]);
+        }
+        Some(&self.mags)
+    }
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    #[test]
+    fn sine_peaks_in_its_bin() {
+        let mut analyzer = SpectrumAnalyzer::new();
+        assert!(analyzer.analyze().is_none());
+        let bin = 64;
+        for i in 0..SPECTRUM_SIZE * 2 {
+            let phase = TAU * bin as f32 * i as f32 / SPECTRUM_SIZE as f32;
+            analyzer.push(0.5 * phase.sin());
+        }
+        let mags = analyzer.analyze().unwrap();
+        assert!((mags[bin] - 0.5).abs() < 0.01, "{}", mags[bin]);
+        assert!(mags[bin + 4] < 1e-3 && mags[0] < 1e-3);
+        assert!(analyzer.analyze().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn spectrum_follows_the_output_once_enabled() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 256);
+        engine.evaluate("/sound/sine/freq/3000/gate/1");
+        let mut out = vec![0.0; 512];
+        engine.process_block(&mut out, &[], &[]);
+        let frames = |e: &Engine| {
+            e.metrics
+                .spectrum
+                .frames
+                .load(std::sync::atomic::Ordering::Relaxed)
+        };
+        assert_eq!(frames(&engine), 0);
+
+        engine.metrics.spectrum.set_enabled(true);
+        for _ in 0..8 {
+            engine.process_block(&mut out, &[], &[]);
+        }
+        assert_eq!(frames(&engine), 8);
+        let bins = engine.metrics.spectrum.latest();
+        let peak = (0..bins.len()).max_by(|&a, &b| bins[a].total_cmp(&bins[b]));
+        // 3 kHz at 48 kHz / 1024 points
+        assert_eq!(peak, Some(64));
+    }
+}
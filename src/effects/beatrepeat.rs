@@ -0,0 +1,318 @@
+//! Per-orbit beat repeat (stutter).
+//!
+//! Keeps a rolling recording of the orbit bus. A trigger freezes the last
+//! `repeatbeats` beats and replays them slice by slice in place of the dry
+//! signal: each slice boundary either repeats the current slice (with
+//! `repeatchance`) or moves on to the next one. Consecutive repeats can drop
+//! in pitch and level, for tape-stop and fade-out stutters.
+
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo, CHANNELS};
+use crate::voice::modulation::lcg;
+
+pub const INFO: ModuleInfo = ModuleInfo {
+    name: "repeat",
+    description: "Orbit beat repeat / stutter",
+    group: ModuleGroup::Effect,
+    params: &[
+        ParamInfo {
+            name: "repeat",
+            aliases: &[],
+            description: "1 = capture and start repeating, 0 = back to dry",
+            default: "0",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "repeatbpm",
+            aliases: &[],
+            description: "tempo the beat lengths refer to",
+            default: "120.0",
+            min: 20.0,
+            max: 400.0,
+        },
+        ParamInfo {
+            name: "repeatbeats",
+            aliases: &[],
+            description: "length of the captured buffer in beats",
+            default: "1.0",
+            min: 0.0625,
+            max: 16.0,
+        },
+        ParamInfo {
+            name: "repeatsize",
+            aliases: &[],
+            description: "slice length in beats",
+            default: "0.25",
+            min: 0.0625,
+            max: 16.0,
+        },
+        ParamInfo {
+            name: "repeatchance",
+            aliases: &[],
+            description: "probability a slice repeats instead of moving on",
+            default: "1.0",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "repeatpitch",
+            aliases: &[],
+            description: "pitch change per consecutive repeat in semitones",
+            default: "0.0",
+            min: -12.0,
+            max: 12.0,
+        },
+        ParamInfo {
+            name: "repeatdecay",
+            aliases: &[],
+            description: "level lost per consecutive repeat",
+            default: "0.0",
+            min: 0.0,
+            max: 1.0,
+        },
+    ],
+};
+
+/// Longest stretch of bus audio the capture ring holds.
+const MAX_REPEAT_SECS: f32 = 8.0;
+/// Wet/dry crossfade when the repeat starts or stops.
+const SWITCH_FADE_SECS: f32 = 0.005;
+/// Fade at slice edges so cuts into the recording don't click.
+const EDGE_FADE_SECS: f32 = 0.002;
+/// Random choices restart from here on every trigger, so the same
+/// gesture plays back the same way.
+const SEED: u32 = 0x5eed_1234;
+
+#[derive(Clone, Copy)]
+pub struct RepeatParams {
+    pub bpm: f32,
+    pub beats: f32,
+    pub size: f32,
+    pub chance: f32,
+    pub pitch: f32,
+    pub decay: f32,
+}
+
+impl Default for RepeatParams {
+    fn default() -> Self {
+        Self {
+            bpm: 120.0,
+            beats: 1.0,
+            size: 0.25,
+            chance: 1.0,
+            pitch: 0.0,
+            decay: 0.0,
+        }
+    }
+}
+
+/// Capture ring plus slice playback state.
+///
+/// Holds no memory until [`arm`](Self::arm) is called; from then on the
+/// orbit bus is recorded whenever the repeat isn't playing.
+#[derive(Clone, Default)]
+pub struct BeatRepeat {
+    ring: [Vec<f32>; CHANNELS],
+    mask: usize,
+    write_pos: usize,
+    /// Frames recorded since arming, capped at the ring length.
+    recorded: usize,
+    active: bool,
+    mix: f32,
+    mix_step: f32,
+    edge: f32,
+    capture_start: usize,
+    slice_len: f32,
+    slices: usize,
+    slice: usize,
+    /// Read offset into the current slice, in (fractional) frames.
+    pos: f32,
+    /// Output frames played of the current slice.
+    elapsed: f32,
+    repeats: u32,
+    rate: f32,
+    gain: f32,
+    seed: u32,
+    sr: f32,
+    pub params: RepeatParams,
+}
+
+impl BeatRepeat {
+    /// Allocates the capture ring and starts recording. Allocates on the
+    /// first call only.
+    pub fn arm(&mut self, sr: f32) {
+        if self.is_armed() {
+            return;
+        }
+        let len = ((MAX_REPEAT_SECS * sr) as usize).next_power_of_two();
+        self.ring = std::array::from_fn(|_| vec![0.0; len]);
+        self.mask = len - 1;
+        self.mix_step = 1.0 / (SWITCH_FADE_SECS * sr).max(1.0);
+        self.edge = (EDGE_FADE_SECS * sr).max(1.0);
+        self.sr = sr;
+    }
+
+    pub fn is_armed(&self) -> bool {
+        !self.ring[0].is_empty()
+    }
+
+    /// True while repeating or fading back to the dry signal.
+    pub fn is_playing(&self) -> bool {
+        self.active || self.mix > 0.0
+    }
+
+    /// Freezes the last `beats` beats and starts replaying them. Ignored
+    /// while already repeating or before anything was recorded.
+    pub fn trigger(&mut self) {
+        if self.active || self.recorded == 0 {
+            return;
+        }
+        let p = self.params;
+        let beat = 60.0 / p.bpm.max(1.0) * self.sr;
+        let capture = (p.beats.max(0.0) * beat).clamp(1.0, self.recorded as f32);
+        self.slice_len = (p.size.max(0.0) * beat).clamp(1.0, capture);
+        self.slices = ((capture / self.slice_len) as usize).max(1);
+        let span = (self.slice_len * self.slices as f32) as usize;
+        self.capture_start = self.write_pos.wrapping_sub(span) & self.mask;
+        self.seed = SEED;
+        self.slice = 0;
+        self.repeats = 0;
+        self.start_slice();
+        self.active = true;
+    }
+
+    /// Fades back to the dry signal and resumes recording.
+    pub fn release(&mut self) {
+        self.active = false;
+    }
+
+    fn rand(&mut self) -> f32 {
+        self.seed = lcg(self.seed);
+        ((self.seed >> 16) & 0x7fff) as f32 / 32767.0
+    }
+
+    fn start_slice(&mut self) {
+        let p = self.params;
+        self.rate = (p.pitch * self.repeats as f32 / 12.0).exp2();
+        self.gain = (1.0 - p.decay.clamp(0.0, 1.0)).powi(self.repeats as i32);
+        self.pos = 0.0;
+        self.elapsed = 0.0;
+    }
+
+    fn next_slice(&mut self) {
+        let chance = self.params.chance;
+        let repeat = chance >= 1.0 || (chance > 0.0 && self.rand() < chance);
+        if repeat {
+            self.repeats += 1;
+        } else {
+            self.slice = (self.slice + 1) % self.slices;
+            self.repeats = 0;
+        }
+        self.start_slice();
+    }
+
+    fn read(&self, channel: usize) -> f32 {
+        let offset = self.slice as f32 * self.slice_len + self.pos;
+        let i = offset as usize;
+        let frac = offset - i as f32;
+        let a = self.ring[channel][(self.capture_start + i) & self.mask];
+        let b = self.ring[channel][(self.capture_start + i + 1) & self.mask];
+        a + frac * (b - a)
+    }
+
+    fn play(&mut self) -> [f32; CHANNELS] {
+        if self.elapsed >= self.slice_len {
+            self.next_slice();
+        }
+        let edge = self
+            .elapsed
+            .min(self.slice_len - self.elapsed)
+            .min(self.edge)
+            / self.edge;
+        let g = self.gain * edge;
+        let out = std::array::from_fn(|c| self.read(c) * g);
+        self.elapsed += 1.0;
+        self.pos += self.rate;
+        if self.pos >= self.slice_len {
+            self.pos %= self.slice_len;
+        }
+        out
+    }
+
+    /// Records `bus` or replaces it with the repeat, crossfading between
+    /// the two on trigger and release.
+    pub fn process(&mut self, bus: &mut [f32; CHANNELS]) {
+        if !self.is_armed() {
+            return;
+        }
+        if !self.active {
+            for (c, &x) in bus.iter().enumerate() {
+                self.ring[c][self.write_pos] = x;
+            }
+            self.write_pos = (self.write_pos + 1) & self.mask;
+            self.recorded = (self.recorded + 1).min(self.mask);
+        }
+        self.mix = if self.active {
+            (self.mix + self.mix_step).min(1.0)
+        } else {
+            (self.mix - self.mix_step).max(0.0)
+        };
+        if self.mix == 0.0 {
+            return;
+        }
+        let wet = self.play();
+        for (c, x) in bus.iter_mut().enumerate() {
+            *x += self.mix * (wet[c] - *x);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stutter_replays_the_first_slice_of_the_capture() {
+        let sr = 1000.0;
+        let mut rep = BeatRepeat::default();
+        rep.arm(sr);
+        // 60 bpm: one beat is 1000 frames; capture 1 beat in 4 slices
+        rep.params.bpm = 60.0;
+        rep.params.beats = 1.0;
+        rep.params.size = 0.25;
+        for i in 0..1000 {
+            let mut bus = [i as f32; CHANNELS];
+            rep.process(&mut bus);
+        }
+        rep.trigger();
+        let mut out = Vec::new();
+        for _ in 0..1000 {
+            let mut bus = [-1.0; CHANNELS];
+            rep.process(&mut bus);
+            out.push(bus[0]);
+        }
+        // Mid-slice, after the switch fade: the slice starts at frame 0
+        assert!((out[100] - 100.0).abs() < 1e-3);
+        // Every slice repeats the same 250 frames
+        assert!((out[350] - 100.0).abs() < 1e-3);
+        assert!((out[850] - 100.0).abs() < 1e-3);
+
+        // Never repeating walks through the capture in order
+        rep.release();
+        for _ in 0..100 {
+            rep.process(&mut [0.0; CHANNELS]);
+        }
+        assert!(!rep.is_playing());
+        for i in 0..1000 {
+            rep.process(&mut [i as f32; CHANNELS]);
+        }
+        rep.params.chance = 0.0;
+        rep.trigger();
+        let mut bus = [0.0; CHANNELS];
+        for _ in 0..351 {
+            bus = [-1.0; CHANNELS];
+            rep.process(&mut bus);
+        }
+        assert!((bus[0] - 350.0).abs() < 1e-3);
+    }
+}
@@ -3,14 +3,15 @@
 //! Creates a shimmering, widened sound by mixing the dry signal with multiple
 //! delayed copies whose delay times are modulated by LFOs. Each voice uses a
 //! different LFO phase, and left/right channels are modulated in opposite
-//! directions for stereo spread.
+//! directions for stereo spread. Left and right keep separate delay lines so
+//! stereo input (e.g. live input) is not collapsed to mono.
 //!
 //! # Signal Flow
 //!
 //! ```text
-//! L+R → mono → delay line ─┬─ voice 0 (LFO phase 0°)   ─┬─→ L
-//!                          ├─ voice 1 (LFO phase 120°) ─┤
-//!                          └─ voice 2 (LFO phase 240°) ─┴─→ R
+//! L → delay line L ─┬─ voice 0 (LFO phase 0°)   ─┬─→ L
+//! R → delay line R ─┼─ voice 1 (LFO phase 120°) ─┤
+//!                   └─ voice 2 (LFO phase 240°) ─┴─→ R
 //! ```
 //!
 //! The three voices are phase-offset by 120° to avoid reinforcement artifacts.
@@ -48,6 +49,14 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 100.0,
         },
+        ParamInfo {
+            name: "chorusmix",
+            aliases: &[],
+            description: "equal-power dry/wet (1 = chorus only)",
+            default: "1.0",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
@@ -59,12 +68,13 @@ const VOICES: usize = 3;
 
 /// Multi-voice stereo chorus effect.
 ///
-/// Uses a circular delay buffer with three LFO-modulated tap points.
+/// Uses a circular delay buffer per channel with three LFO-modulated tap points.
 /// The LFOs are phase-offset by 1/3 cycle (120°) to create smooth,
 /// non-pulsing modulation.
 #[derive(Clone, Copy)]
 pub struct Chorus {
-    delay: DelayLine<BUFFER_SIZE>,
+    delay_l: DelayLine<BUFFER_SIZE>,
+    delay_r: DelayLine<BUFFER_SIZE>,
     lfo: [Phasor; VOICES],
 }
 
@@ -75,7 +85,8 @@ impl Default for Chorus {
             l.phase = i as f32 / VOICES as f32;
         }
         Self {
-            delay: DelayLine::default(),
+            delay_l: DelayLine::default(),
+            delay_r: DelayLine::default(),
             lfo,
         }
     }
@@ -111,8 +122,8 @@ impl Chorus {
         let depth = depth.clamp(0.0, 1.0);
         let mod_range = delay_ms * 0.8;
 
-        let mono = (left + right) * 0.5;
-        self.delay.write(mono);
+        self.delay_l.write(left);
+        self.delay_r.write(right);
 
         let mut out_l = 0.0_f32;
         let mut out_r = 0.0_f32;
@@ -130,14 +141,14 @@ impl Chorus {
             let samp_l = ms_to_samples(dly_l, sr).clamp(1.0, BUFFER_SIZE as f32 - 2.0);
             let samp_r = ms_to_samples(dly_r, sr).clamp(1.0, BUFFER_SIZE as f32 - 2.0);
 
-            out_l += self.delay.read(samp_l);
-            out_r += self.delay.read(samp_r);
+            out_l += self.delay_l.read(samp_l);
+            out_r += self.delay_r.read(samp_r);
         }
 
         out_l /= VOICES as f32;
         out_r /= VOICES as f32;
 
         const MIX: f32 = std::f32::consts::FRAC_1_SQRT_2;
-        [mono * MIX + out_l * MIX, mono * MIX + out_r * MIX]
+        [left * MIX + out_l * MIX, right * MIX + out_r * MIX]
     }
 }
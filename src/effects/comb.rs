@@ -44,6 +44,14 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 1.0,
         },
+        ParamInfo {
+            name: "combreturn",
+            aliases: &[],
+            description: "level of the comb return in the orbit mix",
+            default: "1",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
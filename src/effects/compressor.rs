@@ -1,13 +1,22 @@
-use crate::types::{ModuleGroup, ModuleInfo, ParamInfo};
+//! Dynamics: a threshold compressor for voices and orbits, and the orbit
+//! sidechain ducker.
+
+use crate::dsp::{exp2f, log2f};
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo, CHANNELS};
+
+/// dB per doubling of amplitude (20 * log10(2)).
+const DB_PER_OCTAVE: f32 = 6.020_6;
+/// Width of the soft knee around the threshold, in dB.
+const KNEE_DB: f32 = 6.0;
 
 pub const INFO: ModuleInfo = ModuleInfo {
     name: "compressor",
-    description: "Sidechain compressor (ducking)",
+    description: "Compressor and sidechain ducker",
     group: ModuleGroup::Effect,
     params: &[
         ParamInfo {
             name: "comp",
-            aliases: &[],
+            aliases: &["duckamount"],
             description: "duck amount (0 = off, 1 = full)",
             default: "0.0",
             min: 0.0,
@@ -15,7 +24,7 @@ pub const INFO: ModuleInfo = ModuleInfo {
         },
         ParamInfo {
             name: "compattack",
-            aliases: &["cattack"],
+            aliases: &["cattack", "duckattack"],
             description: "attack time in seconds",
             default: "0.01",
             min: 0.0,
@@ -23,7 +32,7 @@ pub const INFO: ModuleInfo = ModuleInfo {
         },
         ParamInfo {
             name: "comprelease",
-            aliases: &["crelease"],
+            aliases: &["crelease", "duckrelease"],
             description: "release time in seconds",
             default: "0.15",
             min: 0.0,
@@ -31,12 +40,36 @@ pub const INFO: ModuleInfo = ModuleInfo {
         },
         ParamInfo {
             name: "comporbit",
-            aliases: &["corbit"],
+            aliases: &["corbit", "duckfrom"],
             description: "sidechain source orbit index",
             default: "0.0",
             min: 0.0,
             max: 7.0,
         },
+        ParamInfo {
+            name: "compthresh",
+            aliases: &[],
+            description: "compressor threshold in dB (0 = off)",
+            default: "0.0",
+            min: -60.0,
+            max: 0.0,
+        },
+        ParamInfo {
+            name: "compratio",
+            aliases: &[],
+            description: "compression ratio above the threshold",
+            default: "4.0",
+            min: 1.0,
+            max: 20.0,
+        },
+        ParamInfo {
+            name: "compmakeup",
+            aliases: &[],
+            description: "makeup gain in dB",
+            default: "0.0",
+            min: 0.0,
+            max: 24.0,
+        },
     ],
 };
 
@@ -57,6 +90,7 @@ impl Default for CompressorParams {
     }
 }
 
+/// Envelope follower on another orbit's level, for sidechain ducking.
 #[derive(Default)]
 pub struct Compressor {
     env: f32,
@@ -74,3 +108,95 @@ impl Compressor {
         self.env
     }
 }
+
+#[derive(Clone, Copy)]
+pub struct DynamicsParams {
+    /// Threshold in dB. At or above 0 the compressor is bypassed.
+    pub threshold: f32,
+    pub ratio: f32,
+    /// Makeup gain in dB.
+    pub makeup: f32,
+    pub attack: f32,
+    pub release: f32,
+}
+
+impl Default for DynamicsParams {
+    fn default() -> Self {
+        Self {
+            threshold: 0.0,
+            ratio: 4.0,
+            makeup: 0.0,
+            attack: 0.01,
+            release: 0.15,
+        }
+    }
+}
+
+/// Feed-forward compressor with a stereo-linked peak detector and a soft knee.
+#[derive(Clone, Copy, Default)]
+pub struct Dynamics {
+    /// Smoothed gain reduction in dB.
+    reduction: f32,
+}
+
+impl Dynamics {
+    #[inline]
+    pub fn process(&mut self, frame: &mut [f32; CHANNELS], p: &DynamicsParams, isr: f32) {
+        let peak = frame[0].abs().max(frame[1].abs()).max(1e-6);
+        let over = log2f(peak) * DB_PER_OCTAVE - p.threshold;
+        let slope = 1.0 - 1.0 / p.ratio.max(1.0);
+        let target = if over <= -KNEE_DB * 0.5 {
+            0.0
+        } else if over >= KNEE_DB * 0.5 {
+            over * slope
+        } else {
+            let x = over + KNEE_DB * 0.5;
+            slope * x * x / (2.0 * KNEE_DB)
+        };
+        let time = if target > self.reduction {
+            p.attack
+        } else {
+            p.release
+        };
+        let coeff = (isr / time.max(0.0001)).min(1.0);
+        self.reduction += coeff * (target - self.reduction);
+        let gain = exp2f((p.makeup - self.reduction) / DB_PER_OCTAVE);
+        frame[0] *= gain;
+        frame[1] *= gain;
+    }
+
+    pub fn reset(&mut self) {
+        self.reduction = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dynamics_reduce_loud_signals_by_the_ratio() {
+        let isr = 1.0 / 48000.0;
+        let p = DynamicsParams {
+            threshold: -20.0,
+            ratio: 4.0,
+            ..Default::default()
+        };
+        let mut comp = Dynamics::default();
+        // 0 dB input, 20 dB over: settles at -20 + 20 / 4 = -15 dB
+        let mut frame = [1.0, -1.0];
+        for _ in 0..48000 {
+            frame = [1.0, -1.0];
+            comp.process(&mut frame, &p, isr);
+        }
+        let out_db = 20.0 * frame[0].log10();
+        assert!((out_db + 15.0).abs() < 0.3, "{out_db}");
+        assert_eq!(frame[0], -frame[1]);
+
+        // Well below the knee nothing changes
+        let mut comp = Dynamics::default();
+        let mut quiet = [0.01, 0.01];
+        comp.process(&mut quiet, &p, isr);
+        assert!((quiet[0] - 0.01).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,68 @@
+//! Stereo decorrelator — widens mono material without Haas combing.
+//!
+//! The mid signal runs through a short allpass cascade and is added to the
+//! side channel, so the left and right channels get complementary phase:
+//! `L = M + a·D(M)`, `R = M − a·D(M)`. The allpass keeps the magnitude flat,
+//! and `L + R` is a scaled copy of the dry mid, so it folds back to mono
+//! without combing.
+
+use crate::dsp::{ms_to_samples, DelayLine};
+use crate::types::CHANNELS;
+
+const BUFFER_SIZE: usize = 1024;
+/// Mutually prime-ish delays, short enough not to smear transients audibly.
+const STAGE_MS: [f32; 4] = [0.47, 1.13, 1.91, 3.07];
+const ALLPASS_GAIN: f32 = 0.6;
+/// Pure delay ahead of the cascade. Without it the allpass direct term
+/// (`(-g)^4`) correlates with the dry mid and leans the image left.
+const PRE_DELAY_MS: f32 = 0.23;
+
+#[derive(Clone, Copy, Default)]
+struct Allpass {
+    line: DelayLine<BUFFER_SIZE>,
+}
+
+impl Allpass {
+    #[inline]
+    fn process(&mut self, input: f32, delay: f32) -> f32 {
+        let delayed = self.line.read(delay);
+        let v = input + ALLPASS_GAIN * delayed;
+        self.line.write(v);
+        delayed - ALLPASS_GAIN * v
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Decorrelator {
+    pre: DelayLine<BUFFER_SIZE>,
+    stages: [Allpass; STAGE_MS.len()],
+}
+
+impl Decorrelator {
+    /// Decorrelates a stereo frame by `amount` (0 = untouched, 1 = full).
+    #[inline]
+    pub fn process(&mut self, frame: &mut [f32; CHANNELS], amount: f32, sr: f32) {
+        let mid = (frame[0] + frame[1]) * 0.5;
+        let side = (frame[0] - frame[1]) * 0.5;
+        let pre = whole_samples(PRE_DELAY_MS, sr);
+        let mut diffused = self.pre.read(pre);
+        self.pre.write(mid);
+        for (stage, ms) in self.stages.iter_mut().zip(STAGE_MS) {
+            diffused = stage.process(diffused, whole_samples(ms, sr));
+        }
+        let a = amount.clamp(0.0, 1.0);
+        // Keep per-channel power constant as the decorrelated part grows
+        let norm = (1.0 + a * a).sqrt().recip();
+        let side = side + a * diffused;
+        frame[0] = (mid + side) * norm;
+        frame[1] = (mid - side) * norm;
+    }
+}
+
+/// Integer delays: interpolated reads would low-pass the allpass.
+#[inline]
+fn whole_samples(ms: f32, sr: f32) -> f32 {
+    ms_to_samples(ms, sr)
+        .round()
+        .clamp(1.0, (BUFFER_SIZE - 2) as f32)
+}
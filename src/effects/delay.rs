@@ -38,10 +38,51 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 3.0,
         },
+        ParamInfo {
+            name: "delayreturn",
+            aliases: &[],
+            description: "level of the delay return in the orbit mix",
+            default: "1",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "delaysendlpf",
+            aliases: &[],
+            description: "high-cut on the delay send input in Hz (0 = off)",
+            default: "0",
+            min: 0.0,
+            max: 20000.0,
+        },
+        ParamInfo {
+            name: "delaysendhpf",
+            aliases: &[],
+            description: "low-cut on the delay send input in Hz (0 = off)",
+            default: "0",
+            min: 0.0,
+            max: 20000.0,
+        },
+        ParamInfo {
+            name: "delayfreeze",
+            aliases: &[],
+            description: "repeats what the line holds and shuts out new input (0..1)",
+            default: "0",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
-const MAX_DELAY_SAMPLES: usize = 65536;
+/// Delay memory per orbit unless configured otherwise, in seconds.
+pub const DEFAULT_MAX_DELAY_SECS: f32 = 1.0;
+/// Hard ceiling on configurable delay memory, in seconds.
+pub const MAX_DELAY_SECS_LIMIT: f32 = 60.0;
+
+/// Power-of-two line length holding at least `secs` of delay at `sr`.
+fn line_len(secs: f32, sr: f32) -> usize {
+    let frames = (secs.clamp(0.01, MAX_DELAY_SECS_LIMIT) * sr).ceil() as usize;
+    (frames + 1).next_power_of_two()
+}
 
 #[derive(Clone)]
 struct DelayLine {
@@ -84,17 +125,14 @@ impl DelayLine {
     }
 }
 
-impl Default for DelayLine {
-    fn default() -> Self {
-        Self::new(MAX_DELAY_SAMPLES)
-    }
-}
-
 #[derive(Clone, Copy)]
 pub struct DelayParams {
     pub time: f32,
     pub feedback: f32,
     pub delay_type: DelayType,
+    /// 0..1: closes the input and raises the feedback to unity, so the
+    /// line repeats what it holds. The orbit ramps it toward `delayfreeze`.
+    pub freeze: f32,
 }
 
 impl Default for DelayParams {
@@ -103,6 +141,7 @@ impl Default for DelayParams {
             time: 0.333,
             feedback: 0.6,
             delay_type: DelayType::Standard,
+            freeze: 0.0,
         }
     }
 }
@@ -118,8 +157,14 @@ pub struct Delay {
 
 impl Delay {
     pub fn new(sr: f32) -> Self {
+        Self::with_max_time(sr, DEFAULT_MAX_DELAY_SECS)
+    }
+
+    /// Creates a delay whose lines hold at least `max_secs` at `sr`.
+    pub fn with_max_time(sr: f32, max_secs: f32) -> Self {
+        let len = line_len(max_secs, sr);
         Self {
-            lines: Default::default(),
+            lines: std::array::from_fn(|_| DelayLine::new(len)),
             feedback: [0.0; CHANNELS],
             lp: [0.0; CHANNELS],
             params: DelayParams::default(),
@@ -127,10 +172,28 @@ impl Delay {
         }
     }
 
+    /// Longest delay time the lines can hold, in seconds.
+    pub fn max_time(&self) -> f32 {
+        self.lines[0].mask as f32 / self.sr
+    }
+
+    /// Reallocates the lines to hold at least `max_secs`, clearing them.
+    /// Lines that already have the length are only cleared.
+    pub fn set_max_time(&mut self, max_secs: f32) {
+        let len = line_len(max_secs, self.sr);
+        if len != self.lines[0].buffer.len() {
+            self.lines = std::array::from_fn(|_| DelayLine::new(len));
+        }
+        self.clear();
+    }
+
     pub fn process(&mut self, send: [f32; CHANNELS]) -> [f32; CHANNELS] {
         let p = self.params;
-        let delay_samples = ((p.time * self.sr) as usize).min(MAX_DELAY_SAMPLES - 1);
+        let delay_samples = ((p.time * self.sr) as usize).min(self.lines[0].mask);
         let feedback = p.feedback.clamp(0.0, 0.95);
+        let feedback = feedback + (1.0 - feedback) * p.freeze;
+        let open = 1.0 - p.freeze;
+        let send = [send[0] * open, send[1] * open];
 
         match p.delay_type {
             DelayType::Standard => {
@@ -160,10 +223,12 @@ impl Delay {
             }
             DelayType::Tape => {
                 const DAMP: f32 = 0.35;
+                // Frozen repeats skip the damping so they don't darken
+                let damp = DAMP + (1.0 - DAMP) * p.freeze;
                 let mut out = [0.0; CHANNELS];
                 for c in 0..CHANNELS {
                     let fb_raw = ftz(self.feedback[c], 0.0001);
-                    let fb = self.lp[c] + DAMP * (fb_raw - self.lp[c]);
+                    let fb = self.lp[c] + damp * (fb_raw - self.lp[c]);
                     self.lp[c] = fb;
 
                     let input = send[c] + fb * feedback;
@@ -181,10 +246,12 @@ impl Delay {
                 let tap3 = (t * (0.25 + swing * 0.083)).max(1.0) as usize;
                 let tap4 = (t * (0.125 + swing * 0.042)).max(1.0) as usize;
 
+                let tap_feedback = 0.5 + 0.5 * p.freeze;
+
                 let mut out = [0.0; CHANNELS];
                 for c in 0..CHANNELS {
                     let fb = ftz(self.feedback[c], 0.0001);
-                    let input = send[c] + fb * 0.5;
+                    let input = send[c] + fb * tap_feedback;
                     self.lines[c].write(input);
 
                     let out1 = self.lines[c].read_at(tap1);
@@ -208,3 +275,35 @@ impl Delay {
         self.lp = [0.0; CHANNELS];
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_time_scales_with_sample_rate_and_holds_long_delays() {
+        assert!(Delay::new(96000.0).max_time() >= DEFAULT_MAX_DELAY_SECS);
+
+        let sr = 1000.0;
+        let mut delay = Delay::new(sr);
+        delay.params.time = 8.0;
+        delay.params.feedback = 0.0;
+        assert!(delay.max_time() < 8.0);
+
+        delay.set_max_time(8.0);
+        assert!(delay.max_time() >= 8.0);
+        let mut echo_at = None;
+        for i in 0..9000 {
+            let send = if i == 0 {
+                [1.0; CHANNELS]
+            } else {
+                [0.0; CHANNELS]
+            };
+            if delay.process(send)[0] > 0.5 {
+                echo_at = Some(i);
+                break;
+            }
+        }
+        assert_eq!(echo_at, Some(8000));
+    }
+}
@@ -68,6 +68,14 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 0.0,
         },
+        ParamInfo {
+            name: "fbreturn",
+            aliases: &[],
+            description: "level of the feedback return in the orbit mix",
+            default: "1",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
@@ -146,12 +154,7 @@ impl Feedback {
     /// gain). Time/damp/cross and LFO modulation come from `self.params`.
     ///
     /// Returns wet signal only (dry is summed separately by the orbit bus).
-    pub fn process(
-        &mut self,
-        input: [f32; CHANNELS],
-        fb_amount: f32,
-        sr: f32,
-    ) -> [f32; CHANNELS] {
+    pub fn process(&mut self, input: [f32; CHANNELS], fb_amount: f32, sr: f32) -> [f32; CHANNELS] {
         let p = self.params;
         let isr = 1.0 / sr;
         let time_ms = if p.lfo > 0.0 {
@@ -35,6 +35,14 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 0.95,
         },
+        ParamInfo {
+            name: "flangermix",
+            aliases: &[],
+            description: "equal-power dry/wet (1 = flanger only)",
+            default: "1.0",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
@@ -0,0 +1,116 @@
+//! Bode-style frequency shifter.
+//!
+//! A pair of allpass chains splits the input into two signals 90° apart
+//! (a Hilbert transform), which are multiplied by a quadrature oscillator
+//! and summed. Every partial moves by the same number of Hz, so harmonic
+//! sounds turn inharmonic, unlike pitch shifting. Negative shifts move down.
+
+use crate::dsp::{cosf, sinf};
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo};
+use std::f32::consts::TAU;
+
+pub const INFO: ModuleInfo = ModuleInfo {
+    name: "fshift",
+    description: "Hilbert-based frequency shifter",
+    group: ModuleGroup::Effect,
+    params: &[
+        ParamInfo {
+            name: "fshift",
+            aliases: &[],
+            description: "shift in Hz (0 = bypass, negative shifts down)",
+            default: "0.0",
+            min: -5000.0,
+            max: 5000.0,
+        },
+        ParamInfo {
+            name: "fshiftmix",
+            aliases: &[],
+            description: "equal-power dry/wet (1 = shifted only)",
+            default: "1.0",
+            min: 0.0,
+            max: 1.0,
+        },
+    ],
+};
+
+/// Squared allpass coefficients of the two paths (Olli Niemitalo's 8th-order
+/// design: under 0.7° of phase error from 20 Hz up to near Nyquist).
+const PATH_I: [f32; 4] = [0.479_400_87, 0.876_218_5, 0.976_597_6, 0.997_499_3];
+const PATH_Q: [f32; 4] = [0.161_758_5, 0.733_028_9, 0.945_349_7, 0.990_599_16];
+
+/// Allpass chain in z^-2: `y = a²(x + y[n-2]) - x[n-2]` per stage.
+#[derive(Clone, Copy, Default)]
+struct AllpassChain {
+    x: [[f32; 2]; 4],
+    y: [[f32; 2]; 4],
+}
+
+impl AllpassChain {
+    #[inline]
+    fn process(&mut self, input: f32, coefs: &[f32; 4]) -> f32 {
+        let mut s = input;
+        for (i, &a) in coefs.iter().enumerate() {
+            let out = a * (s + self.y[i][1]) - self.x[i][1];
+            self.x[i] = [s, self.x[i][0]];
+            self.y[i] = [out, self.y[i][0]];
+            s = out;
+        }
+        s
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct FreqShifter {
+    path_i: AllpassChain,
+    path_q: AllpassChain,
+    /// One-sample delay that lines the I path up with the Q path.
+    i_delay: f32,
+    /// Quadrature oscillator phase, in turns.
+    phase: f32,
+}
+
+impl FreqShifter {
+    /// Shifts one sample by `shift` Hz, returning the wet signal only.
+    #[inline]
+    pub fn process(&mut self, input: f32, shift: f32, sr: f32) -> f32 {
+        let i = self.i_delay;
+        self.i_delay = self.path_i.process(input, &PATH_I);
+        let q = self.path_q.process(input, &PATH_Q);
+
+        let angle = self.phase * TAU;
+        self.phase += shift / sr;
+        self.phase -= self.phase.floor();
+        i * cosf(angle) + q * sinf(angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn magnitude_at(signal: &[f32], freq: f32, sr: f32) -> f32 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (n, &x) in signal.iter().enumerate() {
+            let w = std::f64::consts::TAU * freq as f64 * n as f64 / sr as f64;
+            re += x as f64 * w.cos();
+            im -= x as f64 * w.sin();
+        }
+        ((re * re + im * im).sqrt() / signal.len() as f64) as f32
+    }
+
+    #[test]
+    fn moves_every_partial_by_the_same_hz() {
+        let sr = 48000.0;
+        for (shift, target, image) in [(200.0, 1200.0, 800.0), (-300.0, 700.0, 1300.0)] {
+            let mut fs = FreqShifter::default();
+            let out: Vec<f32> = (0..9600)
+                .map(|n| fs.process((TAU * 1000.0 * n as f32 / sr).sin(), shift, sr))
+                .skip(4800)
+                .collect();
+            let wanted = magnitude_at(&out, target, sr);
+            assert!(wanted > 0.45, "{shift} Hz: {wanted}");
+            assert!(magnitude_at(&out, image, sr) < wanted * 0.02);
+            assert!(magnitude_at(&out, 1000.0, sr) < wanted * 0.02);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! User-defined insert effects.
+//!
+//! Embedders implement [`InsertEffect`] and register it by name with
+//! `Engine::register_insert` (or send an [`InsertPool`] to a running engine
+//! as `AudioCmd::Insert`). Each voice and each orbit has [`INSERT_SLOTS`]
+//! slots, filled per event: `/insert1/<name>` on the voice (after haas,
+//! before panning), `/orbitinsert1/<name>` on the orbit bus (after the
+//! reverb). `off` empties a slot.
+//!
+//! Processors are built when the effect is registered, one for every slot
+//! that could hold it, on the registering thread. Filling a slot takes one
+//! from the effect's pool and emptying it puts it back, so the audio thread
+//! never builds or drops one. A slot keeps its processor while it holds the
+//! same effect (voice slots are [`reset`](InsertProcessor::reset) on each
+//! new note).
+
+use std::sync::Arc;
+
+use crate::types::CHANNELS;
+
+/// Insert slots per voice and per orbit.
+pub const INSERT_SLOTS: usize = 2;
+
+/// Factory for an insert effect.
+pub trait InsertEffect: Send + Sync {
+    /// Builds a per-slot processor. Called for every slot up front when the
+    /// effect is registered, so keep allocations modest.
+    fn create(&self, sr: f32) -> Box<dyn InsertProcessor>;
+}
+
+/// Running instance of an [`InsertEffect`] in one slot.
+pub trait InsertProcessor: Send {
+    /// Processes one stereo frame in place.
+    fn process(&mut self, frame: &mut [f32; CHANNELS]);
+
+    /// Clears internal state (voice reuse, orbit reset).
+    fn reset(&mut self) {}
+}
+
+/// A registered effect and its idle processors.
+pub struct InsertPool {
+    name: Arc<str>,
+    free: Vec<Box<dyn InsertProcessor>>,
+}
+
+impl InsertPool {
+    /// Builds `processors` processors of `effect` at `sr`: one per slot
+    /// that may hold it at once (`Engine::insert_slot_count` covers every
+    /// slot).
+    pub fn new(name: &str, effect: &dyn InsertEffect, sr: f32, processors: usize) -> Self {
+        Self {
+            name: Arc::from(name),
+            free: (0..processors).map(|_| effect.create(sr)).collect(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Named insert effects, looked up when an event fills a slot.
+///
+/// Registering a name again adds a new pool that later assignments use;
+/// the old one stays, so its processors still have somewhere to go back to
+/// when their slots change.
+#[derive(Default)]
+pub struct InsertRegistry {
+    pools: Vec<InsertPool>,
+}
+
+impl InsertRegistry {
+    /// Adds `pool`, replacing any effect of the same name for later
+    /// assignments.
+    pub fn register(&mut self, pool: InsertPool) {
+        self.pools.push(pool);
+    }
+
+    /// Index of the current pool called `name`.
+    fn find(&self, name: &str) -> Option<usize> {
+        self.pools.iter().rposition(|p| p.name.as_ref() == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.pools
+            .iter()
+            .enumerate()
+            .filter(|&(i, p)| self.find(&p.name) == Some(i))
+            .map(|(_, p)| p.name())
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    /// Pool the processor came from, and goes back to.
+    pool: usize,
+    name: Option<Arc<str>>,
+    processor: Option<Box<dyn InsertProcessor>>,
+}
+
+/// Fixed bank of insert slots.
+#[derive(Default)]
+pub struct InsertSlots {
+    slots: [Slot; INSERT_SLOTS],
+}
+
+impl InsertSlots {
+    /// Fills `slot` from a `/insertN/<name>` value. `off`/`none` or an
+    /// unregistered name empties it; the same name keeps the running
+    /// processor. Processors move between the slot and `registry`'s pools,
+    /// so this neither allocates nor frees.
+    pub fn assign(&mut self, slot: usize, name: &str, registry: &mut InsertRegistry) {
+        let Some(s) = self.slots.get_mut(slot) else {
+            return;
+        };
+        let pool = registry.find(name);
+        if s.name.is_some() && pool == Some(s.pool) {
+            return;
+        }
+        if let Some(processor) = s.processor.take() {
+            registry.pools[s.pool].free.push(processor);
+        }
+        *s = Slot::default();
+        // An effect with no processor left stays off
+        if let Some(i) = pool {
+            let pool = &mut registry.pools[i];
+            if let Some(mut processor) = pool.free.pop() {
+                processor.reset();
+                *s = Slot {
+                    pool: i,
+                    name: Some(Arc::clone(&pool.name)),
+                    processor: Some(processor),
+                };
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|s| s.processor.is_none())
+    }
+
+    /// Name of the effect in `slot`, if any.
+    pub fn name(&self, slot: usize) -> Option<&str> {
+        self.slots.get(slot)?.name.as_deref()
+    }
+
+    #[inline]
+    pub fn process(&mut self, frame: &mut [f32; CHANNELS]) {
+        for s in &mut self.slots {
+            if let Some(p) = s.processor.as_mut() {
+                p.process(frame);
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for p in self.slots.iter_mut().filter_map(|s| s.processor.as_mut()) {
+            p.reset();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    #[test]
+    fn insert_slots_process_voice_and_switch_by_name() {
+        struct Left;
+        struct LeftOnly;
+        impl InsertEffect for Left {
+            fn create(&self, _sr: f32) -> Box<dyn InsertProcessor> {
+                Box::new(LeftOnly)
+            }
+        }
+        impl InsertProcessor for LeftOnly {
+            fn process(&mut self, frame: &mut [f32; CHANNELS]) {
+                frame[1] = 0.0;
+            }
+        }
+
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.register_insert("left", Arc::new(Left));
+        engine.evaluate("/sound/sine/attack/0/insert1/left/orbitinsert2/left/insert2/nope");
+        assert_eq!(engine.voices[0].inserts.name(0), Some("left"));
+        assert_eq!(engine.voices[0].inserts.name(1), None);
+        assert_eq!(engine.orbits[0].inserts.name(1), Some("left"));
+
+        engine.dsp();
+        let frames = || engine.output.chunks(2);
+        assert!(frames().any(|f| f[0] != 0.0));
+        assert!(frames().all(|f| f[1] == 0.0));
+
+        engine.panic_now();
+        engine.evaluate("/sound/sine/attack/0/insert1/off");
+        assert!(engine.voices[0].inserts.is_empty());
+    }
+
+    #[test]
+    fn slots_take_processors_from_the_pool_and_give_them_back() {
+        struct Counted(Arc<std::sync::atomic::AtomicUsize>);
+        struct Pass;
+        impl InsertEffect for Counted {
+            fn create(&self, _sr: f32) -> Box<dyn InsertProcessor> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                Box::new(Pass)
+            }
+        }
+        impl InsertProcessor for Pass {
+            fn process(&mut self, _frame: &mut [f32; CHANNELS]) {}
+        }
+
+        let built = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = InsertRegistry::default();
+        registry.register(InsertPool::new("a", &Counted(built.clone()), 48000.0, 2));
+        let mut slots = InsertSlots::default();
+        slots.assign(0, "a", &mut registry);
+        slots.assign(1, "a", &mut registry);
+        assert_eq!(registry.pools[0].free.len(), 0);
+
+        // Out of processors: the slot stays off rather than building one
+        let mut more = InsertSlots::default();
+        more.assign(0, "a", &mut registry);
+        assert!(more.is_empty());
+
+        slots.assign(0, "off", &mut registry);
+        more.assign(0, "a", &mut registry);
+        assert_eq!(more.name(0), Some("a"));
+
+        // A newer registration serves later slots; the old pool takes its
+        // processors back
+        registry.register(InsertPool::new("a", &Counted(built.clone()), 48000.0, 1));
+        slots.assign(1, "a", &mut registry);
+        assert_eq!(registry.pools[0].free.len(), 1);
+        assert_eq!(registry.pools[1].free.len(), 0);
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["a"]);
+        assert_eq!(built.load(std::sync::atomic::Ordering::Relaxed), 3);
+    }
+}
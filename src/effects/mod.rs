@@ -1,39 +1,55 @@
+mod beatrepeat;
 mod chorus;
 mod coarse;
 mod comb;
 mod compressor;
 mod crush;
+mod decor;
 mod delay;
 mod distort;
 mod eq;
 mod feedback;
 mod flanger;
+mod fshift;
 mod haas;
+mod insert;
 mod ladder;
 mod lag;
+mod orbit_eq;
 mod phaser;
+mod resonator;
 mod reverb;
 mod smear;
 mod tilt;
+mod transient;
 mod vital_reverb;
 
+pub use beatrepeat::{BeatRepeat, RepeatParams};
 pub use chorus::Chorus;
 pub use coarse::Coarse;
 pub use comb::{Comb, CombParams};
-pub use compressor::{Compressor, CompressorParams};
+pub use compressor::{Compressor, CompressorParams, Dynamics, DynamicsParams};
 pub use crush::crush;
+pub use decor::Decorrelator;
 pub use delay::Delay;
 pub use distort::{distort, DcBlocker, Fold, Wrap};
 pub use eq::Eq;
 pub use feedback::{Feedback, FeedbackParams};
 pub use flanger::Flanger;
+pub use fshift::FreqShifter;
 pub use haas::Haas;
+pub use insert::{
+    InsertEffect, InsertPool, InsertProcessor, InsertRegistry, InsertSlots, INSERT_SLOTS,
+};
 pub use ladder::{LadderFilter, LadderMode};
 pub use lag::Lag;
+pub use orbit_eq::OrbitEq;
 pub use phaser::Phaser;
+pub use resonator::ResonatorBank;
 pub use reverb::{DattorroVerb, ReverbParams};
 pub use smear::Smear;
 pub use tilt::Tilt;
+pub use transient::TransientShaper;
 pub use vital_reverb::VitalVerb;
 
 use crate::types::{ModuleGroup, ModuleInfo, ParamInfo};
@@ -101,6 +117,14 @@ const INFO_TIMING: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: f32::MAX,
         },
+        ParamInfo {
+            name: "endtime",
+            aliases: &[],
+            description: "absolute engine time the gate closes at, in seconds",
+            default: "0.0",
+            min: 0.0,
+            max: f32::MAX,
+        },
     ],
 };
 
@@ -125,6 +149,38 @@ const INFO_VOICE: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 7.0,
         },
+        ParamInfo {
+            name: "id",
+            aliases: &[],
+            description: "named voice group for update/retrigger/release",
+            default: "none",
+            min: 0.0,
+            max: 0.0,
+        },
+        ParamInfo {
+            name: "orbit2",
+            aliases: &["orbit3"],
+            description: "secondary orbit fed with the voice (effects only)",
+            default: "0.0",
+            min: 0.0,
+            max: 7.0,
+        },
+        ParamInfo {
+            name: "orbit2send",
+            aliases: &["orbit3send"],
+            description: "level sent to the secondary orbit",
+            default: "0.0",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "cue",
+            aliases: &[],
+            description: "play on the cue orbit only (pre-listen)",
+            default: "0",
+            min: 0.0,
+            max: 0.0,
+        },
         ParamInfo {
             name: "reset",
             aliases: &[],
@@ -133,6 +189,14 @@ const INFO_VOICE: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 0.0,
         },
+        ParamInfo {
+            name: "modsync",
+            aliases: &[],
+            description: "restart the voice's modulations from phase zero",
+            default: "0",
+            min: 0.0,
+            max: 0.0,
+        },
     ],
 };
 
@@ -173,6 +237,30 @@ const INFO_GAIN: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 1.0,
         },
+        ParamInfo {
+            name: "panlaw",
+            aliases: &[],
+            description: "pan gain law (power, linear)",
+            default: "power",
+            min: 0.0,
+            max: 0.0,
+        },
+        ParamInfo {
+            name: "pan2d",
+            aliases: &[],
+            description: "azimuth in turns across all output pairs (0 front, 0.25 right, 0.5 back)",
+            default: "off",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "pan2dspread",
+            aliases: &[],
+            description: "spread of pan2d toward every speaker",
+            default: "0",
+            min: 0.0,
+            max: 1.0,
+        },
         ParamInfo {
             name: "width",
             aliases: &[],
@@ -189,6 +277,14 @@ const INFO_GAIN: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 30.0,
         },
+        ParamInfo {
+            name: "decor",
+            aliases: &[],
+            description: "mono-compatible allpass decorrelation (0 off, 1 widest)",
+            default: "0.0",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
@@ -317,6 +413,30 @@ const INFO_ENVELOPE: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 10.0,
         },
+        ParamInfo {
+            name: "attackcurve",
+            aliases: &[],
+            description: "attack curve (-1 log, 0 linear, 1 exp)",
+            default: "0.5",
+            min: -1.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "decaycurve",
+            aliases: &[],
+            description: "decay curve (-1 log, 0 linear, 1 exp)",
+            default: "0.5",
+            min: -1.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "releasecurve",
+            aliases: &[],
+            description: "release curve (-1 log, 0 linear, 1 exp)",
+            default: "0.5",
+            min: -1.0,
+            max: 1.0,
+        },
     ],
 };
 
@@ -352,6 +472,108 @@ const INFO_VIBRATO: ModuleInfo = ModuleInfo {
     ],
 };
 
+macro_rules! lfo_params {
+    ($($n:literal),+) => {
+        &[$(
+            ParamInfo {
+                name: concat!("lfo", $n, "rate"),
+                aliases: &[],
+                description: "LFO rate in Hz",
+                default: "1.0",
+                min: 0.0,
+                max: 100.0,
+            },
+            ParamInfo {
+                name: concat!("lfo", $n, "depth"),
+                aliases: &[],
+                description: "swing around the target, in its own units",
+                default: "0.0",
+                min: 0.0,
+                max: 0.0,
+            },
+            ParamInfo {
+                name: concat!("lfo", $n, "shape"),
+                aliases: &[],
+                description: "LFO waveform (sine, tri, saw, square, sh)",
+                default: "sine",
+                min: 0.0,
+                max: 0.0,
+            },
+            ParamInfo {
+                name: concat!("lfo", $n, "target"),
+                aliases: &[],
+                description: "param the LFO moves (off to disable)",
+                default: "off",
+                min: 0.0,
+                max: 0.0,
+            },
+            ParamInfo {
+                name: concat!("lfo", $n, "retrig"),
+                aliases: &[],
+                description: "restart on each note (0 = follow the engine clock)",
+                default: "1",
+                min: 0.0,
+                max: 1.0,
+            },
+        )+]
+    };
+}
+
+const INFO_LFO: ModuleInfo = ModuleInfo {
+    name: "lfo",
+    description: "Per-voice LFO bank, each LFO moving one param",
+    group: ModuleGroup::Synthesis,
+    params: lfo_params!(1, 2, 3, 4),
+};
+
+const INFO_EXPRESSION: ModuleInfo = ModuleInfo {
+    name: "expression",
+    description: "Per-voice pitch bend and pressure for MPE-style control",
+    group: ModuleGroup::Synthesis,
+    params: &[
+        ParamInfo {
+            name: "bend",
+            aliases: &[],
+            description: "pitch bend in semitones",
+            default: "0.0",
+            min: -48.0,
+            max: 48.0,
+        },
+        ParamInfo {
+            name: "pressure",
+            aliases: &[],
+            description: "pressure (aftertouch)",
+            default: "0.0",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "pressuretarget",
+            aliases: &[],
+            description: "param pressure moves (off to disable)",
+            default: "off",
+            min: 0.0,
+            max: 0.0,
+        },
+        ParamInfo {
+            name: "pressuredepth",
+            aliases: &[],
+            description: "offset at full pressure, in the target's units",
+            default: "0.0",
+            min: 0.0,
+            max: 0.0,
+        },
+        ParamInfo {
+            name: "exprslew",
+            aliases: &[],
+            description: "bend and pressure smoothing time in seconds",
+            default: "0.02",
+            min: 0.0,
+            max: 1.0,
+        },
+    ],
+};
+
 const INFO_FM: ModuleInfo = ModuleInfo {
     name: "fm",
     description: "Frequency modulation synthesis",
@@ -652,6 +874,8 @@ pub const ALL_MODULES: &[&ModuleInfo] = &[
     &INFO_OSCILLATOR,
     &INFO_ENVELOPE,
     &INFO_VIBRATO,
+    &INFO_LFO,
+    &INFO_EXPRESSION,
     &INFO_FM,
     &INFO_AM,
     &INFO_RM,
@@ -671,10 +895,14 @@ pub const ALL_MODULES: &[&ModuleInfo] = &[
     &flanger::INFO,
     &chorus::INFO,
     &smear::INFO,
+    &resonator::INFO,
+    &fshift::INFO,
     // Effects — distortion
     &coarse::INFO,
     &crush::INFO,
     &distort::INFO,
+    // Effects — dynamics
+    &transient::INFO,
     // Effects — EQ
     &eq::INFO,
     &tilt::INFO,
@@ -684,4 +912,6 @@ pub const ALL_MODULES: &[&ModuleInfo] = &[
     &comb::INFO,
     &feedback::INFO,
     &compressor::INFO,
+    &beatrepeat::INFO,
+    &orbit_eq::INFO,
 ];
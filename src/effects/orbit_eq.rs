@@ -0,0 +1,95 @@
+//! Tone shaping on the orbit bus: high/low cut and a 3-band EQ after the
+//! effect returns, so delay and reverb tails can be filtered with the dry mix.
+
+use super::eq::Eq;
+use crate::dsp::{SvfMode, SvfState};
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo, CHANNELS};
+
+pub const INFO: ModuleInfo = ModuleInfo {
+    name: "orbiteq",
+    description: "Orbit bus filters and 3-band EQ, after the effect returns",
+    group: ModuleGroup::Effect,
+    params: &[
+        ParamInfo {
+            name: "orbithpf",
+            aliases: &[],
+            description: "low-cut on the orbit bus in Hz (0 = off)",
+            default: "0",
+            min: 0.0,
+            max: 20000.0,
+        },
+        ParamInfo {
+            name: "orbitlpf",
+            aliases: &[],
+            description: "high-cut on the orbit bus in Hz (0 = off)",
+            default: "0",
+            min: 0.0,
+            max: 20000.0,
+        },
+        ParamInfo {
+            name: "orbiteqlo",
+            aliases: &[],
+            description: "orbit low shelf gain in dB (200 Hz)",
+            default: "0.0",
+            min: -24.0,
+            max: 24.0,
+        },
+        ParamInfo {
+            name: "orbiteqmid",
+            aliases: &[],
+            description: "orbit mid peak gain in dB (1 kHz)",
+            default: "0.0",
+            min: -24.0,
+            max: 24.0,
+        },
+        ParamInfo {
+            name: "orbiteqhi",
+            aliases: &[],
+            description: "orbit high shelf gain in dB (5 kHz)",
+            default: "0.0",
+            min: -24.0,
+            max: 24.0,
+        },
+    ],
+};
+
+const LO_FREQ: f32 = 200.0;
+const MID_FREQ: f32 = 1000.0;
+const HI_FREQ: f32 = 5000.0;
+
+/// Bus filters and EQ. Cutoffs of 0 and gains of 0 dB are bypassed.
+#[derive(Clone, Copy, Default)]
+pub struct OrbitEq {
+    pub hpf: f32,
+    pub lpf: f32,
+    pub lo: f32,
+    pub mid: f32,
+    pub hi: f32,
+    hp: [SvfState; CHANNELS],
+    lp: [SvfState; CHANNELS],
+    eq: [Eq; CHANNELS],
+}
+
+impl OrbitEq {
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.hpf > 0.0 || self.lpf > 0.0 || self.lo != 0.0 || self.mid != 0.0 || self.hi != 0.0
+    }
+
+    #[inline]
+    pub fn process(&mut self, bus: &mut [f32; CHANNELS], sr: f32) {
+        for (c, x) in bus.iter_mut().enumerate() {
+            if self.hpf > 0.0 {
+                self.hp[c].cutoff = self.hpf;
+                *x = self.hp[c].process(*x, SvfMode::Hp, 0.0, sr);
+            }
+            if self.lpf > 0.0 {
+                self.lp[c].cutoff = self.lpf;
+                *x = self.lp[c].process(*x, SvfMode::Lp, 0.0, sr);
+            }
+            *x = self.eq[c].process(
+                *x, self.lo, self.mid, self.hi, LO_FREQ, MID_FREQ, HI_FREQ, sr,
+            );
+        }
+    }
+}
@@ -43,6 +43,14 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 20000.0,
         },
+        ParamInfo {
+            name: "phasermix",
+            aliases: &[],
+            description: "equal-power dry/wet (1 = phaser only)",
+            default: "1.0",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
@@ -0,0 +1,153 @@
+use crate::dsp::ftz;
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo, CHANNELS};
+use std::f32::consts::PI;
+
+pub const INFO: ModuleInfo = ModuleInfo {
+    name: "reso",
+    description: "modal resonator bank tuned to the voice pitch",
+    group: ModuleGroup::Effect,
+    params: &[
+        ParamInfo {
+            name: "reso",
+            aliases: &[],
+            description: "equal-power dry/wet (0 = bypass, 1 = resonator only)",
+            default: "0.0",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "resostruct",
+            aliases: &[],
+            description: "partial spread (0 = harmonic string, 1 = stiff, bell-like)",
+            default: "0.25",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "resobright",
+            aliases: &[],
+            description: "level of the upper modes",
+            default: "0.5",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "resodamp",
+            aliases: &[],
+            description: "decay (0 = rings ~8 s, 1 = ~50 ms)",
+            default: "0.5",
+            min: 0.0,
+            max: 1.0,
+        },
+    ],
+};
+
+const MODES: usize = 16;
+/// Inharmonicity coefficient at `resostruct = 1` (stiff-string model).
+const MAX_STIFFNESS: f32 = 0.03;
+const MAX_DECAY_SECS: f32 = 8.0;
+const MIN_DECAY_SECS: f32 = 0.05;
+
+#[derive(Clone, Copy, Default)]
+struct Mode {
+    a1: f32,
+    a2: f32,
+    b0: f32,
+    gain: f32,
+}
+
+#[derive(Clone, Copy, Default)]
+struct ModeState {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+/// Bank of two-pole resonators at stiff-string partial ratios, in the spirit
+/// of the Rings modal model. Any signal excites it; the voice pitch tunes it.
+#[derive(Clone, Copy)]
+pub struct ResonatorBank {
+    modes: [Mode; MODES],
+    active: usize,
+    norm: f32,
+    state: [[ModeState; MODES]; CHANNELS],
+    cached: [f32; 5],
+}
+
+impl Default for ResonatorBank {
+    fn default() -> Self {
+        Self {
+            modes: [Mode::default(); MODES],
+            active: 0,
+            norm: 0.0,
+            state: [[ModeState::default(); MODES]; CHANNELS],
+            cached: [f32::NAN; 5],
+        }
+    }
+}
+
+impl ResonatorBank {
+    /// Retunes the modes; cheap when nothing changed since the last call.
+    pub fn update(&mut self, freq: f32, structure: f32, bright: f32, damp: f32, sr: f32) {
+        let key = [freq, structure, bright, damp, sr];
+        if key == self.cached {
+            return;
+        }
+        self.cached = key;
+
+        let structure = structure.clamp(0.0, 1.0);
+        let bright = bright.clamp(0.0, 1.0);
+        let damp = damp.clamp(0.0, 1.0);
+        let stiffness = structure * structure * MAX_STIFFNESS;
+        let decay = MAX_DECAY_SECS * (MIN_DECAY_SECS / MAX_DECAY_SECS).powf(damp);
+        // Upper modes roll off faster and decay sooner as brightness drops
+        let tilt = 2.0 * (1.0 - bright);
+
+        self.active = 0;
+        let mut power = 0.0;
+        for k in 0..MODES {
+            let n = (k + 1) as f32;
+            let f = freq.abs() * n * (1.0 + stiffness * n * n).sqrt();
+            if f >= sr * 0.45 {
+                break;
+            }
+            let t60 = decay / (1.0 + (n - 1.0) * tilt * 0.25);
+            let r = 10f32.powf(-3.0 / (t60 * sr));
+            let w = 2.0 * PI * f / sr;
+            let gain = n.powf(-tilt);
+            self.modes[k] = Mode {
+                a1: 2.0 * r * w.cos(),
+                a2: -r * r,
+                b0: (1.0 - r * r) * 0.5,
+                gain,
+            };
+            power += gain * gain;
+            self.active = k + 1;
+        }
+        self.norm = if power > 0.0 {
+            power.sqrt().recip()
+        } else {
+            0.0
+        };
+    }
+
+    /// Runs one sample of channel `c` through the bank.
+    #[inline]
+    pub fn process(&mut self, input: f32, c: usize) -> f32 {
+        let mut out = 0.0;
+        for (m, s) in self.modes[..self.active]
+            .iter()
+            .zip(&mut self.state[c][..self.active])
+        {
+            // Constant-peak-gain resonator: zeros at DC and Nyquist
+            let y = m.b0 * (input - s.x2) + m.a1 * s.y1 + m.a2 * s.y2;
+            s.x2 = s.x1;
+            s.x1 = input;
+            s.y2 = s.y1;
+            s.y1 = ftz(y, 1e-15);
+            out += y * m.gain;
+        }
+        out * self.norm
+    }
+}
@@ -1,5 +1,5 @@
 use crate::dsp::ftz;
-use crate::types::{ModuleGroup, ModuleInfo, ParamInfo, ReverbType};
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo, ReverbQuality, ReverbType};
 
 #[derive(Clone, Copy)]
 pub struct ReverbParams {
@@ -16,6 +16,9 @@ pub struct ReverbParams {
     pub lowgain: f32,
     pub chorus: f32,
     pub chorus_freq: f32,
+    /// 0..1: closes the input and takes the tank to unity gain without
+    /// damping, so the tail holds. The orbit ramps it toward `verbfreeze`.
+    pub freeze: f32,
 }
 
 impl Default for ReverbParams {
@@ -34,6 +37,7 @@ impl Default for ReverbParams {
             lowgain: 0.1,
             chorus: 0.3,
             chorus_freq: 0.65,
+            freeze: 0.0,
         }
     }
 }
@@ -155,10 +159,54 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 1.0,
         },
+        ParamInfo {
+            name: "verbfreeze",
+            aliases: &[],
+            description: "holds the tail and shuts out new input (0..1)",
+            default: "0",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "verbreturn",
+            aliases: &[],
+            description: "level of the reverb return in the orbit mix",
+            default: "1",
+            min: 0.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "verbsendlpf",
+            aliases: &[],
+            description: "high-cut on the reverb send input in Hz (0 = off)",
+            default: "0",
+            min: 0.0,
+            max: 20000.0,
+        },
+        ParamInfo {
+            name: "verbsendhpf",
+            aliases: &[],
+            description: "low-cut on the reverb send input in Hz (0 = off)",
+            default: "0",
+            min: 0.0,
+            max: 20000.0,
+        },
     ],
 };
 
 const REVERB_SR_REF: f32 = 29761.0;
+/// Highest tank rate [`ReverbQuality::Auto`] runs at.
+const TANK_SR_MAX: f32 = 48000.0;
+
+/// Engine samples per tank sample.
+fn decimation(sr: f32, quality: ReverbQuality) -> usize {
+    let auto = ((sr / TANK_SR_MAX).ceil() as usize).max(1);
+    match quality {
+        ReverbQuality::High => 1,
+        ReverbQuality::Auto => auto,
+        ReverbQuality::Low => auto * 2,
+    }
+}
 
 fn scale_delay(samples: usize, sr: f32) -> usize {
     ((samples as f32 * sr / REVERB_SR_REF) as usize).max(1)
@@ -208,8 +256,18 @@ impl ReverbBuffer {
     }
 }
 
+/// Dattorro plate. The tank runs at the engine rate divided by the
+/// [`ReverbQuality`] decimation: input is averaged over each tank sample
+/// and the output ramps between tank samples.
 #[derive(Clone)]
 pub struct DattorroVerb {
+    sr: f32,
+    quality: ReverbQuality,
+    div: usize,
+    phase: usize,
+    acc: f32,
+    prev: [f32; 2],
+    next: [f32; 2],
     pre_delay: ReverbBuffer,
     in_diff1: ReverbBuffer,
     in_diff2: ReverbBuffer,
@@ -256,6 +314,12 @@ pub struct DattorroVerb {
 
 impl DattorroVerb {
     pub fn new(sr: f32) -> Self {
+        Self::with_quality(sr, ReverbQuality::default())
+    }
+
+    pub fn with_quality(engine_sr: f32, quality: ReverbQuality) -> Self {
+        let div = decimation(engine_sr, quality);
+        let sr = engine_sr / div as f32;
         let pre_delay_len = scale_delay(4800, sr);
         let in_diff1_len = scale_delay(142, sr);
         let in_diff2_len = scale_delay(107, sr);
@@ -271,6 +335,13 @@ impl DattorroVerb {
         let delay2_r_len = scale_delay(3163, sr);
 
         Self {
+            sr: engine_sr,
+            quality,
+            div,
+            phase: 0,
+            acc: 0.0,
+            prev: [0.0; 2],
+            next: [0.0; 2],
             pre_delay: ReverbBuffer::new(pre_delay_len + 1),
             in_diff1: ReverbBuffer::new(in_diff1_len + 1),
             in_diff2: ReverbBuffer::new(in_diff2_len + 1),
@@ -316,9 +387,43 @@ impl DattorroVerb {
         }
     }
 
+    pub fn quality(&self) -> ReverbQuality {
+        self.quality
+    }
+
+    /// Rebuilds the tank for a new engine rate or quality, dropping the
+    /// tail. Keeps it when neither changes the tank rate; a rebuild
+    /// allocates the new tank's delay lines.
+    pub fn reconfigure(&mut self, sr: f32, quality: ReverbQuality) {
+        if sr != self.sr || decimation(sr, quality) != self.div {
+            *self = Self::with_quality(sr, quality);
+        }
+        self.quality = quality;
+    }
+
     pub fn process(&mut self, input: f32, p: &ReverbParams) -> [f32; 2] {
+        self.acc += input;
+        self.phase += 1;
+        if self.phase == self.div {
+            self.phase = 0;
+            self.prev = self.next;
+            self.next = self.tick(self.acc / self.div as f32, p);
+            self.acc = 0.0;
+        }
+        // Ramps to the newest tank sample over the engine samples it spans
+        let t = (self.phase + 1) as f32 / self.div as f32;
+        [
+            self.prev[0] + (self.next[0] - self.prev[0]) * t,
+            self.prev[1] + (self.next[1] - self.prev[1]) * t,
+        ]
+    }
+
+    /// One tank sample.
+    fn tick(&mut self, input: f32, p: &ReverbParams) -> [f32; 2] {
+        let freeze = p.freeze;
         let decay = p.decay.clamp(0.0, 0.99);
-        let damping = p.damp.clamp(0.0, 1.0);
+        let decay = decay + (1.0 - decay) * freeze;
+        let damping = p.damp.clamp(0.0, 1.0) * (1.0 - freeze);
         let diffusion = p.diff.clamp(0.0, 1.0);
         let diff1 = 0.75 * diffusion;
         let diff2 = 0.625 * diffusion;
@@ -327,7 +432,7 @@ impl DattorroVerb {
 
         let pre_delay_samples =
             ((p.predelay * self.pre_delay_len as f32) as usize).min(self.pre_delay_len);
-        let input = ftz(input, 0.0001);
+        let input = ftz(input * (1.0 - freeze), 0.0001);
         let pre = self.pre_delay.read_write(input, pre_delay_samples);
 
         let mut x = pre;
@@ -394,5 +499,71 @@ impl DattorroVerb {
         self.delay2_r.clear();
         self.damp_l = 0.0;
         self.damp_r = 0.0;
+        self.phase = 0;
+        self.acc = 0.0;
+        self.prev = [0.0; 2];
+        self.next = [0.0; 2];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RMS of the tail after a short 440 Hz burst, over the same stretch of
+    /// time at any rate.
+    fn tail_rms(verb: &mut DattorroVerb, sr: f32) -> f32 {
+        let p = ReverbParams::default();
+        let burst = (0.05 * sr) as usize;
+        let len = (0.5 * sr) as usize;
+        let mut sum = 0.0;
+        for i in 0..len {
+            let x = if i < burst {
+                (std::f32::consts::TAU * 440.0 * i as f32 / sr).sin()
+            } else {
+                0.0
+            };
+            let [l, r] = verb.process(x, &p);
+            if i >= burst {
+                sum += l * l + r * r;
+            }
+        }
+        (sum / (len - burst) as f32).sqrt()
+    }
+
+    #[test]
+    fn auto_quality_caps_the_tank_rate_at_high_sample_rates() {
+        let high = DattorroVerb::with_quality(96000.0, ReverbQuality::High);
+        let mut auto = DattorroVerb::with_quality(96000.0, ReverbQuality::Auto);
+        let low = DattorroVerb::with_quality(96000.0, ReverbQuality::Low);
+        assert_eq!((high.div, auto.div, low.div), (1, 2, 4));
+        let len = |v: &DattorroVerb| v.delay1_l.buffer.len();
+        assert_eq!(len(&auto) * 2, len(&high));
+        assert_eq!(len(&low) * 2, len(&auto));
+        // 44.1 and 48 kHz keep the full rate
+        assert_eq!(DattorroVerb::new(48000.0).div, 1);
+
+        // Same tail as the plate at 48 kHz
+        let reference = tail_rms(&mut DattorroVerb::new(48000.0), 48000.0);
+        let decimated = tail_rms(&mut auto, 96000.0);
+        let db = 20.0 * (decimated / reference).log10();
+        assert!(db.abs() < 1.0, "{db} dB off the 48 kHz tail");
+    }
+
+    #[test]
+    fn reconfigure_rebuilds_only_when_the_tank_rate_changes() {
+        let p = ReverbParams::default();
+        let mut verb = DattorroVerb::new(48000.0);
+        verb.process(1.0, &p);
+        let ring = |verb: &mut DattorroVerb| (0..24000).any(|_| verb.process(0.0, &p)[0] != 0.0);
+
+        verb.reconfigure(48000.0, ReverbQuality::High);
+        assert_eq!(verb.quality(), ReverbQuality::High);
+        assert!(ring(&mut verb), "same tank rate keeps the tail");
+
+        verb.reconfigure(96000.0, ReverbQuality::High);
+        assert_eq!(verb.div, 1);
+        assert_eq!(verb.sr, 96000.0);
+        assert!(!ring(&mut verb), "a new rate starts from silence");
     }
 }
@@ -31,6 +31,14 @@ pub const INFO: ModuleInfo = ModuleInfo {
             min: 0.0,
             max: 0.95,
         },
+        ParamInfo {
+            name: "smearmix",
+            aliases: &[],
+            description: "equal-power dry/wet (1 = smear only)",
+            default: "1.0",
+            min: 0.0,
+            max: 1.0,
+        },
     ],
 };
 
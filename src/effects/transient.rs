@@ -0,0 +1,127 @@
+//! Transient shaper: boosts or softens a sound's attacks and tails
+//! independently of how loud it is.
+//!
+//! Three peak followers track the stereo-linked level: a fast one, one that
+//! rises slowly and one that falls slowly. While the fast follower leads the
+//! slow-rising one the sound is in an attack; while the slow-falling one
+//! stays above the fast one it is in its tail. The gain follows the ratios
+//! between them, so a quiet hit is shaped like a loud one.
+
+use crate::dsp::{exp2f, log2f};
+use crate::types::{ModuleGroup, ModuleInfo, ParamInfo};
+
+pub const INFO: ModuleInfo = ModuleInfo {
+    name: "transient",
+    description: "Transient shaper",
+    group: ModuleGroup::Effect,
+    params: &[
+        ParamInfo {
+            name: "transattack",
+            aliases: &[],
+            description: "attack boost (1) or softening (-1)",
+            default: "0.0",
+            min: -1.0,
+            max: 1.0,
+        },
+        ParamInfo {
+            name: "transsustain",
+            aliases: &[],
+            description: "tail boost (1) or tightening (-1)",
+            default: "0.0",
+            min: -1.0,
+            max: 1.0,
+        },
+    ],
+};
+
+const FAST_ATTACK: f32 = 0.0005;
+const FAST_RELEASE: f32 = 0.05;
+/// How long the attack boost lasts.
+const SLOW_ATTACK: f32 = 0.03;
+/// How long a tail is held up against.
+const HOLD_RELEASE: f32 = 0.4;
+/// Largest boost or cut in octaves of amplitude (18 dB).
+const MAX_OCTAVES: f32 = 3.0;
+const FLOOR: f32 = 1e-5;
+
+#[derive(Clone, Copy, Default)]
+pub struct TransientShaper {
+    fast: f32,
+    slow: f32,
+    hold: f32,
+}
+
+#[inline]
+fn follow(env: &mut f32, level: f32, attack: f32, release: f32, isr: f32) {
+    let time = if level > *env { attack } else { release };
+    *env += (isr / time).min(1.0) * (level - *env);
+}
+
+impl TransientShaper {
+    /// Shapes a frame of one or more channels. `attack` and `sustain` go from -1 (soften) to 1
+    /// (boost); 0 leaves that part alone.
+    #[inline]
+    pub fn process(&mut self, frame: &mut [f32], attack: f32, sustain: f32, isr: f32) {
+        let level = frame.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        follow(&mut self.fast, level, FAST_ATTACK, FAST_RELEASE, isr);
+        follow(&mut self.slow, level, SLOW_ATTACK, FAST_RELEASE, isr);
+        follow(&mut self.hold, level, FAST_ATTACK, HOLD_RELEASE, isr);
+        let fast = self.fast.max(FLOOR);
+        // Octaves the attack rises over the body, and the tail lingers
+        // over the sound
+        let rise = log2f(fast / self.slow.max(FLOOR)).max(0.0);
+        let tail = log2f(self.hold.max(FLOOR) / fast).max(0.0);
+        let octaves = attack.clamp(-1.0, 1.0) * rise + sustain.clamp(-1.0, 1.0) * tail;
+        let gain = exp2f(octaves.clamp(-MAX_OCTAVES, MAX_OCTAVES));
+        for s in frame.iter_mut() {
+            *s *= gain;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Peak of the first 5 ms and RMS of 100-300 ms of a decaying hit.
+    fn shape(attack: f32, sustain: f32, gain: f32) -> (f32, f32) {
+        let sr = 48000.0;
+        let mut shaper = TransientShaper::default();
+        let mut peak = 0.0f32;
+        let mut tail = 0.0f32;
+        for i in 0..(sr * 0.3) as usize {
+            let t = i as f32 / sr;
+            let x = gain * (-t * 12.0).exp() * (t * 2000.0).sin();
+            let mut frame = [x, x];
+            shaper.process(&mut frame, attack, sustain, 1.0 / sr);
+            if t < 0.005 {
+                peak = peak.max(frame[0].abs());
+            } else if t > 0.1 {
+                tail += frame[0] * frame[0];
+            }
+        }
+        (peak, tail.sqrt())
+    }
+
+    #[test]
+    fn shapes_attacks_and_tails_at_any_level() {
+        let (peak, tail) = shape(0.0, 0.0, 1.0);
+        let (punchy, _) = shape(1.0, 0.0, 1.0);
+        let (soft, _) = shape(-1.0, 0.0, 1.0);
+        assert!(punchy > peak * 1.5, "{punchy} vs {peak}");
+        assert!(soft < peak * 0.7, "{soft} vs {peak}");
+
+        let (_, tight) = shape(0.0, -1.0, 1.0);
+        let (_, long) = shape(0.0, 1.0, 1.0);
+        assert!(tight < tail * 0.5, "{tight} vs {tail}");
+        assert!(long > tail * 1.5, "{long} vs {tail}");
+
+        // 20 dB quieter, shaped the same way
+        let (quiet_punchy, _) = shape(1.0, 0.0, 0.1);
+        assert!((quiet_punchy * 10.0 / punchy - 1.0).abs() < 0.05);
+    }
+}
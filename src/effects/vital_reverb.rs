@@ -289,8 +289,10 @@ impl VitalVerb {
             }
         }
 
-        let high_gain_linear = c.high_gain_linear;
-        let low_gain_linear = c.low_gain_linear;
+        // Freezing flattens the shelves and lifts every line to unity
+        let freeze = rp.freeze;
+        let high_gain_linear = c.high_gain_linear + (1.0 - c.high_gain_linear) * freeze;
+        let low_gain_linear = c.low_gain_linear + (1.0 - c.low_gain_linear) * freeze;
         let size_mult = c.size_mult;
         let prelow_coeff = c.prelow_coeff;
         let prehigh_coeff = c.prehigh_coeff;
@@ -310,7 +312,8 @@ impl VitalVerb {
         // --- Step 1: Write input to predelay, read back ---
         let mut predelayed = [0.0; 2];
         for channel in 0..2 {
-            self.predelay_buf[channel][wp & self.predelay_mask] = ftz(input[channel], 1e-18);
+            self.predelay_buf[channel][wp & self.predelay_mask] =
+                ftz(input[channel] * (1.0 - freeze), 1e-18);
             predelayed[channel] = lagrange_read(
                 &self.predelay_buf[channel],
                 self.predelay_mask,
@@ -409,7 +412,8 @@ impl VitalVerb {
 
         // --- Step 8: Per-line T60 decay (cached coefficients) ---
         for (line, mo) in matrix_out.iter_mut().enumerate() {
-            *mo *= self.cached.decay_coeffs[line];
+            let coeff = self.cached.decay_coeffs[line];
+            *mo *= coeff + (1.0 - coeff) * freeze;
         }
 
         // --- Step 9: Advance LFOs ---
@@ -1,25 +1,351 @@
-use crate::types::{midi2freq, DelayType, LfoShape, ReverbType, SubWave, SyncMode};
+use crate::effects::INSERT_SLOTS;
+use crate::master::MASTER_EQ_BANDS;
+use crate::orbit::OrbitParam;
+#[cfg(feature = "native")]
+use crate::sampling::SampleData;
+use crate::tuning::Tuning;
+use crate::types::{
+    midi2freq, DelayType, LfoShape, OutMode, PanLaw, PluckExcite, ReverbType, SliceMode, SubWave,
+    SyncMode, ORBIT_SENDS, VOICE_LFOS,
+};
 use crate::voice::{ModChain, ParamId};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
 
-#[derive(Clone, Default, Debug)]
+/// A key or value that [`Event::parse`] had to ignore.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// The key is not a known parameter (`lfp/800`).
+    UnknownKey(String),
+    /// The key is known but its value does not parse (`lpf/8k`).
+    BadValue { key: String, value: String },
+}
+
+impl ParseWarning {
+    /// The offending token, as typed.
+    pub fn token(&self) -> &str {
+        match self {
+            Self::UnknownKey(key) => key,
+            Self::BadValue { value, .. } => value,
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownKey(key) => write!(f, "unknown key `{key}`"),
+            Self::BadValue { key, value } => write!(f, "bad value `{value}` for `{key}`"),
+        }
+    }
+}
+
+/// Splits an event path into its `/`-separated tokens.
+///
+/// Whitespace around tokens and empty tokens are ignored. A token in double
+/// quotes is taken as is, `/` included (`"kits/808/bd.wav"`), and `\`
+/// escapes the next character anywhere (`\/`, `\"`, `\\`). Tokens without
+/// escapes borrow from the input.
+#[derive(Clone)]
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { rest: input }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Cow<'a, str>> {
+        let s = self
+            .rest
+            .trim_start_matches(|c: char| c == '/' || c.is_whitespace());
+        if s.is_empty() {
+            self.rest = s;
+            return None;
+        }
+        if let Some(quoted) = s.strip_prefix('"') {
+            let (token, after) = read_until(quoted, '"');
+            // Anything between the closing quote and the next `/` is dropped
+            self.rest = after.find('/').map_or("", |i| &after[i..]);
+            return Some(token);
+        }
+        let (token, after) = read_until(s, '/');
+        self.rest = after;
+        Some(match token {
+            Cow::Borrowed(t) => Cow::Borrowed(t.trim_end()),
+            Cow::Owned(t) => Cow::Owned(t.trim_end().to_string()),
+        })
+    }
+}
+
+/// Reads `s` up to the first unescaped `stop`, resolving `\` escapes.
+/// Returns the token and the rest of `s` from `stop` on.
+fn read_until(s: &str, stop: char) -> (Cow<'_, str>, &str) {
+    let mut owned: Option<String> = None;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == stop {
+            return (owned.map_or(Cow::Borrowed(&s[..i]), Cow::Owned), &s[i..]);
+        }
+        if c == '\\' {
+            let buf = owned.get_or_insert_with(|| s[..i].to_string());
+            buf.push(chars.next().map_or('\\', |(_, next)| next));
+        } else if let Some(buf) = &mut owned {
+            buf.push(c);
+        }
+    }
+    (owned.map_or(Cow::Borrowed(s), Cow::Owned), "")
+}
+
+/// Splits `input` into events at each `;` outside quotes, skipping empty
+/// ones: `/s/kick; /s/hat/delta/12000` holds two events.
+pub fn split_events(input: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(input);
+    std::iter::from_fn(move || loop {
+        let s = rest?;
+        let mut quoted = false;
+        let mut chars = s.char_indices();
+        let mut end = None;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => quoted = !quoted,
+                ';' if !quoted => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let event = match end {
+            Some(i) => {
+                rest = Some(&s[i + 1..]);
+                &s[..i]
+            }
+            None => {
+                rest = None;
+                s
+            }
+        };
+        if !event.trim().is_empty() {
+            return Some(event.trim());
+        }
+    })
+}
+
+/// A length or time in beats: a number with a `b` suffix (`2b`, `0.5b`),
+/// or `bd` / `bt` for its dotted (x1.5) and triplet (x2/3) value.
+fn beats(val: &str) -> Option<f64> {
+    let (num, scale) = if let Some(num) = val.strip_suffix("bd") {
+        (num, 1.5)
+    } else if let Some(num) = val.strip_suffix("bt") {
+        (num, 2.0 / 3.0)
+    } else {
+        (val.strip_suffix('b')?, 1.0)
+    };
+    num.parse::<f64>().ok().map(|b| b * scale)
+}
+
+/// The tokens of one event, paired up as keys and values. A trailing key
+/// without a value is dropped.
+pub fn pairs(input: &str) -> impl Iterator<Item = (Cow<'_, str>, Cow<'_, str>)> {
+    let mut tokens = Tokens::new(input);
+    std::iter::from_fn(move || Some((tokens.next()?, tokens.next()?)))
+}
+
+/// Appends `token` to `out`, escaping what would end it early.
+pub fn escape_token(token: &str, out: &mut String) {
+    for c in token.chars() {
+        if matches!(c, '/' | ';' | '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Replaces each `$name` in `input` with its value in `vars`, leaving
+/// unknown names as typed. A token that held a variable and now reads as
+/// arithmetic over numbers (`220*2`, `60+7`) is replaced by its result, so
+/// `/freq/$base*2` plays an octave above `base`.
+pub fn substitute<'a>(input: &'a str, vars: &[(String, String)]) -> Cow<'a, str> {
+    if !input.contains('$') {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len());
+    for piece in input.split_inclusive(['/', ';']) {
+        let (token, sep) = match piece.strip_suffix(['/', ';']) {
+            Some(token) => (token, &piece[token.len()..]),
+            None => (piece, ""),
+        };
+        if !token.contains('$') {
+            out.push_str(piece);
+            continue;
+        }
+        let mut expanded = String::new();
+        let mut rest = token;
+        while let Some(i) = rest.find('$') {
+            expanded.push_str(&rest[..i]);
+            let after = &rest[i + 1..];
+            let len = after
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(after.len());
+            let name = &after[..len];
+            match vars.iter().find(|(n, _)| n == name) {
+                Some((_, value)) if !name.is_empty() => expanded.push_str(value),
+                _ => {
+                    expanded.push('$');
+                    expanded.push_str(name);
+                }
+            }
+            rest = &after[len..];
+        }
+        expanded.push_str(rest);
+        match arithmetic(expanded.trim()) {
+            Some(value) => out.push_str(&value.to_string()),
+            None => out.push_str(&expanded),
+        }
+        out.push_str(sep);
+    }
+    Cow::Owned(out)
+}
+
+/// Sums `*`-products of plain numbers: `3+4*2` is 11. A `+` or `-` right
+/// after a number is an operator; anywhere else it is the number's sign.
+fn arithmetic(s: &str) -> Option<f64> {
+    let product =
+        |s: &str| -> Option<f64> { s.split('*').map(|f| f.parse::<f64>().ok()).product() };
+    let mut total = 0.0;
+    let mut sign = 1.0;
+    let mut start = 0;
+    let mut prev = None;
+    for (i, c) in s.char_indices() {
+        if matches!(c, '+' | '-') && prev.is_some_and(|p: char| p.is_ascii_digit() || p == '.') {
+            total += sign * product(&s[start..i])?;
+            sign = if c == '-' { -1.0 } else { 1.0 };
+            start = i + 1;
+        }
+        prev = Some(c);
+    }
+    let total = total + sign * product(&s[start..])?;
+    total.is_finite().then_some(total)
+}
+
+/// Appends `value` to an event path as a single token, quoting it when it
+/// would otherwise be split or trimmed.
+pub fn push_token(path: &mut String, value: &str) {
+    let plain =
+        !value.is_empty() && !value.contains(['/', '"', '\\', ';']) && value.trim() == value;
+    if plain {
+        path.push_str(value);
+        return;
+    }
+    path.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            path.push('\\');
+        }
+        path.push(c);
+    }
+    path.push('"');
+}
+
+/// Settings for one voice LFO carried by an event (`lfo1rate`, ...).
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct LfoEvent {
+    pub rate: Option<f32>,
+    pub depth: Option<f32>,
+    pub shape: Option<LfoShape>,
+    /// `Some(None)` switches the LFO off (`lfo1target/off`).
+    pub target: Option<Option<ParamId>>,
+    pub retrig: Option<bool>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Event {
     pub cmd: Option<String>,
+    /// Token echoed back in an `EventAck` once the event is processed.
+    pub ack: Option<u64>,
 
     // Timing (sample-accurate)
     pub tick: Option<u64>,
+    /// Start in beats on the engine's tempo clock (`time/2b`). Scheduled
+    /// events keep it, so their `tick` follows tempo changes.
+    pub beat: Option<f64>,
     pub delta: Option<i64>,
     pub gate: Option<f32>,
+    /// `gate` in beats (`gate/0.5b`), turned into seconds at the tempo.
+    pub gatebeats: Option<f32>,
+    /// Absolute tick at which the gate closes (`endtime` / `endtick`).
+    pub endtick: Option<u64>,
+    // Ratchet: split the gate into N retriggers
+    pub ratchet: Option<usize>,
+    pub ratchetdecay: Option<f32>,
+    pub ratchetstep: Option<f32>,
+    /// Spacing of ratchet hits in seconds (`rep`), setting their number
+    /// from the gate instead of `ratchet`.
+    pub rep: Option<f32>,
+    /// `rep` in beats (`rep/1b`).
+    pub repbeats: Option<f32>,
+    /// Clock divider and multiplier on `rep` (`rep/1b/div/3`). Without a
+    /// `rep` they divide or multiply one beat.
+    pub div: Option<f32>,
+    pub mult: Option<f32>,
+    /// Random timing offset per ratchet hit, in seconds either way.
+    pub repjitter: Option<f32>,
+    /// Share of each pair of ratchet steps taken by the first (`swing`),
+    /// 0.5 for straight.
+    pub swing: Option<f32>,
+    /// Ratchet hits per swung step (`swinggrid`).
+    pub swinggrid: Option<usize>,
+    /// Gap before the main hit at which a quieter grace hit plays, in seconds.
+    pub flam: Option<f32>,
+    /// Play even when the scheduler reaches it past the catch-up window,
+    /// where other late events are dropped.
+    pub late_ok: Option<bool>,
 
     // Voice control
     pub voice: Option<usize>,
+    pub vid: Option<u32>,
+    /// Tempo set by `/doux/bpm`.
+    pub bpm: Option<f32>,
+    /// Random seed for the voice's noise and random modulation. On
+    /// `/doux/seed`, reseeds the engine's per-voice seeds instead.
+    pub seed: Option<u32>,
+    /// Named voice group (`id/bassline`): tags new voices, and selects the
+    /// voices that `release`, `update` and `retrigger` act on.
+    pub id: Option<String>,
     pub reset: Option<bool>,
+    pub modsync: Option<bool>,
+    /// Flip a running voice back to its params from before the last update.
+    pub revert: Option<bool>,
     pub orbit: Option<usize>,
+    pub cue: Option<bool>,
 
     // Inline parameter modulation
     pub mods: Vec<(ParamId, ModChain)>,
+    /// Orbit automation lanes (`/doux/automate`, `/doux/orbit` and chains
+    /// on a voice event's orbit params); `None` stops the lane.
+    pub orbit_mods: Vec<(OrbitParam, Option<ModChain>)>,
 
     // Pitch
     pub freq: Option<f32>,
+    /// `note` as given, for playing it in a `tuning` instead of 12-TET.
+    pub note: Option<f32>,
+    /// `note` modulation as given, in note numbers.
+    pub note_mod: Option<ModChain>,
+    /// Registered tuning that `note` maps through.
+    pub tuning: Option<String>,
+    /// Preset whose pairs the event plays under its own.
+    pub preset: Option<String>,
     pub detune: Option<f32>,
     pub speed: Option<f32>,
     // Time stretch
@@ -41,10 +367,17 @@ pub struct Event {
     pub partials: Option<f32>,
     pub n: Option<String>,
     pub cut: Option<usize>,
+    /// Fade time, in seconds, of voices replaced by a cut group or restart.
+    pub cutfade: Option<f32>,
     pub begin: Option<f32>,
     pub end: Option<f32>,
     pub slice: Option<f32>,
     pub pick: Option<f32>,
+    pub slicemode: Option<SliceMode>,
+    pub sampleloop: Option<bool>,
+    pub loopbegin: Option<f32>,
+    pub loopend: Option<f32>,
+    pub loopxfade: Option<f32>,
     pub bank: Option<String>,
     pub wave: Option<f32>,
     pub sub: Option<f32>,
@@ -53,6 +386,9 @@ pub struct Event {
     pub sync_ratio: Option<f32>,
     pub sync_phase: Option<f32>,
     pub sync_mode: Option<SyncMode>,
+    pub pluckdamp: Option<f32>,
+    pub pluckdecay: Option<f32>,
+    pub pluck_excite: Option<PluckExcite>,
     pub scan: Option<f32>,
     pub wtlen: Option<u32>,
     // Web sample (WASM only - set by JavaScript)
@@ -66,6 +402,9 @@ pub struct Event {
     pub postgain: Option<f32>,
     pub velocity: Option<f32>,
     pub pan: Option<f32>,
+    pub panlaw: Option<PanLaw>,
+    pub pan2d: Option<f32>,
+    pub pan2dspread: Option<f32>,
 
     // Gain envelope
     pub envdelay: Option<f32>,
@@ -74,6 +413,9 @@ pub struct Event {
     pub decay: Option<f32>,
     pub sustain: Option<f32>,
     pub release: Option<f32>,
+    pub attackcurve: Option<f32>,
+    pub decaycurve: Option<f32>,
+    pub releasecurve: Option<f32>,
 
     // Filters
     pub lpf: Option<f32>,
@@ -103,6 +445,15 @@ pub struct Event {
     pub vib: Option<f32>,
     pub vibmod: Option<f32>,
     pub vibshape: Option<LfoShape>,
+    pub lfos: [LfoEvent; VOICE_LFOS],
+
+    // Note expression
+    pub bend: Option<f32>,
+    pub pressure: Option<f32>,
+    /// `Some(None)` unmaps pressure (`pressuretarget/off`).
+    pub pressure_target: Option<Option<ParamId>>,
+    pub pressure_depth: Option<f32>,
+    pub expr_slew: Option<f32>,
 
     // FM synthesis
     pub fm: Option<f32>,
@@ -128,16 +479,28 @@ pub struct Event {
     pub phaserdepth: Option<f32>,
     pub phasersweep: Option<f32>,
     pub phasercenter: Option<f32>,
+    pub phasermix: Option<f32>,
 
     // Flanger
     pub flanger: Option<f32>,
     pub flangerdepth: Option<f32>,
     pub flangerfeedback: Option<f32>,
+    pub flangermix: Option<f32>,
 
     // Smear
     pub smear: Option<f32>,
     pub smearfreq: Option<f32>,
     pub smearfb: Option<f32>,
+    pub smearmix: Option<f32>,
+
+    // Frequency shifter
+    pub fshift: Option<f32>,
+    pub fshiftmix: Option<f32>,
+
+    pub reso: Option<f32>,
+    pub resostruct: Option<f32>,
+    pub resobright: Option<f32>,
+    pub resodamp: Option<f32>,
 
     // Feedback delay
     pub feedback: Option<f32>,
@@ -147,23 +510,33 @@ pub struct Event {
     pub fblfo: Option<f32>,
     pub fblfodepth: Option<f32>,
     pub fblfoshape: Option<LfoShape>,
+    pub fbreturn: Option<f32>,
 
     // Chorus
     pub chorus: Option<f32>,
     pub chorusdepth: Option<f32>,
     pub chorusdelay: Option<f32>,
+    pub chorusmix: Option<f32>,
 
     // Comb filter
     pub comb: Option<f32>,
     pub combfreq: Option<f32>,
     pub combfeedback: Option<f32>,
     pub combdamp: Option<f32>,
+    pub combreturn: Option<f32>,
 
     // Sidechain compressor
     pub comp: Option<f32>,
     pub compattack: Option<f32>,
     pub comprelease: Option<f32>,
     pub comporbit: Option<usize>,
+    pub compthresh: Option<f32>,
+    pub compratio: Option<f32>,
+    pub compmakeup: Option<f32>,
+
+    // Transient shaper
+    pub transattack: Option<f32>,
+    pub transsustain: Option<f32>,
 
     // Distortion
     pub coarse: Option<f32>,
@@ -176,6 +549,7 @@ pub struct Event {
     // Stereo
     pub width: Option<f32>,
     pub haas: Option<f32>,
+    pub decor: Option<f32>,
 
     // EQ
     pub eqlo: Option<f32>,
@@ -191,10 +565,17 @@ pub struct Event {
     pub delaytime: Option<f32>,
     pub delayfeedback: Option<f32>,
     pub delaytype: Option<DelayType>,
+    pub delayreturn: Option<f32>,
+    pub delaysendlpf: Option<f32>,
+    pub delaysendhpf: Option<f32>,
+    pub delayfreeze: Option<f32>,
 
     // Reverb
     pub verb: Option<f32>,
     pub verbtype: Option<ReverbType>,
+    pub verbreturn: Option<f32>,
+    pub verbsendlpf: Option<f32>,
+    pub verbsendhpf: Option<f32>,
     pub verbdecay: Option<f32>,
     pub verbdamp: Option<f32>,
     pub verbpredelay: Option<f32>,
@@ -207,18 +588,206 @@ pub struct Event {
     pub verblowgain: Option<f32>,
     pub verbchorus: Option<f32>,
     pub verbchorusfreq: Option<f32>,
+    pub verbfreeze: Option<f32>,
+
+    // Orbit bus EQ
+    pub orbithpf: Option<f32>,
+    pub orbitlpf: Option<f32>,
+    pub orbiteqlo: Option<f32>,
+    pub orbiteqmid: Option<f32>,
+    pub orbiteqhi: Option<f32>,
+
+    // Master bus (`/doux/master/...`)
+    pub limit: Option<f32>,
+    pub dc: Option<bool>,
+    pub crossfeed: Option<f32>,
+    pub peakwarn: Option<f32>,
+    pub monowarn: Option<f32>,
+    /// Cue output pair; negative or `off` disables the cue.
+    pub cueout: Option<i64>,
+    pub cuegain: Option<f32>,
+    /// Output pair for `/doux/route`, as a stereo route replacing the orbit's
+    /// others; negative, `off` or `auto` drops the orbit's routes.
+    pub outpair: Option<i64>,
+    /// First device channel of a `/doux/route` matrix route; negative, `off`
+    /// or `auto` drops the orbit's routes.
+    pub outch: Option<i64>,
+    /// Channel of the right side (default: the one after `outch`).
+    pub outch2: Option<usize>,
+    pub outgain: Option<f32>,
+    pub outmono: Option<bool>,
+    /// Add the route next to the orbit's others instead of replacing them.
+    pub outadd: Option<bool>,
+    pub master_eq: [Option<String>; MASTER_EQ_BANDS],
+
+    // Beat repeat
+    pub repeat: Option<bool>,
+    pub repeatbpm: Option<f32>,
+    pub repeatbeats: Option<f32>,
+    pub repeatsize: Option<f32>,
+    pub repeatchance: Option<f32>,
+    pub repeatpitch: Option<f32>,
+    pub repeatdecay: Option<f32>,
+
+    // Metronome (`/doux/click/...`)
+    pub clickgain: Option<f32>,
+    pub clickbpm: Option<f32>,
+    pub clickbeats: Option<u32>,
+
+    // Orbit silence gate
+    pub silencethresh: Option<f32>,
+    pub silencehold: Option<f32>,
+
+    // Secondary orbit sends (`orbit2`/`orbit2send`, ...)
+    pub send_orbits: [Option<usize>; ORBIT_SENDS],
+    pub send_levels: [Option<f32>; ORBIT_SENDS],
+
+    // Insert slot contents (`insert1`.., `orbitinsert1`..)
+    pub inserts: [Option<String>; INSERT_SLOTS],
+    pub orbit_inserts: [Option<String>; INSERT_SLOTS],
 
     // Recorder
     pub overdub: Option<bool>,
+    pub autostop: Option<bool>,
+    /// Sample name of a take (`name`), in place of the positional one.
+    pub name: Option<String>,
+    /// Record the live input (`input/1`) rather than the output.
+    pub input: Option<bool>,
+    /// Length of a take in seconds (`len`, or a number right after `rec`;
+    /// 0 = until stopped). Records the live input unless `input/0` says
+    /// otherwise.
+    pub len: Option<f32>,
 
-    // Live input channel selection
+    // Live input channel selection and level
     pub inchan: Option<usize>,
+    pub inputgain: Option<f32>,
+
+    // Test signal output channel
+    pub out: Option<usize>,
+
+    // Tuning registration (`/doux/tuning`)
+    /// Scala file to load.
+    pub load: Option<String>,
+    /// Cents table, period last.
+    pub cents: Option<Vec<f64>>,
+    pub insum: Option<bool>,
 
     // Pre-computed effective sample name (sound + bank suffix)
     pub effective_name: Option<String>,
 }
 
 impl Event {
+    /// Drops NaN and clamps each parameter into its [`ParamId::range`], the
+    /// same limits modulation is held to.
+    pub fn clamp_params(&mut self) {
+        macro_rules! clamp {
+            ($($field:ident => $id:ident),+ $(,)?) => {
+                $(self.$field = self.$field.and_then(|v| ParamId::$id.clamp(v));)+
+            };
+        }
+        clamp!(
+            freq => Freq,
+            detune => Detune,
+            speed => Speed,
+            stretch => Stretch,
+            pw => Pw,
+            mirror => Mirror,
+            harmonics => Harmonics,
+            timbre => Timbre,
+            morph => Morph,
+            partials => Partials,
+            wave => Wave,
+            sub => Sub,
+            sync_ratio => SyncRatio,
+            sync_phase => SyncPhase,
+            scan => Scan,
+            pluckdamp => Pluckdamp,
+            pluckdecay => Pluckdecay,
+            gain => Gain,
+            postgain => Postgain,
+            pan => Pan,
+            pan2d => Pan2d,
+            pan2dspread => Pan2dspread,
+            lpf => Lpf,
+            lpq => Lpq,
+            hpf => Hpf,
+            hpq => Hpq,
+            bpf => Bpf,
+            bpq => Bpq,
+            slpf => Slpf,
+            slpq => Slpq,
+            shpf => Shpf,
+            shpq => Shpq,
+            sbpf => Sbpf,
+            sbpq => Sbpq,
+            llpf => Llpf,
+            llpq => Llpq,
+            lhpf => Lhpf,
+            lhpq => Lhpq,
+            lbpf => Lbpf,
+            lbpq => Lbpq,
+            vib => Vib,
+            vibmod => Vibmod,
+            fm => Fm,
+            fmh => Fmh,
+            fm2 => Fm2,
+            fm2h => Fm2h,
+            fmpivot => Fmpivot,
+            fmfb => Fmfb,
+            am => Am,
+            amdepth => Amdepth,
+            rm => Rm,
+            rmdepth => Rmdepth,
+            phaser => Phaser,
+            phaserdepth => Phaserdepth,
+            phasersweep => Phasersweep,
+            phasercenter => Phasercenter,
+            phasermix => Phasermix,
+            flanger => Flanger,
+            flangerdepth => Flangerdepth,
+            flangerfeedback => Flangerfeedback,
+            flangermix => Flangermix,
+            smear => Smear,
+            smearfreq => Smearfreq,
+            smearfb => Smearfb,
+            smearmix => Smearmix,
+            fshift => Fshift,
+            fshiftmix => Fshiftmix,
+            reso => Reso,
+            resostruct => Resostruct,
+            resobright => Resobright,
+            resodamp => Resodamp,
+            chorus => Chorus,
+            chorusdepth => Chorusdepth,
+            chorusdelay => Chorusdelay,
+            chorusmix => Chorusmix,
+            coarse => Coarse,
+            crush => Crush,
+            fold => Fold,
+            wrap => Wrap,
+            distort => Distort,
+            width => Width,
+            haas => Haas,
+            decor => Decor,
+            transattack => Transattack,
+            transsustain => Transsustain,
+            envdelay => Envdelay,
+            attack => Attack,
+            hold => Hold,
+            decay => Decay,
+            sustain => Sustain,
+            release => Release,
+            eqlo => Eqlo,
+            eqmid => Eqmid,
+            eqhi => Eqhi,
+            eqlofreq => EqLoFreq,
+            eqmidfreq => EqMidFreq,
+            eqhifreq => EqHiFreq,
+            tilt => Tilt,
+            inputgain => Inputgain,
+        );
+    }
+
     pub fn n_as_index(&self) -> usize {
         self.n.as_ref().and_then(|s| s.parse().ok()).unwrap_or(0)
     }
@@ -227,14 +796,27 @@ impl Event {
         self.n.as_ref().and_then(|s| s.parse().ok()).unwrap_or(0.0)
     }
 
+    /// Sustain loop as `(begin, end, crossfade secs)`, if requested.
+    /// Loop points are fractions of the playback range; giving either
+    /// one turns the loop on.
+    pub fn resolve_loop(&self) -> Option<(f32, f32, f32)> {
+        let on = self
+            .sampleloop
+            .unwrap_or(self.loopbegin.is_some() || self.loopend.is_some());
+        on.then(|| {
+            (
+                self.loopbegin.unwrap_or(0.0),
+                self.loopend.unwrap_or(1.0),
+                self.loopxfade.unwrap_or(0.0).max(0.0),
+            )
+        })
+    }
+
     pub fn resolve_range(&self) -> (f32, f32) {
         if self.begin.is_some() || self.end.is_some() {
             return (self.begin.unwrap_or(0.0), self.end.unwrap_or(1.0));
         }
-        if let Some(slices) = self.slice {
-            let slices = (slices as u32).max(1);
-            let pick = self.pick.unwrap_or(0.0) as i32;
-            let idx = pick.rem_euclid(slices as i32) as u32;
+        if let Some((slices, idx)) = self.slice_pick() {
             let step = 1.0 / slices as f32;
             let begin = idx as f32 * step;
             (begin, begin + step)
@@ -243,103 +825,346 @@ impl Event {
         }
     }
 
+    /// Like [`resolve_range`](Self::resolve_range), but `slicemode/energy`
+    /// cuts at the sample's [`SliceMap`](crate::sampling::SliceMap) when it has one.
+    #[cfg(feature = "native")]
+    pub fn resolve_range_in(&self, data: &SampleData) -> (f32, f32) {
+        let energy = self.slicemode == Some(SliceMode::Energy);
+        match (self.begin.or(self.end), self.slice_pick(), &data.slices) {
+            (None, Some((slices, idx)), Some(map)) if energy => map.slice(slices, idx),
+            _ => self.resolve_range(),
+        }
+    }
+
+    /// Slice count and the picked slice, wrapped into range.
+    fn slice_pick(&self) -> Option<(u32, u32)> {
+        let slices = (self.slice? as u32).max(1);
+        let pick = self.pick.unwrap_or(0.0) as i32;
+        Some((slices, pick.rem_euclid(slices as i32) as u32))
+    }
+
     fn parse_usize(val: &str) -> Option<usize> {
         val.parse::<f32>().ok().map(|f| f as usize)
     }
 
+    /// Zero-based slot for numbered keys like `insert2` (`prefix` + 1..=`count`).
+    fn numbered_slot(key: &str, prefix: &str, count: usize) -> Option<usize> {
+        let n: usize = key.strip_prefix(prefix)?.parse().ok()?;
+        (1..=count).contains(&n).then(|| n - 1)
+    }
+
+    /// Splits `lfo2rate` into the LFO index and the field name.
+    fn lfo_key(key: &str) -> Option<(usize, &str)> {
+        let rest = key.strip_prefix("lfo")?;
+        let n = rest.chars().next()?.to_digit(10)? as usize;
+        (1..=VOICE_LFOS).contains(&n).then(|| (n - 1, &rest[1..]))
+    }
+
+    /// Send index for `orbit2` (orbit) and `orbit2send` (level, `true`) keys.
+    fn send_key(key: &str) -> Option<(usize, bool)> {
+        let (key, is_level) = match key.strip_suffix("send") {
+            Some(key) => (key, true),
+            None => (key, false),
+        };
+        let n: usize = key.strip_prefix("orbit")?.parse().ok()?;
+        (2..=ORBIT_SENDS + 1)
+            .contains(&n)
+            .then(|| (n - 2, is_level))
+    }
+
+    /// Recording length in seconds: finite and not negative.
+    fn parse_len(val: &str) -> Option<f32> {
+        val.parse()
+            .ok()
+            .filter(|&s: &f32| s.is_finite() && s >= 0.0)
+    }
+
+    /// Takes the next token as a command's positional argument when
+    /// `accept` recognises it, leaving it to the key/value pairs otherwise.
+    fn positional(iter: &mut Tokens, accept: impl Fn(&str) -> bool) -> Option<String> {
+        let next = iter.clone().next()?;
+        if !accept(&next) {
+            return None;
+        }
+        iter.next().map(Cow::into_owned)
+    }
+
+    /// Whether the parser knows `token` as a key.
+    fn is_key(token: &str) -> bool {
+        let mut known = true;
+        Self::parse_reporting(&format!("{token}/0"), 48000.0, |_, value| {
+            known &= value.is_some();
+        });
+        known
+    }
+
     fn parse_u8(val: &str) -> Option<u8> {
         val.parse::<f32>().ok().map(|f| f as u8)
     }
 
+    /// Parses a single event; see [`Event::parse_all`] for `;`-separated input.
     pub fn parse(input: &str, sr: f32) -> Self {
+        Self::parse_reporting(input, sr, |_, _| {})
+    }
+
+    /// Parses every event in `input`, in order (see [`split_events`]).
+    pub fn parse_all(input: &str, sr: f32) -> Vec<Self> {
+        split_events(input).map(|e| Self::parse(e, sr)).collect()
+    }
+
+    /// Everything [`Event::parse`] would ignore in `input`, in order.
+    ///
+    /// Meant for the control side (strict mode); the audio thread parses
+    /// without collecting anything.
+    pub fn check(input: &str) -> Vec<ParseWarning> {
+        let mut warnings = Vec::new();
+        for event in split_events(input) {
+            Self::parse_reporting(event, 48000.0, |key, value| {
+                // Variables are only known to the engine
+                if value.is_some_and(|v| v.contains('$')) {
+                    return;
+                }
+                warnings.push(match value {
+                    Some(value) => ParseWarning::BadValue {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    },
+                    None => ParseWarning::UnknownKey(key.to_string()),
+                })
+            });
+        }
+        warnings
+    }
+
+    /// Parses `input`, calling `report` with each ignored key, plus its value
+    /// when it was the value that did not parse.
+    fn parse_reporting(input: &str, sr: f32, mut report: impl FnMut(&str, Option<&str>)) -> Self {
         let mut event = Self::default();
-        let mut iter = input.trim().split('/').filter(|s| !s.is_empty());
+        let mut iter = Tokens::new(input);
 
         macro_rules! parse_param {
             ($val:expr, $field:ident, $id:expr) => {
                 if let Some(chain) = ModChain::parse($val) {
                     event.mods.push(($id, chain));
                 } else {
-                    event.$field = $val.parse().ok();
+                    event.$field = value!($val.parse().ok().filter(|v: &f32| !v.is_nan()));
                 }
             };
         }
 
         while let (Some(key), Some(val)) = (iter.next(), iter.next()) {
+            let (key, val) = (&*key, &*val);
+            // Reports the pair when its value does not parse
+            macro_rules! value {
+                ($parsed:expr) => {{
+                    let parsed = $parsed;
+                    if parsed.is_none() {
+                        report(key, Some(val));
+                    }
+                    parsed
+                }};
+            }
+            // Automation: chains start lanes, `off` stops them, and plain
+            // values stop them and fall through to be set as usual. On a
+            // voice event, chains start lanes on the voice's orbit.
+            if let Ok(param) = key.parse::<OrbitParam>() {
+                let chain = ModChain::parse(val);
+                if matches!(event.cmd.as_deref(), Some("automate" | "orbit")) {
+                    let is_chain = chain.is_some();
+                    event.orbit_mods.push((param, chain));
+                    if is_chain || val == "off" {
+                        continue;
+                    }
+                } else if let (None, Some(chain)) = (&event.cmd, chain) {
+                    event.orbit_mods.push((param, Some(chain)));
+                    continue;
+                }
+            }
             match key {
                 "doux" | "dirt" => {
                     event.cmd = Some(val.to_string());
-                    if val == "rec" && iter.clone().count() % 2 == 1 {
-                        if let Some(name) = iter.next() {
-                            event.sound = Some(name.to_string());
+                    if val == "rec" {
+                        // `rec/<len>/<name>`, both optional: a number is the
+                        // loop length, anything but a key the take's name
+                        event.len = Self::positional(&mut iter, |t| Self::parse_len(t).is_some())
+                            .and_then(|t| Self::parse_len(&t));
+                        event.sound = Self::positional(&mut iter, |t| !Self::is_key(t));
+                    }
+                    if val == "release_id" {
+                        event.id = Self::positional(&mut iter, |t| !Self::is_key(t));
+                    }
+                    if val == "orbit" {
+                        event.orbit = iter.next().and_then(|n| Self::parse_usize(&n));
+                    }
+                    if val == "set" {
+                        event.sound = iter.next().map(Cow::into_owned);
+                        event.n = iter.next().map(Cow::into_owned);
+                    }
+                    if val == "preset" {
+                        // Action and name; the engine reads them from the
+                        // text, as the pairs after them can be anything
+                        iter.next();
+                        iter.next();
+                    }
+                    if val == "record" {
+                        match iter.next().as_deref() {
+                            Some("orbit") => {
+                                event.orbit = iter.next().and_then(|n| Self::parse_usize(&n));
+                            }
+                            Some("voice") => {
+                                event.vid = iter.next().and_then(|n| n.parse().ok());
+                            }
+                            _ => {}
+                        }
+                        event.n = iter.next().map(Cow::into_owned);
+                    }
+                    if val == "bpm" {
+                        event.bpm = iter.next().and_then(|n| n.parse().ok());
+                    }
+                    if val == "seed" {
+                        event.seed = iter.next().and_then(|n| n.parse().ok());
+                    }
+                    if val == "keep" {
+                        event.n = Self::positional(&mut iter, |t| Self::parse_len(t).is_some());
+                    }
+                    if matches!(val, "strict" | "click") {
+                        event.n = Self::positional(&mut iter, |t| {
+                            matches!(t, "0" | "1" | "on" | "off" | "true" | "false")
+                        });
+                    }
+                    if val == "outmode" {
+                        event.n = Self::positional(&mut iter, |t| t.parse::<OutMode>().is_ok());
+                    }
+                    if val == "test" {
+                        event.sound = iter.next().map(Cow::into_owned);
+                        if event.sound.as_deref() != Some("off") {
+                            event.n = iter.next().map(Cow::into_owned);
                         }
                     }
                 }
-                "tick" => event.tick = val.parse().ok(),
-                "time" | "t" => {
-                    // Legacy: convert seconds to ticks
-                    event.tick = val
-                        .parse::<f64>()
-                        .ok()
-                        .map(|t| (t * sr as f64).floor() as u64);
+                "tick" => {
+                    event.tick = value!(val.parse().ok());
+                    event.beat = None;
                 }
-                "delta" => event.delta = val.parse().ok(),
-                "gate" => event.gate = val.parse().ok(),
-                "voice" => event.voice = Self::parse_usize(val),
+                "time" | "t" => match beats(val) {
+                    Some(beat) => (event.tick, event.beat) = (None, Some(beat)),
+                    None => {
+                        // Legacy: convert seconds to ticks
+                        event.tick =
+                            value!(val.parse::<f64>().ok()).map(|t| (t * sr as f64).floor() as u64);
+                        event.beat = None;
+                    }
+                },
+                "endtick" => event.endtick = value!(val.parse().ok()),
+                "endtime" => {
+                    event.endtick =
+                        value!(val.parse::<f64>().ok()).map(|t| (t * sr as f64).floor() as u64);
+                }
+                "delta" => event.delta = value!(val.parse().ok()),
+                "gate" | "dur" => match beats(val) {
+                    Some(b) => (event.gate, event.gatebeats) = (None, Some(b as f32)),
+                    None => (event.gate, event.gatebeats) = (value!(val.parse().ok()), None),
+                },
+                "rep" => match beats(val) {
+                    Some(b) => (event.rep, event.repbeats) = (None, Some(b as f32)),
+                    None => (event.rep, event.repbeats) = (value!(val.parse().ok()), None),
+                },
+                "ratchet" | "rat" => event.ratchet = value!(Self::parse_usize(val)),
+                "ratchetdecay" => event.ratchetdecay = value!(val.parse().ok()),
+                "ratchetstep" => event.ratchetstep = value!(val.parse().ok()),
+                "repjitter" => event.repjitter = value!(val.parse().ok()),
+                "div" => event.div = value!(val.parse().ok().filter(|&d: &f32| d > 0.0)),
+                "mult" => event.mult = value!(val.parse().ok().filter(|&m: &f32| m > 0.0)),
+                "swing" => event.swing = value!(val.parse().ok()),
+                "swinggrid" => event.swinggrid = value!(Self::parse_usize(val)),
+                "flam" => event.flam = value!(val.parse().ok()),
+                "voice" => event.voice = value!(Self::parse_usize(val)),
+                "vid" => event.vid = value!(val.parse().ok()),
+                "seed" => event.seed = value!(val.parse().ok()),
+                "id" => event.id = Some(val.to_string()),
+                "ack" => event.ack = value!(val.parse().ok()),
                 "reset" => event.reset = Some(val == "1" || val == "true"),
-                "orbit" => event.orbit = Self::parse_usize(val),
-                "freq" => parse_param!(val, freq, ParamId::Freq),
+                "modsync" => event.modsync = Some(val == "1" || val == "true"),
+                "revert" => event.revert = Some(val == "1" || val == "true"),
+                "orbit" => event.orbit = value!(Self::parse_usize(val)),
+                "cue" => event.cue = Some(val == "1" || val == "true"),
+                "late_ok" | "lateok" => event.late_ok = Some(val == "1" || val == "true"),
+                "freq" => {
+                    (event.note, event.note_mod) = (None, None);
+                    parse_param!(val, freq, ParamId::Freq);
+                }
                 "note" => {
-                    if let Some(chain) = ModChain::parse(val).map(|c| c.map_values(midi2freq)) {
-                        event.mods.push((ParamId::Freq, chain));
+                    if let Some(chain) = ModChain::parse(val) {
+                        event
+                            .mods
+                            .push((ParamId::Freq, chain.clone().map_values(midi2freq)));
+                        event.note_mod = Some(chain);
                     } else {
-                        event.freq = val.parse().ok().map(midi2freq);
+                        event.note = value!(val.parse().ok());
+                        event.freq = event.note.map(midi2freq);
                     }
                 }
+                "tuning" => event.tuning = Some(val.to_string()),
+                "preset" => event.preset = Some(val.to_string()),
                 "detune" => parse_param!(val, detune, ParamId::Detune),
                 "speed" => parse_param!(val, speed, ParamId::Speed),
                 "stretch" => parse_param!(val, stretch, ParamId::Stretch),
-                "fit" => event.fit = val.parse().ok(),
+                "fit" => event.fit = value!(val.parse().ok()),
                 "sound" | "s" => event.sound = Some(val.to_string()),
                 "pw" => parse_param!(val, pw, ParamId::Pw),
-                "spread" => event.spread = val.parse().ok(),
-                "size" => event.size = val.parse().ok(),
-                "warp" => event.warp = val.parse().ok(),
+                "spread" => event.spread = value!(val.parse().ok()),
+                "size" => event.size = value!(val.parse().ok()),
+                "warp" => event.warp = value!(val.parse().ok()),
                 "mirror" => parse_param!(val, mirror, ParamId::Mirror),
                 "harmonics" | "harm" => parse_param!(val, harmonics, ParamId::Harmonics),
                 "timbre" => parse_param!(val, timbre, ParamId::Timbre),
                 "morph" => parse_param!(val, morph, ParamId::Morph),
                 "partials" => parse_param!(val, partials, ParamId::Partials),
                 "n" => event.n = Some(val.to_string()),
-                "cut" => event.cut = Self::parse_usize(val),
-                "begin" => event.begin = val.parse().ok(),
-                "end" => event.end = val.parse().ok(),
-                "slice" => event.slice = val.parse().ok(),
-                "pick" => event.pick = val.parse().ok(),
+                "cut" => event.cut = value!(Self::parse_usize(val)),
+                "cutfade" => event.cutfade = value!(val.parse().ok()),
+                "begin" => event.begin = value!(val.parse().ok()),
+                "end" => event.end = value!(val.parse().ok()),
+                "slice" => event.slice = value!(val.parse().ok()),
+                "pick" | "sliceindex" => event.pick = value!(val.parse().ok()),
+                "slicemode" => event.slicemode = value!(val.parse().ok()),
+                "loop" => event.sampleloop = Some(val == "1" || val == "true"),
+                "loopbegin" => event.loopbegin = value!(val.parse().ok()),
+                "loopend" => event.loopend = value!(val.parse().ok()),
+                "loopxfade" => event.loopxfade = value!(val.parse().ok()),
                 "bank" => event.bank = Some(val.to_string()),
                 "wave" | "waveform" => parse_param!(val, wave, ParamId::Wave),
                 "sub" => parse_param!(val, sub, ParamId::Sub),
-                "suboct" => event.sub_oct = Self::parse_u8(val),
-                "subwave" => event.sub_wave = val.parse().ok(),
+                "suboct" => event.sub_oct = value!(Self::parse_u8(val)),
+                "subwave" => event.sub_wave = value!(val.parse().ok()),
                 "sync" => parse_param!(val, sync_ratio, ParamId::SyncRatio),
                 "syncphase" | "syncph" => parse_param!(val, sync_phase, ParamId::SyncPhase),
-                "syncmode" => event.sync_mode = val.parse().ok(),
+                "syncmode" => event.sync_mode = value!(val.parse().ok()),
                 "scan" => parse_param!(val, scan, ParamId::Scan),
-                "wtlen" => event.wtlen = val.parse().ok(),
-                "file_pcm" => event.file_pcm = val.parse().ok(),
-                "file_frames" => event.file_frames = val.parse().ok(),
-                "file_channels" => event.file_channels = Self::parse_u8(val),
-                "file_freq" => event.file_freq = val.parse().ok(),
+                "pluckdamp" => parse_param!(val, pluckdamp, ParamId::Pluckdamp),
+                "pluckdecay" => parse_param!(val, pluckdecay, ParamId::Pluckdecay),
+                "pluckexcite" => event.pluck_excite = value!(val.parse().ok()),
+                "wtlen" => event.wtlen = value!(val.parse().ok()),
+                "file_pcm" => event.file_pcm = value!(val.parse().ok()),
+                "file_frames" => event.file_frames = value!(val.parse().ok()),
+                "file_channels" => event.file_channels = value!(Self::parse_u8(val)),
+                "file_freq" => event.file_freq = value!(val.parse().ok()),
                 "gain" => parse_param!(val, gain, ParamId::Gain),
                 "postgain" => parse_param!(val, postgain, ParamId::Postgain),
-                "velocity" => event.velocity = val.parse().ok(),
+                "velocity" => event.velocity = value!(val.parse().ok()),
                 "pan" => parse_param!(val, pan, ParamId::Pan),
-                "envdelay" | "envdly" => event.envdelay = val.parse().ok(),
-                "attack" => event.attack = val.parse().ok(),
-                "hold" | "hld" => event.hold = val.parse().ok(),
-                "decay" => event.decay = val.parse().ok(),
-                "sustain" => event.sustain = val.parse().ok(),
-                "release" => event.release = val.parse().ok(),
+                "panlaw" => event.panlaw = value!(val.parse().ok()),
+                "pan2d" => parse_param!(val, pan2d, ParamId::Pan2d),
+                "pan2dspread" => parse_param!(val, pan2dspread, ParamId::Pan2dspread),
+                "envdelay" | "envdly" => parse_param!(val, envdelay, ParamId::Envdelay),
+                "attack" => parse_param!(val, attack, ParamId::Attack),
+                "hold" | "hld" => parse_param!(val, hold, ParamId::Hold),
+                "decay" => parse_param!(val, decay, ParamId::Decay),
+                "sustain" => parse_param!(val, sustain, ParamId::Sustain),
+                "release" => parse_param!(val, release, ParamId::Release),
+                "attackcurve" => event.attackcurve = value!(val.parse().ok()),
+                "decaycurve" => event.decaycurve = value!(val.parse().ok()),
+                "releasecurve" => event.releasecurve = value!(val.parse().ok()),
                 "lpf" | "cutoff" => parse_param!(val, lpf, ParamId::Lpf),
                 "lpq" | "resonance" => parse_param!(val, lpq, ParamId::Lpq),
                 "hpf" | "hcutoff" => parse_param!(val, hpf, ParamId::Hpf),
@@ -360,56 +1185,90 @@ impl Event {
                 "lbpq" => parse_param!(val, lbpq, ParamId::Lbpq),
                 "vib" => parse_param!(val, vib, ParamId::Vib),
                 "vibmod" => parse_param!(val, vibmod, ParamId::Vibmod),
-                "vibshape" => event.vibshape = val.parse().ok(),
+                "vibshape" => event.vibshape = value!(val.parse().ok()),
+                "bend" => event.bend = value!(val.parse().ok()),
+                "pressure" => event.pressure = value!(val.parse().ok()),
+                "pressuretarget" => {
+                    event.pressure_target = Some(match val {
+                        "off" => None,
+                        _ => value!(val.parse().ok()),
+                    })
+                }
+                "pressuredepth" => event.pressure_depth = value!(val.parse().ok()),
+                "exprslew" => event.expr_slew = value!(val.parse().ok()),
                 "fm" | "fmi" => parse_param!(val, fm, ParamId::Fm),
                 "fmh" => parse_param!(val, fmh, ParamId::Fmh),
-                "fmshape" => event.fmshape = val.parse().ok(),
+                "fmshape" => event.fmshape = value!(val.parse().ok()),
                 "fm2" => parse_param!(val, fm2, ParamId::Fm2),
                 "fm2h" => parse_param!(val, fm2h, ParamId::Fm2h),
                 "fmpivot" => parse_param!(val, fmpivot, ParamId::Fmpivot),
                 "fmfb" => parse_param!(val, fmfb, ParamId::Fmfb),
                 "am" => parse_param!(val, am, ParamId::Am),
                 "amdepth" => parse_param!(val, amdepth, ParamId::Amdepth),
-                "amshape" => event.amshape = val.parse().ok(),
+                "amshape" => event.amshape = value!(val.parse().ok()),
                 "rm" => parse_param!(val, rm, ParamId::Rm),
                 "rmdepth" => parse_param!(val, rmdepth, ParamId::Rmdepth),
-                "rmshape" => event.rmshape = val.parse().ok(),
+                "rmshape" => event.rmshape = value!(val.parse().ok()),
                 "phaser" | "phaserrate" => parse_param!(val, phaser, ParamId::Phaser),
                 "phaserdepth" => parse_param!(val, phaserdepth, ParamId::Phaserdepth),
                 "phasersweep" => parse_param!(val, phasersweep, ParamId::Phasersweep),
                 "phasercenter" => parse_param!(val, phasercenter, ParamId::Phasercenter),
+                "phasermix" => parse_param!(val, phasermix, ParamId::Phasermix),
                 "flanger" | "flangerrate" => parse_param!(val, flanger, ParamId::Flanger),
                 "flangerdepth" => parse_param!(val, flangerdepth, ParamId::Flangerdepth),
                 "flangerfeedback" => parse_param!(val, flangerfeedback, ParamId::Flangerfeedback),
+                "flangermix" => parse_param!(val, flangermix, ParamId::Flangermix),
                 "smear" => parse_param!(val, smear, ParamId::Smear),
                 "smearfreq" => parse_param!(val, smearfreq, ParamId::Smearfreq),
                 "smearfb" => parse_param!(val, smearfb, ParamId::Smearfb),
-                "feedback" | "fb" => event.feedback = val.parse().ok(),
-                "fbtime" | "fbt" => event.fbtime = val.parse().ok(),
-                "fbdamp" | "fbd" => event.fbdamp = val.parse().ok(),
-                "fbcross" | "fbc" => event.fbcross = val.parse().ok(),
-                "fblfo" => event.fblfo = val.parse().ok(),
-                "fblfodepth" => event.fblfodepth = val.parse().ok(),
-                "fblfoshape" => event.fblfoshape = val.parse().ok(),
+                "smearmix" => parse_param!(val, smearmix, ParamId::Smearmix),
+                "fshift" => parse_param!(val, fshift, ParamId::Fshift),
+                "fshiftmix" => parse_param!(val, fshiftmix, ParamId::Fshiftmix),
+                "reso" => parse_param!(val, reso, ParamId::Reso),
+                "resostruct" => parse_param!(val, resostruct, ParamId::Resostruct),
+                "resobright" => parse_param!(val, resobright, ParamId::Resobright),
+                "resodamp" => parse_param!(val, resodamp, ParamId::Resodamp),
+                "feedback" | "fb" => event.feedback = value!(val.parse().ok()),
+                "fbtime" | "fbt" => event.fbtime = value!(val.parse().ok()),
+                "fbdamp" | "fbd" => event.fbdamp = value!(val.parse().ok()),
+                "fbcross" | "fbc" => event.fbcross = value!(val.parse().ok()),
+                "fblfo" => event.fblfo = value!(val.parse().ok()),
+                "fblfodepth" => event.fblfodepth = value!(val.parse().ok()),
+                "fblfoshape" => event.fblfoshape = value!(val.parse().ok()),
+                "fbreturn" => event.fbreturn = value!(val.parse().ok()),
                 "chorus" | "chorusrate" => parse_param!(val, chorus, ParamId::Chorus),
                 "chorusdepth" => parse_param!(val, chorusdepth, ParamId::Chorusdepth),
                 "chorusdelay" => parse_param!(val, chorusdelay, ParamId::Chorusdelay),
-                "comb" => event.comb = val.parse().ok(),
-                "combfreq" => event.combfreq = val.parse().ok(),
-                "combfeedback" => event.combfeedback = val.parse().ok(),
-                "combdamp" => event.combdamp = val.parse().ok(),
-                "comp" => event.comp = val.parse().ok(),
-                "compattack" | "cattack" => event.compattack = val.parse().ok(),
-                "comprelease" | "crelease" => event.comprelease = val.parse().ok(),
-                "comporbit" | "corbit" => event.comporbit = Self::parse_usize(val),
+                "chorusmix" => parse_param!(val, chorusmix, ParamId::Chorusmix),
+                "comb" => event.comb = value!(val.parse().ok()),
+                "combfreq" => event.combfreq = value!(val.parse().ok()),
+                "combfeedback" => event.combfeedback = value!(val.parse().ok()),
+                "combdamp" => event.combdamp = value!(val.parse().ok()),
+                "combreturn" => event.combreturn = value!(val.parse().ok()),
+                "comp" | "duckamount" => event.comp = value!(val.parse().ok()),
+                "compattack" | "cattack" | "duckattack" => {
+                    event.compattack = value!(val.parse().ok())
+                }
+                "comprelease" | "crelease" | "duckrelease" => {
+                    event.comprelease = value!(val.parse().ok())
+                }
+                "compthresh" => event.compthresh = value!(val.parse().ok()),
+                "compratio" => event.compratio = value!(val.parse().ok()),
+                "compmakeup" => event.compmakeup = value!(val.parse().ok()),
+                "comporbit" | "corbit" | "duckfrom" => {
+                    event.comporbit = value!(Self::parse_usize(val))
+                }
                 "coarse" => parse_param!(val, coarse, ParamId::Coarse),
                 "crush" => parse_param!(val, crush, ParamId::Crush),
                 "fold" => parse_param!(val, fold, ParamId::Fold),
                 "wrap" => parse_param!(val, wrap, ParamId::Wrap),
                 "distort" => parse_param!(val, distort, ParamId::Distort),
-                "distortvol" => event.distortvol = val.parse().ok(),
+                "distortvol" => event.distortvol = value!(val.parse().ok()),
                 "width" => parse_param!(val, width, ParamId::Width),
                 "haas" => parse_param!(val, haas, ParamId::Haas),
+                "decor" => parse_param!(val, decor, ParamId::Decor),
+                "transattack" => parse_param!(val, transattack, ParamId::Transattack),
+                "transsustain" => parse_param!(val, transsustain, ParamId::Transsustain),
                 "eqlo" => parse_param!(val, eqlo, ParamId::Eqlo),
                 "eqmid" => parse_param!(val, eqmid, ParamId::Eqmid),
                 "eqhi" => parse_param!(val, eqhi, ParamId::Eqhi),
@@ -417,35 +1276,135 @@ impl Event {
                 "eqmidfreq" => parse_param!(val, eqmidfreq, ParamId::EqMidFreq),
                 "eqhifreq" => parse_param!(val, eqhifreq, ParamId::EqHiFreq),
                 "tilt" => parse_param!(val, tilt, ParamId::Tilt),
-                "delay" => event.delay = val.parse().ok(),
-                "delaytime" => event.delaytime = val.parse().ok(),
-                "delayfeedback" => event.delayfeedback = val.parse().ok(),
-                "delaytype" | "dtype" => event.delaytype = val.parse().ok(),
-                "verb" | "reverb" => event.verb = val.parse().ok(),
-                "verbtype" | "vtype" => event.verbtype = val.parse().ok(),
-                "verbdecay" => event.verbdecay = val.parse().ok(),
-                "verbdamp" => event.verbdamp = val.parse().ok(),
-                "verbpredelay" => event.verbpredelay = val.parse().ok(),
-                "verbdiff" => event.verbdiff = val.parse().ok(),
-                "verbsize" | "vsize" => event.verbsize = val.parse().ok(),
-                "verbprelow" => event.verbprelow = val.parse().ok(),
-                "verbprehigh" => event.verbprehigh = val.parse().ok(),
-                "verblowcut" => event.verblowcut = val.parse().ok(),
-                "verbhighcut" => event.verbhighcut = val.parse().ok(),
-                "verblowgain" => event.verblowgain = val.parse().ok(),
-                "verbchorus" | "vchorus" => event.verbchorus = val.parse().ok(),
-                "verbchorusfreq" | "vchorusfreq" => event.verbchorusfreq = val.parse().ok(),
+                "delay" => event.delay = value!(val.parse().ok()),
+                "delaytime" => event.delaytime = value!(val.parse().ok()),
+                "delayfeedback" => event.delayfeedback = value!(val.parse().ok()),
+                "delayfreeze" => event.delayfreeze = value!(val.parse().ok()),
+                "delaytype" | "dtype" => event.delaytype = value!(val.parse().ok()),
+                "delayreturn" => event.delayreturn = value!(val.parse().ok()),
+                "delaysendlpf" => event.delaysendlpf = value!(val.parse().ok()),
+                "delaysendhpf" => event.delaysendhpf = value!(val.parse().ok()),
+                "verb" | "reverb" => event.verb = value!(val.parse().ok()),
+                "verbtype" | "vtype" => event.verbtype = value!(val.parse().ok()),
+                "verbreturn" => event.verbreturn = value!(val.parse().ok()),
+                "verbsendlpf" => event.verbsendlpf = value!(val.parse().ok()),
+                "verbsendhpf" => event.verbsendhpf = value!(val.parse().ok()),
+                "orbithpf" => event.orbithpf = value!(val.parse().ok()),
+                "orbitlpf" => event.orbitlpf = value!(val.parse().ok()),
+                "orbiteqlo" => event.orbiteqlo = value!(val.parse().ok()),
+                "orbiteqmid" => event.orbiteqmid = value!(val.parse().ok()),
+                "orbiteqhi" => event.orbiteqhi = value!(val.parse().ok()),
+                "verbdecay" => event.verbdecay = value!(val.parse().ok()),
+                "verbdamp" => event.verbdamp = value!(val.parse().ok()),
+                "verbpredelay" => event.verbpredelay = value!(val.parse().ok()),
+                "verbdiff" => event.verbdiff = value!(val.parse().ok()),
+                "verbsize" | "vsize" => event.verbsize = value!(val.parse().ok()),
+                "verbprelow" => event.verbprelow = value!(val.parse().ok()),
+                "verbprehigh" => event.verbprehigh = value!(val.parse().ok()),
+                "verblowcut" => event.verblowcut = value!(val.parse().ok()),
+                "verbhighcut" => event.verbhighcut = value!(val.parse().ok()),
+                "verblowgain" => event.verblowgain = value!(val.parse().ok()),
+                "verbchorus" | "vchorus" => event.verbchorus = value!(val.parse().ok()),
+                "verbchorusfreq" | "vchorusfreq" => event.verbchorusfreq = value!(val.parse().ok()),
+                "verbfreeze" => event.verbfreeze = value!(val.parse().ok()),
+                "limit" => event.limit = value!(val.parse().ok()),
+                "dc" => event.dc = Some(val == "1" || val == "true"),
+                "crossfeed" => event.crossfeed = value!(val.parse().ok()),
+                "cueout" => {
+                    event.cueout = match val {
+                        "off" => Some(-1),
+                        _ => value!(val.parse().ok()),
+                    }
+                }
+                "cuegain" => event.cuegain = value!(val.parse().ok()),
+                "outpair" => {
+                    event.outpair = match val {
+                        "off" | "auto" => Some(-1),
+                        _ => value!(val.parse().ok()),
+                    }
+                }
+                "outch" => {
+                    event.outch = match val {
+                        "off" | "auto" => Some(-1),
+                        _ => value!(val.parse().ok()),
+                    }
+                }
+                "outch2" => event.outch2 = value!(Self::parse_usize(val)),
+                "outgain" => event.outgain = value!(val.parse().ok()),
+                "outmono" => event.outmono = Some(val == "1" || val == "true"),
+                "outadd" => event.outadd = Some(val == "1" || val == "true"),
+                "peakwarn" => event.peakwarn = value!(val.parse().ok()),
+                "monowarn" => event.monowarn = value!(val.parse().ok()),
+                "repeat" => event.repeat = Some(val == "1" || val == "true"),
+                "repeatbpm" => event.repeatbpm = value!(val.parse().ok()),
+                "repeatbeats" => event.repeatbeats = value!(val.parse().ok()),
+                "repeatsize" => event.repeatsize = value!(val.parse().ok()),
+                "repeatchance" => event.repeatchance = value!(val.parse().ok()),
+                "repeatpitch" => event.repeatpitch = value!(val.parse().ok()),
+                "repeatdecay" => event.repeatdecay = value!(val.parse().ok()),
+                "clickgain" => event.clickgain = value!(val.parse().ok()),
+                "clickbpm" => event.clickbpm = value!(val.parse().ok()),
+                "clickbeats" => event.clickbeats = value!(val.parse().ok()),
+                "silencethresh" => event.silencethresh = value!(val.parse().ok()),
+                "silencehold" => event.silencehold = value!(val.parse().ok()),
                 "overdub" | "dub" => event.overdub = Some(val == "1" || val == "true"),
-                "inchan" => event.inchan = Self::parse_usize(val),
-                _ => {}
+                "autostop" => event.autostop = Some(val == "1" || val == "true"),
+                "name" => event.name = Some(val.to_string()),
+                "input" => event.input = Some(val == "1" || val == "true"),
+                "len" => event.len = value!(Self::parse_len(val)),
+                "inchan" | "inputch" => event.inchan = value!(Self::parse_usize(val)),
+                "out" => event.out = value!(Self::parse_usize(val)),
+                "load" => event.load = Some(val.to_string()),
+                "cents" => {
+                    event.cents = value!(Tuning::parse_cents(val).ok()).map(|t| t.degrees);
+                }
+                "insum" => event.insum = Some(val == "1" || val == "true"),
+                "inputgain" => parse_param!(val, inputgain, ParamId::Inputgain),
+                _ => {
+                    if let Some(i) = Self::numbered_slot(key, "orbitinsert", INSERT_SLOTS) {
+                        event.orbit_inserts[i] = Some(val.to_string());
+                    } else if let Some(i) = Self::numbered_slot(key, "insert", INSERT_SLOTS) {
+                        event.inserts[i] = Some(val.to_string());
+                    } else if let Some((i, is_level)) = Self::send_key(key) {
+                        if is_level {
+                            event.send_levels[i] = value!(val.parse().ok());
+                        } else {
+                            event.send_orbits[i] = value!(Self::parse_usize(val));
+                        }
+                    } else if let Some(i) = Self::numbered_slot(key, "eq", MASTER_EQ_BANDS) {
+                        event.master_eq[i] = Some(val.to_string());
+                    } else if let Some((i, field)) = Self::lfo_key(key) {
+                        let lfo = &mut event.lfos[i];
+                        match field {
+                            "rate" => lfo.rate = value!(val.parse().ok()),
+                            "depth" => lfo.depth = value!(val.parse().ok()),
+                            "shape" => lfo.shape = value!(val.parse().ok()),
+                            "target" => {
+                                lfo.target = Some(match val {
+                                    "off" => None,
+                                    _ => value!(val.parse().ok()),
+                                })
+                            }
+                            "retrig" => lfo.retrig = Some(val == "1" || val == "true"),
+                            _ => report(key, None),
+                        }
+                    } else {
+                        report(key, None);
+                    }
+                }
             }
         }
-        event.effective_name = match (&event.sound, &event.bank) {
+        event.resolve_name();
+        event
+    }
+
+    /// Sets `effective_name` from `sound` and `bank` (`sound_bank`).
+    pub(crate) fn resolve_name(&mut self) {
+        self.effective_name = match (&self.sound, &self.bank) {
             (Some(s), Some(b)) => Some(format!("{s}_{b}")),
             (Some(s), None) => Some(s.clone()),
             _ => None,
         };
-        event
     }
 }
 
@@ -495,6 +1454,27 @@ mod tests {
         assert!((end - 1.0).abs() < 1e-6);
     }
 
+    #[cfg(feature = "native")]
+    #[test]
+    fn energy_slices_follow_the_slice_map() {
+        // Silence, then a loud second half: energy cuts crowd into the end
+        let mut frames = vec![0.0f32; 24000];
+        let mut seed = 3u32;
+        frames.extend((0..24000).map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+        }));
+        let mut data = SampleData::new(frames, 1, 261.626);
+        let even = Event::parse("slice/4/sliceindex/1", SR);
+        let energy = Event::parse("slice/4/sliceindex/1/slicemode/energy", SR);
+        // No map yet: energy falls back to even cuts
+        assert_eq!(energy.resolve_range_in(&data), (0.25, 0.5));
+        data.slices = crate::sampling::SliceMap::compute(&data.frames, 1);
+        assert_eq!(even.resolve_range_in(&data), (0.25, 0.5));
+        let (b, _) = energy.resolve_range_in(&data);
+        assert!(b > 0.5, "{b}");
+    }
+
     #[test]
     fn begin_end_takes_precedence() {
         let e = Event::parse("begin/0.1/slice/8/pick/3", SR);
@@ -515,4 +1495,109 @@ mod tests {
             "floor should keep boundary times on distinct ticks"
         );
     }
+
+    #[test]
+    fn tokens_allow_quotes_escapes_and_spaces() {
+        let path = r#" s / "kits/808/bd.wav" /n/ 2 // bank/a\/b/x/"say \"hi\""/y/"" "#;
+        let tokens: Vec<_> = Tokens::new(path).collect();
+        assert_eq!(
+            tokens,
+            [
+                "s",
+                "kits/808/bd.wav",
+                "n",
+                "2",
+                "bank",
+                "a/b",
+                "x",
+                "say \"hi\"",
+                "y",
+                ""
+            ]
+        );
+        assert!(matches!(tokens[1], Cow::Borrowed(_)));
+
+        let e = Event::parse(r#"/s/"drums/kick"/note/ 60 /bank/"é/ü""#, SR);
+        assert_eq!(e.effective_name.as_deref(), Some("drums/kick_é/ü"));
+        assert!((e.freq.unwrap() - 261.63).abs() < 0.01);
+    }
+
+    #[test]
+    fn push_token_round_trips() {
+        for value in [
+            "kick",
+            "kits/808/bd.wav",
+            "say \"hi\"",
+            r"c:\samples",
+            " pad ",
+            "",
+            "a;b",
+        ] {
+            let mut path = String::from("s/");
+            push_token(&mut path, value);
+            let tokens: Vec<_> = Tokens::new(&path).collect();
+            assert_eq!(tokens, ["s", value]);
+            assert_eq!(split_events(&path).collect::<Vec<_>>(), [path.as_str()]);
+        }
+    }
+
+    #[test]
+    fn split_events_skips_quoted_and_escaped_separators() {
+        let events: Vec<_> = split_events(r#"/s/kick; /s/"a;b"/n/1 ;; s/c\;d;"#).collect();
+        assert_eq!(events, ["/s/kick", r#"/s/"a;b"/n/1"#, r"s/c\;d"]);
+        let parsed = Event::parse_all(r#"/s/kick; /s/"a;b"; s/c\;d"#, SR);
+        let sounds: Vec<_> = parsed.iter().map(|e| e.sound.as_deref()).collect();
+        assert_eq!(sounds, [Some("kick"), Some("a;b"), Some("c;d")]);
+        assert_eq!(Event::check("/s/kick/lfp/1; /s/hat/gain/x").len(), 2);
+    }
+
+    #[test]
+    fn check_reports_typos_and_bad_values() {
+        let warnings = Event::check("s/saw/lfp/800/lpf/8k/lfo1rat/2/lfo1target/off");
+        assert_eq!(
+            warnings,
+            [
+                ParseWarning::UnknownKey("lfp".into()),
+                ParseWarning::BadValue {
+                    key: "lpf".into(),
+                    value: "8k".into()
+                },
+                ParseWarning::UnknownKey("lfo1rat".into()),
+            ]
+        );
+        assert_eq!(warnings[1].token(), "8k");
+        assert!(Event::check("s/saw/lpf/800~200:0.5/insert1/chorus/doux/strict/1").is_empty());
+    }
+
+    #[test]
+    fn beat_values_take_dotted_and_triplet_suffixes() {
+        assert_eq!(beats("2b"), Some(2.0));
+        assert_eq!(beats("0.5bd"), Some(0.75));
+        assert_eq!(beats("1.5bt"), Some(1.0));
+        assert_eq!(beats("0.5"), None);
+        let event = Event::parse("/s/hat/gate/1bd/rep/0.5bt/div/0", SR);
+        assert_eq!(
+            (event.gatebeats, event.repbeats),
+            (Some(1.5), Some(1.0 / 3.0))
+        );
+        assert_eq!(event.div, None);
+    }
+
+    #[test]
+    fn command_arguments_are_taken_by_shape() {
+        let e = Event::parse("/doux/keep/4", SR);
+        assert_eq!(e.n.as_deref(), Some("4"));
+        let e = Event::parse("/doux/keep/orbit/1/gain/0.5", SR);
+        assert_eq!((e.n, e.orbit), (None, Some(1)));
+        let e = Event::parse("/doux/click/off/gain/0.5", SR);
+        assert_eq!(e.n.as_deref(), Some("off"));
+        let e = Event::parse("/doux/click/clickgain/0.5", SR);
+        assert_eq!((e.n, e.clickgain), (None, Some(0.5)));
+        let e = Event::parse("/doux/outmode/mono", SR);
+        assert_eq!(e.n.as_deref(), Some("mono"));
+        let e = Event::parse("/doux/release_id/lead/voice/2", SR);
+        assert_eq!((e.id.as_deref(), e.voice), (Some("lead"), Some(2)));
+        let e = Event::parse("/doux/release_id/voice/2", SR);
+        assert_eq!((e.id, e.voice), (None, Some(2)));
+    }
 }
@@ -1,47 +1,263 @@
+/// DSP invariant check for development, compiled in only with `debug-dsp`.
+macro_rules! dsp_assert {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "debug-dsp")]
+        assert!($($arg)+);
+    };
+}
+
+/// Panics if a stage left non-finite or runaway (> +36 dB) samples.
+macro_rules! dsp_check_bounded {
+    ($stage:expr, $samples:expr) => {
+        #[cfg(feature = "debug-dsp")]
+        {
+            let samples: &[f32] = &$samples;
+            assert!(
+                samples.iter().all(|s| s.is_finite() && s.abs() <= 64.0),
+                "{} output out of range: {samples:?}",
+                $stage
+            );
+        }
+    };
+}
+
 #[cfg(feature = "native")]
 pub mod audio;
+pub mod builder;
+mod calibrate;
 #[cfg(feature = "native")]
 pub mod cli_common;
+mod click;
 #[cfg(feature = "native")]
 pub mod config;
+#[cfg(feature = "native")]
+pub mod crash;
 pub mod dsp;
 pub mod effects;
 #[cfg(feature = "native")]
 pub mod error;
 pub mod event;
+pub mod master;
 #[cfg(feature = "native")]
 pub mod offline;
 pub mod orbit;
 #[cfg(feature = "native")]
 pub mod osc;
+pub mod preset;
 #[cfg(feature = "native")]
 mod recorder;
+pub mod routing;
 pub mod sampling;
 pub mod schedule;
+#[cfg(feature = "native")]
+pub mod session;
 #[cfg(feature = "soundfont")]
 pub mod soundfont;
+pub mod spatial;
+pub mod state;
 #[cfg(feature = "native")]
-pub mod telemetry;
+pub mod tap;
 #[cfg(feature = "native")]
+pub mod telemetry;
+mod test_signal;
 pub mod time;
+pub mod tuning;
 pub mod types;
 pub mod voice;
 #[cfg(target_arch = "wasm32")]
 mod wasm;
 
 pub enum AudioCmd {
-    Evaluate { path: String, tick: Option<u64> },
+    Evaluate {
+        path: String,
+        tick: Option<u64>,
+    },
     Hush,
     Panic,
+    /// Fills `state` with [`Engine::snapshot_into`] and sends it back on
+    /// `reply`. Build it with [`EngineState::reserved`].
+    Snapshot {
+        state: Box<EngineState>,
+        reply: std::sync::mpsc::Sender<Box<EngineState>>,
+    },
+    /// Runs [`Engine::restore_from`] and sends the spent state back on
+    /// `reply`, to be dropped there.
+    Restore {
+        state: Box<EngineState>,
+        reply: std::sync::mpsc::Sender<Box<EngineState>>,
+    },
+    /// Fills `buffer` with [`Engine::voice_details_into`] and sends it back
+    /// on `reply`. Give it room for [`MAX_VOICES`] voices.
+    VoiceDetails {
+        buffer: Vec<VoiceDetail>,
+        reply: std::sync::mpsc::Sender<Vec<VoiceDetail>>,
+    },
+    /// Registers a tuning read on the control side (`/doux/tuning/load`).
+    Tuning {
+        name: String,
+        tuning: Tuning,
+    },
+    /// Adds presets read on the control side (`/doux/preset/load`).
+    AddPresets(Box<Presets>),
+    /// Sends a copy of [`Engine::presets`] back on the channel.
+    Presets(std::sync::mpsc::Sender<Presets>),
+    /// Registers an insert effect whose processors were built on the
+    /// sending thread (see [`Engine::register_insert`]).
+    Insert(Box<InsertPool>),
+}
+
+#[cfg(feature = "native")]
+impl AudioCmd {
+    /// Hands an event string to `send`, doing the disk work it asks for on
+    /// the calling thread first: tuning files named by `/doux/tuning/load`
+    /// are read and parsed here and go out as [`AudioCmd::Tuning`], and
+    /// preset files named by `/doux/preset/load` as [`AudioCmd::AddPresets`].
+    /// The event itself follows unchanged, so session logs still replay it.
+    /// For `/doux/preset/write`, the engine's presets come back over
+    /// [`AudioCmd::Presets`] once the event has run, and the file is
+    /// written here.
+    pub fn route(path: String, tick: Option<u64>, mut send: impl FnMut(AudioCmd)) {
+        let mut writes = Vec::new();
+        for input in event::split_events(&path) {
+            let mut pairs = event::pairs(input);
+            match pairs.next() {
+                Some((key, cmd)) if key == "doux" && cmd == "tuning" => {
+                    Self::route_tuning(pairs, &mut send)
+                }
+                Some((key, cmd)) if key == "doux" && cmd == "preset" => match pairs.next() {
+                    Some((action, file)) if action == "load" => {
+                        match Presets::load(std::path::Path::new(&*file)) {
+                            Ok(presets) => send(AudioCmd::AddPresets(Box::new(presets))),
+                            Err(e) => eprintln!("Failed to load presets {file}: {e}"),
+                        }
+                    }
+                    Some((action, file)) if action == "write" => writes.push(file.into_owned()),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        send(AudioCmd::Evaluate { path, tick });
+        if writes.is_empty() {
+            return;
+        }
+        let (reply, presets) = std::sync::mpsc::channel();
+        send(AudioCmd::Presets(reply));
+        let Ok(presets) = presets.recv_timeout(std::time::Duration::from_secs(2)) else {
+            eprintln!("Failed to write presets: the engine did not answer");
+            return;
+        };
+        for file in writes {
+            if let Err(e) = presets.write(std::path::Path::new(&file)) {
+                eprintln!("Failed to write presets {file}: {e}");
+            }
+        }
+    }
+
+    /// Reads the Scala file of a `/doux/tuning/load` event, past its
+    /// `doux/tuning` pair.
+    fn route_tuning<'a>(
+        pairs: impl Iterator<Item = (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>,
+        send: &mut impl FnMut(AudioCmd),
+    ) {
+        let (mut file, mut name) = (None, None);
+        for (key, value) in pairs {
+            match &*key {
+                "load" => file = Some(value),
+                "tuning" => name = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+        let Some(file) = file else {
+            return;
+        };
+        let file = std::path::Path::new(&*file);
+        let name = name.unwrap_or_else(|| {
+            file.file_stem().map_or_else(
+                || file.display().to_string(),
+                |s| s.to_string_lossy().into_owned(),
+            )
+        });
+        match Tuning::read_scl(file) {
+            Ok(tuning) => send(AudioCmd::Tuning { name, tuning }),
+            Err(e) => eprintln!("Failed to load tuning {}: {e}", file.display()),
+        }
+    }
+}
+
+/// Reply to an event carrying an `ack` token, sent once the event is
+/// processed (or dropped as late).
+#[cfg(feature = "native")]
+#[derive(Clone, Debug)]
+pub struct EventAck {
+    pub token: u64,
+    /// Stable voice ID, or `None` if the event was dropped or got no voice.
+    pub voice_id: Option<u32>,
+    /// Resolved sample name (`folder/n`) when the voice plays a sample.
+    pub sample: Option<Arc<str>>,
+}
+
+/// What happened to a voice in a [`VoiceEvent`].
+#[cfg(feature = "native")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoiceEventKind {
+    /// A new voice was allocated and started.
+    Trigger,
+    /// A live voice was restarted in place (cut group reuse or `reset`).
+    Respawn,
+    /// The voice finished or was killed; its ID is no longer valid.
+    Free,
+}
+
+/// Voice lifecycle notification, sent at the tick it happens.
+#[cfg(feature = "native")]
+#[derive(Clone, Debug)]
+pub struct VoiceEvent {
+    pub kind: VoiceEventKind,
+    pub voice_id: u32,
+    pub tick: u64,
+    pub source: Source,
+    /// Resolved sample name when `source` plays a sample.
+    pub sample: Option<Arc<str>>,
+    pub freq: f32,
+    /// `gain × velocity`.
+    pub amp: f32,
+    pub orbit: usize,
 }
 
+#[cfg(feature = "native")]
+impl VoiceEvent {
+    /// Sample name if any, else the source's name.
+    pub fn sound(&self) -> &str {
+        self.sample
+            .as_deref()
+            .unwrap_or(self.source.info().module.name)
+    }
+}
+
+use calibrate::LatencyProbe;
+use click::Click;
 use dsp::{fast_tanh_f32, init_envelope};
 use event::Event;
 
 use orbit::Orbit;
 
+/// Length of the fade [`Engine::panic`] clears the voices under.
+pub const PANIC_FADE_MS: f32 = 8.0;
+
+/// Orbit index that [`Engine::apply_orbit_params`] maps to the cue orbit.
+const CUE_ORBIT: usize = MAX_ORBITS;
+
+pub use builder::EventBuilder;
+use effects::InsertRegistry;
+pub use effects::{InsertEffect, InsertPool, InsertProcessor, INSERT_SLOTS};
+use master::Master;
+pub use preset::Presets;
+#[cfg(feature = "native")]
+pub use recorder::KeepRequest;
 #[cfg(feature = "native")]
-use recorder::Recorder;
+use recorder::{RecSource, Recorder, RetroBuffer};
+use routing::{OutRoute, RouteMatrix};
 #[cfg(feature = "native")]
 use sampling::RegistrySample;
 use sampling::SampleEntry;
@@ -52,17 +268,34 @@ pub use sampling::{SampleData, SampleRegistry};
 #[cfg(not(feature = "native"))]
 use sampling::{SampleInfo, SamplePool};
 use schedule::Schedule;
-#[cfg(feature = "native")]
+use spatial::SpatialBus;
+pub use state::{EngineState, VoiceDetail};
 use std::sync::Arc;
 #[cfg(feature = "native")]
 pub use telemetry::EngineMetrics;
 #[cfg(feature = "native")]
 use telemetry::ProfilePhase;
+use test_signal::TestSignal;
+use tuning::Tuning;
 #[cfg(not(feature = "native"))]
 use types::WASM_BLOCK_SIZE;
-use types::{ModuleInfo, Source, CHANNELS, MAX_ORBITS};
+use types::{
+    ModuleInfo, OutMode, ReverbQuality, Source, CHANNELS, DEFAULT_ORBITS, MAX_ORBITS, MAX_RATCHET,
+    MAX_VOICES,
+};
 use voice::modulation::ParamId;
-use voice::{modulation, Voice, VoiceParams};
+use voice::{modulation, SourceRegistry, Voice, VoiceParams};
+pub use voice::{SourceContext, SourceRenderer, CUSTOM_STATE_LEN};
+
+/// Gain of a flam's grace hit relative to the main hit.
+const FLAM_GAIN: f32 = 0.5;
+/// Voices that can fade out at once after a cut group or restart took
+/// their slot; beyond that they stop with the old 1ms declick.
+const FADE_VOICES: usize = 8;
+/// Default `cutfade`, in seconds.
+const DEFAULT_CUTFADE: f32 = 0.005;
+/// How far behind the clock, in seconds, a scheduled event still plays.
+const LATE_TOLERANCE: f64 = 0.02;
 
 /// All modules in the engine: sources, effects, filters, modulation.
 ///
@@ -97,17 +330,117 @@ struct GmResolved {
 // Master soft-clip: plain tanh. Identity slope at origin, monotonic, bounded by ±1.
 // Loses ~2.4 dB at unity input — the musical price of analog-style saturation.
 #[inline]
+/// Position of ratchet hit `k`, in straight hit spacings, when steps of
+/// `grid` hits take turns lasting `2 * swing` and `2 * (1 - swing)` steps.
+fn swung(k: usize, swing: f32, grid: usize) -> f32 {
+    let (step, hit) = (k / grid, k % grid);
+    let long = 2.0 * swing;
+    let (start, len) = if step % 2 == 0 {
+        (step as f32, long)
+    } else {
+        ((step - 1) as f32 + long, 2.0 - long)
+    };
+    (start + hit as f32 / grid as f32 * len) * grid as f32
+}
+
 fn soft_clip_sample(input: f32) -> f32 {
     fast_tanh_f32(input)
 }
 
+/// Voice pool with every voice up to [`MAX_VOICES`] (or `max_voices`, if
+/// higher) built up front, so raising the limit later never allocates.
+fn voice_pool(max_voices: usize) -> Vec<Voice> {
+    (0..max_voices.max(MAX_VOICES))
+        .map(|_| Voice::default())
+        .collect()
+}
+
+/// Builds all [`MAX_ORBITS`] orbits: the first `DEFAULT_ORBITS` active,
+/// the rest spare, with room for every orbit in both lists.
+fn orbit_bank(sr: f32) -> (Vec<Orbit>, Vec<Orbit>) {
+    let mut orbits = Vec::with_capacity(MAX_ORBITS);
+    let mut spare = Vec::with_capacity(MAX_ORBITS);
+    orbits.extend((0..DEFAULT_ORBITS).map(|_| Orbit::new(sr)));
+    spare.extend((DEFAULT_ORBITS..MAX_ORBITS).map(|_| Orbit::new(sr)));
+    (orbits, spare)
+}
+
+/// Adds a voice's output to its orbit (or the cue orbit) and its sends.
+fn mix_voice(orbits: &mut [Orbit], cue: &mut Orbit, spatial: &mut SpatialBus, v: &Voice) {
+    let num_orbits = orbits.len();
+    let orbit = if v.params.cue {
+        cue
+    } else {
+        &mut orbits[v.params.orbit % num_orbits]
+    };
+    let oi = v.params.orbit % num_orbits;
+    match v.params.pan2d {
+        // The dry signal goes round the ring; the orbit's effects still
+        // hear it through the send
+        Some(azimuth) if !v.params.cue && spatial.on_ring(oi) => {
+            let mono = (v.ch[0] + v.ch[1]) * std::f32::consts::FRAC_1_SQRT_2;
+            spatial.add(oi, azimuth, v.params.pan2dspread, mono);
+            for c in 0..CHANNELS {
+                orbit.send[c] += v.ch[c];
+            }
+        }
+        // Stems and routed orbits keep their channels
+        Some(azimuth) if !v.params.cue => {
+            let mono = (v.ch[0] + v.ch[1]) * std::f32::consts::FRAC_1_SQRT_2;
+            let frame = spatial.fold(azimuth, v.params.pan2dspread, mono);
+            for (c, s) in frame.into_iter().enumerate() {
+                orbit.add_dry(c, s);
+            }
+        }
+        _ => {
+            for c in 0..CHANNELS {
+                orbit.add_dry(c, v.ch[c]);
+            }
+        }
+    }
+    if !v.params.cue {
+        for (&o, &level) in v.params.send_orbits.iter().zip(&v.params.send_levels) {
+            let o = o % num_orbits;
+            if level > 0.0 && o != v.params.orbit % num_orbits {
+                let send = &mut orbits[o].send;
+                send[0] += v.ch[0] * level;
+                send[1] += v.ch[1] * level;
+            }
+        }
+    }
+}
+
 pub struct Engine {
     pub sr: f32,
     pub isr: f32,
     pub max_voices: usize,
     pub voices: Vec<Voice>,
     pub active_voices: usize,
-    pub orbits: [Orbit; MAX_ORBITS],
+    /// Voices fading out under the ones that replaced them (`cutfade`).
+    fading: Vec<Voice>,
+    active_fades: usize,
+    /// Effect buses, `DEFAULT_ORBITS` unless resized with
+    /// [`set_orbit_count`](Self::set_orbit_count).
+    pub orbits: Vec<Orbit>,
+    /// Orbits past the current count, built up front so changing the
+    /// count never allocates. Last entry is the next one to come back.
+    spare_orbits: Vec<Orbit>,
+    /// Pre-listen orbit fed only by `cue` voices.
+    pub cue: Orbit,
+    /// Output pair the cue orbit plays on (`None` = cue voices are muted).
+    /// The pair is reserved: regular orbits spread over the other pairs.
+    pub cue_out: Option<usize>,
+    pub cue_gain: f32,
+    /// Explicit orbit-to-channel routes; routed orbits skip the default
+    /// placement.
+    pub routes: RouteMatrix,
+    /// Pairs (default) or multi-out, set with `/doux/outmode`.
+    pub out_mode: OutMode,
+    /// Voices panned across every output pair with `pan2d`.
+    spatial: SpatialBus,
+    pub master: Master,
+    /// Output ramp of a [`fade_out`](Self::fade_out) in progress.
+    fade: master::Fade,
     pub schedule: Schedule,
     pub time: f64,
     pub tick: u64,
@@ -119,6 +452,20 @@ pub struct Engine {
     pub sample_pool: SamplePool,
     #[cfg(not(feature = "native"))]
     pub samples: Vec<SampleInfo>,
+    /// Names for `samples`, parallel by index (empty for unnamed uploads).
+    #[cfg(not(feature = "native"))]
+    pub sample_names: Vec<String>,
+    /// Waveform peaks for `samples`, parallel by index.
+    #[cfg(not(feature = "native"))]
+    pub sample_overviews: Vec<sampling::WaveformOverview>,
+    #[cfg(not(feature = "native"))]
+    pending_sample_name: Option<String>,
+    /// Rate of the pending chunked upload.
+    #[cfg(not(feature = "native"))]
+    pending_sample_rate: f32,
+    /// Interpolation for uploads at another rate than the engine's.
+    #[cfg(not(feature = "native"))]
+    pub resample_quality: dsp::ResampleQuality,
     // Sample index (native uses registry, WASM uses pool)
     pub sample_index: Vec<SampleEntry>,
     // Lock-free sample registry (native only)
@@ -130,13 +477,39 @@ pub struct Engine {
     recorder: Recorder,
     #[cfg(feature = "native")]
     orbit_rec_bus: Vec<f32>,
+    #[cfg(feature = "native")]
+    retro: RetroBuffer,
+    #[cfg(feature = "native")]
+    spectrum: dsp::SpectrumAnalyzer,
+    #[cfg(feature = "native")]
+    keep_tx: Option<crossbeam_channel::Sender<KeepRequest>>,
+    #[cfg(feature = "native")]
+    taps: Option<tap::Taps>,
     // Telemetry (native only)
     #[cfg(feature = "native")]
     pub metrics: Arc<EngineMetrics>,
     #[cfg(feature = "soundfont")]
     pub gm_bank: Option<soundfont::GmBank>,
     pub input_channels: usize,
+    /// Round-trip latency from the outputs back to the live input, in
+    /// frames, as measured by the last `/doux/calibrate`.
+    pub input_latency: Option<u32>,
     voice_seed: u32,
+    next_voice_id: u32,
+    custom_sources: SourceRegistry,
+    insert_effects: InsertRegistry,
+    test_signal: TestSignal,
+    calibration: LatencyProbe,
+    click: Click,
+    tempo: time::Tempo,
+    tunings: Vec<(String, Tuning)>,
+    /// Variables set with `set name value`, substituted for `$name`.
+    vars: Vec<(String, String)>,
+    presets: Presets,
+    #[cfg(feature = "native")]
+    ack_tx: Option<crossbeam_channel::Sender<EventAck>>,
+    #[cfg(feature = "native")]
+    voice_event_tx: Option<crossbeam_channel::Sender<VoiceEvent>>,
     #[cfg(feature = "native")]
     load_gate: bool,
     #[cfg(feature = "native")]
@@ -157,15 +530,26 @@ impl Engine {
     pub fn new_with_channels(sample_rate: f32, output_channels: usize, max_voices: usize) -> Self {
         dsp::fft::init_twiddles();
 
-        let orbits: [Orbit; MAX_ORBITS] = std::array::from_fn(|_| Orbit::new(sample_rate));
+        let (orbits, spare_orbits) = orbit_bank(sample_rate);
 
         Self {
             sr: sample_rate,
             isr: 1.0 / sample_rate,
             max_voices,
-            voices: vec![Voice::default(); max_voices],
+            voices: voice_pool(max_voices),
             active_voices: 0,
+            fading: (0..FADE_VOICES).map(|_| Voice::default()).collect(),
+            active_fades: 0,
             orbits,
+            spare_orbits,
+            cue: Orbit::new(sample_rate),
+            cue_out: None,
+            cue_gain: 1.0,
+            routes: RouteMatrix::default(),
+            out_mode: OutMode::Pairs,
+            spatial: SpatialBus::new(output_channels),
+            master: Master::new(sample_rate, output_channels),
+            fade: master::Fade::default(),
             schedule: Schedule::new(),
             time: 0.0,
             tick: 0,
@@ -174,9 +558,25 @@ impl Engine {
             output: vec![0.0; WASM_BLOCK_SIZE * output_channels],
             sample_pool: SamplePool::new(),
             samples: Vec::with_capacity(256),
+            sample_names: Vec::with_capacity(256),
+            sample_overviews: Vec::with_capacity(256),
+            pending_sample_name: None,
+            pending_sample_rate: sample_rate,
+            resample_quality: dsp::ResampleQuality::default(),
             sample_index: Vec::new(),
             input_channels: 2,
+            input_latency: None,
             voice_seed: 123456789,
+            next_voice_id: 1,
+            custom_sources: SourceRegistry::default(),
+            insert_effects: InsertRegistry::default(),
+            test_signal: TestSignal::new(sample_rate),
+            calibration: LatencyProbe::new(sample_rate),
+            click: Click::new(sample_rate),
+            tempo: time::Tempo::new(sample_rate),
+            tunings: Vec::new(),
+            vars: Vec::new(),
+            presets: Presets::default(),
         }
     }
 
@@ -209,15 +609,26 @@ impl Engine {
         let registry = Arc::new(SampleRegistry::new());
         let loader = SampleLoader::new(Arc::clone(&registry));
 
-        let orbits: [Orbit; MAX_ORBITS] = std::array::from_fn(|_| Orbit::new(sample_rate));
+        let (orbits, spare_orbits) = orbit_bank(sample_rate);
 
         Self {
             sr: sample_rate,
             isr: 1.0 / sample_rate,
             max_voices,
-            voices: vec![Voice::default(); max_voices],
+            voices: voice_pool(max_voices),
             active_voices: 0,
+            fading: (0..FADE_VOICES).map(|_| Voice::default()).collect(),
+            active_fades: 0,
             orbits,
+            spare_orbits,
+            cue: Orbit::new(sample_rate),
+            cue_out: None,
+            cue_gain: 1.0,
+            routes: RouteMatrix::default(),
+            out_mode: OutMode::Pairs,
+            spatial: SpatialBus::new(output_channels),
+            master: Master::new(sample_rate, output_channels),
+            fade: master::Fade::default(),
             schedule: Schedule::new(),
             time: 0.0,
             tick: 0,
@@ -229,11 +640,28 @@ impl Engine {
             sample_loader: loader,
             recorder: Recorder::new(sample_rate),
             orbit_rec_bus: vec![0.0; MAX_ORBITS * block_size * CHANNELS],
+            retro: RetroBuffer::new(0.0, sample_rate),
+            spectrum: dsp::SpectrumAnalyzer::new(),
+            keep_tx: None,
+            taps: None,
             metrics,
             #[cfg(feature = "soundfont")]
             gm_bank: None,
             input_channels: 2,
+            input_latency: None,
             voice_seed: 123456789,
+            next_voice_id: 1,
+            custom_sources: SourceRegistry::default(),
+            insert_effects: InsertRegistry::default(),
+            test_signal: TestSignal::new(sample_rate),
+            calibration: LatencyProbe::new(sample_rate),
+            click: Click::new(sample_rate),
+            tempo: time::Tempo::new(sample_rate),
+            tunings: Vec::new(),
+            vars: Vec::new(),
+            presets: Presets::default(),
+            ack_tx: None,
+            voice_event_tx: None,
             load_gate: false,
             engine_start_unix_micros: now_unix_micros(),
         }
@@ -247,6 +675,24 @@ impl Engine {
         }
     }
 
+    /// Routes [`EventAck`]s for events carrying an `ack` token to `tx`.
+    ///
+    /// Use a bounded channel: acks are sent with `try_send` from the audio
+    /// thread and silently dropped when the channel is full.
+    #[cfg(feature = "native")]
+    pub fn set_ack_sender(&mut self, tx: crossbeam_channel::Sender<EventAck>) {
+        self.ack_tx = Some(tx);
+    }
+
+    /// Streams a [`VoiceEvent`] for every voice trigger, respawn and free,
+    /// e.g. to drive UI highlighting without polling.
+    ///
+    /// Same contract as [`Self::set_ack_sender`]: bounded channel, lossy.
+    #[cfg(feature = "native")]
+    pub fn set_voice_event_sender(&mut self, tx: crossbeam_channel::Sender<VoiceEvent>) {
+        self.voice_event_tx = Some(tx);
+    }
+
     #[cfg(feature = "soundfont")]
     pub fn load_soundfont(&mut self, path: &std::path::Path) -> Result<(), String> {
         let (samples, bank) = soundfont::load_sf2(path, self.sr)?;
@@ -273,12 +719,92 @@ impl Engine {
 
     #[cfg(not(feature = "native"))]
     pub fn load_sample(&mut self, samples: &[f32], channels: u8, freq: f32) -> Option<usize> {
+        self.load_sample_at(samples, channels, freq, self.sr)
+    }
+
+    /// Loads samples recorded at `sample_rate`, resampled to the engine rate.
+    #[cfg(not(feature = "native"))]
+    pub fn load_sample_at(
+        &mut self,
+        samples: &[f32],
+        channels: u8,
+        freq: f32,
+        sample_rate: f32,
+    ) -> Option<usize> {
         let info = self.sample_pool.add(samples, channels, freq)?;
+        let info =
+            self.sample_pool
+                .resample_last(info, sample_rate, self.sr, self.resample_quality);
         let idx = self.samples.len();
         self.samples.push(info);
+        self.sample_names.push(String::new());
+        self.push_overview(info);
         Some(idx)
     }
 
+    /// Starts a chunked sample upload under `name`. Samples sharing a name
+    /// are addressed with `n`, in upload order.
+    #[cfg(not(feature = "native"))]
+    pub fn begin_sample(&mut self, name: &str, frames: u32, channels: u8, freq: f32) -> bool {
+        self.begin_sample_at(name, frames, channels, freq, self.sr)
+    }
+
+    /// Like [`Self::begin_sample`] for audio at `sample_rate`; the upload is
+    /// resampled to the engine rate when it ends.
+    #[cfg(not(feature = "native"))]
+    pub fn begin_sample_at(
+        &mut self,
+        name: &str,
+        frames: u32,
+        channels: u8,
+        freq: f32,
+        sample_rate: f32,
+    ) -> bool {
+        self.pending_sample_name = None;
+        if !self.sample_pool.begin(frames, channels, freq) {
+            return false;
+        }
+        self.pending_sample_name = Some(name.to_string());
+        self.pending_sample_rate = sample_rate;
+        true
+    }
+
+    /// Appends interleaved samples to the upload started by [`Self::begin_sample`].
+    #[cfg(not(feature = "native"))]
+    pub fn append_sample_chunk(&mut self, chunk: &[f32]) -> usize {
+        self.sample_pool.append(chunk)
+    }
+
+    /// Finishes the pending upload and returns its pool index.
+    #[cfg(not(feature = "native"))]
+    pub fn end_sample(&mut self) -> Option<usize> {
+        let info = self.sample_pool.finish()?;
+        let info = self.sample_pool.resample_last(
+            info,
+            self.pending_sample_rate,
+            self.sr,
+            self.resample_quality,
+        );
+        let idx = self.samples.len();
+        self.samples.push(info);
+        self.sample_names
+            .push(self.pending_sample_name.take().unwrap_or_default());
+        self.push_overview(info);
+        Some(idx)
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn push_overview(&mut self, info: SampleInfo) {
+        let len = info.frames as usize * info.channels as usize;
+        let pcm = &self.sample_pool.data[info.offset..info.offset + len];
+        self.sample_overviews
+            .push(sampling::WaveformOverview::compute(
+                pcm,
+                info.channels,
+                sampling::OVERVIEW_POINTS,
+            ));
+    }
+
     /// Look up sample folder/n (e.g., "wave_tek/3"). `n` wraps via modulo over the folder count.
     /// Walks the index twice (count, then find) — but each walk is O(n) and shared by all callers.
     #[cfg(feature = "native")]
@@ -362,28 +888,178 @@ impl Engine {
 
     /// Get a loaded sample index (WASM only - uses legacy pool)
     #[cfg(not(feature = "native"))]
-    fn get_or_load_sample(&mut self, name: &str, _n: usize) -> Option<usize> {
+    fn get_or_load_sample(&mut self, name: &str, n: usize) -> Option<usize> {
         // For WASM, treat `name` as numeric index if sample_index is empty
         if self.sample_index.is_empty() {
-            let idx: usize = name.parse().ok()?;
-            if idx < self.samples.len() {
-                return Some(idx);
+            if let Ok(idx) = name.parse::<usize>() {
+                return (idx < self.samples.len()).then_some(idx);
             }
         }
-        None
+        // Otherwise match chunk-uploaded names, picking the n-th (wrapping)
+        let count = self.sample_names.iter().filter(|s| *s == name).count();
+        if count == 0 {
+            return None;
+        }
+        self.sample_names
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| *s == name)
+            .nth(n % count)
+            .map(|(i, _)| i)
     }
 
     /// Parse and dispatch — only call this off the RT thread.
+    ///
+    /// `;` separates several events, dispatched together; the result is
+    /// then the voice of the last one.
     pub fn evaluate(&mut self, input: &str) -> Option<usize> {
-        let event = Event::parse(input, self.sr);
-        self.dispatch_event(event)
+        let mut last = None;
+        for event in event::split_events(input) {
+            if let Some(event) = self.parse_event(event) {
+                last = self.dispatch_event(event);
+            }
+        }
+        last
+    }
+
+    /// Like [`evaluate`](Self::evaluate), scheduling events that carry no
+    /// `tick`/`time` of their own at `tick`.
+    pub fn evaluate_at(&mut self, input: &str, tick: Option<u64>) {
+        for part in event::split_events(input) {
+            let Some(mut event) = self.parse_event(part) else {
+                continue;
+            };
+            if event.tick.is_none() {
+                event.tick = tick;
+            }
+            self.dispatch_event(event);
+        }
+    }
+
+    /// Runs a command sent by the control side (see [`AudioCmd`]).
+    pub fn apply(&mut self, cmd: AudioCmd) {
+        match cmd {
+            AudioCmd::Evaluate { path, tick } => self.evaluate_at(&path, tick),
+            AudioCmd::Hush => self.hush(),
+            AudioCmd::Panic => self.panic(),
+            AudioCmd::Snapshot { mut state, reply } => {
+                self.snapshot_into(&mut state);
+                let _ = reply.send(state);
+            }
+            AudioCmd::Restore { mut state, reply } => {
+                self.restore_from(&mut state);
+                let _ = reply.send(state);
+            }
+            AudioCmd::VoiceDetails { mut buffer, reply } => {
+                self.voice_details_into(&mut buffer);
+                let _ = reply.send(buffer);
+            }
+            AudioCmd::Tuning { name, tuning } => self.set_tuning(&name, tuning),
+            AudioCmd::AddPresets(presets) => self.presets.extend(*presets),
+            AudioCmd::Presets(reply) => {
+                let _ = reply.send(self.presets.clone());
+            }
+            AudioCmd::Insert(pool) => self.insert_effects.register(*pool),
+        }
+    }
+
+    /// Like [`evaluate`](Self::evaluate), but returns the voice of every
+    /// event that started or updated one.
+    pub fn evaluate_all(&mut self, input: &str) -> Vec<usize> {
+        event::split_events(input)
+            .filter_map(|event| {
+                let event = self.parse_event(event)?;
+                self.dispatch_event(event)
+            })
+            .collect()
+    }
+
+    /// Parses one event after substituting its `$name` variables and
+    /// putting its `preset` under it. A `set name value` line stores the
+    /// variable instead and gives `None`, as does a `/doux/preset` command.
+    pub fn parse_event(&mut self, input: &str) -> Option<Event> {
+        if let Some(rest) = input.trim().strip_prefix("set ") {
+            let rest = rest.trim();
+            let (name, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let value = event::substitute(value.trim(), &self.vars).into_owned();
+            self.set_var(name, &value);
+            return None;
+        }
+        let input = event::substitute(input, &self.vars);
+        let event = Event::parse(&input, self.sr);
+        if event.cmd.as_deref() == Some("preset") {
+            self.handle_preset(&input);
+            return None;
+        }
+        let expanded = (event.preset.as_deref()).and_then(|name| self.presets.expand(name, &input));
+        match expanded {
+            Some(expanded) => Some(Event::parse(&expanded, self.sr)),
+            None => Some(event),
+        }
+    }
+
+    /// Sets the variable `$name` stands for in later events; an empty
+    /// `value` removes it.
+    pub fn set_var(&mut self, name: &str, value: &str) {
+        let name = name.trim_start_matches('$');
+        if value.is_empty() {
+            self.vars.retain(|(n, _)| n != name);
+            return;
+        }
+        match self.vars.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.vars.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// `/doux/preset`: `save/<name>` stores the pairs after the name and
+    /// `remove/<name>` drops a preset. `load/<path>` and `write/<path>` touch
+    /// the disk, so [`AudioCmd::route`] handles them before the event gets
+    /// here.
+    fn handle_preset(&mut self, input: &str) {
+        // Past `doux/preset`
+        let mut pairs = event::pairs(input).skip(1);
+        let Some((action, arg)) = pairs.next() else {
+            return;
+        };
+        match &*action {
+            "save" => {
+                let pairs = pairs
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+                self.presets.save(&arg, pairs);
+            }
+            "remove" => {
+                self.presets.remove(&arg);
+            }
+            _ => {}
+        }
+    }
+
+    /// Presets that `preset/<name>` plays; see [`preset`].
+    pub fn presets(&self) -> &Presets {
+        &self.presets
+    }
+
+    /// For embedders editing presets in place. `/doux/preset/save` does the
+    /// same from the audio thread, and a new name allocates there too.
+    pub fn presets_mut(&mut self) -> &mut Presets {
+        &mut self.presets
+    }
+
+    pub fn var(&self, name: &str) -> Option<&str> {
+        self.vars
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
     }
 
     /// Dispatch a pre-parsed event.
     ///
     /// `play` events are RT-safe: sample note-on now reuses pre-owned metadata and
     /// only clones `Arc` handles on the callback path. `rec` remains non-RT.
-    pub fn dispatch_event(&mut self, event: Event) -> Option<usize> {
+    pub fn dispatch_event(&mut self, mut event: Event) -> Option<usize> {
+        Self::apply_tempo(&self.tempo, &mut event);
         let cmd = event.cmd.as_deref().unwrap_or("play");
 
         match cmd {
@@ -393,29 +1069,156 @@ impl Engine {
                 self.handle_rec(&event);
                 None
             }
+            #[cfg(feature = "native")]
+            "keep" => {
+                self.handle_keep(&event);
+                None
+            }
+            #[cfg(feature = "native")]
+            "record" => {
+                self.handle_record(&event);
+                None
+            }
             "hush" => {
                 self.hush();
                 None
             }
+            "master" => {
+                self.apply_master_params(&event);
+                None
+            }
+            "orbit" | "automate" => {
+                let idx = event.orbit.unwrap_or(0) % self.orbits.len();
+                self.automate_orbit(idx, &event);
+                self.apply_orbit_params(idx, &event);
+                // Voice events set the voice compressor; only orbit
+                // commands reach the bus one, timing included
+                let cp = &mut self.orbits[idx].dynamics_params;
+                if let Some(threshold) = event.compthresh {
+                    cp.threshold = threshold;
+                }
+                if let Some(ratio) = event.compratio {
+                    cp.ratio = ratio.max(1.0);
+                }
+                if let Some(makeup) = event.compmakeup {
+                    cp.makeup = makeup;
+                }
+                if let Some(attack) = event.compattack {
+                    cp.attack = attack;
+                }
+                if let Some(release) = event.comprelease {
+                    cp.release = release;
+                }
+                None
+            }
+            "test" => {
+                let value = event.n.as_deref().and_then(|n| n.parse::<f32>().ok());
+                let kind = event.sound.as_deref().unwrap_or("off");
+                self.test_signal.start(kind, value, event.out);
+                None
+            }
+            "set" => {
+                if let Some(name) = event.sound.as_deref() {
+                    self.set_var(name, event.n.as_deref().unwrap_or(""));
+                }
+                None
+            }
+            "tuning" => {
+                self.handle_tuning(&event);
+                None
+            }
+            "seed" => {
+                if let Some(seed) = event.seed {
+                    self.set_seed(seed);
+                }
+                None
+            }
+            "bpm" => {
+                if let Some(bpm) = event.bpm {
+                    self.set_bpm(bpm);
+                }
+                None
+            }
+            "calibrate" => {
+                self.calibration.start(event.out, event.inchan);
+                None
+            }
+            "click" => {
+                if let Some(on) = event.n.as_deref() {
+                    self.click.set_active(!matches!(on, "0" | "off" | "false"));
+                }
+                if let Some(gain) = event.clickgain {
+                    self.click.gain = gain.max(0.0);
+                }
+                if let Some(bpm) = event.clickbpm {
+                    self.click.bpm = (bpm > 0.0).then_some(bpm);
+                }
+                if let Some(beats) = event.clickbeats {
+                    self.click.beats = beats;
+                }
+                if let Some(cue) = event.cue {
+                    self.click.cue = cue;
+                }
+                if let Some(orbit) = event.orbit {
+                    self.click.orbit = orbit;
+                    self.click.cue = event.cue.unwrap_or(false);
+                }
+                None
+            }
             "panic" => {
                 self.panic();
                 None
             }
             "reset" => {
-                self.panic();
+                self.panic_now();
+                self.master.reset();
+                for orbit in &mut self.orbits {
+                    orbit.clear_lanes();
+                }
                 self.schedule.clear();
                 self.time = 0.0;
                 self.tick = 0;
+                self.tempo.reset();
                 None
             }
-            "release" => {
+            "release" | "release_id" => {
                 if let Some(v) = event.voice {
                     if v < self.active_voices {
                         self.voices[v].force_release();
                     }
                 }
+                if let Some(ref name) = event.id {
+                    let key = voice::group_key(name);
+                    for i in 0..self.active_voices {
+                        if self.voices[i].params.group == Some(key) {
+                            self.voices[i].force_release();
+                        }
+                    }
+                }
                 None
             }
+            "update" | "retrigger" => {
+                let key = voice::group_key(event.id.as_deref()?);
+                let mut last = None;
+                for i in 0..self.active_voices {
+                    if self.voices[i].params.group != Some(key) {
+                        continue;
+                    }
+                    self.update_voice_params(i, &event);
+                    self.voices[i].ensure_effects();
+                    if cmd == "retrigger" {
+                        let start = event.tick.unwrap_or(self.tick);
+                        let v = &mut self.voices[i];
+                        let gate = event.gate.unwrap_or(v.params.gate);
+                        v.gate_end = event
+                            .endtick
+                            .or_else(|| (gate > 0.0).then(|| start + (gate * self.sr) as u64));
+                        v.retrigger();
+                    }
+                    last = Some(i);
+                }
+                last
+            }
             "hush_endless" => {
                 for i in 0..self.active_voices {
                     if self.voices[i].params.gate == 0.0 {
@@ -424,9 +1227,59 @@ impl Engine {
                 }
                 None
             }
+            "route" => {
+                let idx = event.orbit.unwrap_or(0);
+                // A pair is a stereo route to its two channels
+                match event.outpair.map(usize::try_from) {
+                    Some(Ok(pair)) => {
+                        self.routes.set(OutRoute::pair(idx, pair * CHANNELS));
+                    }
+                    Some(Err(_)) => self.routes.clear_orbit(idx),
+                    None => {}
+                }
+                match event.outch.map(usize::try_from) {
+                    Some(Ok(left)) => {
+                        let mono = event.outmono.unwrap_or(false);
+                        let route = OutRoute {
+                            orbit: idx,
+                            left,
+                            right: event.outch2.unwrap_or(if mono { left } else { left + 1 }),
+                            gain: event.outgain.unwrap_or(1.0).max(0.0),
+                            mono,
+                        };
+                        if event.outadd.unwrap_or(false) {
+                            self.routes.add(route);
+                        } else {
+                            self.routes.set(route);
+                        }
+                    }
+                    Some(Err(_)) => self.routes.clear_orbit(idx),
+                    None => {}
+                }
+                None
+            }
+            "outmode" => {
+                if let Some(mode) = event.n.as_deref().and_then(|n| n.parse().ok()) {
+                    self.out_mode = mode;
+                }
+                None
+            }
+            "orbits" => {
+                if let Some(n) = event.n.as_deref().and_then(|n| n.parse::<f32>().ok()) {
+                    self.set_orbit_count(n as usize);
+                }
+                None
+            }
+            "max_voices" => {
+                if let Some(n) = event.n.as_deref().and_then(|n| n.parse::<f32>().ok()) {
+                    self.set_max_voices(n as usize);
+                }
+                None
+            }
             "reset_time" => {
                 self.time = 0.0;
                 self.tick = 0;
+                self.tempo.reset();
                 None
             }
             "reset_schedule" => {
@@ -438,16 +1291,201 @@ impl Engine {
     }
 
     fn play_event(&mut self, mut event: Event) -> Option<usize> {
+        self.apply_tuning(&mut event);
+        if let Some(rep) = event.repbeats.take().map(|b| self.tempo.secs(b)) {
+            event.rep = Some(rep);
+        }
+        if event.div.is_some() || event.mult.is_some() {
+            let rep = event.rep.unwrap_or_else(|| self.tempo.secs(1.0));
+            let ratio = event.mult.take().unwrap_or(1.0) / event.div.take().unwrap_or(1.0);
+            event.rep = Some(rep * ratio);
+        }
+        if let Some(rep) = event.rep.take().filter(|&r| r > 0.0) {
+            let gate = event.gate.unwrap_or(VoiceParams::default().gate);
+            event.ratchet = Some(((gate / rep).round() as usize).max(1));
+        }
+        event.clamp_params();
         if let Some(delta) = event.delta {
             let base = event.tick.unwrap_or(self.tick) as i64;
             event.tick = Some((base + delta).max(0) as u64);
             event.delta = None;
+            self.rebeat(&mut event);
+        }
+        if let Some(flam) = event.flam.take().filter(|&f| f > 0.0) {
+            self.push_flam(&mut event, flam);
+        }
+        if event.ratchet.is_some_and(|n| n > 1) {
+            return self.play_ratchet(event);
         }
         if event.tick.is_some() {
             self.schedule.push(event);
             return None;
         }
-        self.process_event(&event)
+        self.run_event(&event)
+    }
+
+    /// Places an event given in beats at the current tempo: its start, and
+    /// its gate. Also run on scheduled events when the tempo changes.
+    fn apply_tempo(tempo: &time::Tempo, event: &mut Event) {
+        if let Some(beat) = event.beat {
+            event.tick = Some(tempo.tick_of(beat));
+        }
+        if let Some(beats) = event.gatebeats {
+            event.gate = Some(tempo.secs(beats));
+        }
+    }
+
+    /// Keeps a beat-timed event's `beat` on its `tick` after moving it.
+    fn rebeat(&self, event: &mut Event) {
+        if let (Some(beat), Some(tick)) = (&mut event.beat, event.tick) {
+            *beat = self.tempo.beat_at(tick);
+        }
+    }
+
+    /// Sets the tempo of beat times and lengths (`time/2b`, `gate/0.5b`).
+    /// Scheduled beat-timed events move to the new tempo; the beat reached
+    /// so far stays where it is.
+    pub fn set_bpm(&mut self, bpm: f32) {
+        if !bpm.is_finite() {
+            return;
+        }
+        self.tempo.set_bpm(bpm, self.tick);
+        let tempo = self.tempo;
+        self.schedule
+            .retime(|event| Self::apply_tempo(&tempo, event));
+    }
+
+    pub fn bpm(&self) -> f32 {
+        self.tempo.bpm()
+    }
+
+    /// Beats since the clock started, at the engine's tempo.
+    pub fn beat(&self) -> f64 {
+        self.tempo.beat_at(self.tick)
+    }
+
+    /// Maps the event's `note` through its `tuning`, when one of that name
+    /// is registered; otherwise the note keeps its 12-TET pitch.
+    fn apply_tuning(&self, event: &mut Event) {
+        let Some(tuning) = event.tuning.as_deref().and_then(|name| self.tuning(name)) else {
+            return;
+        };
+        if let Some(note) = event.note.take() {
+            event.freq = Some(tuning.freq(note));
+        }
+        if let Some(chain) = event.note_mod.take() {
+            let chain = chain.map_values(|n| tuning.freq(n));
+            if let Some(m) = event
+                .mods
+                .iter_mut()
+                .rev()
+                .find(|(id, _)| *id == ParamId::Freq)
+            {
+                m.1 = chain;
+            }
+        }
+    }
+
+    /// Schedules a grace hit at `FLAM_GAIN` of the event's gain, `flam`
+    /// seconds ahead of it. When the event is too close to now for that, the
+    /// grace hit plays now and the event moves back to keep the gap.
+    fn push_flam(&mut self, event: &mut Event, flam: f32) {
+        let gap = ((flam * self.sr) as u64).max(1);
+        let base = event.tick.unwrap_or(self.tick).max(self.tick);
+        let grace_tick = base.saturating_sub(gap).max(self.tick);
+        let mut grace = event.clone();
+        grace.ack = None;
+        grace.ratchet = None;
+        grace.tick = Some(grace_tick);
+        grace.gain = Some(event.gain.unwrap_or(VoiceParams::default().gain) * FLAM_GAIN);
+        self.rebeat(&mut grace);
+        self.schedule.push(grace);
+        event.tick = Some(grace_tick + gap);
+        self.rebeat(event);
+    }
+
+    /// Splits the event's gate into `ratchet` evenly spaced hits. Hit `k` is
+    /// scaled by `ratchetdecay^k` and raised `k * ratchetstep` semitones; all
+    /// but the first go through the schedule, each nudged by up to
+    /// `repjitter` seconds either way. With `swing`, pairs of steps of
+    /// `swinggrid` hits alternate long and short. Endless gates play once.
+    fn play_ratchet(&mut self, mut event: Event) -> Option<usize> {
+        let hits = event.ratchet.take().unwrap_or(1).min(MAX_RATCHET);
+        let defaults = VoiceParams::default();
+        let dur = event.gate.unwrap_or(defaults.gate);
+        if dur <= 0.0 {
+            return self.play_event(event);
+        }
+
+        let interval = dur / hits as f32;
+        let step_ticks = ((interval * self.sr) as u64).max(1);
+        let base = event.tick.unwrap_or(self.tick);
+        let gain = event.gain.unwrap_or(defaults.gain);
+        let detune = event.detune.unwrap_or(defaults.detune);
+        let decay = event.ratchetdecay.unwrap_or(1.0).max(0.0);
+        let step = event.ratchetstep.unwrap_or(0.0);
+        let swing = event.swing.take().unwrap_or(0.5).clamp(0.1, 0.9);
+        let grid = event.swinggrid.take().unwrap_or(1).max(1);
+        // Kept under half the shortest spacing so hits never swap places
+        let spacing = interval * 2.0 * swing.min(1.0 - swing);
+        let jitter = event.repjitter.unwrap_or(0.0).clamp(0.0, spacing * 0.5) * self.sr;
+        event.gate = Some(interval);
+        event.gatebeats = event.gatebeats.map(|b| b / hits as f32);
+        // A roll measured in beats stays on them when the tempo changes
+        let on_beat = event.beat.is_some() || event.gatebeats.is_some();
+
+        let mut level = 1.0;
+        for k in 1..hits {
+            level *= decay;
+            let mut hit = event.clone();
+            hit.ack = None;
+            let mut tick = base + (swung(k, swing, grid) * step_ticks as f32).round() as u64;
+            if jitter > 0.0 {
+                self.voice_seed = modulation::lcg(self.voice_seed);
+                let r = (self.voice_seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+                tick = tick.saturating_add_signed((r * jitter) as i64);
+            }
+            hit.tick = Some(tick);
+            if on_beat {
+                hit.beat = Some(self.tempo.beat_at(tick));
+            }
+            hit.gain = Some(gain * level);
+            hit.detune = Some(detune + k as f32 * step * 100.0);
+            self.schedule.push(hit);
+        }
+        self.play_event(event)
+    }
+
+    /// Processes an event and acknowledges it if it carries an `ack` token.
+    fn run_event(&mut self, event: &Event) -> Option<usize> {
+        let idx = self.process_event(event);
+        #[cfg(feature = "native")]
+        if let Some(token) = event.ack {
+            self.send_ack(token, idx);
+        }
+        idx
+    }
+
+    #[cfg(feature = "native")]
+    fn send_ack(&self, token: u64, voice_idx: Option<usize>) {
+        let Some(ref tx) = self.ack_tx else {
+            return;
+        };
+        let voice = voice_idx.map(|i| &self.voices[i]);
+        let _ = tx.try_send(EventAck {
+            token,
+            voice_id: voice.map(|v| v.id),
+            sample: voice
+                .and_then(|v| v.registry_sample.as_ref())
+                .and_then(|rs| rs.sample_name.clone()),
+        });
+    }
+
+    /// Returns the current index of the voice with stable ID `id`.
+    pub fn voice_index_by_id(&self, id: u32) -> Option<usize> {
+        self.voices[..self.active_voices]
+            .iter()
+            .position(|v| v.id == id)
     }
 
     // NOTE: handle_rec allocates (format!, push, insert) but only fires on recording
@@ -455,25 +1493,317 @@ impl Engine {
     #[cfg(feature = "native")]
     fn handle_rec(&mut self, event: &Event) {
         let overdub = event.overdub.unwrap_or(false);
-        let name = event.sound.as_deref();
-        let orbit = event.orbit;
+        let auto_stop = event.autostop.unwrap_or(false);
+        // A length makes the take a loop of the live input
+        let seconds = event.len;
+        let name = event.name.as_deref().or(event.sound.as_deref());
+        let source = match (event.input.unwrap_or(seconds.is_some()), event.orbit) {
+            (true, _) => RecSource::Input(event.inchan.unwrap_or(0)),
+            (false, Some(o)) => RecSource::Orbit(o % self.orbits.len()),
+            (false, None) => RecSource::Output,
+        };
 
         if self
             .recorder
-            .toggle(name, overdub, orbit, &self.sample_registry)
+            .toggle(
+                name,
+                overdub,
+                auto_stop,
+                source,
+                seconds.unwrap_or(0.0),
+                &self.sample_registry,
+            )
             .is_some()
         {
-            if let Some((name, data)) = self.recorder.finalize() {
-                let key = format!("{name}/0");
-                self.sample_registry.insert(key.clone(), data);
-                if !self.sample_index.iter().any(|e| e.name.as_ref() == key) {
-                    self.sample_index.push(SampleEntry {
-                        name: Arc::from(key),
-                        path: Arc::new(std::path::PathBuf::new()),
-                    });
-                }
+            self.store_recording();
+        }
+    }
+
+    #[cfg(feature = "native")]
+    fn handle_keep(&mut self, event: &Event) {
+        let Some(ref tx) = self.keep_tx else {
+            return;
+        };
+        let seconds = event
+            .n
+            .as_deref()
+            .and_then(|n| n.parse::<f32>().ok())
+            .unwrap_or(f32::MAX);
+        let _ = tx.try_send(self.keep(seconds));
+    }
+
+    #[cfg(feature = "native")]
+    fn handle_record(&mut self, event: &Event) {
+        let target = match (event.vid, event.orbit) {
+            (Some(id), _) => tap::TapTarget::Voice(id),
+            (None, Some(o)) if o < self.orbits.len() => tap::TapTarget::Orbit(o),
+            _ => return,
+        };
+        let live = match target {
+            tap::TapTarget::Voice(id) => self.voice_index_by_id(id).is_some(),
+            tap::TapTarget::Orbit(_) => true,
+        };
+        let Some(taps) = self.taps.as_mut() else {
+            return;
+        };
+        match event.n.as_deref() {
+            Some("start") if live => {
+                taps.start(target);
+            }
+            Some("stop") => {
+                taps.stop(target);
+            }
+            _ => {}
+        }
+    }
+
+    /// Enables `/doux/record`, writing its WAV files to `dir`. Finishes
+    /// the files of any taps already recording.
+    ///
+    /// Spawns the writer thread for `dir` and waits for the old one, so the
+    /// CLIs call it while setting the engine up.
+    #[cfg(feature = "native")]
+    pub fn set_tap_dir(&mut self, dir: std::path::PathBuf) -> std::io::Result<()> {
+        let taps = tap::Taps::spawn(dir, self.sr)?;
+        if let Some(old) = self.taps.replace(taps) {
+            old.close();
+        }
+        Ok(())
+    }
+
+    /// Stops every `/doux/record` tap and waits for their files to be
+    /// written. Recording stays off until [`set_tap_dir`](Self::set_tap_dir).
+    #[cfg(feature = "native")]
+    pub fn close_taps(&mut self) {
+        if let Some(taps) = self.taps.take() {
+            taps.close();
+        }
+    }
+
+    /// Sizes the rolling master-output buffer used by `keep` (0 disables it).
+    /// The whole buffer is allocated here; the callback only writes into it.
+    #[cfg(feature = "native")]
+    pub fn set_retro_seconds(&mut self, seconds: f32) {
+        self.retro = RetroBuffer::new(seconds, self.sr);
+    }
+
+    /// Sizes every orbit's delay lines to hold `seconds` of delay at the
+    /// engine sample rate (clamped to 60 s). Clears the lines.
+    ///
+    /// Spare orbits get the same lines, so switching them on with
+    /// [`set_orbit_count`](Self::set_orbit_count) doesn't allocate.
+    pub fn set_max_delay_time(&mut self, seconds: f32) {
+        for orbit in self.orbits.iter_mut().chain(&mut self.spare_orbits) {
+            orbit.delay.set_max_time(seconds);
+        }
+        self.cue.delay.set_max_time(seconds);
+    }
+
+    /// Like [`set_max_delay_time`](Self::set_max_delay_time) for one orbit,
+    /// so only the orbits that need long delays pay for the memory.
+    pub fn set_orbit_max_delay_time(&mut self, orbit: usize, seconds: f32) {
+        if let Some(orbit) = self.orbits.get_mut(orbit) {
+            orbit.delay.set_max_time(seconds);
+        }
+    }
+
+    /// Sets how every orbit's plate reverb scales with the sample rate.
+    /// Changing it drops running tails. Spare orbits are rebuilt as well,
+    /// so an orbit switched on later plays at the same quality.
+    pub fn set_reverb_quality(&mut self, quality: ReverbQuality) {
+        for orbit in self.orbits.iter_mut().chain(&mut self.spare_orbits) {
+            orbit.set_verb_quality(quality);
+        }
+        self.cue.set_verb_quality(quality);
+    }
+
+    /// Sets how many orbits are active (clamped to `1..=MAX_ORBITS`);
+    /// voices routed past the last orbit wrap around.
+    ///
+    /// Every orbit is built with the engine, so this only moves orbits
+    /// between `orbits` and the spares and is safe from the audio thread.
+    /// An orbit switched off keeps its settings but loses its tails.
+    pub fn set_orbit_count(&mut self, n: usize) {
+        let n = n.clamp(1, MAX_ORBITS);
+        while self.orbits.len() > n {
+            let Some(mut orbit) = self.orbits.pop() else {
+                break;
+            };
+            orbit.park();
+            self.spare_orbits.push(orbit);
+        }
+        while self.orbits.len() < n {
+            let Some(orbit) = self.spare_orbits.pop() else {
+                break;
+            };
+            self.orbits.push(orbit);
+        }
+        #[cfg(feature = "native")]
+        {
+            use std::sync::atomic::Ordering;
+            self.metrics.orbit_count.store(n as u32, Ordering::Relaxed);
+            for level in &self.metrics.orbit_env[n..] {
+                level.store(0, Ordering::Relaxed);
+            }
+            for level in self.metrics.return_env[n..].iter().flatten() {
+                level.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Registers where `/doux/keep` sends captured output to be saved.
+    #[cfg(feature = "native")]
+    pub fn set_keep_sender(&mut self, tx: crossbeam_channel::Sender<KeepRequest>) {
+        self.keep_tx = Some(tx);
+    }
+
+    /// The last `seconds` of master output (clamped to what the rolling
+    /// buffer holds). Only notes where they lie; the frames are copied by
+    /// whoever reads the request.
+    #[cfg(feature = "native")]
+    pub fn keep(&self, seconds: f32) -> KeepRequest {
+        let frames = (seconds.max(0.0) * self.sr).min(self.retro.capacity() as f32) as usize;
+        KeepRequest {
+            span: self.retro.last(frames),
+            sample_rate: self.sr,
+        }
+    }
+
+    #[cfg(feature = "native")]
+    fn store_recording(&mut self) {
+        if let Some((name, data)) = self.recorder.finalize() {
+            let key = format!("{name}/0");
+            self.sample_registry.insert(key.clone(), data);
+            if !self.sample_index.iter().any(|e| e.name.as_ref() == key) {
+                self.sample_index.push(SampleEntry {
+                    name: Arc::from(key),
+                    path: Arc::new(std::path::PathBuf::new()),
+                });
+            }
+        }
+    }
+
+    /// Registers `tuning` for events with `tuning/<name>`, replacing any
+    /// tuning of that name.
+    ///
+    /// `/doux/tuning` and [`AudioCmd::Tuning`] land here on the audio
+    /// thread. Replacing a tuning only moves it in; a new name grows the
+    /// list, so register the tunings a set needs before it starts.
+    pub fn set_tuning(&mut self, name: &str, tuning: Tuning) {
+        match self.tunings.iter_mut().find(|(n, _)| n == name) {
+            Some((_, t)) => *t = tuning,
+            None => self.tunings.push((name.to_string(), tuning)),
+        }
+    }
+
+    pub fn tuning(&self, name: &str) -> Option<&Tuning> {
+        self.tunings.iter().find(|(n, _)| n == name).map(|(_, t)| t)
+    }
+
+    /// `/doux/tuning`: registers a named cents table. Scala files
+    /// (`load/<path>`) are read before the event reaches the engine, by
+    /// [`AudioCmd::route`], so the audio thread never waits on the disk.
+    fn handle_tuning(&mut self, event: &Event) {
+        if let (Some(cents), Some(name)) = (&event.cents, event.tuning.as_deref()) {
+            if let Ok(tuning) = Tuning::from_cents(cents.clone()) {
+                self.set_tuning(name, tuning);
+            }
+        }
+    }
+
+    /// Makes `renderer` playable as `/sound/<name>`. Built-in source names
+    /// can't be overridden; registering an existing custom name replaces it.
+    /// Voices look the renderer up by name and only clone its `Arc`.
+    pub fn register_source(&mut self, name: &str, renderer: Arc<dyn SourceRenderer>) {
+        self.custom_sources.register(name, renderer);
+    }
+
+    /// Makes `effect` available to `/insertN/<name>` and `/orbitinsertN/<name>`,
+    /// building a processor for every slot here. Registering an existing
+    /// name replaces it for slots assigned afterwards; slots already holding
+    /// the old effect keep it until reassigned.
+    ///
+    /// For a running engine, build the [`InsertPool`] on the control side
+    /// with [`insert_slot_count`](Self::insert_slot_count) processors and
+    /// send it as [`AudioCmd::Insert`].
+    pub fn register_insert(&mut self, name: &str, effect: Arc<dyn InsertEffect>) {
+        let pool = InsertPool::new(name, &*effect, self.sr, self.insert_slot_count());
+        self.insert_effects.register(pool);
+    }
+
+    /// Insert slots across every voice (fading ones included) and orbit.
+    pub fn insert_slot_count(&self) -> usize {
+        let orbits = self.orbits.len() + self.spare_orbits.len() + 1;
+        (self.voices.len() + self.fading.len() + orbits) * INSERT_SLOTS
+    }
+
+    /// True when nothing can produce sound anymore: no voices, nothing
+    /// scheduled, every orbit's FX tail has decayed and no test signal runs.
+    pub fn is_idle(&self) -> bool {
+        self.active_voices == 0
+            && self.active_fades == 0
+            && !self.test_signal.is_active()
+            && !self.calibration.is_running()
+            && !self.click.is_active()
+            && self.schedule.is_empty()
+            && self.orbits.iter().all(Orbit::is_silent)
+            && self.cue.is_silent()
+    }
+
+    /// Changes the polyphony limit at runtime, up to the size of the voice
+    /// pool ([`MAX_VOICES`] unless the engine was built with more).
+    ///
+    /// Every voice in the pool is built with the engine, so this never
+    /// allocates. Shrinking drops voices that haven't started, then
+    /// hard-cuts the quietest voices above the new limit; they free
+    /// themselves once their 1ms release ends.
+    pub fn set_max_voices(&mut self, n: usize) {
+        let n = n.clamp(1, self.voices.len());
+        self.max_voices = n;
+
+        // Voices that haven't sounded yet can go straight away
+        let mut i = self.active_voices;
+        while i > 0 && self.active_voices > n {
+            i -= 1;
+            if !self.voices[i].triggered {
+                self.free_voice(i);
             }
         }
+
+        let is_cut = |v: &Voice| v.dahdsr.is_releasing() && v.params.release <= 0.001;
+        let already_cut = self.voices[..self.active_voices]
+            .iter()
+            .filter(|v| is_cut(v))
+            .count();
+        for _ in (n + already_cut)..self.active_voices {
+            let quietest = (0..self.active_voices)
+                .filter(|&i| !is_cut(&self.voices[i]))
+                .min_by(|&a, &b| {
+                    let va = self.voices[a].dahdsr.current_val;
+                    let vb = self.voices[b].dahdsr.current_val;
+                    va.total_cmp(&vb)
+                });
+            let Some(i) = quietest else { break };
+            self.voices[i].hard_cut();
+        }
+    }
+
+    /// Restarts the sequence of seeds new voices draw their noise and random
+    /// modulation from, as well as ratchet jitter and random orbit lanes, so
+    /// the same events render the same way.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.voice_seed = seed;
+        let lane_seed = modulation::lcg(seed ^ 0x5eed);
+        for orbit in self.orbits.iter_mut().chain(&mut self.spare_orbits) {
+            orbit.set_lane_seed(lane_seed);
+        }
+        self.cue.set_lane_seed(lane_seed);
+    }
+
+    fn next_voice_seed(&mut self) -> u32 {
+        let seed = self.voice_seed;
+        self.voice_seed = modulation::lcg(seed);
+        seed
     }
 
     pub fn play(&mut self, params: VoiceParams) -> Option<usize> {
@@ -486,19 +1816,23 @@ impl Engine {
         }
         let i = self.active_voices;
         self.voices[i].reset();
-        self.voices[i].seed = self.voice_seed;
-        self.voice_seed = modulation::lcg(self.voice_seed);
+        self.voices[i].seed = self.next_voice_seed();
         self.voices[i].params = params;
         self.voices[i].sr = self.sr;
+        self.voices[i].id = self.next_voice_id;
+        self.next_voice_id = self.next_voice_id.wrapping_add(1).max(1);
         self.voices[i].sync_source_state();
         self.voices[i].ensure_effects();
         self.active_voices += 1;
+        #[cfg(feature = "native")]
+        self.send_voice_event(VoiceEventKind::Trigger, i);
         Some(i)
     }
 
     /// Process an event, handling voice selection like dough.c's process_engine_event()
     fn process_event(&mut self, event: &Event) -> Option<usize> {
-        // Cut group: reuse first matching voice, hard_cut any extras
+        let cutfade = event.cutfade.unwrap_or(DEFAULT_CUTFADE).max(0.0);
+        // Cut group: reuse first matching voice, fade out any extras
         let mut cut_reuse: Option<usize> = None;
         if let Some(cut) = event.cut {
             for i in 0..self.active_voices {
@@ -506,7 +1840,7 @@ impl Engine {
                     if cut_reuse.is_none() {
                         cut_reuse = Some(i);
                     } else {
-                        self.voices[i].hard_cut();
+                        self.voices[i].fade_out(cutfade);
                     }
                 }
             }
@@ -516,7 +1850,10 @@ impl Engine {
         // Skip this check if WebSample data is already present (WASM with JS-loaded sample)
         let has_web_sample = event.file_pcm.is_some() && event.file_frames.is_some();
         if let Some(ref sound_str) = event.sound {
-            if !has_web_sample && sound_str.parse::<Source>().is_err() {
+            if !has_web_sample
+                && sound_str.parse::<Source>().is_err()
+                && self.custom_sources.get(sound_str).is_none()
+            {
                 let effective_name = event.effective_name.as_deref().unwrap_or(sound_str);
                 #[cfg(feature = "native")]
                 {
@@ -531,7 +1868,15 @@ impl Engine {
             }
         }
 
-        let (voice_idx, is_new_voice) = if let Some(reuse_idx) = cut_reuse {
+        // Stable voice ID: update that voice if it's still alive, else drop
+        let by_id = match event.vid {
+            Some(id) => Some(self.voice_index_by_id(id)?),
+            None => None,
+        };
+
+        let (voice_idx, is_new_voice) = if let Some(idx) = by_id {
+            (idx, false)
+        } else if let Some(reuse_idx) = cut_reuse {
             (reuse_idx, true)
         } else if let Some(v) = event.voice {
             if v < self.active_voices {
@@ -574,20 +1919,94 @@ impl Engine {
             } else {
                 0.0
             };
+            // A live voice hands its sound over to the fade pool and the new
+            // one starts from silence; without room it carries on from the
+            // old level instead
+            let live = cut_reuse.is_some() || !is_new_voice;
+            let faded = live && self.fade_out_voice(voice_idx, cutfade);
             self.voices[voice_idx].reset();
-            self.voices[voice_idx].dahdsr.current_val = old_env;
-            self.voices[voice_idx].seed = self.voice_seed;
-            self.voice_seed = modulation::lcg(self.voice_seed);
+            if !faded {
+                self.voices[voice_idx].dahdsr.current_val = old_env;
+            }
+            // An explicit seed leaves the engine's sequence for other voices as is
+            self.voices[voice_idx].seed = event.seed.unwrap_or_else(|| self.next_voice_seed());
             self.voices[voice_idx].sr = self.sr;
+        } else if let Some(seed) = event.seed {
+            self.voices[voice_idx].seed = seed;
+        }
+        // Cut-group reuse is a respawn and keeps the voice's ID
+        if is_new_voice && cut_reuse.is_none() {
+            self.voices[voice_idx].id = self.next_voice_id;
+            self.next_voice_id = self.next_voice_id.wrapping_add(1).max(1);
+        }
+
+        // Re-targeting keeps the old set around for `revert` (A/B compare)
+        if !should_reset {
+            let v = &mut self.voices[voice_idx];
+            if event.revert == Some(true) {
+                v.revert_params();
+            } else {
+                v.snapshot_params();
+            }
         }
 
         // Update voice params (only the ones explicitly set in event)
         self.update_voice_params(voice_idx, event);
         self.voices[voice_idx].ensure_effects();
+        // Gates run from the event's scheduled tick, not from when the voice
+        // happened to start, so late or re-targeted events still end on time
+        let start = event.tick.unwrap_or(self.tick);
+        let gate_ticks = |gate: f32| (gate > 0.0).then(|| start + (gate * self.sr) as u64);
+        let v = &mut self.voices[voice_idx];
+        if let Some(end) = event.endtick {
+            v.gate_end = Some(end);
+        } else if let Some(gate) = event.gate {
+            v.gate_end = gate_ticks(gate);
+        } else if should_reset {
+            v.gate_end = gate_ticks(v.params.gate);
+        }
+        // A reset voice starts its mods from scratch anyway
+        if event.modsync == Some(true) && !should_reset {
+            self.voices[voice_idx].resync_mods();
+        }
+        if should_reset {
+            self.voices[voice_idx].sync_free_lfos(self.time);
+        }
+
+        #[cfg(feature = "native")]
+        if should_reset {
+            let kind = if is_new_voice && cut_reuse.is_none() {
+                VoiceEventKind::Trigger
+            } else {
+                VoiceEventKind::Respawn
+            };
+            self.send_voice_event(kind, voice_idx);
+        }
 
         Some(voice_idx)
     }
 
+    #[cfg(feature = "native")]
+    fn send_voice_event(&self, kind: VoiceEventKind, voice_idx: usize) {
+        let Some(ref tx) = self.voice_event_tx else {
+            return;
+        };
+        let v = &self.voices[voice_idx];
+        let _ = tx.try_send(VoiceEvent {
+            kind,
+            voice_id: v.id,
+            tick: self.tick,
+            source: v.params.sound,
+            sample: v
+                .registry_sample
+                .as_ref()
+                .and_then(|rs| rs.sample_name.clone()),
+            freq: v.params.freq,
+            amp: v.params.gain * v.params.velocity,
+            orbit: v.params.orbit,
+        });
+    }
+
     /// Update voice params - only updates fields that are explicitly set in the event
     fn update_voice_params(&mut self, idx: usize, event: &Event) {
         macro_rules! copy_opt {
@@ -601,11 +2020,17 @@ impl Engine {
             };
         }
         // Resolve sound/sample first (before borrowing voice)
-        // If sound parses as a Source, use it; otherwise treat as sample folder name
+        // Built-in sources win, then registered custom sources; anything else
+        // is treated as a sample folder name
+        let custom_source = event
+            .sound
+            .as_deref()
+            .filter(|s| s.parse::<Source>().is_err())
+            .and_then(|s| self.custom_sources.get(s));
         #[cfg(feature = "native")]
         let (registry_sample_data, registry_sample_data_b, sample_blend) =
             if let Some(ref sound_str) = event.sound {
-                if sound_str.parse::<Source>().is_ok() {
+                if sound_str.parse::<Source>().is_ok() || custom_source.is_some() {
                     (None, None, 0.0f32)
                 } else {
                     let effective_name = event.effective_name.as_deref().unwrap_or(sound_str);
@@ -625,7 +2050,10 @@ impl Engine {
             };
 
         let parsed_source = if let Some(ref sound_str) = event.sound {
-            sound_str.parse::<Source>().ok()
+            sound_str
+                .parse::<Source>()
+                .ok()
+                .or(custom_source.as_ref().map(|_| Source::Custom))
         } else {
             None
         };
@@ -657,92 +2085,42 @@ impl Engine {
         // orbit. The orbit is the source of truth for all FX state.
         let target_orbit = event
             .orbit
-            .map(|o| o % MAX_ORBITS)
-            .unwrap_or_else(|| self.voices[idx].params.orbit % MAX_ORBITS);
-        {
-            let orbit = &mut self.orbits[target_orbit];
-            macro_rules! set {
-                ($evt:ident, $dst:expr) => {
-                    if let Some(x) = event.$evt {
-                        $dst = x;
-                    }
-                };
-            }
-            macro_rules! set_pos {
-                ($evt:ident, $dst:expr) => {
-                    if let Some(x) = event.$evt {
-                        $dst = x.max(0.0);
-                    }
-                };
-            }
-            set_pos!(delay, orbit.delay_level);
-            set_pos!(verb, orbit.verb_level);
-            set_pos!(comb, orbit.comb_level);
-            set_pos!(feedback, orbit.fb_level);
-            set_pos!(comp, orbit.comp.params.amount);
-            set!(delaytime, orbit.delay.params.time);
-            set!(delayfeedback, orbit.delay.params.feedback);
-            set!(delaytype, orbit.delay.params.delay_type);
-            set!(verbtype, orbit.reverb_params.verb_type);
-            set!(verbdecay, orbit.reverb_params.decay);
-            set!(verbdamp, orbit.reverb_params.damp);
-            set!(verbpredelay, orbit.reverb_params.predelay);
-            set!(verbdiff, orbit.reverb_params.diff);
-            set!(verbsize, orbit.reverb_params.size);
-            set!(verbprelow, orbit.reverb_params.prelow);
-            set!(verbprehigh, orbit.reverb_params.prehigh);
-            set!(verblowcut, orbit.reverb_params.lowcut);
-            set!(verbhighcut, orbit.reverb_params.highcut);
-            set!(verblowgain, orbit.reverb_params.lowgain);
-            set!(verbchorus, orbit.reverb_params.chorus);
-            set!(verbchorusfreq, orbit.reverb_params.chorus_freq);
-            set!(combfreq, orbit.comb_params.freq);
-            set!(combfeedback, orbit.comb_params.feedback);
-            set!(combdamp, orbit.comb_params.damp);
-            set!(fbtime, orbit.fb.params.time_ms);
-            set!(fbdamp, orbit.fb.params.damp);
-            set!(fbcross, orbit.fb.params.cross);
-            set!(fblfo, orbit.fb.params.lfo);
-            set!(fblfodepth, orbit.fb.params.lfo_depth);
-            set!(fblfoshape, orbit.fb.params.lfo_shape);
-            set!(compattack, orbit.comp.params.attack);
-            set!(comprelease, orbit.comp.params.release);
-            set!(comporbit, orbit.comp_orbit);
-        }
+            .map(|o| o % self.orbits.len())
+            .unwrap_or_else(|| self.voices[idx].params.orbit % self.orbits.len());
+        // Cue voices shape the cue orbit, leaving their main orbit untouched
+        let fx_orbit = if event.cue.unwrap_or(self.voices[idx].params.cue) {
+            CUE_ORBIT
+        } else {
+            target_orbit
+        };
+        self.automate_orbit(fx_orbit, event);
+        self.apply_orbit_params(fx_orbit, event);
 
         let v = &mut self.voices[idx];
+        // Params are updated from their set values, without LFO swing
+        v.remove_mod_offsets();
 
         // --- Pitch ---
-        copy_opt!(event, v.params, freq, detune, speed);
-        if let Some(stretch) = event.stretch {
-            v.params.stretch = stretch.max(0.0);
-        }
+        copy_opt!(event, v.params, freq, detune, speed, stretch);
         // --- Source ---
         if let Some(source) = parsed_source {
-            v.params.sound = source;
-        }
-        copy_opt!(event, v.params, pw, spread);
-        if let Some(wave) = event.wave {
-            v.params.wave = wave.clamp(0.0, 1.0);
-        }
-        if let Some(sub) = event.sub {
-            v.params.sub = sub.clamp(0.0, 1.0);
+            v.params.sound = source;
+            v.custom = custom_source;
         }
+        copy_opt!(event, v.params, pw, spread, wave, sub);
         if let Some(sub_oct) = event.sub_oct {
             v.params.sub_oct = sub_oct.clamp(1, 3);
         }
         if let Some(sub_wave) = event.sub_wave {
             v.params.sub_wave = sub_wave;
         }
-        if let Some(sync_ratio) = event.sync_ratio {
-            v.params.sync_ratio = sync_ratio.clamp(0.0, 64.0);
-        }
-        if let Some(sync_phase) = event.sync_phase {
-            v.params.sync_phase = sync_phase.clamp(0.0, 1.0);
-        }
+        copy_opt!(event, v.params, sync_ratio, sync_phase);
         if let Some(sync_mode) = event.sync_mode {
             v.params.sync_mode = sync_mode;
         }
+        if let Some(panlaw) = event.panlaw {
+            v.params.panlaw = panlaw;
+        }
         if let Some(size) = event.size {
             v.params.shape.size = size.min(256);
         }
@@ -750,26 +2128,15 @@ impl Engine {
             v.params.shape.warp = warp.clamp(-1.0, 1.0);
         }
         if let Some(mirror) = event.mirror {
-            v.params.shape.mirror = mirror.clamp(0.0, 1.0);
+            v.params.shape.mirror = mirror;
         }
-        if let Some(harmonics) = event.harmonics {
-            v.params.harmonics = harmonics.clamp(0.01, 0.999);
-        }
-        if let Some(timbre) = event.timbre {
-            v.params.timbre = timbre.clamp(0.01, 0.999);
-        }
-        if let Some(morph) = event.morph {
-            v.params.morph = morph.clamp(0.01, 0.999);
-        }
-        if let Some(partials) = event.partials {
-            v.params.partials = partials.clamp(1.0, 32.0);
+        copy_opt!(event, v.params, harmonics, timbre, morph, partials, scan);
+        copy_opt!(event, v.params, pluckdamp, pluckdecay);
+        if let Some(pluck_excite) = event.pluck_excite {
+            v.params.pluck_excite = pluck_excite;
         }
         copy_opt_some!(event, v.params, cut);
 
-        // Wavetable scan parameter
-        if let Some(scan) = event.scan {
-            v.params.scan = scan.clamp(0.0, 1.0);
-        }
         if let Some(wtlen) = event.wtlen {
             v.params.wt_cycle_len = wtlen;
         }
@@ -779,6 +2146,7 @@ impl Engine {
         if let Some(gm) = gm_resolved {
             let mut rs = RegistrySample::new(None, gm.data, 0.0, 1.0);
             rs.root_freq = gm.root_freq;
+            rs.tuning = rs.data.rate_ratio(self.sr);
             rs.scale_tuning = gm.scale_tuning;
             if gm.looping {
                 rs.set_loop(gm.loop_start, gm.loop_end);
@@ -818,15 +2186,17 @@ impl Engine {
         // Sample playback via lock-free registry (native)
         #[cfg(feature = "native")]
         if let Some((sample_name, sample_data)) = registry_sample_data {
-            // Use Wavetable mode if scan param present (static or modulated), otherwise Sample
-            let has_scan =
-                event.scan.is_some() || event.mods.iter().any(|(id, _)| *id == ParamId::Scan);
+            // Wavetable mode for wavetable files or with a scan param (static
+            // or modulated), otherwise Sample
+            let has_scan = sample_data.wt_cycle.is_some()
+                || event.scan.is_some()
+                || event.mods.iter().any(|(id, _)| *id == ParamId::Scan);
             v.params.sound = if has_scan {
                 Source::Wavetable
             } else {
                 Source::Sample
             };
-            let (begin, end) = event.resolve_range();
+            let (begin, end) = event.resolve_range_in(&sample_data);
             let frame_count = sample_data.total_frames;
             v.registry_sample = Some(RegistrySample::new(
                 Some(sample_name),
@@ -841,22 +2211,44 @@ impl Engine {
                 v.registry_sample_b = None;
                 v.sample_blend = 0.0;
             }
+            if let Some((lb, le, xfade)) = event.resolve_loop() {
+                for rs in [&mut v.registry_sample, &mut v.registry_sample_b]
+                    .into_iter()
+                    .flatten()
+                {
+                    rs.set_loop_region(lb, le, xfade * self.sr);
+                }
+            }
             if event.freq.is_none() {
                 v.params.freq = 261.626;
             }
+            // A detected pitch makes freq/note absolute instead of relative
+            // to middle C
+            let pitched =
+                event.freq.is_some() || event.mods.iter().any(|(id, _)| *id == ParamId::Freq);
+            // Files kept at another rate than the engine's (a device switch
+            // after loading) play faster or slower to stay in pitch
+            let mut rate = self.sr;
+            if let Some(rs) = v.registry_sample.as_mut() {
+                rs.tuning = match rs.data.pitch {
+                    Some(pitch) if pitched => 261.626 / pitch,
+                    _ => 1.0,
+                } * rs.data.rate_ratio(self.sr);
+                rate = rs.data.sample_rate.unwrap_or(self.sr);
+            }
             if let Some(target_dur) = event.fit {
-                let sample_dur = frame_count as f32 * (end - begin) / self.sr;
+                let sample_dur = frame_count as f32 * (end - begin) / rate;
                 v.params.speed = sample_dur / target_dur;
             }
         } else if event.begin.is_some() || event.end.is_some() || event.slice.is_some() {
             #[cfg(feature = "native")]
             {
                 if let Some(ref mut rs) = v.registry_sample {
-                    let (begin, end) = event.resolve_range();
+                    let (begin, end) = event.resolve_range_in(&rs.data);
                     rs.update_range(Some(begin), Some(end));
                 }
                 if let Some(ref mut rs) = v.registry_sample_b {
-                    let (begin, end) = event.resolve_range();
+                    let (begin, end) = event.resolve_range_in(&rs.data);
                     rs.update_range(Some(begin), Some(end));
                 }
             }
@@ -876,7 +2268,11 @@ impl Engine {
                     Source::Sample
                 };
                 let (begin, end) = event.resolve_range();
-                v.file_source = Some(FileSource::new(sample_idx, info.frames, begin, end));
+                let mut fs = FileSource::new(sample_idx, info.frames, begin, end);
+                if let Some((lb, le, xfade)) = event.resolve_loop() {
+                    fs.set_loop_region(lb, le, xfade * self.sr);
+                }
+                v.file_source = Some(fs);
                 if event.freq.is_none() {
                     v.params.freq = 261.626;
                 }
@@ -907,23 +2303,42 @@ impl Engine {
             } else {
                 Source::WebSample
             };
-            v.web_sample = Some(WebSampleSource::new(
+            let mut ws = WebSampleSource::new(
                 offset,
                 frames as u32,
                 event.file_channels.unwrap_or(1),
                 event.file_freq.unwrap_or(65.406),
                 begin,
                 end,
-            ));
+            );
+            if let Some((lb, le, xfade)) = event.resolve_loop() {
+                ws.set_loop_region(lb, le, xfade * self.sr);
+            }
+            v.web_sample = Some(ws);
             if event.freq.is_none() {
                 v.params.freq = 261.626;
             }
         }
 
         // --- Gain ---
-        copy_opt!(event, v.params, gain, postgain, velocity, pan, gate);
+        copy_opt!(
+            event,
+            v.params,
+            gain,
+            postgain,
+            velocity,
+            pan,
+            gate,
+            pan2dspread
+        );
+        copy_opt_some!(event, v.params, pan2d);
 
         // --- Gain Envelope ---
+        // A stage driven by a mod chain counts as set for the sustain
+        // inference; the chain writes its value from the first frame
+        let stage = |val: Option<f32>, id: ParamId, default: f32| {
+            val.or_else(|| event.mods.iter().any(|(m, _)| *m == id).then_some(default))
+        };
         let (att, dec, sus, rel) =
             if let Some((d_freq, d_att, d_dec, d_sus, d_rel)) = v.params.sound.drum_defaults() {
                 if event.freq.is_none() {
@@ -936,9 +2351,16 @@ impl Engine {
                     event.release.or(Some(d_rel)),
                 )
             } else {
-                (event.attack, event.decay, event.sustain, event.release)
+                (
+                    stage(event.attack, ParamId::Attack, 0.003),
+                    stage(event.decay, ParamId::Decay, 0.0),
+                    stage(event.sustain, ParamId::Sustain, 1.0),
+                    stage(event.release, ParamId::Release, 0.005),
+                )
             };
-        let gain_env = init_envelope(None, event.envdelay, att, event.hold, dec, sus, rel);
+        let dly = stage(event.envdelay, ParamId::Envdelay, 0.0);
+        let hld = stage(event.hold, ParamId::Hold, 0.0);
+        let gain_env = init_envelope(None, dly, att, hld, dec, sus, rel);
         if gain_env.active {
             v.params.envdelay = gain_env.dly;
             v.params.attack = gain_env.att;
@@ -947,6 +2369,22 @@ impl Engine {
             v.params.sustain = gain_env.sus;
             v.params.release = gain_env.rel;
         }
+        if event.attackcurve.is_some() || event.decaycurve.is_some() || event.releasecurve.is_some()
+        {
+            let clamp = |c: f32| c.clamp(-1.0, 1.0);
+            if let Some(c) = event.attackcurve {
+                v.params.attackcurve = clamp(c);
+            }
+            if let Some(c) = event.decaycurve {
+                v.params.decaycurve = clamp(c);
+            }
+            if let Some(c) = event.releasecurve {
+                v.params.releasecurve = clamp(c);
+            }
+            let p = &v.params;
+            v.dahdsr
+                .set_curves(p.attackcurve, p.decaycurve, p.releasecurve);
+        }
 
         // --- Filters ---
         copy_opt_some!(event, v.params, lpf);
@@ -970,6 +2408,16 @@ impl Engine {
 
         // --- Modulation ---
         copy_opt!(event, v.params, vib, vibmod, vibshape);
+        for (lfo, ev) in v.params.lfos.iter_mut().zip(&event.lfos) {
+            copy_opt!(ev, lfo, rate, depth, shape, target, retrig);
+        }
+        copy_opt!(event, v.params, bend, pressure_target, pressure_depth);
+        if let Some(pressure) = event.pressure {
+            v.params.pressure = pressure.clamp(0.0, 1.0);
+        }
+        if let Some(slew) = event.expr_slew {
+            v.params.expr_slew = slew.max(0.0);
+        }
         copy_opt!(event, v.params, fm, fmh, fmshape, fm2, fm2h, fmpivot, fmfb);
         copy_opt!(event, v.params, am, amdepth, amshape);
         copy_opt!(event, v.params, rm, rmdepth, rmshape);
@@ -981,39 +2429,266 @@ impl Engine {
             phaser,
             phaserdepth,
             phasersweep,
-            phasercenter
+            phasercenter,
+            phasermix
+        );
+        copy_opt!(
+            event,
+            v.params,
+            flanger,
+            flangerdepth,
+            flangerfeedback,
+            flangermix
         );
-        copy_opt!(event, v.params, flanger, flangerdepth, flangerfeedback);
-        copy_opt!(event, v.params, smear, smearfreq, smearfb);
-        copy_opt!(event, v.params, chorus, chorusdepth, chorusdelay);
+        copy_opt!(event, v.params, smear, smearfreq, smearfb, smearmix);
+        copy_opt!(event, v.params, fshift, fshiftmix);
+        copy_opt!(event, v.params, reso, resostruct, resobright, resodamp);
+        copy_opt!(event, v.params, chorus, chorusdepth, chorusdelay, chorusmix);
         copy_opt_some!(event, v.params, coarse, crush, fold, wrap, distort);
         copy_opt!(event, v.params, distortvol);
-        copy_opt!(event, v.params, width, haas);
+        copy_opt!(event, v.params, compthresh, compratio, compmakeup);
+        copy_opt!(event, v.params, compattack, comprelease);
+        copy_opt!(event, v.params, transattack, transsustain);
+        copy_opt!(event, v.params, width, haas, decor);
         copy_opt!(event, v.params, eqlo, eqmid, eqhi, eqlofreq, eqmidfreq, eqhifreq, tilt);
 
         // --- Routing (orbit FX state lives on the orbit, not the voice) ---
-        copy_opt!(event, v.params, orbit);
+        copy_opt!(event, v.params, orbit, cue);
+        for (i, orbit) in event.send_orbits.iter().enumerate() {
+            if let Some(orbit) = *orbit {
+                v.params.send_orbits[i] = orbit;
+            }
+        }
+        for (i, level) in event.send_levels.iter().enumerate() {
+            if let Some(level) = *level {
+                v.params.send_levels[i] = level.max(0.0);
+            }
+        }
+        if let Some(ref name) = event.id {
+            v.params.group = Some(voice::group_key(name));
+        }
 
-        // Live input channel
+        // Live input channel and level
         v.params.inchan = event.inchan;
+        v.params.insum = event.insum.unwrap_or(false);
+        copy_opt!(event, v.params, inputgain);
 
         // Install inline parameter modulations
         for (id, chain) in &event.mods {
-            v.set_mod(*id, *chain);
+            v.set_mod(*id, chain.clone());
+        }
+
+        for (i, name) in event.inserts.iter().enumerate() {
+            if let Some(name) = name {
+                v.inserts.assign(i, name, &mut self.insert_effects);
+            }
+        }
+
+        if let (Some(renderer), Some(_)) = (&v.custom, &event.sound) {
+            let ctx = SourceContext {
+                freq: v.params.freq,
+                isr: self.isr,
+                time: 0.0,
+                params: &v.params,
+            };
+            renderer.init(&mut v.custom_state, &ctx);
         }
 
         v.sync_source_state();
+        #[cfg(feature = "debug-dsp")]
+        v.params.debug_check();
+    }
+
+    /// Moves voice `i` to the fade pool to fade out over `secs`, leaving a
+    /// spare voice with the same ID in its slot. Returns `false` when the
+    /// pool is full or there is nothing to fade.
+    fn fade_out_voice(&mut self, i: usize, secs: f32) -> bool {
+        if secs <= 0.0 || self.active_fades >= self.fading.len() {
+            return false;
+        }
+        let slot = self.active_fades;
+        std::mem::swap(&mut self.voices[i], &mut self.fading[slot]);
+        self.fading[slot].fade_out(secs);
+        self.voices[i].id = self.fading[slot].id;
+        self.active_fades += 1;
+        true
     }
 
     fn free_voice(&mut self, i: usize) {
+        #[cfg(feature = "native")]
+        self.send_voice_event(VoiceEventKind::Free, i);
+        #[cfg(feature = "native")]
+        if let Some(taps) = self.taps.as_mut().filter(|t| t.is_active()) {
+            taps.stop(tap::TapTarget::Voice(self.voices[i].id));
+        }
         if self.active_voices > 0 {
             self.active_voices -= 1;
             self.voices.swap(i, self.active_voices);
         }
     }
 
+    fn apply_master_params(&mut self, event: &Event) {
+        if let Some(gain) = event.gain {
+            self.master.gain = gain.max(0.0);
+        }
+        if let Some(limit) = event.limit {
+            self.master.limit = limit.max(0.0);
+        }
+        if let Some(dc) = event.dc {
+            self.master.dc = dc;
+        }
+        if let Some(crossfeed) = event.crossfeed {
+            self.master.crossfeed = crossfeed.clamp(0.0, 1.0);
+        }
+        if let Some(peakwarn) = event.peakwarn {
+            self.master.peakwarn = peakwarn.max(0.0);
+        }
+        if let Some(monowarn) = event.monowarn {
+            self.master.monowarn = monowarn.clamp(0.0, 1.0);
+        }
+        if let Some(tilt) = event.tilt {
+            self.master.tilt = tilt.clamp(-1.0, 1.0);
+        }
+        if let Some(pair) = event.cueout {
+            self.cue_out = usize::try_from(pair).ok();
+        }
+        if let Some(gain) = event.cuegain {
+            self.cue_gain = gain.max(0.0);
+        }
+        for (i, spec) in event.master_eq.iter().enumerate() {
+            match spec.as_deref() {
+                Some("off") => self.master.set_eq_band(i, None),
+                Some(spec) => {
+                    if let Ok(band) = spec.parse() {
+                        self.master.set_eq_band(i, Some(band));
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Writes any FX params carried by `event` into orbit `orbit_idx`
+    /// ([`CUE_ORBIT`] for the cue orbit).
+    /// Starts or stops the event's automation lanes on an orbit, indexed
+    /// like [`Self::apply_orbit_params`].
+    fn automate_orbit(&mut self, orbit_idx: usize, event: &Event) {
+        let orbit = match self.orbits.get_mut(orbit_idx) {
+            Some(orbit) => orbit,
+            None => &mut self.cue,
+        };
+        for (param, chain) in &event.orbit_mods {
+            match chain {
+                Some(chain) => orbit.automate(*param, chain.clone()),
+                None => orbit.stop_lane(*param),
+            }
+        }
+    }
+
+    fn apply_orbit_params(&mut self, orbit_idx: usize, event: &Event) {
+        let orbit = match self.orbits.get_mut(orbit_idx) {
+            Some(orbit) => orbit,
+            None => &mut self.cue,
+        };
+        macro_rules! set {
+            ($evt:ident, $dst:expr) => {
+                if let Some(x) = event.$evt {
+                    $dst = x;
+                }
+            };
+        }
+        macro_rules! set_pos {
+            ($evt:ident, $dst:expr) => {
+                if let Some(x) = event.$evt {
+                    $dst = x.max(0.0);
+                }
+            };
+        }
+        set_pos!(delay, orbit.delay_level);
+        set_pos!(verb, orbit.verb_level);
+        set_pos!(comb, orbit.comb_level);
+        set_pos!(feedback, orbit.fb_level);
+        set_pos!(combreturn, orbit.comb_return);
+        set_pos!(fbreturn, orbit.fb_return);
+        set_pos!(delayreturn, orbit.delay_return);
+        set_pos!(verbreturn, orbit.verb_return);
+        set_pos!(delaysendlpf, orbit.delay_send_filter.lpf);
+        set_pos!(delaysendhpf, orbit.delay_send_filter.hpf);
+        set_pos!(verbsendlpf, orbit.verb_send_filter.lpf);
+        set_pos!(verbsendhpf, orbit.verb_send_filter.hpf);
+        set_pos!(orbithpf, orbit.tone.hpf);
+        set_pos!(orbitlpf, orbit.tone.lpf);
+        set!(orbiteqlo, orbit.tone.lo);
+        set!(orbiteqmid, orbit.tone.mid);
+        set!(orbiteqhi, orbit.tone.hi);
+        set_pos!(comp, orbit.comp.params.amount);
+        set!(delaytime, orbit.delay.params.time);
+        set!(delayfeedback, orbit.delay.params.feedback);
+        set!(delaytype, orbit.delay.params.delay_type);
+        set!(verbtype, orbit.reverb_params.verb_type);
+        set!(verbdecay, orbit.reverb_params.decay);
+        set!(verbdamp, orbit.reverb_params.damp);
+        set!(verbpredelay, orbit.reverb_params.predelay);
+        set!(verbdiff, orbit.reverb_params.diff);
+        set!(verbsize, orbit.reverb_params.size);
+        set!(verbprelow, orbit.reverb_params.prelow);
+        set!(verbprehigh, orbit.reverb_params.prehigh);
+        set!(verblowcut, orbit.reverb_params.lowcut);
+        set!(verbhighcut, orbit.reverb_params.highcut);
+        set!(verblowgain, orbit.reverb_params.lowgain);
+        set!(verbchorus, orbit.reverb_params.chorus);
+        set!(verbchorusfreq, orbit.reverb_params.chorus_freq);
+        set!(verbfreeze, orbit.verb_freeze);
+        set!(delayfreeze, orbit.delay_freeze);
+        set!(combfreq, orbit.comb_params.freq);
+        set!(combfeedback, orbit.comb_params.feedback);
+        set!(combdamp, orbit.comb_params.damp);
+        set!(fbtime, orbit.fb.params.time_ms);
+        set!(fbdamp, orbit.fb.params.damp);
+        set!(fbcross, orbit.fb.params.cross);
+        set!(fblfo, orbit.fb.params.lfo);
+        set!(fblfodepth, orbit.fb.params.lfo_depth);
+        set!(fblfoshape, orbit.fb.params.lfo_shape);
+        set!(compattack, orbit.comp.params.attack);
+        set!(comprelease, orbit.comp.params.release);
+        set!(comporbit, orbit.comp_orbit);
+        set_pos!(repeatbpm, orbit.repeat.params.bpm);
+        set_pos!(repeatbeats, orbit.repeat.params.beats);
+        set_pos!(repeatsize, orbit.repeat.params.size);
+        set!(repeatchance, orbit.repeat.params.chance);
+        set!(repeatpitch, orbit.repeat.params.pitch);
+        set!(repeatdecay, orbit.repeat.params.decay);
+        let touches_repeat = event.repeat.is_some()
+            || event.repeatbpm.is_some()
+            || event.repeatbeats.is_some()
+            || event.repeatsize.is_some()
+            || event.repeatchance.is_some()
+            || event.repeatpitch.is_some()
+            || event.repeatdecay.is_some();
+        if touches_repeat {
+            // First use allocates the capture ring; recording starts here
+            orbit.repeat.arm(self.sr);
+        }
+        match event.repeat {
+            Some(true) => orbit.repeat.trigger(),
+            Some(false) => orbit.repeat.release(),
+            None => {}
+        }
+        set_pos!(silencethresh, orbit.silence_threshold);
+        if let Some(hold) = event.silencehold {
+            orbit.set_silence_hold(hold);
+        }
+        for (i, name) in event.orbit_inserts.iter().enumerate() {
+            if let Some(name) = name {
+                orbit.inserts.assign(i, name, &mut self.insert_effects);
+            }
+        }
+    }
+
+    /// Runs the events due by now. Events more than `LATE_TOLERANCE` behind
+    /// are dropped, unless they carry `late_ok`.
     fn process_schedule(&mut self) {
-        let tolerance = (0.02 * self.sr as f64) as u64;
+        let tolerance = (LATE_TOLERANCE * self.sr as f64) as u64;
         loop {
             let t = match self.schedule.peek_tick() {
                 Some(t) if t <= self.tick => t,
@@ -1026,13 +2701,18 @@ impl Engine {
                 None => return,
             };
 
-            if diff < tolerance {
-                self.process_event(&event);
+            if diff < tolerance || event.late_ok == Some(true) {
+                self.run_event(&event);
             } else {
                 #[cfg(feature = "native")]
-                self.metrics
-                    .dropped_events
-                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                {
+                    self.metrics
+                        .dropped_events
+                        .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    if let Some(token) = event.ack {
+                        self.send_ack(token, None);
+                    }
+                }
             }
         }
     }
@@ -1048,6 +2728,7 @@ impl Engine {
     ) {
         let base_idx = sample_idx * self.output_channels;
         let num_pairs = self.output_channels / 2;
+        let num_orbits = self.orbits.len();
 
         for c in 0..self.output_channels {
             output[base_idx + c] = 0.0;
@@ -1057,6 +2738,15 @@ impl Engine {
         for orbit in &mut self.orbits {
             orbit.clear_bus();
         }
+        self.cue.clear_bus();
+        let cue_pair = self.cue_out.filter(|&p| p < num_pairs);
+        let on_ring = match self.out_mode {
+            OutMode::Pairs => (0..num_orbits)
+                .filter(|&oi| !self.routes.is_routed(oi))
+                .fold(0, |mask, oi| mask | 1 << oi),
+            OutMode::Stereo | OutMode::Mono => 0,
+        };
+        self.spatial.clear(cue_pair, on_ring);
 
         // Process voices - matches dough.c behavior exactly:
         // When a voice dies, it's freed immediately and the loop continues,
@@ -1069,6 +2759,7 @@ impl Engine {
 
         let mut i = 0;
         while i < self.active_voices {
+            self.voices[i].check_gate_end(self.tick);
             #[cfg(all(feature = "native", feature = "profiling"))]
             let alive = {
                 let mut alive = false;
@@ -1115,14 +2806,59 @@ impl Engine {
                 self.free_voice(i);
                 continue;
             }
+            mix_voice(
+                &mut self.orbits,
+                &mut self.cue,
+                &mut self.spatial,
+                &self.voices[i],
+            );
+            #[cfg(feature = "native")]
+            if let Some(taps) = self.taps.as_mut().filter(|t| t.is_active()) {
+                let v = &self.voices[i];
+                taps.capture(tap::TapTarget::Voice(v.id), v.ch);
+            }
+            i += 1;
+        }
+
+        let mut f = 0;
+        while f < self.active_fades {
+            #[cfg(feature = "native")]
+            let alive =
+                self.fading[f].process(isr, web_pcm, sample_idx, live_input, self.input_channels);
+            #[cfg(not(feature = "native"))]
+            let alive = self.fading[f].process(
+                isr,
+                self.sample_pool.data.as_slice(),
+                self.samples.as_slice(),
+                web_pcm,
+                sample_idx,
+                live_input,
+                self.input_channels,
+            );
+            if !alive {
+                self.active_fades -= 1;
+                self.fading.swap(f, self.active_fades);
+                continue;
+            }
+            mix_voice(
+                &mut self.orbits,
+                &mut self.cue,
+                &mut self.spatial,
+                &self.fading[f],
+            );
+            f += 1;
+        }
 
-            let orbit_idx = self.voices[i].params.orbit % MAX_ORBITS;
-            let orbit = &mut self.orbits[orbit_idx];
+        if self.click.is_active() {
+            let s = self.click.next(self.tick, &self.tempo);
+            let orbit = if self.click.cue {
+                &mut self.cue
+            } else {
+                &mut self.orbits[self.click.orbit % num_orbits]
+            };
             for c in 0..CHANNELS {
-                orbit.add_dry(c, self.voices[i].ch[c]);
+                orbit.add_dry(c, s);
             }
-
-            i += 1;
         }
 
         // Phase 1: run the orbit FX chain. After process(), orbit.bus
@@ -1131,9 +2867,11 @@ impl Engine {
         let orbit_fx_start = std::time::Instant::now();
         let mut orbit_bus = [[0.0f32; CHANNELS]; MAX_ORBITS];
         for (oi, orbit) in self.orbits.iter_mut().enumerate() {
+            orbit.tick_lanes(isr);
             orbit.process();
             orbit_bus[oi] = orbit.bus;
         }
+        self.cue.process();
         #[cfg(all(feature = "native", feature = "profiling"))]
         let orbit_fx_ns = orbit_fx_start.elapsed().as_nanos() as u64;
 
@@ -1142,39 +2880,71 @@ impl Engine {
         #[cfg(all(feature = "native", feature = "profiling"))]
         let final_mix_start = std::time::Instant::now();
         for (oi, orbit) in self.orbits.iter_mut().enumerate() {
-            let out_pair = oi % num_pairs;
-            let pair_offset = out_pair * 2;
             let cp = orbit.comp.params;
 
-            let total = orbit_bus[oi];
+            let mut total = orbit_bus[oi];
+            let mut duck = 1.0;
 
             if cp.amount > 0.0 {
-                let sc = orbit.comp_orbit % MAX_ORBITS;
+                let sc = orbit.comp_orbit % num_orbits;
                 let sc_total = orbit_bus[sc];
                 let sc_level = sc_total[0].abs().max(sc_total[1].abs());
                 let attack_coeff = (isr / cp.attack.max(0.0001)).min(1.0);
                 let release_coeff = (isr / cp.release.max(0.0001)).min(1.0);
                 let env = orbit.comp.process(sc_level, attack_coeff, release_coeff);
-                let gain = (1.0 - env).powf(1.0 + cp.amount * 4.0);
-                for c in 0..CHANNELS {
-                    output[base_idx + pair_offset + c] += total[c] * gain;
-                }
-                #[cfg(feature = "native")]
-                if self.recorder.target_orbit().is_some() {
-                    let bus_idx = (oi * block_samples + sample_idx) * CHANNELS;
-                    self.orbit_rec_bus[bus_idx] = total[0] * gain;
-                    self.orbit_rec_bus[bus_idx + 1] = total[1] * gain;
+                duck = (1.0 - env).powf(1.0 + cp.amount * 4.0);
+                total = [total[0] * duck, total[1] * duck];
+            }
+
+            let frame = &mut output[base_idx..base_idx + self.output_channels];
+            self.spatial.mix(oi, duck, frame);
+            match self.out_mode {
+                _ if self.routes.is_routed(oi) => self.routes.mix(oi, total, frame),
+                OutMode::Pairs => {
+                    let out_pair = match cue_pair {
+                        Some(cp) if num_pairs > 1 => {
+                            let p = oi % (num_pairs - 1);
+                            p + usize::from(p >= cp)
+                        }
+                        _ => oi % num_pairs,
+                    };
+                    let pair_offset = out_pair * 2;
+                    for c in 0..CHANNELS {
+                        frame[pair_offset + c] += total[c];
+                    }
                 }
-            } else {
-                for c in 0..CHANNELS {
-                    output[base_idx + pair_offset + c] += total[c];
+                // Orbits without a channel group of their own stay silent
+                OutMode::Stereo if oi < num_pairs => {
+                    for c in 0..CHANNELS {
+                        frame[oi * CHANNELS + c] += total[c];
+                    }
                 }
-                #[cfg(feature = "native")]
-                if self.recorder.target_orbit().is_some() {
-                    let bus_idx = (oi * block_samples + sample_idx) * CHANNELS;
-                    self.orbit_rec_bus[bus_idx] = total[0];
-                    self.orbit_rec_bus[bus_idx + 1] = total[1];
+                OutMode::Mono if oi < frame.len() => {
+                    frame[oi] += (total[0] + total[1]) * 0.5;
                 }
+                OutMode::Stereo | OutMode::Mono => {}
+            }
+            orbit.follow(total);
+            #[cfg(feature = "native")]
+            if let Some(taps) = self.taps.as_mut().filter(|t| t.is_active()) {
+                taps.capture(tap::TapTarget::Orbit(oi), total);
+            }
+            #[cfg(feature = "native")]
+            if matches!(self.recorder.source(), RecSource::Orbit(_)) {
+                let bus_idx = (oi * block_samples + sample_idx) * CHANNELS;
+                self.orbit_rec_bus[bus_idx] = total[0];
+                self.orbit_rec_bus[bus_idx + 1] = total[1];
+            }
+        }
+
+        self.master
+            .process(&mut output[base_idx..base_idx + self.output_channels]);
+
+        // Cue skips the master chain: its correction EQ is for the speakers
+        if let Some(cp) = cue_pair {
+            let pair_base = base_idx + cp * CHANNELS;
+            for c in 0..CHANNELS {
+                output[pair_base + c] += self.cue.bus[c] * self.cue_gain;
             }
         }
 
@@ -1184,6 +2954,37 @@ impl Engine {
             output[pair_base + 1] = soft_clip_sample(output[pair_base + 1]);
         }
 
+        // Test signal bypasses the clipper so its level stays calibrated
+        if self.test_signal.is_active() {
+            let s = self.test_signal.next();
+            self.add_to_outputs(output, base_idx, self.test_signal.target(), s);
+        }
+
+        // So do calibration clicks, which listen for themselves on the input
+        if self.calibration.is_running() {
+            let ic = self.input_channels;
+            let frame = live_input
+                .get(sample_idx * ic..(sample_idx + 1) * ic)
+                .unwrap_or(&[]);
+            let s = self.calibration.next(frame);
+            self.add_to_outputs(output, base_idx, self.calibration.target(), s);
+            if let Some(latency) = self.calibration.take_result() {
+                self.input_latency = latency;
+                #[cfg(feature = "native")]
+                self.metrics.store_input_latency(latency);
+            }
+        }
+
+        if self.fade.is_active() {
+            let (gain, silent) = self.fade.step();
+            for s in &mut output[base_idx..base_idx + self.output_channels] {
+                *s *= gain;
+            }
+            if silent {
+                self.silence();
+            }
+        }
+
         #[cfg(all(feature = "native", feature = "profiling"))]
         {
             let profiler = &self.metrics.profiler;
@@ -1197,9 +2998,24 @@ impl Engine {
         }
     }
 
+    /// Adds `s` to output channel `target`, or to every output.
+    fn add_to_outputs(&self, output: &mut [f32], base_idx: usize, target: Option<usize>, s: f32) {
+        match target {
+            Some(ch) if ch < self.output_channels => output[base_idx + ch] += s,
+            Some(_) => {}
+            None => {
+                for c in 0..self.output_channels {
+                    output[base_idx + c] += s;
+                }
+            }
+        }
+    }
+
     pub fn process_block(&mut self, output: &mut [f32], web_pcm: &[f32], live_input: &[f32]) {
         #[cfg(feature = "native")]
         let start = std::time::Instant::now();
+        #[cfg(all(feature = "native", feature = "profiling"))]
+        let _alloc_scope = telemetry::AudioThreadScope::enter();
 
         let samples = output.len() / self.output_channels;
 
@@ -1275,17 +3091,30 @@ impl Engine {
             #[cfg(feature = "profiling")]
             let recorder_start = std::time::Instant::now();
             let n = samples * CHANNELS;
-            if let Some(oi) = self.recorder.target_orbit() {
-                let start_idx = oi * samples * CHANNELS;
-                self.recorder.capture_block(
-                    &self.orbit_rec_bus[start_idx..start_idx + n],
-                    samples,
-                    CHANNELS,
-                );
-            } else {
-                self.recorder
-                    .capture_block(output, samples, self.output_channels);
+            match self.recorder.source() {
+                RecSource::Orbit(oi) => {
+                    let start_idx = oi * samples * CHANNELS;
+                    self.recorder.capture_block(
+                        &self.orbit_rec_bus[start_idx..start_idx + n],
+                        samples,
+                        CHANNELS,
+                    );
+                }
+                RecSource::Input(first) => {
+                    self.recorder
+                        .capture_input(live_input, samples, self.input_channels, first);
+                }
+                RecSource::Output => {
+                    self.recorder
+                        .capture_block(output, samples, self.output_channels);
+                }
+            }
+            let done = self.recorder.should_auto_stop(self.is_idle()) || self.recorder.is_full();
+            if done && self.recorder.stop().is_some() {
+                self.store_recording();
             }
+            self.retro
+                .capture_block(output, samples, self.output_channels);
             #[cfg(feature = "profiling")]
             self.metrics.profiler.record_phase(
                 ProfilePhase::RecorderCapture,
@@ -1293,6 +3122,18 @@ impl Engine {
             );
         }
 
+        // Spectrum of the first output pair, for UIs that turned it on
+        #[cfg(feature = "native")]
+        if self.metrics.spectrum.is_enabled() {
+            for frame in output.chunks(self.output_channels).take(samples) {
+                let right = frame.get(1).copied().unwrap_or(frame[0]);
+                self.spectrum.push((frame[0] + right) * 0.5);
+            }
+            if let Some(mags) = self.spectrum.analyze() {
+                self.metrics.spectrum.publish(mags);
+            }
+        }
+
         #[cfg(feature = "native")]
         {
             use std::sync::atomic::Ordering;
@@ -1308,12 +3149,60 @@ impl Engine {
             self.metrics
                 .peak_voices
                 .fetch_max(self.active_voices as u32, Ordering::Relaxed);
+            self.metrics
+                .store_voice_envelopes(self.voices[..self.active_voices].iter().map(|v| {
+                    telemetry::VoiceEnvelope {
+                        voice_id: v.id,
+                        stage: v.dahdsr.state(),
+                        value: v.dahdsr.value(),
+                    }
+                }));
             self.metrics
                 .schedule_depth
                 .store(self.schedule.len() as u32, Ordering::Relaxed);
             self.metrics
                 .time_bits
                 .store(self.time.to_bits(), Ordering::Relaxed);
+            self.metrics.tick.store(self.tick, Ordering::Relaxed);
+            let (sleeps, wakes) = self
+                .orbits
+                .iter()
+                .fold((0, 0), |(s, w), o| (s + o.sleeps, w + o.wakes));
+            self.metrics.orbit_sleeps.store(sleeps, Ordering::Relaxed);
+            self.metrics.orbit_wakes.store(wakes, Ordering::Relaxed);
+            self.metrics
+                .hot_frames
+                .store(self.master.hot_frames, Ordering::Relaxed);
+            for (level, orbit) in self.metrics.orbit_env.iter().zip(&self.orbits) {
+                level.store(orbit.env.to_bits(), Ordering::Relaxed);
+            }
+            for (levels, orbit) in self.metrics.return_env.iter().zip(&self.orbits) {
+                for (level, env) in levels.iter().zip(&orbit.return_env) {
+                    level.store(env.to_bits(), Ordering::Relaxed);
+                }
+            }
+            let monowarn = self.master.monowarn;
+            let mut anti =
+                u32::from(self.master.correlation.check(monowarn, samples)) << MAX_ORBITS;
+            let mut warnings = self.master.correlation.warnings;
+            for (i, (corr, orbit)) in self
+                .metrics
+                .orbit_corr
+                .iter()
+                .zip(&mut self.orbits)
+                .enumerate()
+            {
+                corr.store(orbit.correlation.value().to_bits(), Ordering::Relaxed);
+                anti |= u32::from(orbit.correlation.check(monowarn, samples)) << i;
+                warnings += orbit.correlation.warnings;
+            }
+            self.metrics
+                .master_corr
+                .store(self.master.correlation.value().to_bits(), Ordering::Relaxed);
+            self.metrics.anti_phase.store(anti, Ordering::Relaxed);
+            self.metrics
+                .mono_warnings
+                .store(warnings, Ordering::Relaxed);
 
             let instant = self.metrics.load.instant_load();
             let smoothed = self.metrics.load.get_load();
@@ -1380,8 +3269,41 @@ impl Engine {
         }
     }
 
+    /// Stops everything over a short fade ([`PANIC_FADE_MS`]) so nothing
+    /// clicks. [`panic_now`](Self::panic_now) stops at once.
     pub fn panic(&mut self) {
+        self.fade_out(PANIC_FADE_MS);
+    }
+
+    /// Ramps the whole output down to silence over `ms` milliseconds,
+    /// clears the voices there like [`panic_now`](Self::panic_now), then
+    /// ramps back up over the same time for whatever plays next. Orbit
+    /// tails come back with it. 0 or less clears at once.
+    pub fn fade_out(&mut self, ms: f32) {
+        if ms <= 0.0 {
+            self.panic_now();
+            return;
+        }
+        self.fade.start((ms * 0.001 * self.sr) as u32);
+    }
+
+    /// Clears every voice, test signal and click at once, dropping any
+    /// fade in progress (may click).
+    pub fn panic_now(&mut self) {
+        self.fade = master::Fade::default();
+        self.silence();
+    }
+
+    fn silence(&mut self) {
+        #[cfg(feature = "native")]
+        for i in 0..self.active_voices {
+            self.send_voice_event(VoiceEventKind::Free, i);
+        }
         self.active_voices = 0;
+        self.active_fades = 0;
+        self.test_signal.start("off", None, None);
+        self.calibration = LatencyProbe::new(self.sr);
+        self.click.set_active(false);
     }
 }
 
@@ -1401,4 +3323,332 @@ mod tests {
         assert!((soft_clip_sample(0.1) - 0.1).abs() < 1e-2);
         assert!((soft_clip_sample(-0.05) + 0.05).abs() < 1e-2);
     }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn ack_reports_stable_voice_id() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let (tx, rx) = crossbeam_channel::bounded(4);
+        engine.set_ack_sender(tx);
+
+        engine.evaluate("/sound/sine/ack/5");
+        engine.evaluate("/sound/sine/ack/6");
+        let first = rx.try_recv().unwrap();
+        let second = rx.try_recv().unwrap();
+        assert_eq!(first.token, 5);
+        assert_ne!(first.voice_id, second.voice_id);
+
+        // Follow-up targets by ID, not index
+        let id = second.voice_id.unwrap();
+        assert_eq!(engine.evaluate(&format!("/vid/{id}/freq/220")), Some(1));
+        assert_eq!(engine.voices[1].params.freq, 220.0);
+        assert_eq!(engine.evaluate("/vid/999/freq/220"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn named_groups_address_their_voices() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/id/bass/freq/55");
+        engine.evaluate("/sound/sine/freq/440");
+        engine.evaluate("/sound/sine/id/bass/freq/110");
+        // Swap-remove reorders the array; the group still finds its voices
+        engine.free_voice(0);
+
+        engine.evaluate("/doux/update/id/bass/lpf/400");
+        let bass: Vec<_> = engine.voices[..engine.active_voices]
+            .iter()
+            .filter(|v| v.params.group.is_some())
+            .collect();
+        assert_eq!(bass.len(), 1);
+        assert_eq!(bass[0].params.freq, 110.0);
+        assert_eq!(bass[0].params.lpf, Some(400.0));
+        assert_eq!(engine.evaluate("/doux/update/id/lead/lpf/400"), None);
+
+        engine.process_block(&mut [0.0; 128], &[], &[]);
+        engine.evaluate("/doux/release_id/bass");
+        for v in &engine.voices[..engine.active_voices] {
+            assert_eq!(v.params.group.is_some(), v.dahdsr.is_releasing());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn set_max_voices_grows_and_steals_on_shrink() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 2, 64);
+        let pool = engine.voices.as_ptr();
+        engine.set_max_voices(4);
+        assert_eq!(engine.voices.as_ptr(), pool);
+        for _ in 0..4 {
+            assert!(engine.evaluate("/sound/sine").is_some());
+        }
+        assert!(engine.evaluate("/sound/sine").is_none());
+        engine.dsp();
+
+        engine.evaluate("/doux/max_voices/n/2");
+        assert_eq!(engine.max_voices, 2);
+        let cut = engine.voices[..engine.active_voices]
+            .iter()
+            .filter(|v| v.dahdsr.is_releasing())
+            .count();
+        assert_eq!(cut, 2);
+        for _ in 0..4 {
+            engine.dsp();
+        }
+        assert_eq!(engine.active_voices, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn seeds_make_noise_reproducible_per_voice_and_engine() {
+        let render = |cmds: &[&str]| {
+            let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+            for cmd in cmds {
+                engine.evaluate(cmd);
+            }
+            engine.dsp();
+            engine.output.clone()
+        };
+        let seeded = render(&["/sound/white/seed/7"]);
+        // Another voice first doesn't shift a seeded voice's noise
+        assert_eq!(
+            seeded,
+            render(&["/sound/sine/gain/0", "/sound/white/seed/7"])
+        );
+        assert_ne!(seeded, render(&["/sound/white/seed/8"]));
+
+        // Unseeded voices draw from the engine's seed
+        let engine_seeded = render(&["/doux/seed/7", "/sound/white"]);
+        assert_eq!(engine_seeded, seeded);
+        assert_ne!(engine_seeded, render(&["/sound/white"]));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn variables_are_substituted_and_computed_in_later_events() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        assert_eq!(engine.evaluate("set base 220"), None);
+        engine.evaluate("/doux/set/shape/saw; set top $base*4-20");
+        assert_eq!(engine.var("top"), Some("860"));
+
+        let i = engine
+            .evaluate("/sound/$shape/freq/$base*2/lpf/$top")
+            .unwrap();
+        assert_eq!(engine.voices[i].params.sound, Source::Saw);
+        assert_eq!(engine.voices[i].params.freq, 440.0);
+        assert_eq!(engine.voices[i].params.lpf, Some(860.0));
+
+        // Unknown names stay as typed; an empty set removes a variable
+        assert_eq!(
+            event::substitute("/freq/$nope/pan/$base", &engine.vars),
+            "/freq/$nope/pan/220"
+        );
+        engine.evaluate("set base");
+        assert_eq!(engine.var("base"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn cue_voices_play_only_on_the_reserved_cue_pair() {
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/master/cueout/1/cuegain/0.5");
+        assert_eq!(engine.cue_out, Some(1));
+        // Orbit 1 would normally land on pair 1; with the cue there it can't
+        engine.evaluate("/sound/sine/orbit/1/verb/0.2");
+        engine.evaluate("/sound/sine/freq/330/cue/1/delay/0.4");
+        assert!(engine.voices[1].params.cue);
+        assert_eq!(engine.cue.delay_level, 0.4);
+        assert_eq!(engine.orbits[0].delay_level, 0.0);
+        assert_eq!(engine.orbits[1].verb_level, 0.2);
+
+        let peaks = |engine: &mut Engine| {
+            let mut peak = [0.0f32; 4];
+            for _ in 0..20 {
+                engine.dsp();
+                for frame in engine.output.chunks(4) {
+                    for (p, x) in peak.iter_mut().zip(frame) {
+                        *p = p.max(x.abs());
+                    }
+                }
+            }
+            peak
+        };
+        let peak = peaks(&mut engine);
+        assert!(peak[0] > 0.01 && peak[2] > 0.01, "{peak:?}");
+
+        // A fresh engine, so no orbit FX tails reach the main pair
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/master/cueout/1");
+        engine.evaluate("/sound/sine/cue/1");
+        let peak = peaks(&mut engine);
+        assert_eq!(peak[0], 0.0);
+        assert!(peak[2] > 0.01);
+
+        engine.evaluate("/doux/master/cueout/off");
+        assert_eq!(engine.cue_out, None);
+        engine.evaluate("/doux/reset");
+        engine.evaluate("/sound/sine/cue/1");
+        assert_eq!(peaks(&mut engine), [0.0; 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn events_and_modulation_share_param_ranges() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/saw/voice/0/gate/0/freq/-100/lpq/7/pan/nan");
+        let p = &engine.voices[0].params;
+        assert_eq!((p.freq, p.lpq, p.pan), (0.0, 1.0, 0.5));
+
+        // wrap/0 is the identity, not the bottom of a fold range
+        engine.evaluate("/voice/0/wrap/0");
+        assert_eq!(engine.voices[0].params.wrap, Some(0.0));
+
+        // Chains and LFOs stop at the same limits
+        engine.evaluate("/voice/0/freq/440/lpq/0.5>4:0.001/fmfb/-50~50:4");
+        for _ in 0..4 {
+            engine.dsp();
+        }
+        let p = &engine.voices[0].params;
+        assert_eq!(p.lpq, 1.0);
+        assert!((-10.0..=10.0).contains(&p.fmfb));
+
+        // An LFO pushed into the clamp comes back out without drift
+        engine.evaluate("/voice/0/pan/0.9/lfo1target/pan/lfo1depth/0.5/lfo1rate/50");
+        let (mut lo, mut hi) = (f32::MAX, f32::MIN);
+        for _ in 0..30 {
+            engine.dsp();
+            let pan = engine.voices[0].params.pan;
+            lo = lo.min(pan);
+            hi = hi.max(pan);
+        }
+        assert!(lo < 0.45 && hi == 1.0);
+        engine.evaluate("/voice/0/lfo1target/off");
+        engine.dsp();
+        assert!((engine.voices[0].params.pan - 0.9).abs() < 1e-5);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn several_events_per_line_start_in_the_same_block() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let voices = engine.evaluate_all("/s/sine/freq/220; /s/saw/freq/330;");
+        assert_eq!(voices, [0, 1]);
+        assert_eq!(engine.voices[1].params.freq, 330.0);
+
+        // Scheduled parts go to the scheduler, commands return nothing
+        assert_eq!(
+            engine.evaluate_all("/s/sine/tick/64; /doux/orbits/n/4"),
+            Vec::<usize>::new()
+        );
+        assert_eq!(engine.orbits.len(), 4);
+        assert_eq!(engine.evaluate("/s/sine; /s/saw"), Some(3));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn panic_fades_the_output_before_clearing_voices() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let mut out = vec![0.0f32; 64 * 2];
+        let mut peak = |engine: &mut Engine| {
+            engine.process_block(&mut out, &[], &[]);
+            out.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+        };
+        engine.evaluate("/sound/sine/gate/1");
+        for _ in 0..20 {
+            peak(&mut engine);
+        }
+        let level = peak(&mut engine);
+        assert!(level > 0.1);
+
+        // 8 ms is 6 blocks: the level falls all the way, then the voice goes
+        engine.panic();
+        let peaks: Vec<_> = (0..6).map(|_| peak(&mut engine)).collect();
+        assert!(peaks[0] < level && peaks[5] < level * 0.01, "{peaks:?}");
+        assert!(peaks.windows(2).all(|w| w[1] < w[0]), "{peaks:?}");
+        assert_eq!(engine.active_voices, 0);
+
+        // The output comes back up for the next sound
+        engine.evaluate("/sound/sine/gate/1");
+        let peaks: Vec<_> = (0..8).map(|_| peak(&mut engine)).collect();
+        assert!(
+            peaks[0] < level * 0.1 && peaks[7] > level * 0.9,
+            "{peaks:?}"
+        );
+
+        engine.fade_out(0.0);
+        assert_eq!(engine.active_voices, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn is_idle_waits_for_orbit_tails() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 512);
+        assert!(engine.is_idle());
+        engine.evaluate("/sound/sine/gate/0.01/release/0.01/delay/0.5/delaytime/0.1");
+
+        let mut blocks = 0;
+        let mut voices_done_at = None;
+        while !engine.is_idle() && blocks < 1000 {
+            engine.dsp();
+            blocks += 1;
+            if engine.active_voices == 0 && voices_done_at.is_none() {
+                voices_done_at = Some(blocks);
+            }
+        }
+        assert!(engine.is_idle());
+        // The delay tail plus silence holdoff outlasts the voice by > 1s
+        assert!(blocks - voices_done_at.unwrap() > 48000 / 512);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn cut_voices_fade_out_under_their_replacement() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/cut/1/gate/1");
+        engine.dsp();
+        let id = engine.voices[0].id;
+        engine.evaluate("/sound/saw/cut/1/gate/1/cutfade/0.01");
+        assert_eq!((engine.active_voices, engine.active_fades), (1, 1));
+        assert_eq!(engine.voices[0].id, id);
+        assert_eq!(engine.voices[0].dahdsr.current_val, 0.0);
+        assert_eq!(engine.fading[0].params.sound, Source::Sine);
+        // 10ms of fade at 64-frame blocks
+        for _ in 0..10 {
+            engine.dsp();
+        }
+        assert_eq!(engine.active_fades, 0);
+
+        // No fade: the old voice stops and the new one takes over its level
+        engine.evaluate("/sound/sine/cut/1/gate/1/cutfade/0");
+        assert_eq!(engine.active_fades, 0);
+        assert!(engine.voices[0].dahdsr.current_val > 0.0);
+
+        // Restarting a voice by index fades it the same way
+        engine.evaluate("/voice/0/sound/tri/reset/1");
+        assert_eq!(engine.active_fades, 1);
+        engine.panic_now();
+        assert_eq!(engine.active_fades, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn voice_events_follow_lifecycle() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let (tx, rx) = crossbeam_channel::bounded(16);
+        engine.set_voice_event_sender(tx);
+
+        engine.evaluate("/sound/sine/cut/1");
+        engine.evaluate("/sound/sine/cut/1");
+        engine.panic_now();
+
+        let kinds: Vec<_> = rx.try_iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                VoiceEventKind::Trigger,
+                VoiceEventKind::Respawn,
+                VoiceEventKind::Free
+            ]
+        );
+    }
 }
@@ -0,0 +1,490 @@
+//! Master bus: the last stage before the hardware outputs.
+//!
+//! Runs on the summed orbits, per output pair: gain → DC blocker → EQ
+//! (correction bands, then tilt) → crossfeed → peak limiter → the engine's
+//! tanh soft clip. Configured with `/doux/master/...`:
+//!
+//! | Key         | Effect                                                  |
+//! |-------------|---------------------------------------------------------|
+//! | `gain`      | Linear master gain (default 1)                          |
+//! | `limit`     | Limiter ceiling, linear peak; 0 disables (default)      |
+//! | `dc`        | `1` enables the DC blocker                              |
+//! | `tilt`      | Spectral tilt, −1 (dark) to 1 (bright)                  |
+//! | `eq1`–`eq8` | Correction band `type:freq:gain:q`, or `off`            |
+//! | `crossfeed` | Headphone crossfeed amount, 0–1; 0 disables (default)   |
+//! | `peakwarn`  | Counts frames louder than this linear peak; 0 disables  |
+//! | `monowarn`  | Warns when a bus stays below this negative correlation; 0 disables |
+//!
+//! Band types are `pk`, `ls`, `hs`, `lp`, `hp` and `no` (notch). A whole
+//! correction can also be loaded from an Equalizer APO / REW filter file
+//! with [`Master::load_correction`].
+
+use crate::dsp::{Biquad, CorrelationMeter};
+use crate::effects::{DcBlocker, Tilt};
+use crate::types::{FilterType, CHANNELS};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+/// Correction bands on the master bus.
+pub const MASTER_EQ_BANDS: usize = 8;
+
+/// Time for the limiter gain to recover by ~63% after a peak.
+const LIMIT_RELEASE_SECS: f32 = 0.1;
+/// Crossfeed corner: below it the opposite ear hears most of the signal,
+/// as with speakers (bs2b uses 700 Hz as its default).
+const CROSSFEED_HZ: f32 = 700.0;
+/// Opposite-ear level at `crossfeed = 1` (about −4.5 dB, as in bs2b).
+const CROSSFEED_LEVEL: f32 = 0.6;
+
+/// One master EQ band. `q` is the linear Q for every type.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    pub kind: FilterType,
+    pub freq: f32,
+    pub gain: f32,
+    pub q: f32,
+}
+
+impl EqBand {
+    /// Q as [`Biquad`] expects it: resonance in dB for lowpass/highpass.
+    fn biquad_q(&self) -> f32 {
+        match self.kind {
+            FilterType::Lowpass | FilterType::Highpass => 20.0 * self.q.max(0.05).log10(),
+            _ => self.q,
+        }
+    }
+
+    fn kind_from_code(code: &str) -> Option<FilterType> {
+        Some(match code.to_ascii_lowercase().as_str() {
+            "pk" | "peq" => FilterType::Peaking,
+            "ls" | "lsc" => FilterType::Lowshelf,
+            "hs" | "hsc" => FilterType::Highshelf,
+            "lp" | "lpq" => FilterType::Lowpass,
+            "hp" | "hpq" => FilterType::Highpass,
+            "no" => FilterType::Notch,
+            _ => return None,
+        })
+    }
+}
+
+/// Parses `type:freq[:gain[:q]]`, e.g. `pk:120:-4:2`.
+impl FromStr for EqBand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts
+            .next()
+            .and_then(Self::kind_from_code)
+            .ok_or_else(|| format!("unknown EQ band type in '{s}'"))?;
+        let mut num = |default: Option<f32>| match parts.next() {
+            Some(v) => v.parse().map_err(|_| format!("bad number '{v}' in '{s}'")),
+            None => default.ok_or_else(|| format!("missing frequency in '{s}'")),
+        };
+        Ok(Self {
+            kind,
+            freq: num(None)?,
+            gain: num(Some(0.0))?,
+            q: num(Some(std::f32::consts::FRAC_1_SQRT_2))?,
+        })
+    }
+}
+
+pub struct Master {
+    pub gain: f32,
+    /// Limiter ceiling (0 = off).
+    pub limit: f32,
+    pub dc: bool,
+    /// Headphone crossfeed amount (0 = off).
+    pub crossfeed: f32,
+    /// Peak warning threshold (0 = off).
+    pub peakwarn: f32,
+    /// Frames that reached `peakwarn`, after the limiter.
+    pub hot_frames: u64,
+    /// Sustained correlation below `-monowarn` on the master or an orbit
+    /// raises a mono-compatibility warning (0 = off).
+    pub monowarn: f32,
+    /// Stereo correlation of the main output pair, after the limiter.
+    pub correlation: CorrelationMeter,
+    /// Spectral tilt (0 = flat).
+    pub tilt: f32,
+    /// Correction bands, applied in order.
+    pub eq: [Option<EqBand>; MASTER_EQ_BANDS],
+    /// Linear gain ahead of the correction bands (a file's `Preamp`).
+    pub eq_preamp: f32,
+    eq_state: Vec<[Biquad; MASTER_EQ_BANDS]>,
+    tilt_state: Vec<Tilt>,
+    sr: f32,
+    dc_block: Vec<DcBlocker>,
+    /// Low-passed input per channel, fed to the opposite ear.
+    crossfeed_lp: Vec<f32>,
+    crossfeed_coeff: f32,
+    /// Limiter gain per output pair, linked across the pair.
+    limit_gain: Vec<f32>,
+    release_coeff: f32,
+}
+
+impl Master {
+    pub fn new(sr: f32, output_channels: usize) -> Self {
+        Self {
+            gain: 1.0,
+            limit: 0.0,
+            dc: false,
+            crossfeed: 0.0,
+            peakwarn: 0.0,
+            hot_frames: 0,
+            monowarn: 0.0,
+            correlation: CorrelationMeter::new(sr),
+            tilt: 0.0,
+            eq: [None; MASTER_EQ_BANDS],
+            eq_preamp: 1.0,
+            eq_state: vec![[Biquad::default(); MASTER_EQ_BANDS]; output_channels],
+            tilt_state: vec![Tilt::default(); output_channels],
+            sr,
+            dc_block: vec![DcBlocker::default(); output_channels],
+            crossfeed_lp: vec![0.0; output_channels],
+            crossfeed_coeff: 1.0 - (-2.0 * PI * CROSSFEED_HZ / sr).exp(),
+            limit_gain: vec![1.0; output_channels.div_ceil(CHANNELS)],
+            release_coeff: 1.0 - (-1.0 / (LIMIT_RELEASE_SECS * sr)).exp(),
+        }
+    }
+
+    /// Processes one output frame (all channels) in place, before the clipper.
+    #[inline]
+    pub fn process(&mut self, frame: &mut [f32]) {
+        if self.gain != 1.0 {
+            for s in frame.iter_mut() {
+                *s *= self.gain;
+            }
+        }
+        if self.dc {
+            for (s, dc) in frame.iter_mut().zip(&mut self.dc_block) {
+                *s = dc.process(*s);
+            }
+        }
+        if self.eq.iter().any(Option::is_some) {
+            for (s, state) in frame.iter_mut().zip(&mut self.eq_state) {
+                *s *= self.eq_preamp;
+                for (band, biquad) in self.eq.iter().zip(state.iter_mut()) {
+                    if let Some(b) = band {
+                        *s = biquad.process_with_gain(
+                            *s,
+                            b.kind,
+                            b.freq,
+                            b.biquad_q(),
+                            b.gain,
+                            self.sr,
+                        );
+                    }
+                }
+            }
+        }
+        if self.tilt != 0.0 {
+            for (s, tilt) in frame.iter_mut().zip(&mut self.tilt_state) {
+                *s = tilt.process(*s, self.tilt, self.sr);
+            }
+        }
+        if self.crossfeed > 0.0 {
+            let g = self.crossfeed.min(1.0) * CROSSFEED_LEVEL;
+            let norm = (1.0 + g).recip();
+            for (pair, lp) in frame
+                .chunks_exact_mut(CHANNELS)
+                .zip(self.crossfeed_lp.chunks_exact_mut(CHANNELS))
+            {
+                for (s, l) in pair.iter().zip(lp.iter_mut()) {
+                    *l += (*s - *l) * self.crossfeed_coeff;
+                }
+                // Lows bleed into the other ear, the direct highs are lifted
+                // by the same amount, so centred material stays flat
+                let (l, r) = (pair[0], pair[1]);
+                pair[0] = (l + g * (l - lp[0]) + g * lp[1]) * norm;
+                pair[1] = (r + g * (r - lp[1]) + g * lp[0]) * norm;
+            }
+        }
+        if self.limit > 0.0 {
+            for (pair, g) in frame.chunks_mut(CHANNELS).zip(&mut self.limit_gain) {
+                let peak = pair.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                let target = if peak > self.limit {
+                    self.limit / peak
+                } else {
+                    1.0
+                };
+                // Instant attack keeps peaks under the ceiling; smooth release
+                *g = if target < *g {
+                    target
+                } else {
+                    *g + (target - *g) * self.release_coeff
+                };
+                for s in pair {
+                    *s *= *g;
+                }
+            }
+        }
+        if self.peakwarn > 0.0 && frame.iter().any(|s| s.abs() >= self.peakwarn) {
+            self.hot_frames += 1;
+        }
+        if let [l, r, ..] = *frame {
+            self.correlation.process(l, r);
+        }
+    }
+
+    /// Sets or clears one correction band, restarting its filters.
+    pub fn set_eq_band(&mut self, idx: usize, band: Option<EqBand>) {
+        if idx >= MASTER_EQ_BANDS {
+            return;
+        }
+        self.eq[idx] = band;
+        for state in &mut self.eq_state {
+            state[idx] = Biquad::default();
+        }
+    }
+
+    /// Replaces the correction bands with an Equalizer APO / REW filter
+    /// file (`Preamp: -3 dB`, `Filter 1: ON PK Fc 120 Hz Gain -4 dB Q 2`).
+    /// Lines with `OFF`, comments and unknown lines are skipped. Returns the
+    /// number of bands loaded.
+    pub fn load_correction(&mut self, text: &str) -> Result<usize, String> {
+        let (preamp_db, bands) = parse_correction(text)?;
+        for i in 0..MASTER_EQ_BANDS {
+            self.set_eq_band(i, bands.get(i).copied());
+        }
+        self.eq_preamp = 10f32.powf(preamp_db / 20.0);
+        Ok(bands.len())
+    }
+
+    pub fn reset(&mut self) {
+        self.dc_block.fill(DcBlocker::default());
+        self.limit_gain.fill(1.0);
+        self.crossfeed_lp.fill(0.0);
+        self.eq_state.fill([Biquad::default(); MASTER_EQ_BANDS]);
+        self.tilt_state.fill(Tilt::default());
+    }
+}
+
+fn parse_correction(text: &str) -> Result<(f32, Vec<EqBand>), String> {
+    let mut preamp = 0.0;
+    let mut bands = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Preamp:") {
+            let db = rest.split_whitespace().next().unwrap_or("");
+            preamp = db
+                .parse()
+                .map_err(|_| format!("line {}: bad preamp '{db}'", n + 1))?;
+            continue;
+        }
+        let Some((_, spec)) = line.split_once(':').filter(|_| line.starts_with("Filter")) else {
+            continue;
+        };
+        let mut tokens = spec.split_whitespace();
+        if tokens.next() != Some("ON") {
+            continue;
+        }
+        let code = tokens.next().unwrap_or("");
+        let kind = EqBand::kind_from_code(code)
+            .ok_or_else(|| format!("line {}: unsupported filter type '{code}'", n + 1))?;
+        let mut band = EqBand {
+            kind,
+            freq: 0.0,
+            gain: 0.0,
+            q: std::f32::consts::FRAC_1_SQRT_2,
+        };
+        while let Some(key) = tokens.next() {
+            let field = match key {
+                "Fc" => &mut band.freq,
+                "Gain" => &mut band.gain,
+                "Q" => &mut band.q,
+                _ => continue,
+            };
+            let val = tokens.next().unwrap_or("");
+            *field = val
+                .parse()
+                .map_err(|_| format!("line {}: bad {key} '{val}'", n + 1))?;
+        }
+        if band.freq <= 0.0 {
+            return Err(format!("line {}: missing Fc", n + 1));
+        }
+        bands.push(band);
+    }
+    if bands.len() > MASTER_EQ_BANDS {
+        return Err(format!(
+            "{} filters, the master EQ has {MASTER_EQ_BANDS} bands",
+            bands.len()
+        ));
+    }
+    Ok((preamp, bands))
+}
+
+/// Level a fade reaches before the voices are cleared (−80 dB).
+const FADE_FLOOR: f32 = 1e-4;
+
+/// Exponential ramp of the whole output around a
+/// [`fade_out`](crate::Engine::fade_out): down to silence, where the
+/// voices are cleared, then back up over the same time. Any DC offset is
+/// ramped with everything else, so nothing steps.
+#[derive(Clone, Copy, Debug)]
+pub struct Fade {
+    gain: f32,
+    fall: f32,
+    rise: f32,
+    /// Frames until silence; 0 while rising back or idle.
+    left: u32,
+}
+
+impl Default for Fade {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            fall: 1.0,
+            rise: 1.0,
+            left: 0,
+        }
+    }
+}
+
+impl Fade {
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.left > 0 || self.gain < 1.0
+    }
+
+    /// Starts falling to silence over `frames`, from wherever the level is.
+    pub fn start(&mut self, frames: u32) {
+        let frames = frames.max(1);
+        let n = 1.0 / frames as f32;
+        self.fall = (FADE_FLOOR / self.gain.max(FADE_FLOOR)).powf(n);
+        self.rise = FADE_FLOOR.powf(-n);
+        self.left = frames;
+    }
+
+    /// Gain for the next frame, and whether this frame reached silence.
+    #[inline]
+    pub fn step(&mut self) -> (f32, bool) {
+        if self.left > 0 {
+            self.gain *= self.fall;
+            self.left -= 1;
+            if self.left == 0 {
+                self.gain = FADE_FLOOR;
+                return (0.0, true);
+            }
+        } else if self.gain < 1.0 {
+            self.gain = (self.gain * self.rise).min(1.0);
+        }
+        (self.gain, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FilterType;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    #[test]
+    fn master_applies_gain_dc_block_and_limiter() {
+        let mut master = Master::new(48000.0, 4);
+        master.gain = 0.5;
+        let mut frame = [0.8, -0.4, 0.2, 0.0];
+        master.process(&mut frame);
+        assert_eq!(frame, [0.4, -0.2, 0.1, 0.0]);
+
+        // Limiter links each pair and leaves quieter pairs alone
+        master.gain = 1.0;
+        master.limit = 0.5;
+        let mut frame = [2.0, 1.0, 0.2, 0.1];
+        master.process(&mut frame);
+        assert_eq!(frame, [0.5, 0.25, 0.2, 0.1]);
+
+        master.limit = 0.0;
+        master.dc = true;
+        let mut last = [0.0; 4];
+        for _ in 0..48000 {
+            last = [1.0; 4];
+            master.process(&mut last);
+        }
+        assert!(last.iter().all(|s| s.abs() < 1e-3));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn master_command_configures_bus() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/master/gain/0.8/limit/0.9/dc/1/crossfeed/0.5/peakwarn/0.95");
+        assert_eq!(engine.master.gain, 0.8);
+        assert_eq!(engine.master.limit, 0.9);
+        assert!(engine.master.dc);
+        assert_eq!(engine.master.crossfeed, 0.5);
+        assert_eq!(engine.master.peakwarn, 0.95);
+        engine.evaluate("/doux/master/tilt/-0.5/eq3/pk:250:-3:1.4");
+        assert_eq!(engine.master.tilt, -0.5);
+        assert_eq!(engine.master.eq[2].map(|b| b.freq), Some(250.0));
+        engine.evaluate("/doux/master/eq3/off");
+        assert!(engine.master.eq[2].is_none());
+        assert_eq!(engine.active_voices, 0);
+    }
+
+    #[test]
+    fn master_eq_loads_correction_file_and_cuts_at_band() {
+        let mut master = Master::new(48000.0, 2);
+        let text = "# REW export\nPreamp: -6 dB\n\
+                    Filter 1: ON PK Fc 1000 Hz Gain -12.0 dB Q 2.0\n\
+                    Filter 2: OFF PK Fc 50 Hz Gain 3.0 dB Q 1.0\n\
+                    Filter 3: ON HP Fc 20 Hz\n";
+        assert_eq!(master.load_correction(text), Ok(2));
+        assert_eq!(master.eq[1].map(|b| b.kind), Some(FilterType::Highpass));
+        assert!(master.eq[2].is_none());
+
+        let mut peak: f32 = 0.0;
+        for i in 0..9600 {
+            let s = (i as f32 * 2.0 * std::f32::consts::PI * 1000.0 / 48000.0).sin();
+            let mut frame = [s, s];
+            master.process(&mut frame);
+            if i > 4800 {
+                peak = peak.max(frame[0].abs());
+            }
+        }
+        // -6 dB preamp and -12 dB band
+        assert!((peak - 10f32.powf(-18.0 / 20.0)).abs() < 0.01);
+
+        assert!(master.load_correction("Filter 1: ON XX Fc 100 Hz").is_err());
+        assert_eq!(
+            "ls:80:3".parse(),
+            Ok(EqBand {
+                kind: FilterType::Lowshelf,
+                freq: 80.0,
+                gain: 3.0,
+                q: std::f32::consts::FRAC_1_SQRT_2,
+            })
+        );
+    }
+
+    #[test]
+    fn master_crossfeed_keeps_centre_flat_and_counts_hot_peaks() {
+        let mut master = Master::new(48000.0, 2);
+        master.crossfeed = 1.0;
+        master.peakwarn = 0.9;
+        let mut frame = [0.0; 2];
+        for _ in 0..4800 {
+            frame = [0.5, 0.5];
+            master.process(&mut frame);
+        }
+        assert!((frame[0] - 0.5).abs() < 1e-4 && (frame[1] - 0.5).abs() < 1e-4);
+
+        // A hard-left low tone reaches the right ear
+        master.reset();
+        let mut right: f32 = 0.0;
+        for i in 0..4800 {
+            let s = (i as f32 * 2.0 * std::f32::consts::PI * 100.0 / 48000.0).sin();
+            let mut frame = [s, 0.0];
+            master.process(&mut frame);
+            right = right.max(frame[1].abs());
+        }
+        assert!(right > 0.3);
+        assert_eq!(master.hot_frames, 0);
+
+        master.process(&mut [0.0, 2.0]);
+        assert_eq!(master.hot_frames, 1);
+    }
+}
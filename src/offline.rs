@@ -1,17 +1,30 @@
 //! Shared native offline engine runner for rendering and benchmarking.
 
-use crate::sampling::{decode_sample_file, scan_samples_dir};
-use crate::Engine;
+use crate::dsp::ResampleQuality;
+use crate::recorder::write_wav_f32;
+use crate::sampling::{decode_sample_file_with, scan_samples_dir, DecodeOptions};
+use crate::session::SessionEntry;
+use crate::types::{OutMode, DEFAULT_ORBITS};
+use crate::{AudioCmd, Engine};
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Output level below which trailing tail frames are trimmed (~-120 dBFS).
+const TAIL_SILENCE_THRESHOLD: f32 = 1e-6;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct OfflineEngineConfig {
     pub sample_rate: f32,
     pub channels: usize,
     pub max_voices: usize,
     pub block_size: usize,
+    pub orbits: usize,
+    pub out_mode: OutMode,
+    /// Estimate each sample's pitch so `freq`/`note` play it in tune.
+    pub detect_pitch: bool,
+    /// Interpolation for sample files at another rate than `sample_rate`.
+    pub resample: ResampleQuality,
 }
 
 impl Default for OfflineEngineConfig {
@@ -21,6 +34,10 @@ impl Default for OfflineEngineConfig {
             channels: 2,
             max_voices: 64,
             block_size: 512,
+            orbits: DEFAULT_ORBITS,
+            out_mode: OutMode::Pairs,
+            detect_pitch: false,
+            resample: ResampleQuality::default(),
         }
     }
 }
@@ -43,11 +60,17 @@ pub fn create_engine(
         config.max_voices,
         config.block_size,
     );
+    engine.set_orbit_count(config.orbits);
+    engine.out_mode = config.out_mode;
 
     if let Some(dir) = samples_dir {
         let index = scan_samples_dir(dir);
         for entry in &index {
-            let data = decode_sample_file(&entry.path, config.sample_rate)
+            let options = DecodeOptions {
+                detect_pitch: config.detect_pitch,
+                resample: config.resample,
+            };
+            let data = decode_sample_file_with(&entry.path, config.sample_rate, options)
                 .map_err(|err| format!("failed to load {}: {err}", entry.name))?;
             engine
                 .sample_registry
@@ -68,19 +91,154 @@ where
     S: AsRef<str>,
 {
     for command in commands {
-        engine.evaluate(command.as_ref());
+        AudioCmd::route(command.as_ref().into(), None, |cmd| engine.apply(cmd));
+    }
+}
+
+/// Reads a render script: one command per line, blank lines and `//`
+/// comments ignored.
+pub fn read_script(path: &Path) -> Result<Vec<String>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    Ok(text
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// One event of a script run with the REPL's `.run` or `--exec`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptLine {
+    /// Seconds after the script starts, from an `@time` prefix. `None`
+    /// plays the event as soon as it is read.
+    pub at: Option<f64>,
+    pub event: String,
+}
+
+/// Parses a script: one event per line, `//` comments, a trailing `\`
+/// continuing the event on the next line, and an optional `@time` prefix
+/// (`@1.5 /sound/kick`) scheduling it that many seconds after the start.
+pub fn parse_script(text: &str) -> Vec<ScriptLine> {
+    let mut lines = Vec::new();
+    let mut pending = String::new();
+    for line in text.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if let Some(head) = line.strip_suffix('\\') {
+            pending.push_str(head.trim_end());
+            continue;
+        }
+        pending.push_str(line);
+        lines.extend(script_line(std::mem::take(&mut pending)));
+    }
+    lines.extend(script_line(pending));
+    lines
+}
+
+fn script_line(event: String) -> Option<ScriptLine> {
+    let (at, event) = match event.strip_prefix('@') {
+        Some(rest) => {
+            let (time, event) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            (time.parse().ok(), event.trim().to_string())
+        }
+        None => (None, event),
+    };
+    (!event.is_empty()).then_some(ScriptLine { at, event })
+}
+
+impl Engine {
+    /// Renders `duration_secs` of everything evaluated so far, faster than
+    /// realtime, and writes it to `path` as a 32-bit float WAV.
+    ///
+    /// Schedule events with `time`/`tick` before calling this to bounce a
+    /// pattern.
+    pub fn render_offline(
+        &mut self,
+        duration_secs: f32,
+        path: impl AsRef<Path>,
+    ) -> std::io::Result<()> {
+        let pass = render_to_buffer(self, duration_secs);
+        let output = pass.output.unwrap_or_default();
+        write_wav_f32(path.as_ref(), &output, self.output_channels, self.sr)
     }
 }
 
 pub fn render_to_buffer(engine: &mut Engine, duration_seconds: f32) -> OfflinePass {
-    run_engine(engine, duration_seconds, true)
+    run_engine(engine, duration_seconds, true, &[])
+}
+
+/// Renders a session log (see [`crate::session`]): each entry is evaluated
+/// once the engine reaches its tick, as the audio callback did live. Blocks
+/// are cut at the entry ticks so none lands late. With `max_tail_seconds`
+/// the tail is rendered as in [`render_with_tail`].
+pub fn render_session(
+    engine: &mut Engine,
+    session: &[SessionEntry],
+    duration_seconds: f32,
+    max_tail_seconds: Option<f32>,
+) -> OfflinePass {
+    let pass = run_engine(engine, duration_seconds, true, session);
+    match max_tail_seconds {
+        Some(max_tail) => render_tail(engine, pass, max_tail),
+        None => pass,
+    }
+}
+
+/// Renders `duration_seconds`, then freewheels until the engine is idle
+/// (voices done, schedule empty, orbit tails decayed) or `max_tail_seconds`
+/// more have elapsed. Trailing silence after the duration is trimmed.
+pub fn render_with_tail(
+    engine: &mut Engine,
+    duration_seconds: f32,
+    max_tail_seconds: f32,
+) -> OfflinePass {
+    let pass = run_engine(engine, duration_seconds, true, &[]);
+    render_tail(engine, pass, max_tail_seconds)
+}
+
+fn render_tail(engine: &mut Engine, mut pass: OfflinePass, max_tail_seconds: f32) -> OfflinePass {
+    let channels = engine.output_channels;
+    let block_samples = engine.block_size.max(1);
+    let max_tail = seconds_to_samples(engine.sr, max_tail_seconds);
+    let Some(output) = pass.output.as_mut() else {
+        return pass;
+    };
+
+    let start = Instant::now();
+    let mut tail = 0usize;
+    while tail < max_tail && !engine.is_idle() {
+        let chunk_samples = (max_tail - tail).min(block_samples);
+        let offset = output.len();
+        output.resize(offset + chunk_samples * channels, 0.0);
+        engine.process_block(&mut output[offset..], &[], &[]);
+        tail += chunk_samples;
+        pass.blocks += 1;
+    }
+
+    let body = pass.samples * channels;
+    let trailing = output[body..]
+        .iter()
+        .rev()
+        .take_while(|s| s.abs() < TAIL_SILENCE_THRESHOLD)
+        .count()
+        / channels;
+    output.truncate(output.len() - trailing * channels);
+    pass.samples = output.len() / channels;
+    pass.elapsed_ns += start.elapsed().as_nanos() as u64;
+    pass
 }
 
 pub fn run_without_capture(engine: &mut Engine, duration_seconds: f32) -> OfflinePass {
-    run_engine(engine, duration_seconds, false)
+    run_engine(engine, duration_seconds, false, &[])
 }
 
-fn run_engine(engine: &mut Engine, duration_seconds: f32, capture_output: bool) -> OfflinePass {
+fn run_engine(
+    engine: &mut Engine,
+    duration_seconds: f32,
+    capture_output: bool,
+    session: &[SessionEntry],
+) -> OfflinePass {
     let total_samples = seconds_to_samples(engine.sr, duration_seconds);
     let channels = engine.output_channels;
     let block_samples = engine.block_size.max(1);
@@ -89,9 +247,19 @@ fn run_engine(engine: &mut Engine, duration_seconds: f32, capture_output: bool)
     let mut scratch = vec![0.0f32; block_samples * channels];
     let mut output = capture_output.then(|| vec![0.0f32; total_samples * channels]);
 
+    let mut pending = session.iter().peekable();
+
     let start = Instant::now();
     while rendered_samples < total_samples {
-        let chunk_samples = (total_samples - rendered_samples).min(block_samples);
+        while let Some(entry) = pending.next_if(|entry| entry.tick <= engine.tick) {
+            AudioCmd::route(entry.event.clone(), entry.at, |cmd| engine.apply(cmd));
+        }
+        let until_next = pending
+            .peek()
+            .map_or(usize::MAX, |entry| (entry.tick - engine.tick) as usize);
+        let chunk_samples = (total_samples - rendered_samples)
+            .min(block_samples)
+            .min(until_next);
         let chunk_len = chunk_samples * channels;
         let chunk = if let Some(buffer) = output.as_mut() {
             &mut buffer[rendered_samples * channels..rendered_samples * channels + chunk_len]
@@ -119,3 +287,58 @@ pub fn seconds_to_samples(sample_rate: f32, duration_seconds: f32) -> usize {
         (sample_rate * duration_seconds) as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    #[test]
+    fn render_offline_writes_scripted_events_to_wav() {
+        let dir = std::env::temp_dir().join(format!("doux-render-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("pattern.txt");
+        std::fs::write(
+            &script,
+            "// two hits\n/sound/sine/time/0/gate/0.01\n\n/sound/sine/time/0.05 // late\n",
+        )
+        .unwrap();
+        let commands = read_script(&script).unwrap();
+        assert_eq!(
+            commands,
+            ["/sound/sine/time/0/gate/0.01", "/sound/sine/time/0.05"]
+        );
+
+        let mut engine = Engine::new_with_channels(1000.0, 2, 8, 64);
+        apply_setup_commands(&mut engine, &commands);
+        let wav = dir.join("out.wav");
+        engine.render_offline(0.1, &wav).unwrap();
+        let bytes = std::fs::read(&wav).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // 44-byte header + 100 stereo f32 frames
+        assert_eq!(bytes.len(), 44 + 100 * 2 * 4);
+        let data: Vec<f32> = bytes[44..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert!(data[..20].iter().any(|&s| s != 0.0));
+        assert!(data[100..].iter().any(|&s| s != 0.0));
+    }
+
+    #[test]
+    fn scripts_schedule_at_prefixed_events_and_join_continued_lines() {
+        let script = "// intro\n/doux/seed/3\n@0.5 /sound/kick // downbeat\n\n@1 /sound/saw \\\n  /lpf/800 \\\n/gate/2\nset root 48\n";
+        let lines = parse_script(script);
+        let got: Vec<_> = lines.iter().map(|l| (l.at, l.event.as_str())).collect();
+        assert_eq!(
+            got,
+            [
+                (None, "/doux/seed/3"),
+                (Some(0.5), "/sound/kick"),
+                (Some(1.0), "/sound/saw/lpf/800/gate/2"),
+                (None, "set root 48"),
+            ]
+        );
+    }
+}
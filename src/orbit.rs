@@ -1,35 +1,285 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dsp::{CorrelationMeter, SvfMode, SvfState};
 use crate::effects::{
-    Comb, CombParams, Compressor, DattorroVerb, Delay, Feedback, ReverbParams, VitalVerb,
+    BeatRepeat, Comb, CombParams, Compressor, DattorroVerb, Delay, Dynamics, DynamicsParams,
+    Feedback, InsertSlots, OrbitEq, ReverbParams, VitalVerb,
 };
-use crate::types::{ReverbType, CHANNELS};
+use crate::types::{ReverbQuality, ReverbType, CHANNELS};
+use crate::voice::modulation::lcg;
+use crate::voice::{ModChain, ParamMod};
 
 const SILENCE_THRESHOLD: f32 = 1e-7;
 const SILENCE_HOLDOFF_SECS: f32 = 1.0;
+/// A quiet stretch only restarts the holdoff once the bus gets this much
+/// louder than the threshold (≈ +6 dB), so tails hovering at it still sleep.
+const SILENCE_HYSTERESIS: f32 = 2.0;
+/// Crossfade between reverb engines when `verbtype` changes mid-tail.
+const VERB_XFADE_SECS: f32 = 0.05;
+/// Time `verbfreeze` / `delayfreeze` take to close the input and open the
+/// feedback, so neither steps audibly.
+const FREEZE_RAMP_SECS: f32 = 0.05;
+/// Release of the output envelope follower (time to fall by 1/e).
+const ENV_RELEASE_SECS: f32 = 0.1;
+/// Most automation lanes running at once on one orbit.
+pub const MAX_ORBIT_LANES: usize = 8;
+/// Effect returns, in chain order: comb, feedback, delay, reverb.
+pub const ORBIT_RETURNS: usize = 4;
+
+/// Numeric orbit parameter an automation lane can drive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrbitParam {
+    Delay,
+    DelayTime,
+    DelayFeedback,
+    Verb,
+    VerbDecay,
+    VerbDamp,
+    VerbPredelay,
+    VerbDiff,
+    VerbSize,
+    VerbPrelow,
+    VerbPrehigh,
+    VerbLowcut,
+    VerbHighcut,
+    VerbLowgain,
+    VerbChorus,
+    VerbChorusFreq,
+    Comb,
+    CombFreq,
+    CombFeedback,
+    CombDamp,
+    Feedback,
+    FbTime,
+    FbDamp,
+    FbCross,
+    FbLfo,
+    FbLfoDepth,
+    Comp,
+    CompAttack,
+    CompRelease,
+    CombReturn,
+    FbReturn,
+    DelayReturn,
+    VerbReturn,
+    DelaySendLpf,
+    DelaySendHpf,
+    VerbSendLpf,
+    VerbSendHpf,
+    Hpf,
+    Lpf,
+    EqLo,
+    EqMid,
+    EqHi,
+    VerbFreeze,
+    DelayFreeze,
+}
+
+impl OrbitParam {
+    /// Every parameter, in declaration order.
+    pub const ALL: [Self; 44] = [
+        Self::Delay,
+        Self::DelayTime,
+        Self::DelayFeedback,
+        Self::Verb,
+        Self::VerbDecay,
+        Self::VerbDamp,
+        Self::VerbPredelay,
+        Self::VerbDiff,
+        Self::VerbSize,
+        Self::VerbPrelow,
+        Self::VerbPrehigh,
+        Self::VerbLowcut,
+        Self::VerbHighcut,
+        Self::VerbLowgain,
+        Self::VerbChorus,
+        Self::VerbChorusFreq,
+        Self::Comb,
+        Self::CombFreq,
+        Self::CombFeedback,
+        Self::CombDamp,
+        Self::Feedback,
+        Self::FbTime,
+        Self::FbDamp,
+        Self::FbCross,
+        Self::FbLfo,
+        Self::FbLfoDepth,
+        Self::Comp,
+        Self::CompAttack,
+        Self::CompRelease,
+        Self::CombReturn,
+        Self::FbReturn,
+        Self::DelayReturn,
+        Self::VerbReturn,
+        Self::DelaySendLpf,
+        Self::DelaySendHpf,
+        Self::VerbSendLpf,
+        Self::VerbSendHpf,
+        Self::Hpf,
+        Self::Lpf,
+        Self::EqLo,
+        Self::EqMid,
+        Self::EqHi,
+        Self::VerbFreeze,
+        Self::DelayFreeze,
+    ];
+}
+
+impl FromStr for OrbitParam {
+    type Err = ();
+
+    /// Accepts the event key (or alias) that sets the parameter.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "delay" => Self::Delay,
+            "delaytime" => Self::DelayTime,
+            "delayfeedback" => Self::DelayFeedback,
+            "verb" | "reverb" => Self::Verb,
+            "verbdecay" => Self::VerbDecay,
+            "verbdamp" => Self::VerbDamp,
+            "verbpredelay" => Self::VerbPredelay,
+            "verbdiff" => Self::VerbDiff,
+            "verbsize" | "vsize" => Self::VerbSize,
+            "verbprelow" => Self::VerbPrelow,
+            "verbprehigh" => Self::VerbPrehigh,
+            "verblowcut" => Self::VerbLowcut,
+            "verbhighcut" => Self::VerbHighcut,
+            "verblowgain" => Self::VerbLowgain,
+            "verbchorus" | "vchorus" => Self::VerbChorus,
+            "verbchorusfreq" | "vchorusfreq" => Self::VerbChorusFreq,
+            "comb" => Self::Comb,
+            "combfreq" => Self::CombFreq,
+            "combfeedback" => Self::CombFeedback,
+            "combdamp" => Self::CombDamp,
+            "feedback" | "fb" => Self::Feedback,
+            "fbtime" | "fbt" => Self::FbTime,
+            "fbdamp" | "fbd" => Self::FbDamp,
+            "fbcross" | "fbc" => Self::FbCross,
+            "fblfo" => Self::FbLfo,
+            "fblfodepth" => Self::FbLfoDepth,
+            "comp" | "duckamount" => Self::Comp,
+            "compattack" | "cattack" | "duckattack" => Self::CompAttack,
+            "comprelease" | "crelease" | "duckrelease" => Self::CompRelease,
+            "combreturn" => Self::CombReturn,
+            "fbreturn" => Self::FbReturn,
+            "delayreturn" => Self::DelayReturn,
+            "verbreturn" => Self::VerbReturn,
+            "delaysendlpf" => Self::DelaySendLpf,
+            "delaysendhpf" => Self::DelaySendHpf,
+            "verbsendlpf" => Self::VerbSendLpf,
+            "verbsendhpf" => Self::VerbSendHpf,
+            "orbithpf" => Self::Hpf,
+            "orbitlpf" => Self::Lpf,
+            "orbiteqlo" => Self::EqLo,
+            "orbiteqmid" => Self::EqMid,
+            "orbiteqhi" => Self::EqHi,
+            "verbfreeze" => Self::VerbFreeze,
+            "delayfreeze" => Self::DelayFreeze,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Low- and high-cut on an effect's send input (`verbsendlpf`, ...).
+/// A cutoff of 0 leaves that side open.
+#[derive(Clone, Copy, Default)]
+pub struct SendFilter {
+    pub lpf: f32,
+    pub hpf: f32,
+    lp: [SvfState; CHANNELS],
+    hp: [SvfState; CHANNELS],
+}
+
+impl SendFilter {
+    #[inline]
+    fn process(&mut self, mut input: [f32; CHANNELS], sr: f32) -> [f32; CHANNELS] {
+        for (c, x) in input.iter_mut().enumerate() {
+            if self.hpf > 0.0 {
+                self.hp[c].cutoff = self.hpf;
+                *x = self.hp[c].process(*x, SvfMode::Hp, 0.0, sr);
+            }
+            if self.lpf > 0.0 {
+                self.lp[c].cutoff = self.lpf;
+                *x = self.lp[c].process(*x, SvfMode::Lp, 0.0, sr);
+            }
+        }
+        input
+    }
+}
 
 // SuperDirt-style chain: voices accumulate into `bus`; each FX reads
 // `bus * send_level`, adds its wet back into `bus`, in order. Order matters —
 // later FX see the running signal including previous FX wet.
 //
-// Chain order: comb → fb → delay → verb. Tonal/short → spatial/long.
+// Chain order: repeat → comb → fb → delay → verb → eq → compressor → inserts. Tonal/short → spatial/long.
+// Each wet is scaled by its return gain (`verbreturn`, ...) before it joins the bus.
 // Reverb last so it captures delay echoes (the load-bearing reason for chaining).
+// Beat repeat replaces the dry bus before any FX, so echoes and tails run on.
 pub struct Orbit {
     pub bus: [f32; CHANNELS],
+    /// Signal sent from voices of other orbits: feeds the effects, but is
+    /// not part of the dry output.
+    pub send: [f32; CHANNELS],
     pub delay: Delay,
     pub delay_level: f32,
+    pub delay_send_filter: SendFilter,
     pub dattorro: [DattorroVerb; CHANNELS],
     pub vital: VitalVerb,
     pub reverb_params: ReverbParams,
     pub verb_level: f32,
+    pub verb_send_filter: SendFilter,
+    /// How frozen the delay and reverb should be (0..1). The effects reach
+    /// it over [`FREEZE_RAMP_SECS`].
+    pub delay_freeze: f32,
+    pub verb_freeze: f32,
+    freeze_step: f32,
+    /// Filters and EQ on the bus after the effect returns.
+    pub tone: OrbitEq,
+    /// Engine currently heard; lags `reverb_params.verb_type` until a switch.
+    verb_active: ReverbType,
+    /// Engine being faded out after a switch.
+    verb_prev: ReverbType,
+    verb_fade: u32,
+    verb_fade_len: u32,
     pub comb: [Comb; CHANNELS],
     pub comb_params: CombParams,
     pub comb_level: f32,
     pub fb: Feedback,
     pub fb_level: f32,
+    /// Gain on each effect's wet signal before it joins the bus.
+    pub comb_return: f32,
+    pub fb_return: f32,
+    pub delay_return: f32,
+    pub verb_return: f32,
+    /// Peak envelope of each return after its gain, in [`ORBIT_RETURNS`] order.
+    pub return_env: [f32; ORBIT_RETURNS],
     pub comp: Compressor,
     pub comp_orbit: usize,
+    /// Threshold compressor on the bus, set by orbit commands only.
+    pub dynamics: Dynamics,
+    pub dynamics_params: DynamicsParams,
+    pub repeat: BeatRepeat,
+    /// User insert slots, run on the bus after the reverb.
+    pub inserts: InsertSlots,
     pub sr: f32,
+    /// Bus level (|L| + |R|) below which the orbit counts as silent.
+    pub silence_threshold: f32,
     silent_samples: u32,
     silence_holdoff: u32,
+    /// Times the orbit went to sleep / woke up, for telemetry.
+    pub sleeps: u64,
+    pub wakes: u64,
+    /// Peak envelope of what the orbit sends to its output (instant attack).
+    pub env: f32,
+    env_release: f32,
+    /// Stereo correlation of the orbit's output.
+    pub correlation: CorrelationMeter,
+    /// Automation lanes, ticked every sample even while the orbit sleeps.
+    lanes: [(OrbitParam, ParamMod); MAX_ORBIT_LANES],
+    lane_count: u8,
+    lane_seed: u32,
 }
 
 impl Orbit {
@@ -37,27 +287,268 @@ impl Orbit {
         let silence_holdoff = (sr * SILENCE_HOLDOFF_SECS) as u32;
         Self {
             bus: [0.0; CHANNELS],
+            send: [0.0; CHANNELS],
             delay: Delay::new(sr),
             delay_level: 0.0,
+            delay_send_filter: SendFilter::default(),
             dattorro: std::array::from_fn(|_| DattorroVerb::new(sr)),
             vital: VitalVerb::new(sr),
             reverb_params: ReverbParams::default(),
             verb_level: 0.0,
+            verb_send_filter: SendFilter::default(),
+            delay_freeze: 0.0,
+            verb_freeze: 0.0,
+            freeze_step: 1.0 / (sr * FREEZE_RAMP_SECS).max(1.0),
+            tone: OrbitEq::default(),
+            verb_active: ReverbType::default(),
+            verb_prev: ReverbType::default(),
+            verb_fade: 0,
+            verb_fade_len: ((sr * VERB_XFADE_SECS) as u32).max(1),
             comb: [Comb::default(); CHANNELS],
             comb_params: CombParams::default(),
             comb_level: 0.0,
             fb: Feedback::default(),
             fb_level: 0.0,
+            comb_return: 1.0,
+            fb_return: 1.0,
+            delay_return: 1.0,
+            verb_return: 1.0,
+            return_env: [0.0; ORBIT_RETURNS],
             comp: Compressor::default(),
             comp_orbit: 0,
+            dynamics: Dynamics::default(),
+            dynamics_params: DynamicsParams::default(),
+            repeat: BeatRepeat::default(),
+            inserts: InsertSlots::default(),
             sr,
+            silence_threshold: SILENCE_THRESHOLD,
             silent_samples: silence_holdoff + 1,
             silence_holdoff,
+            sleeps: 0,
+            wakes: 0,
+            env: 0.0,
+            env_release: (-1.0 / (sr * ENV_RELEASE_SECS)).exp(),
+            correlation: CorrelationMeter::new(sr),
+            lanes: std::array::from_fn(|_| (OrbitParam::Delay, ParamMod::default())),
+            lane_count: 0,
+            lane_seed: 987654321,
+        }
+    }
+
+    /// Starts an automation lane on `param`, replacing any lane already on it.
+    /// Slews start from the current value; `follow` chains need a voice and
+    /// are ignored. Past [`MAX_ORBIT_LANES`] new lanes are dropped.
+    pub fn automate(&mut self, param: OrbitParam, chain: ModChain) {
+        let chain = match chain {
+            ModChain::Follow { .. } => return,
+            ModChain::Slew {
+                target,
+                freq,
+                curve,
+            } => ModChain::Transition {
+                start: self.read_param(param),
+                target,
+                freq,
+                curve,
+                looping: false,
+            },
+            chain => chain,
+        };
+        let mut lane = ParamMod::new(chain, self.lane_seed);
+        lane.trigger(0.0);
+        self.lane_seed = lcg(self.lane_seed);
+        let count = self.lane_count as usize;
+        if let Some(i) = self.lanes[..count].iter().position(|(p, _)| *p == param) {
+            self.lanes[i].1 = lane;
+        } else if count < MAX_ORBIT_LANES {
+            self.lanes[count] = (param, lane);
+            self.lane_count += 1;
+        }
+    }
+
+    /// Stops the lane on `param`, leaving the parameter at its last value.
+    pub fn stop_lane(&mut self, param: OrbitParam) {
+        let count = self.lane_count as usize;
+        if let Some(i) = self.lanes[..count].iter().position(|(p, _)| *p == param) {
+            self.lanes.swap(i, count - 1);
+            self.lane_count -= 1;
+        }
+    }
+
+    pub fn clear_lanes(&mut self) {
+        self.lane_count = 0;
+    }
+
+    /// Empties the effect lines and stops the lanes of an orbit that is
+    /// switched off, so it comes back silent. Settings are kept.
+    pub fn park(&mut self) {
+        self.clear_lanes();
+        self.clear_bus();
+        self.delay.clear();
+        for verb in &mut self.dattorro {
+            verb.clear();
+        }
+        self.vital.clear();
+        self.verb_fade = 0;
+        self.return_env = [0.0; ORBIT_RETURNS];
+        self.env = 0.0;
+        self.silent_samples = self.silence_holdoff + 1;
+    }
+
+    /// Restarts the seeds random lanes started from now on draw from.
+    pub fn set_lane_seed(&mut self, seed: u32) {
+        self.lane_seed = seed;
+    }
+
+    /// Running automation lanes and their chains.
+    pub fn lanes(&self) -> impl Iterator<Item = (OrbitParam, &ModChain)> {
+        self.lanes[..self.lane_count as usize]
+            .iter()
+            .map(|(param, lane)| (*param, &lane.chain))
+    }
+
+    /// Advances every automation lane by one sample.
+    #[inline]
+    pub fn tick_lanes(&mut self, isr: f32) {
+        for i in 0..self.lane_count as usize {
+            let (param, ref mut lane) = self.lanes[i];
+            let val = lane.tick(isr);
+            self.write_param(param, val);
+        }
+    }
+
+    pub(crate) fn read_param(&self, param: OrbitParam) -> f32 {
+        let rp = &self.reverb_params;
+        match param {
+            OrbitParam::Delay => self.delay_level,
+            OrbitParam::DelayTime => self.delay.params.time,
+            OrbitParam::DelayFeedback => self.delay.params.feedback,
+            OrbitParam::Verb => self.verb_level,
+            OrbitParam::VerbDecay => rp.decay,
+            OrbitParam::VerbDamp => rp.damp,
+            OrbitParam::VerbPredelay => rp.predelay,
+            OrbitParam::VerbDiff => rp.diff,
+            OrbitParam::VerbSize => rp.size,
+            OrbitParam::VerbPrelow => rp.prelow,
+            OrbitParam::VerbPrehigh => rp.prehigh,
+            OrbitParam::VerbLowcut => rp.lowcut,
+            OrbitParam::VerbHighcut => rp.highcut,
+            OrbitParam::VerbLowgain => rp.lowgain,
+            OrbitParam::VerbChorus => rp.chorus,
+            OrbitParam::VerbChorusFreq => rp.chorus_freq,
+            OrbitParam::Comb => self.comb_level,
+            OrbitParam::CombFreq => self.comb_params.freq,
+            OrbitParam::CombFeedback => self.comb_params.feedback,
+            OrbitParam::CombDamp => self.comb_params.damp,
+            OrbitParam::Feedback => self.fb_level,
+            OrbitParam::FbTime => self.fb.params.time_ms,
+            OrbitParam::FbDamp => self.fb.params.damp,
+            OrbitParam::FbCross => self.fb.params.cross,
+            OrbitParam::FbLfo => self.fb.params.lfo,
+            OrbitParam::FbLfoDepth => self.fb.params.lfo_depth,
+            OrbitParam::Comp => self.comp.params.amount,
+            OrbitParam::CompAttack => self.comp.params.attack,
+            OrbitParam::CompRelease => self.comp.params.release,
+            OrbitParam::CombReturn => self.comb_return,
+            OrbitParam::FbReturn => self.fb_return,
+            OrbitParam::DelayReturn => self.delay_return,
+            OrbitParam::VerbReturn => self.verb_return,
+            OrbitParam::DelaySendLpf => self.delay_send_filter.lpf,
+            OrbitParam::DelaySendHpf => self.delay_send_filter.hpf,
+            OrbitParam::VerbSendLpf => self.verb_send_filter.lpf,
+            OrbitParam::VerbSendHpf => self.verb_send_filter.hpf,
+            OrbitParam::Hpf => self.tone.hpf,
+            OrbitParam::Lpf => self.tone.lpf,
+            OrbitParam::EqLo => self.tone.lo,
+            OrbitParam::EqMid => self.tone.mid,
+            OrbitParam::EqHi => self.tone.hi,
+            OrbitParam::VerbFreeze => self.verb_freeze,
+            OrbitParam::DelayFreeze => self.delay_freeze,
+        }
+    }
+
+    /// Writes `val` like the matching event key would (send levels and
+    /// cutoffs stay >= 0).
+    pub(crate) fn write_param(&mut self, param: OrbitParam, val: f32) {
+        dsp_assert!(val.is_finite(), "{param:?} written with {val}");
+        let rp = &mut self.reverb_params;
+        match param {
+            OrbitParam::Delay => self.delay_level = val.max(0.0),
+            OrbitParam::DelayTime => self.delay.params.time = val,
+            OrbitParam::DelayFeedback => self.delay.params.feedback = val,
+            OrbitParam::Verb => self.verb_level = val.max(0.0),
+            OrbitParam::VerbDecay => rp.decay = val,
+            OrbitParam::VerbDamp => rp.damp = val,
+            OrbitParam::VerbPredelay => rp.predelay = val,
+            OrbitParam::VerbDiff => rp.diff = val,
+            OrbitParam::VerbSize => rp.size = val,
+            OrbitParam::VerbPrelow => rp.prelow = val,
+            OrbitParam::VerbPrehigh => rp.prehigh = val,
+            OrbitParam::VerbLowcut => rp.lowcut = val,
+            OrbitParam::VerbHighcut => rp.highcut = val,
+            OrbitParam::VerbLowgain => rp.lowgain = val,
+            OrbitParam::VerbChorus => rp.chorus = val,
+            OrbitParam::VerbChorusFreq => rp.chorus_freq = val,
+            OrbitParam::Comb => self.comb_level = val.max(0.0),
+            OrbitParam::CombFreq => self.comb_params.freq = val,
+            OrbitParam::CombFeedback => self.comb_params.feedback = val,
+            OrbitParam::CombDamp => self.comb_params.damp = val,
+            OrbitParam::Feedback => self.fb_level = val.max(0.0),
+            OrbitParam::FbTime => self.fb.params.time_ms = val,
+            OrbitParam::FbDamp => self.fb.params.damp = val,
+            OrbitParam::FbCross => self.fb.params.cross = val,
+            OrbitParam::FbLfo => self.fb.params.lfo = val,
+            OrbitParam::FbLfoDepth => self.fb.params.lfo_depth = val,
+            OrbitParam::Comp => self.comp.params.amount = val.max(0.0),
+            OrbitParam::CompAttack => self.comp.params.attack = val,
+            OrbitParam::CompRelease => self.comp.params.release = val,
+            OrbitParam::CombReturn => self.comb_return = val.max(0.0),
+            OrbitParam::FbReturn => self.fb_return = val.max(0.0),
+            OrbitParam::DelayReturn => self.delay_return = val.max(0.0),
+            OrbitParam::VerbReturn => self.verb_return = val.max(0.0),
+            OrbitParam::DelaySendLpf => self.delay_send_filter.lpf = val.max(0.0),
+            OrbitParam::DelaySendHpf => self.delay_send_filter.hpf = val.max(0.0),
+            OrbitParam::VerbSendLpf => self.verb_send_filter.lpf = val.max(0.0),
+            OrbitParam::VerbSendHpf => self.verb_send_filter.hpf = val.max(0.0),
+            OrbitParam::Hpf => self.tone.hpf = val.max(0.0),
+            OrbitParam::Lpf => self.tone.lpf = val.max(0.0),
+            OrbitParam::EqLo => self.tone.lo = val,
+            OrbitParam::EqMid => self.tone.mid = val,
+            OrbitParam::EqHi => self.tone.hi = val,
+            OrbitParam::VerbFreeze => self.verb_freeze = val,
+            OrbitParam::DelayFreeze => self.delay_freeze = val,
+        }
+    }
+
+    /// True once the orbit has been silent for the holdoff period, i.e. all
+    /// FX tails have decayed and processing is skipped.
+    pub fn is_silent(&self) -> bool {
+        self.silent_samples > self.silence_holdoff
+    }
+
+    /// Rebuilds the plate reverb for `quality` (see [`ReverbQuality`]).
+    /// Tanks whose rate doesn't change keep their lines and their tail.
+    pub fn set_verb_quality(&mut self, quality: ReverbQuality) {
+        for verb in &mut self.dattorro {
+            verb.reconfigure(self.sr, quality);
+        }
+    }
+
+    /// How long the bus must stay below the threshold before FX stop running.
+    pub fn set_silence_hold(&mut self, seconds: f32) {
+        let asleep = self.is_silent();
+        self.silence_holdoff = (self.sr * seconds.max(0.0)) as u32;
+        // Keep the current state; only the next countdown uses the new hold
+        if asleep {
+            self.silent_samples = self.silence_holdoff + 1;
+        } else {
+            self.silent_samples = self.silent_samples.min(self.silence_holdoff);
         }
     }
 
     pub fn clear_bus(&mut self) {
         self.bus = [0.0; CHANNELS];
+        self.send = [0.0; CHANNELS];
     }
 
     pub fn add_dry(&mut self, ch: usize, value: f32) {
@@ -65,72 +556,597 @@ impl Orbit {
     }
 
     pub fn process(&mut self) {
-        let has_input = self.bus[0] != 0.0 || self.bus[1] != 0.0;
+        self.repeat.process(&mut self.bus);
+        let has_input =
+            self.bus[0] != 0.0 || self.bus[1] != 0.0 || self.send[0] != 0.0 || self.send[1] != 0.0;
+        let asleep = self.is_silent();
 
         if has_input {
+            if asleep {
+                self.wakes += 1;
+            }
             self.silent_samples = 0;
-        } else if self.silent_samples > self.silence_holdoff {
+        } else if asleep {
+            // Tails have died out; nothing left to crossfade
+            if self.verb_fade > 0 {
+                self.end_verb_fade();
+            }
             return;
         }
 
+        let step = self.freeze_step;
+        ramp(&mut self.delay.params.freeze, self.delay_freeze, step);
+        ramp(&mut self.reverb_params.freeze, self.verb_freeze, step);
+
+        // Wet signal of each return after its gain, for the meters
+        let mut returns = [[0.0_f32; CHANNELS]; ORBIT_RETURNS];
+
         // Comb (per-channel mono resonator, shared params)
         if self.comb_level > 0.0 {
             let mut wet = [0.0_f32; CHANNELS];
             for (channel, w) in wet.iter_mut().enumerate() {
                 *w = self.comb[channel].process(
-                    self.bus[channel] * self.comb_level,
+                    (self.bus[channel] + self.send[channel]) * self.comb_level,
                     &self.comb_params,
                     self.sr,
                 );
             }
-            self.bus[0] += wet[0];
-            self.bus[1] += wet[1];
+            returns[0] = self.add_return(wet, self.comb_return);
         }
 
+        dsp_check_bounded!("orbit comb", self.bus);
+
         // Feedback (stereo short delay with cross-channel, LFO + params on FX)
         if self.fb_level > 0.0 {
-            let fb_in = [self.bus[0] * self.fb_level, self.bus[1] * self.fb_level];
+            let fb_in = [
+                (self.bus[0] + self.send[0]) * self.fb_level,
+                (self.bus[1] + self.send[1]) * self.fb_level,
+            ];
             let wet = self.fb.process(fb_in, self.fb_level, self.sr);
-            self.bus[0] += wet[0];
-            self.bus[1] += wet[1];
+            returns[1] = self.add_return(wet, self.fb_return);
         }
 
+        dsp_check_bounded!("orbit feedback", self.bus);
+
         // Delay (stereo)
         if self.delay_level > 0.0 {
             let delay_in = [
-                self.bus[0] * self.delay_level,
-                self.bus[1] * self.delay_level,
+                (self.bus[0] + self.send[0]) * self.delay_level,
+                (self.bus[1] + self.send[1]) * self.delay_level,
             ];
+            let delay_in = self.delay_send_filter.process(delay_in, self.sr);
             let wet = self.delay.process(delay_in);
-            self.bus[0] += wet[0];
-            self.bus[1] += wet[1];
+            returns[2] = self.add_return(wet, self.delay_return);
         }
 
-        // Reverb — last in chain so it captures delay echoes
-        if self.verb_level > 0.0 {
-            let verb_in = [self.bus[0] * self.verb_level, self.bus[1] * self.verb_level];
+        dsp_check_bounded!("orbit delay", self.bus);
+
+        // Reverb — last in chain so it captures delay echoes. A type switch
+        // runs its crossfade to the end even if the level drops meanwhile.
+        if self.verb_level > 0.0 || self.verb_fade > 0 {
+            let verb_in = [
+                (self.bus[0] + self.send[0]) * self.verb_level,
+                (self.bus[1] + self.send[1]) * self.verb_level,
+            ];
+            let verb_in = self.verb_send_filter.process(verb_in, self.sr);
             let rp = &self.reverb_params;
-            let wet = match rp.verb_type {
-                ReverbType::Plate => {
-                    let mut out = [0.0; CHANNELS];
-                    for (channel, vin) in verb_in.iter().enumerate() {
-                        let w = self.dattorro[channel].process(*vin, rp);
-                        out[0] += w[0];
-                        out[1] += w[1];
-                    }
-                    out
+            if rp.verb_type != self.verb_active {
+                self.verb_prev = self.verb_active;
+                self.verb_active = rp.verb_type;
+                self.verb_fade = self.verb_fade_len;
+            }
+            let mut wet = run_verb(
+                &mut self.dattorro,
+                &mut self.vital,
+                self.verb_active,
+                verb_in,
+                rp,
+            );
+            if self.verb_fade > 0 {
+                // Equal-power: the two tanks are uncorrelated. The old one
+                // only rings out; new input goes to the new type.
+                let old = run_verb(
+                    &mut self.dattorro,
+                    &mut self.vital,
+                    self.verb_prev,
+                    [0.0; CHANNELS],
+                    rp,
+                );
+                self.verb_fade -= 1;
+                let t = 1.0 - self.verb_fade as f32 / self.verb_fade_len as f32;
+                let (g_new, g_old) = (t * std::f32::consts::FRAC_PI_2).sin_cos();
+                wet[0] = wet[0] * g_new + old[0] * g_old;
+                wet[1] = wet[1] * g_new + old[1] * g_old;
+                if self.verb_fade == 0 {
+                    self.end_verb_fade();
                 }
-                ReverbType::Space => self.vital.process(verb_in, rp),
+            }
+            returns[3] = self.add_return(wet, self.verb_return);
+        }
+
+        dsp_check_bounded!("orbit reverb", self.bus);
+
+        for (env, wet) in self.return_env.iter_mut().zip(&returns) {
+            let level = wet[0].abs().max(wet[1].abs());
+            *env = if level > *env {
+                level
+            } else {
+                *env * self.env_release
             };
-            self.bus[0] += wet[0];
-            self.bus[1] += wet[1];
         }
 
-        let energy = self.bus[0].abs() + self.bus[1].abs();
-        if energy < SILENCE_THRESHOLD {
-            self.silent_samples = self.silent_samples.saturating_add(1);
+        if self.tone.is_active() {
+            self.tone.process(&mut self.bus, self.sr);
+            dsp_check_bounded!("orbit eq", self.bus);
+        }
+
+        if self.dynamics_params.threshold < 0.0 {
+            self.dynamics
+                .process(&mut self.bus, &self.dynamics_params, 1.0 / self.sr);
+            dsp_check_bounded!("orbit compressor", self.bus);
+        }
+
+        if !self.inserts.is_empty() {
+            self.inserts.process(&mut self.bus);
+            dsp_check_bounded!("orbit inserts", self.bus);
+        }
+
+        self.update_silence(self.bus[0].abs() + self.bus[1].abs());
+    }
+
+    /// Stops a reverb type crossfade and drops the old tank's tail, so
+    /// switching back starts clean.
+    fn end_verb_fade(&mut self) {
+        self.verb_fade = 0;
+        match self.verb_prev {
+            ReverbType::Plate => self.dattorro.iter_mut().for_each(|d| d.clear()),
+            ReverbType::Space => self.vital.clear(),
+        }
+    }
+
+    /// Adds an effect's wet signal to the bus at its return gain.
+    #[inline]
+    fn add_return(&mut self, wet: [f32; CHANNELS], gain: f32) -> [f32; CHANNELS] {
+        let wet = [wet[0] * gain, wet[1] * gain];
+        self.bus[0] += wet[0];
+        self.bus[1] += wet[1];
+        wet
+    }
+
+    /// Feeds one output frame to the envelope follower and the
+    /// correlation meter.
+    #[inline]
+    pub fn follow(&mut self, out: [f32; CHANNELS]) {
+        self.correlation.process(out[0], out[1]);
+        let level = out[0].abs().max(out[1].abs());
+        self.env = if level > self.env {
+            level
         } else {
+            self.env * self.env_release
+        };
+    }
+
+    fn update_silence(&mut self, energy: f32) {
+        if energy < self.silence_threshold {
+            self.silent_samples = self.silent_samples.saturating_add(1);
+            if self.is_silent() {
+                self.sleeps += 1;
+            }
+        } else if energy >= self.silence_threshold * SILENCE_HYSTERESIS {
             self.silent_samples = 0;
         }
     }
 }
+
+/// Moves `value` toward `target` (clamped to 0..1) by at most `step`.
+#[inline]
+fn ramp(value: &mut f32, target: f32, step: f32) {
+    let target = target.clamp(0.0, 1.0);
+    *value += (target - *value).clamp(-step, step);
+}
+
+fn run_verb(
+    dattorro: &mut [DattorroVerb; CHANNELS],
+    vital: &mut VitalVerb,
+    verb_type: ReverbType,
+    verb_in: [f32; CHANNELS],
+    rp: &ReverbParams,
+) -> [f32; CHANNELS] {
+    match verb_type {
+        ReverbType::Plate => {
+            let mut out = [0.0; CHANNELS];
+            for (channel, vin) in verb_in.iter().enumerate() {
+                let w = dattorro[channel].process(*vin, rp);
+                out[0] += w[0];
+                out[1] += w[1];
+            }
+            out
+        }
+        ReverbType::Space => vital.process(verb_in, rp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::{Engine, MAX_ORBITS};
+
+    #[test]
+    fn silence_gate_uses_threshold_hold_and_hysteresis() {
+        let mut orbit = Orbit::new(1000.0);
+        orbit.silence_threshold = 0.1;
+        orbit.set_silence_hold(0.01);
+
+        orbit.bus = [0.2, 0.0];
+        orbit.process();
+        assert_eq!(orbit.wakes, 1);
+        assert_eq!(orbit.silent_samples, 0);
+
+        orbit.update_silence(0.05);
+        orbit.update_silence(0.05);
+        assert_eq!(orbit.silent_samples, 2);
+        // Inside the hysteresis band the countdown holds
+        orbit.update_silence(0.15);
+        assert_eq!(orbit.silent_samples, 2);
+        orbit.update_silence(0.25);
+        assert_eq!(orbit.silent_samples, 0);
+
+        for _ in 0..11 {
+            orbit.clear_bus();
+            orbit.process();
+        }
+        assert!(orbit.is_silent());
+        assert_eq!(orbit.sleeps, 1);
+    }
+
+    #[test]
+    fn envelope_follows_peaks_and_releases() {
+        let mut orbit = Orbit::new(1000.0);
+        orbit.follow([0.5, -0.8]);
+        assert_eq!(orbit.env, 0.8);
+        for _ in 0..100 {
+            orbit.follow([0.0; CHANNELS]);
+        }
+        assert!((orbit.env - 0.8 / std::f32::consts::E).abs() < 1e-3);
+    }
+
+    #[test]
+    fn send_filters_shape_only_the_effect_input() {
+        // Peak of the delay return for a tone at `freq`
+        let wet_peak = |freq: f32, lpf: f32, hpf: f32| {
+            let mut orbit = Orbit::new(48000.0);
+            orbit.delay_level = 1.0;
+            orbit.delay.params.time = 0.001;
+            orbit.delay_send_filter.lpf = lpf;
+            orbit.delay_send_filter.hpf = hpf;
+            for i in 0..4800 {
+                let x = (i as f32 * freq * std::f32::consts::TAU / 48000.0).sin();
+                orbit.bus = [x, x];
+                orbit.process();
+            }
+            orbit.return_env[2]
+        };
+        let open = wet_peak(5000.0, 0.0, 0.0);
+        assert!(wet_peak(5000.0, 500.0, 0.0) < open * 0.1);
+        assert!(wet_peak(5000.0, 0.0, 500.0) > open * 0.9);
+        let low = wet_peak(100.0, 0.0, 0.0);
+        assert!(wet_peak(100.0, 0.0, 1000.0) < low * 0.1);
+    }
+
+    #[test]
+    fn bus_eq_filters_effect_tails_with_the_dry_mix() {
+        // Peak of a delay tail at `freq`, after the dry tone stops
+        let tail_peak = |freq: f32, hpf: f32, lo: f32| {
+            let mut orbit = Orbit::new(48000.0);
+            orbit.delay_level = 1.0;
+            orbit.delay.params.time = 0.05;
+            orbit.tone.hpf = hpf;
+            orbit.tone.lo = lo;
+            for i in 0..2400 {
+                let x = (i as f32 * freq * std::f32::consts::TAU / 48000.0).sin();
+                orbit.bus = [x, x];
+                orbit.process();
+            }
+            let mut peak = 0.0_f32;
+            for _ in 0..2400 {
+                orbit.clear_bus();
+                orbit.process();
+                peak = peak.max(orbit.bus[0].abs());
+            }
+            peak
+        };
+        let open = tail_peak(100.0, 0.0, 0.0);
+        assert!(open > 0.1);
+        assert!(tail_peak(100.0, 1000.0, 0.0) < open * 0.1);
+        assert!(tail_peak(100.0, 0.0, -24.0) < open * 0.2);
+        assert!(tail_peak(5000.0, 0.0, -24.0) > tail_peak(5000.0, 0.0, 0.0) * 0.9);
+    }
+
+    #[test]
+    fn freeze_holds_tails_and_shuts_out_new_input() {
+        // Delay and reverb return peaks after two seconds of `input`, fed
+        // once the freeze has ramped in
+        let tails = |verb_type: ReverbType, freeze: f32, input: f32| {
+            let mut orbit = Orbit::new(48000.0);
+            orbit.delay_level = 1.0;
+            orbit.delay.params.time = 0.01;
+            orbit.delay.params.feedback = 0.3;
+            orbit.verb_level = 1.0;
+            orbit.reverb_params.verb_type = verb_type;
+            for i in 0..480 {
+                orbit.bus = [(i as f32 * 0.3).sin(); CHANNELS];
+                orbit.process();
+            }
+            orbit.delay_freeze = freeze;
+            orbit.verb_freeze = freeze;
+            for _ in 0..4800 {
+                orbit.clear_bus();
+                orbit.process();
+            }
+            for i in 0..96000 {
+                orbit.bus = [(i as f32 * 0.1).sin() * input; CHANNELS];
+                orbit.process();
+            }
+            (orbit.return_env[2], orbit.return_env[3])
+        };
+        for verb_type in [ReverbType::Space, ReverbType::Plate] {
+            let (delay, verb) = tails(verb_type, 0.0, 0.0);
+            assert!(delay < 1e-6 && verb < 1e-3, "{verb_type:?}: {delay} {verb}");
+            let (delay, verb) = tails(verb_type, 1.0, 0.0);
+            assert!(delay > 0.05 && verb > 0.01, "{verb_type:?}: {delay} {verb}");
+            assert_eq!(tails(verb_type, 1.0, 1.0), (delay, verb));
+        }
+    }
+
+    #[test]
+    fn verbtype_change_crossfades_then_clears_old_tank() {
+        let mut orbit = Orbit::new(48000.0);
+        orbit.verb_level = 1.0;
+        orbit.reverb_params.verb_type = ReverbType::Space;
+        orbit.bus = [1.0, 1.0];
+        orbit.process();
+        for _ in 0..4800 {
+            orbit.clear_bus();
+            orbit.process();
+        }
+        assert_ne!(orbit.bus, [0.0; CHANNELS]);
+
+        orbit.reverb_params.verb_type = ReverbType::Plate;
+        orbit.clear_bus();
+        orbit.process();
+        assert_eq!(orbit.verb_fade, orbit.verb_fade_len - 1);
+        // The old tail is still heard instead of cutting to the silent plate
+        assert!(orbit.bus[0].abs() + orbit.bus[1].abs() > 0.0);
+
+        for _ in 0..orbit.verb_fade_len {
+            orbit.clear_bus();
+            orbit.process();
+        }
+        assert_eq!(orbit.verb_fade, 0);
+        let rp = orbit.reverb_params;
+        assert_eq!(orbit.vital.process([0.0; CHANNELS], &rp), [0.0; CHANNELS]);
+    }
+
+    #[test]
+    fn verbtype_crossfade_runs_on_time_and_spares_the_old_tank() {
+        let mut orbit = Orbit::new(48000.0);
+        orbit.verb_level = 1.0;
+        orbit.reverb_params.verb_type = ReverbType::Plate;
+        orbit.bus = [1.0, 1.0];
+        orbit.process();
+        assert_eq!(orbit.verb_fade, orbit.verb_fade_len - 1);
+
+        // Dropping the level mid-fade does not stall it
+        orbit.verb_level = 0.0;
+        let half = orbit.verb_fade_len / 2;
+        for _ in 0..half {
+            orbit.clear_bus();
+            orbit.process();
+        }
+        // The impulse went to the plate only; the space tank stayed empty
+        let rp = orbit.reverb_params;
+        let mut old = orbit.vital.clone();
+        let ring: f32 = (0..4800)
+            .map(|_| old.process([0.0; CHANNELS], &rp)[0].abs())
+            .sum();
+        assert_eq!(ring, 0.0);
+
+        for _ in half..orbit.verb_fade_len {
+            orbit.clear_bus();
+            orbit.process();
+        }
+        assert_eq!(orbit.verb_fade, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn orbit_command_sets_fx_without_a_voice() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/orbit/2/verbdecay/0.9/delaytime/0.3/delay/0.5");
+        assert_eq!(engine.active_voices, 0);
+        let orbit = &engine.orbits[2];
+        assert_eq!(orbit.reverb_params.decay, 0.9);
+        assert_eq!(orbit.delay.params.time, 0.3);
+        assert_eq!(orbit.delay_level, 0.5);
+        assert_eq!(engine.orbits[0].delay_level, 0.0);
+
+        // Voices without FX keys leave the bus settings alone
+        engine.evaluate("/sound/sine/orbit/2");
+        assert_eq!(engine.orbits[2].reverb_params.decay, 0.9);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn orbit_eq_is_set_and_automated_by_orbit_commands() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/orbit/2/orbithpf/120/orbitlpf/8000/orbiteqmid/-3");
+        let tone = &engine.orbits[2].tone;
+        assert_eq!((tone.hpf, tone.lpf, tone.mid), (120.0, 8000.0, -3.0));
+        assert_eq!(engine.orbits[0].tone.hpf, 0.0);
+
+        engine.evaluate("/doux/orbit/2/orbiteqhi/0~6:4");
+        assert!(engine.orbits[2]
+            .lanes()
+            .any(|(param, _)| param == OrbitParam::EqHi));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn orbit_repeat_replays_audio_after_the_voice_ends() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/orbit/1/repeatbpm/240/repeatbeats/1/repeatsize/0.5");
+        assert!(engine.orbits[1].repeat.is_armed());
+        engine.evaluate("/sound/sine/orbit/1/gate/0.1/release/0.01");
+        // One beat at 240 bpm is 0.25 s
+        for _ in 0..188 {
+            engine.dsp();
+        }
+        assert_eq!(engine.active_voices, 0);
+        engine.evaluate("/doux/orbit/1/repeat/1");
+        let mut peak: f32 = 0.0;
+        for _ in 0..375 {
+            engine.dsp();
+            peak = engine.output.iter().fold(peak, |p, x| p.max(x.abs()));
+        }
+        assert!(peak > 0.1, "repeat played nothing (peak {peak})");
+
+        engine.evaluate("/doux/orbit/1/repeat/0");
+        for _ in 0..10 {
+            engine.dsp();
+        }
+        assert!(!engine.orbits[1].repeat.is_playing());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn duck_aliases_sidechain_one_orbit_from_another() {
+        let run = |duck: bool| {
+            let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+            if duck {
+                engine.evaluate("/doux/orbit/1/duckfrom/0/duckamount/0.8/duckrelease/0.2");
+            }
+            engine.evaluate("/sound/sine/orbit/1/freq/220/gate/1");
+            engine.evaluate("/sound/sine/orbit/0/freq/55/gate/1");
+            let mut peak: f32 = 0.0;
+            for i in 0..100 {
+                engine.dsp();
+                if i >= 50 {
+                    peak = engine.output.iter().fold(peak, |p, x| p.max(x.abs()));
+                }
+            }
+            (engine.orbits[1].comp_orbit, peak)
+        };
+        let (_, dry) = run(false);
+        let (source, ducked) = run(true);
+        assert_eq!(source, 0);
+        // Orbit 1 is pushed down while orbit 0 plays, so the sum drops
+        assert!(ducked < dry * 0.9, "ducked {ducked} vs dry {dry}");
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn automation_lanes_drive_orbit_params() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 480);
+        engine.evaluate("/doux/automate/orbit/1/verbdecay/0.3>0.9:0.02/delaytime/>0.5:0.02");
+        let start_time = engine.orbits[1].delay.params.time;
+        engine.process_block(&mut [0.0; 960], &[], &[]);
+        let orbit = &engine.orbits[1];
+        assert!(orbit.reverb_params.decay > 0.3 && orbit.reverb_params.decay < 0.9);
+        assert!(orbit.delay.params.time != start_time);
+        for _ in 0..4 {
+            engine.process_block(&mut [0.0; 960], &[], &[]);
+        }
+        assert_eq!(engine.orbits[1].reverb_params.decay, 0.9);
+        assert_eq!(engine.orbits[1].delay.params.time, 0.5);
+        assert_eq!(
+            engine.orbits[0].reverb_params.decay,
+            ReverbParams::default().decay
+        );
+
+        // A plain value stops the lane and sets the param
+        engine.evaluate("/doux/automate/orbit/1/verbdecay/0.2~0.4:1");
+        engine.evaluate("/doux/automate/orbit/1/verbdecay/0.5");
+        engine.process_block(&mut [0.0; 960], &[], &[]);
+        assert_eq!(engine.orbits[1].reverb_params.decay, 0.5);
+
+        engine.evaluate("/doux/automate/orbit/1/combfreq/100~200:4");
+        engine.evaluate("/doux/automate/orbit/1/combfreq/off");
+        engine.process_block(&mut [0.0; 960], &[], &[]);
+        let freq = engine.orbits[1].comb_params.freq;
+        engine.process_block(&mut [0.0; 960], &[], &[]);
+        assert_eq!(engine.orbits[1].comb_params.freq, freq);
+
+        // Orbit commands take chains too
+        engine.evaluate("/doux/orbit/2/verbdecay/0.3~0.9:8/verbreturn/0.5");
+        assert_eq!(engine.orbits[2].lanes().count(), 1);
+        assert_eq!(engine.orbits[2].verb_return, 0.5);
+        engine.process_block(&mut [0.0; 960], &[], &[]);
+        assert!(engine.orbits[2].reverb_params.decay >= 0.3);
+        engine.evaluate("/doux/orbit/2/verbdecay/0.4");
+        assert_eq!(engine.orbits[2].lanes().count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn orbit_envelopes_reach_the_metrics() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/orbit/2");
+        for _ in 0..4 {
+            engine.process_block(&mut [0.0; 128], &[], &[]);
+        }
+        assert!(engine.metrics.orbit_env(2) > 0.01);
+        assert_eq!(engine.metrics.orbit_env(0), 0.0);
+        assert_eq!(engine.metrics.orbit_env(MAX_ORBITS), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn return_gain_scales_the_wet_signal() {
+        let wet_peak = |ret: &str| {
+            let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+            engine.evaluate(&format!(
+                "/doux/orbit/0/delay/1/delaytime/0.01/delayreturn/{ret}"
+            ));
+            engine.evaluate("/sound/sine");
+            for _ in 0..20 {
+                engine.process_block(&mut [0.0; 128], &[], &[]);
+            }
+            engine.metrics.return_env(0, 2)
+        };
+        let full = wet_peak("1");
+        assert!(full > 0.01);
+        assert!((wet_peak("0.5") - full * 0.5).abs() < 1e-4);
+        assert_eq!(wet_peak("0"), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn orbit_sends_feed_effects_without_the_dry_signal() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/orbit2/1/orbit2send/0.5");
+        for _ in 0..4 {
+            engine.process_block(&mut [0.0; 128], &[], &[]);
+        }
+        assert!(engine.orbits[0].env > 0.01);
+        assert_eq!(engine.orbits[1].env, 0.0, "no effects, nothing heard");
+
+        engine.evaluate("/doux/orbit/1/delay/1/delaytime/0.001");
+        for _ in 0..4 {
+            engine.process_block(&mut [0.0; 128], &[], &[]);
+        }
+        assert!(engine.orbits[1].env > 0.001);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn bus_compressor_timing_comes_from_orbit_commands_only() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/orbit/0/compthresh/-20/compattack/0.05/comprelease/0.3");
+        engine.evaluate("/sound/sine/compthresh/-10/compattack/0.002/comprelease/0.05");
+        let p = engine.orbits[0].dynamics_params;
+        assert_eq!((p.threshold, p.attack, p.release), (-20.0, 0.05, 0.3));
+        // The voice's timing still reaches the ducker
+        assert_eq!(engine.orbits[0].comp.params.attack, 0.002);
+    }
+}
@@ -23,23 +23,252 @@
 //! `delta` arg, which takes precedence. The OSC "immediately" sentinel
 //! `(0, 1)` falls through to fire-on-receipt.
 //!
+//! # Acknowledgements
+//!
+//! A message carrying an `ack` arg gets a reply once the engine has handled
+//! it. The `ack` value is the client's own correlation ID and is echoed back:
+//!
+//! ```text
+//! OSC: /play ["sound", "kick", "ack", 7]
+//!  ←   /doux/ack [7, <voice id>, <sample name>]
+//! ```
+//!
+//! The voice ID is stable for the voice's lifetime; address follow-up updates
+//! with `vid/<id>`. A voice ID of `-1` means the event was dropped (late,
+//! voice limit, unknown sound). The sample name is the resolved `folder/n`
+//! entry, or the requested `sound` for synth sources.
+//!
+//! # Trigger Out
+//!
+//! [`run_trigger_out`] forwards every voice trigger to another host, timed by
+//! the engine rather than by audio analysis:
+//!
+//! ```text
+//! →   /doux/trigger [<voice id>, <sound>, <freq>, <amp>, <orbit>]
+//! ```
+//!
+//! # Envelope Out
+//!
+//! [`run_env_out`] streams each orbit's output envelope at a low, fixed rate
+//! (one bundle per tick), for lights and visuals that follow the music:
+//!
+//! ```text
+//! →   /doux/env/<orbit> [<level>]
+//! ```
+//!
+//! With `/doux/master/monowarn/<threshold>` set, a bus whose stereo
+//! correlation stays below `-threshold` would cancel on a mono PA. The
+//! stream then carries one warning as it happens (orbit `-1` is the
+//! master):
+//!
+//! ```text
+//! →   /doux/monowarn [<orbit>, <correlation>]
+//! ```
+//!
+//! # Clock Out
+//!
+//! [`run_clock_out`] broadcasts the engine clock at a fixed interval, so
+//! visualizers and sync partners can follow it: time in seconds, tick,
+//! active voices and CPU load (`1.0` is the whole block budget). The
+//! address is configurable and defaults to [`CLOCK_ADDRESS`]:
+//!
+//! ```text
+//! →   /doux/clock [<time>, <tick>, <voices>, <load>]
+//! ```
+//!
+//! # Waveform Overviews
+//!
+//! With a sample registry attached, `/doux/overview ["sample", <name>]` (and
+//! an optional `"points", <n>`) is answered with the loaded sample's peaks:
+//!
+//! ```text
+//! ←   /doux/overview [<name>, <channels>, <points>, <frames>, <total frames>, <blob>]
+//! ```
+//!
+//! The blob holds little-endian f32 `[min, max]` pairs, channel-major. Samples
+//! that have not been loaded yet reply with zero channels and an empty blob;
+//! `frames < total frames` means only the head is loaded so far.
+//!
+//! # Latency Calibration
+//!
+//! `/doux/calibrate` (see [`crate::Engine`]) measures the round trip from
+//! the outputs back to the live input. With engine metrics attached, the
+//! result is answered to `/doux/latency`, in frames (`-1` until measured):
+//!
+//! ```text
+//! OSC: /doux/latency
+//!  ←   /doux/latency [<frames>]
+//! ```
+//!
+//! # Voice Details
+//!
+//! `/doux/query/voices_detail` (or `/doux/<name>/query/voices_detail` for a
+//! named engine) is answered with the engine's active voices as a JSON array
+//! of [`VoiceDetail`](crate::VoiceDetail)s: sound, freq, gain, velocity,
+//! orbit, envelope stage and value, sends and age in seconds.
+//!
+//! ```text
+//! OSC: /doux/query/voices_detail
+//!  ←   /doux/query/voices_detail [<json>]
+//! ```
+//!
+//! # Strict Mode
+//!
+//! With `--strict`, or after `/doux/strict [1]`, every message is checked
+//! before it reaches an engine. Messages with unknown keys or unparsable
+//! values are not played; the sender gets one reply per problem instead:
+//!
+//! ```text
+//! OSC: /play ["sound", "saw", "lfp", 800]
+//!  ←   /doux/warning ["lfp", "unknown key `lfp`"]
+//! ```
+//!
+//! `/doux/strict [0]` switches back to ignoring them silently.
+//!
+//! # Multiple Engines
+//!
+//! Extra engines registered as [`OscRoute`]s are addressed by name: messages
+//! sent to `/doux/<name>` (or any address below it) go to that engine, with
+//! bundle timetags resolved against its own clock. Everything else goes to
+//! the main engine.
+//!
+//! ```text
+//! OSC: /doux/cue ["sound", "kick"]   →  engine "cue"
+//! OSC: /play ["sound", "kick"]       →  main engine
+//! ```
+//!
 //! # Protocol
 //!
 //! - Transport: UDP
 //! - Default bind: `0.0.0.0:<port>` (all interfaces)
 //! - Supports both single messages and bundles (bundles are flattened)
 
+use crate::event::{push_token, Event};
+use crate::sampling::SampleRegistry;
+use crate::telemetry::EngineMetrics;
 use crate::time::TimeAnchor;
-use crate::AudioCmd;
-use crossbeam_channel::Sender;
-use rosc::{OscMessage, OscPacket, OscType};
-use std::net::UdpSocket;
+use crate::types::{MAX_ORBITS, MAX_VOICES};
+use crate::{AudioCmd, EventAck, VoiceEvent, VoiceEventKind};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use rosc::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 
 /// Maximum UDP packet size for incoming OSC messages.
 const BUFFER_SIZE: usize = 4096;
 
+/// Unanswered acks kept before the oldest are forgotten.
+const MAX_PENDING_ACKS: usize = 1024;
+
+/// Where to send the reply for an acknowledged message.
+struct PendingAck {
+    addr: SocketAddr,
+    client_id: OscType,
+    sound: String,
+}
+
+/// Ack bookkeeping shared between the receive loop and the reply thread.
+#[derive(Default)]
+struct AckTable {
+    next_token: u64,
+    pending: HashMap<u64, PendingAck>,
+}
+
+impl AckTable {
+    fn register(&mut self, pending: PendingAck) -> u64 {
+        self.next_token += 1;
+        let token = self.next_token;
+        if self.pending.len() >= MAX_PENDING_ACKS {
+            let cutoff = token.saturating_sub(MAX_PENDING_ACKS as u64 / 2);
+            self.pending.retain(|&t, _| t > cutoff);
+        }
+        self.pending.insert(token, pending);
+        token
+    }
+}
+
+/// An extra engine reachable under `/doux/<name>`.
+pub struct OscRoute {
+    pub name: String,
+    pub tx: Sender<AudioCmd>,
+    pub anchor: TimeAnchor,
+    /// Ack receiver of this engine; replies share the main ack table.
+    pub acks: Option<Receiver<EventAck>>,
+}
+
+/// Where a message ends up: an engine's command queue and its clock.
+struct Target {
+    tx: Sender<AudioCmd>,
+    anchor: TimeAnchor,
+}
+
+/// Picks the engine for each message by its address.
+struct Router {
+    main: Target,
+    named: Vec<(String, Target)>,
+}
+
+impl Router {
+    fn target(&self, addr: &str) -> &Target {
+        addr.strip_prefix("/doux/")
+            .map(|rest| rest.split('/').next().unwrap_or(rest))
+            .and_then(|name| self.named.iter().find(|(n, _)| n == name))
+            .map_or(&self.main, |(_, target)| target)
+    }
+}
+
+/// Optional services for [`run_recoverable`].
+#[derive(Default)]
+pub struct OscOptions {
+    /// Receiving end of [`crate::Engine::set_ack_sender`]. Messages with an
+    /// `ack` arg are answered from a reply thread that lives until the engine
+    /// drops its sender.
+    pub acks: Option<Receiver<EventAck>>,
+    /// Registry to answer `/doux/overview` queries from.
+    pub registry: Option<Arc<SampleRegistry>>,
+    /// Main engine's metrics, to answer `/doux/latency` queries from.
+    pub metrics: Option<Arc<EngineMetrics>>,
+    /// Extra engines addressed by name.
+    pub routes: Vec<OscRoute>,
+    /// Start in strict mode (see the module docs).
+    pub strict: bool,
+}
+
+/// Sender of a packet, for strict-mode warnings.
+struct Client<'a> {
+    socket: &'a UdpSocket,
+    from: SocketAddr,
+}
+
+impl Client<'_> {
+    /// Rejects `path` with `/doux/warning` replies if it does not parse cleanly.
+    fn rejects(&self, path: &str) -> bool {
+        let warnings = Event::check(path);
+        for warning in &warnings {
+            let reply = OscPacket::Message(OscMessage {
+                addr: "/doux/warning".into(),
+                args: vec![
+                    OscType::String(warning.token().to_string()),
+                    OscType::String(warning.to_string()),
+                ],
+            });
+            if let Ok(bytes) = rosc::encoder::encode(&reply) {
+                let _ = self.socket.send_to(&bytes, self.from);
+            }
+        }
+        !warnings.is_empty()
+    }
+}
+
+/// Per-packet context for registering acks.
+struct AckContext<'a> {
+    from: SocketAddr,
+    table: &'a Mutex<AckTable>,
+}
+
 /// Starts the OSC receiver loop on the specified port.
 ///
 /// Binds to all interfaces (`0.0.0.0`) and returns when `device_lost` is set.
@@ -50,21 +279,90 @@ pub fn run_recoverable(
     port: u16,
     anchor: TimeAnchor,
     device_lost: &AtomicBool,
+    options: OscOptions,
 ) -> std::io::Result<bool> {
     let addr = format!("0.0.0.0:{port}");
     let socket = UdpSocket::bind(&addr)?;
     socket.set_read_timeout(Some(Duration::from_millis(500)))?;
 
+    let mut ack_rxs: Vec<_> = options.acks.into_iter().collect();
+    let mut named = Vec::with_capacity(options.routes.len());
+    for route in options.routes {
+        ack_rxs.extend(route.acks);
+        let target = Target {
+            tx: route.tx,
+            anchor: route.anchor,
+        };
+        named.push((route.name, target));
+    }
+    let router = Router {
+        main: Target { tx, anchor },
+        named,
+    };
+
+    // Tokens come from one table, so any engine's ack finds its client
+    let table = if ack_rxs.is_empty() {
+        None
+    } else {
+        Some(Arc::new(Mutex::new(AckTable::default())))
+    };
+    for rx in ack_rxs {
+        let reply_socket = socket.try_clone()?;
+        let reply_table = Arc::clone(table.as_ref().expect("ack table exists"));
+        std::thread::Builder::new()
+            .name("doux-osc-ack".into())
+            .spawn(move || run_ack_replies(reply_socket, rx, reply_table))?;
+    }
+
     let mut buf = [0u8; BUFFER_SIZE];
+    let mut strict = options.strict;
 
     loop {
         if device_lost.load(Ordering::Acquire) {
             return Ok(true);
         }
         match socket.recv_from(&mut buf) {
-            Ok((size, _addr)) => {
+            Ok((size, from)) => {
                 if let Ok(packet) = rosc::decoder::decode_udp(&buf[..size]) {
-                    handle_packet(&tx, &packet.1, &anchor, None);
+                    if let (OscPacket::Message(msg), Some(registry)) =
+                        (&packet.1, options.registry.as_deref())
+                    {
+                        if msg.addr == "/doux/overview" {
+                            reply_overview(&socket, from, msg, registry);
+                            continue;
+                        }
+                    }
+                    if let (OscPacket::Message(msg), Some(metrics)) =
+                        (&packet.1, options.metrics.as_deref())
+                    {
+                        if msg.addr == "/doux/latency" {
+                            reply_latency(&socket, from, metrics);
+                            continue;
+                        }
+                    }
+                    if let OscPacket::Message(msg) = &packet.1 {
+                        if msg.addr.ends_with("/query/voices_detail") {
+                            let target = router.target(&msg.addr);
+                            reply_voices_detail(&socket, from, &msg.addr, &target.tx);
+                            continue;
+                        }
+                        if msg.addr == "/doux/strict" {
+                            strict = msg.args.first().is_none_or(is_truthy);
+                            continue;
+                        }
+                    }
+                    let ctx = table.as_deref().map(|table| AckContext { from, table });
+                    let client = strict.then_some(Client {
+                        socket: &socket,
+                        from,
+                    });
+                    // The panic hook has dumped it; drop the packet and keep listening
+                    let handled = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        handle_packet(&router, &packet.1, None, ctx.as_ref(), client.as_ref())
+                    }));
+                    if handled.is_err() {
+                        eprintln!("[doux] PANIC handling OSC packet, dropped it");
+                    }
                 }
             }
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
@@ -75,42 +373,333 @@ pub fn run_recoverable(
     }
 }
 
+/// Answers a `/doux/overview` query with the sample's waveform peaks.
+fn reply_overview(socket: &UdpSocket, to: SocketAddr, msg: &OscMessage, registry: &SampleRegistry) {
+    let Some(OscType::String(name)) = find_arg(msg, "sample") else {
+        return;
+    };
+    let points = match find_arg(msg, "points") {
+        Some(OscType::Int(n)) => (*n).max(1) as usize,
+        Some(OscType::Float(n)) => n.max(1.0) as usize,
+        _ => usize::MAX,
+    };
+    let key = if name.contains('/') {
+        name.clone()
+    } else {
+        format!("{name}/0")
+    };
+    let mut args = vec![OscType::String(key.clone())];
+    match registry.get(&key) {
+        Some(data) => {
+            let ov = data.overview.reduce(points);
+            let blob = ov
+                .peaks
+                .iter()
+                .flatten()
+                .flat_map(|v| v.to_le_bytes())
+                .collect();
+            args.extend([
+                OscType::Int(ov.channels as i32),
+                OscType::Int(ov.points as i32),
+                OscType::Int(data.frame_count as i32),
+                OscType::Int(data.total_frames as i32),
+                OscType::Blob(blob),
+            ]);
+        }
+        None => args.extend([
+            OscType::Int(0),
+            OscType::Int(0),
+            OscType::Int(0),
+            OscType::Int(0),
+            OscType::Blob(Vec::new()),
+        ]),
+    }
+    let reply = OscPacket::Message(OscMessage {
+        addr: "/doux/overview".into(),
+        args,
+    });
+    if let Ok(bytes) = rosc::encoder::encode(&reply) {
+        let _ = socket.send_to(&bytes, to);
+    }
+}
+
+/// Answers a `/doux/latency` query with the calibrated input latency.
+fn reply_latency(socket: &UdpSocket, to: SocketAddr, metrics: &EngineMetrics) {
+    let frames = metrics.input_latency().map_or(-1, |f| f as i32);
+    let reply = OscPacket::Message(OscMessage {
+        addr: "/doux/latency".into(),
+        args: vec![OscType::Int(frames)],
+    });
+    if let Ok(bytes) = rosc::encoder::encode(&reply) {
+        let _ = socket.send_to(&bytes, to);
+    }
+}
+
+/// Answers a voices detail query with the engine's active voices as JSON.
+/// Waits on the audio thread, which answers within a block.
+fn reply_voices_detail(socket: &UdpSocket, to: SocketAddr, addr: &str, tx: &Sender<AudioCmd>) {
+    let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+    let cmd = AudioCmd::VoiceDetails {
+        buffer: Vec::with_capacity(MAX_VOICES),
+        reply: reply_tx,
+    };
+    if tx.send(cmd).is_err() {
+        return;
+    }
+    let Ok(voices) = reply_rx.recv_timeout(Duration::from_secs(1)) else {
+        return;
+    };
+    let Ok(json) = serde_json::to_string(&voices) else {
+        return;
+    };
+    let reply = OscPacket::Message(OscMessage {
+        addr: addr.into(),
+        args: vec![OscType::String(json)],
+    });
+    if let Ok(bytes) = rosc::encoder::encode(&reply) {
+        let _ = socket.send_to(&bytes, to);
+    }
+}
+
+/// Sends `/doux/ack` replies until the engine's ack sender is dropped.
+fn run_ack_replies(socket: UdpSocket, rx: Receiver<EventAck>, table: Arc<Mutex<AckTable>>) {
+    loop {
+        let ack = match rx.recv_timeout(Duration::from_millis(500)) {
+            Ok(ack) => ack,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        let Some(pending) = table
+            .lock()
+            .ok()
+            .and_then(|mut t| t.pending.remove(&ack.token))
+        else {
+            continue;
+        };
+        let voice_id = ack.voice_id.map_or(-1, |id| id as i32);
+        let name = ack.sample.as_deref().unwrap_or(&pending.sound).to_string();
+        let reply = OscPacket::Message(OscMessage {
+            addr: "/doux/ack".into(),
+            args: vec![
+                pending.client_id,
+                OscType::Int(voice_id),
+                OscType::String(name),
+            ],
+        });
+        if let Ok(bytes) = rosc::encoder::encode(&reply) {
+            let _ = socket.send_to(&bytes, pending.addr);
+        }
+    }
+}
+
+/// Sends a `/doux/trigger` message to `target` for each voice trigger or
+/// respawn received. Free events are ignored.
+///
+/// Returns once the engine drops its voice event sender.
+pub fn run_trigger_out(rx: Receiver<VoiceEvent>, target: SocketAddr) -> std::io::Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    for trig in rx {
+        if trig.kind == VoiceEventKind::Free {
+            continue;
+        }
+        let msg = OscPacket::Message(OscMessage {
+            addr: "/doux/trigger".into(),
+            args: vec![
+                OscType::Int(trig.voice_id as i32),
+                OscType::String(trig.sound().to_string()),
+                OscType::Float(trig.freq),
+                OscType::Float(trig.amp),
+                OscType::Int(trig.orbit as i32),
+            ],
+        });
+        if let Ok(bytes) = rosc::encoder::encode(&msg) {
+            let _ = socket.send_to(&bytes, target);
+        }
+    }
+    Ok(())
+}
+
+/// Sends every orbit's output envelope to `target` as `/doux/env/<orbit>`,
+/// `rate` times per second, along with a `/doux/monowarn` whenever a bus
+/// goes into sustained anti-correlation.
+///
+/// Returns once the engine owning `metrics` is gone.
+pub fn run_env_out(
+    metrics: Weak<EngineMetrics>,
+    target: SocketAddr,
+    rate: f32,
+) -> std::io::Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    let period = Duration::from_secs_f32(1.0 / rate.clamp(1.0, 1000.0));
+    let mut warned = 0u32;
+    while let Some(metrics) = metrics.upgrade() {
+        let orbits = metrics.orbit_count.load(Ordering::Relaxed) as usize;
+        let mut content: Vec<_> = (0..orbits.min(MAX_ORBITS))
+            .map(|orbit| {
+                OscPacket::Message(OscMessage {
+                    addr: format!("/doux/env/{orbit}"),
+                    args: vec![OscType::Float(metrics.orbit_env(orbit))],
+                })
+            })
+            .collect();
+        let anti = metrics.anti_phase.load(Ordering::Relaxed);
+        for bit in (0..=MAX_ORBITS).filter(|b| ((anti & !warned) >> b) & 1 == 1) {
+            let orbit = (bit < MAX_ORBITS).then_some(bit);
+            content.push(OscPacket::Message(OscMessage {
+                addr: "/doux/monowarn".into(),
+                args: vec![
+                    OscType::Int(orbit.map_or(-1, |o| o as i32)),
+                    OscType::Float(metrics.correlation(orbit)),
+                ],
+            }));
+        }
+        warned = anti;
+        drop(metrics);
+        let bundle = OscPacket::Bundle(OscBundle {
+            timetag: OscTime {
+                seconds: 0,
+                fractional: 1,
+            },
+            content,
+        });
+        if let Ok(bytes) = rosc::encoder::encode(&bundle) {
+            let _ = socket.send_to(&bytes, target);
+        }
+        std::thread::sleep(period);
+    }
+    Ok(())
+}
+
+/// Default OSC address of [`run_clock_out`] messages.
+pub const CLOCK_ADDRESS: &str = "/doux/clock";
+
+/// Sends the engine clock to `target` as `address [<time>, <tick>,
+/// <voices>, <load>]` every `interval` seconds.
+///
+/// Returns once the engine owning `metrics` is gone.
+pub fn run_clock_out(
+    metrics: Weak<EngineMetrics>,
+    target: SocketAddr,
+    address: &str,
+    interval: f32,
+) -> std::io::Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    let period = Duration::from_secs_f32(interval.clamp(0.001, 60.0));
+    while let Some(metrics) = metrics.upgrade() {
+        let msg = OscPacket::Message(clock_message(&metrics, address));
+        drop(metrics);
+        if let Ok(bytes) = rosc::encoder::encode(&msg) {
+            let _ = socket.send_to(&bytes, target);
+        }
+        std::thread::sleep(period);
+    }
+    Ok(())
+}
+
+fn clock_message(metrics: &EngineMetrics, address: &str) -> OscMessage {
+    OscMessage {
+        addr: address.to_string(),
+        args: vec![
+            OscType::Double(f64::from_bits(metrics.time_bits.load(Ordering::Relaxed))),
+            OscType::Long(metrics.tick.load(Ordering::Relaxed) as i64),
+            OscType::Int(metrics.active_voices.load(Ordering::Relaxed) as i32),
+            OscType::Float(metrics.load.get_load()),
+        ],
+    }
+}
+
 /// Recursively processes an OSC packet, handling both messages and bundles.
 ///
-/// `parent_tick` propagates a tick resolved from an outer bundle's timetag.
+/// `parent_time` propagates the timetag of an outer bundle; it is resolved to
+/// a tick per message, against the clock of the engine the message goes to.
 /// Nested bundles override with their own timetag.
 fn handle_packet(
-    tx: &Sender<AudioCmd>,
+    router: &Router,
     packet: &OscPacket,
-    anchor: &TimeAnchor,
-    parent_tick: Option<u64>,
+    parent_time: Option<OscTime>,
+    ack: Option<&AckContext>,
+    strict: Option<&Client>,
 ) {
     match packet {
-        OscPacket::Message(msg) => handle_message(tx, msg, parent_tick),
+        OscPacket::Message(msg) => {
+            let target = router.target(&msg.addr);
+            let tick = parent_time.and_then(|t| target.anchor.ntp_to_tick(t.seconds, t.fractional));
+            handle_message(&target.tx, msg, tick, ack, strict);
+        }
         OscPacket::Bundle(bundle) => {
-            let tick = anchor
-                .ntp_to_tick(bundle.timetag.seconds, bundle.timetag.fractional)
-                .or(parent_tick);
+            let t = bundle.timetag;
+            // "Immediately" keeps the outer bundle's time
+            let time = match router.main.anchor.ntp_to_tick(t.seconds, t.fractional) {
+                Some(_) => Some(t),
+                None => parent_time,
+            };
             for p in &bundle.content {
-                handle_packet(tx, p, anchor, tick);
+                handle_packet(router, p, time, ack, strict);
             }
         }
     }
 }
 
 /// Converts an OSC message to a path string and sends it as an AudioCmd.
-fn handle_message(tx: &Sender<AudioCmd>, msg: &OscMessage, tick: Option<u64>) {
-    let path = osc_to_path(msg);
-    if !path.is_empty() {
-        let _ = tx.send(AudioCmd::Evaluate { path, tick });
+///
+/// If the message asks for an ack, registers it and appends the engine-side
+/// token to the path. In strict mode, messages that do not parse cleanly are
+/// answered with warnings and dropped.
+fn handle_message(
+    tx: &Sender<AudioCmd>,
+    msg: &OscMessage,
+    tick: Option<u64>,
+    ack: Option<&AckContext>,
+    strict: Option<&Client>,
+) {
+    let mut path = osc_to_path(msg);
+    if path.is_empty() || strict.is_some_and(|client| client.rejects(&path)) {
+        return;
+    }
+    if let (Some(ctx), Some(client_id)) = (ack, find_arg(msg, "ack")) {
+        let sound = find_arg(msg, "sound")
+            .or_else(|| find_arg(msg, "s"))
+            .map(|v| {
+                let mut s = String::new();
+                push_osc_arg(&mut s, v);
+                s
+            })
+            .unwrap_or_default();
+        if let Ok(mut table) = ctx.table.lock() {
+            let token = table.register(PendingAck {
+                addr: ctx.from,
+                client_id: client_id.clone(),
+                sound,
+            });
+            use std::fmt::Write;
+            let _ = write!(path, "/ack/{token}");
+        }
     }
+    AudioCmd::route(path, tick, |cmd| {
+        let _ = tx.send(cmd);
+    });
+}
+
+/// Returns the value paired with string key `key`, if present.
+fn find_arg<'a>(msg: &'a OscMessage, key: &str) -> Option<&'a OscType> {
+    let args = &msg.args;
+    let mut i = 0;
+    while i + 1 < args.len() {
+        match &args[i] {
+            OscType::String(k) if k == key => return Some(&args[i + 1]),
+            OscType::String(_) => i += 2,
+            _ => i += 1,
+        }
+    }
+    None
 }
 
 /// Converts OSC message arguments to a slash-separated path string.
 ///
 /// Arguments are processed as key-value pairs. Keys must be strings;
-/// non-string keys cause the pair to be skipped. Values are written
-/// directly into a single String without intermediate allocations.
+/// non-string keys cause the pair to be skipped. The `ack` pair is consumed
+/// by the OSC layer and never forwarded. Values are written directly into a
+/// single String without intermediate allocations.
 fn osc_to_path(msg: &OscMessage) -> String {
     let args = &msg.args;
     let mut path = String::with_capacity(args.len() * 8);
@@ -124,6 +713,10 @@ fn osc_to_path(msg: &OscMessage) -> String {
                 continue;
             }
         };
+        if key == "ack" {
+            i += 2;
+            continue;
+        }
         if !path.is_empty() {
             path.push('/');
         }
@@ -136,6 +729,20 @@ fn osc_to_path(msg: &OscMessage) -> String {
     path
 }
 
+/// Reads an on/off flag: nonzero numbers, `true`, and strings other than
+/// `0`/`off`/`false`.
+fn is_truthy(arg: &OscType) -> bool {
+    match arg {
+        OscType::Int(v) => *v != 0,
+        OscType::Long(v) => *v != 0,
+        OscType::Float(v) => *v != 0.0,
+        OscType::Double(v) => *v != 0.0,
+        OscType::Bool(b) => *b,
+        OscType::String(s) => !matches!(s.as_str(), "0" | "off" | "false"),
+        _ => false,
+    }
+}
+
 fn push_osc_arg(buf: &mut String, arg: &OscType) {
     use std::fmt::Write;
     match arg {
@@ -143,8 +750,87 @@ fn push_osc_arg(buf: &mut String, arg: &OscType) {
         OscType::Float(v) => write!(buf, "{v}").unwrap(),
         OscType::Double(v) => write!(buf, "{v}").unwrap(),
         OscType::Long(v) => write!(buf, "{v}").unwrap(),
-        OscType::String(s) => buf.push_str(s),
+        OscType::String(s) => push_token(buf, s),
         OscType::Bool(b) => buf.push(if *b { '1' } else { '0' }),
         _ => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(sample_rate: f32) -> (Target, Receiver<AudioCmd>) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let anchor = TimeAnchor {
+            start_unix_micros: 0,
+            sample_rate,
+        };
+        (Target { tx, anchor }, rx)
+    }
+
+    fn message(addr: &str) -> OscPacket {
+        OscPacket::Message(OscMessage {
+            addr: addr.into(),
+            args: vec![
+                OscType::String("sound".into()),
+                OscType::String("kick".into()),
+            ],
+        })
+    }
+
+    fn tick_of(cmd: AudioCmd) -> Option<u64> {
+        match cmd {
+            AudioCmd::Evaluate { tick, .. } => tick,
+            _ => panic!("expected an evaluate command"),
+        }
+    }
+
+    #[test]
+    fn prefixed_addresses_route_to_named_engines_on_their_own_clock() {
+        let (main, main_rx) = target(48000.0);
+        let (cue, cue_rx) = target(44100.0);
+        let router = Router {
+            main,
+            named: vec![("cue".into(), cue)],
+        };
+
+        // One second after the Unix epoch, in NTP time
+        let bundle = OscPacket::Bundle(OscBundle {
+            timetag: OscTime {
+                seconds: 2_208_988_801,
+                fractional: 0,
+            },
+            content: vec![
+                message("/doux/cue"),
+                message("/doux/cue/play"),
+                message("/play"),
+                message("/doux/cuex"),
+            ],
+        });
+        handle_packet(&router, &bundle, None, None, None);
+
+        assert_eq!(tick_of(cue_rx.try_recv().unwrap()), Some(44100));
+        assert_eq!(tick_of(cue_rx.try_recv().unwrap()), Some(44100));
+        assert!(cue_rx.try_recv().is_err());
+        assert_eq!(tick_of(main_rx.try_recv().unwrap()), Some(48000));
+        assert_eq!(tick_of(main_rx.try_recv().unwrap()), Some(48000));
+        assert!(main_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn clock_messages_follow_the_engine() {
+        let mut engine = crate::Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/gate/1");
+        let mut output = vec![0.0; 64 * 2];
+        for _ in 0..3 {
+            engine.process_block(&mut output, &[], &[]);
+        }
+        let msg = clock_message(&engine.metrics, "/beat/clock");
+        assert_eq!(msg.addr, "/beat/clock");
+        assert_eq!(msg.args[0], OscType::Double(engine.time));
+        assert_eq!(msg.args[1], OscType::Long(192));
+        assert_eq!(msg.args[2], OscType::Int(1));
+        assert!(matches!(msg.args[3], OscType::Float(load) if load >= 0.0));
+    }
+}
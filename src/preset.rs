@@ -0,0 +1,225 @@
+//! Named parameter presets.
+//!
+//! A preset is a list of key/value pairs stored under a name. An event
+//! with `preset/<name>` plays the preset's pairs first and its own after
+//! them, so the event's pairs win on any key both give:
+//!
+//! | Command                                    | Effect                                  |
+//! |--------------------------------------------|-----------------------------------------|
+//! | `/doux/preset/save/<name>/<key>/<value>/..` | Store the pairs after the name          |
+//! | `/doux/preset/remove/<name>`               | Drop a preset                           |
+//! | `/doux/preset/load/<path>`                 | Add the presets of a JSON file (native) |
+//! | `/doux/preset/write/<path>`                | Write every preset to a JSON file (native) |
+//! | `/sound/saw/preset/<name>/lpf/800`         | Play a preset, overriding `lpf`         |
+//!
+//! A preset saved from an event that itself uses a preset takes that
+//! preset's pairs in, so presets can build on each other. The JSON file
+//! maps each name to its pairs: `{"acidbass": [["sound", "saw"], ["lpf", "400"]]}`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event;
+
+/// Presets by name. See the [module docs](self).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Presets {
+    presets: BTreeMap<String, Vec<(String, String)>>,
+}
+
+impl Presets {
+    /// Stores `pairs` as preset `name`, replacing any preset of that name.
+    /// A `preset` pair is replaced by the pairs of the preset it names.
+    pub fn save(&mut self, name: &str, pairs: Vec<(String, String)>) {
+        let base = pairs
+            .iter()
+            .find(|(key, _)| key == "preset")
+            .and_then(|(_, base)| self.presets.get(base));
+        let mut saved: Vec<_> = base
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| !pairs.iter().any(|(k, _)| k == key))
+            .cloned()
+            .collect();
+        saved.extend(pairs.into_iter().filter(|(key, _)| key != "preset"));
+        self.presets.insert(name.to_string(), saved);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[(String, String)]> {
+        self.presets.get(name).map(Vec::as_slice)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Adds every preset of `other`, replacing those of the same name.
+    pub fn extend(&mut self, other: Presets) {
+        self.presets.extend(other.presets);
+    }
+
+    /// `input` with the pairs of preset `name` in front of its own, minus
+    /// the keys `input` gives itself. `None` when there is no such preset.
+    pub fn expand(&self, name: &str, input: &str) -> Option<String> {
+        let pairs = self.presets.get(name)?;
+        let keys: Vec<_> = event::pairs(input).map(|(key, _)| key).collect();
+        let mut out = String::with_capacity(input.len() + pairs.len() * 16);
+        for (key, value) in pairs {
+            if keys.iter().any(|k| k == key) {
+                continue;
+            }
+            for token in [key, value] {
+                out.push('/');
+                event::escape_token(token, &mut out);
+            }
+        }
+        out.push('/');
+        out.push_str(input.trim_start_matches('/'));
+        Some(out)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    #[cfg(feature = "native")]
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_json(&json)
+    }
+
+    #[cfg(feature = "native")]
+    pub fn write(&self, path: &std::path::Path) -> Result<(), String> {
+        std::fs::write(path, self.to_json()?).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::types::{midi2freq, Source};
+    #[cfg(feature = "native")]
+    use crate::{AudioCmd, Engine};
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn presets_expand_under_the_event_and_build_on_each_other() {
+        let mut presets = Presets::default();
+        presets.save(
+            "bass",
+            pairs(&[("sound", "saw"), ("lpf", "400"), ("decay", "2")]),
+        );
+        assert_eq!(
+            presets.expand("bass", "/preset/bass/lpf/800").as_deref(),
+            Some("/sound/saw/decay/2/preset/bass/lpf/800")
+        );
+        assert_eq!(presets.expand("lead", "/preset/lead"), None);
+
+        presets.save(
+            "acid",
+            pairs(&[("preset", "bass"), ("lpq", "0.8"), ("decay", "4")]),
+        );
+        assert_eq!(
+            presets.get("acid"),
+            Some(
+                &pairs(&[
+                    ("sound", "saw"),
+                    ("lpf", "400"),
+                    ("lpq", "0.8"),
+                    ("decay", "4")
+                ])[..]
+            )
+        );
+
+        // Values that need escaping survive the trip through text
+        presets.save("path", pairs(&[("sound", "a/b")]));
+        let text = presets.expand("path", "/gain/0.5").unwrap();
+        assert_eq!(event::pairs(&text).next().unwrap().1, "a/b");
+
+        let json = presets.to_json().unwrap();
+        assert_eq!(Presets::from_json(&json).unwrap(), presets);
+        assert!(presets.remove("bass"));
+        assert_eq!(presets.names().collect::<Vec<_>>(), ["acid", "path"]);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn presets_play_under_the_event_that_names_them() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        assert_eq!(
+            engine.evaluate("/doux/preset/save/acidbass/sound/saw/lpf/400/lpq/0.7/note/36"),
+            None
+        );
+        assert_eq!(engine.active_voices, 0);
+
+        let i = engine.evaluate("/preset/acidbass/lpf/800").unwrap();
+        let params = &engine.voices[i].params;
+        assert_eq!(params.sound, Source::Saw);
+        assert_eq!((params.lpf, params.lpq), (Some(800.0), 0.7));
+        assert_eq!(params.freq, midi2freq(36.0));
+
+        // A modulated override replaces the preset's plain value
+        let i = engine.evaluate("/preset/acidbass/lpf/200~2000:1").unwrap();
+        assert!(engine.voices[i].params.lpf.is_none());
+
+        engine.evaluate("/doux/preset/remove/acidbass");
+        let i = engine.evaluate("/sound/sine/preset/acidbass").unwrap();
+        assert_eq!(engine.voices[i].params.lpf, None);
+        assert!(engine.presets().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn preset_files_are_read_and_written_off_the_engine() {
+        let dir = std::env::temp_dir().join(format!("doux-presets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("bank.json");
+        let path = format!(
+            "/doux/preset/save/pluck/sound/tri/decay/0.2; /doux/preset/write/\"{}\"",
+            file.display()
+        );
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        AudioCmd::route(path, None, |cmd| engine.apply(cmd));
+        assert!(file.exists());
+
+        // The engine alone leaves the disk alone
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate(&format!("/doux/preset/load/\"{}\"", file.display()));
+        assert!(engine.presets().is_empty());
+
+        let mut cmds = Vec::new();
+        let path = format!(
+            "/doux/preset/load/\"{}\"; /preset/pluck/freq/330",
+            file.display()
+        );
+        AudioCmd::route(path, None, |cmd| cmds.push(cmd));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(cmds[0], AudioCmd::AddPresets(_)));
+        cmds.into_iter().for_each(|cmd| engine.apply(cmd));
+        assert_eq!(engine.voices[0].params.sound, Source::Tri);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::sampling::{SampleData, SampleRegistry};
 use crate::types::CHANNELS;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 
 const MAX_RECORD_SECONDS: usize = 60;
@@ -10,6 +11,18 @@ enum State {
     Overdubbing,
 }
 
+/// What a take records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecSource {
+    /// The master output's first pair.
+    Output,
+    /// One orbit's output.
+    Orbit(usize),
+    /// Live input from this channel and the next (the same one twice when
+    /// the input is mono).
+    Input(usize),
+}
+
 pub struct Recorder {
     buffer: Vec<f32>,
     write_pos: usize,
@@ -17,7 +30,12 @@ pub struct Recorder {
     state: State,
     name: String,
     counter: usize,
-    target_orbit: Option<usize>,
+    sr: f32,
+    source: RecSource,
+    /// Samples a fresh take stops at, if it was given a length.
+    limit: Option<usize>,
+    auto_stop: bool,
+    heard: bool,
 }
 
 impl Recorder {
@@ -30,19 +48,28 @@ impl Recorder {
             state: State::Idle,
             name: String::new(),
             counter: 0,
-            target_orbit: None,
+            sr,
+            source: RecSource::Output,
+            limit: None,
+            auto_stop: false,
+            heard: false,
         }
     }
 
-    pub fn target_orbit(&self) -> Option<usize> {
-        self.target_orbit
+    /// What the take in progress records (`Output` while idle).
+    pub fn source(&self) -> RecSource {
+        self.source
     }
 
+    /// Starts a take, or stops the one in progress. A fresh take stops by
+    /// itself after `seconds` (0 = when the buffer is full).
     pub fn toggle(
         &mut self,
         name: Option<&str>,
         overdub: bool,
-        target_orbit: Option<usize>,
+        auto_stop: bool,
+        source: RecSource,
+        seconds: f32,
         registry: &SampleRegistry,
     ) -> Option<String> {
         match self.state {
@@ -71,59 +98,106 @@ impl Recorder {
                 }
 
                 self.name = rec_name;
-                self.target_orbit = target_orbit;
+                self.source = source;
+                self.limit = (seconds > 0.0)
+                    .then(|| ((seconds * self.sr) as usize * CHANNELS).min(self.max_len));
+                self.auto_stop = auto_stop;
+                self.heard = false;
                 None
             }
-            State::Recording | State::Overdubbing => {
-                self.state = State::Idle;
-                self.target_orbit = None;
-                Some(self.name.clone())
-            }
+            State::Recording | State::Overdubbing => self.stop(),
+        }
+    }
+
+    /// Stops recording and returns the take's name, or `None` if idle.
+    pub fn stop(&mut self) -> Option<String> {
+        if matches!(self.state, State::Idle) {
+            return None;
+        }
+        self.state = State::Idle;
+        self.source = RecSource::Output;
+        self.auto_stop = false;
+        Some(self.name.clone())
+    }
+
+    /// For auto-stop takes: true once the engine has gone idle after making
+    /// sound at least once since recording started.
+    pub fn should_auto_stop(&mut self, engine_idle: bool) -> bool {
+        if !self.auto_stop || matches!(self.state, State::Idle) {
+            return false;
+        }
+        if !engine_idle {
+            self.heard = true;
+            return false;
         }
+        self.heard
+    }
+
+    /// True once a fresh take has recorded all it was asked for.
+    pub fn is_full(&self) -> bool {
+        matches!(self.state, State::Recording)
+            && self.limit.is_some_and(|limit| self.buffer.len() >= limit)
     }
 
     #[inline]
     pub fn capture_block(&mut self, output: &[f32], block_samples: usize, output_channels: usize) {
+        self.capture(block_samples, |i| {
+            let base = i * output_channels;
+            [output[base], output[base + 1]]
+        });
+    }
+
+    /// Like [`capture_block`](Self::capture_block) for an interleaved input
+    /// block, from channel `first` and the next one.
+    #[inline]
+    pub fn capture_input(
+        &mut self,
+        input: &[f32],
+        block_samples: usize,
+        channels: usize,
+        first: usize,
+    ) {
+        self.capture(block_samples, |i| {
+            let base = i * channels + first;
+            let l = input.get(base).copied().unwrap_or(0.0);
+            let r = if first + 1 < channels {
+                input.get(base + 1).copied().unwrap_or(0.0)
+            } else {
+                l
+            };
+            [l, r]
+        });
+    }
+
+    #[inline]
+    fn capture(&mut self, block_samples: usize, frame: impl Fn(usize) -> [f32; CHANNELS]) {
         match self.state {
             State::Idle => {}
-            State::Recording => self.record_block(output, block_samples, output_channels),
-            State::Overdubbing => self.overdub_block(output, block_samples, output_channels),
+            State::Recording => self.record_block(block_samples, frame),
+            State::Overdubbing => self.overdub_block(block_samples, frame),
         }
     }
 
     #[inline]
-    fn record_block(&mut self, output: &[f32], block_samples: usize, output_channels: usize) {
-        let remaining = self.max_len - self.buffer.len();
-        if remaining == 0 {
-            return;
-        }
-
-        if output_channels == CHANNELS {
-            let n = (block_samples * CHANNELS).min(remaining);
-            self.buffer.extend_from_slice(&output[..n]);
-        } else {
-            let max_frames = remaining / CHANNELS;
-            let frames = block_samples.min(max_frames);
-            for i in 0..frames {
-                let base = i * output_channels;
-                self.buffer.push(output[base]);
-                self.buffer.push(output[base + 1]);
-            }
+    fn record_block(&mut self, block_samples: usize, frame: impl Fn(usize) -> [f32; CHANNELS]) {
+        let limit = self.limit.unwrap_or(self.max_len);
+        let remaining = limit.saturating_sub(self.buffer.len());
+        let frames = block_samples.min(remaining / CHANNELS);
+        for i in 0..frames {
+            self.buffer.extend_from_slice(&frame(i));
         }
     }
 
     #[inline]
-    fn overdub_block(&mut self, output: &[f32], block_samples: usize, output_channels: usize) {
+    fn overdub_block(&mut self, block_samples: usize, frame: impl Fn(usize) -> [f32; CHANNELS]) {
         let buf_len = self.buffer.len();
         if buf_len == 0 {
-            self.record_block(output, block_samples, output_channels);
+            self.record_block(block_samples, frame);
             return;
         }
 
         for i in 0..block_samples {
-            let base = i * output_channels;
-            let l = output[base];
-            let r = output[base + 1];
+            let [l, r] = frame(i);
 
             if self.write_pos >= buf_len {
                 self.write_pos = 0;
@@ -143,3 +217,289 @@ impl Recorder {
         Some((self.name.clone(), Arc::new(data)))
     }
 }
+
+/// Rolling capture of the last few seconds of master output, so a moment
+/// can be kept after it already happened (`/doux/keep/<secs>`).
+///
+/// The ring is shared with the thread that writes kept stretches out: the
+/// audio thread only notes where a stretch lies ([`RetroSpan`]) and the
+/// writer copies it.
+pub struct RetroBuffer {
+    ring: Arc<RetroRing>,
+    write_pos: usize,
+    filled: usize,
+    written: u64,
+}
+
+/// Samples of a [`RetroBuffer`], readable while the audio thread writes.
+struct RetroRing {
+    /// `f32` bits.
+    samples: Box<[AtomicU32]>,
+    /// Samples the audio thread has started writing, in total.
+    written: AtomicU64,
+}
+
+impl RetroBuffer {
+    /// Allocates room for `seconds` of stereo output (0 disables capture).
+    pub fn new(seconds: f32, sr: f32) -> Self {
+        let frames = (seconds.max(0.0) * sr) as usize;
+        Self {
+            ring: Arc::new(RetroRing {
+                samples: (0..frames * CHANNELS).map(|_| AtomicU32::new(0)).collect(),
+                written: AtomicU64::new(0),
+            }),
+            write_pos: 0,
+            filled: 0,
+            written: 0,
+        }
+    }
+
+    /// Capacity in frames.
+    pub fn capacity(&self) -> usize {
+        self.ring.samples.len() / CHANNELS
+    }
+
+    #[inline]
+    pub fn capture_block(&mut self, output: &[f32], block_samples: usize, output_channels: usize) {
+        let samples = &self.ring.samples;
+        let len = samples.len();
+        if len == 0 {
+            return;
+        }
+        // Announced first, so a reader that sees the count knows these
+        // slots are no longer the old samples
+        self.written += (block_samples * CHANNELS) as u64;
+        self.ring.written.store(self.written, Ordering::Release);
+        for i in 0..block_samples {
+            let base = i * output_channels;
+            samples[self.write_pos].store(output[base].to_bits(), Ordering::Relaxed);
+            samples[self.write_pos + 1].store(output[base + 1].to_bits(), Ordering::Relaxed);
+            self.write_pos += CHANNELS;
+            if self.write_pos >= len {
+                self.write_pos = 0;
+            }
+        }
+        self.filled = (self.filled + block_samples * CHANNELS).min(len);
+    }
+
+    /// Where the most recent `frames` frames (or fewer if not yet captured)
+    /// lie. Copies nothing.
+    pub fn last(&self, frames: usize) -> RetroSpan {
+        RetroSpan {
+            ring: Arc::clone(&self.ring),
+            end: self.written,
+            len: (frames * CHANNELS).min(self.filled),
+        }
+    }
+}
+
+/// A stretch of a [`RetroBuffer`], to be copied off the audio thread.
+pub struct RetroSpan {
+    ring: Arc<RetroRing>,
+    /// Total samples written when the stretch was taken; it ends there.
+    end: u64,
+    /// Length in samples.
+    len: usize,
+}
+
+impl RetroSpan {
+    /// Copies the stretch out, oldest first. Samples the audio thread has
+    /// gone round to again by the end of the copy are newer than the rest,
+    /// so they are dropped from the front.
+    pub fn copy(&self) -> Vec<f32> {
+        let samples = &self.ring.samples;
+        let start = self.end - self.len as u64;
+        let mut out: Vec<f32> = (start..self.end)
+            .map(|i| {
+                f32::from_bits(samples[(i % samples.len() as u64) as usize].load(Ordering::Relaxed))
+            })
+            .collect();
+        let written = self.ring.written.load(Ordering::Acquire);
+        let overwritten = written
+            .saturating_sub(samples.len() as u64)
+            .saturating_sub(start);
+        // Whole frames, so the channels stay in order
+        let overwritten = (overwritten as usize)
+            .min(self.len)
+            .next_multiple_of(CHANNELS);
+        out.drain(..overwritten.min(out.len()));
+        out
+    }
+}
+
+/// A stretch of captured master output, ready to be written to disk.
+pub struct KeepRequest {
+    pub span: RetroSpan,
+    pub sample_rate: f32,
+}
+
+impl KeepRequest {
+    /// Copies the frames out (interleaved stereo). Keep it off the audio
+    /// thread.
+    pub fn frames(&self) -> Vec<f32> {
+        self.span.copy()
+    }
+
+    pub fn duration(&self) -> f32 {
+        (self.span.len / CHANNELS) as f32 / self.sample_rate
+    }
+
+    /// Writes the frames as a 32-bit float WAV file.
+    pub fn write_wav(&self, path: &std::path::Path) -> std::io::Result<()> {
+        write_wav_f32(path, &self.frames(), CHANNELS, self.sample_rate)
+    }
+}
+
+/// Writes interleaved samples as a 32-bit float WAV file.
+pub(crate) fn write_wav_f32(
+    path: &std::path::Path,
+    samples: &[f32],
+    channels: usize,
+    sample_rate: f32,
+) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut w = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_wav_header(&mut w, channels, sample_rate, samples.len())?;
+    for s in samples {
+        w.write_all(&s.to_le_bytes())?;
+    }
+    w.flush()
+}
+
+/// Writes the 44-byte header of a 32-bit float WAV file holding `samples`
+/// interleaved samples.
+pub(crate) fn write_wav_header(
+    w: &mut impl std::io::Write,
+    channels: usize,
+    sample_rate: f32,
+    samples: usize,
+) -> std::io::Result<()> {
+    let channels = channels as u16;
+    let sr = sample_rate as u32;
+    let block_align = channels * 4;
+    let data_len = (samples * 4) as u32;
+
+    w.write_all(b"RIFF")?;
+    w.write_all(&(36 + data_len).to_le_bytes())?;
+    w.write_all(b"WAVEfmt ")?;
+    w.write_all(&16u32.to_le_bytes())?;
+    w.write_all(&3u16.to_le_bytes())?; // IEEE float
+    w.write_all(&channels.to_le_bytes())?;
+    w.write_all(&sr.to_le_bytes())?;
+    w.write_all(&(sr * block_align as u32).to_le_bytes())?;
+    w.write_all(&block_align.to_le_bytes())?;
+    w.write_all(&32u16.to_le_bytes())?;
+    w.write_all(b"data")?;
+    w.write_all(&data_len.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Engine, Event};
+
+    #[test]
+    fn a_take_with_a_length_stops_at_it() {
+        let mut rec = Recorder::new(1000.0);
+        let registry = SampleRegistry::new();
+        rec.toggle(
+            Some("loop"),
+            false,
+            false,
+            RecSource::Input(1),
+            0.01,
+            &registry,
+        );
+        assert_eq!(rec.source(), RecSource::Input(1));
+
+        // Channels 1 and 2 of a three-channel input, 10 frames of 16
+        let input: Vec<f32> = (0..16 * 3).map(|i| i as f32).collect();
+        rec.capture_input(&input, 8, 3, 1);
+        assert!(!rec.is_full());
+        rec.capture_input(&input[8 * 3..], 8, 3, 1);
+        assert!(rec.is_full());
+
+        let (name, take) = rec.finalize().unwrap();
+        assert_eq!(name, "loop");
+        assert_eq!(take.frames.len(), 10 * 2);
+        assert_eq!(take.frames[..4], [1.0, 2.0, 4.0, 5.0]);
+        assert_eq!(rec.stop().as_deref(), Some("loop"));
+        assert_eq!(rec.source(), RecSource::Output);
+
+        // The last channel of an input pairs with itself
+        rec.toggle(None, false, false, RecSource::Input(2), 0.0, &registry);
+        rec.capture_input(&input, 1, 3, 2);
+        assert!(!rec.is_full());
+        assert_eq!(rec.finalize().unwrap().1.frames[..], [2.0, 2.0]);
+    }
+
+    #[test]
+    fn keep_returns_latest_output_oldest_first() {
+        let mut engine = Engine::new_with_channels(1000.0, 2, 8, 4);
+        engine.set_retro_seconds(0.01);
+        let mut out = [0.0f32; 8];
+        for block in 0..5 {
+            for (i, s) in out.iter_mut().enumerate() {
+                *s = (block * 4 + i / 2) as f32;
+            }
+            engine.retro.capture_block(&out, 4, 2);
+        }
+
+        // 10-frame ring after 20 frames: last 3 are frames 17..20
+        let kept = engine.keep(0.003);
+        assert_eq!(kept.frames(), vec![17.0, 17.0, 18.0, 18.0, 19.0, 19.0]);
+        assert_eq!(engine.keep(1.0).frames().len(), 20);
+        assert_eq!(engine.keep(1.0).frames()[0], 10.0);
+
+        // A block written before the copy replaces the oldest frames, which
+        // are dropped rather than mixed in
+        let kept = engine.keep(1.0);
+        engine.retro.capture_block(&out, 4, 2);
+        let frames = kept.frames();
+        assert_eq!(frames.len(), 12);
+        assert_eq!(frames[0], 14.0);
+    }
+
+    #[test]
+    fn rec_with_a_length_loops_live_input_into_a_sample() {
+        let mut engine = Engine::new_with_channels(1000.0, 2, 8, 64);
+        let input: Vec<f32> = (0..128).map(|i| i as f32 / 128.0).collect();
+        let mut out = vec![0.0; 128];
+        engine.evaluate("/doux/rec/0.1/name/loop1");
+        for _ in 0..2 {
+            engine.process_block(&mut out, &[], &input);
+        }
+        // 100 frames in, the take stopped and was stored by itself
+        let take = engine.sample_registry.get("loop1/0").unwrap();
+        assert_eq!(take.frames.len(), 100 * 2);
+        assert_eq!(take.frames[..4], input[..4]);
+        assert_eq!(take.frames[128..132], input[..4]);
+        assert_eq!(engine.evaluate("/s/loop1"), Some(0));
+
+        // Until stopped, from one channel of a mono input
+        engine.input_channels = 1;
+        engine.evaluate("/doux/rec/loop2/input/1");
+        engine.process_block(&mut out, &[], &input[..64]);
+        engine.evaluate("/doux/rec");
+        let take = engine.sample_registry.get("loop2/0").unwrap();
+        assert_eq!(take.frames.len(), 64 * 2);
+        assert_eq!(take.frames[6..8], [input[3], input[3]]);
+
+        // A bare number is the length; a numeric name needs `name`
+        let event = Event::parse("/doux/rec/4/take", 1000.0);
+        assert_eq!(
+            (event.len, event.sound.as_deref()),
+            (Some(4.0), Some("take"))
+        );
+        let event = Event::parse("/doux/rec/overdub/1", 1000.0);
+        assert_eq!((event.len, event.sound), (None, None));
+        engine.evaluate("/doux/rec/name/808");
+        assert_eq!(engine.recorder.source(), RecSource::Output);
+        engine.process_block(&mut out, &[], &input);
+        engine.evaluate("/doux/rec");
+        assert!(engine.sample_registry.get("808/0").is_some());
+        engine.evaluate("/doux/rec/len/nan");
+        assert_eq!(engine.recorder.source(), RecSource::Output);
+        engine.evaluate("/doux/rec");
+    }
+}
@@ -0,0 +1,293 @@
+//! Output routing matrix: orbit outputs to arbitrary device channels.
+//!
+//! Orbits without a route keep the default placement (`out_mode`). Once an
+//! orbit has routes, it is heard only through them, each with its own gain,
+//! so asymmetric speaker setups (a centre fill, a mono sub, a pair split
+//! across interfaces) need no extra orbits. `/doux/route/.../outpair` is a
+//! stereo route to the pair's two channels.
+
+use crate::types::{CHANNELS, MAX_ORBITS};
+use serde::{Deserialize, Serialize};
+
+/// Most routes across all orbits.
+pub const MAX_ROUTES: usize = 32;
+
+/// One orbit output sent to device channels.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutRoute {
+    pub orbit: usize,
+    /// Device channel of the orbit's left side.
+    pub left: usize,
+    /// Device channel of the orbit's right side; may equal `left`.
+    pub right: usize,
+    pub gain: f32,
+    /// Sum the orbit to mono before sending it to `left` (and `right` if it
+    /// differs).
+    pub mono: bool,
+}
+
+impl OutRoute {
+    /// Stereo route to `channel` and the one after it.
+    pub fn pair(orbit: usize, channel: usize) -> Self {
+        Self {
+            orbit,
+            left: channel,
+            right: channel + 1,
+            gain: 1.0,
+            mono: false,
+        }
+    }
+
+    /// Mono mix of the orbit to a single channel.
+    pub fn mono(orbit: usize, channel: usize) -> Self {
+        Self {
+            orbit,
+            left: channel,
+            right: channel,
+            gain: 1.0,
+            mono: true,
+        }
+    }
+
+    #[inline]
+    fn mix(&self, total: [f32; CHANNELS], frame: &mut [f32]) {
+        let (l, r) = if self.mono {
+            let m = (total[0] + total[1]) * 0.5 * self.gain;
+            (m, m)
+        } else {
+            (total[0] * self.gain, total[1] * self.gain)
+        };
+        if let Some(out) = frame.get_mut(self.left) {
+            *out += l;
+        }
+        // A mono route to one channel is heard once
+        if self.right != self.left || !self.mono {
+            if let Some(out) = frame.get_mut(self.right) {
+                *out += r;
+            }
+        }
+    }
+}
+
+/// Routes for every orbit, in a fixed-size table the audio thread can read
+/// without allocating.
+#[derive(Clone, Copy, Debug)]
+pub struct RouteMatrix {
+    routes: [OutRoute; MAX_ROUTES],
+    count: usize,
+    /// Bit per orbit with at least one route.
+    routed: u32,
+}
+
+impl Default for RouteMatrix {
+    fn default() -> Self {
+        Self {
+            routes: [OutRoute::pair(0, 0); MAX_ROUTES],
+            count: 0,
+            routed: 0,
+        }
+    }
+}
+
+impl RouteMatrix {
+    /// Adds a route next to the orbit's others. Returns false when the
+    /// table is full or the orbit is out of range.
+    pub fn add(&mut self, route: OutRoute) -> bool {
+        if self.count == MAX_ROUTES || route.orbit >= MAX_ORBITS {
+            return false;
+        }
+        self.routes[self.count] = route;
+        self.count += 1;
+        self.routed |= 1 << route.orbit;
+        true
+    }
+
+    /// Replaces the orbit's routes with `route`.
+    pub fn set(&mut self, route: OutRoute) -> bool {
+        self.clear_orbit(route.orbit);
+        self.add(route)
+    }
+
+    /// Drops the orbit's routes, back to the default placement.
+    pub fn clear_orbit(&mut self, orbit: usize) {
+        let mut kept = 0;
+        for i in 0..self.count {
+            if self.routes[i].orbit != orbit {
+                self.routes[kept] = self.routes[i];
+                kept += 1;
+            }
+        }
+        self.count = kept;
+        if orbit < MAX_ORBITS {
+            self.routed &= !(1 << orbit);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.routed = 0;
+    }
+
+    /// True if `orbit` is placed by the matrix instead of the default.
+    #[inline]
+    pub fn is_routed(&self, orbit: usize) -> bool {
+        orbit < MAX_ORBITS && self.routed & (1 << orbit) != 0
+    }
+
+    pub fn routes(&self) -> &[OutRoute] {
+        &self.routes[..self.count]
+    }
+
+    /// Adds the orbit's output to one interleaved device `frame`.
+    #[inline]
+    pub fn mix(&self, orbit: usize, total: [f32; CHANNELS], frame: &mut [f32]) {
+        for route in self.routes() {
+            if route.orbit == orbit {
+                route.mix(total, frame);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::types::OutMode;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    #[test]
+    fn routes_split_gain_and_mono_mix() {
+        let mut matrix = RouteMatrix::default();
+        assert!(matrix.add(OutRoute::pair(1, 3)));
+        assert!(matrix.add(OutRoute {
+            gain: 0.5,
+            ..OutRoute::mono(1, 0)
+        }));
+        assert!(matrix.is_routed(1) && !matrix.is_routed(0));
+
+        let mut frame = [0.0; 6];
+        matrix.mix(1, [1.0, 0.2], &mut frame);
+        assert_eq!(frame, [0.3, 0.0, 0.0, 1.0, 0.2, 0.0]);
+        // Channels past the device are skipped
+        matrix.set(OutRoute::pair(1, 5));
+        let mut frame = [0.0; 6];
+        matrix.mix(1, [1.0, 0.2], &mut frame);
+        assert_eq!(frame, [0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+
+        matrix.clear_orbit(1);
+        assert!(!matrix.is_routed(1));
+        assert!(matrix.routes().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn orbits_resize_and_route_to_any_pair() {
+        let mut engine = Engine::new_with_channels(48000.0, 6, 8, 64);
+        engine.set_max_delay_time(3.0);
+        let bank = engine.orbits.as_ptr();
+        engine.set_orbit_count(12);
+        assert_eq!(engine.orbits.len(), 12);
+        assert_eq!(engine.orbits.as_ptr(), bank);
+        assert_eq!(
+            engine.orbits[11].delay.max_time(),
+            engine.orbits[0].delay.max_time()
+        );
+        engine.evaluate("/doux/orbits/n/0");
+        assert_eq!(engine.orbits.len(), 1);
+        engine.set_orbit_count(4);
+
+        engine.evaluate("/doux/route/orbit/3/outpair/2");
+        assert_eq!(engine.routes.routes(), [OutRoute::pair(3, 4)]);
+        engine.evaluate("/sound/sine/orbit/3");
+        let mut peak = [0.0f32; 6];
+        for _ in 0..20 {
+            engine.dsp();
+            for frame in engine.output.chunks(6) {
+                for (p, x) in peak.iter_mut().zip(frame) {
+                    *p = p.max(x.abs());
+                }
+            }
+        }
+        // Without the route, orbit 3 would land on pair 0
+        assert_eq!(peak[0], 0.0);
+        assert!(peak[4] > 0.01, "{peak:?}");
+
+        engine.evaluate("/doux/route/orbit/3/outpair/auto");
+        assert!(!engine.routes.is_routed(3));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn route_matrix_sends_orbits_to_any_channels() {
+        let mut engine = Engine::new_with_channels(48000.0, 5, 8, 64);
+        // Orbit 1: left/right on channels 1 and 3, plus a quiet mono fill on 4
+        engine.evaluate("/doux/route/orbit/1/outch/1/outch2/3");
+        engine.evaluate("/doux/route/orbit/1/outch/4/outmono/1/outgain/0.5/outadd/1");
+        assert_eq!(engine.routes.routes().len(), 2);
+        engine.evaluate("/sound/sine/orbit/1/pan/0");
+        let mut peak = [0.0f32; 5];
+        for _ in 0..20 {
+            engine.dsp();
+            for frame in engine.output.chunks(5) {
+                for (p, x) in peak.iter_mut().zip(frame) {
+                    *p = p.max(x.abs());
+                }
+            }
+        }
+        // Panned hard left: only the left route and the mono fill sound
+        assert_eq!([peak[0], peak[2], peak[3]], [0.0; 3], "{peak:?}");
+        assert!(peak[1] > 0.01);
+        assert!(
+            (peak[4] - peak[1] * 0.25).abs() < peak[1] * 0.05,
+            "{peak:?}"
+        );
+
+        engine.evaluate("/doux/route/orbit/1/outch/off");
+        assert!(!engine.routes.is_routed(1));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn multi_out_gives_each_orbit_its_own_channels() {
+        let peaks = |engine: &mut Engine| {
+            let mut peak = [0.0f32; 4];
+            for _ in 0..20 {
+                engine.dsp();
+                for frame in engine.output.chunks(4) {
+                    for (p, x) in peak.iter_mut().zip(frame) {
+                        *p = p.max(x.abs());
+                    }
+                }
+            }
+            peak
+        };
+
+        // Pairs: orbit 2 wraps onto pair 0
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/sound/sine/orbit/2");
+        let peak = peaks(&mut engine);
+        assert!(peak[0] > 0.01 && peak[2] == 0.0, "{peak:?}");
+
+        // Stereo: orbit 2 has no pair of its own and is dropped
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/outmode/stereo");
+        assert_eq!(engine.out_mode, OutMode::Stereo);
+        engine.evaluate("/sound/sine/orbit/1");
+        engine.evaluate("/sound/sine/orbit/2");
+        let peak = peaks(&mut engine);
+        assert!(
+            peak[0] == 0.0 && peak[2] > 0.01 && peak[3] > 0.01,
+            "{peak:?}"
+        );
+
+        // Mono: orbit n on channel n
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/outmode/mono");
+        engine.evaluate("/sound/sine/orbit/2");
+        let peak = peaks(&mut engine);
+        assert!(peak[2] > 0.01, "{peak:?}");
+        assert_eq!([peak[0], peak[1], peak[3]], [0.0; 3]);
+    }
+}
@@ -0,0 +1,251 @@
+//! Sample chains for hardware samplers.
+//!
+//! Lays samples end to end in slots of equal length, so a sampler that cuts
+//! a file into even slices (Octatrack, Digitakt and the like) finds one
+//! sample per slice. `doux-chain` decodes the samples, builds the chain and
+//! writes it out as a WAV with a cue sheet.
+
+use super::registry::SampleData;
+use super::sample::SampleEntry;
+
+/// Frames faded out at the end of a sample cut short by its slot.
+const TRUNCATE_FADE: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChainOptions {
+    /// Output channels: 1 mixes down, 2 or more map source channels across.
+    pub channels: usize,
+    /// Slot count; defaults to one per sample. Extra slots stay silent.
+    pub slots: Option<usize>,
+    /// Slot length in frames; defaults to the longest sample.
+    pub slot_frames: Option<usize>,
+    /// Peak each slot is normalised to (linear), or `None` to keep levels.
+    pub normalize: Option<f32>,
+}
+
+impl Default for ChainOptions {
+    fn default() -> Self {
+        Self {
+            channels: 2,
+            slots: None,
+            slot_frames: None,
+            normalize: None,
+        }
+    }
+}
+
+/// One filled slot of a chain.
+#[derive(Clone, Debug)]
+pub struct ChainSlot {
+    pub name: String,
+    /// First frame of the slot.
+    pub start: usize,
+    /// Frames of audio in the slot; the rest is silence.
+    pub frames: usize,
+    /// Gain applied by normalisation.
+    pub gain: f32,
+    /// The sample was longer than the slot and got cut.
+    pub truncated: bool,
+}
+
+/// Interleaved chain audio and where each sample sits in it.
+#[derive(Clone, Debug)]
+pub struct SampleChain {
+    pub frames: Vec<f32>,
+    pub channels: usize,
+    pub slot_frames: usize,
+    pub slot_count: usize,
+    pub slots: Vec<ChainSlot>,
+}
+
+impl SampleChain {
+    /// Builds a chain of `samples`, in order, one per slot.
+    pub fn build(
+        samples: &[(String, &SampleData)],
+        options: &ChainOptions,
+    ) -> Result<Self, String> {
+        if samples.is_empty() {
+            return Err("no samples to chain".into());
+        }
+        let channels = options.channels.max(1);
+        let slot_count = options.slots.unwrap_or(samples.len());
+        if samples.len() > slot_count {
+            return Err(format!(
+                "{} samples do not fit in {slot_count} slots",
+                samples.len()
+            ));
+        }
+        let slot_frames = options
+            .slot_frames
+            .unwrap_or_else(|| {
+                samples
+                    .iter()
+                    .map(|(_, d)| d.frame_count as usize)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .max(1);
+
+        let mut frames = vec![0.0f32; slot_count * slot_frames * channels];
+        let mut slots = Vec::with_capacity(samples.len());
+        for (i, (name, data)) in samples.iter().enumerate() {
+            let start = i * slot_frames;
+            let len = (data.frame_count as usize).min(slot_frames);
+            let out = &mut frames[start * channels..(start + len) * channels];
+            mix_channels(data, out, channels);
+
+            let truncated = data.frame_count as usize > slot_frames;
+            if truncated {
+                let fade = TRUNCATE_FADE.min(len);
+                for (k, frame) in out.chunks_mut(channels).rev().take(fade).enumerate() {
+                    let g = k as f32 / fade as f32;
+                    frame.iter_mut().for_each(|s| *s *= g);
+                }
+            }
+
+            let peak = out.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            let gain = match options.normalize {
+                Some(target) if peak > 0.0 => target / peak,
+                _ => 1.0,
+            };
+            out.iter_mut().for_each(|s| *s *= gain);
+
+            slots.push(ChainSlot {
+                name: name.clone(),
+                start,
+                frames: len,
+                gain,
+                truncated,
+            });
+        }
+
+        Ok(Self {
+            frames,
+            channels,
+            slot_frames,
+            slot_count,
+            slots,
+        })
+    }
+
+    /// CUE sheet for the chain written to `wav_name`, one track per sample.
+    /// CUE times are in 1/75 s; `REM FRAMES` lines give the exact starts.
+    pub fn cue_sheet(&self, wav_name: &str, sample_rate: u32) -> String {
+        let sr = sample_rate.max(1) as usize;
+        let mut out = format!(
+            "REM SLOTS {}\nREM SLOT_FRAMES {}\nFILE \"{wav_name}\" WAVE\n",
+            self.slot_count, self.slot_frames
+        );
+        for (i, slot) in self.slots.iter().enumerate() {
+            let cd = slot.start * 75 / sr;
+            out += &format!(
+                "  TRACK {:02} AUDIO\n    TITLE \"{}\"\n    REM FRAMES {} {}\n    INDEX 01 {:02}:{:02}:{:02}\n",
+                i + 1,
+                slot.name,
+                slot.start,
+                slot.frames,
+                cd / (75 * 60),
+                cd / 75 % 60,
+                cd % 75
+            );
+        }
+        out
+    }
+}
+
+/// Copies `data` into `out` (`channels` wide): a mono chain averages the
+/// source channels, a wider one repeats them across.
+fn mix_channels(data: &SampleData, out: &mut [f32], channels: usize) {
+    let src_ch = usize::from(data.channels.max(1));
+    for (frame, src) in out.chunks_mut(channels).zip(data.frames.chunks(src_ch)) {
+        if channels == 1 {
+            frame[0] = src.iter().sum::<f32>() / src_ch as f32;
+        } else {
+            for (c, s) in frame.iter_mut().enumerate() {
+                *s = src[c % src_ch];
+            }
+        }
+    }
+}
+
+/// Expands chain arguments into registry entries: `kick/2` picks one
+/// sample, a bare folder name like `kick` takes the whole folder in order.
+pub fn expand_chain_names<'a>(
+    index: &'a [SampleEntry],
+    names: &[String],
+) -> Result<Vec<&'a SampleEntry>, String> {
+    let mut entries = Vec::new();
+    for name in names {
+        if let Some(entry) = index.iter().find(|e| e.name.as_ref() == name) {
+            entries.push(entry);
+            continue;
+        }
+        let prefix = format!("{name}/");
+        let before = entries.len();
+        entries.extend(index.iter().filter(|e| e.name.starts_with(&prefix)));
+        if entries.len() == before {
+            return Err(format!("no sample or folder named '{name}'"));
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    #[test]
+    fn samples_land_in_even_slots() {
+        let mono = SampleData::new(vec![0.25; 100], 1, 261.626);
+        let stereo = SampleData::new([0.5, -0.1].repeat(300), 2, 261.626);
+        let options = ChainOptions {
+            slots: Some(4),
+            slot_frames: Some(200),
+            normalize: Some(1.0),
+            ..ChainOptions::default()
+        };
+        let chain =
+            SampleChain::build(&[("a".into(), &mono), ("b".into(), &stereo)], &options).unwrap();
+        assert_eq!(chain.frames.len(), 4 * 200 * 2);
+        assert_eq!((chain.slots[1].start, chain.slots[1].frames), (200, 200));
+        assert!(chain.slots[1].truncated && !chain.slots[0].truncated);
+        // Mono spreads to both channels, each slot peaks at the target
+        assert_eq!(&chain.frames[..2], &[1.0, 1.0]);
+        assert_eq!(chain.frames[200 * 2], 1.0);
+        assert!((chain.frames[200 * 2 + 1] + 0.2).abs() < 1e-6);
+        // Silence after the short sample and in the unused slots
+        assert!(chain.frames[100 * 2..200 * 2].iter().all(|&s| s == 0.0));
+        assert!(chain.frames[400 * 2..].iter().all(|&s| s == 0.0));
+
+        let cue = chain.cue_sheet("chain.wav", 100);
+        assert!(cue.contains("FILE \"chain.wav\" WAVE"));
+        assert!(cue.contains("TITLE \"b\"\n    REM FRAMES 200 200\n    INDEX 01 00:02:00"));
+        assert!(SampleChain::build(
+            &[("a".into(), &mono)],
+            &ChainOptions {
+                slots: Some(0),
+                ..options
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn folder_names_expand_to_every_sample() {
+        let entry = |name: &str| SampleEntry {
+            name: Arc::from(name),
+            path: Arc::new(PathBuf::new()),
+        };
+        let index = [entry("kick/0"), entry("kick/1"), entry("snare/0")];
+        let names = ["snare/0".to_string(), "kick".to_string()];
+        let picked: Vec<_> = expand_chain_names(&index, &names)
+            .unwrap()
+            .iter()
+            .map(|e| e.name.to_string())
+            .collect();
+        assert_eq!(picked, ["snare/0", "kick/0", "kick/1"]);
+        assert!(expand_chain_names(&index, &["hat".to_string()]).is_err());
+    }
+}
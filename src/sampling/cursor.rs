@@ -25,6 +25,10 @@ pub struct Cursor {
     loop_start: f32,
     /// Loop end position in frames (relative to start_pos). 0 = no loop.
     loop_end: f32,
+    /// Crossfade length in frames ahead of loop_end.
+    loop_xfade: f32,
+    /// Whether the loop only holds until [`release_loop`](Self::release_loop).
+    sustain: bool,
 }
 
 impl Default for Cursor {
@@ -36,6 +40,8 @@ impl Default for Cursor {
             started: false,
             loop_start: 0.0,
             loop_end: 0.0,
+            loop_xfade: 0.0,
+            sustain: false,
         }
     }
 }
@@ -58,6 +64,8 @@ impl Cursor {
             started: false,
             loop_start: 0.0,
             loop_end: 0.0,
+            loop_xfade: 0.0,
+            sustain: false,
         }
     }
 
@@ -99,10 +107,55 @@ impl Cursor {
         self.length = length;
     }
 
-    /// Sets loop points (in frames relative to start_pos).
+    /// Sets loop points (in frames relative to start_pos). The loop runs
+    /// until the voice ends, as SoundFont loops do.
     pub fn set_loop(&mut self, loop_start: f32, loop_end: f32) {
         self.loop_start = loop_start;
         self.loop_end = loop_end;
+        self.sustain = false;
+    }
+
+    /// Sets a sustain loop over a fraction of the playback region.
+    ///
+    /// `begin`/`end` are normalized to the region (0.0-1.0). The last
+    /// `xfade` frames before the loop end blend into the material just
+    /// before the loop start, so the wrap is seamless. The crossfade is
+    /// limited to the loop length and to the frames available before it.
+    pub fn set_loop_region(&mut self, begin: f32, end: f32, xfade: f32) {
+        let (start, length) = Self::compute_range(1, begin, end);
+        let (lo, hi) = (start * self.length, (start + length) * self.length);
+        if hi - lo < 1.0 {
+            self.set_loop(0.0, 0.0);
+            return;
+        }
+        self.set_loop(lo, hi);
+        self.loop_xfade = xfade.clamp(0.0, (hi - lo).min(lo));
+        self.sustain = true;
+    }
+
+    /// Leaves a sustain loop set with [`set_loop_region`](Self::set_loop_region):
+    /// playback continues to the end of the region. Loops set with
+    /// [`set_loop`](Self::set_loop) keep running.
+    pub fn release_loop(&mut self) {
+        if self.sustain {
+            self.loop_end = 0.0;
+            self.loop_xfade = 0.0;
+        }
+    }
+
+    /// Crossfade partner near the loop end, as (absolute frame, weight).
+    ///
+    /// The partner sits one loop length back, so at the wrap point the
+    /// output already equals the loop start.
+    #[inline]
+    pub fn loop_xfade(&self) -> Option<(f32, f32)> {
+        let fade_start = self.loop_end - self.loop_xfade;
+        if self.loop_xfade <= 0.0 || self.pos < fade_start || self.pos >= self.loop_end {
+            return None;
+        }
+        let partner = self.pos - (self.loop_end - self.loop_start);
+        let weight = (self.pos - fade_start) / self.loop_xfade;
+        Some((self.start_pos + partner.max(0.0), weight))
     }
 
     /// Advances the cursor by the given speed (frames per sample).
@@ -200,6 +253,52 @@ impl Cursor {
 mod tests {
     use super::*;
 
+    #[test]
+    fn loop_region_sustains_crossfades_and_releases() {
+        let mut c = Cursor::new(1000, 0.0, 0.5);
+        c.set_loop_region(0.2, 0.8, 50.0);
+        assert_eq!((c.loop_start, c.loop_end), (100.0, 400.0));
+        for _ in 0..2000 {
+            c.advance(1.0);
+            assert!(!c.is_done());
+            assert!(c.pos < 400.0);
+        }
+
+        c.pos = 375.0;
+        let (partner, weight) = c.loop_xfade().unwrap();
+        assert_eq!(partner, 75.0);
+        assert_eq!(weight, 0.5);
+        c.pos = 200.0;
+        assert!(c.loop_xfade().is_none());
+
+        c.release_loop();
+        for _ in 0..300 {
+            c.advance(1.0);
+        }
+        assert!(c.is_done());
+    }
+
+    #[test]
+    fn fixed_loops_outlast_the_release() {
+        let mut c = Cursor::new(1000, 0.0, 1.0);
+        c.set_loop(100.0, 400.0);
+        c.release_loop();
+        for _ in 0..2000 {
+            c.advance(1.0);
+        }
+        assert!(c.is_looping());
+        assert!(!c.is_done());
+    }
+
+    #[test]
+    fn loop_xfade_limited_by_material_before_loop() {
+        let mut c = Cursor::new(1000, 0.0, 1.0);
+        c.set_loop_region(0.01, 0.5, 400.0);
+        assert_eq!(c.loop_xfade, 10.0);
+        c.set_loop_region(0.5, 0.5, 0.0);
+        assert!(!c.is_looping());
+    }
+
     #[test]
     fn new_full_range() {
         let c = Cursor::new(1000, 0.0, 1.0);
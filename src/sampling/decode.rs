@@ -1,7 +1,9 @@
 //! Audio sample loading and directory scanning.
 //!
 //! Handles discovery and decoding of audio files into the engine's sample pool.
-//! Supports common audio formats via Symphonia: WAV, MP3, OGG, FLAC, AAC, M4A.
+//! Supports common audio formats via Symphonia: WAV, MP3, OGG, FLAC, AAC, M4A,
+//! plus `.wt` wavetables. Wavetables are recognised on load (see
+//! [`wavetable`](super::wavetable)) and keep their cycle length.
 //!
 //! # Directory Structure
 //!
@@ -36,8 +38,12 @@ use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+use super::pitch::detect_pitch;
 use super::registry::SampleData;
 use super::sample::SampleEntry;
+use super::slices::SliceMap;
+use super::wavetable::{parse_wt, wav_cycle_marker};
+use crate::dsp::{resample, ResampleQuality};
 
 /// Default base frequency assigned to loaded samples (C2 = 65.406 Hz).
 ///
@@ -45,16 +51,42 @@ use super::sample::SampleEntry;
 /// Used for pitch-shifting calculations during playback.
 const DEFAULT_BASE_FREQ: f32 = 65.406;
 
+/// Conversion and analysis run on a sample after decoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Estimate the fundamental and store it in [`SampleData::pitch`].
+    pub detect_pitch: bool,
+    /// Interpolation for files at another rate than the engine's.
+    pub resample: ResampleQuality,
+}
+
 /// Supported audio file extensions.
-const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "ogg", "flac", "aac", "m4a"];
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "ogg", "flac", "aac", "m4a", "wt"];
 
 /// Checks if a file path has a supported audio extension.
 fn is_audio_file(path: &Path) -> bool {
-    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
-        AUDIO_EXTENSIONS
-            .iter()
-            .any(|ext| e.eq_ignore_ascii_case(ext))
-    })
+    AUDIO_EXTENSIONS.iter().any(|ext| has_extension(path, ext))
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+}
+
+/// Cycle length of a wavetable `.wav` that says so in a `clm ` chunk.
+fn wav_marker(path: &Path) -> Option<u32> {
+    let file = File::open(path).ok()?;
+    wav_cycle_marker(std::io::BufReader::new(file))
+}
+
+/// Decodes a `.wt` wavetable (mono, never resampled).
+fn decode_wt(path: &Path) -> Result<SampleData, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to open file: {e}"))?;
+    let wt = parse_wt(&bytes)?;
+    let mut data = SampleData::new(wt.frames, 1, DEFAULT_BASE_FREQ);
+    data.wt_cycle = Some(wt.cycle);
+    Ok(data)
 }
 
 /// Scans a directory for audio samples without loading audio data.
@@ -138,6 +170,20 @@ pub fn scan_samples_dir(dir: &Path) -> Vec<SampleEntry> {
 /// - No audio track is found
 /// - Decoding fails completely (partial decode errors are skipped)
 pub fn decode_sample_file(path: &Path, target_sr: f32) -> Result<SampleData, String> {
+    decode_sample_file_with(path, target_sr, DecodeOptions::default())
+}
+
+/// Like [`decode_sample_file`], then runs the analysis asked for in `options`.
+pub fn decode_sample_file_with(
+    path: &Path,
+    target_sr: f32,
+    options: DecodeOptions,
+) -> Result<SampleData, String> {
+    if has_extension(path, "wt") {
+        return decode_wt(path);
+    }
+    let wt_cycle = wav_marker(path);
+
     let file = File::open(path).map_err(|e| format!("Failed to open file: {e}"))?;
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
@@ -208,13 +254,28 @@ pub fn decode_sample_file(path: &Path, target_sr: f32) -> Result<SampleData, Str
         return Err("No samples decoded".to_string());
     }
 
-    let resampled = if (sample_rate - target_sr).abs() > 1.0 {
-        resample_linear(&samples, channels as usize, sample_rate, target_sr)
+    // Wavetables keep their frames so cycles stay whole
+    let rate = if wt_cycle.is_none() && (sample_rate - target_sr).abs() > 1.0 {
+        samples = resample(
+            &samples,
+            channels as usize,
+            sample_rate,
+            target_sr,
+            options.resample,
+        );
+        target_sr
     } else {
-        samples
+        sample_rate
     };
 
-    Ok(SampleData::new(resampled, channels, DEFAULT_BASE_FREQ))
+    let mut data = SampleData::new(samples, channels, DEFAULT_BASE_FREQ);
+    data.wt_cycle = wt_cycle;
+    data.sample_rate = Some(rate);
+    data.slices = SliceMap::compute(&data.frames, channels);
+    if options.detect_pitch {
+        data.pitch = detect_pitch(&data.frames, channels, rate);
+    }
+    Ok(data)
 }
 
 /// Maximum frames to decode for head preloading (~93ms at 44.1kHz).
@@ -225,7 +286,21 @@ pub const HEAD_FRAMES: usize = 4096;
 /// If the file is shorter than HEAD_FRAMES, the entire file is decoded.
 /// Used for head-preloading: the attack portion lives in RAM so playback
 /// can start instantly while the rest streams from disk on demand.
+/// Marked wavetables are small and decoded whole.
 pub fn decode_sample_head(path: &Path, target_sr: f32) -> Result<SampleData, String> {
+    decode_sample_head_with(path, target_sr, DecodeOptions::default())
+}
+
+/// Like [`decode_sample_head`], resampling with `options.resample` so the
+/// head lines up with the full decode it is later swapped for.
+pub fn decode_sample_head_with(
+    path: &Path,
+    target_sr: f32,
+    options: DecodeOptions,
+) -> Result<SampleData, String> {
+    if has_extension(path, "wt") || wav_marker(path).is_some() {
+        return decode_sample_file_with(path, target_sr, options);
+    }
     let file = File::open(path).map_err(|e| format!("Failed to open file: {e}"))?;
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
@@ -308,17 +383,22 @@ pub fn decode_sample_head(path: &Path, target_sr: f32) -> Result<SampleData, Str
         samples.truncate(max_interleaved);
     }
 
-    let resample = (sample_rate - target_sr).abs() > 1.0;
-    let resampled = if resample {
-        resample_linear(&samples, channels as usize, sample_rate, target_sr)
-    } else {
-        samples
-    };
+    let converted = (sample_rate - target_sr).abs() > 1.0;
+    if converted {
+        samples = resample(
+            &samples,
+            channels as usize,
+            sample_rate,
+            target_sr,
+            options.resample,
+        );
+    }
+    let rate = if converted { target_sr } else { sample_rate };
 
-    let decoded_frames = (resampled.len() / channels as usize) as u32;
+    let decoded_frames = (samples.len() / channels as usize) as u32;
     let total_frames = match file_n_frames {
         Some(n) => {
-            let n = if resample {
+            let n = if converted {
                 (n as f32 * target_sr / sample_rate) as u32
             } else {
                 n as u32
@@ -328,41 +408,7 @@ pub fn decode_sample_head(path: &Path, target_sr: f32) -> Result<SampleData, Str
         None => decoded_frames,
     };
 
-    Ok(SampleData::new_head(
-        resampled,
-        channels,
-        DEFAULT_BASE_FREQ,
-        total_frames,
-    ))
-}
-
-/// Resamples interleaved audio using linear interpolation.
-///
-/// Simple but fast resampling suitable for non-critical applications.
-/// For higher quality, consider using a dedicated resampling library like rubato.
-pub(crate) fn resample_linear(
-    samples: &[f32],
-    channels: usize,
-    from_sr: f32,
-    to_sr: f32,
-) -> Vec<f32> {
-    let ratio = to_sr / from_sr;
-    let in_frames = samples.len() / channels;
-    let out_frames = (in_frames as f32 * ratio) as usize;
-    let mut output = vec![0.0; out_frames * channels];
-
-    for out_frame in 0..out_frames {
-        let in_pos = out_frame as f32 / ratio;
-        let in_frame = (in_pos as usize).min(in_frames.saturating_sub(1));
-        let next_frame = (in_frame + 1).min(in_frames.saturating_sub(1));
-        let frac = in_pos - in_frame as f32;
-
-        for ch in 0..channels {
-            let s0 = samples[in_frame * channels + ch];
-            let s1 = samples[next_frame * channels + ch];
-            output[out_frame * channels + ch] = s0 + frac * (s1 - s0);
-        }
-    }
-
-    output
+    let mut data = SampleData::new_head(samples, channels, DEFAULT_BASE_FREQ, total_frames);
+    data.sample_rate = Some(rate);
+    Ok(data)
 }
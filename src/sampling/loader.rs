@@ -9,7 +9,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 
-use super::decode::decode_sample_file;
+use super::decode::decode_sample_file_with;
 use super::registry::SampleRegistry;
 
 /// Request to load a sample from disk.
@@ -97,7 +97,8 @@ fn loader_thread(rx: Receiver<LoadRequest>, registry: Arc<SampleRegistry>) {
 
         pending.insert(Arc::clone(&request.name));
 
-        match decode_sample_file(request.path.as_ref(), request.target_sr) {
+        let options = registry.decode_options();
+        match decode_sample_file_with(request.path.as_ref(), request.target_sr, options) {
             Ok(data) => {
                 registry.insert(request.name.as_ref().to_string(), Arc::new(data));
             }
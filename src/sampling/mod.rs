@@ -1,33 +1,50 @@
 //! Sample storage, loading, and playback.
 
+#[cfg(feature = "native")]
+mod chain;
 mod cursor;
 #[cfg(feature = "native")]
 mod decode;
 #[cfg(feature = "native")]
 mod loader;
+mod overview;
+#[cfg(feature = "native")]
+mod pitch;
 #[cfg(feature = "native")]
 mod registry;
 mod sample;
 #[cfg(feature = "native")]
+mod slices;
+#[cfg(feature = "native")]
 mod source;
 #[cfg(feature = "native")]
 pub mod stretch;
+#[cfg(feature = "native")]
+pub mod wavetable;
 
 pub use cursor::Cursor;
+pub use overview::{WaveformOverview, OVERVIEW_POINTS};
 pub use sample::SampleEntry;
 #[cfg(not(feature = "native"))]
 pub use sample::{FileSource, SampleInfo, SamplePool};
 pub use sample::{WebSampleInfo, WebSampleSource};
 
-#[cfg(feature = "soundfont")]
-pub(crate) use decode::resample_linear;
 #[cfg(feature = "native")]
-pub use decode::{decode_sample_file, decode_sample_head, scan_samples_dir, HEAD_FRAMES};
+pub use chain::{expand_chain_names, ChainOptions, ChainSlot, SampleChain};
+#[cfg(feature = "native")]
+pub use decode::{
+    decode_sample_file, decode_sample_file_with, decode_sample_head, decode_sample_head_with,
+    scan_samples_dir, DecodeOptions, HEAD_FRAMES,
+};
 #[cfg(feature = "native")]
 pub use loader::SampleLoader;
 #[cfg(feature = "native")]
+pub use pitch::detect_pitch;
+#[cfg(feature = "native")]
 pub use registry::{SampleData, SampleRegistry};
 #[cfg(feature = "native")]
+pub use slices::SliceMap;
+#[cfg(feature = "native")]
 pub use source::RegistrySample;
 #[cfg(feature = "native")]
 pub use stretch::StretchState;
@@ -0,0 +1,109 @@
+//! Downsampled min/max peaks for drawing sample waveforms.
+//!
+//! Computed once when a sample is loaded so UIs can draw overviews without
+//! fetching raw PCM.
+
+/// Default number of peak points per channel.
+pub const OVERVIEW_POINTS: usize = 1000;
+
+/// Min/max peaks per channel over evenly sized frame buckets.
+#[derive(Clone, Debug, Default)]
+pub struct WaveformOverview {
+    /// Buckets per channel (fewer than requested for very short samples).
+    pub points: usize,
+    pub channels: u8,
+    /// `[min, max]` pairs, channel-major: all of channel 0, then channel 1.
+    pub peaks: Box<[[f32; 2]]>,
+}
+
+impl WaveformOverview {
+    /// Builds an overview of interleaved `frames` with up to `points` buckets.
+    pub fn compute(frames: &[f32], channels: u8, points: usize) -> Self {
+        let nch = channels.max(1) as usize;
+        let frame_count = frames.len() / nch;
+        let points = points.min(frame_count);
+        let mut peaks = vec![[0.0f32; 2]; points * nch];
+
+        for p in 0..points {
+            let start = p * frame_count / points;
+            let end = ((p + 1) * frame_count / points).max(start + 1);
+            for ch in 0..nch {
+                let mut lo = f32::MAX;
+                let mut hi = f32::MIN;
+                for f in start..end {
+                    let s = frames[f * nch + ch];
+                    lo = lo.min(s);
+                    hi = hi.max(s);
+                }
+                peaks[ch * points + p] = [lo, hi];
+            }
+        }
+
+        Self {
+            points,
+            channels,
+            peaks: peaks.into_boxed_slice(),
+        }
+    }
+
+    /// Merges buckets down to at most `points` per channel.
+    pub fn reduce(&self, points: usize) -> Self {
+        let points = points.min(self.points);
+        let nch = self.channels.max(1) as usize;
+        let mut peaks = vec![[0.0f32; 2]; points * nch];
+        for ch in 0..nch {
+            let src = self.channel(ch);
+            for p in 0..points {
+                let start = p * src.len() / points;
+                let end = ((p + 1) * src.len() / points).max(start + 1);
+                peaks[ch * points + p] = src[start..end]
+                    .iter()
+                    .fold([f32::MAX, f32::MIN], |[lo, hi], &[a, b]| {
+                        [lo.min(a), hi.max(b)]
+                    });
+            }
+        }
+        Self {
+            points,
+            channels: self.channels,
+            peaks: peaks.into_boxed_slice(),
+        }
+    }
+
+    /// Peaks for one channel (clamped to the last channel).
+    pub fn channel(&self, ch: usize) -> &[[f32; 2]] {
+        if self.points == 0 {
+            return &[];
+        }
+        let ch = ch.min(self.channels.max(1) as usize - 1);
+        &self.peaks[ch * self.points..(ch + 1) * self.points]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_track_min_and_max_per_channel() {
+        // Stereo: left ramps up, right is its negation
+        let frames: Vec<f32> = (0..8).flat_map(|i| [i as f32, -(i as f32)]).collect();
+        let ov = WaveformOverview::compute(&frames, 2, 4);
+        assert_eq!(ov.points, 4);
+        assert_eq!(
+            ov.channel(0),
+            &[[0.0, 1.0], [2.0, 3.0], [4.0, 5.0], [6.0, 7.0]]
+        );
+        assert_eq!(ov.channel(1)[3], [-7.0, -6.0]);
+
+        let halved = ov.reduce(2);
+        assert_eq!(halved.channel(0), &[[0.0, 3.0], [4.0, 7.0]]);
+    }
+
+    #[test]
+    fn short_samples_get_one_point_per_frame() {
+        let ov = WaveformOverview::compute(&[0.5, -0.5], 1, OVERVIEW_POINTS);
+        assert_eq!(ov.points, 2);
+        assert!(WaveformOverview::compute(&[], 1, 10).channel(0).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+//! Fundamental frequency estimation for loaded samples.
+//!
+//! Runs YIN (de Cheveigné & Kawahara, 2002) on one window of the mono
+//! mixdown, just past the attack. Unpitched material (drums, noise) has no
+//! dip below the threshold and yields `None`.
+
+/// Frames compared per lag.
+const WINDOW: usize = 2048;
+const MIN_FREQ: f32 = 40.0;
+const MAX_FREQ: f32 = 2000.0;
+/// Absolute threshold on the cumulative mean normalized difference.
+const THRESHOLD: f32 = 0.15;
+/// Transients are skipped by starting the window at most this far in.
+const SKIP_SECS: f32 = 0.05;
+/// Mean square below which the window counts as silence.
+const SILENCE: f32 = 1e-8;
+
+/// Estimates the fundamental of interleaved `frames`, in Hz.
+pub fn detect_pitch(frames: &[f32], channels: u8, sr: f32) -> Option<f32> {
+    let channels = usize::from(channels.max(1));
+    let frame_count = frames.len() / channels;
+    let max_tau = ((sr / MIN_FREQ) as usize).min(frame_count / 2);
+    let min_tau = ((sr / MAX_FREQ) as usize).max(2);
+    if max_tau <= min_tau + 1 {
+        return None;
+    }
+    let window = WINDOW.max(max_tau).min(frame_count - max_tau);
+    let start = ((frame_count - window - max_tau) / 4).min((SKIP_SECS * sr) as usize);
+
+    let mono: Vec<f32> = frames[start * channels..(start + window + max_tau) * channels]
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    if mono[..window].iter().map(|x| x * x).sum::<f32>() / (window as f32) < SILENCE {
+        return None;
+    }
+
+    // Cumulative mean normalized difference, d'(0) = 1
+    let mut cmnd = vec![1.0_f32; max_tau + 1];
+    let mut running = 0.0;
+    for tau in 1..=max_tau {
+        let d: f32 = (0..window).map(|i| (mono[i] - mono[i + tau]).powi(2)).sum();
+        running += d;
+        cmnd[tau] = if running > 0.0 {
+            d * tau as f32 / running
+        } else {
+            1.0
+        };
+    }
+
+    let mut tau = (min_tau..max_tau).find(|&t| cmnd[t] < THRESHOLD)?;
+    while tau + 1 < max_tau && cmnd[tau + 1] < cmnd[tau] {
+        tau += 1;
+    }
+
+    // Parabolic interpolation around the dip
+    let (a, b, c) = (cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+    let denom = a - 2.0 * b + c;
+    let offset = if denom.abs() > f32::EPSILON {
+        (0.5 * (a - c) / denom).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    Some(sr / (tau as f32 + offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::sampling::{SampleData, SampleEntry};
+    #[cfg(feature = "native")]
+    use crate::Engine;
+    use std::f32::consts::TAU;
+    #[cfg(feature = "native")]
+    use std::sync::Arc;
+
+    #[test]
+    fn finds_the_fundamental_of_tones_and_not_of_noise() {
+        let sr = 48000.0;
+        let sine: Vec<f32> = (0..sr as usize)
+            .map(|i| (i as f32 * 220.0 * TAU / sr).sin())
+            .collect();
+        let pitch = detect_pitch(&sine, 1, sr).unwrap();
+        assert!((pitch - 220.0).abs() < 0.5, "{pitch}");
+
+        // Stereo saw with strong harmonics
+        let saw: Vec<f32> = (0..sr as usize)
+            .flat_map(|i| {
+                let x = (i as f32 * 110.0 / sr).fract() * 2.0 - 1.0;
+                [x, x * 0.5]
+            })
+            .collect();
+        let pitch = detect_pitch(&saw, 2, sr).unwrap();
+        assert!((pitch - 110.0).abs() < 0.5, "{pitch}");
+
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..sr as usize)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+            })
+            .collect();
+        assert_eq!(detect_pitch(&noise, 1, sr), None);
+        assert_eq!(detect_pitch(&[0.0; 4800], 1, sr), None);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn detected_sample_pitch_makes_notes_absolute() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let mut data = SampleData::new(vec![0.1; 48000], 1, 65.406);
+        data.pitch = Some(440.0);
+        engine
+            .sample_registry
+            .insert("tone/0".to_string(), Arc::new(data));
+        engine.sample_index.push(SampleEntry {
+            name: Arc::from("tone/0"),
+            path: Arc::new(std::path::PathBuf::new()),
+        });
+
+        let tuning =
+            |engine: &Engine, i: usize| engine.voices[i].registry_sample.as_ref().unwrap().tuning;
+        engine.evaluate("/s/tone/note/69");
+        assert!((tuning(&engine, 0) * 440.0 - 261.626).abs() < 1e-3);
+        engine.evaluate("/s/tone");
+        assert_eq!(tuning(&engine, 1), 1.0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn samples_at_another_rate_keep_their_pitch() {
+        // Decoded for a 44.1 kHz device, then played on a 48 kHz engine
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let mut data = SampleData::new(vec![0.1; 44100], 1, 65.406);
+        data.sample_rate = Some(44100.0);
+        engine
+            .sample_registry
+            .insert("tone/0".to_string(), Arc::new(data));
+        engine.sample_index.push(SampleEntry {
+            name: Arc::from("tone/0"),
+            path: Arc::new(std::path::PathBuf::new()),
+        });
+
+        engine.evaluate("/s/tone");
+        let rs = engine.voices[0].registry_sample.as_ref().unwrap();
+        assert!((rs.tuning - 44100.0 / 48000.0).abs() < 1e-6);
+        // One second of audio fits two seconds at half speed
+        engine.evaluate("/s/tone/fit/2");
+        assert!((engine.voices[1].params.speed - 0.5).abs() < 1e-6);
+    }
+}
@@ -5,8 +5,14 @@
 
 use arc_swap::ArcSwap;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
 
+use super::decode::DecodeOptions;
+use super::overview::{WaveformOverview, OVERVIEW_POINTS};
+use super::slices::SliceMap;
+use crate::dsp::ResampleQuality;
+
 /// Immutable sample data that can be safely shared across threads.
 ///
 /// Once created, sample data never changes, making it safe to share
@@ -22,33 +28,61 @@ pub struct SampleData {
     pub frame_count: u32,
     /// Total frames in the original file (may differ from frame_count for head preloads).
     pub total_frames: u32,
+    /// Min/max peaks of the loaded frames, for waveform displays.
+    pub overview: WaveformOverview,
+    /// Detected fundamental in Hz, when decoded with pitch detection.
+    pub pitch: Option<f32>,
+    /// Energy curve for `slicemode/energy` (full decodes only).
+    pub slices: Option<SliceMap>,
+    /// Cycle length in frames when the file is a wavetable.
+    pub wt_cycle: Option<u32>,
+    /// Rate the frames are stored at; `None` plays them at the engine rate.
+    pub sample_rate: Option<f32>,
 }
 
 impl SampleData {
     /// Creates new sample data from decoded audio.
     pub fn new(samples: Vec<f32>, channels: u8, freq: f32) -> Self {
         let frame_count = (samples.len() / channels as usize) as u32;
+        let overview = WaveformOverview::compute(&samples, channels, OVERVIEW_POINTS);
         Self {
             frames: samples.into_boxed_slice(),
             channels,
             freq,
             frame_count,
             total_frames: frame_count,
+            overview,
+            pitch: None,
+            slices: None,
+            wt_cycle: None,
+            sample_rate: None,
         }
     }
 
     /// Creates sample data for a head preload where total_frames may exceed frame_count.
     pub fn new_head(samples: Vec<f32>, channels: u8, freq: f32, total_frames: u32) -> Self {
         let frame_count = (samples.len() / channels as usize) as u32;
+        let overview = WaveformOverview::compute(&samples, channels, OVERVIEW_POINTS);
         Self {
             frames: samples.into_boxed_slice(),
             channels,
             freq,
             frame_count,
             total_frames,
+            overview,
+            pitch: None,
+            slices: None,
+            wt_cycle: None,
+            sample_rate: None,
         }
     }
 
+    /// Playback speed that keeps the original pitch on an engine running at `sr`.
+    #[inline]
+    pub fn rate_ratio(&self, sr: f32) -> f32 {
+        self.sample_rate.map_or(1.0, |rate| rate / sr)
+    }
+
     /// Reads a sample at the given frame and channel with 4-tap cubic Hermite interpolation.
     #[inline]
     pub fn read_interpolated(&self, pos: f32, channel: usize) -> f32 {
@@ -84,6 +118,10 @@ impl SampleData {
 /// HashMap and atomically swap it in, while readers get a consistent snapshot.
 pub struct SampleRegistry {
     samples: ArcSwap<HashMap<String, Arc<SampleData>>>,
+    /// Pitch detection for samples decoded into this registry.
+    detect_pitch: AtomicBool,
+    /// [`ResampleQuality`] index for samples decoded into this registry.
+    resample: AtomicU8,
 }
 
 impl Default for SampleRegistry {
@@ -96,9 +134,27 @@ impl SampleRegistry {
     pub fn new() -> Self {
         Self {
             samples: ArcSwap::from_pointee(HashMap::new()),
+            detect_pitch: AtomicBool::new(false),
+            resample: AtomicU8::new(ResampleQuality::default().index()),
         }
     }
 
+    /// Options to decode samples for this registry with.
+    pub fn decode_options(&self) -> DecodeOptions {
+        DecodeOptions {
+            detect_pitch: self.detect_pitch.load(Ordering::Relaxed),
+            resample: ResampleQuality::from_index(self.resample.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Sets the options used by the loader (and preloads) from now on.
+    pub fn set_decode_options(&self, options: DecodeOptions) {
+        self.detect_pitch
+            .store(options.detect_pitch, Ordering::Relaxed);
+        self.resample
+            .store(options.resample.index(), Ordering::Relaxed);
+    }
+
     /// Gets a sample by name (lock-free).
     ///
     /// Returns a cloned `Arc` to the sample data, which can be held
@@ -7,6 +7,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use super::cursor::Cursor;
+#[cfg(not(feature = "native"))]
+use crate::dsp::{resample, ResampleQuality};
 
 /// Index entry for a discoverable sample file.
 ///
@@ -24,6 +26,17 @@ pub struct SampleEntry {
 #[derive(Default)]
 pub struct SamplePool {
     pub data: Vec<f32>,
+    pending: Option<PendingSample>,
+}
+
+/// A sample being assembled from chunks at the tail of the pool (WASM only).
+#[cfg(not(feature = "native"))]
+#[derive(Clone, Copy)]
+struct PendingSample {
+    offset: usize,
+    len: usize,
+    channels: u8,
+    freq: f32,
 }
 
 #[cfg(not(feature = "native"))]
@@ -32,7 +45,14 @@ impl SamplePool {
         Self::default()
     }
 
+    /// Appends a whole sample to the pool.
+    ///
+    /// Returns `None` while a chunked upload is in progress, since that
+    /// upload owns the tail of the pool until it is finished or aborted.
     pub fn add(&mut self, samples: &[f32], channels: u8, freq: f32) -> Option<SampleInfo> {
+        if self.pending.is_some() || channels == 0 {
+            return None;
+        }
         let frames = samples.len() / channels as usize;
         let offset = self.data.len();
         let info = SampleInfo {
@@ -44,6 +64,76 @@ impl SamplePool {
         self.data.extend_from_slice(samples);
         Some(info)
     }
+
+    /// Starts a chunked upload of `frames` frames. Discards any unfinished upload.
+    pub fn begin(&mut self, frames: u32, channels: u8, freq: f32) -> bool {
+        self.abort();
+        if channels == 0 || frames == 0 {
+            return false;
+        }
+        let len = frames as usize * channels as usize;
+        self.data.reserve_exact(len);
+        self.pending = Some(PendingSample {
+            offset: self.data.len(),
+            len,
+            channels,
+            freq,
+        });
+        true
+    }
+
+    /// Appends interleaved samples to the pending upload.
+    ///
+    /// Returns the number of samples accepted; anything past the declared
+    /// length is ignored.
+    pub fn append(&mut self, chunk: &[f32]) -> usize {
+        let Some(p) = self.pending else {
+            return 0;
+        };
+        let written = self.data.len() - p.offset;
+        let n = chunk.len().min(p.len - written);
+        self.data.extend_from_slice(&chunk[..n]);
+        n
+    }
+
+    /// Completes the pending upload. Missing tail samples are zero-filled.
+    pub fn finish(&mut self) -> Option<SampleInfo> {
+        let p = self.pending.take()?;
+        self.data.resize(p.offset + p.len, 0.0);
+        Some(SampleInfo {
+            offset: p.offset,
+            frames: (p.len / p.channels as usize) as u32,
+            channels: p.channels,
+            freq: p.freq,
+        })
+    }
+
+    /// Drops a pending upload and releases the space it used.
+    pub fn abort(&mut self) {
+        if let Some(p) = self.pending.take() {
+            self.data.truncate(p.offset);
+        }
+    }
+
+    /// Converts the last added sample from `from_sr` to `to_sr` in place.
+    pub fn resample_last(
+        &mut self,
+        info: SampleInfo,
+        from_sr: f32,
+        to_sr: f32,
+        quality: ResampleQuality,
+    ) -> SampleInfo {
+        if (from_sr - to_sr).abs() <= 1.0 || info.offset > self.data.len() {
+            return info;
+        }
+        let pcm = self.data.split_off(info.offset);
+        let converted = resample(&pcm, info.channels as usize, from_sr, to_sr, quality);
+        self.data.extend_from_slice(&converted);
+        SampleInfo {
+            frames: (converted.len() / info.channels.max(1) as usize) as u32,
+            ..info
+        }
+    }
 }
 
 /// Metadata for a sample stored in the pool (WASM only).
@@ -85,7 +175,14 @@ impl FileSource {
 
         let s0 = pool.get(idx0).copied().unwrap_or(0.0);
         let s1 = pool.get(idx1).copied().unwrap_or(0.0);
-        s0 + frac * (s1 - s0)
+        let s = s0 + frac * (s1 - s0);
+        match self.cursor.loop_xfade() {
+            Some((pos, w)) => {
+                let idx = offset + pos as usize * channels + ch;
+                s + w * (pool.get(idx).copied().unwrap_or(0.0) - s)
+            }
+            None => s,
+        }
     }
 
     #[inline]
@@ -101,6 +198,19 @@ impl FileSource {
     pub fn update_range(&mut self, frames: u32, begin: Option<f32>, end: Option<f32>) {
         self.cursor.update_range(frames, begin, end);
     }
+
+    pub fn set_loop_region(&mut self, begin: f32, end: f32, xfade: f32) {
+        self.cursor.set_loop_region(begin, end, xfade);
+    }
+
+    pub fn release_loop(&mut self) {
+        self.cursor.release_loop();
+    }
+
+    #[inline]
+    pub fn is_looping(&self) -> bool {
+        self.cursor.is_looping()
+    }
 }
 
 /// Sample info for WebSampleSource (used on all platforms for web sample playback).
@@ -136,7 +246,14 @@ impl WebSampleSource {
         let ch = channel.min(self.info.channels as usize - 1);
         let current = self.cursor.current_frame();
         let idx = self.info.offset + current * self.info.channels as usize + ch;
-        pcm_buffer.get(idx).copied().unwrap_or(0.0)
+        let s = pcm_buffer.get(idx).copied().unwrap_or(0.0);
+        match self.cursor.loop_xfade() {
+            Some((pos, w)) => {
+                let idx = self.info.offset + pos as usize * self.info.channels as usize + ch;
+                s + w * (pcm_buffer.get(idx).copied().unwrap_or(0.0) - s)
+            }
+            None => s,
+        }
     }
 
     #[inline]
@@ -149,9 +266,42 @@ impl WebSampleSource {
         self.cursor.is_done()
     }
 
+    pub fn set_loop_region(&mut self, begin: f32, end: f32, xfade: f32) {
+        self.cursor.set_loop_region(begin, end, xfade);
+    }
+
+    pub fn release_loop(&mut self) {
+        self.cursor.release_loop();
+    }
+
+    #[inline]
+    pub fn is_looping(&self) -> bool {
+        self.cursor.is_looping()
+    }
+
     /// Returns the total frame count.
     #[inline]
     pub fn frame_count(&self) -> f32 {
         self.cursor.length()
     }
 }
+
+#[cfg(all(test, not(feature = "native")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_is_refused_while_a_chunked_upload_is_open() {
+        let mut pool = SamplePool::new();
+        assert!(pool.begin(4, 1, 440.0));
+        assert_eq!(pool.append(&[1.0, 2.0]), 2);
+        assert!(pool.add(&[9.0; 3], 1, 440.0).is_none());
+        assert_eq!(pool.append(&[3.0, 4.0]), 2);
+        let info = pool.finish().unwrap();
+        assert_eq!((info.offset, info.frames), (0, 4));
+        assert_eq!(pool.data, [1.0, 2.0, 3.0, 4.0]);
+
+        let next = pool.add(&[5.0, 6.0], 2, 440.0).unwrap();
+        assert_eq!((next.offset, next.frames), (4, 1));
+    }
+}
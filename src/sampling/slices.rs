@@ -0,0 +1,156 @@
+//! Energy-based slice points for loaded samples.
+//!
+//! Built once on decode: the spectral flux of the mono mixdown, summed hop
+//! by hop into a cumulative curve. `slicemode/energy` cuts where that curve
+//! (blended with plain duration) crosses equal steps, so busy passages get
+//! more, shorter slices and sparse ones fewer, longer ones. Onsets are not
+//! needed, which suits ambient material that has none to find.
+
+use crate::dsp::fft;
+use std::f32::consts::TAU;
+
+const WINDOW: usize = 1024;
+const HOP: usize = WINDOW / 2;
+/// Share of the cut placement driven by energy; the rest is duration, so
+/// silent stretches still get some slices.
+const ENERGY_WEIGHT: f32 = 0.75;
+/// Total flux below which the sample counts as silent.
+const SILENCE: f32 = 1e-6;
+
+/// Cumulative spectral flux of a sample, sampled every [`HOP`] frames.
+#[derive(Clone, Debug)]
+pub struct SliceMap {
+    frame_count: u32,
+    /// Normalised flux up to each hop boundary: starts at 0, ends at 1.
+    cumulative: Box<[f32]>,
+}
+
+impl SliceMap {
+    /// Analyses interleaved `frames`; `None` for very short or silent ones.
+    pub fn compute(frames: &[f32], channels: u8) -> Option<Self> {
+        let nch = usize::from(channels.max(1));
+        let frame_count = frames.len() / nch;
+        if frame_count < WINDOW {
+            return None;
+        }
+        let window: Vec<f32> = (0..WINDOW)
+            .map(|i| 0.5 - 0.5 * (TAU * i as f32 / WINDOW as f32).cos())
+            .collect();
+        let mut re = vec![0.0f32; WINDOW];
+        let mut im = vec![0.0f32; WINDOW];
+        let mut prev = vec![0.0f32; WINDOW / 2];
+        let hops = frame_count.div_ceil(HOP);
+        let mut cumulative = Vec::with_capacity(hops + 1);
+        let mut total = 0.0f32;
+        cumulative.push(0.0);
+
+        for h in 0..hops {
+            let start = h * HOP;
+            for (i, (r, w)) in re.iter_mut().zip(&window).enumerate() {
+                let f = start + i;
+                *r = if f < frame_count {
+                    frames[f * nch..(f + 1) * nch].iter().sum::<f32>() / nch as f32 * w
+                } else {
+                    0.0
+                };
+            }
+            im.fill(0.0);
+            fft::fft(&mut re, &mut im, false);
+            let mut flux = 0.0;
+            for (bin, p) in prev.iter_mut().enumerate() {
+                let mag = (re[bin] * re[bin] + im[bin] * im[bin]).sqrt();
+                flux += (mag - *p).max(0.0);
+                *p = mag;
+            }
+            total += flux;
+            cumulative.push(total);
+        }
+        if total < SILENCE {
+            return None;
+        }
+        for c in &mut cumulative {
+            *c /= total;
+        }
+        Some(Self {
+            frame_count: frame_count as u32,
+            cumulative: cumulative.into_boxed_slice(),
+        })
+    }
+
+    /// Begin and end (0-1) of slice `index` out of `count`.
+    pub fn slice(&self, count: u32, index: u32) -> (f32, f32) {
+        let count = count.max(1);
+        let index = index.min(count - 1);
+        let cut = |k: u32| match k {
+            0 => 0.0,
+            k if k >= count => 1.0,
+            k => self.position(k as f32 / count as f32),
+        };
+        (cut(index), cut(index + 1))
+    }
+
+    /// Position (0-1) where the blended energy/duration curve reaches `target`.
+    fn position(&self, target: f32) -> f32 {
+        let n = self.frame_count as f32;
+        let x = |i: usize| ((i * HOP) as f32).min(n) / n;
+        let w = |i: usize| (1.0 - ENERGY_WEIGHT) * x(i) + ENERGY_WEIGHT * self.cumulative[i];
+        // The curve never falls: binary search for the first point at or
+        // past target, which brackets it with the one before
+        let (mut lo, mut hi) = (1, self.cumulative.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if w(mid) < target {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let i = lo;
+        let (w0, w1) = (w(i - 1), w(i));
+        let t = if w1 > w0 {
+            ((target - w0) / (w1 - w0)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        x(i - 1) + (x(i) - x(i - 1)) * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: &mut u32) -> impl Iterator<Item = f32> + '_ {
+        (0..len).map(move |_| {
+            *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (*seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+        })
+    }
+
+    #[test]
+    fn steady_material_slices_evenly() {
+        let frames: Vec<f32> = noise(48000, &mut 1).collect();
+        let map = SliceMap::compute(&frames, 1).unwrap();
+        for k in 0..4 {
+            let (b, e) = map.slice(4, k);
+            assert!((b - k as f32 * 0.25).abs() < 0.03, "{k}: {b}");
+            assert!((e - (k + 1) as f32 * 0.25).abs() < 0.03, "{k}: {e}");
+        }
+    }
+
+    #[test]
+    fn busy_passages_get_more_slices() {
+        // Three quarters of silence, then a burst of noise
+        let mut seed = 7;
+        let mut frames = vec![0.0f32; 36000];
+        frames.extend(noise(12000, &mut seed));
+        let map = SliceMap::compute(&frames, 1).unwrap();
+        let (b, e) = map.slice(4, 0);
+        assert_eq!(b, 0.0);
+        assert!(e > 0.75, "first cut at {e}");
+        // Slices tile the sample in order
+        let cuts: Vec<f32> = (0..4).map(|k| map.slice(4, k).1).collect();
+        assert!(cuts.windows(2).all(|w| w[0] < w[1]) && cuts[3] == 1.0);
+        assert!(SliceMap::compute(&[0.0; 4800], 1).is_none());
+    }
+}
@@ -17,6 +17,9 @@ pub struct RegistrySample {
     pub root_freq: f32,
     pub attenuation: f32,
     pub scale_tuning: f32,
+    /// Playback speed factor for the sample's detected pitch and stored rate
+    /// (1 = relative to middle C at the engine rate).
+    pub tuning: f32,
     cursor: Cursor,
 }
 
@@ -31,6 +34,7 @@ impl RegistrySample {
             root_freq,
             attenuation: 1.0,
             scale_tuning: 1.0,
+            tuning: 1.0,
             cursor,
         }
     }
@@ -45,6 +49,9 @@ impl RegistrySample {
     pub fn upgrade(&mut self, new_data: Arc<SampleData>) {
         let old_fc = self.data.frame_count;
         let new_fc = new_data.frame_count;
+        if let (Some(old), Some(new)) = (self.data.sample_rate, new_data.sample_rate) {
+            self.tuning *= new / old;
+        }
         self.data = new_data;
         if new_fc != old_fc {
             self.cursor.upgrade_frame_count(old_fc, new_fc);
@@ -61,8 +68,13 @@ impl RegistrySample {
     /// Reads the sample value at current position with linear interpolation.
     #[inline]
     pub fn read(&self, channel: usize) -> f32 {
-        self.data
-            .read_interpolated(self.cursor.frame_position(), channel)
+        let s = self
+            .data
+            .read_interpolated(self.cursor.frame_position(), channel);
+        match self.cursor.loop_xfade() {
+            Some((pos, w)) => s + w * (self.data.read_interpolated(pos, channel) - s),
+            None => s,
+        }
     }
 
     /// Advances the cursor by the given speed (frames per sample).
@@ -76,6 +88,17 @@ impl RegistrySample {
         self.cursor.set_loop(loop_start, loop_end);
     }
 
+    /// Sets a sustain loop (normalized to the playback region) with a
+    /// crossfade of `xfade` frames.
+    pub fn set_loop_region(&mut self, begin: f32, end: f32, xfade: f32) {
+        self.cursor.set_loop_region(begin, end, xfade);
+    }
+
+    /// Leaves the sustain loop and plays on to the region end.
+    pub fn release_loop(&mut self) {
+        self.cursor.release_loop();
+    }
+
     /// Returns true if playback has finished.
     #[inline]
     pub fn is_done(&self) -> bool {
@@ -109,6 +132,7 @@ impl Clone for RegistrySample {
             root_freq: self.root_freq,
             attenuation: self.attenuation,
             scale_tuning: self.scale_tuning,
+            tuning: self.tuning,
             cursor: self.cursor,
         }
     }
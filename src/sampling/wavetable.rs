@@ -0,0 +1,167 @@
+//! Wavetable file detection.
+//!
+//! Wavetables are single-cycle waveforms laid end to end. Two kinds are
+//! recognised on load, so `scan/0..1` sweeps the whole table without a
+//! `wtlen`:
+//!
+//! | File                              | Cycle length                   |
+//! |-----------------------------------|--------------------------------|
+//! | `.wt` (Surge / Bitwig `vawt`)     | From the header                |
+//! | `.wav` with a `clm ` chunk        | From the chunk (`<!>2048 ...`) |
+//!
+//! Any other `.wav` plays as an ordinary sample, whatever its length; set
+//! `wtlen` to scan it as a table.
+//!
+//! Wavetables are not resampled: their cycles are pitched by the
+//! oscillator, and resampling would blur the cycle boundaries.
+
+use std::io::{Read, Seek, SeekFrom};
+
+/// `.wt` header flag: samples are 16-bit integers instead of 32-bit floats.
+const WT_INT16: u16 = 0x04;
+/// `.wt` header flag: 16-bit samples use the full range instead of 15 bits.
+const WT_FULL_RANGE: u16 = 0x08;
+
+/// Decoded `.wt` file: mono frames and the cycle length.
+pub struct WtFile {
+    pub frames: Vec<f32>,
+    pub cycle: u32,
+}
+
+/// Parses a `.wt` file: `vawt`, the cycle length (u32), the cycle count
+/// (u16) and flags (u16), then the samples, all little-endian.
+pub fn parse_wt(bytes: &[u8]) -> Result<WtFile, String> {
+    if bytes.len() < 12 || &bytes[..4] != b"vawt" {
+        return Err("not a vawt wavetable".into());
+    }
+    let cycle = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    let count = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let flags = u16::from_le_bytes([bytes[10], bytes[11]]);
+    if cycle == 0 || count == 0 {
+        return Err("empty wavetable".into());
+    }
+    let samples = cycle as usize * count;
+    let data = &bytes[12..];
+    let frames: Vec<f32> = if flags & WT_INT16 != 0 {
+        let scale = if flags & WT_FULL_RANGE != 0 {
+            1.0 / 32768.0
+        } else {
+            1.0 / 16384.0
+        };
+        data.chunks_exact(2)
+            .take(samples)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 * scale)
+            .collect()
+    } else {
+        data.chunks_exact(4)
+            .take(samples)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    };
+    if frames.len() < samples {
+        return Err(format!(
+            "wavetable truncated: {} of {samples} samples",
+            frames.len()
+        ));
+    }
+    Ok(WtFile { frames, cycle })
+}
+
+/// Cycle length from a WAV's `clm ` chunk (written by Serum and most
+/// wavetable editors as `<!>2048 ...`), without reading the audio.
+pub fn wav_cycle_marker(mut file: impl Read + Seek) -> Option<u32> {
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header).ok()?;
+    if &header[..4] != b"RIFF" || &header[8..] != b"WAVE" {
+        return None;
+    }
+    let mut chunk = [0u8; 8];
+    while file.read_exact(&mut chunk).is_ok() {
+        let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        if &chunk[..4] == b"clm " {
+            let mut text = vec![0u8; size.min(64) as usize];
+            file.read_exact(&mut text).ok()?;
+            let text = String::from_utf8_lossy(&text);
+            let digits: String = text
+                .strip_prefix("<!>")?
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            return digits.parse().ok().filter(|&n| n > 0);
+        }
+        // Chunks are padded to an even size
+        file.seek(SeekFrom::Current(i64::from(size + (size & 1))))
+            .ok()?;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::types::Source;
+    #[cfg(feature = "native")]
+    use crate::{sampling, Engine};
+    #[cfg(feature = "native")]
+    use std::sync::Arc;
+
+    #[test]
+    fn wt_files_carry_their_cycle_length() {
+        let mut bytes = b"vawt".to_vec();
+        bytes.extend(4u32.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend((WT_INT16 | WT_FULL_RANGE).to_le_bytes());
+        for s in [0i16, 16384, -16384, 0, 8192, 0, -8192, 0] {
+            bytes.extend(s.to_le_bytes());
+        }
+        let wt = parse_wt(&bytes).unwrap();
+        assert_eq!(wt.cycle, 4);
+        assert_eq!(wt.frames, [0.0, 0.5, -0.5, 0.0, 0.25, 0.0, -0.25, 0.0]);
+        assert!(parse_wt(&bytes[..20]).is_err());
+        assert!(parse_wt(b"RIFF....WAVE").is_err());
+    }
+
+    #[test]
+    fn wav_banks_are_found_by_marker() {
+        let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+        wav.extend(b"fmt \x03\0\0\0abc\0");
+        wav.extend(b"clm \x10\0\0\0<!>1024 01000000");
+        wav.extend(b"data\0\0\0\0");
+        assert_eq!(wav_cycle_marker(std::io::Cursor::new(&wav)), Some(1024));
+        let plain = [&wav[..24], b"data\0\0\0\0"].concat();
+        assert_eq!(wav_cycle_marker(std::io::Cursor::new(plain)), None);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn wt_files_play_as_wavetables() {
+        let dir = std::env::temp_dir().join(format!("doux-wt-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("table")).unwrap();
+        let path = dir.join("table").join("basic.wt");
+        let mut bytes = b"vawt".to_vec();
+        bytes.extend(64u32.to_le_bytes());
+        bytes.extend(3u16.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        for i in 0..64 * 3 {
+            bytes.extend(((i % 64) as f32 / 32.0 - 1.0).to_le_bytes());
+        }
+        std::fs::write(&path, bytes).unwrap();
+        let index = sampling::scan_samples_dir(&dir);
+        let data = sampling::decode_sample_file(&path, 44100.0).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(index[0].name.as_ref(), "table/0");
+        assert_eq!((data.frame_count, data.wt_cycle), (192, Some(64)));
+
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine
+            .sample_registry
+            .insert("table/0".to_string(), Arc::new(data));
+        engine.sample_index = index;
+        let i = engine.evaluate("/sound/table/note/60").unwrap();
+        assert_eq!(engine.voices[i].params.sound, Source::Wavetable);
+        let mut out = vec![0.0; 128];
+        engine.process_block(&mut out, &[], &[]);
+        assert!(out.iter().any(|&s| s != 0.0));
+    }
+}
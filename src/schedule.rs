@@ -83,6 +83,26 @@ impl Schedule {
         self.events.is_empty()
     }
 
+    /// Scheduled events, earliest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    /// Lets `retime` move events, then restores the order. Ties keep their
+    /// order, and nothing allocates.
+    pub fn retime(&mut self, mut retime: impl FnMut(&mut Event)) {
+        let events = self.events.make_contiguous();
+        events.iter_mut().for_each(&mut retime);
+        let key = |e: &Event| e.tick.unwrap_or(u64::MAX);
+        for i in 1..events.len() {
+            let mut j = i;
+            while j > 0 && key(&events[j - 1]) > key(&events[j]) {
+                events.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
     /// Removes all scheduled events.
     pub fn clear(&mut self) {
         self.events.clear();
@@ -94,3 +114,148 @@ impl Default for Schedule {
         Self::new()
     }
 }
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use crate::Engine;
+
+    #[test]
+    fn ratchet_splits_gate_into_decaying_stepped_hits() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/gate/0.1/ratchet/4/ratchetdecay/0.5/ratchetstep/12");
+        assert_eq!(engine.active_voices, 1);
+        assert_eq!(engine.schedule.len(), 3);
+        assert_eq!(engine.voices[0].params.gate, 0.025);
+
+        // Second hit lands 1200 samples in
+        while engine.tick < 1200 + 64 {
+            engine.dsp();
+        }
+        let hit = &engine.voices[engine.active_voices - 1];
+        assert_eq!(hit.params.gain, 0.5);
+        assert_eq!(hit.params.detune, 1200.0);
+        assert_eq!(engine.schedule.len(), 2);
+
+        // Endless gates aren't subdivided
+        engine.evaluate("/doux/reset");
+        engine.evaluate("/sound/sine/gate/0/ratchet/4");
+        assert_eq!(engine.active_voices, 1);
+        assert!(engine.schedule.is_empty());
+    }
+
+    #[test]
+    fn swing_alternates_long_and_short_ratchet_steps() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let ticks = |engine: &mut Engine, cmd: &str| {
+            engine.evaluate(cmd);
+            let mut ticks = Vec::new();
+            while let Some(event) = engine.schedule.pop_front() {
+                ticks.push(event.tick.unwrap());
+            }
+            ticks
+        };
+        // Hits 1200 apart, every other one pushed back 240
+        assert_eq!(
+            ticks(
+                &mut engine,
+                "/sound/sine/tick/0/gate/0.1/ratchet/4/swing/0.6"
+            ),
+            [0, 1440, 2400, 3840]
+        );
+        // Pairs of hits swung as one step
+        assert_eq!(
+            ticks(
+                &mut engine,
+                "/sound/sine/tick/0/gate/0.2/ratchet/8/swing/0.75/swinggrid/2"
+            ),
+            [0, 1800, 3600, 4200, 4800, 6600, 8400, 9000]
+        );
+        assert_eq!(
+            ticks(
+                &mut engine,
+                "/sound/sine/tick/0/gate/0.1/ratchet/4/swing/0.5"
+            ),
+            [0, 1200, 2400, 3600]
+        );
+    }
+
+    #[test]
+    fn repjitter_and_flam_shift_hits_in_the_schedule() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/tick/10000/gate/0.1/ratchet/4/repjitter/0.01");
+        let mut ticks = Vec::new();
+        while let Some(event) = engine.schedule.pop_front() {
+            ticks.push(event.tick.unwrap());
+        }
+        // Hits 1200 apart, each off by at most 480 (clamped to 600)
+        assert_eq!(ticks.len(), 4);
+        assert_eq!(ticks[0], 10000);
+        assert!(ticks.windows(2).all(|w| w[0] < w[1]));
+        assert!(ticks
+            .iter()
+            .zip([10000, 11200, 12400, 13600])
+            .all(|(&t, grid)| t.abs_diff(grid) <= 480));
+        assert!(ticks[1..] != [11200, 12400, 13600]);
+
+        // Grace hit lands ahead of the main hit, which stays on its tick
+        engine.evaluate("/sound/sine/tick/10000/gain/0.8/flam/0.03");
+        let grace = engine.schedule.pop_front().unwrap();
+        let main = engine.schedule.pop_front().unwrap();
+        assert_eq!((grace.tick, main.tick), (Some(8560), Some(10000)));
+        assert_eq!((grace.gain, main.gain), (Some(0.4), Some(0.8)));
+
+        // Played live, the grace hit goes now and the main hit follows
+        engine.evaluate("/sound/sine/flam/0.03");
+        let grace = engine.schedule.pop_front().unwrap();
+        let main = engine.schedule.pop_front().unwrap();
+        assert_eq!((grace.tick, main.tick), (Some(0), Some(1440)));
+    }
+
+    #[test]
+    fn gate_counts_from_scheduled_tick_and_endtime_is_absolute() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        // Runs 500 samples late, but still ends 4800 samples after tick 1000
+        engine.tick = 1500;
+        engine.evaluate("/sound/sine/tick/1000/gate/0.1");
+        engine.dsp();
+        assert_eq!(engine.voices[0].gate_end, Some(5800));
+        while engine.tick < 5800 {
+            assert!(!engine.voices[0].dahdsr.is_releasing());
+            engine.dsp();
+        }
+        assert!(engine.voices[0].dahdsr.is_releasing());
+
+        // Re-targeting a running voice moves its end to the new event's time
+        engine.evaluate("/doux/reset");
+        engine.evaluate("/sound/sine/voice/0/gate/1");
+        engine.dsp();
+        engine.evaluate("/voice/0/freq/330/gate/0.01");
+        assert_eq!(engine.voices[0].gate_end, Some(engine.tick + 480));
+        engine.evaluate("/voice/0/endtime/0.5");
+        assert_eq!(engine.voices[0].gate_end, Some(24000));
+        engine.evaluate("/voice/0/gate/0");
+        assert_eq!(engine.voices[0].gate_end, None);
+    }
+
+    #[test]
+    fn late_ok_events_survive_the_catch_up_window() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        // 0.05 s behind the clock, past the 0.02 s window
+        engine.tick = 4800;
+        engine.evaluate("/sound/sine/tick/2400");
+        engine.evaluate("/sound/sine/tick/2400/late_ok/1/id/scene");
+        engine.dsp();
+        assert_eq!(engine.active_voices, 1);
+        assert_eq!(
+            engine.voices[0].params.group,
+            Some(crate::voice::group_key("scene"))
+        );
+        assert_eq!(
+            engine
+                .metrics
+                .dropped_events
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Session logs: every event the audio thread takes, with the engine tick
+//! it took it at, so an improvised session renders again sample for
+//! sample. Native only.
+//!
+//! The log is plain text, one event per line:
+//!
+//! ```text
+//! // doux session, 48000 Hz
+//! 0 /doux/seed/7
+//! 23552 /sound/kick
+//! 47104@48000 /sound/hat
+//! ```
+//!
+//! The number is the tick the engine was at when the event arrived;
+//! `@tick` is the tick the sender scheduled it for (an OSC bundle time
+//! tag). [`render_session`](crate::offline::render_session) evaluates
+//! each line when the offline engine reaches its tick, the way the audio
+//! callback did. The engine's random seeds start the same on every run,
+//! so noise, random modulation and jitter come out the same too.
+//!
+//! Ticks restart when the audio device is rebuilt; each start writes a
+//! comment line, and replay is only exact up to the first restart.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crossbeam_channel::{bounded, Receiver, Sender};
+
+/// One logged event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionEntry {
+    /// Engine tick the event arrived at.
+    pub tick: u64,
+    /// Tick the sender scheduled the event for, if any.
+    pub at: Option<u64>,
+    pub event: String,
+}
+
+impl SessionEntry {
+    /// Parses a log line. Blank lines and `//` comments give `None`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            return None;
+        }
+        let (ticks, event) = line.split_once(char::is_whitespace)?;
+        let (tick, at) = match ticks.split_once('@') {
+            Some((tick, at)) => (tick, Some(at.parse().ok()?)),
+            None => (ticks, None),
+        };
+        Some(Self {
+            tick: tick.parse().ok()?,
+            at,
+            event: event.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for SessionEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tick)?;
+        if let Some(at) = self.at {
+            write!(f, "@{at}")?;
+        }
+        write!(f, " {}", self.event)
+    }
+}
+
+/// Reads a session log, sorted by tick.
+pub fn read_session(path: &Path) -> Result<Vec<SessionEntry>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    let mut entries: Vec<_> = text.lines().filter_map(SessionEntry::parse).collect();
+    entries.sort_by_key(|entry| entry.tick);
+    Ok(entries)
+}
+
+enum Line {
+    Start(f32),
+    Entry(SessionEntry),
+}
+
+/// Lines the writer can fall behind by before events are dropped.
+const QUEUE: usize = 1024;
+
+/// Starting capacity of each pooled event string; longer events grow it
+/// once and keep the room.
+const EVENT_CAPACITY: usize = 256;
+
+/// Handle to a session log file. Clones write to the same file; the lines
+/// are formatted and written on a background thread, never the audio
+/// thread.
+///
+/// [`record`](Self::record) copies the event into a string from a pool
+/// filled up front and queues it, so it doesn't allocate. If the writer
+/// falls [`QUEUE`] lines behind, events are dropped and the log notes how
+/// many.
+#[derive(Clone)]
+pub struct SessionLog {
+    tx: Sender<Line>,
+    free: Receiver<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl SessionLog {
+    /// Creates (or truncates) the log at `path`.
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let (tx, rx) = bounded::<Line>(QUEUE);
+        let (free_tx, free) = bounded::<String>(QUEUE);
+        for _ in 0..QUEUE {
+            let _ = free_tx.send(String::with_capacity(EVENT_CAPACITY));
+        }
+        let dropped = Arc::new(AtomicU64::new(0));
+        let missed = Arc::clone(&dropped);
+        std::thread::Builder::new()
+            .name("doux-session".into())
+            .spawn(move || {
+                for line in rx {
+                    let mut written = match line {
+                        Line::Start(sr) => writeln!(file, "// doux session, {sr} Hz"),
+                        Line::Entry(entry) => {
+                            let written = writeln!(file, "{entry}");
+                            let _ = free_tx.send(entry.event);
+                            written
+                        }
+                    };
+                    let n = missed.swap(0, Ordering::Relaxed);
+                    if n > 0 {
+                        written = written
+                            .and_then(|()| writeln!(file, "// {n} events dropped, log full"));
+                    }
+                    if let Err(err) = written.and_then(|()| file.flush()) {
+                        eprintln!("[doux] session log: {err}");
+                        return;
+                    }
+                }
+            })?;
+        Ok(Self { tx, free, dropped })
+    }
+
+    /// Marks a new engine starting from tick 0.
+    pub fn start(&self, sample_rate: f32) {
+        let _ = self.tx.send(Line::Start(sample_rate));
+    }
+
+    /// Queues an event for the log. Safe to call from the audio callback.
+    pub fn record(&self, tick: u64, at: Option<u64>, event: &str) {
+        let Ok(mut text) = self.free.try_recv() else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        text.clear();
+        text.push_str(event);
+        let entry = SessionEntry {
+            tick,
+            at,
+            event: text,
+        };
+        if self.tx.try_send(Line::Entry(entry)).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    #[test]
+    fn entries_round_trip_through_a_line() {
+        let entry = SessionEntry {
+            tick: 47104,
+            at: Some(48000),
+            event: "/sound/hat".into(),
+        };
+        assert_eq!(entry.to_string(), "47104@48000 /sound/hat");
+        assert_eq!(SessionEntry::parse(&entry.to_string()), Some(entry));
+        assert_eq!(SessionEntry::parse("// doux session, 48000 Hz"), None);
+        assert_eq!(SessionEntry::parse("x /sound/kick"), None);
+    }
+
+    #[test]
+    fn recorded_events_reach_the_file_in_order() {
+        let path = std::env::temp_dir().join(format!("doux-session-{}.txt", std::process::id()));
+        let log = SessionLog::create(&path).unwrap();
+        log.start(48000.0);
+        let long = format!("/sound/{}", "a".repeat(EVENT_CAPACITY * 2));
+        log.record(0, None, "/doux/seed/7");
+        log.record(512, Some(1024), &long);
+        log.record(1024, None, "/sound/kick");
+
+        let mut entries = Vec::new();
+        for _ in 0..200 {
+            entries = read_session(&path).unwrap();
+            if entries.len() == 3 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        std::fs::remove_file(&path).unwrap();
+        let events: Vec<_> = entries.iter().map(|e| e.event.as_str()).collect();
+        assert_eq!(events, ["/doux/seed/7", long.as_str(), "/sound/kick"]);
+        assert_eq!(entries[1].at, Some(1024));
+    }
+
+    #[test]
+    fn session_log_renders_live_events_again_sample_for_sample() {
+        let arrivals = [
+            (0, None, "/doux/seed/7"),
+            (96, None, "/sound/white/gate/0.002/lpf/400~4000:0.2r"),
+            (
+                480,
+                Some(500),
+                "/sound/sine/freq/440/delay/0.3/delaytime/0.002",
+            ),
+            (960, None, "/doux/hush"),
+        ];
+
+        // Live: events land at the start of 48-frame callbacks
+        let mut live = Engine::new_with_channels(48000.0, 2, 8, 48);
+        let mut played = vec![0.0; 1440 * 2];
+        let mut log = String::from("// doux session, 48000 Hz\n");
+        for block in played.chunks_mut(48 * 2) {
+            for &(tick, at, path) in &arrivals {
+                if tick == live.tick {
+                    live.evaluate_at(path, at);
+                    let entry = SessionEntry {
+                        tick,
+                        at,
+                        event: path.to_string(),
+                    };
+                    log.push_str(&format!("{entry}\n"));
+                }
+            }
+            live.process_block(block, &[], &[]);
+        }
+
+        let session: Vec<_> = log.lines().filter_map(SessionEntry::parse).collect();
+        assert_eq!(session.len(), arrivals.len());
+        assert_eq!(
+            session[2].to_string(),
+            "480@500 /sound/sine/freq/440/delay/0.3/delaytime/0.002"
+        );
+
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let pass = crate::offline::render_session(&mut engine, &session, 0.03, None);
+        let rendered = pass.output.unwrap();
+        assert!(played.iter().any(|&s| s != 0.0));
+        assert_eq!(rendered, played);
+    }
+}
@@ -4,7 +4,8 @@ use std::path::Path;
 use soundfont::raw::GeneratorType;
 use soundfont::SoundFont2;
 
-use crate::sampling::{resample_linear, SampleData};
+use crate::dsp::{resample, ResampleQuality};
+use crate::sampling::SampleData;
 use crate::types::midi2freq;
 
 #[derive(Clone)]
@@ -418,7 +419,13 @@ pub fn load_sf2(
         sample_ratios.push(ratio);
 
         let pcm = if needs_resample {
-            resample_linear(&pcm, 1, hdr.sample_rate as f32, target_sr)
+            resample(
+                &pcm,
+                1,
+                hdr.sample_rate as f32,
+                target_sr,
+                ResampleQuality::default(),
+            )
         } else {
             pcm
         };
@@ -426,7 +433,13 @@ pub fn load_sf2(
         let root_note = hdr.origpitch as f32 + hdr.pitchadj as f32 / 100.0;
         let root_freq = midi2freq(root_note);
         let name = format!("_sf2_{i}");
-        samples.push((name, SampleData::new(pcm, 1, root_freq)));
+        let mut data = SampleData::new(pcm, 1, root_freq);
+        data.sample_rate = Some(if needs_resample {
+            target_sr
+        } else {
+            hdr.sample_rate as f32
+        });
+        samples.push((name, data));
     }
 
     // Build zone lookup table
@@ -604,3 +617,57 @@ pub fn find_sf2_file(dir: &Path) -> Option<std::path::PathBuf> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+    use std::sync::Arc;
+
+    #[test]
+    fn looped_zones_keep_looping_through_the_release() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let data = SampleData::new(vec![0.1; 1000], 1, 261.626);
+        engine
+            .sample_registry
+            .insert("gm/strings".to_string(), Arc::new(data));
+        engine.gm_bank = Some(GmBank {
+            zones: vec![ZoneEntry {
+                preset: 0,
+                bank: 0,
+                key_lo: 0,
+                key_hi: 127,
+                vel_lo: 0,
+                vel_hi: 127,
+                sample_name: "gm/strings".to_string(),
+                root_freq: 261.626,
+                loop_start: 100.0,
+                loop_end: 400.0,
+                looping: true,
+                attenuation: 1.0,
+                pan: 0.5,
+                filter_fc: 20000.0,
+                filter_q: 0.0,
+                scale_tuning: 1.0,
+                delay: 0.0,
+                hold: 0.0,
+                attack: 0.001,
+                decay: 0.0,
+                sustain: 1.0,
+                release: 1.0,
+            }],
+        });
+
+        // Released after 640 frames, then held for 6400 more: far past
+        // the end of the 1000-frame sample unless the loop keeps going
+        engine.evaluate("/sound/gm0/note/60/gate/0.01");
+        for _ in 0..110 {
+            engine.dsp();
+        }
+        let v = &engine.voices[0];
+        assert_eq!(engine.active_voices, 1);
+        assert!(v.dahdsr.is_releasing());
+        assert!(v.registry_sample.as_ref().unwrap().is_looping());
+        assert!(engine.output.iter().any(|&s| s != 0.0));
+    }
+}
@@ -0,0 +1,334 @@
+//! Multichannel voice panning (`pan2d`).
+//!
+//! The output pairs form a ring of evenly spaced speakers around the
+//! listener: pair `p` of `n` channels puts its right channel `(p + 0.5) / n`
+//! turns clockwise from the front and its left channel as far the other
+//! way. Four channels are a quad square (front pair, rear pair), six a
+//! hexagon and eight an octagon. A voice with an azimuth plays on the two
+//! speakers around it with VBAP gains; `pan2dspread` blends it toward all
+//! of them.
+//!
+//! Only orbits that reach the device through the default pairs (`outmode`
+//! `pairs`, no `outpair` or route) feed the ring. On the others a `pan2d`
+//! voice is folded to stereo and mixed like any other voice, so stems and
+//! routes keep their channels.
+//!
+//! A voice on the ring skips its orbit's bus: beat repeat, the orbit EQ and
+//! filters, the bus compressor, the inserts, and the orbit's meter, taps and
+//! recordings don't hear its dry signal. Its sends to the orbit's effects
+//! and the orbit's sidechain ducking still apply.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::dsp::{cosf, sinf};
+use crate::types::{CHANNELS, MAX_ORBITS};
+
+/// Most speakers a ring holds.
+pub const MAX_SPEAKERS: usize = 32;
+
+/// Speaker angles and channels of the output ring, sorted by angle.
+#[derive(Clone, Copy, Debug)]
+pub struct SpeakerRing {
+    /// Angle in turns, in `[0, 1)`.
+    angles: [f32; MAX_SPEAKERS],
+    channels: [usize; MAX_SPEAKERS],
+    count: usize,
+    /// Output pair left out of the ring (the cue pair).
+    skip: Option<usize>,
+}
+
+impl SpeakerRing {
+    /// Ring over the pairs of `output_channels`, without pair `skip`.
+    /// Doesn't allocate, so the audio thread can rebuild it.
+    pub fn new(output_channels: usize, skip: Option<usize>) -> Self {
+        let pairs = (output_channels / 2).min(MAX_SPEAKERS / 2);
+        let used = pairs - usize::from(skip.is_some_and(|p| p < pairs));
+        let n = (used * 2) as f32;
+        let mut ring = Self {
+            angles: [0.0; MAX_SPEAKERS],
+            channels: [0; MAX_SPEAKERS],
+            count: used * 2,
+            skip,
+        };
+        // Right channels go clockwise down the front half, left channels
+        // mirror them back up the other half
+        let pairs = (0..pairs).filter(|&p| Some(p) != skip);
+        for (i, p) in pairs.enumerate() {
+            let angle = (i as f32 + 0.5) / n;
+            ring.angles[i] = angle;
+            ring.channels[i] = p * 2 + 1;
+            ring.angles[used * 2 - 1 - i] = 1.0 - angle;
+            ring.channels[used * 2 - 1 - i] = p * 2;
+        }
+        ring
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The output pair the ring was built without.
+    pub fn skipped(&self) -> Option<usize> {
+        self.skip
+    }
+
+    /// Adds `sample` to `bus` (one slot per output channel) at `azimuth`
+    /// turns clockwise from the front, spread toward every speaker by
+    /// `spread` in `0..=1`. The gains keep the power of `sample`.
+    #[inline]
+    pub fn pan(&self, azimuth: f32, spread: f32, sample: f32, bus: &mut [f32]) {
+        let n = self.count;
+        if n == 0 {
+            return;
+        }
+        let az = azimuth.rem_euclid(1.0);
+        // Speakers `a` and `b` on either side of the azimuth, `b` clockwise
+        let b = self.angles[..n].partition_point(|&angle| angle <= az) % n;
+        let a = (b + n - 1) % n;
+        let (mut from, to) = (self.angles[a], self.angles[b]);
+        let mut pos = az;
+        if to <= from {
+            // The pair straddles the front
+            from -= 1.0;
+            if pos > to {
+                pos -= 1.0;
+            }
+        }
+        let span = to - from;
+        let (ga, gb) = if span < 0.5 {
+            // VBAP: solve for the gains that point the pair's sum at `pos`
+            (sinf((to - pos) * TAU), sinf((pos - from) * TAU))
+        } else {
+            // Speakers facing each other (stereo) don't span a direction;
+            // crossfade at constant power instead
+            let t = ((pos - from) / span) * FRAC_PI_2;
+            (cosf(t), sinf(t))
+        };
+        let norm = (ga * ga + gb * gb).sqrt().max(1e-9);
+        let (ga, gb) = (ga / norm, gb / norm);
+
+        let spread = spread.clamp(0.0, 1.0);
+        if spread == 0.0 || n == 1 {
+            bus[self.channels[a]] += sample * ga;
+            if b != a {
+                bus[self.channels[b]] += sample * gb;
+            }
+            return;
+        }
+        // Blend toward equal gains, then restore the power
+        let even = (1.0 / n as f32).sqrt() * spread;
+        let gain = |i: usize| {
+            let g = if i == a {
+                ga
+            } else if i == b {
+                gb
+            } else {
+                0.0
+            };
+            g * (1.0 - spread) + even
+        };
+        let power: f32 = (0..n).map(|i| gain(i) * gain(i)).sum();
+        let scale = sample / power.sqrt().max(1e-9);
+        for i in 0..n {
+            bus[self.channels[i]] += gain(i) * scale;
+        }
+    }
+}
+
+/// Per-orbit sums of the voices panned onto the ring, one slot per output
+/// channel. They skip the orbit's stereo bus and join the output after the
+/// orbit's sidechain ducking.
+pub struct SpatialBus {
+    ring: SpeakerRing,
+    /// Left and right, for voices folded back to stereo.
+    stereo: SpeakerRing,
+    channels: usize,
+    bus: Vec<f32>,
+    /// Bit per orbit holding anything since the last [`clear`](Self::clear).
+    active: u32,
+    /// Bit per orbit whose voices go on the ring.
+    on_ring: u32,
+}
+
+impl SpatialBus {
+    pub fn new(output_channels: usize) -> Self {
+        Self {
+            ring: SpeakerRing::new(output_channels, None),
+            stereo: SpeakerRing::new(CHANNELS, None),
+            channels: output_channels,
+            bus: vec![0.0; MAX_ORBITS * output_channels],
+            active: 0,
+            on_ring: 0,
+        }
+    }
+
+    /// Starts a new frame, rebuilding the ring if the cue pair moved.
+    /// `on_ring` has a bit per orbit whose voices go on the ring.
+    #[inline]
+    pub fn clear(&mut self, cue_pair: Option<usize>, on_ring: u32) {
+        if self.ring.skipped() != cue_pair {
+            self.ring = SpeakerRing::new(self.channels, cue_pair);
+        }
+        self.on_ring = on_ring;
+        while self.active != 0 {
+            let orbit = self.active.trailing_zeros() as usize;
+            self.active &= self.active - 1;
+            self.bus[orbit * self.channels..(orbit + 1) * self.channels].fill(0.0);
+        }
+    }
+
+    /// Whether `orbit`'s voices go on the ring this frame.
+    #[inline]
+    pub fn on_ring(&self, orbit: usize) -> bool {
+        orbit < MAX_ORBITS && self.on_ring & (1 << orbit) != 0
+    }
+
+    /// Pans `sample` between left and right for an orbit off the ring.
+    #[inline]
+    pub fn fold(&self, azimuth: f32, spread: f32, sample: f32) -> [f32; CHANNELS] {
+        let mut frame = [0.0; CHANNELS];
+        self.stereo.pan(azimuth, spread, sample, &mut frame);
+        frame
+    }
+
+    #[inline]
+    pub fn add(&mut self, orbit: usize, azimuth: f32, spread: f32, sample: f32) {
+        if orbit >= MAX_ORBITS {
+            return;
+        }
+        let slots = &mut self.bus[orbit * self.channels..(orbit + 1) * self.channels];
+        self.ring.pan(azimuth, spread, sample, slots);
+        self.active |= 1 << orbit;
+    }
+
+    /// Adds the orbit's panned voices, times `gain`, to an output frame.
+    #[inline]
+    pub fn mix(&self, orbit: usize, gain: f32, frame: &mut [f32]) {
+        if orbit >= MAX_ORBITS || self.active & (1 << orbit) == 0 {
+            return;
+        }
+        let slots = &self.bus[orbit * self.channels..(orbit + 1) * self.channels];
+        for (out, s) in frame.iter_mut().zip(slots) {
+            *out += s * gain;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    fn gains(ring: &SpeakerRing, channels: usize, azimuth: f32, spread: f32) -> Vec<f32> {
+        let mut bus = vec![0.0; channels];
+        ring.pan(azimuth, spread, 1.0, &mut bus);
+        bus
+    }
+
+    #[test]
+    fn quad_ring_pans_between_neighbouring_speakers_at_constant_power() {
+        let ring = SpeakerRing::new(4, None);
+        // Front left, front right, rear left, rear right
+        let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-3);
+        let h = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(close(&gains(&ring, 4, 0.0, 0.0), &[h, h, 0.0, 0.0]));
+        assert!(close(&gains(&ring, 4, 0.125, 0.0), &[0.0, 1.0, 0.0, 0.0]));
+        assert!(close(&gains(&ring, 4, 0.25, 0.0), &[0.0, h, 0.0, h]));
+        assert!(close(&gains(&ring, 4, 0.5, 0.0), &[0.0, 0.0, h, h]));
+        assert!(close(&gains(&ring, 4, -0.125, 0.0), &[1.0, 0.0, 0.0, 0.0]));
+
+        for i in 0..64 {
+            for spread in [0.0, 0.4, 1.0] {
+                let g = gains(&ring, 4, i as f32 / 64.0, spread);
+                let power: f32 = g.iter().map(|g| g * g).sum();
+                assert!((power - 1.0).abs() < 1e-3, "{i}/64 spread {spread}: {g:?}");
+            }
+        }
+        assert!(close(&gains(&ring, 4, 0.3, 1.0), &[0.5; 4]));
+    }
+
+    #[test]
+    fn stereo_and_skipped_pairs() {
+        let stereo = SpeakerRing::new(2, None);
+        let g = gains(&stereo, 2, 0.0, 0.0);
+        assert!((g[0] - g[1]).abs() < 1e-6);
+        assert!((gains(&stereo, 2, 0.25, 0.0)[1] - 1.0).abs() < 1e-6);
+
+        // The cue pair stays silent
+        let ring = SpeakerRing::new(6, Some(1));
+        assert_eq!(ring.len(), 4);
+        for i in 0..16 {
+            let g = gains(&ring, 6, i as f32 / 16.0, 0.5);
+            assert_eq!((g[2], g[3]), (0.0, 0.0));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn pan2d_places_voices_on_the_quad_ring() {
+        let peaks = |path: &str| {
+            let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+            engine.evaluate(path);
+            let mut peak = [0.0f32; 4];
+            for _ in 0..20 {
+                engine.dsp();
+                for frame in engine.output.chunks(4) {
+                    for (p, x) in peak.iter_mut().zip(frame) {
+                        *p = p.max(x.abs());
+                    }
+                }
+            }
+            peak
+        };
+        // Front left, front right, rear left, rear right
+        let behind = peaks("/sound/sine/pan2d/0.5");
+        assert!(behind[0] < 1e-6 && behind[1] < 1e-6, "{behind:?}");
+        assert!((behind[2] - behind[3]).abs() < 1e-4 && behind[2] > 0.01);
+
+        let right = peaks("/sound/sine/pan2d/0.25");
+        assert!(right[0] < 1e-6 && right[2] < 1e-6, "{right:?}");
+        assert!((right[1] - right[3]).abs() < 1e-4);
+
+        let everywhere = peaks("/sound/sine/pan2d/0.5/pan2dspread/1");
+        assert!(everywhere.iter().all(|&p| (p - everywhere[0]).abs() < 1e-4));
+
+        // Effects still hear the voice through the orbit
+        let echo = peaks("/sound/sine/pan2d/0.5/delay/0.5/delaytime/0.005");
+        assert!(echo[0] > 1e-4, "{echo:?}");
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn stems_and_routes_keep_pan2d_voices_on_their_channels() {
+        let peaks = |setup: &str, path: &str| {
+            let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+            engine.evaluate(setup);
+            engine.evaluate(path);
+            let mut peak = [0.0f32; 4];
+            for _ in 0..20 {
+                engine.dsp();
+                for frame in engine.output.chunks(4) {
+                    for (p, x) in peak.iter_mut().zip(frame) {
+                        *p = p.max(x.abs());
+                    }
+                }
+            }
+            peak
+        };
+        // Folded to stereo: behind is centred, right is right
+        let stem = peaks("/doux/outmode/stereo", "/sound/sine/pan2d/0.5");
+        assert!(stem[2] == 0.0 && stem[3] == 0.0, "{stem:?}");
+        assert!((stem[0] - stem[1]).abs() < 1e-4 && stem[0] > 0.01);
+        let right = peaks("/doux/outmode/stereo", "/sound/sine/pan2d/0.25");
+        assert!(right[0] < 1e-6 && right[1] > 0.01, "{right:?}");
+
+        let routed = peaks("/doux/route/orbit/0/outpair/1", "/sound/sine/pan2d/0");
+        assert!(routed[0] == 0.0 && routed[1] == 0.0, "{routed:?}");
+        assert!(routed[2] > 0.01 && routed[3] > 0.01);
+    }
+}
@@ -0,0 +1,697 @@
+//! Engine snapshots, for saving and sharing sessions.
+//!
+//! [`Engine::snapshot`] captures what a session has built up: running
+//! voices' params and modulations, the orbit count, orbit effect settings
+//! and automation lanes, the master bus, output mode and routes, the click,
+//! pending scheduled events, the clock and tempo. [`Engine::restore`] brings
+//! it back, on the same engine or another one. The [`EngineState`] is plain
+//! data and serialises with serde; [`EngineState::to_json`] gives a compact
+//! file format.
+//!
+//! On the audio thread, [`Engine::snapshot_into`] fills a state built with
+//! [`EngineState::reserved`] and [`Engine::restore_from`] moves a state's
+//! events in rather than cloning them, so the state is built and dropped
+//! by the thread that asked for it (see [`AudioCmd`](crate::AudioCmd)).
+//!
+//! DSP memory is not part of the state: restored voices start their
+//! envelopes again, and delay lines and reverb tails start from silence.
+//! Neither are `$` variables, tunings, presets, insert effects or the cue
+//! orbit's effects: they are set by text the control side can send again.
+
+use std::sync::Arc;
+
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::click::Click;
+use crate::dsp::DahdsrState;
+use crate::event::Event;
+use crate::master::{EqBand, MASTER_EQ_BANDS};
+use crate::orbit::{Orbit, OrbitParam, MAX_ORBIT_LANES};
+use crate::routing::{OutRoute, MAX_ROUTES};
+use crate::time::DEFAULT_BPM;
+use crate::types::{
+    DelayType, LfoShape, OutMode, ReverbType, MAX_EVENTS, MAX_ORBITS, MAX_VOICES, ORBIT_SENDS,
+};
+use crate::voice::{ModChain, ParamId, VoiceParams, MAX_PARAM_MODS};
+use crate::Engine;
+
+/// A saved engine session. See the [module docs](self).
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct EngineState {
+    /// Sample rate the ticks below count at.
+    pub sr: f32,
+    pub time: f64,
+    pub tick: u64,
+    pub tempo: TempoState,
+    pub voices: Vec<VoiceState>,
+    /// One per orbit; restoring sets the orbit count to match.
+    pub orbits: Vec<OrbitState>,
+    pub master: MasterState,
+    pub out_mode: OutMode,
+    pub routes: Vec<OutRoute>,
+    pub click: ClickState,
+    /// Scheduled events, earliest first.
+    pub schedule: Vec<Event>,
+    /// Entries `voices` and `orbits` gave up, kept so that refilling the
+    /// state reuses their buffers.
+    #[serde(skip)]
+    spare_voices: Vec<VoiceState>,
+    #[serde(skip)]
+    spare_orbits: Vec<OrbitState>,
+}
+
+/// The clock's tempo and the beat it had reached at the saved tick.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct TempoState {
+    pub bpm: f32,
+    pub beat: f64,
+}
+
+impl Default for TempoState {
+    fn default() -> Self {
+        Self {
+            bpm: DEFAULT_BPM,
+            beat: 0.0,
+        }
+    }
+}
+
+/// Master bus settings and the cue output.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct MasterState {
+    pub gain: f32,
+    pub limit: f32,
+    pub dc: bool,
+    pub crossfeed: f32,
+    pub peakwarn: f32,
+    pub monowarn: f32,
+    pub tilt: f32,
+    pub eq: [Option<EqBand>; MASTER_EQ_BANDS],
+    pub eq_preamp: f32,
+    pub cue_out: Option<usize>,
+    pub cue_gain: f32,
+}
+
+impl Default for MasterState {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            limit: 0.0,
+            dc: false,
+            crossfeed: 0.0,
+            peakwarn: 0.0,
+            monowarn: 0.0,
+            tilt: 0.0,
+            eq: [None; MASTER_EQ_BANDS],
+            eq_preamp: 1.0,
+            cue_out: None,
+            cue_gain: 1.0,
+        }
+    }
+}
+
+/// Metronome settings, and whether it was clicking.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ClickState {
+    pub active: bool,
+    pub gain: f32,
+    /// Tempo of its own, if it doesn't follow the engine's.
+    pub bpm: Option<f32>,
+    pub beats: u32,
+    pub orbit: usize,
+    pub cue: bool,
+}
+
+impl Default for ClickState {
+    fn default() -> Self {
+        Self::capture(&Click::new(48000.0))
+    }
+}
+
+/// One running voice.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VoiceState {
+    pub params: VoiceParams,
+    /// Inline modulations (`lpf/200~2000:0.3t`), restarted on restore.
+    pub mods: Vec<(ParamId, ModChain)>,
+    /// Registry sample (`folder/n`) the voice plays, if any.
+    pub sample: Option<Arc<str>>,
+    /// Playback range within the sample (0-1).
+    pub range: (f32, f32),
+    /// Tick at which the gate closes.
+    pub gate_end: Option<u64>,
+}
+
+/// What one active voice is playing, as [`Engine::voice_details`] reports
+/// it. Unlike [`VoiceState`], values include running modulation.
+///
+/// Copying one out of a voice does not allocate, so the audio thread can
+/// fill a list of them; serialising gives `sound` and the sends that are on.
+#[derive(Clone, Debug)]
+pub struct VoiceDetail {
+    /// Index in [`Engine::voices`], as `vid`-less events address it.
+    pub index: usize,
+    pub id: u32,
+    /// Name of the voice's source.
+    pub source: &'static str,
+    /// Registry sample (`folder/n`) the voice plays, if any.
+    pub sample: Option<Arc<str>>,
+    pub freq: f32,
+    pub gain: f32,
+    pub velocity: f32,
+    pub orbit: usize,
+    pub stage: DahdsrState,
+    /// Amplitude envelope value in `[0, 1]`.
+    pub env: f32,
+    /// Secondary orbit sends as `(orbit, level)`; level 0 is off.
+    pub sends: [(usize, f32); ORBIT_SENDS],
+    /// Seconds since the voice started.
+    pub age: f32,
+}
+
+impl VoiceDetail {
+    /// The sample when the voice plays one, else the source.
+    pub fn sound(&self) -> &str {
+        self.sample.as_deref().unwrap_or(self.source)
+    }
+
+    /// Secondary orbit sends that are on.
+    pub fn active_sends(&self) -> impl Iterator<Item = (usize, f32)> + '_ {
+        self.sends.iter().copied().filter(|&(_, level)| level > 0.0)
+    }
+}
+
+impl Serialize for VoiceDetail {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("VoiceDetail", 11)?;
+        s.serialize_field("index", &self.index)?;
+        s.serialize_field("id", &self.id)?;
+        s.serialize_field("sound", self.sound())?;
+        s.serialize_field("freq", &self.freq)?;
+        s.serialize_field("gain", &self.gain)?;
+        s.serialize_field("velocity", &self.velocity)?;
+        s.serialize_field("orbit", &self.orbit)?;
+        s.serialize_field("stage", &self.stage)?;
+        s.serialize_field("env", &self.env)?;
+        let sends: Vec<_> = self.active_sends().collect();
+        s.serialize_field("sends", &sends)?;
+        s.serialize_field("age", &self.age)?;
+        s.end()
+    }
+}
+
+/// Effect settings and automation lanes of one orbit.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OrbitState {
+    pub params: Vec<(OrbitParam, f32)>,
+    pub delay_type: DelayType,
+    pub verb_type: ReverbType,
+    pub fb_lfo_shape: LfoShape,
+    pub comp_orbit: usize,
+    pub lanes: Vec<(OrbitParam, ModChain)>,
+}
+
+impl EngineState {
+    /// An empty state with room for a whole engine, which
+    /// [`Engine::snapshot_into`] fills without allocating, scheduled events
+    /// aside: they are cloned with their strings. Build it off the audio
+    /// thread.
+    pub fn reserved() -> Self {
+        Self {
+            voices: Vec::with_capacity(MAX_VOICES),
+            orbits: Vec::with_capacity(MAX_ORBITS),
+            routes: Vec::with_capacity(MAX_ROUTES),
+            schedule: Vec::with_capacity(MAX_EVENTS),
+            spare_voices: (0..MAX_VOICES).map(|_| VoiceState::reserved()).collect(),
+            spare_orbits: (0..MAX_ORBITS).map(|_| OrbitState::reserved()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Serialises the state as JSON, leaving out unset event fields.
+    pub fn to_json(&self) -> Result<String, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        strip_nulls(&mut value);
+        serde_json::to_string(&value).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
+
+/// Drops `null` object members: events are mostly unset options, which
+/// deserialise back to `None` through `#[serde(default)]`.
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Resizes `items` to `len` by moving entries to and from `spare`, so
+/// their buffers are reused rather than freed and allocated again.
+fn fit<T>(items: &mut Vec<T>, spare: &mut Vec<T>, len: usize, new: impl Fn() -> T) {
+    while items.len() > len {
+        spare.extend(items.pop());
+    }
+    while items.len() < len {
+        items.push(spare.pop().unwrap_or_else(&new));
+    }
+}
+
+impl VoiceState {
+    fn reserved() -> Self {
+        Self {
+            params: VoiceParams::default(),
+            mods: Vec::with_capacity(MAX_PARAM_MODS),
+            sample: None,
+            range: (0.0, 1.0),
+            gate_end: None,
+        }
+    }
+}
+
+impl OrbitState {
+    fn reserved() -> Self {
+        Self {
+            params: Vec::with_capacity(OrbitParam::ALL.len()),
+            delay_type: DelayType::default(),
+            verb_type: ReverbType::default(),
+            fb_lfo_shape: LfoShape::default(),
+            comp_orbit: 0,
+            lanes: Vec::with_capacity(MAX_ORBIT_LANES),
+        }
+    }
+
+    fn capture(&mut self, orbit: &Orbit) {
+        self.params.clear();
+        self.params
+            .extend(OrbitParam::ALL.iter().map(|&p| (p, orbit.read_param(p))));
+        self.delay_type = orbit.delay.params.delay_type;
+        self.verb_type = orbit.reverb_params.verb_type;
+        self.fb_lfo_shape = orbit.fb.params.lfo_shape;
+        self.comp_orbit = orbit.comp_orbit;
+        self.lanes.clear();
+        self.lanes
+            .extend(orbit.lanes().map(|(p, c)| (p, c.clone())));
+    }
+
+    fn apply(&self, orbit: &mut Orbit) {
+        for &(param, value) in &self.params {
+            orbit.write_param(param, value);
+        }
+        orbit.delay.params.delay_type = self.delay_type;
+        orbit.reverb_params.verb_type = self.verb_type;
+        orbit.fb.params.lfo_shape = self.fb_lfo_shape;
+        orbit.comp_orbit = self.comp_orbit;
+        orbit.clear_lanes();
+        for (param, chain) in &self.lanes {
+            orbit.automate(*param, chain.clone());
+        }
+    }
+}
+
+impl MasterState {
+    fn capture(engine: &Engine) -> Self {
+        let m = &engine.master;
+        Self {
+            gain: m.gain,
+            limit: m.limit,
+            dc: m.dc,
+            crossfeed: m.crossfeed,
+            peakwarn: m.peakwarn,
+            monowarn: m.monowarn,
+            tilt: m.tilt,
+            eq: m.eq,
+            eq_preamp: m.eq_preamp,
+            cue_out: engine.cue_out,
+            cue_gain: engine.cue_gain,
+        }
+    }
+
+    fn apply(&self, engine: &mut Engine) {
+        let m = &mut engine.master;
+        m.gain = self.gain;
+        m.limit = self.limit;
+        m.dc = self.dc;
+        m.crossfeed = self.crossfeed;
+        m.peakwarn = self.peakwarn;
+        m.monowarn = self.monowarn;
+        m.tilt = self.tilt;
+        for (i, &band) in self.eq.iter().enumerate() {
+            m.set_eq_band(i, band);
+        }
+        m.eq_preamp = self.eq_preamp;
+        engine.cue_out = self.cue_out;
+        engine.cue_gain = self.cue_gain;
+    }
+}
+
+impl ClickState {
+    fn capture(click: &Click) -> Self {
+        Self {
+            active: click.is_active(),
+            gain: click.gain,
+            bpm: click.bpm,
+            beats: click.beats,
+            orbit: click.orbit,
+            cue: click.cue,
+        }
+    }
+
+    fn apply(&self, click: &mut Click) {
+        click.gain = self.gain;
+        click.bpm = self.bpm;
+        click.beats = self.beats;
+        click.orbit = self.orbit;
+        click.cue = self.cue;
+        click.set_active(self.active);
+    }
+}
+
+impl Engine {
+    /// Captures the session into a new state. Allocates; on the audio
+    /// thread use [`snapshot_into`](Self::snapshot_into).
+    pub fn snapshot(&mut self) -> EngineState {
+        let mut state = EngineState::default();
+        self.snapshot_into(&mut state);
+        state
+    }
+
+    /// Captures the session into `state`, reusing its buffers: one built
+    /// with [`EngineState::reserved`] only grows for scheduled events'
+    /// strings. Voices' LFO and pressure offsets are taken out first, so
+    /// the saved params are the ones that were set.
+    pub fn snapshot_into(&mut self, state: &mut EngineState) {
+        state.sr = self.sr;
+        state.time = self.time;
+        state.tick = self.tick;
+        state.tempo = TempoState {
+            bpm: self.tempo.bpm(),
+            beat: self.tempo.beat_at(self.tick),
+        };
+
+        let active = self.active_voices.min(self.voices.len());
+        fit(
+            &mut state.voices,
+            &mut state.spare_voices,
+            active,
+            VoiceState::reserved,
+        );
+        for (v, saved) in self.voices[..active].iter_mut().zip(&mut state.voices) {
+            v.remove_mod_offsets();
+            #[cfg(feature = "native")]
+            let (sample, range) = match &v.registry_sample {
+                Some(rs) => {
+                    let frames = rs.data.frame_count.max(1) as f64;
+                    (
+                        rs.sample_name.clone(),
+                        (
+                            (rs.cursor_start() / frames) as f32,
+                            (rs.cursor_end() / frames) as f32,
+                        ),
+                    )
+                }
+                None => (None, (0.0, 1.0)),
+            };
+            #[cfg(not(feature = "native"))]
+            let (sample, range) = (None, (0.0, 1.0));
+            saved.params = v.params;
+            saved.mods.clear();
+            saved.mods.extend(
+                v.param_mods[..v.param_mod_count as usize]
+                    .iter()
+                    .map(|(id, m)| (*id, m.chain.clone())),
+            );
+            saved.sample = sample;
+            saved.range = range;
+            saved.gate_end = v.gate_end;
+        }
+
+        fit(
+            &mut state.orbits,
+            &mut state.spare_orbits,
+            self.orbits.len(),
+            OrbitState::reserved,
+        );
+        for (orbit, saved) in self.orbits.iter().zip(&mut state.orbits) {
+            saved.capture(orbit);
+        }
+
+        state.master = MasterState::capture(self);
+        state.out_mode = self.out_mode;
+        state.routes.clear();
+        state.routes.extend_from_slice(self.routes.routes());
+        state.click = ClickState::capture(&self.click);
+        state.schedule.clear();
+        state.schedule.extend(self.schedule.iter().cloned());
+    }
+
+    /// The active voices as of the last block, for debugging what is
+    /// sounding. See [`voice_details_into`](Self::voice_details_into) for
+    /// the audio thread.
+    pub fn voice_details(&self) -> Vec<VoiceDetail> {
+        let mut voices = Vec::with_capacity(self.active_voices);
+        self.voice_details_into(&mut voices);
+        voices
+    }
+
+    /// Like [`voice_details`](Self::voice_details), into `out`. Voices past
+    /// its capacity are left out, so it never allocates: a host reaching it
+    /// through [`AudioCmd::VoiceDetails`](crate::AudioCmd::VoiceDetails)
+    /// sends a buffer with room for [`MAX_VOICES`](crate::types::MAX_VOICES).
+    pub fn voice_details_into(&self, out: &mut Vec<VoiceDetail>) {
+        out.clear();
+        let active = self.active_voices.min(self.voices.len());
+        let room = out.capacity();
+        for (index, v) in self.voices[..active].iter().enumerate().take(room) {
+            let p = &v.params;
+            #[cfg(feature = "native")]
+            let sample = v
+                .registry_sample
+                .as_ref()
+                .and_then(|rs| rs.sample_name.clone());
+            #[cfg(not(feature = "native"))]
+            let sample = None;
+            out.push(VoiceDetail {
+                index,
+                id: v.id,
+                source: p.sound.info().module.name,
+                sample,
+                freq: p.freq,
+                gain: p.gain,
+                velocity: p.velocity,
+                orbit: p.orbit,
+                stage: v.dahdsr.state(),
+                env: v.dahdsr.value(),
+                sends: std::array::from_fn(|i| (p.send_orbits[i], p.send_levels[i])),
+                age: v.time,
+            });
+        }
+    }
+
+    /// Replaces the session with `state`. Ticks are rescaled when it was
+    /// saved at another sample rate. Sample voices whose sample is not
+    /// loaded yet are dropped; the load is requested so a later restore
+    /// finds it.
+    pub fn restore(&mut self, state: &EngineState) {
+        self.restore_from(&mut state.clone());
+    }
+
+    /// Like [`restore`](Self::restore), moving the scheduled events out of
+    /// `state` instead of cloning them. Nothing else in `state` changes,
+    /// and nothing allocates, so the audio thread can run it and hand the
+    /// state back to be dropped elsewhere.
+    pub fn restore_from(&mut self, state: &mut EngineState) {
+        let scale = self.sr as f64 / state.sr.max(1.0) as f64;
+        let rescale = |tick: u64| (tick as f64 * scale) as u64;
+
+        self.panic_now();
+        self.schedule.clear();
+        self.time = state.time;
+        self.tick = rescale(state.tick);
+        self.tempo.set_bpm(state.tempo.bpm, self.tick);
+        self.tempo.set_beat(state.tempo.beat, self.tick);
+
+        if !state.orbits.is_empty() {
+            self.set_orbit_count(state.orbits.len());
+        }
+        for (orbit, saved) in self.orbits.iter_mut().zip(&state.orbits) {
+            saved.apply(orbit);
+        }
+        state.master.apply(self);
+        self.out_mode = state.out_mode;
+        self.routes.clear();
+        for &route in &state.routes {
+            self.routes.add(route);
+        }
+        state.click.apply(&mut self.click);
+
+        for saved in &state.voices {
+            #[cfg(feature = "native")]
+            let sample = match &saved.sample {
+                Some(name) => match self.restore_sample(name) {
+                    Some(found) => Some(found),
+                    None => continue,
+                },
+                None => None,
+            };
+            let Some(i) = self.play(saved.params) else {
+                break;
+            };
+            let v = &mut self.voices[i];
+            #[cfg(feature = "native")]
+            if let Some((name, data)) = sample {
+                let (begin, end) = saved.range;
+                v.registry_sample = Some(crate::sampling::RegistrySample::new(
+                    Some(name),
+                    data,
+                    begin,
+                    end,
+                ));
+            }
+            for (id, chain) in &saved.mods {
+                v.set_mod(*id, chain.clone());
+            }
+            v.gate_end = saved.gate_end.map(rescale);
+        }
+
+        for mut event in state.schedule.drain(..) {
+            event.tick = event.tick.map(rescale);
+            event.endtick = event.endtick.map(rescale);
+            self.schedule.push(event);
+        }
+    }
+
+    /// Looks up a registry sample by its full name (`folder/n`), asking the
+    /// loader for it when it is not in memory.
+    #[cfg(feature = "native")]
+    fn restore_sample(
+        &mut self,
+        name: &str,
+    ) -> Option<(std::sync::Arc<str>, std::sync::Arc<crate::SampleData>)> {
+        let entry = self.sample_index.iter().find(|e| e.name.as_ref() == name)?;
+        let (name, path) = (entry.name.clone(), entry.path.clone());
+        match self.sample_registry.get(&name) {
+            Some(data) => Some((name, data)),
+            None => {
+                self.sample_loader.request(name, path, self.sr);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_session_survives_json_and_another_sample_rate() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/saw/freq/220/lpf/200~2000:0.5t/gate/2/orbit/1");
+        engine.evaluate("/doux/orbit/1/verb/0.4/verbtype/plate/delaytime/0.3");
+        engine.evaluate("/sound/sine/note/72/time/5");
+        let mut out = vec![0.0; 128];
+        engine.process_block(&mut out, &[], &[]);
+
+        let json = engine.snapshot().to_json().unwrap();
+        // Unset fields are left out
+        assert!(!json.contains(":null"));
+        let state = EngineState::from_json(&json).unwrap();
+
+        let mut restored = Engine::new_with_channels(96000.0, 2, 8, 64);
+        restored.restore(&state);
+        assert_eq!(restored.active_voices, 1);
+        assert_eq!(restored.tick, 128);
+        let (a, b) = (&engine.voices[0], &restored.voices[0]);
+        assert_eq!(
+            (a.params.sound, a.params.freq),
+            (b.params.sound, b.params.freq)
+        );
+        assert_eq!(b.param_mod_count, 1);
+        assert_eq!(b.gate_end, a.gate_end.map(|t| t * 2));
+        let orbit = &restored.orbits[1];
+        assert_eq!(orbit.verb_level, 0.4);
+        assert_eq!(orbit.reverb_params.verb_type, ReverbType::Plate);
+        assert_eq!(orbit.delay.params.time, 0.3);
+        assert_eq!(restored.schedule.peek_tick(), Some(5 * 96000));
+    }
+
+    #[test]
+    fn a_reserved_state_fills_in_place_and_carries_the_bus_settings() {
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/orbits/n/3");
+        engine.evaluate("/doux/master/gain/0.5/limit/0.9/eq1/pk:120:-4:2");
+        engine.evaluate("/doux/outmode/stereo");
+        engine.evaluate("/doux/route/orbit/2/outch/3/outmono/1");
+        engine.evaluate("/doux/click/1/clickgain/0.3/clickbeats/3");
+        engine.evaluate("/doux/bpm/90");
+        engine.evaluate("/sound/saw/lpf/200~2000:0.5t");
+        engine.evaluate("/sound/sine/beat/8");
+        let mut out = vec![0.0; 256];
+        engine.process_block(&mut out, &[], &[]);
+
+        let mut state = EngineState::reserved();
+        let buffers = (state.voices.as_ptr(), state.orbits.as_ptr());
+        engine.snapshot_into(&mut state);
+        engine.snapshot_into(&mut state);
+        assert_eq!((state.voices.as_ptr(), state.orbits.as_ptr()), buffers);
+        assert_eq!(state.voices.len(), engine.active_voices);
+        assert_eq!(state.orbits.len(), 3);
+        assert_eq!(state.voices[0].mods.capacity(), MAX_PARAM_MODS);
+
+        let mut restored = Engine::new_with_channels(48000.0, 4, 8, 64);
+        restored.restore_from(&mut state);
+        assert!(state.schedule.is_empty());
+        assert_eq!(restored.orbits.len(), 3);
+        assert_eq!((restored.master.gain, restored.master.limit), (0.5, 0.9));
+        assert_eq!(restored.master.eq[0], engine.master.eq[0]);
+        assert_eq!(restored.out_mode, OutMode::Stereo);
+        assert_eq!(restored.routes.routes(), engine.routes.routes());
+        assert!(restored.click.is_active());
+        assert_eq!((restored.click.gain, restored.click.beats), (0.3, 3));
+        assert_eq!(restored.tempo.bpm(), 90.0);
+        assert_eq!(
+            restored.tempo.beat_at(restored.tick),
+            engine.tempo.beat_at(engine.tick)
+        );
+        assert_eq!(restored.schedule.peek_tick(), engine.schedule.peek_tick());
+    }
+
+    #[test]
+    fn voice_details_show_what_is_sounding() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 512);
+        engine.evaluate("/sound/saw/freq/220/gain/0.8/orbit/1/orbit2/3/orbit2send/0.5/gate/0");
+        engine.evaluate("/sound/sine/attack/1/gate/0");
+        let mut out = vec![0.0; 480 * 2];
+        engine.process_block(&mut out, &[], &[]);
+
+        let voices = engine.voice_details();
+        assert_eq!(voices.len(), 2);
+        let saw = &voices[0];
+        assert_eq!((saw.index, saw.id), (0, engine.voices[0].id));
+        assert_eq!(saw.sound(), "saw");
+        assert_eq!((saw.freq, saw.gain, saw.orbit), (220.0, 0.8, 1));
+        assert_eq!(saw.active_sends().collect::<Vec<_>>(), [(3, 0.5)]);
+        assert_eq!(saw.stage, DahdsrState::Sustain);
+        assert!((saw.age - 0.01).abs() < 1e-4);
+        assert_eq!(voices[1].stage, DahdsrState::Attack);
+
+        let json = serde_json::to_string(&voices).unwrap();
+        assert!(json.contains(r#""sound":"sine""#));
+        assert!(json.contains(r#""sends":[[3,0.5]]"#));
+        assert!(json.contains(r#""stage":"attack""#));
+
+        // A buffer without room gets as many voices as fit
+        let mut few = Vec::with_capacity(1);
+        engine.voice_details_into(&mut few);
+        assert_eq!((few.len(), few.capacity()), (1, 1));
+    }
+}
@@ -0,0 +1,360 @@
+//! Recording taps: an orbit's or a single voice's output streamed to a WAV
+//! file while the engine plays. Native only.
+//!
+//! | Command                           | Effect                                        |
+//! |-----------------------------------|-----------------------------------------------|
+//! | `/doux/record/orbit/<n>/start`    | Record orbit `n` after its effects            |
+//! | `/doux/record/voice/<vid>/start`  | Record voice `vid` until it ends              |
+//! | `/doux/record/<orbit\|voice>/<n>/stop` | Finish the file                          |
+//!
+//! The audio thread copies each tapped frame into a lock-free ring; a writer
+//! thread drains the rings into `orbit<n>-<stamp>.wav` or
+//! `voice<vid>-<stamp>.wav`. Up to [`MAX_TAPS`] record at once. When the
+//! disk falls behind and a ring fills, frames are dropped and the writer
+//! says so as the file is finished.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+
+use crate::recorder::write_wav_header;
+use crate::types::CHANNELS;
+
+/// Most taps recording at once.
+pub const MAX_TAPS: usize = 8;
+/// Seconds of output a ring holds while the writer catches up.
+const RING_SECONDS: f32 = 2.0;
+const DRAIN_INTERVAL: Duration = Duration::from_millis(20);
+
+/// What a tap records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TapTarget {
+    /// An orbit's output, after its effects.
+    Orbit(usize),
+    /// A voice's output by stable ID, before its orbit.
+    Voice(u32),
+}
+
+impl fmt::Display for TapTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Orbit(n) => write!(f, "orbit{n}"),
+            Self::Voice(id) => write!(f, "voice{id}"),
+        }
+    }
+}
+
+enum Msg {
+    Start(usize, TapTarget),
+    Stop(usize, bool),
+}
+
+struct Slot {
+    target: Option<TapTarget>,
+    prod: HeapProd<f32>,
+    dropped: bool,
+}
+
+/// The engine's side of the taps. See the [module docs](self).
+pub struct Taps {
+    slots: Vec<Slot>,
+    active: usize,
+    tx: Sender<Msg>,
+    writer: JoinHandle<()>,
+}
+
+impl Taps {
+    /// Allocates the rings and starts the writer thread, which puts its
+    /// files in `dir`.
+    pub fn spawn(dir: PathBuf, sample_rate: f32) -> std::io::Result<Self> {
+        let len = (RING_SECONDS * sample_rate) as usize * CHANNELS;
+        let (prods, cons): (Vec<_>, Vec<_>) = (0..MAX_TAPS)
+            .map(|_| HeapRb::<f32>::new(len).split())
+            .unzip();
+        // Each slot has at most a start and a stop in flight
+        let (tx, rx) = bounded(MAX_TAPS * 2);
+        let writer = std::thread::Builder::new()
+            .name("doux-tap".into())
+            .spawn(move || run_writer(rx, cons, &dir, sample_rate))?;
+        let slots = prods
+            .into_iter()
+            .map(|prod| Slot {
+                target: None,
+                prod,
+                dropped: false,
+            })
+            .collect();
+        Ok(Self {
+            slots,
+            active: 0,
+            tx,
+            writer,
+        })
+    }
+
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active > 0
+    }
+
+    pub fn is_recording(&self, target: TapTarget) -> bool {
+        self.slots.iter().any(|s| s.target == Some(target))
+    }
+
+    /// Starts recording `target`. False if it already is or every tap is
+    /// busy.
+    pub fn start(&mut self, target: TapTarget) -> bool {
+        if self.is_recording(target) {
+            return false;
+        }
+        let Some(i) = self.slots.iter().position(|s| s.target.is_none()) else {
+            return false;
+        };
+        if self.tx.try_send(Msg::Start(i, target)).is_err() {
+            return false;
+        }
+        self.slots[i].target = Some(target);
+        self.slots[i].dropped = false;
+        self.active += 1;
+        true
+    }
+
+    /// Finishes the file of `target`. False if it wasn't recording.
+    pub fn stop(&mut self, target: TapTarget) -> bool {
+        let Some(i) = self.slots.iter().position(|s| s.target == Some(target)) else {
+            return false;
+        };
+        let slot = &mut self.slots[i];
+        let _ = self.tx.try_send(Msg::Stop(i, slot.dropped));
+        slot.target = None;
+        self.active -= 1;
+        true
+    }
+
+    /// Hands a frame of `target`'s output to its tap, if it has one.
+    #[inline]
+    pub fn capture(&mut self, target: TapTarget, frame: [f32; CHANNELS]) {
+        for slot in &mut self.slots {
+            if slot.target != Some(target) {
+                continue;
+            }
+            if slot.prod.vacant_len() >= CHANNELS {
+                slot.prod.push_slice(&frame);
+            } else {
+                slot.dropped = true;
+            }
+        }
+    }
+
+    /// Stops every tap and waits for the writer to finish their files.
+    pub fn close(self) {
+        let Self { tx, writer, .. } = self;
+        drop(tx);
+        let _ = writer.join();
+    }
+}
+
+struct TapFile {
+    target: TapTarget,
+    path: PathBuf,
+    w: BufWriter<File>,
+    samples: usize,
+}
+
+impl TapFile {
+    fn create(dir: &Path, target: TapTarget, sample_rate: f32) -> std::io::Result<Self> {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let path = dir.join(format!("{target}-{stamp}.wav"));
+        let mut w = BufWriter::new(File::create(&path)?);
+        // Sizes are filled in when the file is finished
+        write_wav_header(&mut w, CHANNELS, sample_rate, 0)?;
+        Ok(Self {
+            target,
+            path,
+            w,
+            samples: 0,
+        })
+    }
+
+    fn write(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        for s in samples {
+            self.w.write_all(&s.to_le_bytes())?;
+        }
+        self.samples += samples.len();
+        Ok(())
+    }
+
+    fn finish(mut self, sample_rate: f32, dropped: bool) {
+        let written = self.w.flush().and_then(|()| {
+            let mut file = self.w.get_ref();
+            file.seek(SeekFrom::Start(0))?;
+            write_wav_header(&mut file, CHANNELS, sample_rate, self.samples)
+        });
+        match written {
+            Ok(()) => {
+                let secs = (self.samples / CHANNELS) as f32 / sample_rate;
+                println!(
+                    "Recorded {} ({secs:.1}s) to {}",
+                    self.target,
+                    self.path.display()
+                );
+                if dropped {
+                    eprintln!(
+                        "[doux] {}: the disk fell behind, frames were dropped",
+                        self.target
+                    );
+                }
+            }
+            Err(e) => eprintln!("Record error ({}): {e}", self.path.display()),
+        }
+    }
+}
+
+fn run_writer(rx: Receiver<Msg>, mut rings: Vec<HeapCons<f32>>, dir: &Path, sample_rate: f32) {
+    let mut files: Vec<Option<TapFile>> = (0..rings.len()).map(|_| None).collect();
+    let mut buf = vec![0.0f32; 4096];
+    loop {
+        let msg = rx.recv_timeout(DRAIN_INTERVAL);
+        if let Ok(Msg::Start(i, target)) = msg {
+            match TapFile::create(dir, target, sample_rate) {
+                Ok(file) => files[i] = Some(file),
+                Err(e) => eprintln!("Record error ({target}): {e}"),
+            }
+        }
+        // Drained after a start opens its file and before a stop finishes
+        // it, so neither loses frames. A ring whose start is still queued
+        // keeps its frames until then.
+        for (ring, file) in rings.iter_mut().zip(&mut files) {
+            let Some(f) = file else {
+                continue;
+            };
+            loop {
+                let n = ring.pop_slice(&mut buf);
+                if n == 0 {
+                    break;
+                }
+                if let Err(e) = f.write(&buf[..n]) {
+                    eprintln!("Record error ({}): {e}", f.path.display());
+                    *file = None;
+                    break;
+                }
+            }
+        }
+        match msg {
+            Ok(Msg::Stop(i, dropped)) => {
+                match files[i].take() {
+                    Some(file) => file.finish(sample_rate, dropped),
+                    // The file failed; drop what it didn't take
+                    None => {
+                        rings[i].clear();
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                for file in files.iter_mut().filter_map(Option::take) {
+                    file.finish(sample_rate, false);
+                }
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Engine;
+
+    #[test]
+    fn taps_stream_frames_to_wav_files() {
+        let dir = std::env::temp_dir().join(format!("doux-tap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut taps = Taps::spawn(dir.clone(), 1000.0).unwrap();
+        assert!(!taps.is_active());
+        assert!(taps.start(TapTarget::Orbit(1)));
+        assert!(!taps.start(TapTarget::Orbit(1)));
+        assert!(taps.start(TapTarget::Voice(7)));
+        for i in 0..300 {
+            taps.capture(TapTarget::Orbit(1), [i as f32, -(i as f32)]);
+            taps.capture(TapTarget::Orbit(0), [1.0, 1.0]);
+        }
+        assert!(taps.stop(TapTarget::Orbit(1)));
+        assert!(!taps.stop(TapTarget::Orbit(1)));
+        assert!(taps.is_active());
+        taps.close();
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        files.sort();
+        let names: Vec<_> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names[0].starts_with("orbit1-") && names[1].starts_with("voice7-"));
+        let orbit = std::fs::read(&files[0]).unwrap();
+        let voice = std::fs::read(&files[1]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(orbit.len(), 44 + 300 * 2 * 4);
+        assert_eq!(u32::from_le_bytes(orbit[40..44].try_into().unwrap()), 2400);
+        let sample =
+            |i: usize| f32::from_le_bytes(orbit[44 + i * 4..48 + i * 4].try_into().unwrap());
+        assert_eq!((sample(2 * 299), sample(2 * 299 + 1)), (299.0, -299.0));
+        assert_eq!(voice.len(), 44);
+    }
+
+    #[test]
+    fn record_taps_write_orbits_and_voices_to_disk() {
+        let dir = std::env::temp_dir().join(format!("doux-record-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut engine = Engine::new_with_channels(1000.0, 2, 8, 64);
+        engine.set_tap_dir(dir.clone()).unwrap();
+        engine.evaluate("/sound/sine/orbit/1/gate/0.05/release/0.01");
+        let id = engine.voices[0].id;
+        engine.evaluate("/doux/record/orbit/1/start");
+        engine.evaluate(&format!("/doux/record/voice/{id}/start"));
+        engine.evaluate("/doux/record/voice/999/start");
+        let mut out = vec![0.0; 128];
+        for _ in 0..4 {
+            engine.process_block(&mut out, &[], &[]);
+        }
+        assert_eq!(engine.active_voices, 0);
+        engine.evaluate("/doux/record/orbit/1/stop");
+        engine.close_taps();
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        files.sort();
+        let sizes: Vec<_> = files
+            .iter()
+            .map(|p| std::fs::metadata(p).unwrap().len())
+            .collect();
+        let orbit = std::fs::read(&files[0]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // The whole 256 frames of the orbit; the voice until it ended
+        assert_eq!(files.len(), 2);
+        assert_eq!(sizes[0], 44 + 256 * 2 * 4);
+        assert!(
+            sizes[1] > 44 + 50 * 2 * 4 && sizes[1] < sizes[0],
+            "{sizes:?}"
+        );
+        assert!(orbit[44..].iter().any(|&b| b != 0));
+    }
+}
@@ -1,12 +1,42 @@
 //! Audio engine telemetry. Native only.
 
-use serde::Serialize;
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
 use std::time::Instant;
 
+use crate::dsp::{DahdsrState, SPECTRUM_BINS};
+use crate::orbit::ORBIT_RETURNS;
+use crate::types::{DEFAULT_ORBITS, MAX_ORBITS};
+
 const LOAD_SCALE: f32 = 1_000_000.0; // fixed-point for atomic float storage
 const DEFAULT_SMOOTHING: f32 = 0.6;
 const PROFILE_PHASE_COUNT: usize = 8;
+/// Voices whose envelope is published each block; voices past it are left out.
+pub const MAX_VOICE_ENVELOPES: usize = 128;
+/// Envelope stages in the order they are packed into [`EngineMetrics::voice_env`].
+const ENV_STAGES: [DahdsrState; 7] = [
+    DahdsrState::Off,
+    DahdsrState::Delay,
+    DahdsrState::Attack,
+    DahdsrState::Hold,
+    DahdsrState::Decay,
+    DahdsrState::Sustain,
+    DahdsrState::Release,
+];
+/// Log2 buckets of call duration: bucket 0 is under ~1 µs, each next one
+/// doubles, the last one catches everything from ~16 ms up.
+pub const HISTOGRAM_BUCKETS: usize = 16;
+
+/// Histogram bucket for a call that took `ns`.
+#[inline]
+pub fn histogram_bucket(ns: u64) -> usize {
+    ((u64::BITS - (ns >> 10).leading_zeros()) as usize).min(HISTOGRAM_BUCKETS - 1)
+}
+
+/// Upper bound (ns) of histogram bucket `idx`.
+pub const fn histogram_bucket_limit(idx: usize) -> u64 {
+    1024 << idx
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -71,6 +101,11 @@ pub struct ProfilingSnapshot {
     pub total_samples: u64,
     pub total_blocks: u64,
     pub phases: [PhaseProfile; PROFILE_PHASE_COUNT],
+    /// Call durations per phase, see [`HISTOGRAM_BUCKETS`].
+    pub histograms: [[u64; HISTOGRAM_BUCKETS]; PROFILE_PHASE_COUNT],
+    /// Heap allocations made inside `process_block` (needs
+    /// [`CountingAllocator`] installed as the global allocator).
+    pub audio_allocations: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize)]
@@ -81,6 +116,9 @@ pub struct PhaseSummary {
     pub calls: u64,
     pub ns_per_sample: f64,
     pub percent_total: f64,
+    /// Median and 99th percentile call time, as histogram bucket bounds.
+    pub p50_ns: u64,
+    pub p99_ns: u64,
 }
 
 impl ProfilingSnapshot {
@@ -91,6 +129,31 @@ impl ProfilingSnapshot {
             dst.total_ns += src.total_ns;
             dst.calls += src.calls;
         }
+        for (dst, src) in self.histograms.iter_mut().zip(other.histograms.iter()) {
+            for (d, s) in dst.iter_mut().zip(src) {
+                *d += s;
+            }
+        }
+        self.audio_allocations += other.audio_allocations;
+    }
+
+    /// Call time at percentile `p` (0–1) for `phase`, as the upper bound of
+    /// its histogram bucket. 0 when the phase has no recorded calls.
+    pub fn percentile_ns(&self, phase: ProfilePhase, p: f64) -> u64 {
+        let hist = &self.histograms[phase.index()];
+        let calls: u64 = hist.iter().sum();
+        if calls == 0 {
+            return 0;
+        }
+        let rank = ((calls as f64 * p).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, count) in hist.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return histogram_bucket_limit(idx);
+            }
+        }
+        histogram_bucket_limit(HISTOGRAM_BUCKETS - 1)
     }
 
     pub fn phase(&self, phase: ProfilePhase) -> PhaseProfile {
@@ -116,6 +179,8 @@ impl ProfilingSnapshot {
                     calls: stat.calls,
                     ns_per_sample: stat.total_ns as f64 / samples,
                     percent_total,
+                    p50_ns: self.percentile_ns(phase, 0.5),
+                    p99_ns: self.percentile_ns(phase, 0.99),
                 }
             })
             .filter(|summary| summary.total_ns > 0)
@@ -135,6 +200,7 @@ impl ProfilingSnapshot {
 struct PhaseCounters {
     total_ns: AtomicU64,
     calls: AtomicU64,
+    histogram: [AtomicU64; HISTOGRAM_BUCKETS],
 }
 
 /// Optional aggregate hotspot profiler for native engine development.
@@ -155,6 +221,7 @@ impl EngineProfiler {
             let counters = &self.phases[phase.index()];
             counters.total_ns.fetch_add(elapsed_ns, Ordering::Relaxed);
             counters.calls.fetch_add(1, Ordering::Relaxed);
+            counters.histogram[histogram_bucket(elapsed_ns)].fetch_add(1, Ordering::Relaxed);
         }
         #[cfg(not(feature = "profiling"))]
         {
@@ -183,7 +250,11 @@ impl EngineProfiler {
             for counters in &self.phases {
                 counters.total_ns.store(0, Ordering::Relaxed);
                 counters.calls.store(0, Ordering::Relaxed);
+                for bucket in &counters.histogram {
+                    bucket.store(0, Ordering::Relaxed);
+                }
             }
+            alloc::AUDIO_ALLOCATIONS.store(0, Ordering::Relaxed);
         }
     }
 
@@ -194,12 +265,17 @@ impl EngineProfiler {
                 total_samples: self.total_samples.load(Ordering::Relaxed),
                 total_blocks: self.total_blocks.load(Ordering::Relaxed),
                 phases: [PhaseProfile::default(); PROFILE_PHASE_COUNT],
+                histograms: [[0; HISTOGRAM_BUCKETS]; PROFILE_PHASE_COUNT],
+                audio_allocations: alloc::AUDIO_ALLOCATIONS.load(Ordering::Relaxed),
             };
             for (idx, counters) in self.phases.iter().enumerate() {
                 snapshot.phases[idx] = PhaseProfile {
                     total_ns: counters.total_ns.load(Ordering::Relaxed),
                     calls: counters.calls.load(Ordering::Relaxed),
                 };
+                for (dst, bucket) in snapshot.histograms[idx].iter_mut().zip(&counters.histogram) {
+                    *dst = bucket.load(Ordering::Relaxed);
+                }
             }
             snapshot
         }
@@ -210,6 +286,76 @@ impl EngineProfiler {
     }
 }
 
+#[cfg(feature = "profiling")]
+pub use alloc::{AudioThreadScope, CountingAllocator};
+
+/// Audio-thread allocation counting for the `profiling` feature.
+#[cfg(feature = "profiling")]
+mod alloc {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub(super) static AUDIO_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+    thread_local! {
+        static IN_AUDIO: Cell<bool> = const { Cell::new(false) };
+    }
+
+    #[inline]
+    fn count() {
+        if IN_AUDIO.try_with(Cell::get).unwrap_or(false) {
+            AUDIO_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// System allocator that counts allocations made inside an
+    /// [`AudioThreadScope`]. Install it in a binary:
+    ///
+    /// ```ignore
+    /// #[global_allocator]
+    /// static ALLOC: doux::telemetry::CountingAllocator = doux::telemetry::CountingAllocator;
+    /// ```
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count();
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            count();
+            unsafe { System.alloc_zeroed(layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count();
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    /// Marks the current thread as the audio thread until dropped.
+    pub struct AudioThreadScope(());
+
+    impl AudioThreadScope {
+        pub fn enter() -> Self {
+            IN_AUDIO.with(|f| f.set(true));
+            Self(())
+        }
+    }
+
+    impl Drop for AudioThreadScope {
+        fn drop(&mut self) {
+            let _ = IN_AUDIO.try_with(|f| f.set(false));
+        }
+    }
+}
+
 /// Measures DSP load: the fraction of the audio callback deadline consumed
 /// by `process_block`.
 ///
@@ -302,6 +448,87 @@ impl Drop for ScopedTimer<'_> {
     }
 }
 
+/// Set on [`SpectrumBuffer`]'s spare index when it holds a newer spectrum
+/// than the reader's.
+const SPECTRUM_FRESH: u8 = 4;
+
+/// Output spectrum handed from the audio thread to a UI, off by default.
+///
+/// A triple buffer: the engine fills one set of bins while the reader holds
+/// another, and the spare carries the newest complete set between them, so
+/// neither side waits. Bins are linear magnitudes of the first output pair
+/// (see [`SpectrumAnalyzer`](crate::dsp::SpectrumAnalyzer)), one set per
+/// block. Meant for a single reader.
+pub struct SpectrumBuffer {
+    enabled: AtomicBool,
+    bins: [[AtomicU32; SPECTRUM_BINS]; 3],
+    /// Set the engine writes next (engine-owned).
+    write: AtomicU8,
+    /// Set last handed to the reader (reader-owned).
+    read: AtomicU8,
+    /// Spare set, with [`SPECTRUM_FRESH`] when unread.
+    spare: AtomicU8,
+    /// Spectra published so far.
+    pub frames: AtomicU64,
+}
+
+impl Default for SpectrumBuffer {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            bins: std::array::from_fn(|_| std::array::from_fn(|_| AtomicU32::new(0))),
+            write: AtomicU8::new(0),
+            read: AtomicU8::new(1),
+            spare: AtomicU8::new(2),
+            frames: AtomicU64::new(0),
+        }
+    }
+}
+
+impl SpectrumBuffer {
+    /// Turns the analyzer on or off; it costs one FFT per block while on.
+    pub fn set_enabled(&self, on: bool) {
+        self.enabled.store(on, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Publishes a spectrum. Called by the engine once per block.
+    pub fn publish(&self, mags: &[f32]) {
+        let write = self.write.load(Ordering::Relaxed);
+        for (bin, &mag) in self.bins[write as usize].iter().zip(mags) {
+            bin.store(mag.to_bits(), Ordering::Relaxed);
+        }
+        let prev = self.spare.swap(write | SPECTRUM_FRESH, Ordering::AcqRel);
+        self.write.store(prev & !SPECTRUM_FRESH, Ordering::Relaxed);
+        self.frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Copies the newest spectrum into `out` (up to [`SPECTRUM_BINS`]
+    /// values). Returns `false` if it is the one read last time.
+    pub fn read_into(&self, out: &mut [f32]) -> bool {
+        let mut read = self.read.load(Ordering::Relaxed);
+        let fresh = self.spare.load(Ordering::Relaxed) & SPECTRUM_FRESH != 0;
+        if fresh {
+            read = self.spare.swap(read, Ordering::AcqRel) & !SPECTRUM_FRESH;
+            self.read.store(read, Ordering::Relaxed);
+        }
+        for (o, bin) in out.iter_mut().zip(&self.bins[read as usize]) {
+            *o = f32::from_bits(bin.load(Ordering::Relaxed));
+        }
+        fresh
+    }
+
+    /// The newest spectrum, [`SPECTRUM_BINS`] linear magnitudes.
+    pub fn latest(&self) -> Vec<f32> {
+        let mut out = vec![0.0; SPECTRUM_BINS];
+        self.read_into(&mut out);
+        out
+    }
+}
+
 /// Aggregated engine metrics. All fields atomic for cross-thread access.
 pub struct EngineMetrics {
     pub load: ProcessLoadMeasurer,
@@ -311,7 +538,66 @@ pub struct EngineMetrics {
     pub schedule_depth: AtomicU32,
     pub sample_pool_bytes: AtomicU64,
     pub time_bits: AtomicU64,
+    /// Engine tick at the end of the last block.
+    pub tick: AtomicU64,
     pub dropped_events: AtomicU32,
+    /// Total orbit FX sleep / wake transitions (silence gate).
+    pub orbit_sleeps: AtomicU64,
+    pub orbit_wakes: AtomicU64,
+    /// Master frames at or above the `peakwarn` threshold.
+    pub hot_frames: AtomicU64,
+    /// Orbits the engine currently runs.
+    pub orbit_count: AtomicU32,
+    /// Per-orbit output envelope at the end of the last block (`f32` bits).
+    pub orbit_env: [AtomicU32; MAX_ORBITS],
+    /// Per-orbit stereo correlation at the end of the last block (`f32` bits).
+    pub orbit_corr: [AtomicU32; MAX_ORBITS],
+    pub master_corr: AtomicU32,
+    /// Buses in sustained anti-correlation (`monowarn`): bit `i` for orbit
+    /// `i`, bit [`MAX_ORBITS`] for the master.
+    pub anti_phase: AtomicU32,
+    /// Mono-compatibility warnings raised so far.
+    pub mono_warnings: AtomicU64,
+    /// Per-orbit envelope of each effect return (comb, feedback, delay, reverb).
+    pub return_env: [[AtomicU32; ORBIT_RETURNS]; MAX_ORBITS],
+    /// Envelope of the first `voice_env_count` active voices, packed as
+    /// voice ID, stage and 16-bit value.
+    pub voice_env: [AtomicU64; MAX_VOICE_ENVELOPES],
+    pub voice_env_count: AtomicU32,
+    /// Measured input round-trip latency in frames (`u32::MAX` if unknown).
+    pub input_latency: AtomicU32,
+    /// Finished latency calibrations, successful or not.
+    pub calibrations: AtomicU32,
+    /// Output spectrum, published while enabled.
+    pub spectrum: SpectrumBuffer,
+}
+
+/// Envelope progress of one active voice at the end of the last block.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoiceEnvelope {
+    pub voice_id: u32,
+    pub stage: DahdsrState,
+    /// Amplitude envelope value in `[0, 1]`.
+    pub value: f32,
+}
+
+impl VoiceEnvelope {
+    fn pack(self) -> u64 {
+        let stage = ENV_STAGES
+            .iter()
+            .position(|&s| s == self.stage)
+            .unwrap_or(0) as u64;
+        let value = (self.value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u64;
+        (u64::from(self.voice_id) << 32) | (stage << 16) | value
+    }
+
+    fn unpack(bits: u64) -> Self {
+        Self {
+            voice_id: (bits >> 32) as u32,
+            stage: ENV_STAGES[((bits >> 16) & 0xff) as usize % ENV_STAGES.len()],
+            value: (bits & 0xffff) as f32 / u16::MAX as f32,
+        }
+    }
 }
 
 impl Default for EngineMetrics {
@@ -324,7 +610,23 @@ impl Default for EngineMetrics {
             schedule_depth: AtomicU32::new(0),
             sample_pool_bytes: AtomicU64::new(0),
             time_bits: AtomicU64::new(0),
+            tick: AtomicU64::new(0),
             dropped_events: AtomicU32::new(0),
+            orbit_sleeps: AtomicU64::new(0),
+            orbit_wakes: AtomicU64::new(0),
+            hot_frames: AtomicU64::new(0),
+            orbit_count: AtomicU32::new(DEFAULT_ORBITS as u32),
+            orbit_env: std::array::from_fn(|_| AtomicU32::new(0)),
+            orbit_corr: std::array::from_fn(|_| AtomicU32::new(0)),
+            master_corr: AtomicU32::new(0),
+            anti_phase: AtomicU32::new(0),
+            mono_warnings: AtomicU64::new(0),
+            return_env: std::array::from_fn(|_| std::array::from_fn(|_| AtomicU32::new(0))),
+            voice_env: std::array::from_fn(|_| AtomicU64::new(0)),
+            voice_env_count: AtomicU32::new(0),
+            input_latency: AtomicU32::new(u32::MAX),
+            calibrations: AtomicU32::new(0),
+            spectrum: SpectrumBuffer::default(),
         }
     }
 }
@@ -342,6 +644,72 @@ impl EngineMetrics {
         self.profiler.snapshot()
     }
 
+    /// Output envelope of orbit `orbit` (0 if out of range).
+    pub fn orbit_env(&self, orbit: usize) -> f32 {
+        self.orbit_env
+            .get(orbit)
+            .map_or(0.0, |level| f32::from_bits(level.load(Ordering::Relaxed)))
+    }
+
+    /// Stereo correlation of orbit `orbit`, or of the master for `None`
+    /// (0 if out of range).
+    pub fn correlation(&self, orbit: Option<usize>) -> f32 {
+        let corr = match orbit {
+            Some(orbit) => self.orbit_corr.get(orbit),
+            None => Some(&self.master_corr),
+        };
+        corr.map_or(0.0, |c| f32::from_bits(c.load(Ordering::Relaxed)))
+    }
+
+    /// Whether orbit `orbit` (or the master for `None`) is in sustained
+    /// anti-correlation.
+    pub fn anti_phase(&self, orbit: Option<usize>) -> bool {
+        let bit = orbit.unwrap_or(MAX_ORBITS);
+        bit <= MAX_ORBITS && (self.anti_phase.load(Ordering::Relaxed) >> bit) & 1 == 1
+    }
+
+    /// Envelope of return `ret` (comb, feedback, delay, reverb) of orbit
+    /// `orbit` after its return gain (0 if out of range).
+    pub fn return_env(&self, orbit: usize, ret: usize) -> f32 {
+        self.return_env
+            .get(orbit)
+            .and_then(|levels| levels.get(ret))
+            .map_or(0.0, |level| f32::from_bits(level.load(Ordering::Relaxed)))
+    }
+
+    /// Publishes the envelopes of the active voices (up to
+    /// [`MAX_VOICE_ENVELOPES`]). Called by the engine once per block.
+    pub fn store_voice_envelopes(&self, envelopes: impl Iterator<Item = VoiceEnvelope>) {
+        let mut count = 0;
+        for (slot, env) in self.voice_env.iter().zip(envelopes) {
+            slot.store(env.pack(), Ordering::Relaxed);
+            count += 1;
+        }
+        self.voice_env_count.store(count, Ordering::Relaxed);
+    }
+
+    /// Envelope stage and value of the active voices, as of the last block.
+    pub fn voice_envelopes(&self) -> Vec<VoiceEnvelope> {
+        let count = self.voice_env_count.load(Ordering::Relaxed) as usize;
+        self.voice_env[..count.min(MAX_VOICE_ENVELOPES)]
+            .iter()
+            .map(|slot| VoiceEnvelope::unpack(slot.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Publishes the outcome of a latency calibration.
+    pub fn store_input_latency(&self, frames: Option<u32>) {
+        self.input_latency
+            .store(frames.unwrap_or(u32::MAX), Ordering::Relaxed);
+        self.calibrations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Input round-trip latency in frames from the last calibration.
+    pub fn input_latency(&self) -> Option<u32> {
+        let frames = self.input_latency.load(Ordering::Relaxed);
+        (frames != u32::MAX).then_some(frames)
+    }
+
     pub fn sample_pool_mb(&self) -> f32 {
         self.sample_pool_bytes.load(Ordering::Relaxed) as f32 / (1024.0 * 1024.0)
     }
@@ -412,6 +780,41 @@ mod tests {
         assert_eq!(summaries[2].phase, ProfilePhase::FinalMix);
     }
 
+    #[test]
+    fn spectrum_buffer_hands_over_the_newest_set() {
+        let buffer = SpectrumBuffer::default();
+        let mut out = [0.0; 2];
+        assert!(!buffer.read_into(&mut out));
+        buffer.publish(&[1.0, 2.0]);
+        buffer.publish(&[3.0, 4.0]);
+        assert!(buffer.read_into(&mut out));
+        assert_eq!(out, [3.0, 4.0]);
+        // Nothing new: the same set again
+        assert!(!buffer.read_into(&mut out));
+        assert_eq!(out, [3.0, 4.0]);
+        buffer.publish(&[5.0, 6.0]);
+        assert!(buffer.read_into(&mut out));
+        assert_eq!(out, [5.0, 6.0]);
+        assert_eq!(buffer.frames.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn histogram_buckets_double_and_give_percentiles() {
+        assert_eq!(histogram_bucket(0), 0);
+        assert_eq!(histogram_bucket(1_023), 0);
+        assert_eq!(histogram_bucket(1_024), 1);
+        assert_eq!(histogram_bucket(5_000), 3);
+        assert_eq!(histogram_bucket(u64::MAX), HISTOGRAM_BUCKETS - 1);
+
+        let mut snapshot = ProfilingSnapshot::default();
+        let hist = &mut snapshot.histograms[ProfilePhase::OrbitFx.index()];
+        hist[2] = 98;
+        hist[9] = 2;
+        assert_eq!(snapshot.percentile_ns(ProfilePhase::OrbitFx, 0.5), 4_096);
+        assert_eq!(snapshot.percentile_ns(ProfilePhase::OrbitFx, 0.99), 524_288);
+        assert_eq!(snapshot.percentile_ns(ProfilePhase::Schedule, 0.5), 0);
+    }
+
     #[test]
     fn summaries_compute_percent_and_ns_per_sample() {
         let mut snapshot = ProfilingSnapshot {
@@ -0,0 +1,185 @@
+//! Calibrated test signals for checking output routing and levels.
+//!
+//! Driven by `/doux/test/...` commands and mixed straight into the hardware
+//! outputs after the soft clipper, so levels are exactly what was asked for:
+//!
+//! | Command                       | Signal                                   |
+//! |-------------------------------|------------------------------------------|
+//! | `/doux/test/white/<rms>`      | White noise at the given RMS level       |
+//! | `/doux/test/pink/<rms>`       | Pink noise at the given RMS level        |
+//! | `/doux/test/channel/<i>`      | `i + 1` beeps on output `i`, repeating   |
+//! | `/doux/test/off`              | Stop                                     |
+//!
+//! Noise goes to every output unless `/out/<i>` picks one (0-indexed).
+
+use crate::dsp::PinkNoise;
+use crate::voice::modulation::lcg;
+
+/// Measured RMS of `PinkNoise` fed with uniform white noise in `[-1, 1]`.
+const PINK_RMS: f32 = 0.1945;
+const WHITE_RMS: f32 = 0.577_350_26; // 1/√3
+const BEEP_FREQ: f32 = 1000.0;
+const BEEP_LEVEL: f32 = 0.25;
+const BEEP_ON_SECS: f32 = 0.1;
+const BEEP_OFF_SECS: f32 = 0.15;
+const BEEP_REST_SECS: f32 = 0.75;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TestKind {
+    #[default]
+    Off,
+    White,
+    Pink,
+    Channel,
+}
+
+pub struct TestSignal {
+    kind: TestKind,
+    level: f32,
+    out: Option<usize>,
+    pink: PinkNoise,
+    seed: u32,
+    phase: f32,
+    pos: usize,
+    sr: f32,
+}
+
+impl TestSignal {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            kind: TestKind::Off,
+            level: 0.0,
+            out: None,
+            pink: PinkNoise::default(),
+            seed: 0x2545_f491,
+            phase: 0.0,
+            pos: 0,
+            sr,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.kind != TestKind::Off
+    }
+
+    /// Starts a signal from a `/doux/test/<kind>/<value>` command.
+    /// Unknown kinds stop the generator.
+    pub fn start(&mut self, kind: &str, value: Option<f32>, out: Option<usize>) {
+        self.phase = 0.0;
+        self.pos = 0;
+        match kind {
+            "white" | "pink" => {
+                self.kind = if kind == "white" {
+                    TestKind::White
+                } else {
+                    TestKind::Pink
+                };
+                self.level = value.unwrap_or(0.1).clamp(0.0, 1.0);
+                self.out = out;
+            }
+            "channel" => {
+                self.kind = TestKind::Channel;
+                self.level = BEEP_LEVEL;
+                self.out = Some(value.or(out.map(|o| o as f32)).unwrap_or(0.0) as usize);
+            }
+            _ => self.kind = TestKind::Off,
+        }
+    }
+
+    /// Output channel to write to, or `None` for all of them.
+    pub fn target(&self) -> Option<usize> {
+        self.out
+    }
+
+    /// Next sample of the active signal (0 when off).
+    pub fn next(&mut self) -> f32 {
+        match self.kind {
+            TestKind::Off => 0.0,
+            TestKind::White => self.white() * self.level / WHITE_RMS,
+            TestKind::Pink => {
+                let w = self.white();
+                self.pink.next(w) * self.level / PINK_RMS
+            }
+            TestKind::Channel => self.beep(),
+        }
+    }
+
+    fn white(&mut self) -> f32 {
+        self.seed = lcg(self.seed);
+        ((self.seed >> 16) & 0x7fff) as f32 / 32767.0 * 2.0 - 1.0
+    }
+
+    /// `out + 1` beeps followed by a rest, so channel 0 still sounds.
+    fn beep(&mut self) -> f32 {
+        let on = (BEEP_ON_SECS * self.sr) as usize;
+        let slot = on + (BEEP_OFF_SECS * self.sr) as usize;
+        let beeps = self.out.unwrap_or(0) + 1;
+        let cycle = beeps * slot + (BEEP_REST_SECS * self.sr) as usize;
+
+        let pos = self.pos;
+        self.pos = (self.pos + 1) % cycle.max(1);
+        self.phase = (self.phase + BEEP_FREQ / self.sr).fract();
+
+        if pos < beeps * slot && pos % slot < on {
+            (self.phase * std::f32::consts::TAU).sin() * self.level
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    fn rms(sig: &mut TestSignal, n: usize) -> f32 {
+        ((0..n).map(|_| sig.next().powi(2)).sum::<f32>() / n as f32).sqrt()
+    }
+
+    #[test]
+    fn noise_is_calibrated_to_requested_rms() {
+        let mut sig = TestSignal::new(48000.0);
+        sig.start("white", Some(0.2), None);
+        assert!((rms(&mut sig, 200_000) - 0.2).abs() < 0.01);
+        sig.start("pink", Some(0.2), Some(3));
+        assert_eq!(sig.target(), Some(3));
+        assert!((rms(&mut sig, 400_000) - 0.2).abs() < 0.02);
+    }
+
+    #[test]
+    fn channel_ident_beeps_once_per_index_plus_one() {
+        let sr = 8000.0;
+        let mut sig = TestSignal::new(sr);
+        sig.start("channel", Some(2.0), None);
+        assert_eq!(sig.target(), Some(2));
+
+        // One cycle: 3 beep slots of 0.25 s, then a 0.75 s rest
+        let frames: Vec<f32> = (0..3 * 2000 + 6000).map(|_| sig.next().abs()).collect();
+        let loud = |range: std::ops::Range<usize>| frames[range].iter().any(|&s| s > 0.1);
+        assert!(loud(0..800) && loud(2000..2800) && loud(4000..4800));
+        assert!(!loud(800..2000) && !loud(6000..12000));
+
+        sig.start("off", None, None);
+        assert!(!sig.is_active());
+        assert_eq!(sig.next(), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_signal_targets_one_output_until_panic() {
+        let mut engine = Engine::new_with_channels(48000.0, 4, 8, 64);
+        engine.evaluate("/doux/test/pink/0.2/out/3");
+        let mut out = vec![0.0f32; 64 * 4];
+        engine.process_block(&mut out, &[], &[]);
+        assert!(out.chunks(4).all(|f| f[..3] == [0.0; 3]));
+        assert!(out.chunks(4).any(|f| f[3] != 0.0));
+        assert!(!engine.is_idle());
+
+        engine.panic_now();
+        out.fill(0.0);
+        engine.process_block(&mut out, &[], &[]);
+        assert!(out.iter().all(|&s| s == 0.0));
+    }
+}
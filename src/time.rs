@@ -1,8 +1,9 @@
-//! Wall-clock → engine-tick conversion.
+//! Wall-clock and beat → engine-tick conversion.
 //!
-//! Used by the OSC receiver to honor OSC bundle timetags so external clients
-//! (Tidal, Zwirn, …) get sample-accurate scheduling on the same footing as
-//! the in-process Sova/Cagire path.
+//! [`TimeAnchor`] is used by the OSC receiver to honor OSC bundle timetags so
+//! external clients (Tidal, Zwirn, …) get sample-accurate scheduling on the
+//! same footing as the in-process Sova/Cagire path. [`Tempo`] places event
+//! times given in beats (`time/2b`).
 
 /// Seconds between the NTP epoch (1900-01-01) and the Unix epoch (1970-01-01).
 const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
@@ -40,3 +41,156 @@ impl TimeAnchor {
         Some(self.unix_micros_to_tick(unix_micros))
     }
 }
+
+/// Tempo of beat times before any `/doux/bpm`.
+pub const DEFAULT_BPM: f32 = 120.0;
+
+/// The engine's beat clock. Beats count from tick 0 at the current tempo;
+/// a tempo change keeps the beat reached so far and only changes how fast
+/// later beats come.
+#[derive(Clone, Copy, Debug)]
+pub struct Tempo {
+    bpm: f64,
+    sr: f64,
+    /// Tick and beat of the last tempo change.
+    origin_tick: u64,
+    origin_beat: f64,
+}
+
+impl Tempo {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            bpm: f64::from(DEFAULT_BPM),
+            sr: f64::from(sample_rate),
+            origin_tick: 0,
+            origin_beat: 0.0,
+        }
+    }
+
+    pub fn bpm(&self) -> f32 {
+        self.bpm as f32
+    }
+
+    /// Changes the tempo from `now` on.
+    pub fn set_bpm(&mut self, bpm: f32, now: u64) {
+        self.origin_beat = self.beat_at(now);
+        self.origin_tick = now;
+        self.bpm = f64::from(bpm.clamp(1.0, 1000.0));
+    }
+
+    /// Puts `beat` at `now`, keeping the tempo.
+    pub fn set_beat(&mut self, beat: f64, now: u64) {
+        self.origin_beat = beat;
+        self.origin_tick = now;
+    }
+
+    /// Restarts the clock: tick 0 is beat 0 again.
+    pub fn reset(&mut self) {
+        self.origin_tick = 0;
+        self.origin_beat = 0.0;
+    }
+
+    pub fn beat_at(&self, tick: u64) -> f64 {
+        self.origin_beat + (tick as f64 - self.origin_tick as f64) * self.bpm / (60.0 * self.sr)
+    }
+
+    /// Tick at which `beat` falls at the current tempo (0 for beats before
+    /// the clock started).
+    pub fn tick_of(&self, beat: f64) -> u64 {
+        let ticks = (beat - self.origin_beat) * 60.0 * self.sr / self.bpm;
+        (self.origin_tick as f64 + ticks).round().max(0.0) as u64
+    }
+
+    /// Length of `beats` in seconds at the current tempo.
+    pub fn secs(&self, beats: f32) -> f32 {
+        (f64::from(beats) * 60.0 / self.bpm) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
+
+    #[test]
+    fn tempo_changes_keep_the_beat_reached_so_far() {
+        let mut tempo = Tempo::new(48000.0);
+        assert_eq!(tempo.tick_of(2.0), 48000);
+        tempo.set_bpm(60.0, 24000);
+        assert_eq!(tempo.beat_at(24000), 1.0);
+        assert_eq!(tempo.tick_of(2.0), 72000);
+        assert_eq!(tempo.tick_of(tempo.beat_at(12345)), 12345);
+        assert_eq!(tempo.secs(0.5), 0.5);
+        tempo.reset();
+        assert_eq!(tempo.tick_of(1.0), 48000);
+    }
+
+    #[test]
+    fn timetags_map_to_ticks_from_the_anchor() {
+        let anchor = TimeAnchor {
+            start_unix_micros: 1_000_000,
+            sample_rate: 48000.0,
+        };
+        assert_eq!(anchor.unix_micros_to_tick(500_000), 0);
+        assert_eq!(anchor.unix_micros_to_tick(1_500_000), 24000);
+        // Half a second past the anchor, as an NTP seconds/fraction pair
+        let secs = (NTP_UNIX_OFFSET_SECS + 1) as u32;
+        assert_eq!(anchor.ntp_to_tick(secs, 1 << 31), Some(24000));
+        assert_eq!(anchor.ntp_to_tick(0, 1), None);
+        assert_eq!(anchor.ntp_to_tick(1000, 0), None);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn beat_times_and_lengths_follow_tempo_changes() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        let scheduled = |engine: &Engine| -> Vec<_> {
+            engine.schedule.iter().map(|e| (e.tick, e.gate)).collect()
+        };
+        // Beat 4 at 120 bpm, and 1.5 s, which tempo doesn't move
+        engine.evaluate("/sound/sine/time/4b/dur/0.5b; /sound/sine/time/1.5");
+        assert_eq!(
+            scheduled(&engine),
+            [(Some(72000), None), (Some(96000), Some(0.25))]
+        );
+        engine.evaluate("/doux/bpm/60");
+        assert_eq!(
+            scheduled(&engine),
+            [(Some(72000), None), (Some(192000), Some(0.5))]
+        );
+
+        // Hits every half beat over two beats, re-spaced mid-roll
+        engine.evaluate("/doux/reset");
+        let i = engine.evaluate("/sound/sine/gate/2b/rep/0.5b").unwrap();
+        assert_eq!(engine.voices[i].params.gate, 0.5);
+        engine.tick = 12000;
+        engine.evaluate("/doux/bpm/120");
+        assert_eq!(engine.bpm(), 120.0);
+        assert_eq!(engine.beat(), 0.25);
+        assert_eq!(
+            scheduled(&engine),
+            [
+                (Some(18000), Some(0.25)),
+                (Some(30000), Some(0.25)),
+                (Some(42000), Some(0.25))
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn div_and_mult_set_roll_spacing_from_the_beat() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        // (first hit's gate, hits) of a roll
+        let mut roll = |cmd: &str| {
+            engine.evaluate("/doux/reset");
+            let i = engine.evaluate(cmd).unwrap();
+            (engine.voices[i].params.gate, engine.schedule.len() + 1)
+        };
+        assert_eq!(roll("/sound/sine/gate/1b/rep/1b/div/4"), (0.125, 4));
+        assert_eq!(roll("/sound/sine/gate/1b/div/2"), (0.25, 2));
+        assert_eq!(roll("/sound/sine/gate/1/rep/0.25/mult/2"), (0.5, 2));
+        assert_eq!(roll("/sound/sine/gate/1b/rep/0.25bt"), (0.5 / 6.0, 6));
+    }
+}
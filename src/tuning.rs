@@ -0,0 +1,253 @@
+//! Alternative tunings for `note`.
+//!
+//! By default a `note` is a 12-TET MIDI note number. A [`Tuning`] maps note
+//! numbers through a scale instead: each note above the root steps one
+//! degree up the scale, and the scale repeats every period (usually an
+//! octave). Fractional notes glide between neighbouring degrees.
+//!
+//! Tunings are registered on the engine by name and picked per event:
+//!
+//! | Command                                         | Effect                            |
+//! |-------------------------------------------------|-----------------------------------|
+//! | `/doux/tuning/load/<path>`                      | Load a Scala `.scl`, named after the file |
+//! | `/doux/tuning/load/<path>/tuning/<name>`        | Same, under `name`                |
+//! | `/doux/tuning/cents/<c1,c2,..>/tuning/<name>`   | Scale from a cents table          |
+//! | `/note/<n>/tuning/<name>`                       | Play a note in that tuning        |
+//!
+//! Scala files are read by [`AudioCmd::route`](crate::AudioCmd::route) on
+//! the thread sending the event; embedders evaluating events themselves
+//! call [`Tuning::read_scl`] and [`Engine::set_tuning`](crate::Engine::set_tuning).
+//!
+//! Cents tables follow the Scala layout: one entry per degree above the
+//! root, the last one being the period (`200,400,700,900,1200` for a major
+//! pentatonic). Note 60 is the root and keeps its 12-TET pitch (middle C).
+
+use crate::types::midi2freq;
+
+/// Note number that plays the scale's root.
+pub const DEFAULT_ROOT_NOTE: f32 = 60.0;
+
+/// A scale that `note` numbers map through. See the [module docs](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tuning {
+    pub description: String,
+    /// Cents of each degree above the root; the last one is the period.
+    pub degrees: Vec<f64>,
+    pub root_note: f32,
+    /// Frequency of `root_note`.
+    pub root_freq: f32,
+}
+
+impl Tuning {
+    /// Scale from the cents of each degree above the root, period last.
+    pub fn from_cents(degrees: Vec<f64>) -> Result<Self, String> {
+        match degrees.last() {
+            None => return Err("no pitches in the scale".into()),
+            Some(&period) if period.is_nan() || period <= 0.0 => {
+                return Err(format!("period must be above 0 cents, got {period}"))
+            }
+            _ => {}
+        }
+        if degrees.iter().any(|c| !c.is_finite()) {
+            return Err("pitches must be finite".into());
+        }
+        Ok(Self {
+            description: String::new(),
+            degrees,
+            root_note: DEFAULT_ROOT_NOTE,
+            root_freq: midi2freq(DEFAULT_ROOT_NOTE),
+        })
+    }
+
+    /// Reads and parses a Scala `.scl` file. Blocks on the disk, so keep it
+    /// off the audio thread.
+    #[cfg(feature = "native")]
+    pub fn read_scl(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse_scl(&text)
+    }
+
+    /// Parses a Scala `.scl` file: `!` comment lines, a description, the
+    /// pitch count, then one pitch per line, in cents (with a `.`) or as a
+    /// ratio (`3/2`, `2`).
+    pub fn parse_scl(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().filter(|l| !l.starts_with('!'));
+        let description = lines.next().ok_or("empty scale file")?.trim().to_string();
+        let count = lines
+            .next()
+            .and_then(|l| l.split_whitespace().next())
+            .and_then(|n| n.parse::<usize>().ok())
+            .ok_or("missing pitch count")?;
+        let degrees = lines
+            .filter_map(|l| l.split_whitespace().next())
+            .take(count)
+            .map(parse_pitch)
+            .collect::<Result<Vec<_>, _>>()?;
+        if degrees.len() < count {
+            return Err(format!("expected {count} pitches, found {}", degrees.len()));
+        }
+        Ok(Self {
+            description,
+            ..Self::from_cents(degrees)?
+        })
+    }
+
+    /// Parses a comma-separated cents table (`100,200,...,1200`).
+    pub fn parse_cents(list: &str) -> Result<Self, String> {
+        let degrees = list
+            .split(',')
+            .map(|c| {
+                c.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("bad cents value `{c}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_cents(degrees)
+    }
+
+    /// Cents above the root of scale step `step` (negative steps go down).
+    fn step_cents(&self, step: i64) -> f64 {
+        let len = self.degrees.len() as i64;
+        let (period, degree) = (step.div_euclid(len), step.rem_euclid(len));
+        let within = match degree {
+            0 => 0.0,
+            d => self.degrees[d as usize - 1],
+        };
+        period as f64 * self.degrees[len as usize - 1] + within
+    }
+
+    /// Frequency of `note`, gliding between degrees for fractional notes.
+    pub fn freq(&self, note: f32) -> f32 {
+        let steps = f64::from(note - self.root_note);
+        let below = steps.floor();
+        let (lo, hi) = (
+            self.step_cents(below as i64),
+            self.step_cents(below as i64 + 1),
+        );
+        let cents = lo + (hi - lo) * (steps - below);
+        self.root_freq * (cents / 1200.0).exp2() as f32
+    }
+}
+
+/// One `.scl` pitch in cents: `701.955` is cents, `3/2` and `2` are ratios.
+fn parse_pitch(token: &str) -> Result<f64, String> {
+    let bad = || format!("bad pitch `{token}`");
+    if token.contains('.') {
+        return token.parse().map_err(|_| bad());
+    }
+    let (num, den) = token.split_once('/').unwrap_or((token, "1"));
+    let (num, den) = (
+        num.parse::<f64>().map_err(|_| bad())?,
+        den.parse::<f64>().map_err(|_| bad())?,
+    );
+    if num <= 0.0 || den <= 0.0 {
+        return Err(bad());
+    }
+    Ok(1200.0 * (num / den).log2())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "native")]
+    use crate::event::Event;
+    #[cfg(feature = "native")]
+    use crate::voice::ModChain;
+    #[cfg(feature = "native")]
+    use crate::{AudioCmd, Engine};
+
+    const PENTATONIC: &str =
+        "! slendro.scl\n!\nJust pentatonic\n 5\n!\n 9/8\n 5/4\n 3/2\n 5/3\n 2/1\n";
+
+    #[test]
+    fn scala_files_map_notes_through_the_scale() {
+        let tuning = Tuning::parse_scl(PENTATONIC).unwrap();
+        assert_eq!(tuning.description, "Just pentatonic");
+        assert_eq!(tuning.degrees.len(), 5);
+        let c = midi2freq(60.0);
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3 * b;
+        assert!(close(tuning.freq(60.0), c));
+        assert!(close(tuning.freq(63.0), c * 1.5));
+        // One period up and down
+        assert!(close(tuning.freq(65.0), c * 2.0));
+        assert!(close(tuning.freq(59.0), c * 5.0 / 3.0 / 2.0));
+        // Halfway between the root and 9/8, in cents
+        assert!(close(tuning.freq(60.5), c * (9.0f32 / 8.0).sqrt()));
+
+        assert!(Tuning::parse_scl("name\n3\n100.0\n200.0\n").is_err());
+        assert!(Tuning::parse_scl("name\n1\n-3/2\n").is_err());
+    }
+
+    #[test]
+    fn twelve_equal_cents_match_midi() {
+        let cents: Vec<String> = (1..=12).map(|i| (i * 100).to_string()).collect();
+        let tuning = Tuning::parse_cents(&cents.join(",")).unwrap();
+        for note in [0.0, 45.5, 69.0, 127.0] {
+            assert!((tuning.freq(note) - midi2freq(note)).abs() < 1e-3 * midi2freq(note));
+        }
+        assert!(Tuning::parse_cents("100,x").is_err());
+        assert!(Tuning::parse_cents("100,0").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn notes_play_in_a_registered_tuning() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/doux/tuning/cents/200,400,700,900,1200/tuning/penta");
+        let c = midi2freq(60.0);
+        let freq_of = |engine: &mut Engine, path: &str| {
+            let i = engine.evaluate(path).unwrap();
+            engine.voices[i].params.freq
+        };
+        assert!(
+            (freq_of(&mut engine, "/sound/sine/note/62/tuning/penta") - c * 2f32.powf(4.0 / 12.0))
+                .abs()
+                < 0.01
+        );
+        assert!((freq_of(&mut engine, "/sound/sine/note/65/tuning/penta") - c * 2.0).abs() < 0.01);
+        // Unknown tunings and plain notes stay in 12-TET
+        assert_eq!(
+            freq_of(&mut engine, "/sound/sine/note/62/tuning/nope"),
+            midi2freq(62.0)
+        );
+        assert_eq!(freq_of(&mut engine, "/sound/sine/note/62"), midi2freq(62.0));
+
+        let event = Event::parse("/sound/sine/note/60~65:1/tuning/penta", 48000.0);
+        let mut tuned = event.clone();
+        engine.apply_tuning(&mut tuned);
+        let (_, ModChain::Oscillate { min, max, .. }) = &tuned.mods[0] else {
+            panic!("expected an oscillating note");
+        };
+        assert_eq!(
+            (*min, *max),
+            (c, engine.tuning("penta").unwrap().freq(65.0))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn tuning_files_are_read_before_they_reach_the_engine() {
+        let dir = std::env::temp_dir().join(format!("doux-tuning-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("penta.scl");
+        std::fs::write(
+            &file,
+            "! penta.scl\nPentatonic\n5\n200.\n400.\n700.\n900.\n2/1\n",
+        )
+        .unwrap();
+        let path = format!(
+            "/doux/tuning/load/\"{}\"; /sound/sine/note/62/tuning/penta",
+            file.display()
+        );
+        let mut cmds = Vec::new();
+        AudioCmd::route(path, None, |cmd| cmds.push(cmd));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(&cmds[0], AudioCmd::Tuning { name, .. } if name == "penta"));
+        assert!(matches!(cmds[1], AudioCmd::Evaluate { .. }));
+
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        cmds.into_iter().for_each(|cmd| engine.apply(cmd));
+        let freq = engine.voices[0].params.freq;
+        assert!((freq - midi2freq(60.0) * 2f32.powf(4.0 / 12.0)).abs() < 0.01);
+    }
+}
@@ -1,11 +1,23 @@
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 pub const WASM_BLOCK_SIZE: usize = 128;
 pub const DEFAULT_NATIVE_BLOCK_SIZE: usize = 512;
 pub const CHANNELS: usize = 2;
 pub const DEFAULT_MAX_VOICES: usize = 32;
+/// Polyphony [`Engine::set_max_voices`](crate::Engine::set_max_voices) can
+/// reach without allocating; the voice pool reserves this many up front.
+pub const MAX_VOICES: usize = 256;
 pub const MAX_EVENTS: usize = 256;
-pub const MAX_ORBITS: usize = 8;
+/// Upper bound on the engine's orbit count (see [`Engine::set_orbit_count`](crate::Engine::set_orbit_count)).
+pub const MAX_ORBITS: usize = 16;
+pub const DEFAULT_ORBITS: usize = 8;
+/// Secondary orbit sends per voice (`orbit2`, `orbit3`).
+pub const ORBIT_SENDS: usize = 2;
+/// Upper bound on `ratchet` hits per event (each extra hit takes a schedule slot).
+pub const MAX_RATCHET: usize = 64;
+/// General-purpose LFOs per voice (`lfo1rate` .. `lfo4target`).
+pub const VOICE_LFOS: usize = 4;
 
 // --- Metadata ---
 
@@ -58,7 +70,7 @@ pub struct SourceInfo {
     pub debug_id: i32,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum Source {
     #[default]
     Tri,
@@ -69,6 +81,7 @@ pub enum Source {
     Pulze,
     Add,
     Osc,
+    Pluck,
     White,
     Pink,
     Brown,
@@ -84,9 +97,12 @@ pub enum Source {
     Wavetable,
     WebSample,
     LiveInput,
+    /// A [`SourceRenderer`](crate::voice::SourceRenderer) registered by the
+    /// embedder. Not listed in [`Source::all`]; resolved by name per event.
+    Custom,
 }
 
-const ALL_SOURCES: [Source; 23] = [
+const ALL_SOURCES: [Source; 24] = [
     Source::Tri,
     Source::Sine,
     Source::Saw,
@@ -95,6 +111,7 @@ const ALL_SOURCES: [Source; 23] = [
     Source::Pulze,
     Source::Add,
     Source::Osc,
+    Source::Pluck,
     Source::White,
     Source::Pink,
     Source::Brown,
@@ -261,6 +278,40 @@ const INFO_OSC: SourceInfo = source_info!(
     },],
     14
 );
+const INFO_PLUCK: SourceInfo = source_info!(
+    "pluck",
+    &["ks", "string"],
+    SourceCategory::Oscillator,
+    "Karplus-Strong plucked string: a burst of excitation ringing in a tuned delay loop",
+    None,
+    &[
+        ParamInfo {
+            name: "pluckdamp",
+            aliases: &[],
+            description: "loop damping (0 bright, 1 dark)",
+            default: "0.5",
+            min: 0.0,
+            max: 1.0
+        },
+        ParamInfo {
+            name: "pluckdecay",
+            aliases: &[],
+            description: "ring time to -60 dB in seconds",
+            default: "2.0",
+            min: 0.01,
+            max: 60.0
+        },
+        ParamInfo {
+            name: "pluckexcite",
+            aliases: &[],
+            description: "excitation (noise or impulse, 0/1)",
+            default: "noise",
+            min: 0.0,
+            max: 1.0
+        },
+    ],
+    34
+);
 
 const INFO_WHITE: SourceInfo = source_info!(
     "white",
@@ -738,10 +789,45 @@ const INFO_LIVEINPUT: SourceInfo = source_info!(
     SourceCategory::Input,
     "Live audio input (microphone, line-in)",
     None,
-    &[],
+    &[
+        ParamInfo {
+            name: "inputch",
+            aliases: &["inchan"],
+            description: "mono input channel (unset = channels 0 and 1 as a stereo pair)",
+            default: "off",
+            min: 0.0,
+            max: 63.0
+        },
+        ParamInfo {
+            name: "inputgain",
+            aliases: &[],
+            description: "input level (linear)",
+            default: "0.7",
+            min: 0.0,
+            max: 4.0
+        },
+        ParamInfo {
+            name: "insum",
+            aliases: &[],
+            description: "mono-sum the stereo pair",
+            default: "0",
+            min: 0.0,
+            max: 1.0
+        },
+    ],
     13
 );
 
+const INFO_CUSTOM: SourceInfo = source_info!(
+    "custom",
+    &[],
+    SourceCategory::Oscillator,
+    "User-defined source registered by the host application",
+    None,
+    &[],
+    33
+);
+
 impl Source {
     pub const fn all() -> &'static [Source] {
         &ALL_SOURCES
@@ -757,6 +843,7 @@ impl Source {
             Self::Pulze => &INFO_PULZE,
             Self::Add => &INFO_ADD,
             Self::Osc => &INFO_OSC,
+            Self::Pluck => &INFO_PLUCK,
             Self::White => &INFO_WHITE,
             Self::Pink => &INFO_PINK,
             Self::Brown => &INFO_BROWN,
@@ -772,6 +859,7 @@ impl Source {
             Self::Wavetable => &INFO_WAVETABLE,
             Self::WebSample => &INFO_WEBSAMPLE,
             Self::LiveInput => &INFO_LIVEINPUT,
+            Self::Custom => &INFO_CUSTOM,
         }
     }
 
@@ -846,7 +934,7 @@ impl FromStr for Source {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum SubWave {
     #[default]
     Tri,
@@ -867,7 +955,7 @@ impl FromStr for SubWave {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum LfoShape {
     #[default]
     Sine,
@@ -892,7 +980,7 @@ impl FromStr for LfoShape {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum ReverbType {
     Plate,
     #[default]
@@ -911,7 +999,56 @@ impl FromStr for ReverbType {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+/// How the plate reverb's tank scales with the sample rate. The tank is
+/// tuned in samples, so at 96 kHz and up it would otherwise need two to
+/// four times the memory and CPU for detail above hearing.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum ReverbQuality {
+    /// The tank runs at the engine rate, however high.
+    High,
+    /// The tank runs at the engine rate divided down to 48 kHz or less.
+    #[default]
+    Auto,
+    /// Half the `Auto` tank rate: half the memory and CPU, a darker tail.
+    Low,
+}
+
+impl FromStr for ReverbQuality {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" => Ok(Self::High),
+            "auto" => Ok(Self::Auto),
+            "low" => Ok(Self::Low),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What fills the [`Source::Pluck`] string when it is struck.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum PluckExcite {
+    /// A period of white noise: the classic bright, breathy pluck.
+    #[default]
+    Noise,
+    /// A single click: a purer, more even tone.
+    Impulse,
+}
+
+impl FromStr for PluckExcite {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "noise" | "0" => Ok(Self::Noise),
+            "impulse" | "click" | "1" => Ok(Self::Impulse),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum SyncMode {
     #[default]
     Hard,
@@ -930,7 +1067,78 @@ impl FromStr for SyncMode {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+/// Where `slice` cuts a sample.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum SliceMode {
+    /// Equal durations.
+    #[default]
+    Even,
+    /// Equal shares of spectral energy, blended with duration.
+    Energy,
+}
+
+impl FromStr for SliceMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "even" | "0" => Ok(Self::Even),
+            "energy" | "1" => Ok(Self::Energy),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How `pan` splits a voice between left and right.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum PanLaw {
+    /// `cos`/`sin` gains: constant power across the sweep.
+    #[default]
+    Power,
+    /// Balance: unity at center, the far side fades linearly to silence.
+    /// Leaves stereo sources untouched at center.
+    Linear,
+}
+
+impl FromStr for PanLaw {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "power" | "equal" | "0" => Ok(Self::Power),
+            "linear" | "balance" | "1" => Ok(Self::Linear),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How orbits map onto the output channels.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum OutMode {
+    /// Orbits share stereo pairs, wrapping when there are more orbits than
+    /// pairs (see `/doux/route`).
+    #[default]
+    Pairs,
+    /// Multi-out: orbit `n` on channels `2n`/`2n + 1`, never summed.
+    Stereo,
+    /// Multi-out: orbit `n` folded to mono on channel `n`, never summed.
+    Mono,
+}
+
+impl FromStr for OutMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pairs" | "off" | "0" => Ok(Self::Pairs),
+            "stereo" | "1" => Ok(Self::Stereo),
+            "mono" | "2" => Ok(Self::Mono),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub enum DelayType {
     #[default]
     Standard,
@@ -953,7 +1161,7 @@ impl FromStr for DelayType {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum FilterType {
     Lowpass,
     Highpass,
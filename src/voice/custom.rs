@@ -0,0 +1,119 @@
+//! User-defined sources.
+//!
+//! Embedders implement [`SourceRenderer`] and register it by name with
+//! `Engine::register_source`; `/sound/<name>` then plays it through the
+//! regular envelope, filter and effect chain like any built-in oscillator.
+//!
+//! Renderers are shared between voices. Per-voice state lives in a fixed
+//! [`CUSTOM_STATE_LEN`]-float array owned by the voice, so triggering a custom
+//! source never allocates on the audio thread.
+
+use std::sync::Arc;
+
+use super::VoiceParams;
+use crate::types::CHANNELS;
+
+/// Floats of per-voice state available to a [`SourceRenderer`].
+pub const CUSTOM_STATE_LEN: usize = 16;
+
+/// Per-sample inputs handed to a custom source.
+pub struct SourceContext<'a> {
+    /// Frequency after detune, speed, vibrato and FM.
+    pub freq: f32,
+    /// Inverse sample rate.
+    pub isr: f32,
+    /// Seconds since the voice started.
+    pub time: f32,
+    pub params: &'a VoiceParams,
+}
+
+/// A synthesis model that renders one frame at a time.
+pub trait SourceRenderer: Send + Sync {
+    /// Output channels: 1 (mono, spread/duplicated like the oscillators) or 2.
+    fn channels(&self) -> usize {
+        1
+    }
+
+    /// Resets per-voice state when a voice starts this source.
+    fn init(&self, state: &mut [f32; CUSTOM_STATE_LEN], ctx: &SourceContext) {
+        let _ = ctx;
+        state.fill(0.0);
+    }
+
+    /// Writes one frame into `out[..channels()]`. Returning `false` marks the
+    /// source as finished and releases the voice.
+    fn render(
+        &self,
+        state: &mut [f32; CUSTOM_STATE_LEN],
+        ctx: &SourceContext,
+        out: &mut [f32; CHANNELS],
+    ) -> bool;
+}
+
+/// Named custom sources, looked up when an event's `sound` isn't built in.
+#[derive(Clone, Default)]
+pub struct SourceRegistry {
+    entries: Vec<(Arc<str>, Arc<dyn SourceRenderer>)>,
+}
+
+impl SourceRegistry {
+    /// Adds or replaces the source called `name`.
+    pub fn register(&mut self, name: &str, renderer: Arc<dyn SourceRenderer>) {
+        match self.entries.iter_mut().find(|(n, _)| n.as_ref() == name) {
+            Some(entry) => entry.1 = renderer,
+            None => self.entries.push((Arc::from(name), renderer)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SourceRenderer>> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n.as_ref() == name)
+            .map(|(_, r)| Arc::clone(r))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(n, _)| n.as_ref())
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::types::Source;
+    use crate::Engine;
+
+    #[test]
+    fn registered_source_renders_through_voice_chain() {
+        struct Ramp;
+        impl SourceRenderer for Ramp {
+            fn init(&self, state: &mut [f32; CUSTOM_STATE_LEN], ctx: &SourceContext) {
+                state[0] = ctx.freq * 0.001;
+            }
+            fn render(
+                &self,
+                state: &mut [f32; CUSTOM_STATE_LEN],
+                _ctx: &SourceContext,
+                out: &mut [f32; CHANNELS],
+            ) -> bool {
+                out[0] = state[0];
+                state[1] += 1.0;
+                state[1] < 100.0
+            }
+        }
+
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.register_source("ramp", Arc::new(Ramp));
+        engine.evaluate("/sound/ramp/freq/200/attack/0");
+        assert_eq!(engine.voices[0].params.sound, Source::Custom);
+        assert_eq!(engine.voices[0].custom_state[0], 0.2);
+
+        engine.dsp();
+        assert!(engine.output.iter().any(|&s| s != 0.0));
+        // Finishing the source releases the voice
+        for _ in 0..200 {
+            engine.dsp();
+        }
+        assert_eq!(engine.active_voices, 0);
+    }
+}
@@ -1,21 +1,27 @@
 //! Voice - the core synthesis unit.
 
+mod custom;
 mod drums;
 pub mod modulation;
 mod params;
+mod pluck;
 mod source;
 
+pub use custom::{SourceContext, SourceRegistry, SourceRenderer, CUSTOM_STATE_LEN};
 pub use modulation::{ModChain, ParamId, ParamMod};
-pub use params::VoiceParams;
+pub use params::{VoiceLfo, VoiceParams};
+pub use pluck::PluckString;
 
 use std::f32::consts::PI;
+use std::sync::Arc;
 
 use crate::dsp::{
     cosf, exp2f, sinf, BrownNoise, Dahdsr, Phasor, PinkNoise, SvfCascade, SvfMode, SvfState,
 };
 use crate::effects::{
-    crush, distort, Chorus, Coarse, DcBlocker, Eq, Flanger, Fold, Haas, LadderFilter, LadderMode,
-    Phaser, Smear, Tilt, Wrap,
+    crush, distort, Chorus, Coarse, DcBlocker, Decorrelator, Dynamics, DynamicsParams, Eq, Flanger,
+    Fold, FreqShifter, Haas, InsertSlots, LadderFilter, LadderMode, Phaser, ResonatorBank, Smear,
+    Tilt, TransientShaper, Wrap,
 };
 #[cfg(feature = "native")]
 use crate::sampling::RegistrySample;
@@ -24,7 +30,7 @@ use crate::sampling::StretchState;
 use crate::sampling::WebSampleSource;
 #[cfg(not(feature = "native"))]
 use crate::sampling::{FileSource, SampleInfo};
-use crate::types::CHANNELS;
+use crate::types::{PanLaw, CHANNELS, VOICE_LFOS};
 
 pub const MAX_PARAM_MODS: usize = 15;
 pub(crate) const MAX_ADDITIVE_PARTIALS: usize = 32;
@@ -55,7 +61,6 @@ impl Default for AdditiveCache {
     }
 }
 
-#[derive(Clone)]
 pub struct Voice {
     pub params: VoiceParams,
     pub phasor: Phasor,
@@ -72,6 +77,16 @@ pub struct Voice {
     pub shp: [SvfCascade; CHANNELS],
     pub sbp: [SvfCascade; CHANNELS],
     pub vib_lfo: Phasor,
+    pub lfo_phasors: [Phasor; VOICE_LFOS],
+    /// Offset each LFO last added, and where, so it can be taken back out.
+    lfo_applied: [Option<(ParamId, f32)>; VOICE_LFOS],
+    /// Bend and pressure as smoothed towards `params.bend`/`params.pressure`.
+    pub bend_now: f32,
+    pub pressure_now: f32,
+    /// Offset pressure last added, and where.
+    pressure_applied: Option<(ParamId, f32)>,
+    /// Params as they were before the last update, for `revert`.
+    pub prev_params: Option<VoiceParams>,
     pub fm_phasor: Phasor,
     pub fm2_phasor: Phasor,
     pub fm_fb_prev: f32,
@@ -98,10 +113,17 @@ pub struct Voice {
     pub stretch: StretchState,
     // Sample playback (web)
     pub web_sample: Option<WebSampleSource>,
+    // User-defined source and its per-voice state
+    pub custom: Option<Arc<dyn SourceRenderer>>,
+    pub custom_state: [f32; CUSTOM_STATE_LEN],
+    // Plucked string delay loop
+    pub pluck: Option<Box<PluckString>>,
     // Effects
     pub phaser: [Phaser; CHANNELS],
     pub flanger: Option<Box<[Flanger; CHANNELS]>>,
     pub smear: [Smear; CHANNELS],
+    pub fshift: [FreqShifter; CHANNELS],
+    pub reso: Option<Box<ResonatorBank>>,
     pub chorus: Option<Box<Chorus>>,
     pub coarse: [Coarse; CHANNELS],
     pub fold_state: [Fold; CHANNELS],
@@ -109,7 +131,11 @@ pub struct Voice {
     pub dc_block: [DcBlocker; CHANNELS],
     pub eq: [Eq; CHANNELS],
     pub tilt: [Tilt; CHANNELS],
+    pub transient: TransientShaper,
+    pub dynamics: Dynamics,
     pub haas: Option<Box<Haas>>,
+    pub decor: Option<Box<Decorrelator>>,
+    pub inserts: InsertSlots,
     pub ladder_lp: [LadderFilter; CHANNELS],
     pub ladder_hp: [LadderFilter; CHANNELS],
     pub ladder_bp: [LadderFilter; CHANNELS],
@@ -119,6 +145,9 @@ pub struct Voice {
     pub param_mod_count: u8,
 
     pub triggered: bool,
+    /// Engine tick at which the gate closes. When set, the engine releases
+    /// the voice and the envelopes run without their own gate timer.
+    pub gate_end: Option<u64>,
     pub time: f32,
     pub ch: [f32; CHANNELS],
     pub nch: usize,
@@ -129,6 +158,9 @@ pub struct Voice {
     pub(crate) shape_active: bool,
     pub sr: f32,
     pub seed: u32,
+    /// Stable ID assigned at allocation. Survives voice-array compaction,
+    /// unlike the voice's index.
+    pub id: u32,
 
     // Drum synthesis filter
     pub(super) drum_svf: SvfState,
@@ -156,6 +188,12 @@ impl Default for Voice {
             shp: [SvfCascade::default(); CHANNELS],
             sbp: [SvfCascade::default(); CHANNELS],
             vib_lfo: Phasor::default(),
+            lfo_phasors: [Phasor::default(); VOICE_LFOS],
+            lfo_applied: [None; VOICE_LFOS],
+            bend_now: 0.0,
+            pressure_now: 0.0,
+            pressure_applied: None,
+            prev_params: None,
             fm_phasor: Phasor::default(),
             fm2_phasor: Phasor::default(),
             fm_fb_prev: 0.0,
@@ -176,9 +214,14 @@ impl Default for Voice {
             #[cfg(feature = "native")]
             stretch: StretchState::default(),
             web_sample: None,
+            custom: None,
+            custom_state: [0.0; CUSTOM_STATE_LEN],
+            pluck: Some(Box::default()),
             phaser: [Phaser::default(); CHANNELS],
             flanger: Some(Box::new([Flanger::default(); CHANNELS])),
             smear: [Smear::default(); CHANNELS],
+            fshift: [FreqShifter::default(); CHANNELS],
+            reso: Some(Box::default()),
             chorus: Some(Box::new(Chorus::default())),
             coarse: [Coarse::default(); CHANNELS],
             fold_state: [Fold::default(); CHANNELS],
@@ -186,13 +229,18 @@ impl Default for Voice {
             dc_block: [DcBlocker::default(); CHANNELS],
             eq: [Eq::default(); CHANNELS],
             tilt: [Tilt::default(); CHANNELS],
+            transient: TransientShaper::default(),
+            dynamics: Dynamics::default(),
             haas: Some(Box::new(Haas::default())),
+            decor: Some(Box::default()),
+            inserts: InsertSlots::default(),
             ladder_lp: [LadderFilter::default(); CHANNELS],
             ladder_hp: [LadderFilter::default(); CHANNELS],
             ladder_bp: [LadderFilter::default(); CHANNELS],
-            param_mods: [(ParamId::Gain, ParamMod::default()); MAX_PARAM_MODS],
+            param_mods: std::array::from_fn(|_| (ParamId::Gain, ParamMod::default())),
             param_mod_count: 0,
             triggered: false,
+            gate_end: None,
             time: 0.0,
             ch: [0.0; CHANNELS],
             nch: 1,
@@ -203,6 +251,7 @@ impl Default for Voice {
             shape_active: false,
             sr,
             seed: 123456789,
+            id: 0,
             drum_svf: SvfState::default(),
         }
     }
@@ -223,6 +272,12 @@ impl Voice {
         self.hp = [SvfState::default(); CHANNELS];
         self.bp = [SvfState::default(); CHANNELS];
         self.vib_lfo = Phasor::default();
+        self.lfo_phasors = [Phasor::default(); VOICE_LFOS];
+        self.lfo_applied = [None; VOICE_LFOS];
+        self.bend_now = 0.0;
+        self.pressure_now = 0.0;
+        self.pressure_applied = None;
+        self.prev_params = None;
         self.fm_phasor = Phasor::default();
         self.fm2_phasor = Phasor::default();
         self.fm_fb_prev = 0.0;
@@ -248,11 +303,20 @@ impl Voice {
             self.stretch = StretchState::default();
         }
         self.web_sample = None;
+        self.custom_state = [0.0; CUSTOM_STATE_LEN];
+        if let Some(ref mut p) = self.pluck {
+            p.reset();
+        }
+        self.inserts.reset();
         self.phaser = [Phaser::default(); CHANNELS];
         if let Some(ref mut f) = self.flanger {
             **f = [Flanger::default(); CHANNELS];
         }
         self.smear = [Smear::default(); CHANNELS];
+        self.fshift = [FreqShifter::default(); CHANNELS];
+        if let Some(ref mut r) = self.reso {
+            **r = ResonatorBank::default();
+        }
         if let Some(ref mut c) = self.chorus {
             **c = Chorus::default();
         }
@@ -262,15 +326,21 @@ impl Voice {
         self.dc_block = [DcBlocker::default(); CHANNELS];
         self.eq = [Eq::default(); CHANNELS];
         self.tilt = [Tilt::default(); CHANNELS];
+        self.transient.reset();
+        self.dynamics.reset();
         if let Some(ref mut h) = self.haas {
             **h = Haas::default();
         }
+        if let Some(ref mut d) = self.decor {
+            **d = Decorrelator::default();
+        }
         self.ladder_lp = [LadderFilter::default(); CHANNELS];
         self.ladder_hp = [LadderFilter::default(); CHANNELS];
         self.ladder_bp = [LadderFilter::default(); CHANNELS];
-        self.param_mods = [(ParamId::Gain, ParamMod::default()); MAX_PARAM_MODS];
+        self.param_mods = std::array::from_fn(|_| (ParamId::Gain, ParamMod::default()));
         self.param_mod_count = 0;
         self.triggered = false;
+        self.gate_end = None;
         self.time = 0.0;
         self.ch = [0.0; CHANNELS];
         self.nch = 1;
@@ -360,6 +430,8 @@ impl Voice {
             ParamId::Gain => self.params.gain,
             ParamId::Postgain => self.params.postgain,
             ParamId::Pan => self.params.pan,
+            ParamId::Pan2d => self.params.pan2d.unwrap_or(0.0),
+            ParamId::Pan2dspread => self.params.pan2dspread,
             ParamId::Speed => self.params.speed,
             ParamId::Stretch => self.params.stretch,
             ParamId::Detune => self.params.detune,
@@ -372,6 +444,8 @@ impl Voice {
             ParamId::Timbre => self.params.timbre,
             ParamId::Morph => self.params.morph,
             ParamId::Scan => self.params.scan,
+            ParamId::Pluckdamp => self.params.pluckdamp,
+            ParamId::Pluckdecay => self.params.pluckdecay,
             ParamId::Mirror => self.params.shape.mirror,
             ParamId::Partials => self.params.partials,
             ParamId::Lpf => self.params.lpf.unwrap_or(20000.0),
@@ -408,15 +482,25 @@ impl Voice {
             ParamId::Phaserdepth => self.params.phaserdepth,
             ParamId::Phasersweep => self.params.phasersweep,
             ParamId::Phasercenter => self.params.phasercenter,
+            ParamId::Phasermix => self.params.phasermix,
             ParamId::Flanger => self.params.flanger,
             ParamId::Flangerdepth => self.params.flangerdepth,
             ParamId::Flangerfeedback => self.params.flangerfeedback,
+            ParamId::Flangermix => self.params.flangermix,
             ParamId::Smear => self.params.smear,
             ParamId::Smearfreq => self.params.smearfreq,
             ParamId::Smearfb => self.params.smearfb,
+            ParamId::Smearmix => self.params.smearmix,
+            ParamId::Fshift => self.params.fshift,
+            ParamId::Fshiftmix => self.params.fshiftmix,
+            ParamId::Reso => self.params.reso,
+            ParamId::Resostruct => self.params.resostruct,
+            ParamId::Resobright => self.params.resobright,
+            ParamId::Resodamp => self.params.resodamp,
             ParamId::Chorus => self.params.chorus,
             ParamId::Chorusdepth => self.params.chorusdepth,
             ParamId::Chorusdelay => self.params.chorusdelay,
+            ParamId::Chorusmix => self.params.chorusmix,
             ParamId::Fold => self.params.fold.unwrap_or(0.0),
             ParamId::Crush => self.params.crush.unwrap_or(0.0),
             ParamId::Coarse => self.params.coarse.unwrap_or(0.0),
@@ -428,6 +512,16 @@ impl Voice {
             ParamId::Tilt => self.params.tilt,
             ParamId::Width => self.params.width,
             ParamId::Haas => self.params.haas,
+            ParamId::Decor => self.params.decor,
+            ParamId::Transattack => self.params.transattack,
+            ParamId::Transsustain => self.params.transsustain,
+            ParamId::Envdelay => self.params.envdelay,
+            ParamId::Attack => self.params.attack,
+            ParamId::Hold => self.params.hold,
+            ParamId::Decay => self.params.decay,
+            ParamId::Sustain => self.params.sustain,
+            ParamId::Release => self.params.release,
+            ParamId::Inputgain => self.params.inputgain,
             ParamId::EqLoFreq => self.params.eqlofreq,
             ParamId::EqMidFreq => self.params.eqmidfreq,
             ParamId::EqHiFreq => self.params.eqhifreq,
@@ -437,17 +531,104 @@ impl Voice {
     fn apply_mods(&mut self, isr: f32) {
         for i in 0..self.param_mod_count as usize {
             let (id, ref mut m) = self.param_mods[i];
-            let val = m.tick(isr);
+            let val = match m.chain {
+                ModChain::Follow { source, scale } => self.read_param(source) * scale,
+                _ => m.tick(isr),
+            };
             self.write_param(id, val);
         }
     }
 
+    /// Takes the LFO bank's and pressure's offsets back out of their
+    /// targets, leaving the params at their set (or mod-chain) values.
+    pub(crate) fn remove_mod_offsets(&mut self) {
+        for i in 0..VOICE_LFOS {
+            if let Some((id, offset)) = self.lfo_applied[i].take() {
+                self.write_param(id, self.read_param(id) - offset);
+            }
+        }
+        if let Some((id, offset)) = self.pressure_applied.take() {
+            self.write_param(id, self.read_param(id) - offset);
+        }
+    }
+
+    /// Keeps the current set values so a later `revert` can return to them.
+    pub(crate) fn snapshot_params(&mut self) {
+        self.remove_mod_offsets();
+        self.prev_params = Some(self.params);
+    }
+
+    /// Swaps the current params with the ones from before the last update,
+    /// so repeated reverts flip between the two. The source is left alone:
+    /// a sample can't be swapped back in from the audio thread.
+    pub(crate) fn revert_params(&mut self) {
+        let Some(mut prev) = self.prev_params else {
+            return;
+        };
+        self.remove_mod_offsets();
+        prev.sound = self.params.sound;
+        self.prev_params = Some(std::mem::replace(&mut self.params, prev));
+        let p = &self.params;
+        self.dahdsr
+            .set_curves(p.attackcurve, p.decaycurve, p.releasecurve);
+    }
+
+    /// Adds `offset` to a param and returns how much of it got through the
+    /// clamp, so taking it back out lands on the value from before.
+    fn offset_param(&mut self, id: ParamId, offset: f32) -> f32 {
+        let before = self.read_param(id);
+        self.write_param(id, before + offset);
+        self.read_param(id) - before
+    }
+
+    fn apply_lfos(&mut self, isr: f32) {
+        for i in 0..VOICE_LFOS {
+            let lfo = self.params.lfos[i];
+            let Some(id) = lfo.target else {
+                continue;
+            };
+            let offset = self.lfo_phasors[i].lfo(lfo.shape, lfo.rate, isr) * lfo.depth;
+            let applied = self.offset_param(id, offset);
+            self.lfo_applied[i] = Some((id, applied));
+        }
+    }
+
+    /// Moves bend and pressure towards their set values, then adds the
+    /// pressure offset to its target.
+    fn apply_expression(&mut self, isr: f32) {
+        let coeff = (isr / self.params.expr_slew).min(1.0);
+        self.bend_now += (self.params.bend - self.bend_now) * coeff;
+        self.pressure_now += (self.params.pressure - self.pressure_now) * coeff;
+        if let Some(id) = self.params.pressure_target {
+            let offset = self.pressure_now * self.params.pressure_depth;
+            let applied = self.offset_param(id, offset);
+            self.pressure_applied = Some((id, applied));
+        }
+    }
+
+    /// Puts free-running LFOs at the phase the engine clock `time` gives
+    /// them, so they stay in step across notes and voices.
+    pub fn sync_free_lfos(&mut self, time: f64) {
+        for (lfo, phasor) in self.params.lfos.iter().zip(&mut self.lfo_phasors) {
+            if !lfo.retrig {
+                phasor.phase = (time * lfo.rate as f64).rem_euclid(1.0) as f32;
+            }
+        }
+    }
+
+    /// Sets a param, clamped into its range; NaN leaves it unchanged.
     fn write_param(&mut self, id: ParamId, val: f32) {
+        dsp_assert!(val.is_finite(), "{id:?} written with {val}");
+        let Some(val) = id.clamp(val) else {
+            return;
+        };
         match id {
             ParamId::Freq => self.params.freq = val,
             ParamId::Gain => self.params.gain = val,
             ParamId::Postgain => self.params.postgain = val,
             ParamId::Pan => self.params.pan = val,
+            ParamId::Pan2d => self.params.pan2d = Some(val),
+            ParamId::Pan2dspread => self.params.pan2dspread = val,
             ParamId::Speed => self.params.speed = val,
             ParamId::Stretch => self.params.stretch = val,
             ParamId::Detune => self.params.detune = val,
@@ -469,6 +650,8 @@ impl Voice {
                 self.invalidate_additive_cache();
             }
             ParamId::Scan => self.params.scan = val,
+            ParamId::Pluckdamp => self.params.pluckdamp = val,
+            ParamId::Pluckdecay => self.params.pluckdecay = val,
             ParamId::Mirror => {
                 self.params.shape.mirror = val;
                 self.shape_active = self.params.shape.is_active();
@@ -511,15 +694,25 @@ impl Voice {
             ParamId::Phaserdepth => self.params.phaserdepth = val,
             ParamId::Phasersweep => self.params.phasersweep = val,
             ParamId::Phasercenter => self.params.phasercenter = val,
+            ParamId::Phasermix => self.params.phasermix = val,
             ParamId::Flanger => self.params.flanger = val,
             ParamId::Flangerdepth => self.params.flangerdepth = val,
             ParamId::Flangerfeedback => self.params.flangerfeedback = val,
+            ParamId::Flangermix => self.params.flangermix = val,
             ParamId::Smear => self.params.smear = val,
             ParamId::Smearfreq => self.params.smearfreq = val,
             ParamId::Smearfb => self.params.smearfb = val,
+            ParamId::Smearmix => self.params.smearmix = val,
+            ParamId::Fshift => self.params.fshift = val,
+            ParamId::Fshiftmix => self.params.fshiftmix = val,
+            ParamId::Reso => self.params.reso = val,
+            ParamId::Resostruct => self.params.resostruct = val,
+            ParamId::Resobright => self.params.resobright = val,
+            ParamId::Resodamp => self.params.resodamp = val,
             ParamId::Chorus => self.params.chorus = val,
             ParamId::Chorusdepth => self.params.chorusdepth = val,
             ParamId::Chorusdelay => self.params.chorusdelay = val,
+            ParamId::Chorusmix => self.params.chorusmix = val,
             ParamId::Fold => self.params.fold = Some(val),
             ParamId::Crush => self.params.crush = Some(val),
             ParamId::Coarse => self.params.coarse = Some(val),
@@ -531,6 +724,16 @@ impl Voice {
             ParamId::Tilt => self.params.tilt = val,
             ParamId::Width => self.params.width = val,
             ParamId::Haas => self.params.haas = val,
+            ParamId::Decor => self.params.decor = val,
+            ParamId::Transattack => self.params.transattack = val,
+            ParamId::Transsustain => self.params.transsustain = val,
+            ParamId::Envdelay => self.params.envdelay = val,
+            ParamId::Attack => self.params.attack = val,
+            ParamId::Hold => self.params.hold = val,
+            ParamId::Decay => self.params.decay = val,
+            ParamId::Sustain => self.params.sustain = val,
+            ParamId::Release => self.params.release = val,
+            ParamId::Inputgain => self.params.inputgain = val,
             ParamId::EqLoFreq => self.params.eqlofreq = val,
             ParamId::EqMidFreq => self.params.eqmidfreq = val,
             ParamId::EqHiFreq => self.params.eqhifreq = val,
@@ -552,6 +755,10 @@ impl Voice {
             freq *= exp2f(self.params.detune / 1200.0);
         }
 
+        if self.bend_now != 0.0 {
+            freq *= exp2f(self.bend_now / 12.0);
+        }
+
         // Speed multiplier
         freq *= self.params.speed;
 
@@ -615,17 +822,59 @@ impl Voice {
         }
     }
 
+    /// Restarts envelopes and modulations on the next frame, from their
+    /// current level, keeping the voice's params and oscillator phases.
+    pub fn retrigger(&mut self) {
+        self.triggered = false;
+    }
+
     /// Cut this voice immediately (~1ms fade to avoid clicks).
     pub fn hard_cut(&mut self) {
-        self.params.release = 0.001;
+        self.fade_out(0.001);
+    }
+
+    /// Releases over `secs` (at least 1ms), whatever the voice's own release.
+    pub fn fade_out(&mut self, secs: f32) {
+        self.params.release = secs.max(0.001);
         self.force_release();
     }
 
+    /// Puts every installed modulation back at phase zero without resetting
+    /// the voice, so LFOs line up with the event that retriggered it.
+    pub fn resync_mods(&mut self) {
+        let gate = self.envelope_gate();
+        for i in 0..self.param_mod_count as usize {
+            self.param_mods[i].1.resync(gate);
+        }
+    }
+
+    /// Gate handed to the envelopes: endless when the engine closes it.
+    fn envelope_gate(&self) -> f32 {
+        if self.gate_end.is_some() {
+            0.0
+        } else {
+            self.params.gate
+        }
+    }
+
     fn trigger_envelopes(&mut self) {
-        self.dahdsr.trigger(self.params.gate);
+        let gate = self.envelope_gate();
+        self.dahdsr.trigger(gate);
         self.sync_direction = 1.0;
         for i in 0..self.param_mod_count as usize {
-            self.param_mods[i].1.trigger(self.params.gate);
+            self.param_mods[i].1.trigger(gate);
+        }
+    }
+
+    /// Releases the voice if its gate ends at or before `tick`.
+    pub(crate) fn check_gate_end(&mut self, tick: u64) {
+        if self.gate_end.is_some_and(|end| tick >= end) {
+            self.gate_end = None;
+            if !self.triggered {
+                self.trigger_envelopes();
+                self.triggered = true;
+            }
+            self.force_release();
         }
     }
 
@@ -633,8 +882,24 @@ impl Voice {
         if !self.triggered {
             self.trigger_envelopes();
             self.triggered = true;
+            // A new note starts at its bend and pressure, not gliding to them
+            self.bend_now = self.params.bend;
+            self.pressure_now = self.params.pressure;
         }
 
+        let lfos = self.params.lfos.iter().any(|l| l.target.is_some());
+        if lfos || self.pressure_applied.is_some() {
+            self.remove_mod_offsets();
+        }
+        if self.param_mod_count > 0 {
+            self.apply_mods(isr);
+        }
+        if lfos {
+            self.apply_lfos(isr);
+        }
+        self.apply_expression(isr);
+
+        // After the mods, which can drive the envelope's stages
         let env = self.dahdsr.update(
             isr,
             self.params.envdelay,
@@ -648,10 +913,6 @@ impl Voice {
             return None;
         }
 
-        if self.param_mod_count > 0 {
-            self.apply_mods(isr);
-        }
-
         Some((env, self.compute_freq(isr)))
     }
 
@@ -676,6 +937,7 @@ impl Voice {
     }
 
     #[cfg(not(feature = "native"))]
+    #[allow(clippy::too_many_arguments)]
     pub fn process(
         &mut self,
         isr: f32,
@@ -733,6 +995,16 @@ impl Voice {
             self.ch[c] *= self.params.gain;
         }
 
+        // Transient shaper
+        if self.params.transattack != 0.0 || self.params.transsustain != 0.0 {
+            self.transient.process(
+                &mut self.ch[..nch],
+                self.params.transattack,
+                self.params.transsustain,
+                isr,
+            );
+        }
+
         // SVF filters (LP -> HP -> BP)
         if self.params.lpf.is_some() {
             for c in 0..nch {
@@ -808,6 +1080,8 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("filters", self.ch[..nch]);
+
         // Distortion effects
         if let Some(coarse_factor) = self.params.coarse {
             for c in 0..nch {
@@ -835,6 +1109,8 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("distortion", self.ch[..nch]);
+
         // DC blocker: asymmetric drive + modulation upstream can park a DC
         // offset. Only runs if any distortion stage was active.
         if self.params.coarse.is_some()
@@ -848,6 +1124,8 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("dc blocker", self.ch[..nch]);
+
         // AM modulation (LFO ticks once, applied per-channel)
         if self.params.am > 0.0 {
             let modulator = self.am_lfo.lfo(self.params.amshape, self.params.am, isr);
@@ -858,6 +1136,8 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("am", self.ch[..nch]);
+
         // Ring modulation
         if self.params.rm > 0.0 {
             let modulator = self.rm_lfo.lfo(self.params.rmshape, self.params.rm, isr);
@@ -868,10 +1148,22 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("ring mod", self.ch[..nch]);
+
+        // Frequency shifter
+        if self.params.fshift != 0.0 {
+            for c in 0..nch {
+                let wet = self.fshift[c].process(self.ch[c], self.params.fshift, self.sr);
+                self.ch[c] = mix_equal_power(self.ch[c], wet, self.params.fshiftmix);
+            }
+        }
+
+        dsp_check_bounded!("fshift", self.ch[..nch]);
+
         // Phaser
         if self.params.phaser > 0.0 {
             for c in 0..nch {
-                self.ch[c] = self.phaser[c].process(
+                let wet = self.phaser[c].process(
                     self.ch[c],
                     self.params.phaser,
                     self.params.phaserdepth,
@@ -880,14 +1172,17 @@ impl Voice {
                     self.sr,
                     isr,
                 );
+                self.ch[c] = mix_equal_power(self.ch[c], wet, self.params.phasermix);
             }
         }
 
+        dsp_check_bounded!("phaser", self.ch[..nch]);
+
         // Flanger (must be pre-allocated via ensure_effects)
         if self.params.flanger > 0.0 {
             if let Some(flanger) = self.flanger.as_mut() {
                 for c in 0..nch {
-                    self.ch[c] = flanger[c].process(
+                    let wet = flanger[c].process(
                         self.ch[c],
                         self.params.flanger,
                         self.params.flangerdepth,
@@ -895,10 +1190,13 @@ impl Voice {
                         self.sr,
                         isr,
                     );
+                    self.ch[c] = mix_equal_power(self.ch[c], wet, self.params.flangermix);
                 }
             }
         }
 
+        dsp_check_bounded!("flanger", self.ch[..nch]);
+
         // EQ
         if self.params.eqlo != 0.0 || self.params.eqmid != 0.0 || self.params.eqhi != 0.0 {
             for c in 0..nch {
@@ -915,6 +1213,8 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("eq", self.ch[..nch]);
+
         // Tilt
         if self.params.tilt != 0.0 {
             for c in 0..nch {
@@ -922,19 +1222,43 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("tilt", self.ch[..nch]);
+
         // Smear
         if self.params.smear > 0.0 {
             for c in 0..nch {
-                self.ch[c] = self.smear[c].process(
+                let wet = self.smear[c].process(
                     self.ch[c],
                     self.params.smear,
                     self.params.smearfreq,
                     self.params.smearfb,
                     self.sr,
                 );
+                self.ch[c] = mix_equal_power(self.ch[c], wet, self.params.smearmix);
             }
         }
 
+        dsp_check_bounded!("smear", self.ch[..nch]);
+
+        // Resonator bank, tuned to the current pitch
+        if self.params.reso > 0.0 {
+            if let Some(bank) = self.reso.as_mut() {
+                bank.update(
+                    self.current_freq,
+                    self.params.resostruct,
+                    self.params.resobright,
+                    self.params.resodamp,
+                    self.sr,
+                );
+                for c in 0..nch {
+                    let wet = bank.process(self.ch[c], c);
+                    self.ch[c] = mix_equal_power(self.ch[c], wet, self.params.reso);
+                }
+            }
+        }
+
+        dsp_check_bounded!("reso", self.ch[..nch]);
+
         // VCA: envelope × postgain × velocity
         let voice_gain = env * self.params.postgain * self.params.velocity;
         for c in 0..nch {
@@ -952,6 +1276,30 @@ impl Voice {
             }
         }
 
+        dsp_check_bounded!("vca", self.ch);
+
+        // Compressor, after the VCA so it shapes the note's dynamics
+        if self.params.compthresh < 0.0 {
+            let params = DynamicsParams {
+                threshold: self.params.compthresh,
+                ratio: self.params.compratio,
+                makeup: self.params.compmakeup,
+                attack: self.params.compattack,
+                release: self.params.comprelease,
+            };
+            self.dynamics.process(&mut self.ch, &params, isr);
+            dsp_check_bounded!("compressor", self.ch);
+        }
+
+        // Decorrelation (must be pre-allocated via ensure_effects)
+        if self.params.decor > 0.0 {
+            if let Some(decor) = self.decor.as_mut() {
+                decor.process(&mut self.ch, self.params.decor, self.sr);
+            }
+        }
+
+        dsp_check_bounded!("decor", self.ch);
+
         // Chorus (must be pre-allocated via ensure_effects)
         if self.params.chorus > 0.0 {
             if let Some(chorus) = self.chorus.as_mut() {
@@ -964,11 +1312,14 @@ impl Voice {
                     self.sr,
                     isr,
                 );
-                self.ch[0] = stereo[0];
-                self.ch[1] = stereo[1];
+                let mix = self.params.chorusmix;
+                self.ch[0] = mix_equal_power(self.ch[0], stereo[0], mix);
+                self.ch[1] = mix_equal_power(self.ch[1], stereo[1], mix);
             }
         }
 
+        dsp_check_bounded!("chorus", self.ch);
+
         // Stereo width (mid-side matrix)
         if self.params.width != 1.0 {
             let mid = (self.ch[0] + self.ch[1]) * 0.5;
@@ -978,6 +1329,8 @@ impl Voice {
             self.ch[1] = mid - side * w;
         }
 
+        dsp_check_bounded!("width", self.ch);
+
         // Haas (must be pre-allocated via ensure_effects)
         if self.params.haas > 0.0 {
             if let Some(haas) = self.haas.as_mut() {
@@ -985,11 +1338,18 @@ impl Voice {
             }
         }
 
-        // Panning
-        if self.params.pan != 0.5 {
-            let pan_pos = self.params.pan * PI / 2.0;
-            self.ch[0] *= cosf(pan_pos);
-            self.ch[1] *= sinf(pan_pos);
+        // User insert slots
+        if !self.inserts.is_empty() {
+            self.inserts.process(&mut self.ch);
+            dsp_check_bounded!("voice inserts", self.ch);
+        }
+
+        // Panning; `pan2d` voices are placed on the speaker ring instead
+        let ring = self.params.pan2d.is_some() && !self.params.cue;
+        if self.params.pan != 0.5 && !ring {
+            let [left, right] = pan_gains(self.params.pan, self.params.panlaw);
+            self.ch[0] *= left;
+            self.ch[1] *= right;
         }
 
         for c in 0..CHANNELS {
@@ -997,12 +1357,53 @@ impl Voice {
         }
 
         self.time += isr;
+
+        dsp_check_bounded!("voice output", self.ch);
+    }
+}
+
+/// Key stored in [`VoiceParams::group`] for a group name (FNV-1a).
+pub(crate) fn group_key(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Left/right gains for `pan` under `law`.
+#[inline]
+fn pan_gains(pan: f32, law: PanLaw) -> [f32; 2] {
+    match law {
+        PanLaw::Power => {
+            let pan_pos = pan * PI / 2.0;
+            [cosf(pan_pos), sinf(pan_pos)]
+        }
+        PanLaw::Linear => {
+            let pan = pan.clamp(0.0, 1.0);
+            [(2.0 - 2.0 * pan).min(1.0), (2.0 * pan).min(1.0)]
+        }
+    }
+}
+
+/// Equal-power crossfade from `dry` (mix 0) to `wet` (mix 1).
+#[inline]
+fn mix_equal_power(dry: f32, wet: f32, mix: f32) -> f32 {
+    if mix >= 1.0 {
+        return wet;
+    }
+    if mix <= 0.0 {
+        return dry;
     }
+    let theta = mix * (PI / 2.0);
+    dry * cosf(theta) + wet * sinf(theta)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "native")]
+    use crate::types::Source;
+    #[cfg(feature = "native")]
+    use crate::Engine;
 
     #[test]
     fn additive_cache_invalidates_on_reset() {
@@ -1017,6 +1418,14 @@ mod tests {
         assert!(!voice.shape_active);
     }
 
+    #[cfg(feature = "debug-dsp")]
+    #[test]
+    #[should_panic(expected = "Gain written with NaN")]
+    fn debug_dsp_rejects_non_finite_param_writes() {
+        let mut voice = Voice::default();
+        voice.write_param(ParamId::Gain, f32::NAN);
+    }
+
     #[test]
     fn additive_cache_invalidates_for_additive_params_only() {
         let mut voice = Voice::default();
@@ -1044,4 +1453,178 @@ mod tests {
         voice.sync_source_state();
         assert!(!voice.shape_active);
     }
+
+    #[test]
+    fn resonator_bank_rings_at_pitch_and_decays_with_damping() {
+        let sr = 48000.0;
+        let tail_energy = |damp: f32| {
+            let mut bank = ResonatorBank::default();
+            bank.update(200.0, 0.0, 0.5, damp, sr);
+            let out: Vec<f32> = (0..9600)
+                .map(|i| bank.process(if i == 0 { 1.0 } else { 0.0 }, 0))
+                .collect();
+            // Harmonic structure: the ring repeats every 1/200 s
+            let period = (sr / 200.0) as usize;
+            let corr: f32 = (4800..4800 + period)
+                .map(|i| out[i] * out[i + period])
+                .sum();
+            assert!(corr > 0.0);
+            out[4800..].iter().map(|s| s * s).sum::<f32>()
+        };
+        assert!(tail_energy(0.1) > tail_energy(0.9) * 100.0);
+        assert!(tail_energy(0.9) > 0.0);
+    }
+
+    #[test]
+    fn pluck_rings_at_pitch_and_decays_with_pluckdecay() {
+        let sr = 48000.0;
+        let render = |decay: f32, excite: crate::types::PluckExcite| {
+            let mut voice = Voice::default();
+            voice.params.pluckdecay = decay;
+            voice.params.pluck_excite = excite;
+            (0..24000)
+                .map(|_| {
+                    voice.run_pluck(200.0, 1.0 / sr);
+                    voice.ch[0]
+                })
+                .collect::<Vec<f32>>()
+        };
+        let energy = |out: &[f32]| out.iter().map(|s| s * s).sum::<f32>();
+        for excite in [
+            crate::types::PluckExcite::Noise,
+            crate::types::PluckExcite::Impulse,
+        ] {
+            let out = render(2.0, excite);
+            // The ring repeats every 1/200 s
+            let period = (sr / 200.0) as usize;
+            let corr: f32 = (12000..12000 + period)
+                .map(|i| out[i] * out[i + period])
+                .sum();
+            let norm = energy(&out[12000..12000 + period]);
+            assert!(corr > 0.9 * norm, "{excite:?}: {corr} vs {norm}");
+            // No DC left to ring out
+            let mean = out[12000..].iter().sum::<f32>() / 12000.0;
+            assert!(mean.abs() < 1e-3, "{excite:?}: mean {mean}");
+        }
+        let short = render(0.2, crate::types::PluckExcite::Noise);
+        let long = render(2.0, crate::types::PluckExcite::Noise);
+        assert!(energy(&long[12000..]) > energy(&short[12000..]) * 1000.0);
+        assert!(energy(&short[..2400]) > 0.0);
+    }
+
+    #[test]
+    fn decor_decorrelates_mono_and_folds_back_cleanly() {
+        let mut decor = Decorrelator::default();
+        let norm = 2f32.sqrt().recip();
+        let mut seed = 1u32;
+        let (mut ll, mut rr, mut lr) = (0.0, 0.0, 0.0);
+        for _ in 0..48000 {
+            seed = modulation::lcg(seed);
+            let x = (seed >> 16) as f32 / 32768.0 - 1.0;
+            let mut frame = [x, x];
+            decor.process(&mut frame, 1.0, 48000.0);
+            // Mono sum is the dry signal, only scaled
+            assert!(((frame[0] + frame[1]) * 0.5 - x * norm).abs() < 1e-5);
+            ll += frame[0] * frame[0];
+            rr += frame[1] * frame[1];
+            lr += frame[0] * frame[1];
+        }
+        let corr = lr / (ll * rr).sqrt();
+        assert!(corr.abs() < 0.1, "correlation {corr}");
+        assert!((ll / rr - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn linear_pan_law_is_unity_at_center_and_balances_off_center() {
+        assert_eq!(pan_gains(0.5, PanLaw::Linear), [1.0, 1.0]);
+        assert_eq!(pan_gains(0.25, PanLaw::Linear), [1.0, 0.5]);
+        assert_eq!(pan_gains(1.0, PanLaw::Linear), [0.0, 1.0]);
+        let [l, r] = pan_gains(0.3, PanLaw::Power);
+        assert!((l * l + r * r - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn mix_equal_power_endpoints_and_midpoint() {
+        assert_eq!(mix_equal_power(0.3, 0.9, 1.0), 0.9);
+        assert_eq!(mix_equal_power(0.3, 0.9, 0.0), 0.3);
+        // Uncorrelated unit signals keep unit power at the midpoint
+        let g = mix_equal_power(1.0, 0.0, 0.5);
+        let h = mix_equal_power(0.0, 1.0, 0.5);
+        assert!((g * g + h * h - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn bend_and_pressure_glide_on_a_held_voice() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate(
+            "/sound/saw/freq/220/bend/+12/lpf/1000/pressuretarget/lpf/pressuredepth/2000",
+        );
+        engine.dsp();
+        // The first note starts at its bend, no glide in
+        assert_eq!(engine.voices[0].bend_now, 12.0);
+        assert!((engine.voices[0].current_freq - 440.0).abs() < 0.01);
+
+        engine.evaluate("/voice/0/bend/0/pressure/0.5/exprslew/0.01");
+        engine.dsp();
+        let v = &engine.voices[0];
+        assert!(v.bend_now > 0.0 && v.bend_now < 12.0);
+        for _ in 0..100 {
+            engine.dsp();
+        }
+        let v = &engine.voices[0];
+        assert!(v.bend_now.abs() < 1e-3 && (v.current_freq - 220.0).abs() < 0.1);
+        assert!((v.params.lpf.unwrap() - 2000.0).abs() < 1.0);
+
+        // Unmapping pressure hands the target back untouched
+        engine.evaluate("/voice/0/pressuretarget/off");
+        engine.dsp();
+        assert!((engine.voices[0].params.lpf.unwrap() - 1000.0).abs() < 0.1);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn revert_flips_a_voice_between_its_last_two_param_sets() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/voice/0/sound/saw/lpf/800/gain/0.5");
+        // Nothing to go back to on a fresh voice
+        engine.evaluate("/voice/0/revert/1");
+        assert_eq!(engine.voices[0].params.lpf, Some(800.0));
+
+        engine.evaluate("/voice/0/lpf/3000/gain/0.8/attackcurve/1");
+        engine.evaluate("/voice/0/revert/1");
+        let p = &engine.voices[0].params;
+        assert_eq!((p.lpf, p.gain, p.attackcurve), (Some(800.0), 0.5, 0.5));
+        assert_eq!(p.sound, Source::Saw);
+
+        engine.evaluate("/voice/0/revert/1");
+        let p = &engine.voices[0].params;
+        assert_eq!((p.lpf, p.gain, p.attackcurve), (Some(3000.0), 0.8, 1.0));
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn voice_envelopes_reach_the_metrics() {
+        use crate::dsp::DahdsrState;
+
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/attack/1/gate/0");
+        engine.evaluate("/sound/sine/attack/0.001/decay/0.001/sustain/0.5/gate/0");
+        for _ in 0..4 {
+            engine.process_block(&mut [0.0; 128], &[], &[]);
+        }
+        let envs = engine.metrics.voice_envelopes();
+        assert_eq!(envs.len(), 2);
+        assert_eq!(envs[0].voice_id, engine.voices[0].id);
+        assert_eq!(envs[0].stage, DahdsrState::Attack);
+        assert!(envs[0].value > 0.0 && envs[0].value < 0.1);
+        assert_eq!(envs[1].stage, DahdsrState::Sustain);
+        assert!((envs[1].value - 0.5).abs() < 1e-3);
+
+        engine.evaluate("/doux/panic");
+        for _ in 0..8 {
+            engine.process_block(&mut [0.0; 128], &[], &[]);
+        }
+        assert!(engine.metrics.voice_envelopes().is_empty());
+    }
 }
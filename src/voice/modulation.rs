@@ -1,4 +1,8 @@
 use std::f32::consts::PI;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::dsp::envelope::Dahdsr;
 use crate::dsp::{cosf, exp2f, log2f, sinf};
@@ -8,7 +12,7 @@ pub fn lcg(seed: u32) -> u32 {
     seed.wrapping_mul(1103515245).wrapping_add(12345)
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ModCurve {
     Linear,
     Exponential,
@@ -18,7 +22,7 @@ pub enum ModCurve {
     Stair,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ModShape {
     Sine,
     Triangle,
@@ -29,7 +33,44 @@ pub enum ModShape {
     Drunk,
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Most breakpoints in one `a>b:t>c:t...` curve.
+pub const MAX_BREAKPOINTS: usize = 32;
+
+/// Multi-segment automation curve, built when the event is parsed so
+/// triggering it only clones an `Arc`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoints {
+    values: Vec<f32>,
+    /// Duration in seconds and curve of the segment ending at `values[i + 1]`.
+    segments: Vec<(f32, ModCurve)>,
+    /// Breakpoint to jump back to after the last segment; holds when `None`.
+    loop_from: Option<usize>,
+}
+
+impl Breakpoints {
+    /// Advances `elapsed` seconds into `segment` by `dt` and returns the value.
+    fn advance(&self, segment: &mut usize, elapsed: &mut f32, dt: f32) -> f32 {
+        *elapsed += dt;
+        loop {
+            let (dur, curve) = self.segments[*segment];
+            if *elapsed < dur {
+                let t = *elapsed / dur;
+                return interpolate(self.values[*segment], self.values[*segment + 1], t, curve);
+            }
+            match self.loop_from {
+                _ if *segment + 1 < self.segments.len() => *segment += 1,
+                Some(from) => *segment = from,
+                None => {
+                    *elapsed = dur;
+                    return self.values[*segment + 1];
+                }
+            }
+            *elapsed -= dur;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ModChain {
     Oscillate {
         min: f32,
@@ -57,11 +98,19 @@ pub enum ModChain {
         freq: f32,
         curve: ModCurve,
     },
+    Breakpoints(Arc<Breakpoints>),
+    /// Tracks another parameter of the same voice: `follow:<param>:<scale>`.
+    Follow {
+        source: ParamId,
+        scale: f32,
+    },
 }
 
 impl ModChain {
     pub fn parse(s: &str) -> Option<Self> {
-        if s.contains('^') {
+        if let Some(rest) = s.strip_prefix("follow:") {
+            Self::parse_follow(rest)
+        } else if s.contains('^') {
             Self::parse_envelope(s)
         } else if s.contains('>') {
             if s.starts_with('>') {
@@ -128,9 +177,27 @@ impl ModChain {
                 freq,
                 curve,
             },
+            // Scaling is relative to the source parameter's own units
+            ModChain::Follow { .. } => self,
+            ModChain::Breakpoints(bp) => ModChain::Breakpoints(Arc::new(Breakpoints {
+                values: bp.values.iter().map(|&v| f(v)).collect(),
+                segments: bp.segments.clone(),
+                loop_from: bp.loop_from,
+            })),
         }
     }
 
+    fn parse_follow(s: &str) -> Option<Self> {
+        let (name, scale) = match s.split_once(':') {
+            Some((name, scale)) => (name, scale.parse().ok()?),
+            None => (s, 1.0),
+        };
+        Some(ModChain::Follow {
+            source: name.parse().ok()?,
+            scale,
+        })
+    }
+
     fn parse_envelope(s: &str) -> Option<Self> {
         let caret = s.find('^')?;
         let min: f32 = s[..caret].parse().ok()?;
@@ -212,6 +279,9 @@ impl ModChain {
 
     fn parse_transition(s: &str) -> Option<Self> {
         let parts: Vec<&str> = s.split('>').collect();
+        if parts.len() > 2 {
+            return Self::parse_breakpoints(&parts);
+        }
         if parts.len() != 2 {
             return None;
         }
@@ -237,6 +307,43 @@ impl ModChain {
         })
     }
 
+    /// `v0>v1:d1>v2:d2...`, each duration taking its own curve suffix. A
+    /// trailing `~` loops back to the start, `~N` to breakpoint `N`.
+    fn parse_breakpoints(parts: &[&str]) -> Option<Self> {
+        if parts.len() > MAX_BREAKPOINTS {
+            return None;
+        }
+        let mut values = vec![parts[0].parse().ok()?];
+        let mut segments = Vec::with_capacity(parts.len() - 1);
+        let mut loop_from = None;
+        for (i, part) in parts[1..].iter().enumerate() {
+            let colon = part.find(':')?;
+            values.push(part[..colon].parse().ok()?);
+            let mut dur_str = &part[colon + 1..];
+            if let Some(tilde) = dur_str.find('~') {
+                if i + 2 != parts.len() {
+                    return None;
+                }
+                let to = &dur_str[tilde + 1..];
+                loop_from = Some(if to.is_empty() { 0 } else { to.parse().ok()? });
+                dur_str = &dur_str[..tilde];
+            }
+            let (dur, curve) = parse_duration_curve(dur_str)?;
+            if dur <= 0.0 {
+                return None;
+            }
+            segments.push((dur, curve));
+        }
+        if loop_from.is_some_and(|from| from >= segments.len()) {
+            return None;
+        }
+        Some(ModChain::Breakpoints(Arc::new(Breakpoints {
+            values,
+            segments,
+            loop_from,
+        })))
+    }
+
     fn parse_slew(s: &str) -> Option<Self> {
         let rest = &s[1..];
         let colon = rest.find(':')?;
@@ -296,7 +403,7 @@ fn interpolate(from: f32, to: f32, t: f32, curve: ModCurve) -> f32 {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum ParamId {
     Freq,
@@ -348,15 +455,21 @@ pub enum ParamId {
     Phaserdepth,
     Phasersweep,
     Phasercenter,
+    Phasermix,
     Flanger,
     Flangerdepth,
     Flangerfeedback,
+    Flangermix,
     Smear,
     Smearfreq,
     Smearfb,
+    Smearmix,
+    Fshift,
+    Fshiftmix,
     Chorus,
     Chorusdepth,
     Chorusdelay,
+    Chorusmix,
     Fold,
     Crush,
     Coarse,
@@ -374,12 +487,194 @@ pub enum ParamId {
     Mirror,
     SyncRatio,
     SyncPhase,
+    Reso,
+    Resostruct,
+    Resobright,
+    Resodamp,
+    Decor,
+    Inputgain,
+    Pluckdamp,
+    Pluckdecay,
+    Pan2d,
+    Pan2dspread,
+    Transattack,
+    Transsustain,
+    Envdelay,
+    Attack,
+    Hold,
+    Decay,
+    Sustain,
+    Release,
+}
+
+impl ParamId {
+    /// Range the parameter is kept in, wherever its value comes from: an
+    /// event, a modulation chain, an LFO or pressure. Looser than the
+    /// documented ranges, it keeps values the DSP can't handle (negative
+    /// frequencies, runaway feedback) out of the voice.
+    pub fn range(self) -> (f32, f32) {
+        use ParamId::*;
+        match self {
+            Freq => (0.0, 96_000.0),
+            Gain | Postgain | Inputgain => (0.0, 64.0),
+            Speed | Fmh | Fm2h => (-64.0, 64.0),
+            Stretch | SyncRatio => (0.0, 64.0),
+            Detune => (-4800.0, 4800.0),
+            Harmonics | Timbre | Morph => (0.01, 0.999),
+            Partials => (1.0, 32.0),
+            Pan | Pw | Wave | Sub | Scan | Mirror | SyncPhase | Fmpivot | Amdepth | Rmdepth
+            | Phaserdepth | Phasermix | Flangerdepth | Flangermix | Smear | Smearmix
+            | Fshiftmix | Reso | Resostruct | Resobright | Resodamp | Chorusdepth | Chorusmix
+            | Fold | Decor | Pluckdamp | Pan2dspread | Sustain => (0.0, 1.0),
+            Lpq | Hpq | Bpq | Slpq | Shpq | Sbpq | Llpq | Lhpq | Lbpq => (0.0, 1.0),
+            Flangerfeedback | Smearfb => (0.0, 0.95),
+            Lpf | Hpf | Bpf | Slpf | Shpf | Sbpf | Llpf | Lhpf | Lbpf | Phasercenter
+            | Phasersweep | EqLoFreq | EqMidFreq | EqHiFreq => (0.0, 20_000.0),
+            Smearfreq => (20.0, 20_000.0),
+            Vib | Am | Rm | Phaser | Flanger | Chorus => (0.0, 20_000.0),
+            Fshift => (-20_000.0, 20_000.0),
+            Vibmod => (0.0, 96.0),
+            Fm | Fm2 => (0.0, 100.0),
+            Fmfb => (-10.0, 10.0),
+            Chorusdelay => (0.0, 100.0),
+            Crush => (1.0, 16.0),
+            Coarse => (1.0, 1024.0),
+            Wrap => (0.0, 64.0),
+            Distort => (0.0, 100.0),
+            Eqlo | Eqmid | Eqhi => (-48.0, 48.0),
+            Tilt | Transattack | Transsustain => (-1.0, 1.0),
+            Width => (0.0, 2.0),
+            Haas => (0.0, 35.0),
+            Pluckdecay => (0.01, 60.0),
+            Envdelay | Attack | Hold | Decay | Release => (0.0, 600.0),
+            Pan2d => (-16.0, 16.0),
+        }
+    }
+
+    /// `val` clamped into [`range`](Self::range), or `None` for NaN.
+    #[inline]
+    pub fn clamp(self, val: f32) -> Option<f32> {
+        let (lo, hi) = self.range();
+        (!val.is_nan()).then(|| val.clamp(lo, hi))
+    }
+}
+
+impl FromStr for ParamId {
+    type Err = ();
+
+    /// Accepts the event key (or alias) that sets the parameter.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "freq" => ParamId::Freq,
+            "detune" => ParamId::Detune,
+            "speed" => ParamId::Speed,
+            "stretch" => ParamId::Stretch,
+            "pw" => ParamId::Pw,
+            "mirror" => ParamId::Mirror,
+            "harmonics" | "harm" => ParamId::Harmonics,
+            "timbre" => ParamId::Timbre,
+            "morph" => ParamId::Morph,
+            "partials" => ParamId::Partials,
+            "wave" | "waveform" => ParamId::Wave,
+            "pluckdamp" => ParamId::Pluckdamp,
+            "pluckdecay" => ParamId::Pluckdecay,
+            "sub" => ParamId::Sub,
+            "sync" => ParamId::SyncRatio,
+            "syncphase" | "syncph" => ParamId::SyncPhase,
+            "scan" => ParamId::Scan,
+            "gain" => ParamId::Gain,
+            "postgain" => ParamId::Postgain,
+            "pan" => ParamId::Pan,
+            "pan2d" => ParamId::Pan2d,
+            "pan2dspread" => ParamId::Pan2dspread,
+            "lpf" | "cutoff" => ParamId::Lpf,
+            "lpq" | "resonance" => ParamId::Lpq,
+            "hpf" | "hcutoff" => ParamId::Hpf,
+            "hpq" | "hresonance" => ParamId::Hpq,
+            "bpf" | "bandf" => ParamId::Bpf,
+            "bpq" | "bandq" => ParamId::Bpq,
+            "slpf" => ParamId::Slpf,
+            "slpq" => ParamId::Slpq,
+            "shpf" => ParamId::Shpf,
+            "shpq" => ParamId::Shpq,
+            "sbpf" => ParamId::Sbpf,
+            "sbpq" => ParamId::Sbpq,
+            "llpf" => ParamId::Llpf,
+            "llpq" => ParamId::Llpq,
+            "lhpf" => ParamId::Lhpf,
+            "lhpq" => ParamId::Lhpq,
+            "lbpf" => ParamId::Lbpf,
+            "lbpq" => ParamId::Lbpq,
+            "vib" => ParamId::Vib,
+            "vibmod" => ParamId::Vibmod,
+            "fm" | "fmi" => ParamId::Fm,
+            "fmh" => ParamId::Fmh,
+            "fm2" => ParamId::Fm2,
+            "fm2h" => ParamId::Fm2h,
+            "fmpivot" => ParamId::Fmpivot,
+            "fmfb" => ParamId::Fmfb,
+            "am" => ParamId::Am,
+            "amdepth" => ParamId::Amdepth,
+            "rm" => ParamId::Rm,
+            "rmdepth" => ParamId::Rmdepth,
+            "phaser" | "phaserrate" => ParamId::Phaser,
+            "phaserdepth" => ParamId::Phaserdepth,
+            "phasersweep" => ParamId::Phasersweep,
+            "phasercenter" => ParamId::Phasercenter,
+            "phasermix" => ParamId::Phasermix,
+            "flanger" | "flangerrate" => ParamId::Flanger,
+            "flangerdepth" => ParamId::Flangerdepth,
+            "flangerfeedback" => ParamId::Flangerfeedback,
+            "flangermix" => ParamId::Flangermix,
+            "smear" => ParamId::Smear,
+            "smearfreq" => ParamId::Smearfreq,
+            "smearfb" => ParamId::Smearfb,
+            "smearmix" => ParamId::Smearmix,
+            "fshift" => ParamId::Fshift,
+            "fshiftmix" => ParamId::Fshiftmix,
+            "reso" => ParamId::Reso,
+            "resostruct" => ParamId::Resostruct,
+            "resobright" => ParamId::Resobright,
+            "resodamp" => ParamId::Resodamp,
+            "chorus" | "chorusrate" => ParamId::Chorus,
+            "chorusdepth" => ParamId::Chorusdepth,
+            "chorusdelay" => ParamId::Chorusdelay,
+            "chorusmix" => ParamId::Chorusmix,
+            "coarse" => ParamId::Coarse,
+            "crush" => ParamId::Crush,
+            "fold" => ParamId::Fold,
+            "wrap" => ParamId::Wrap,
+            "distort" => ParamId::Distort,
+            "width" => ParamId::Width,
+            "haas" => ParamId::Haas,
+            "decor" => ParamId::Decor,
+            "transattack" => ParamId::Transattack,
+            "transsustain" => ParamId::Transsustain,
+            "envdelay" | "envdly" => ParamId::Envdelay,
+            "attack" => ParamId::Attack,
+            "hold" | "hld" => ParamId::Hold,
+            "decay" => ParamId::Decay,
+            "sustain" => ParamId::Sustain,
+            "release" => ParamId::Release,
+            "inputgain" => ParamId::Inputgain,
+            "eqlo" => ParamId::Eqlo,
+            "eqmid" => ParamId::Eqmid,
+            "eqhi" => ParamId::Eqhi,
+            "eqlofreq" => ParamId::EqLoFreq,
+            "eqmidfreq" => ParamId::EqMidFreq,
+            "eqhifreq" => ParamId::EqHiFreq,
+            "tilt" => ParamId::Tilt,
+            _ => return Err(()),
+        })
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct ParamMod {
     pub chain: ModChain,
     pub phase: f32,
+    /// Current segment of a `Breakpoints` curve (`phase` counts seconds in it).
+    pub segment: usize,
     pub prev_rand: f32,
     pub next_rand: f32,
     pub seed: u32,
@@ -397,6 +692,7 @@ impl Default for ParamMod {
                 shape: ModShape::Sine,
             },
             phase: 0.0,
+            segment: 0,
             prev_rand: 0.0,
             next_rand: 0.0,
             seed: 0,
@@ -411,6 +707,7 @@ impl ParamMod {
         let mut m = Self {
             chain,
             phase: 0.0,
+            segment: 0,
             prev_rand: 0.0,
             next_rand: 0.0,
             seed,
@@ -433,6 +730,13 @@ impl ParamMod {
         }
     }
 
+    /// Restarts the chain from its first cycle/segment, keeping its random state.
+    pub fn resync(&mut self, gate: f32) {
+        self.phase = 0.0;
+        self.segment = 0;
+        self.trigger(gate);
+    }
+
     pub fn force_release(&mut self) {
         if matches!(self.chain, ModChain::Envelope { .. }) {
             self.envelope.force_release();
@@ -482,6 +786,9 @@ impl ParamMod {
                 min + (max - min) * env_val
             }
             ModChain::Slew { target, .. } => target,
+            ModChain::Breakpoints(ref bp) => bp.advance(&mut self.segment, &mut self.phase, isr),
+            // Resolved by the voice, which owns the source parameter
+            ModChain::Follow { .. } => 0.0,
         }
     }
 
@@ -555,6 +862,8 @@ impl ParamMod {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "native")]
+    use crate::Engine;
 
     #[test]
     fn parse_legacy_returns_none() {
@@ -744,8 +1053,68 @@ mod tests {
     }
 
     #[test]
-    fn parse_transition_multi_segment_rejected() {
-        assert!(ModChain::parse("200>4000:1>800:2").is_none());
+    fn parse_transition_multi_segment() {
+        let m = ModChain::parse("200>4000:1>800:2e>1000:0.5~1").unwrap();
+        let ModChain::Breakpoints(bp) = m else {
+            panic!("expected Breakpoints");
+        };
+        assert_eq!(bp.values, [200.0, 4000.0, 800.0, 1000.0]);
+        assert_eq!(
+            bp.segments,
+            [
+                (1.0, ModCurve::Linear),
+                (2.0, ModCurve::Exponential),
+                (0.5, ModCurve::Linear)
+            ]
+        );
+        assert_eq!(bp.loop_from, Some(1));
+
+        // Loop point out of range, `~` before the last segment, too many points
+        assert!(ModChain::parse("0>1:1>2:1~2").is_none());
+        assert!(ModChain::parse("0>1:1~>2:1").is_none());
+        let long = (0..=MAX_BREAKPOINTS)
+            .map(|i| format!("{i}:1"))
+            .collect::<Vec<_>>();
+        assert!(ModChain::parse(&format!("0>{}", long.join(">"))).is_none());
+    }
+
+    #[test]
+    fn parse_follow() {
+        match ModChain::parse("follow:freq:2").unwrap() {
+            ModChain::Follow { source, scale } => {
+                assert_eq!(source, ParamId::Freq);
+                assert_eq!(scale, 2.0);
+            }
+            _ => panic!("expected Follow"),
+        }
+        match ModChain::parse("follow:cutoff").unwrap() {
+            ModChain::Follow { source, scale } => {
+                assert_eq!(source, ParamId::Lpf);
+                assert_eq!(scale, 1.0);
+            }
+            _ => panic!("expected Follow"),
+        }
+        assert!(ModChain::parse("follow:nope:2").is_none());
+        assert!(ModChain::parse("follow:freq:x").is_none());
+    }
+
+    #[test]
+    fn breakpoints_walk_segments_then_loop_or_hold() {
+        let isr = 0.25;
+        let run = |s: &str, n: usize| {
+            let mut m = ParamMod::new(ModChain::parse(s).unwrap(), 1);
+            (0..n).map(|_| m.tick(isr)).collect::<Vec<f32>>()
+        };
+        // 1 s up, 1 s down, 1 s up again, then hold
+        let held = run("0>4:1>0:1>8:1", 16);
+        assert_eq!(held[..4], [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(held[4..8], [3.0, 2.0, 1.0, 0.0]);
+        assert_eq!(held[10], 6.0);
+        assert!(held[12..].iter().all(|&v| v == 8.0));
+
+        // Looping from breakpoint 1 replays 4 → 0 → 8
+        let looped = run("0>4:1>0:1>8:1~1", 16);
+        assert_eq!(looped[12..16], [3.0, 2.0, 1.0, 0.0]);
     }
 
     #[test]
@@ -946,4 +1315,106 @@ mod tests {
             _ => panic!("expected Envelope"),
         }
     }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn follow_tracks_another_param_of_the_voice() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/saw/freq/200>400:0.1/lpf/follow:freq:2/pan/follow:scan");
+        for _ in 0..10 {
+            engine.dsp();
+        }
+        let p = &engine.voices[0].params;
+        assert!(p.freq > 200.0 && p.freq < 400.0);
+        assert_eq!(p.lpf, Some(p.freq * 2.0));
+        assert_eq!(p.pan, p.scan);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn modsync_restarts_mods_of_a_running_voice() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/saw/voice/0/gate/0/lpf/200~2000:0.3t");
+        for _ in 0..10 {
+            engine.dsp();
+        }
+        let phase = |e: &Engine| e.voices[0].param_mods[0].1.phase;
+        let running = phase(&engine);
+        assert!(running > 0.0);
+
+        // Plain updates leave the LFO free-running
+        engine.evaluate("/voice/0/note/43");
+        assert_eq!(phase(&engine), running);
+
+        engine.evaluate("/voice/0/note/45/modsync/1");
+        assert_eq!(engine.voices[0].param_mod_count, 1);
+        assert_eq!(phase(&engine), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn voice_lfo_swings_target_around_its_set_value() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate(
+            "/sound/saw/voice/0/gate/0/lpf/1000/lfo2target/lpf/lfo2depth/500/lfo2rate/10",
+        );
+        let (mut lo, mut hi) = (f32::MAX, f32::MIN);
+        for _ in 0..100 {
+            engine.dsp();
+            let lpf = engine.voices[0].params.lpf.unwrap();
+            lo = lo.min(lpf);
+            hi = hi.max(lpf);
+        }
+        // Fast sine approximation overshoots by a hair
+        assert!((495.0..600.0).contains(&lo) && (1400.0..1505.0).contains(&hi));
+
+        // Updating the target moves the centre without drift
+        engine.evaluate("/voice/0/lpf/3000");
+        let (mut lo, mut hi) = (f32::MAX, f32::MIN);
+        for _ in 0..100 {
+            engine.dsp();
+            let lpf = engine.voices[0].params.lpf.unwrap();
+            lo = lo.min(lpf);
+            hi = hi.max(lpf);
+        }
+        assert!(lo >= 2495.0 && hi <= 3505.0 && hi - lo > 800.0);
+
+        engine.evaluate("/voice/0/lfo2target/off");
+        engine.dsp();
+        assert_eq!(engine.voices[0].params.lpf, Some(3000.0));
+
+        // Free-running LFOs take their phase from the engine clock
+        engine.time = 0.25;
+        engine.evaluate("/sound/saw/lfo1target/pan/lfo1rate/1/lfo1retrig/0");
+        let v = &engine.voices[engine.active_voices - 1];
+        assert_eq!(v.lfo_phasors[0].phase, 0.25);
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn voice_chains_drive_envelope_stages_and_orbit_params() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine.evaluate("/sound/sine/voice/0/gate/0/attack/0.001>0.2:4/decay/0.1~0.3:2");
+        engine.dsp();
+        let v = &engine.voices[0];
+        // The chain sets the attack from the first frame
+        assert!(v.params.attack < 0.002 && v.dahdsr.value() > 0.9);
+        // A chained decay infers the sustain like a plain one
+        assert_eq!(v.params.sustain, 0.0);
+        for _ in 0..750 {
+            engine.dsp();
+        }
+        let p = &engine.voices[0].params;
+        assert!(p.attack > 0.02 && (0.1..=0.3).contains(&p.decay));
+
+        // Orbit params start lanes on the voice's orbit
+        engine.evaluate("/sound/sine/orbit/1/verbdecay/0.3>0.9:0.02/delaytime/0.2");
+        assert_eq!(engine.orbits[1].lanes().count(), 1);
+        assert_eq!(engine.orbits[1].delay.params.time, 0.2);
+        for _ in 0..20 {
+            engine.dsp();
+        }
+        assert_eq!(engine.orbits[1].reverb_params.decay, 0.9);
+        assert_eq!(engine.orbits[0].lanes().count(), 0);
+    }
 }
@@ -9,17 +9,50 @@
 //! - **Filters** - lowpass, highpass, bandpass
 //! - **Pitch Modulation** - vibrato, FM
 //! - **Amplitude Modulation** - AM, ring modulation
-//! - **Effects** - phaser, flanger, chorus, distortion
+//! - **Effects** - phaser, flanger, chorus, distortion, compressor
 //! - **Routing** - orbit assignment, effect sends
 
+use serde::{Deserialize, Serialize};
+
+use super::ParamId;
+use crate::dsp::envelope::DEFAULT_CURVE;
 use crate::dsp::PhaseShape;
-use crate::types::{LfoShape, Source, SubWave, SyncMode};
+use crate::types::{
+    LfoShape, PanLaw, PluckExcite, Source, SubWave, SyncMode, ORBIT_SENDS, VOICE_LFOS,
+};
+
+/// One of the voice's general-purpose LFOs (`lfo1rate`, `lfo1target`, ...).
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct VoiceLfo {
+    /// Rate in Hz.
+    pub rate: f32,
+    /// Swing around the target's value, in the target's own units.
+    pub depth: f32,
+    pub shape: LfoShape,
+    /// Parameter the LFO moves (`None` = off).
+    pub target: Option<ParamId>,
+    /// Restart at phase 0 on each note; otherwise follow the engine clock.
+    pub retrig: bool,
+}
+
+impl Default for VoiceLfo {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            depth: 0.0,
+            shape: LfoShape::Sine,
+            target: None,
+            retrig: true,
+        }
+    }
+}
 
 /// All parameters that control a voice's sound generation.
 ///
 /// This is a pure data structure with no methods beyond [`Default`].
 /// The actual signal processing happens in [`Voice`](super::Voice).
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VoiceParams {
     // ─────────────────────────────────────────────────────────────────────
     // Core
@@ -40,6 +73,13 @@ pub struct VoiceParams {
     pub postgain: f32,
     /// Stereo pan position (0.0 = left, 0.5 = center, 1.0 = right).
     pub pan: f32,
+    /// Gain law used by `pan`.
+    pub panlaw: PanLaw,
+    /// Azimuth in turns clockwise from the front for panning across every
+    /// output pair. `None` = stereo `pan`.
+    pub pan2d: Option<f32>,
+    /// How far `pan2d` spreads toward all speakers (0.0 to 1.0).
+    pub pan2dspread: f32,
     /// Gate duration in seconds (0.0 = infinite sustain).
     pub gate: f32,
 
@@ -83,6 +123,12 @@ pub struct VoiceParams {
     pub sync_phase: f32,
     /// Sync algorithm: `Hard` resets slave phase on master wrap; `Soft` flips slave direction.
     pub sync_mode: SyncMode,
+    /// Pluck loop damping (0.0 = bright, 1.0 = dark).
+    pub pluckdamp: f32,
+    /// Pluck ring time to -60 dB in seconds.
+    pub pluckdecay: f32,
+    /// What strikes the pluck string.
+    pub pluck_excite: PluckExcite,
 
     // ─────────────────────────────────────────────────────────────────────
     // Amplitude Envelope (DAHDSR)
@@ -99,6 +145,10 @@ pub struct VoiceParams {
     pub sustain: f32,
     /// Release time in seconds.
     pub release: f32,
+    /// Stage curves, -1 (logarithmic) to 1 (exponential), 0 = linear.
+    pub attackcurve: f32,
+    pub decaycurve: f32,
+    pub releasecurve: f32,
 
     // ─────────────────────────────────────────────────────────────────────
     // Filters
@@ -158,6 +208,26 @@ pub struct VoiceParams {
     /// Vibrato LFO waveform.
     pub vibshape: LfoShape,
 
+    // ─────────────────────────────────────────────────────────────────────
+    // LFO bank
+    // ─────────────────────────────────────────────────────────────────────
+    /// General-purpose LFOs, each moving one other param.
+    pub lfos: [VoiceLfo; VOICE_LFOS],
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Note expression
+    // ─────────────────────────────────────────────────────────────────────
+    /// Pitch bend in semitones.
+    pub bend: f32,
+    /// Pressure (aftertouch), 0 to 1.
+    pub pressure: f32,
+    /// Parameter pressure moves (`None` = off).
+    pub pressure_target: Option<ParamId>,
+    /// Offset at full pressure, in the target's own units.
+    pub pressure_depth: f32,
+    /// Time bend and pressure take to follow a new value, in seconds.
+    pub expr_slew: f32,
+
     // ─────────────────────────────────────────────────────────────────────
     // FM Synthesis
     // ─────────────────────────────────────────────────────────────────────
@@ -210,6 +280,8 @@ pub struct VoiceParams {
     pub phasersweep: f32,
     /// Phaser center frequency in Hz.
     pub phasercenter: f32,
+    /// Phaser equal-power dry/wet (0 = dry, 1 = effect only).
+    pub phasermix: f32,
 
     // ─────────────────────────────────────────────────────────────────────
     // Flanger
@@ -220,6 +292,8 @@ pub struct VoiceParams {
     pub flangerdepth: f32,
     /// Flanger feedback amount (0.0 to 1.0).
     pub flangerfeedback: f32,
+    /// Flanger equal-power dry/wet (0 = dry, 1 = effect only).
+    pub flangermix: f32,
 
     // ─────────────────────────────────────────────────────────────────────
     // Smear
@@ -230,6 +304,28 @@ pub struct VoiceParams {
     pub smearfreq: f32,
     /// Smear feedback for resonance (0-0.95).
     pub smearfb: f32,
+    /// Smear equal-power dry/wet (0 = dry, 1 = effect only).
+    pub smearmix: f32,
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Frequency shifter
+    // ─────────────────────────────────────────────────────────────────────
+    /// Frequency shift in Hz (0 = bypass, negative shifts down).
+    pub fshift: f32,
+    /// Frequency shifter equal-power dry/wet (0 = dry, 1 = shifted only).
+    pub fshiftmix: f32,
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Resonator bank
+    // ─────────────────────────────────────────────────────────────────────
+    /// Resonator equal-power dry/wet (0 = bypass, 1 = resonator only).
+    pub reso: f32,
+    /// Partial spread (0 = harmonic, 1 = stiff/bell-like).
+    pub resostruct: f32,
+    /// Level of the upper modes (0-1).
+    pub resobright: f32,
+    /// Decay (0 = long ring, 1 = short).
+    pub resodamp: f32,
 
     // ─────────────────────────────────────────────────────────────────────
     // Chorus
@@ -240,6 +336,8 @@ pub struct VoiceParams {
     pub chorusdepth: f32,
     /// Chorus base delay time in ms.
     pub chorusdelay: f32,
+    /// Chorus equal-power dry/wet (0 = dry, 1 = effect only).
+    pub chorusmix: f32,
 
     // ─────────────────────────────────────────────────────────────────────
     // Distortion
@@ -257,6 +355,28 @@ pub struct VoiceParams {
     /// Distortion output volume compensation.
     pub distortvol: f32,
 
+    // ─────────────────────────────────────────────────────────────────────
+    // Compressor
+    // ─────────────────────────────────────────────────────────────────────
+    /// Threshold in dB (0.0 = off). Runs after the VCA.
+    pub compthresh: f32,
+    /// Ratio above the threshold.
+    pub compratio: f32,
+    /// Makeup gain in dB.
+    pub compmakeup: f32,
+    /// Attack time in seconds.
+    pub compattack: f32,
+    /// Release time in seconds.
+    pub comprelease: f32,
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Transient shaper
+    // ─────────────────────────────────────────────────────────────────────
+    /// Attack boost (1.0) or softening (-1.0), 0.0 = off.
+    pub transattack: f32,
+    /// Tail boost (1.0) or tightening (-1.0), 0.0 = off.
+    pub transsustain: f32,
+
     // ─────────────────────────────────────────────────────────────────────
     // Stereo
     // ─────────────────────────────────────────────────────────────────────
@@ -264,6 +384,8 @@ pub struct VoiceParams {
     pub width: f32,
     /// Haas delay in ms (0.0 = off). Delays right channel for spatial placement.
     pub haas: f32,
+    /// Allpass decorrelation amount (0.0 = off, 1.0 = widest).
+    pub decor: f32,
 
     // ─────────────────────────────────────────────────────────────────────
     // EQ
@@ -290,9 +412,21 @@ pub struct VoiceParams {
     /// All orbit FX (delay/verb/comb/feedback/comp) live on the orbit itself,
     /// not on the voice — see `Orbit` in `src/orbit.rs`.
     pub orbit: usize,
+    /// Pre-listen: play on the engine's cue orbit instead of `orbit`.
+    pub cue: bool,
+    /// Secondary orbits fed with the voice output, effects only (`orbit2`..).
+    pub send_orbits: [usize; ORBIT_SENDS],
+    /// Level of each secondary send (0.0 = off).
+    pub send_levels: [f32; ORBIT_SENDS],
+    /// Key of the named group the voice was tagged with (`id/<name>`).
+    pub group: Option<u64>,
 
     /// Input channel index for LiveInput (0-indexed). None = stereo (ch 0+1).
     pub inchan: Option<usize>,
+    /// Mono-sum the stereo live input instead of keeping L/R separate.
+    pub insum: bool,
+    /// Live input level (linear). The 0.7 default leaves headroom.
+    pub inputgain: f32,
 }
 
 impl Default for VoiceParams {
@@ -306,6 +440,9 @@ impl Default for VoiceParams {
             velocity: 1.0,
             postgain: 1.0,
             pan: 0.5,
+            panlaw: PanLaw::default(),
+            pan2d: None,
+            pan2dspread: 0.0,
             gate: 1.0,
             sound: Source::Tri,
             pw: 0.5,
@@ -325,12 +462,18 @@ impl Default for VoiceParams {
             sync_ratio: 1.0,
             sync_phase: 0.0,
             sync_mode: SyncMode::default(),
+            pluckdamp: 0.5,
+            pluckdecay: 2.0,
+            pluck_excite: PluckExcite::default(),
             envdelay: 0.0,
             attack: 0.003,
             hold: 0.0,
             decay: 0.0,
             sustain: 1.0,
             release: 0.005,
+            attackcurve: DEFAULT_CURVE,
+            decaycurve: DEFAULT_CURVE,
+            releasecurve: DEFAULT_CURVE,
             lpf: None,
             lpq: 0.2,
             hpf: None,
@@ -352,6 +495,12 @@ impl Default for VoiceParams {
             vib: 0.0,
             vibmod: 0.5,
             vibshape: LfoShape::Sine,
+            lfos: [VoiceLfo::default(); VOICE_LFOS],
+            bend: 0.0,
+            pressure: 0.0,
+            pressure_target: None,
+            pressure_depth: 0.0,
+            expr_slew: 0.02,
             fm: 0.0,
             fmh: 1.0,
             fmshape: LfoShape::Sine,
@@ -369,23 +518,41 @@ impl Default for VoiceParams {
             phaserdepth: 0.75,
             phasersweep: 1200.0,
             phasercenter: 800.0,
+            phasermix: 1.0,
             flanger: 0.0,
             flangerdepth: 0.7,
             flangerfeedback: 0.35,
+            flangermix: 1.0,
             smear: 0.0,
             smearfreq: 1000.0,
             smearfb: 0.0,
+            smearmix: 1.0,
+            fshift: 0.0,
+            fshiftmix: 1.0,
+            reso: 0.0,
+            resostruct: 0.25,
+            resobright: 0.5,
+            resodamp: 0.5,
             chorus: 0.0,
             chorusdepth: 0.35,
             chorusdelay: 25.0,
+            chorusmix: 1.0,
             coarse: None,
             crush: None,
             fold: None,
             wrap: None,
             distort: None,
             distortvol: 1.0,
+            compthresh: 0.0,
+            compratio: 4.0,
+            compmakeup: 0.0,
+            compattack: 0.01,
+            comprelease: 0.15,
+            transattack: 0.0,
+            transsustain: 0.0,
             width: 1.0,
             haas: 0.0,
+            decor: 0.0,
             eqlo: 0.0,
             eqmid: 0.0,
             eqhi: 0.0,
@@ -394,7 +561,51 @@ impl Default for VoiceParams {
             eqhifreq: 5000.0,
             tilt: 0.0,
             orbit: 0,
+            cue: false,
+            send_orbits: [0; ORBIT_SENDS],
+            send_levels: [0.0; ORBIT_SENDS],
+            group: None,
             inchan: None,
+            insum: false,
+            inputgain: 0.7,
+        }
+    }
+}
+
+#[cfg(feature = "debug-dsp")]
+impl VoiceParams {
+    /// Asserts that event-written params stayed inside their clamps.
+    pub fn debug_check(&self) {
+        for (name, v) in [
+            ("freq", self.freq),
+            ("gain", self.gain),
+            ("postgain", self.postgain),
+            ("velocity", self.velocity),
+            ("pan", self.pan),
+            ("speed", self.speed),
+        ] {
+            assert!(v.is_finite(), "{name} is not finite: {v}");
+        }
+        for (name, v, lo, hi) in [
+            ("wave", self.wave, 0.0, 1.0),
+            ("sub", self.sub, 0.0, 1.0),
+            ("syncratio", self.sync_ratio, 0.0, 64.0),
+            ("syncphase", self.sync_phase, 0.0, 1.0),
+            ("warp", self.shape.warp, -1.0, 1.0),
+            ("mirror", self.shape.mirror, 0.0, 1.0),
+            ("harmonics", self.harmonics, 0.01, 0.999),
+            ("timbre", self.timbre, 0.01, 0.999),
+            ("morph", self.morph, 0.01, 0.999),
+            ("partials", self.partials, 1.0, 32.0),
+            ("scan", self.scan, 0.0, 1.0),
+            ("pluckdamp", self.pluckdamp, 0.0, 1.0),
+            ("pluckdecay", self.pluckdecay, 0.01, 60.0),
+            ("pan2dspread", self.pan2dspread, 0.0, 1.0),
+            ("transattack", self.transattack, -1.0, 1.0),
+            ("transsustain", self.transsustain, -1.0, 1.0),
+        ] {
+            assert!((lo..=hi).contains(&v), "{name} = {v} outside [{lo}, {hi}]");
         }
+        assert!(self.stretch >= 0.0, "stretch = {} < 0", self.stretch);
     }
 }
@@ -0,0 +1,89 @@
+//! Karplus-Strong plucked string.
+//!
+//! One period of excitation circulates through a delay line tuned to the
+//! voice pitch. Each trip through the loop a two-point average darkens it
+//! (`pluckdamp`) and a gain sets the ring time (`pluckdecay`).
+
+use crate::dsp::{exp2f, DelayLine};
+use crate::types::PluckExcite;
+
+use super::modulation::lcg;
+use super::Voice;
+
+/// Longest loop: about 11.7 Hz at 48 kHz.
+const PLUCK_LEN: usize = 4096;
+/// `log2(1000)`: the -60 dB ring time as a power of two.
+const LOG2_T60: f32 = 9.965_784;
+
+#[derive(Clone, Copy, Default)]
+pub struct PluckString {
+    line: DelayLine<PLUCK_LEN>,
+    excited: bool,
+}
+
+impl PluckString {
+    /// Rearms the string so the next sample plucks it again.
+    pub fn reset(&mut self) {
+        self.excited = false;
+    }
+
+    /// Fills one period of the loop with `excite`, without DC so the string
+    /// settles on silence.
+    fn excite(&mut self, period: usize, excite: PluckExcite, seed: &mut u32) {
+        self.line = DelayLine::default();
+        match excite {
+            PluckExcite::Noise => {
+                // Two passes over the same noise: one for its mean, one to write it
+                let white = |seed: &mut u32| {
+                    *seed = lcg(*seed);
+                    ((*seed >> 16) & 0x7fff) as f32 / 32767.0 * 2.0 - 1.0
+                };
+                let mut replay = *seed;
+                let mean = (0..period).map(|_| white(&mut replay)).sum::<f32>() / period as f32;
+                for _ in 0..period {
+                    self.line.write(white(seed) - mean);
+                }
+            }
+            PluckExcite::Impulse => {
+                let mean = 1.0 / period as f32;
+                self.line.write(1.0 - mean);
+                for _ in 1..period {
+                    self.line.write(-mean);
+                }
+            }
+        }
+        self.excited = true;
+    }
+
+    /// Next string sample. `damp` in `0..=1` blends in the averaging
+    /// lowpass; `decay` is the -60 dB time of the fundamental in seconds.
+    #[inline]
+    pub fn process(&mut self, freq: f32, damp: f32, decay: f32, sr: f32) -> f32 {
+        let s = 0.5 * damp.clamp(0.0, 1.0);
+        // The average delays the loop by `s` samples; the read makes up the rest
+        let period = (sr / freq.max(1.0)).clamp(2.0, (PLUCK_LEN - 2) as f32);
+        let delay = period - s;
+        let x0 = self.line.read(delay);
+        let x1 = self.line.read(delay + 1.0);
+        let g = exp2f(-LOG2_T60 / (freq.max(1.0) * decay.max(0.001)));
+        let y = g * (x0 + s * (x1 - x0));
+        self.line.write(y);
+        y
+    }
+}
+
+impl Voice {
+    #[inline]
+    pub(super) fn run_pluck(&mut self, freq: f32, isr: f32) {
+        let sr = 1.0 / isr;
+        let Some(string) = self.pluck.as_mut() else {
+            self.ch[0] = 0.0;
+            return;
+        };
+        if !string.excited {
+            let period = (sr / freq.max(1.0)).clamp(2.0, (PLUCK_LEN - 2) as f32) as usize;
+            string.excite(period, self.params.pluck_excite, &mut self.seed);
+        }
+        self.ch[0] = string.process(freq, self.params.pluckdamp, self.params.pluckdecay, sr) * 0.5;
+    }
+}
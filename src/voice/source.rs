@@ -2,13 +2,13 @@
 
 use std::f32::consts::TAU;
 
-use crate::dsp::oscillator::{blamp_post_kink, blamp_pre_kink, blep_post_step, blep_pre_step};
+use crate::dsp::oscillator::{blamp_post_kink, blamp_pre_kink, sync_reset_post, sync_reset_pre};
 use crate::dsp::{exp2f, sinf, PhaseShape, Phasor};
 #[cfg(not(feature = "native"))]
 use crate::sampling::SampleInfo;
 use crate::types::{Source, SubWave, SyncMode, CHANNELS};
 
-use super::{Voice, MAX_ADDITIVE_PARTIALS};
+use super::{SourceContext, Voice, MAX_ADDITIVE_PARTIALS};
 
 const INV_MIDDLE_C: f32 = 1.0 / 261.626;
 const SYNC_RATIO_EPS: f32 = 1e-4;
@@ -160,6 +160,55 @@ impl Voice {
         }
     }
 
+    /// Reads one frame of live input into the voice channels.
+    ///
+    /// `inchan` picks a single mono channel out of however many the device
+    /// has (silent past the last one); otherwise channels 0 and 1 are kept
+    /// as a stereo pair, a mono device feeding both. `insum` folds the pair
+    /// down to mono.
+    fn read_live_input(&mut self, live_input: &[f32], input_channels: usize, sample_idx: usize) {
+        let nch = input_channels.max(1);
+        let base = sample_idx * nch;
+        let gain = self.params.inputgain;
+        let read = |ch: usize| {
+            live_input
+                .get(base + ch.min(nch - 1))
+                .copied()
+                .unwrap_or(0.0)
+                * gain
+        };
+        if let Some(ch) = self.params.inchan {
+            self.nch = 1;
+            self.ch[0] = if ch < nch { read(ch) } else { 0.0 };
+        } else if self.params.insum {
+            self.nch = 1;
+            self.ch[0] = (read(0) + read(1)) * 0.5;
+        } else {
+            self.nch = CHANNELS;
+            self.ch[0] = read(0);
+            self.ch[1] = read(1);
+        }
+    }
+
+    /// Renders one frame from the registered [`SourceRenderer`], if any.
+    fn run_custom(&mut self, freq: f32, isr: f32) {
+        let Some(ref renderer) = self.custom else {
+            self.nch = 1;
+            self.ch[0] = 0.0;
+            return;
+        };
+        let ctx = SourceContext {
+            freq,
+            isr,
+            time: self.time,
+            params: &self.params,
+        };
+        if !renderer.render(&mut self.custom_state, &ctx, &mut self.ch) {
+            self.dahdsr.force_release();
+        }
+        self.nch = renderer.channels().clamp(1, CHANNELS);
+    }
+
     #[cfg(feature = "native")]
     pub(crate) fn run_source(
         &mut self,
@@ -190,7 +239,7 @@ impl Voice {
                             let semitones = 12.0 * ratio.log2();
                             2.0_f32.powf(semitones * rs.scale_tuning / 12.0)
                         };
-                        rs.advance(speed);
+                        rs.advance(speed * rs.tuning);
                     }
                     self.nch = CHANNELS;
                     return true;
@@ -200,8 +249,9 @@ impl Voice {
             }
             Source::Sample => {
                 let stretch = self.params.stretch;
+                let tuning = self.registry_sample.as_ref().map_or(1.0, |rs| rs.tuning);
                 if stretch != 1.0 {
-                    let pitch_ratio = (freq * INV_MIDDLE_C) as f64;
+                    let pitch_ratio = (freq * INV_MIDDLE_C * tuning) as f64;
                     match (&self.registry_sample, &self.registry_sample_b) {
                         (Some(a), Some(b)) if self.sample_blend > 0.0 => {
                             if self.stretch.needs_init() {
@@ -249,10 +299,16 @@ impl Voice {
                     self.nch = CHANNELS;
                     return true;
                 }
-                let speed = freq * INV_MIDDLE_C;
+                let speed = freq * INV_MIDDLE_C * tuning;
                 let blend = self.sample_blend;
+                // Sustain loops hold while the gate is open
+                let releasing = self.dahdsr.is_releasing();
                 match (&mut self.registry_sample, &mut self.registry_sample_b) {
                     (Some(a), Some(b)) if blend > 0.0 => {
+                        if releasing {
+                            a.release_loop();
+                            b.release_loop();
+                        }
                         let done_a = a.is_done();
                         let done_b = b.is_done();
                         if done_a && done_b {
@@ -271,6 +327,9 @@ impl Voice {
                         return true;
                     }
                     (Some(rs), _) => {
+                        if releasing {
+                            rs.release_loop();
+                        }
                         let done = rs.is_done();
                         if done {
                             self.dahdsr.force_release();
@@ -296,6 +355,9 @@ impl Voice {
             }
             Source::WebSample => {
                 if let Some(ref mut ws) = self.web_sample {
+                    if self.dahdsr.is_releasing() {
+                        ws.release_loop();
+                    }
                     let done = ws.is_done();
                     if done {
                         self.dahdsr.force_release();
@@ -313,21 +375,12 @@ impl Voice {
                 self.ch[1] = 0.0;
             }
             Source::LiveInput => {
-                let nch = input_channels.max(1);
-                if let Some(ch) = self.params.inchan {
-                    self.nch = 1;
-                    let idx = sample_idx * nch + ch.min(nch - 1);
-                    self.ch[0] = live_input.get(idx).copied().unwrap_or(0.0) * 0.7;
-                } else {
-                    self.nch = CHANNELS;
-                    let base = sample_idx * nch;
-                    self.ch[0] = live_input.get(base).copied().unwrap_or(0.0) * 0.7;
-                    self.ch[1] = live_input
-                        .get(base + 1.min(nch - 1))
-                        .copied()
-                        .unwrap_or(0.0)
-                        * 0.7;
-                }
+                self.read_live_input(live_input, input_channels, sample_idx);
+            }
+            Source::Custom => self.run_custom(freq, isr),
+            Source::Pluck => {
+                self.nch = 1;
+                self.run_pluck(freq, isr);
             }
             Source::Kick
             | Source::Snare
@@ -354,6 +407,7 @@ impl Voice {
     }
 
     #[cfg(not(feature = "native"))]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn run_source(
         &mut self,
         freq: f32,
@@ -369,6 +423,9 @@ impl Voice {
             Source::Sample => {
                 if let Some(ref mut fs) = self.file_source {
                     if let Some(info) = samples.get(fs.sample_idx) {
+                        if self.dahdsr.is_releasing() {
+                            fs.release_loop();
+                        }
                         let done = fs.is_done();
                         if done {
                             self.dahdsr.force_release();
@@ -397,6 +454,9 @@ impl Voice {
             }
             Source::WebSample => {
                 if let Some(ref mut ws) = self.web_sample {
+                    if self.dahdsr.is_releasing() {
+                        ws.release_loop();
+                    }
                     let done = ws.is_done();
                     if done {
                         self.dahdsr.force_release();
@@ -414,21 +474,12 @@ impl Voice {
                 self.ch[1] = 0.0;
             }
             Source::LiveInput => {
-                let nch = input_channels.max(1);
-                if let Some(ch) = self.params.inchan {
-                    self.nch = 1;
-                    let idx = sample_idx * nch + ch.min(nch - 1);
-                    self.ch[0] = live_input.get(idx).copied().unwrap_or(0.0) * 0.7;
-                } else {
-                    self.nch = CHANNELS;
-                    let base = sample_idx * nch;
-                    self.ch[0] = live_input.get(base).copied().unwrap_or(0.0) * 0.7;
-                    self.ch[1] = live_input
-                        .get(base + 1.min(nch - 1))
-                        .copied()
-                        .unwrap_or(0.0)
-                        * 0.7;
-                }
+                self.read_live_input(live_input, input_channels, sample_idx);
+            }
+            Source::Custom => self.run_custom(freq, isr),
+            Source::Pluck => {
+                self.nch = 1;
+                self.run_pluck(freq, isr);
             }
             Source::Kick
             | Source::Snare
@@ -528,8 +579,15 @@ impl Voice {
             0.0
         };
 
+        // A pulse is a saw minus a saw `pw` ahead; each one steps at a reset
+        let saws = [(0.0, 1.0), (self.params.pw, -1.0)];
+        let blep_saws: &[(f32, f32)] = match self.params.sound {
+            Source::Saw => &saws[..1],
+            Source::Pulse if self.params.sync_mode == SyncMode::Hard => &saws,
+            _ => &[],
+        };
         let aa_saw = matches!(self.params.sound, Source::Saw);
-        let next_wrap_frac = if aa_saw && master_dt > 0.0 {
+        let next_wrap_frac = if !blep_saws.is_empty() && master_dt > 0.0 {
             let overshoot = self.sync_phasor.phase + master_dt - 1.0;
             if overshoot >= 0.0 {
                 Some(overshoot / master_dt)
@@ -549,22 +607,24 @@ impl Voice {
                 }
                 self.generate_main_osc(freq * ratio, isr);
 
-                if master_wrapped && aa_saw {
+                if master_wrapped {
                     let phase_at_wrap = wrap_phase(phase_before + (1.0 - wrap_frac) * slave_dt);
-                    let h = 2.0 * (p - phase_at_wrap);
-                    // saw_shaped's natural-wrap polyBLEP fires on the post-reset
-                    // phase assuming a −2 step; cancel it before applying the
-                    // correct lobe for the actual step height.
-                    let d = 1.0 - wrap_frac;
-                    let natural = if p < slave_dt { 0.5 * d * d } else { 0.0 };
-                    self.ch[0] += 0.5 * h * blep_post_step(wrap_frac) - natural;
+                    for &(offset, sign) in blep_saws {
+                        let before = wrap_phase(phase_at_wrap + offset);
+                        let after = wrap_phase(p + offset);
+                        self.ch[0] +=
+                            0.5 * sign * sync_reset_post(before, after, wrap_frac, slave_dt);
+                    }
                 }
 
                 if let Some(wfn) = next_wrap_frac {
                     let phase_at_next = wrap_phase(self.phasor.phase + (1.0 - wfn) * slave_dt);
                     let p_next = wrap_phase(self.params.sync_phase + slave_dt * wfn);
-                    let h_next = 2.0 * (p_next - phase_at_next);
-                    self.ch[0] += 0.5 * h_next * blep_pre_step(wfn);
+                    for &(offset, sign) in blep_saws {
+                        let before = wrap_phase(phase_at_next + offset);
+                        let after = wrap_phase(p_next + offset);
+                        self.ch[0] += 0.5 * sign * sync_reset_pre(before, after, wfn);
+                    }
                 }
             }
             SyncMode::Soft => {
@@ -645,7 +705,7 @@ impl Voice {
             let cycle_len = if self.params.wt_cycle_len > 0 {
                 self.params.wt_cycle_len as f32
             } else {
-                frame_count
+                rs.data.wt_cycle.map_or(frame_count, |c| c as f32)
             };
 
             let num_cycles = (frame_count / cycle_len).floor().max(1.0);
@@ -799,7 +859,11 @@ fn read_interpolated(
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "native")]
+    use crate::types::PluckExcite;
     use crate::voice::modulation::ParamId;
+    #[cfg(feature = "native")]
+    use crate::Engine;
 
     #[test]
     fn additive_cache_builds_expected_partial_table() {
@@ -820,6 +884,49 @@ mod tests {
         assert!(voice.additive_cache.norm_prefix[4] > voice.additive_cache.norm_prefix[3]);
     }
 
+    #[test]
+    fn live_input_keeps_stereo_unless_summed() {
+        let mut voice = Voice::default();
+        voice.params.sound = Source::LiveInput;
+        let input = [1.0, 0.0, 0.5, -0.5];
+
+        voice.read_live_input(&input, 2, 0);
+        assert_eq!(voice.nch, CHANNELS);
+        assert_eq!(&voice.ch[..2], &[0.7, 0.0]);
+
+        voice.params.insum = true;
+        voice.read_live_input(&input, 2, 1);
+        assert_eq!(voice.nch, 1);
+        assert_eq!(voice.ch[0], 0.0);
+
+        voice.params.inchan = Some(1);
+        voice.read_live_input(&input, 2, 0);
+        assert_eq!(voice.ch[0], 0.0);
+    }
+
+    #[test]
+    fn live_input_picks_any_channel_of_a_wide_device() {
+        let mut voice = Voice::default();
+        voice.params.sound = Source::LiveInput;
+        voice.params.inputgain = 1.0;
+        let input = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+
+        voice.params.inchan = Some(3);
+        voice.read_live_input(&input, 4, 1);
+        assert_eq!(voice.ch[0], 0.8);
+
+        // Past the device's channels is silence, not the last channel
+        voice.params.inchan = Some(4);
+        voice.read_live_input(&input, 4, 1);
+        assert_eq!(voice.ch[0], 0.0);
+
+        // A mono device feeds both sides of the default pair
+        voice.params.inchan = None;
+        voice.params.inputgain = 0.5;
+        voice.read_live_input(&input, 1, 2);
+        assert_eq!(&voice.ch[..2], &[0.15, 0.15]);
+    }
+
     #[test]
     fn additive_cache_rebuilds_after_additive_param_change() {
         let mut voice = Voice::default();
@@ -933,6 +1040,53 @@ mod tests {
         );
     }
 
+    // Same for a pulse: both of its edges get a lobe at each reset, so the
+    // sync jumps stay at the first-difference of the pulse's own edges.
+    #[test]
+    fn hard_sync_pulse_step_is_bounded() {
+        let sr = 44_100.0_f32;
+        let isr = 1.0 / sr;
+        let freq = 110.0_f32;
+        let max_jump = |ratio: f32, sync_phase: f32| {
+            let mut voice = Voice::default();
+            voice.params.sound = Source::Pulse;
+            voice.params.pw = 0.3;
+            voice.params.sync_ratio = ratio;
+            voice.params.sync_phase = sync_phase;
+            let mut prev = 0.0_f32;
+            let mut max_jump = 0.0_f32;
+            for i in 0..4096 {
+                voice.run_single_osc(freq, isr);
+                if i > 0 {
+                    max_jump = max_jump.max((voice.ch[0] - prev).abs());
+                }
+                prev = voice.ch[0];
+            }
+            max_jump
+        };
+        // A free-running pulse at the slave's rate sets the floor
+        let mut plain = Voice::default();
+        plain.params.sound = Source::Pulse;
+        plain.params.pw = 0.3;
+        let mut prev = 0.0_f32;
+        let mut floor = 0.0_f32;
+        for i in 0..4096 {
+            plain.run_single_osc(freq * 3.7, isr);
+            if i > 0 {
+                floor = floor.max((plain.ch[0] - prev).abs());
+            }
+            prev = plain.ch[0];
+        }
+        // Resets from the low part into the high one and back
+        for (ratio, sync_phase) in [(3.4, 0.8), (3.9, 0.0), (2.3, 0.85)] {
+            let jump = max_jump(ratio, sync_phase);
+            assert!(
+                jump < floor * 1.1,
+                "hard-sync pulse jump {jump} at ratio {ratio}, phase {sync_phase} (floor {floor})"
+            );
+        }
+    }
+
     // PolyBLAMP smooths the direction-reversal kink. The dominant 2nd-difference
     // contribution is still the natural saw wrap (now band-limited in both
     // directions after the negative-`dt` fix in `poly_blep`). Without AA for
@@ -968,4 +1122,24 @@ mod tests {
             "soft-sync saw second-difference should be bounded, got {max_2nd}"
         );
     }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn pluck_source_is_played_and_shaped_by_its_params() {
+        let mut engine = Engine::new_with_channels(48000.0, 2, 8, 64);
+        engine
+            .evaluate("/sound/ks/pluckdamp/0.2/pluckdecay/0.5~1.5:0.1t/pluckexcite/impulse/gate/0");
+        let v = &engine.voices[0];
+        assert_eq!(v.params.sound, Source::Pluck);
+        assert_eq!(v.params.pluckdamp, 0.2);
+        assert_eq!(v.params.pluck_excite, PluckExcite::Impulse);
+
+        let mut peak = 0.0f32;
+        for _ in 0..100 {
+            engine.dsp();
+            peak = engine.output.iter().fold(peak, |p, s| p.max(s.abs()));
+        }
+        assert!(peak > 0.01);
+        assert!(engine.voices[0].params.pluckdecay > 0.5);
+    }
 }
@@ -12,8 +12,11 @@
 //! │ OUTPUT          │ Audio output buffer (WASM_BLOCK_SIZE × CHANNELS f32)   │
 //! │ INPUT_BUFFER    │ Live audio input (WASM_BLOCK_SIZE × CHANNELS f32)      │
 //! │ EVENT_INPUT     │ Command strings from JS (1024 bytes, null-term)   │
-//! │ SAMPLE_BUFFER   │ Staging area for sample uploads (16MB of f32)     │
+//! │ SAMPLE_BUFFER   │ Web PCM store for file_pcm playback (grown by JS) │
+//! │ CHUNK_BUFFER    │ Staging area for chunked uploads (256KB of f32)   │
 //! │ FRAMEBUFFER     │ Ring buffer for waveform visualization            │
+//! │ OUTPUT_BANKS    │ Double-buffered copy of OUTPUT (2 × block)        │
+//! │ COMMAND_RING    │ Lock-free command queue (null-separated strings)  │
 //! └─────────────────┴───────────────────────────────────────────────────┘
 //! ```
 //!
@@ -26,7 +29,7 @@
 //! 2. get_*_pointer()          →   Get buffer addresses
 //! 3. Write command to EVENT_INPUT
 //! 4. evaluate()               →   Parse & execute command
-//! 5. [Optional] Write samples to SAMPLE_BUFFER
+//! 5. [Optional] reserve_sample_buffer(len), write samples there
 //! 6. load_sample(len, ch, freq) → Add to pool
 //! 7. [Optional] Write mic input to INPUT_BUFFER
 //! 8. dsp()                    →   Process one block
@@ -40,9 +43,26 @@
 //! - `dsp()` is called each audio quantum (~128 samples)
 //! - Output buffer is copied to the worklet's output
 //! - Input buffer receives microphone data for live processing
+//! - Passing each `dsp()` call's duration to `report_dsp_time()` makes
+//!   `get_cpu_load()` report how close the engine runs to overruns
+//!
+//! # Shared Memory Mode
+//!
+//! When the module's memory is a `SharedArrayBuffer`, the main thread can talk
+//! to the worklet without `postMessage`:
+//!
+//! - **Commands**: write null-terminated strings into `COMMAND_RING` starting at
+//!   the write index (wrapping at `get_command_ring_len()`), then publish the
+//!   new write index with `Atomics.store`. `dsp()` drains every pending command
+//!   before rendering, so events land on the next quantum boundary.
+//! - **Output**: each `dsp()` also copies the block into the back half of
+//!   `OUTPUT_BANKS` and then flips `OUTPUT_BANK`. Readers on other threads
+//!   (scopes, recorders) read the bank it names and never see a torn block.
 
 #![allow(static_mut_refs)]
 
+use core::sync::atomic::{AtomicU32, Ordering};
+
 use crate::types::{CHANNELS, WASM_BLOCK_SIZE};
 use crate::Engine;
 
@@ -62,14 +82,59 @@ static mut EVENT_INPUT: [u8; EVENT_INPUT_SIZE] = [0; EVENT_INPUT_SIZE];
 static mut FRAMEBUFFER: [f32; FRAMEBUFFER_SIZE] = [0.0; FRAMEBUFFER_SIZE];
 static mut FRAME_IDX: i32 = 0;
 
-/// Sample upload staging buffer (16MB = 4M floats).
-/// JS decodes audio files and writes f32 samples here before calling `load_sample`.
-const SAMPLE_BUFFER_SIZE: usize = 4_194_304;
-static mut SAMPLE_BUFFER: [f32; SAMPLE_BUFFER_SIZE] = [0.0; SAMPLE_BUFFER_SIZE];
+/// Decoded PCM shared with JS, empty until `reserve_sample_buffer` grows it.
+///
+/// `dsp()` hands it to the engine as the web PCM store, so events carrying
+/// `file_pcm`/`file_frames` play straight out of it without copying into the
+/// pool. `load_sample` also stages one-shot uploads here; large samples
+/// should go through `begin_sample`/`append_chunk`/`end_sample` instead.
+static mut SAMPLE_BUFFER: Vec<f32> = Vec::new();
+
+/// Chunked upload staging buffer (256KB = 64K floats).
+/// JS fills it with the next slice of a sample, then calls `append_chunk`.
+const CHUNK_BUFFER_SIZE: usize = 65536;
+static mut CHUNK_BUFFER: [f32; CHUNK_BUFFER_SIZE] = [0.0; CHUNK_BUFFER_SIZE];
 
 /// Live audio input buffer (microphone/line-in from Web Audio).
 static mut INPUT_BUFFER: [f32; WASM_BLOCK_SIZE * CHANNELS] = [0.0; WASM_BLOCK_SIZE * CHANNELS];
 
+/// Completed output blocks, alternating between two banks.
+static mut OUTPUT_BANKS: [[f32; WASM_BLOCK_SIZE * CHANNELS]; 2] =
+    [[0.0; WASM_BLOCK_SIZE * CHANNELS]; 2];
+/// Index of the bank holding the most recent complete block.
+static OUTPUT_BANK: AtomicU32 = AtomicU32::new(0);
+/// Number of blocks published to `OUTPUT_BANKS` since init.
+static OUTPUT_SEQ: AtomicU32 = AtomicU32::new(0);
+
+/// Command ring capacity in bytes. Must be a power of two.
+const COMMAND_RING_SIZE: usize = 16384;
+static mut COMMAND_RING: [u8; COMMAND_RING_SIZE] = [0; COMMAND_RING_SIZE];
+/// Producer position (main thread), in bytes, free-running modulo 2^32.
+static COMMAND_WRITE: AtomicU32 = AtomicU32::new(0);
+/// Consumer position (audio thread), in bytes, free-running modulo 2^32.
+static COMMAND_READ: AtomicU32 = AtomicU32::new(0);
+/// Commands discarded because they exceeded `EVENT_INPUT_SIZE`.
+static COMMAND_DROPPED: AtomicU32 = AtomicU32::new(0);
+
+/// Load smoothing factor, as in the native load measurer.
+const LOAD_SMOOTHING: f32 = 0.6;
+
+/// Counters behind the telemetry getters. The native `telemetry` module
+/// needs threads and a clock; here everything runs on the worklet thread,
+/// and JS times `dsp()` itself (`report_dsp_time`).
+struct WasmMetrics {
+    /// Smoothed fraction of the block period `dsp()` takes.
+    load: f32,
+    peak_voices: u32,
+    schedule_depth: u32,
+}
+
+static mut METRICS: WasmMetrics = WasmMetrics {
+    load: 0.0,
+    peak_voices: 0,
+    schedule_depth: 0,
+};
+
 // =============================================================================
 // Lifecycle
 // =============================================================================
@@ -86,6 +151,15 @@ pub extern "C" fn doux_init(sample_rate: f32, max_voices: usize) {
             max_voices,
         ));
     }
+    let write = COMMAND_WRITE.load(Ordering::Acquire);
+    COMMAND_READ.store(write, Ordering::Release);
+    COMMAND_DROPPED.store(0, Ordering::Relaxed);
+    OUTPUT_SEQ.store(0, Ordering::Relaxed);
+    unsafe {
+        METRICS.load = 0.0;
+        METRICS.peak_voices = 0;
+        METRICS.schedule_depth = 0;
+    }
 }
 
 // =============================================================================
@@ -94,13 +168,22 @@ pub extern "C" fn doux_init(sample_rate: f32, max_voices: usize) {
 
 /// Processes one block of audio and updates the framebuffer.
 ///
-/// Call this from the AudioWorklet's `process()` method. Reads from
-/// `INPUT_BUFFER`, writes to `OUTPUT`, and appends to `FRAMEBUFFER`.
+/// Call this from the AudioWorklet's `process()` method. Drains the command
+/// ring, reads from `INPUT_BUFFER`, writes to `OUTPUT`, publishes the block to
+/// `OUTPUT_BANKS`, and appends to `FRAMEBUFFER`.
 #[no_mangle]
 pub extern "C" fn dsp() {
     unsafe {
         if let Some(ref mut engine) = ENGINE {
+            drain_command_ring(engine);
             engine.process_block(&mut OUTPUT, &SAMPLE_BUFFER, &INPUT_BUFFER);
+            METRICS.peak_voices = METRICS.peak_voices.max(engine.active_voices as u32);
+            METRICS.schedule_depth = engine.schedule.len() as u32;
+
+            let back = (OUTPUT_BANK.load(Ordering::Relaxed) ^ 1) as usize;
+            OUTPUT_BANKS[back].copy_from_slice(&OUTPUT);
+            OUTPUT_BANK.store(back as u32, Ordering::Release);
+            OUTPUT_SEQ.fetch_add(1, Ordering::Release);
 
             // Copy to ring buffer for visualization
             let fb_len = FRAMEBUFFER.len() as i32;
@@ -147,6 +230,85 @@ pub extern "C" fn evaluate() -> i32 {
     }
 }
 
+// =============================================================================
+// Command Ring
+// =============================================================================
+
+/// Evaluates every complete command between the read and write positions.
+///
+/// Commands longer than `EVENT_INPUT_SIZE` are skipped and counted. A trailing
+/// command without its terminator is left for the next call.
+unsafe fn drain_command_ring(engine: &mut Engine) {
+    let mut scratch = [0u8; EVENT_INPUT_SIZE];
+    let write = COMMAND_WRITE.load(Ordering::Acquire);
+    let mut read = COMMAND_READ.load(Ordering::Relaxed);
+    let mask = (COMMAND_RING_SIZE - 1) as u32;
+
+    while read != write {
+        let mut pos = read;
+        let mut len = 0usize;
+        let mut terminated = false;
+        while pos != write {
+            let byte = COMMAND_RING[(pos & mask) as usize];
+            pos = pos.wrapping_add(1);
+            if byte == 0 {
+                terminated = true;
+                break;
+            }
+            if len < EVENT_INPUT_SIZE {
+                scratch[len] = byte;
+            }
+            len += 1;
+        }
+        if !terminated {
+            break;
+        }
+        read = pos;
+        if len > EVENT_INPUT_SIZE {
+            COMMAND_DROPPED.fetch_add(1, Ordering::Relaxed);
+        } else if len > 0 {
+            if let Ok(cmd) = core::str::from_utf8(&scratch[..len]) {
+                engine.evaluate(cmd);
+            }
+        }
+    }
+    COMMAND_READ.store(read, Ordering::Release);
+}
+
+/// Returns mutable pointer to the command ring.
+///
+/// Write null-terminated commands at `write_index % len`, wrapping around the
+/// end, then advance the write index with `Atomics.store`. Free space is
+/// `len - (write - read)`; never let the writer overtake the reader.
+#[no_mangle]
+pub extern "C" fn get_command_ring_pointer() -> *mut u8 {
+    unsafe { COMMAND_RING.as_mut_ptr() }
+}
+
+/// Returns the capacity of the command ring in bytes.
+#[no_mangle]
+pub extern "C" fn get_command_ring_len() -> usize {
+    COMMAND_RING_SIZE
+}
+
+/// Returns pointer to the ring's write index (owned by the main thread).
+#[no_mangle]
+pub extern "C" fn get_command_write_pointer() -> *const u32 {
+    COMMAND_WRITE.as_ptr()
+}
+
+/// Returns pointer to the ring's read index (owned by the audio thread).
+#[no_mangle]
+pub extern "C" fn get_command_read_pointer() -> *const u32 {
+    COMMAND_READ.as_ptr()
+}
+
+/// Returns how many oversized commands were skipped since init.
+#[no_mangle]
+pub extern "C" fn get_command_dropped() -> u32 {
+    COMMAND_DROPPED.load(Ordering::Relaxed)
+}
+
 // =============================================================================
 // Buffer Pointers (for JS interop)
 // =============================================================================
@@ -163,6 +325,26 @@ pub extern "C" fn get_output_len() -> usize {
     WASM_BLOCK_SIZE * CHANNELS
 }
 
+/// Returns pointer to the two output banks (`2 × get_output_len()` floats).
+#[no_mangle]
+pub extern "C" fn get_output_banks_pointer() -> *const f32 {
+    unsafe { OUTPUT_BANKS.as_ptr() as *const f32 }
+}
+
+/// Returns pointer to the index (0 or 1) of the most recently completed bank.
+#[no_mangle]
+pub extern "C" fn get_output_bank_pointer() -> *const u32 {
+    OUTPUT_BANK.as_ptr()
+}
+
+/// Returns pointer to the count of blocks published since init.
+///
+/// Readers compare this before and after copying a bank to detect overruns.
+#[no_mangle]
+pub extern "C" fn get_output_seq_pointer() -> *const u32 {
+    OUTPUT_SEQ.as_ptr()
+}
+
 /// Returns mutable pointer to the event input buffer.
 ///
 /// Write null-terminated UTF-8 command strings here, then call `evaluate()`.
@@ -171,6 +353,22 @@ pub extern "C" fn get_event_input_pointer() -> *mut u8 {
     unsafe { EVENT_INPUT.as_mut_ptr() }
 }
 
+/// Grows the sample buffer to at least `len` floats, keeping what it holds,
+/// and returns its pointer.
+///
+/// Growing may move the buffer and grow the module's memory, which detaches
+/// every view JS holds on it: take the pointer again and rebuild views
+/// afterwards. Call it between `dsp()` calls, never from another thread.
+#[no_mangle]
+pub extern "C" fn reserve_sample_buffer(len: usize) -> *mut f32 {
+    unsafe {
+        if SAMPLE_BUFFER.len() < len {
+            SAMPLE_BUFFER.resize(len, 0.0);
+        }
+        SAMPLE_BUFFER.as_mut_ptr()
+    }
+}
+
 /// Returns mutable pointer to the sample upload staging buffer.
 ///
 /// Write decoded f32 samples here, then call `load_sample()`.
@@ -179,10 +377,10 @@ pub extern "C" fn get_sample_buffer_pointer() -> *mut f32 {
     unsafe { SAMPLE_BUFFER.as_mut_ptr() }
 }
 
-/// Returns the capacity of the sample buffer in floats.
+/// Returns the current size of the sample buffer in floats.
 #[no_mangle]
 pub extern "C" fn get_sample_buffer_len() -> usize {
-    SAMPLE_BUFFER_SIZE
+    unsafe { SAMPLE_BUFFER.len() }
 }
 
 /// Returns mutable pointer to the live audio input buffer.
@@ -225,12 +423,13 @@ pub extern "C" fn get_frame_pointer() -> *const i32 {
 ///
 /// # Returns
 ///
-/// Pool index on success, `-1` on failure.
+/// Pool index on success, `-1` on failure or while a chunked upload
+/// (`begin_sample` .. `end_sample`) is open.
 #[no_mangle]
 pub extern "C" fn load_sample(len: usize, channels: u8, freq: f32) -> i32 {
     unsafe {
         if let Some(ref mut engine) = ENGINE {
-            let samples = &SAMPLE_BUFFER[..len.min(SAMPLE_BUFFER_SIZE)];
+            let samples = &SAMPLE_BUFFER[..len.min(SAMPLE_BUFFER.len())];
             match engine.load_sample(samples, channels, freq) {
                 Some(idx) => idx as i32,
                 None => -1,
@@ -241,6 +440,155 @@ pub extern "C" fn load_sample(len: usize, channels: u8, freq: f32) -> i32 {
     }
 }
 
+/// Like [`load_sample`] for audio decoded at `sample_rate`, which is
+/// resampled to the engine rate (see [`set_resample_quality`]).
+#[no_mangle]
+pub extern "C" fn load_sample_at(len: usize, channels: u8, freq: f32, sample_rate: f32) -> i32 {
+    unsafe {
+        if let Some(ref mut engine) = ENGINE {
+            let samples = &SAMPLE_BUFFER[..len.min(SAMPLE_BUFFER.len())];
+            match engine.load_sample_at(samples, channels, freq, sample_rate) {
+                Some(idx) => idx as i32,
+                None => -1,
+            }
+        } else {
+            -1
+        }
+    }
+}
+
+/// Starts a chunked sample upload, independent of `SAMPLE_BUFFER`'s size.
+///
+/// The sample name is read from `EVENT_INPUT` (null-terminated, may be empty).
+/// Named samples are played with `/s/<name>/n/<i>`, where `i` picks among
+/// uploads sharing the name. Starting a new upload discards an unfinished one.
+///
+/// # Returns
+///
+/// `1` if the upload was started, `0` on invalid arguments or no engine.
+#[no_mangle]
+pub extern "C" fn begin_sample(frames: u32, channels: u8, freq: f32) -> i32 {
+    unsafe {
+        if let Some(ref mut engine) = ENGINE {
+            let len = EVENT_INPUT
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(EVENT_INPUT_SIZE);
+            let name = core::str::from_utf8(&EVENT_INPUT[..len]).unwrap_or("");
+            let started = engine.begin_sample(name, frames, channels, freq);
+            EVENT_INPUT[0] = 0;
+            started as i32
+        } else {
+            0
+        }
+    }
+}
+
+/// Like [`begin_sample`] for audio decoded at `sample_rate`; `end_sample`
+/// resamples it to the engine rate.
+#[no_mangle]
+pub extern "C" fn begin_sample_at(frames: u32, channels: u8, freq: f32, sample_rate: f32) -> i32 {
+    unsafe {
+        if let Some(ref mut engine) = ENGINE {
+            let len = EVENT_INPUT
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(EVENT_INPUT_SIZE);
+            let name = core::str::from_utf8(&EVENT_INPUT[..len]).unwrap_or("");
+            let started = engine.begin_sample_at(name, frames, channels, freq, sample_rate);
+            EVENT_INPUT[0] = 0;
+            started as i32
+        } else {
+            0
+        }
+    }
+}
+
+/// Appends `len` interleaved f32 samples at `ptr` to the pending upload.
+///
+/// `ptr` must point into WASM memory, normally `get_chunk_buffer_pointer()`.
+///
+/// # Returns
+///
+/// Number of samples accepted (`0` if no upload is pending).
+#[no_mangle]
+pub extern "C" fn append_chunk(ptr: *const f32, len: usize) -> usize {
+    if ptr.is_null() || len == 0 {
+        return 0;
+    }
+    unsafe {
+        if let Some(ref mut engine) = ENGINE {
+            let chunk = core::slice::from_raw_parts(ptr, len);
+            engine.append_sample_chunk(chunk)
+        } else {
+            0
+        }
+    }
+}
+
+/// Finishes the pending upload. Frames never written are left silent.
+///
+/// # Returns
+///
+/// Pool index on success, `-1` if no upload was pending.
+#[no_mangle]
+pub extern "C" fn end_sample() -> i32 {
+    unsafe {
+        if let Some(ref mut engine) = ENGINE {
+            engine.end_sample().map(|i| i as i32).unwrap_or(-1)
+        } else {
+            -1
+        }
+    }
+}
+
+/// Sets the interpolation for uploads at another rate than the engine's:
+/// `0` fast (linear), `1` medium, `2` high (windowed sinc).
+#[no_mangle]
+pub extern "C" fn set_resample_quality(quality: u8) {
+    unsafe {
+        if let Some(ref mut engine) = ENGINE {
+            engine.resample_quality = crate::dsp::ResampleQuality::from_index(quality);
+        }
+    }
+}
+
+/// Returns mutable pointer to the chunked upload staging buffer.
+#[no_mangle]
+pub extern "C" fn get_chunk_buffer_pointer() -> *mut f32 {
+    unsafe { CHUNK_BUFFER.as_mut_ptr() }
+}
+
+/// Returns the capacity of the chunk buffer in floats.
+#[no_mangle]
+pub extern "C" fn get_chunk_buffer_len() -> usize {
+    CHUNK_BUFFER_SIZE
+}
+
+/// Returns pointer to a sample's waveform overview, or null if out of range.
+///
+/// Layout: `[min, max]` f32 pairs, channel-major (`points` pairs per channel).
+#[no_mangle]
+pub extern "C" fn get_sample_overview_pointer(idx: usize) -> *const f32 {
+    unsafe {
+        match ENGINE.as_ref().and_then(|e| e.sample_overviews.get(idx)) {
+            Some(ov) => ov.peaks.as_ptr() as *const f32,
+            None => core::ptr::null(),
+        }
+    }
+}
+
+/// Returns the number of overview points per channel for a sample (0 if none).
+#[no_mangle]
+pub extern "C" fn get_sample_overview_points(idx: usize) -> usize {
+    unsafe {
+        ENGINE
+            .as_ref()
+            .and_then(|e| e.sample_overviews.get(idx))
+            .map_or(0, |ov| ov.points)
+    }
+}
+
 /// Returns the number of samples loaded in the pool.
 #[no_mangle]
 pub extern "C" fn get_sample_count() -> usize {
@@ -305,6 +653,80 @@ pub extern "C" fn get_active_voices() -> usize {
     }
 }
 
+/// Envelope stage of an active voice: Off=0, Delay=1, Attack=2, Hold=3,
+/// Decay=4, Sustain=5, Release=6. Returns `-1` if the index is invalid.
+#[no_mangle]
+pub extern "C" fn get_voice_env_stage(voice_idx: usize) -> i32 {
+    unsafe {
+        match ENGINE {
+            Some(ref engine) if voice_idx < engine.active_voices => {
+                engine.voices[voice_idx].dahdsr.state() as i32
+            }
+            _ => -1,
+        }
+    }
+}
+
+/// Envelope value (0-1) of an active voice, 0 if the index is invalid.
+#[no_mangle]
+pub extern "C" fn get_voice_env_value(voice_idx: usize) -> f32 {
+    unsafe {
+        match ENGINE {
+            Some(ref engine) if voice_idx < engine.active_voices => {
+                engine.voices[voice_idx].dahdsr.value()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+// =============================================================================
+// Telemetry
+// =============================================================================
+
+/// Reports how long the last `dsp()` call took, in milliseconds, as timed
+/// by the caller. Feeds `get_cpu_load()`.
+#[no_mangle]
+pub extern "C" fn report_dsp_time(ms: f64) {
+    let sr = get_sample_rate();
+    if sr <= 0.0 {
+        return;
+    }
+    let block_ms = WASM_BLOCK_SIZE as f64 / sr as f64 * 1000.0;
+    let instant = (ms / block_ms).clamp(0.0, 2.0) as f32;
+    unsafe {
+        METRICS.load = LOAD_SMOOTHING * METRICS.load + (1.0 - LOAD_SMOOTHING) * instant;
+    }
+}
+
+/// Smoothed DSP load: the fraction of each block's period `dsp()` takes
+/// (1.0 = the whole period, past which audio glitches; up to 2.0). Stays 0
+/// until JS calls `report_dsp_time`.
+#[no_mangle]
+pub extern "C" fn get_cpu_load() -> f32 {
+    unsafe { METRICS.load }
+}
+
+/// Most voices active at the end of a block since init or the last
+/// `reset_peak_voices()`.
+#[no_mangle]
+pub extern "C" fn get_peak_voices() -> u32 {
+    unsafe { METRICS.peak_voices }
+}
+
+#[no_mangle]
+pub extern "C" fn reset_peak_voices() {
+    unsafe {
+        METRICS.peak_voices = 0;
+    }
+}
+
+/// Events waiting in the scheduler after the last block.
+#[no_mangle]
+pub extern "C" fn get_schedule_depth() -> u32 {
+    unsafe { METRICS.schedule_depth }
+}
+
 /// Fades out all active voices smoothly.
 #[no_mangle]
 pub extern "C" fn hush() {
@@ -315,7 +737,7 @@ pub extern "C" fn hush() {
     }
 }
 
-/// Immediately silences all voices (may click).
+/// Fades the output out over a few milliseconds and clears all voices.
 #[no_mangle]
 pub extern "C" fn panic() {
     unsafe {
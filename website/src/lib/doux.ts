@@ -136,9 +136,21 @@ class DouxProcessor extends AudioWorkletProcessor {
         this.port.postMessage({ ready: true, sampleRate });
       } else if (writePcm) {
         const { data, offset } = writePcm;
-        const pcm_ptr = wasmExports.get_sample_buffer_pointer();
-        const pcm_len = wasmExports.get_sample_buffer_len();
-        const pcm = new Float32Array(wasmMemory.buffer, pcm_ptr, pcm_len);
+        // Growing the store can grow the memory and detach the views
+        const pcm_ptr = wasmExports.reserve_sample_buffer(offset + data.length);
+        if (output.buffer !== wasmMemory.buffer) {
+          output = new Float32Array(
+            wasmMemory.buffer,
+            wasmExports.get_output_pointer(),
+            BLOCK_SIZE * CHANNELS,
+          );
+          input_buffer = new Float32Array(
+            wasmMemory.buffer,
+            wasmExports.get_input_buffer_pointer(),
+            BLOCK_SIZE * CHANNELS,
+          );
+        }
+        const pcm = new Float32Array(wasmMemory.buffer, pcm_ptr, offset + data.length);
         pcm.set(data, offset);
         this.port.postMessage({ pcmWritten: offset });
       } else if (evaluate && event_input) {
@@ -164,7 +176,9 @@ class DouxProcessor extends AudioWorkletProcessor {
           }
         }
       }
+      const t0 = Date.now();
       wasmExports.dsp();
+      wasmExports.report_dsp_time?.(Date.now() - t0);
       const out = outputs[0];
       for (let i = 0; i < out[0].length; i++) {
         const offset = i * CHANNELS;
@@ -182,6 +196,7 @@ class DouxProcessor extends AudioWorkletProcessor {
         this.port.postMessage({
           framebuffer: framebuffer.slice(),
           frame: frameIdx,
+          load: wasmExports.get_cpu_load?.() ?? 0,
         });
       }
 
@@ -205,6 +220,8 @@ export class Doux {
 	sampleRate = 0;
 	frame: Int32Array = new Int32Array(1);
 	framebuffer: Float32Array = new Float32Array(0);
+	/** Smoothed share of each block's period the engine takes (1 = overruns). */
+	load = 0;
 	samplesReady: Promise<void> | null = null;
 
 	private initAudio: Promise<AudioContext>;
@@ -256,6 +273,7 @@ export class Doux {
 				} else if (e.data.framebuffer) {
 					this.framebuffer.set(e.data.framebuffer);
 					this.frame[0] = e.data.frame;
+					this.load = e.data.load ?? 0;
 				}
 			};
 			worklet.port.postMessage({ wasm });